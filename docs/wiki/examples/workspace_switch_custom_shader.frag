@@ -0,0 +1,155 @@
+// Your shader must contain one function (see the bottom of this file).
+//
+// It should not contain any uniform definitions or anything else, as niri
+// provides them for you.
+//
+// All symbols defined by niri will have a niri_ prefix, so don't use it for
+// your own variables and functions.
+
+// The function that you must define looks like this:
+vec4 workspace_switch_color(vec3 coords_geo, vec3 size_geo) {
+    vec4 color = /* ...compute the color... */;
+    return color;
+}
+
+// It takes as input:
+//
+// * coords_geo: coordinates of the current pixel relative to the monitor.
+//
+// These are homogeneous (the Z component is equal to 1) and scaled in such a
+// way that the 0 to 1 coordinates cover the whole monitor. The shader runs
+// exactly over the monitor area.
+//
+// * size_geo: size of the monitor in logical pixels.
+//
+// It is homogeneous (the Z component is equal to 1).
+//
+// The function must return the color of the pixel (with premultiplied alpha).
+// Transparent pixels will show the backdrop behind the workspaces.
+
+// Now let's go over the uniforms that niri defines.
+//
+// You should only rely on the uniforms documented here. Any other uniforms can
+// change or be removed without notice.
+
+// Snapshot of the upper of the two workspaces that the switch is between.
+//
+// The snapshot includes the windows, the bottom and background layer-shell
+// surfaces and the workspace background color.
+uniform sampler2D niri_tex_from;
+
+// Matrix that converts geometry coordinates into the upper workspace texture
+// coordinates.
+uniform mat3 niri_geo_to_tex_from;
+
+// Snapshot of the lower of the two workspaces that the switch is between.
+uniform sampler2D niri_tex_to;
+
+// Matrix that converts geometry coordinates into the lower workspace texture
+// coordinates.
+uniform mat3 niri_geo_to_tex_to;
+
+
+// Unclamped position between the two workspaces.
+//
+// At 0, only the upper workspace is visible, and at 1, only the lower one.
+// When switching down, it goes from 0 to 1, and when switching up, from 1 to
+// 0. It may overshoot and oscillate, for example with spring animations or
+// touchpad gestures.
+//
+// When switching across several workspaces at once, the switch goes through
+// every pair of neighboring workspaces in turn.
+uniform float niri_progress;
+
+// Position between the two workspaces clamped to [0, 1].
+uniform float niri_clamped_progress;
+
+// Now let's look at some examples. You can copy everything below this line
+// into your custom-shader to experiment.
+
+// Example: slide the workspaces vertically, like the default animation.
+vec4 slide(vec3 coords_geo, vec3 size_geo) {
+    float y = coords_geo.y + niri_progress;
+
+    if (y < 1.0) {
+        vec3 coords = vec3(coords_geo.x, y, 1.0);
+        return texture2D(niri_tex_from, (niri_geo_to_tex_from * coords).st);
+    }
+
+    if (y < 2.0) {
+        vec3 coords = vec3(coords_geo.x, y - 1.0, 1.0);
+        return texture2D(niri_tex_to, (niri_geo_to_tex_to * coords).st);
+    }
+
+    return vec4(0.0);
+}
+
+// Example: crossfade between the workspaces.
+vec4 crossfade(vec3 coords_geo, vec3 size_geo) {
+    vec4 color_from = texture2D(niri_tex_from, (niri_geo_to_tex_from * coords_geo).st);
+    vec4 color_to = texture2D(niri_tex_to, (niri_geo_to_tex_to * coords_geo).st);
+    return mix(color_from, color_to, niri_clamped_progress);
+}
+
+// Example: slide the workspaces with a vertical motion blur that is the
+// strongest in the middle of the switch.
+vec4 swipe_with_blur(vec3 coords_geo, vec3 size_geo) {
+    // Blur radius as a fraction of the monitor height.
+    float radius = 0.03 * sin(3.14159 * niri_clamped_progress);
+
+    vec4 color = vec4(0.0);
+    for (int i = -4; i <= 4; i++) {
+        vec3 offset = vec3(0.0, radius * float(i) / 4.0, 0.0);
+        color += slide(coords_geo + offset, size_geo);
+    }
+
+    return color / 9.0;
+}
+
+// Example: rotate the workspaces like the faces of a cube.
+vec4 cube(vec3 coords_geo, vec3 size_geo) {
+    float progress = niri_clamped_progress;
+    float angle = progress * 3.14159 / 2.0;
+
+    // The edge between the two faces that is the closest to the viewer.
+    float edge = 1.0 - progress;
+
+    vec4 color = vec4(0.0);
+
+    if (coords_geo.y < edge) {
+        // The upper workspace is rotating away to the top.
+        float y = coords_geo.y / edge;
+
+        // Fake the perspective by shrinking the face towards its far edge.
+        float shrink = 1.0 - 0.3 * sin(angle) * (1.0 - y);
+        float x = (coords_geo.x - 0.5) / shrink + 0.5;
+
+        if (0.0 <= x && x <= 1.0) {
+            vec3 coords = vec3(x, y, 1.0);
+            color = texture2D(niri_tex_from, (niri_geo_to_tex_from * coords).st);
+
+            // Darken the face as it turns away.
+            color.rgb *= 1.0 - 0.5 * sin(angle);
+        }
+    } else {
+        // The lower workspace is rotating in from the bottom.
+        float y = (coords_geo.y - edge) / max(progress, 0.0001);
+
+        float shrink = 1.0 - 0.3 * cos(angle) * y;
+        float x = (coords_geo.x - 0.5) / shrink + 0.5;
+
+        if (0.0 <= x && x <= 1.0) {
+            vec3 coords = vec3(x, y, 1.0);
+            color = texture2D(niri_tex_to, (niri_geo_to_tex_to * coords).st);
+            color.rgb *= 1.0 - 0.5 * cos(angle);
+        }
+    }
+
+    return color;
+}
+
+// This is the function that you must define.
+vec4 workspace_switch_color(vec3 coords_geo, vec3 size_geo) {
+    // You can pick one of the example functions or write your own.
+    return cube(coords_geo, size_geo);
+}
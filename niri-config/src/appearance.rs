@@ -536,6 +536,8 @@ pub struct TabIndicator {
     pub hide_when_single_tab: bool,
     pub hide_titles: bool,
     pub title_font_size: u32,
+    pub title_debounce_ms: u32,
+    pub title_fallback: TitleFallback,
     pub gap: f64,
     pub width: f64,
     pub length: TabIndicatorLength,
@@ -557,6 +559,8 @@ impl Default for TabIndicator {
             hide_when_single_tab: false,
             hide_titles: false,
             title_font_size: 12,
+            title_debounce_ms: 250,
+            title_fallback: TitleFallback::AppId,
             gap: 5.,
             width: 4.,
             length: TabIndicatorLength {
@@ -592,7 +596,14 @@ impl MergeWith<TabIndicatorPart> for TabIndicator {
             corner_radius,
         );
 
-        merge_clone!((self, part), title_font_size, length, position);
+        merge_clone!(
+            (self, part),
+            title_font_size,
+            title_debounce_ms,
+            title_fallback,
+            length,
+            position
+        );
 
         merge_color_gradient_opt!(
             (self, part),
@@ -613,6 +624,10 @@ pub struct TabIndicatorPart {
     pub hide_titles: Option<Flag>,
     #[knuffel(child, unwrap(argument))]
     pub title_font_size: Option<u32>,
+    #[knuffel(child, unwrap(argument))]
+    pub title_debounce_ms: Option<u32>,
+    #[knuffel(child, unwrap(argument))]
+    pub title_fallback: Option<TitleFallback>,
     #[knuffel(child)]
     pub hide_when_single_tab: Option<Flag>,
     #[knuffel(child, unwrap(argument))]
@@ -653,6 +668,41 @@ pub enum TabIndicatorPosition {
     Bottom,
 }
 
+/// What to show in place of an empty window title.
+#[derive(knuffel::DecodeScalar, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleFallback {
+    /// The app ID, or "Untitled N" if that is empty too.
+    AppId,
+    /// "Untitled N".
+    Untitled,
+    /// Nothing.
+    Empty,
+}
+
+impl TitleFallback {
+    /// Returns the title to show for a window, falling back as configured if it is empty.
+    ///
+    /// `number` goes into "Untitled N" to tell apart several untitled windows.
+    pub fn apply(self, title: Option<&str>, app_id: Option<&str>, number: u64) -> String {
+        let non_empty = |s: Option<&str>| s.filter(|s| !s.is_empty());
+
+        if let Some(title) = non_empty(title) {
+            return title.to_owned();
+        }
+
+        if self == Self::AppId {
+            if let Some(app_id) = non_empty(app_id) {
+                return app_id.to_owned();
+            }
+        }
+
+        match self {
+            Self::AppId | Self::Untitled => format!("Untitled {number}"),
+            Self::Empty => String::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct InsertHint {
     pub off: bool,
@@ -1143,6 +1193,20 @@ mod tests {
     use super::*;
     use crate::Config;
 
+    #[test]
+    fn title_fallback() {
+        let apply = |fallback: TitleFallback, title, app_id| fallback.apply(title, app_id, 3);
+
+        assert_eq!(apply(TitleFallback::AppId, Some("foo"), Some("bar")), "foo");
+        assert_eq!(apply(TitleFallback::AppId, Some(""), Some("bar")), "bar");
+        assert_eq!(apply(TitleFallback::AppId, None, Some("")), "Untitled 3");
+        assert_eq!(
+            apply(TitleFallback::Untitled, None, Some("bar")),
+            "Untitled 3"
+        );
+        assert_eq!(apply(TitleFallback::Empty, None, Some("bar")), "");
+    }
+
     #[test]
     fn parse_gradient_interpolation() {
         assert_eq!(
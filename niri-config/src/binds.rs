@@ -1,4 +1,5 @@
 use std::collections::HashSet;
+use std::mem;
 use std::str::FromStr;
 use std::time::Duration;
 
@@ -14,15 +15,28 @@ use smithay::input::keyboard::Keysym;
 use crate::recent_windows::{MruDirection, MruFilter, MruScope};
 use crate::utils::{expect_only_children, MergeWith};
 
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Binds(pub Vec<Bind>);
 
+/// Named set of binds that replaces the regular binds while the mode is active.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BindMode {
+    pub name: String,
+    pub binds: Binds,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Bind {
     pub key: Key,
     pub action: Action,
     pub repeat: bool,
+    /// Delay in milliseconds before repeating, overriding the keyboard repeat delay.
+    pub repeat_delay: Option<u16>,
+    /// Repeats per second, overriding the keyboard repeat rate.
+    pub repeat_rate: Option<u8>,
     pub release: bool,
+    /// Number of quick taps of a lone modifier key that trigger the bind, 1 for normal binds.
+    pub taps: u8,
     pub cooldown: Option<Duration>,
     pub allow_when_locked: bool,
     pub allow_inhibiting: bool,
@@ -45,6 +59,10 @@ pub enum Trigger {
     MouseMiddle,
     MouseBack,
     MouseForward,
+    /// Mouse button without a name, by its number like in X11 (`Button10` and up).
+    MouseButton(u32),
+    /// Two mouse buttons pressed together, by their numbers, the lower one first.
+    MouseChord(u32, u32),
     WheelScrollDown,
     WheelScrollUp,
     WheelScrollLeft,
@@ -53,6 +71,27 @@ pub enum Trigger {
     TouchpadScrollUp,
     TouchpadScrollLeft,
     TouchpadScrollRight,
+    /// Touchpad swipe with the given number of fingers.
+    TouchpadSwipe(u8, SwipeDirection),
+    /// Touchpad pinch with the given number of fingers.
+    TouchpadPinch(u8, PinchDirection),
+}
+
+/// Direction the fingers move in during a touchpad swipe.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum SwipeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum PinchDirection {
+    /// Fingers move closer together.
+    In,
+    /// Fingers move apart.
+    Out,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, DecodeScalar, Hash)]
@@ -106,6 +145,37 @@ pub struct SwitchAction {
     pub spawn: Vec<String>,
 }
 
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TabletPadBinds(pub Vec<TabletPadBind>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabletPadBind {
+    pub trigger: TabletPadTrigger,
+    /// Pad mode the bind applies in, or every mode if `None`.
+    pub mode: Option<u32>,
+    pub action: Action,
+}
+
+/// Tablet pad button, or a step of a ring or strip, by their number.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum TabletPadTrigger {
+    Button(u32),
+    RingClockwise(u32),
+    RingCounterClockwise(u32),
+    StripUp(u32),
+    StripDown(u32),
+}
+
+impl TabletPadBinds {
+    /// Returns the bind for the trigger in the pad mode, preferring binds for that specific mode.
+    pub fn find(&self, trigger: TabletPadTrigger, mode: u32) -> Option<&TabletPadBind> {
+        let binds = || self.0.iter().filter(move |bind| bind.trigger == trigger);
+        binds()
+            .find(|bind| bind.mode == Some(mode))
+            .or_else(|| binds().find(|bind| bind.mode.is_none()))
+    }
+}
+
 // Remember to add new actions to the CLI enum too.
 #[derive(knuffel::Decode, Debug, Clone, PartialEq)]
 pub enum Action {
@@ -115,9 +185,12 @@ pub enum Action {
     Suspend,
     PowerOffMonitors,
     PowerOnMonitors,
+    PowerOffMonitor(#[knuffel(argument)] String),
+    PowerOnMonitor(#[knuffel(argument)] String),
     ToggleDebugTint,
     DebugToggleOpaqueRegions,
     DebugToggleDamage,
+    DebugTogglePremultipliedAlpha,
     Spawn(#[knuffel(arguments)] Vec<String>),
     SpawnSh(#[knuffel(argument)] String),
     DoScreenTransition(#[knuffel(property(name = "delay-ms"))] Option<u16>),
@@ -143,6 +216,8 @@ pub enum Action {
     ScreenshotWindow(
         #[knuffel(property(name = "write-to-disk"), default = true)] bool,
         #[knuffel(property(name = "show-pointer"), default = false)] bool,
+        #[knuffel(property(name = "include-border"), default = false)] bool,
+        #[knuffel(property(name = "include-shadow"), default = false)] bool,
         // Path; not settable from knuffel
         Option<String>,
     ),
@@ -151,9 +226,12 @@ pub enum Action {
         id: u64,
         write_to_disk: bool,
         show_pointer: bool,
+        include_border: bool,
+        include_shadow: bool,
         path: Option<String>,
     },
     ToggleKeyboardShortcutsInhibit,
+    ToggleForcedPointerConstraint,
     CloseWindow,
     #[knuffel(skip)]
     CloseWindowById(u64),
@@ -335,6 +413,7 @@ pub enum Action {
     MaximizeWindowToEdgesById(u64),
     SetColumnWidth(#[knuffel(argument, str)] SizeChange),
     ExpandColumnToAvailableWidth,
+    FitColumnWidthToContent,
     SwitchLayout(#[knuffel(argument, str)] LayoutSwitchTarget),
     ShowHotkeyOverlay,
     MoveWorkspaceToMonitorLeft,
@@ -360,17 +439,41 @@ pub enum Action {
         id: Option<u64>,
         x: PositionChange,
         y: PositionChange,
+        animate: bool,
     },
     ToggleWindowRuleOpacity,
     #[knuffel(skip)]
     ToggleWindowRuleOpacityById(u64),
+    SetWindowOpacity(#[knuffel(argument)] f32),
+    #[knuffel(skip)]
+    SetWindowOpacityById {
+        id: u64,
+        opacity: f32,
+    },
+    ClearWindowOpacity,
+    #[knuffel(skip)]
+    ClearWindowOpacityById(u64),
     SetDynamicCastWindow,
     #[knuffel(skip)]
     SetDynamicCastWindowById(u64),
     SetDynamicCastMonitor(#[knuffel(argument)] Option<String>),
     ClearDynamicCastTarget,
     ToggleOverview,
+    #[knuffel(skip)]
+    ToggleOverviewWith {
+        zoom: Option<f64>,
+        workspace: Option<WorkspaceReference>,
+    },
+    ToggleColumnGrid,
+    ToggleClipboardHistory,
+    #[knuffel(skip)]
+    SelectClipboardHistoryEntry(u64),
     OpenOverview,
+    #[knuffel(skip)]
+    OpenOverviewWith {
+        zoom: Option<f64>,
+        workspace: Option<WorkspaceReference>,
+    },
     CloseOverview,
     #[knuffel(skip)]
     ToggleWindowUrgent(u64),
@@ -378,8 +481,26 @@ pub enum Action {
     SetWindowUrgent(u64),
     #[knuffel(skip)]
     UnsetWindowUrgent(u64),
+    MarkWindow(#[knuffel(argument)] String),
+    #[knuffel(skip)]
+    MarkWindowById {
+        id: u64,
+        mark: String,
+    },
+    UnmarkWindow(#[knuffel(argument)] Option<String>),
+    #[knuffel(skip)]
+    UnmarkWindowById {
+        id: u64,
+        mark: Option<String>,
+    },
+    FocusMark(#[knuffel(argument)] String),
+    SwapWithMark(#[knuffel(argument)] String),
     #[knuffel(skip)]
     LoadConfigFile,
+    UnparkWindows,
+    EnterBindMode(#[knuffel(argument)] String),
+    ExitBindMode,
+    EmulateKey(#[knuffel(argument, str)] Key),
     #[knuffel(skip)]
     MruAdvance {
         direction: MruDirection,
@@ -400,6 +521,15 @@ pub enum Action {
     MruSetScope(MruScope),
     #[knuffel(skip)]
     MruCycleScope,
+    /// Key grabbed through IPC, with the key as it was registered.
+    #[knuffel(skip)]
+    IpcKeyGrab(String),
+    /// Start of a key sequence, with the binds for the next key.
+    #[knuffel(skip)]
+    KeySequence(Vec<Bind>),
+    /// Several actions run in order, from a bind with more than one action.
+    #[knuffel(skip)]
+    Multiple(Vec<Action>),
 }
 
 // TODO: macro, bruh
@@ -409,6 +539,8 @@ impl From<niri_ipc::Action> for Action {
             niri_ipc::Action::Quit { skip_confirmation } => Self::Quit(skip_confirmation),
             niri_ipc::Action::PowerOffMonitors {} => Self::PowerOffMonitors,
             niri_ipc::Action::PowerOnMonitors {} => Self::PowerOnMonitors,
+            niri_ipc::Action::PowerOffMonitor { output } => Self::PowerOffMonitor(output),
+            niri_ipc::Action::PowerOnMonitor { output } => Self::PowerOnMonitor(output),
             niri_ipc::Action::Spawn { command } => Self::Spawn(command),
             niri_ipc::Action::SpawnSh { command } => Self::SpawnSh(command),
             niri_ipc::Action::DoScreenTransition { delay_ms } => Self::DoScreenTransition(delay_ms),
@@ -424,22 +556,37 @@ impl From<niri_ipc::Action> for Action {
                 id: None,
                 write_to_disk,
                 show_pointer,
+                include_border,
+                include_shadow,
                 path,
-            } => Self::ScreenshotWindow(write_to_disk, show_pointer, path),
+            } => Self::ScreenshotWindow(
+                write_to_disk,
+                show_pointer,
+                include_border,
+                include_shadow,
+                path,
+            ),
             niri_ipc::Action::ScreenshotWindow {
                 id: Some(id),
                 write_to_disk,
                 show_pointer,
+                include_border,
+                include_shadow,
                 path,
             } => Self::ScreenshotWindowById {
                 id,
                 write_to_disk,
                 show_pointer,
+                include_border,
+                include_shadow,
                 path,
             },
             niri_ipc::Action::ToggleKeyboardShortcutsInhibit {} => {
                 Self::ToggleKeyboardShortcutsInhibit
             }
+            niri_ipc::Action::ToggleForcedPointerConstraint {} => {
+                Self::ToggleForcedPointerConstraint
+            }
             niri_ipc::Action::CloseWindow { id: None } => Self::CloseWindow,
             niri_ipc::Action::CloseWindow { id: Some(id) } => Self::CloseWindowById(id),
             niri_ipc::Action::FullscreenWindow { id: None } => Self::FullscreenWindow,
@@ -635,6 +782,7 @@ impl From<niri_ipc::Action> for Action {
             }
             niri_ipc::Action::SetColumnWidth { change } => Self::SetColumnWidth(change),
             niri_ipc::Action::ExpandColumnToAvailableWidth {} => Self::ExpandColumnToAvailableWidth,
+            niri_ipc::Action::FitColumnWidthToContent {} => Self::FitColumnWidthToContent,
             niri_ipc::Action::SwitchLayout { layout } => Self::SwitchLayout(layout),
             niri_ipc::Action::ShowHotkeyOverlay {} => Self::ShowHotkeyOverlay,
             niri_ipc::Action::MoveWorkspaceToMonitorLeft {} => Self::MoveWorkspaceToMonitorLeft,
@@ -670,6 +818,9 @@ impl From<niri_ipc::Action> for Action {
             niri_ipc::Action::ToggleDebugTint {} => Self::ToggleDebugTint,
             niri_ipc::Action::DebugToggleOpaqueRegions {} => Self::DebugToggleOpaqueRegions,
             niri_ipc::Action::DebugToggleDamage {} => Self::DebugToggleDamage,
+            niri_ipc::Action::DebugTogglePremultipliedAlpha {} => {
+                Self::DebugTogglePremultipliedAlpha
+            }
             niri_ipc::Action::ToggleWindowFloating { id: None } => Self::ToggleWindowFloating,
             niri_ipc::Action::ToggleWindowFloating { id: Some(id) } => {
                 Self::ToggleWindowFloatingById(id)
@@ -687,13 +838,27 @@ impl From<niri_ipc::Action> for Action {
             niri_ipc::Action::SwitchFocusBetweenFloatingAndTiling {} => {
                 Self::SwitchFocusBetweenFloatingAndTiling
             }
-            niri_ipc::Action::MoveFloatingWindow { id, x, y } => {
-                Self::MoveFloatingWindowById { id, x, y }
+            niri_ipc::Action::MoveFloatingWindow { id, x, y, animate } => {
+                Self::MoveFloatingWindowById { id, x, y, animate }
             }
             niri_ipc::Action::ToggleWindowRuleOpacity { id: None } => Self::ToggleWindowRuleOpacity,
             niri_ipc::Action::ToggleWindowRuleOpacity { id: Some(id) } => {
                 Self::ToggleWindowRuleOpacityById(id)
             }
+            niri_ipc::Action::SetWindowOpacity { id: None, opacity } => {
+                Self::SetWindowOpacity(opacity as f32)
+            }
+            niri_ipc::Action::SetWindowOpacity {
+                id: Some(id),
+                opacity,
+            } => Self::SetWindowOpacityById {
+                id,
+                opacity: opacity as f32,
+            },
+            niri_ipc::Action::ClearWindowOpacity { id: None } => Self::ClearWindowOpacity,
+            niri_ipc::Action::ClearWindowOpacity { id: Some(id) } => {
+                Self::ClearWindowOpacityById(id)
+            }
             niri_ipc::Action::SetDynamicCastWindow { id: None } => Self::SetDynamicCastWindow,
             niri_ipc::Action::SetDynamicCastWindow { id: Some(id) } => {
                 Self::SetDynamicCastWindowById(id)
@@ -702,13 +867,45 @@ impl From<niri_ipc::Action> for Action {
                 Self::SetDynamicCastMonitor(output)
             }
             niri_ipc::Action::ClearDynamicCastTarget {} => Self::ClearDynamicCastTarget,
-            niri_ipc::Action::ToggleOverview {} => Self::ToggleOverview,
-            niri_ipc::Action::OpenOverview {} => Self::OpenOverview,
+            niri_ipc::Action::ToggleOverview {
+                zoom: None,
+                workspace: None,
+            } => Self::ToggleOverview,
+            niri_ipc::Action::ToggleOverview { zoom, workspace } => Self::ToggleOverviewWith {
+                zoom,
+                workspace: workspace.map(WorkspaceReference::from),
+            },
+            niri_ipc::Action::ToggleColumnGrid {} => Self::ToggleColumnGrid,
+            niri_ipc::Action::ToggleClipboardHistory {} => Self::ToggleClipboardHistory,
+            niri_ipc::Action::SelectClipboardHistoryEntry { id } => {
+                Self::SelectClipboardHistoryEntry(id)
+            }
+            niri_ipc::Action::OpenOverview {
+                zoom: None,
+                workspace: None,
+            } => Self::OpenOverview,
+            niri_ipc::Action::OpenOverview { zoom, workspace } => Self::OpenOverviewWith {
+                zoom,
+                workspace: workspace.map(WorkspaceReference::from),
+            },
             niri_ipc::Action::CloseOverview {} => Self::CloseOverview,
             niri_ipc::Action::ToggleWindowUrgent { id } => Self::ToggleWindowUrgent(id),
             niri_ipc::Action::SetWindowUrgent { id } => Self::SetWindowUrgent(id),
             niri_ipc::Action::UnsetWindowUrgent { id } => Self::UnsetWindowUrgent(id),
+            niri_ipc::Action::MarkWindow { id: None, mark } => Self::MarkWindow(mark),
+            niri_ipc::Action::MarkWindow { id: Some(id), mark } => {
+                Self::MarkWindowById { id, mark }
+            }
+            niri_ipc::Action::UnmarkWindow { id: None, mark } => Self::UnmarkWindow(mark),
+            niri_ipc::Action::UnmarkWindow { id: Some(id), mark } => {
+                Self::UnmarkWindowById { id, mark }
+            }
+            niri_ipc::Action::FocusMark { mark } => Self::FocusMark(mark),
+            niri_ipc::Action::SwapWithMark { mark } => Self::SwapWithMark(mark),
             niri_ipc::Action::LoadConfigFile {} => Self::LoadConfigFile,
+            niri_ipc::Action::UnparkWindows {} => Self::UnparkWindows,
+            niri_ipc::Action::EnterBindMode { name } => Self::EnterBindMode(name),
+            niri_ipc::Action::ExitBindMode {} => Self::ExitBindMode,
         }
     }
 }
@@ -777,60 +974,101 @@ where
         ctx: &mut knuffel::decode::Context<S>,
     ) -> Result<Self, DecodeError<S>> {
         expect_only_children(node, ctx);
+        Ok(Self(decode_binds(node, ctx)))
+    }
+}
+
+impl<S> knuffel::Decode<S> for BindMode
+where
+    S: knuffel::traits::ErrorSpan,
+{
+    fn decode_node(
+        node: &knuffel::ast::SpannedNode<S>,
+        ctx: &mut knuffel::decode::Context<S>,
+    ) -> Result<Self, DecodeError<S>> {
+        if let Some(type_name) = &node.type_name {
+            ctx.emit_error(DecodeError::unexpected(
+                type_name,
+                "type name",
+                "no type name expected for this node",
+            ));
+        }
 
-        let mut seen_keys = HashSet::new();
-        let mut seen_keys_release = HashSet::new();
+        let mut iter_args = node.arguments.iter();
+        let name = iter_args
+            .next()
+            .ok_or_else(|| DecodeError::missing(node, "additional argument `name` is required"))?;
+        let name: String = knuffel::traits::DecodeScalar::decode(name, ctx)?;
+
+        for val in iter_args {
+            ctx.emit_error(DecodeError::unexpected(
+                &val.literal,
+                "argument",
+                "unexpected argument",
+            ));
+        }
+
+        for name in node.properties.keys() {
+            ctx.emit_error(DecodeError::unexpected(
+                name,
+                "property",
+                "no properties expected for this node",
+            ));
+        }
+
+        let binds = decode_binds(node, ctx);
+
+        // Without a way out, the mode would take over the keyboard until the config changes.
+        if !binds.iter().any(|bind| exits_bind_mode(&bind.action)) {
+            ctx.emit_error(DecodeError::unexpected(
+                node,
+                "node",
+                "bind mode must have a bind with the exit-bind-mode action",
+            ));
+        }
 
+        Ok(Self {
+            name,
+            binds: Binds(binds),
+        })
+    }
+}
+
+fn exits_bind_mode(action: &Action) -> bool {
+    match action {
+        Action::ExitBindMode => true,
+        Action::Multiple(actions) => actions.iter().any(exits_bind_mode),
+        Action::KeySequence(binds) => binds.iter().any(|bind| exits_bind_mode(&bind.action)),
+        _ => false,
+    }
+}
+
+impl<S> knuffel::Decode<S> for TabletPadBinds
+where
+    S: knuffel::traits::ErrorSpan,
+{
+    fn decode_node(
+        node: &knuffel::ast::SpannedNode<S>,
+        ctx: &mut knuffel::decode::Context<S>,
+    ) -> Result<Self, DecodeError<S>> {
+        expect_only_children(node, ctx);
+
+        let mut seen = HashSet::new();
         let mut binds = Vec::new();
 
         for child in node.children() {
-            match Bind::decode_node(child, ctx) {
+            match TabletPadBind::decode_node(child, ctx) {
                 Err(e) => {
                     ctx.emit_error(e);
                 }
                 Ok(bind) => {
-                    // ideally, these errors should point to the previous instance of the keybind
-                    //
-                    // i (sodiboo) have tried to implement this in various ways:
-                    // miette!(), #[derive(Diagnostic)]
-                    // DecodeError::Custom, DecodeError::Conversion
-                    // nothing seems to work, and i suspect it's not possible.
-                    //
-                    // DecodeError is fairly restrictive.
-                    // even DecodeError::Custom just wraps a std::error::Error
-                    // and this erases all rich information from miette. (why???)
-                    //
-                    // why does knuffel do this?
-                    // from what i can tell, it doesn't even use DecodeError for much.
-                    // it only ever converts them to a Report anyways!
-                    // https://github.com/tailhook/knuffel/blob/c44c6b0c0f31ea6d1174d5d2ed41064922ea44ca/src/wrappers.rs#L55-L58
-                    //
-                    // besides like, allowing downstream users (such as us!)
-                    // to match on parse failure, i don't understand why
-                    // it doesn't just use a generic error type
-                    //
-                    // even the matching isn't consistent,
-                    // because errors can also be omitted as ctx.emit_error.
-                    // why does *that one* especially, require a DecodeError?
-                    //
-                    // anyways if you can make it format nicely, definitely do fix this
-                    if bind.release {
-                        if seen_keys_release.insert(bind.key) {
-                            binds.push(bind);
-                        } else {
-                            ctx.emit_error(DecodeError::unexpected(
-                                &child.node_name,
-                                "keybind",
-                                "duplicate release keybind",
-                            ));
-                        }
-                    } else if seen_keys.insert(bind.key) {
+                    if seen.insert((bind.trigger, bind.mode)) {
                         binds.push(bind);
                     } else {
                         ctx.emit_error(DecodeError::unexpected(
                             &child.node_name,
-                            "keybind",
-                            "duplicate keybind",
+                            "pad bind",
+                            "duplicate pad bind",
                         ));
                     }
                 }
@@ -841,6 +1079,299 @@ where
     }
 }
 
+impl<S> knuffel::Decode<S> for TabletPadBind
+where
+    S: knuffel::traits::ErrorSpan,
+{
+    fn decode_node(
+        node: &knuffel::ast::SpannedNode<S>,
+        ctx: &mut knuffel::decode::Context<S>,
+    ) -> Result<Self, DecodeError<S>> {
+        if let Some(type_name) = &node.type_name {
+            ctx.emit_error(DecodeError::unexpected(
+                type_name,
+                "type name",
+                "no type name expected for this node",
+            ));
+        }
+
+        let mut iter_args = node.arguments.iter();
+        let number: Option<u32> = iter_args
+            .next()
+            .map(|val| knuffel::traits::DecodeScalar::decode(val, ctx))
+            .transpose()?;
+
+        for val in iter_args {
+            ctx.emit_error(DecodeError::unexpected(
+                &val.literal,
+                "argument",
+                "unexpected argument",
+            ));
+        }
+
+        let trigger = match &**node.node_name {
+            "button" => {
+                let number = number.ok_or_else(|| {
+                    DecodeError::missing(node, "additional argument `number` is required")
+                })?;
+                TabletPadTrigger::Button(number)
+            }
+            // Most pads have a single ring or strip, so let the number default to the first one.
+            "ring-cw" => TabletPadTrigger::RingClockwise(number.unwrap_or(0)),
+            "ring-ccw" => TabletPadTrigger::RingCounterClockwise(number.unwrap_or(0)),
+            "strip-up" => TabletPadTrigger::StripUp(number.unwrap_or(0)),
+            "strip-down" => TabletPadTrigger::StripDown(number.unwrap_or(0)),
+            name_str => {
+                return Err(DecodeError::unexpected(
+                    &node.node_name,
+                    "node",
+                    format!(
+                        "unexpected node `{}`, expected button, ring-cw, ring-ccw, strip-up or \
+                         strip-down",
+                        name_str.escape_default()
+                    ),
+                ));
+            }
+        };
+
+        let mut mode = None;
+        for (name, val) in &node.properties {
+            match &***name {
+                "mode" => {
+                    mode = Some(knuffel::traits::DecodeScalar::decode(val, ctx)?);
+                }
+                name_str => {
+                    ctx.emit_error(DecodeError::unexpected(
+                        name,
+                        "property",
+                        format!("unexpected property `{}`", name_str.escape_default()),
+                    ));
+                }
+            }
+        }
+
+        let mut children = node.children();
+        let child = children
+            .next()
+            .ok_or_else(|| DecodeError::missing(node, "expected an action for this pad bind"))?;
+        for unwanted_child in children {
+            ctx.emit_error(DecodeError::unexpected(
+                unwanted_child,
+                "node",
+                "only one action is allowed per pad bind",
+            ));
+        }
+
+        Ok(Self {
+            trigger,
+            mode,
+            action: Action::decode_node(child, ctx)?,
+        })
+    }
+}
+
+fn decode_binds<S>(
+    node: &knuffel::ast::SpannedNode<S>,
+    ctx: &mut knuffel::decode::Context<S>,
+) -> Vec<Bind>
+where
+    S: knuffel::traits::ErrorSpan,
+{
+    let mut seen_keys = HashSet::new();
+    let mut seen_keys_release = HashSet::new();
+    let mut seen_keys_taps = HashSet::new();
+
+    let mut binds = Vec::new();
+
+    for child in node.children() {
+        match Bind::decode_node(child, ctx) {
+            Err(e) => {
+                ctx.emit_error(e);
+            }
+            Ok(bind) if is_key_sequence(&child.node_name) => {
+                let (prefix, _) = split_key_sequence(&child.node_name);
+                let prefix = match prefix
+                    .split_whitespace()
+                    .map(str::parse)
+                    .collect::<Result<Vec<Key>, _>>()
+                {
+                    Ok(prefix) => prefix,
+                    Err(e) => {
+                        ctx.emit_error(DecodeError::conversion(
+                            &child.node_name,
+                            e.wrap_err("invalid key sequence"),
+                        ));
+                        continue;
+                    }
+                };
+
+                // Keys after the first one are matched only against keyboard key presses.
+                let keys_only = prefix[1..]
+                    .iter()
+                    .chain([&bind.key])
+                    .all(|key| matches!(key.trigger, Trigger::Keysym(_)));
+                if bind.release || bind.taps > 1 || !keys_only {
+                    ctx.emit_error(DecodeError::unexpected(
+                        &child.node_name,
+                        "keybind",
+                        "only regular keys can follow the first key of a sequence",
+                    ));
+                    continue;
+                }
+
+                match insert_sequence_bind(&mut binds, &prefix, bind) {
+                    Ok(()) => {
+                        seen_keys.insert(prefix[0]);
+                    }
+                    Err(msg) => {
+                        ctx.emit_error(DecodeError::unexpected(&child.node_name, "keybind", msg));
+                    }
+                }
+            }
+            Ok(bind) => {
+                // ideally, these errors should point to the previous instance of the keybind
+                //
+                // i (sodiboo) have tried to implement this in various ways:
+                // miette!(), #[derive(Diagnostic)]
+                // DecodeError::Custom, DecodeError::Conversion
+                // nothing seems to work, and i suspect it's not possible.
+                //
+                // DecodeError is fairly restrictive.
+                // even DecodeError::Custom just wraps a std::error::Error
+                // and this erases all rich information from miette. (why???)
+                //
+                // why does knuffel do this?
+                // from what i can tell, it doesn't even use DecodeError for much.
+                // it only ever converts them to a Report anyways!
+                // https://github.com/tailhook/knuffel/blob/c44c6b0c0f31ea6d1174d5d2ed41064922ea44ca/src/wrappers.rs#L55-L58
+                //
+                // besides like, allowing downstream users (such as us!)
+                // to match on parse failure, i don't understand why
+                // it doesn't just use a generic error type
+                //
+                // even the matching isn't consistent,
+                // because errors can also be omitted as ctx.emit_error.
+                // why does *that one* especially, require a DecodeError?
+                //
+                // anyways if you can make it format nicely, definitely do fix this
+                if bind.taps > 1 {
+                    if seen_keys_taps.insert((bind.key, bind.taps)) {
+                        binds.push(bind);
+                    } else {
+                        ctx.emit_error(DecodeError::unexpected(
+                            &child.node_name,
+                            "keybind",
+                            "duplicate tap keybind",
+                        ));
+                    }
+                } else if bind.release {
+                    if seen_keys_release.insert(bind.key) {
+                        binds.push(bind);
+                    } else {
+                        ctx.emit_error(DecodeError::unexpected(
+                            &child.node_name,
+                            "keybind",
+                            "duplicate release keybind",
+                        ));
+                    }
+                } else if seen_keys.insert(bind.key) {
+                    binds.push(bind);
+                } else {
+                    ctx.emit_error(DecodeError::unexpected(
+                        &child.node_name,
+                        "keybind",
+                        "duplicate keybind",
+                    ));
+                }
+            }
+        }
+    }
+
+    binds
+}
+
+fn is_key_sequence(node_name: &str) -> bool {
+    node_name.split_whitespace().nth(1).is_some()
+}
+
+/// Splits a bind name like `Mod+A C` into the keys before the last one, and the last key.
+fn split_key_sequence(node_name: &str) -> (&str, &str) {
+    let node_name = node_name.trim();
+    match node_name.rsplit_once(char::is_whitespace) {
+        Some((prefix, key)) => (prefix, key),
+        None => ("", node_name),
+    }
+}
+
+/// Adds a bind that triggers after pressing the `prefix` keys one after another.
+fn insert_sequence_bind(
+    binds: &mut Vec<Bind>,
+    prefix: &[Key],
+    bind: Bind,
+) -> Result<(), &'static str> {
+    let Some((first, rest)) = prefix.split_first() else {
+        if binds.iter().any(|existing| existing.key == bind.key) {
+            return Err("duplicate keybind");
+        }
+        binds.push(bind);
+        return Ok(());
+    };
+
+    let existing = binds
+        .iter()
+        .position(|existing| existing.key == *first && !existing.release && existing.taps == 1);
+    let idx = existing.unwrap_or_else(|| {
+        binds.push(Bind {
+            key: *first,
+            action: Action::KeySequence(Vec::new()),
+            repeat: false,
+            repeat_delay: None,
+            repeat_rate: None,
+            release: false,
+            taps: 1,
+            cooldown: None,
+            allow_when_locked: false,
+            allow_inhibiting: true,
+            allow_invalidation: true,
+            hotkey_overlay_title: None,
+        });
+        binds.len() - 1
+    });
+
+    let Action::KeySequence(next) = &mut binds[idx].action else {
+        return Err("the start of this key sequence is already bound to an action");
+    };
+    insert_sequence_bind(next, rest, bind)
+}
+
+/// Merges binds from a later config section into `binds`.
+///
+/// New binds replace existing binds with the same key, except for key sequences starting with the
+/// same key as an existing sequence: those are merged key by key, so that overriding one sequence
+/// keeps the other sequences sharing its first keys.
+pub(crate) fn merge_binds(binds: &mut Vec<Bind>, new: Vec<Bind>) {
+    let mut replacing = Vec::new();
+    for mut bind in new {
+        if let Action::KeySequence(next) = &mut bind.action {
+            if let Some(existing) = sequence_binds_mut(binds, &bind.key) {
+                merge_binds(existing, mem::take(next));
+                continue;
+            }
+        }
+        replacing.push(bind);
+    }
+
+    binds.retain(|bind| !replacing.iter().any(|new| new.key == bind.key));
+    binds.extend(replacing);
+}
+
+fn sequence_binds_mut<'a>(binds: &'a mut [Bind], key: &Key) -> Option<&'a mut Vec<Bind>> {
+    binds.iter_mut().find_map(|bind| match &mut bind.action {
+        Action::KeySequence(next) if bind.key == *key => Some(next),
+        _ => None,
+    })
+}
+
 impl<S> knuffel::Decode<S> for Bind
 where
     S: knuffel::traits::ErrorSpan,
@@ -865,13 +1396,18 @@ where
             ));
         }
 
-        let key = node
-            .node_name
+        // The keys leading up to the last one in a key sequence are handled in Binds.
+        let (_, key) = split_key_sequence(&node.node_name);
+        let key = key
             .parse::<Key>()
             .map_err(|e| DecodeError::conversion(&node.node_name, e.wrap_err("invalid keybind")))?;
 
         let mut repeat = true;
+        let mut repeat_delay = None;
+        let mut repeat_rate = None;
         let mut release = false;
+        let mut taps = 1;
+        let mut taps_node = None;
         let mut cooldown = None;
         let mut allow_when_locked = false;
         let mut allow_when_locked_node = None;
@@ -883,9 +1419,19 @@ where
                 "repeat" => {
                     repeat = knuffel::traits::DecodeScalar::decode(val, ctx)?;
                 }
+                "repeat-delay" => {
+                    repeat_delay = Some(knuffel::traits::DecodeScalar::decode(val, ctx)?);
+                }
+                "repeat-rate" => {
+                    repeat_rate = Some(knuffel::traits::DecodeScalar::decode(val, ctx)?);
+                }
                 "release" => {
                     release = knuffel::traits::DecodeScalar::decode(val, ctx)?;
                 }
+                "taps" => {
+                    taps = knuffel::traits::DecodeScalar::decode(val, ctx)?;
+                    taps_node = Some(&val.literal);
+                }
                 "cooldown-ms" => {
                     cooldown = Some(Duration::from_millis(
                         knuffel::traits::DecodeScalar::decode(val, ctx)?,
@@ -914,7 +1460,26 @@ where
             }
         }
 
-        let mut children = node.children();
+        if let Some(val) = taps_node {
+            if taps < 2 {
+                ctx.emit_error(DecodeError::conversion(
+                    val,
+                    "taps must be at least 2, use release=true to bind a single tap",
+                ));
+                taps = 1;
+            } else if !key.is_lone_modifier() {
+                ctx.emit_error(DecodeError::conversion(
+                    val,
+                    "taps can only be set on binds to a lone modifier key",
+                ));
+                taps = 1;
+            } else if release {
+                ctx.emit_error(DecodeError::conversion(
+                    val,
+                    "taps binds always trigger on release, remove release=true",
+                ));
+            }
+        }
 
         // If the action is invalid but the key is fine, we still want to return something.
         // That way, the parent can handle the existence of duplicate keybinds,
@@ -923,7 +1488,10 @@ where
             key,
             action: Action::Spawn(vec![]),
             repeat: true,
+            repeat_delay: None,
+            repeat_rate: None,
             release: false,
+            taps: 1,
             cooldown: None,
             allow_when_locked: false,
             allow_inhibiting: true,
@@ -931,55 +1499,193 @@ where
             hotkey_overlay_title: None,
         };
 
-        if let Some(child) = children.next() {
-            for unwanted_child in children {
-                ctx.emit_error(DecodeError::unexpected(
-                    unwanted_child,
-                    "node",
-                    "only one action is allowed per keybind",
-                ));
-            }
+        // Several actions run one after another.
+        let mut actions = Vec::new();
+        let mut is_valid = true;
+        for child in node.children() {
             match Action::decode_node(child, ctx) {
-                Ok(action) => {
-                    if !matches!(action, Action::Spawn(_) | Action::SpawnSh(_)) {
-                        if let Some(node) = allow_when_locked_node {
-                            ctx.emit_error(DecodeError::unexpected(
-                                node,
-                                "property",
-                                "allow-when-locked can only be set on spawn binds",
-                            ));
-                        }
-                    }
-
-                    // The toggle-inhibit action must always be uninhibitable.
-                    // Otherwise, it would be impossible to trigger it.
-                    if matches!(action, Action::ToggleKeyboardShortcutsInhibit) {
-                        allow_inhibiting = false;
-                    }
-
-                    Ok(Self {
-                        key,
-                        action,
-                        repeat,
-                        release,
-                        cooldown,
-                        allow_when_locked,
-                        allow_inhibiting,
-                        allow_invalidation,
-                        hotkey_overlay_title,
-                    })
-                }
+                Ok(action) => actions.push(action),
                 Err(e) => {
                     ctx.emit_error(e);
-                    Ok(dummy)
+                    is_valid = false;
                 }
             }
-        } else {
+        }
+
+        if !is_valid {
+            return Ok(dummy);
+        }
+
+        if actions.is_empty() {
             ctx.emit_error(DecodeError::missing(
                 node,
                 "expected an action for this keybind",
             ));
-            Ok(dummy)
+            return Ok(dummy);
+        }
+
+        if !actions
+            .iter()
+            .all(|action| matches!(action, Action::Spawn(_) | Action::SpawnSh(_)))
+        {
+            if let Some(node) = allow_when_locked_node {
+                ctx.emit_error(DecodeError::unexpected(
+                    node,
+                    "property",
+                    "allow-when-locked can only be set on spawn binds",
+                ));
+            }
+        }
+
+        for action in &actions {
+            // The toggle-inhibit action must always be uninhibitable.
+            // Otherwise, it would be impossible to trigger it.
+            if matches!(action, Action::ToggleKeyboardShortcutsInhibit) {
+                allow_inhibiting = false;
+            }
+
+            // Same for releasing the forced pointer constraint, a game inhibiting
+            // shortcuts must not trap the pointer.
+            if matches!(action, Action::ToggleForcedPointerConstraint) {
+                allow_inhibiting = false;
+            }
+        }
+
+        let action = if actions.len() == 1 {
+            actions.pop().unwrap()
+        } else {
+            Action::Multiple(actions)
+        };
+
+        Ok(Self {
+            key,
+            action,
+            repeat,
+            repeat_delay,
+            repeat_rate,
+            release,
+            taps,
+            cooldown,
+            allow_when_locked,
+            allow_inhibiting,
+            allow_invalidation,
+            hotkey_overlay_title,
+        })
+    }
+}
+
+impl Key {
+    /// Whether the key is a modifier key on its own, like `Mod` or `Shift_L`.
+    pub fn is_lone_modifier(&self) -> bool {
+        match self.trigger {
+            Trigger::KeyCompositor => true,
+            Trigger::Keysym(keysym) => keysym.is_modifier_key(),
+            _ => false,
+        }
+    }
+}
+
+impl Trigger {
+    /// Returns the trigger for a mouse button by its number like in X11.
+    ///
+    /// Buttons 4 to 7 are the scroll wheel, so they don't have a trigger.
+    pub fn mouse_button(number: u32) -> Option<Self> {
+        match number {
+            1 => Some(Self::MouseLeft),
+            2 => Some(Self::MouseMiddle),
+            3 => Some(Self::MouseRight),
+            8 => Some(Self::MouseBack),
+            9 => Some(Self::MouseForward),
+            10..=255 => Some(Self::MouseButton(number)),
+            _ => None,
+        }
+    }
+
+    /// Returns the number like in X11 of a single mouse button trigger.
+    pub fn mouse_button_number(&self) -> Option<u32> {
+        match self {
+            Self::MouseLeft => Some(1),
+            Self::MouseMiddle => Some(2),
+            Self::MouseRight => Some(3),
+            Self::MouseBack => Some(8),
+            Self::MouseForward => Some(9),
+            Self::MouseButton(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    /// Whether the trigger is a mouse button press or a chord of mouse buttons.
+    pub fn is_mouse_button(&self) -> bool {
+        self.mouse_button_number().is_some() || matches!(self, Self::MouseChord(..))
+    }
+
+    fn parse_mouse_button(name: &str) -> Option<Self> {
+        if name.eq_ignore_ascii_case("MouseLeft") {
+            Some(Self::MouseLeft)
+        } else if name.eq_ignore_ascii_case("MouseRight") {
+            Some(Self::MouseRight)
+        } else if name.eq_ignore_ascii_case("MouseMiddle") {
+            Some(Self::MouseMiddle)
+        } else if name.eq_ignore_ascii_case("MouseBack") {
+            Some(Self::MouseBack)
+        } else if name.eq_ignore_ascii_case("MouseForward") {
+            Some(Self::MouseForward)
+        } else {
+            let number = name.get(..6)?;
+            if !number.eq_ignore_ascii_case("Button") {
+                return None;
+            }
+            Self::mouse_button(name[6..].parse().ok()?)
+        }
+    }
+
+    /// Parses gesture names like `TouchpadSwipe3Up` or `TouchpadPinch4In`.
+    fn parse_gesture(name: &str) -> Option<Self> {
+        let prefix = |prefix: &str| {
+            let head = name.get(..prefix.len())?;
+            head.eq_ignore_ascii_case(prefix)
+                .then(|| &name[prefix.len()..])
+        };
+        let split_fingers = |rest: &str| {
+            let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+            let fingers = rest[..digits].parse().ok()?;
+            Some((fingers, &rest[digits..]))
+        };
+
+        if let Some(rest) = prefix("TouchpadSwipe") {
+            let (fingers, direction) = split_fingers(rest)?;
+            if !(3..=5).contains(&fingers) {
+                return None;
+            }
+
+            let direction = if direction.eq_ignore_ascii_case("Up") {
+                SwipeDirection::Up
+            } else if direction.eq_ignore_ascii_case("Down") {
+                SwipeDirection::Down
+            } else if direction.eq_ignore_ascii_case("Left") {
+                SwipeDirection::Left
+            } else if direction.eq_ignore_ascii_case("Right") {
+                SwipeDirection::Right
+            } else {
+                return None;
+            };
+            Some(Self::TouchpadSwipe(fingers, direction))
+        } else if let Some(rest) = prefix("TouchpadPinch") {
+            let (fingers, direction) = split_fingers(rest)?;
+            if !(2..=5).contains(&fingers) {
+                return None;
+            }
+
+            let direction = if direction.eq_ignore_ascii_case("In") {
+                PinchDirection::In
+            } else if direction.eq_ignore_ascii_case("Out") {
+                PinchDirection::Out
+            } else {
+                return None;
+            };
+            Some(Self::TouchpadPinch(fingers, direction))
+        } else {
+            None
         }
     }
 }
@@ -989,13 +1695,18 @@ impl FromStr for Key {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut modifiers = Modifiers::empty();
+        let mut held_button = None;
 
         let mut split = s.split('+');
         let key = split.next_back().unwrap();
 
         for part in split {
             let part = part.trim();
-            if part.eq_ignore_ascii_case("mod") {
+            if let Some(button) = Trigger::parse_mouse_button(part) {
+                if held_button.replace(button).is_some() {
+                    return Err(miette!("at most two mouse buttons can be combined"));
+                }
+            } else if part.eq_ignore_ascii_case("mod") {
                 modifiers |= Modifiers::COMPOSITOR
             } else if part.eq_ignore_ascii_case("ctrl") || part.eq_ignore_ascii_case("control") {
                 modifiers |= Modifiers::CTRL;
@@ -1018,16 +1729,25 @@ impl FromStr for Key {
             }
         }
 
-        let trigger = if key.eq_ignore_ascii_case("MouseLeft") {
-            Trigger::MouseLeft
-        } else if key.eq_ignore_ascii_case("MouseRight") {
-            Trigger::MouseRight
-        } else if key.eq_ignore_ascii_case("MouseMiddle") {
-            Trigger::MouseMiddle
-        } else if key.eq_ignore_ascii_case("MouseBack") {
-            Trigger::MouseBack
-        } else if key.eq_ignore_ascii_case("MouseForward") {
-            Trigger::MouseForward
+        if let Some(held) = held_button {
+            let Some(pressed) = Trigger::parse_mouse_button(key) else {
+                return Err(miette!(
+                    "mouse buttons can only be combined with another mouse button"
+                ));
+            };
+
+            let a = held.mouse_button_number().unwrap();
+            let b = pressed.mouse_button_number().unwrap();
+            if a == b {
+                return Err(miette!("a mouse button cannot be combined with itself"));
+            }
+
+            let trigger = Trigger::MouseChord(a.min(b), a.max(b));
+            return Ok(Key { trigger, modifiers });
+        }
+
+        let trigger = if let Some(trigger) = Trigger::parse_mouse_button(key) {
+            trigger
         } else if key.eq_ignore_ascii_case("WheelScrollDown") {
             Trigger::WheelScrollDown
         } else if key.eq_ignore_ascii_case("WheelScrollUp") {
@@ -1044,6 +1764,8 @@ impl FromStr for Key {
             Trigger::TouchpadScrollLeft
         } else if key.eq_ignore_ascii_case("TouchpadScrollRight") {
             Trigger::TouchpadScrollRight
+        } else if let Some(trigger) = Trigger::parse_gesture(key) {
+            trigger
         } else if key.eq_ignore_ascii_case("Mod") {
             Trigger::KeyCompositor
         } else {
@@ -1088,6 +1810,218 @@ impl FromStr for Key {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Config;
+
+    #[test]
+    fn parse_taps() {
+        let config = Config::parse_mem(
+            r#"
+            binds {
+                Mod taps=2 { toggle-overview; }
+                Mod taps=3 { spawn "fuzzel"; }
+                Mod release=true { show-hotkey-overlay; }
+            }
+            "#,
+        )
+        .unwrap();
+        let taps: Vec<_> = config.binds.0.iter().map(|bind| bind.taps).collect();
+        assert_eq!(taps, [2, 3, 1]);
+
+        for invalid in [
+            "Mod+T taps=2 { toggle-overview; }",
+            "Mod taps=1 { toggle-overview; }",
+            "Mod taps=2 { toggle-overview; }\nMod taps=2 { spawn \"fuzzel\"; }",
+        ] {
+            let config = format!("binds {{\n{invalid}\n}}");
+            assert!(Config::parse_mem(&config).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn parse_key_sequences() {
+        let config = Config::parse_mem(
+            r#"
+            binds {
+                "Mod+A C" { spawn "alacritty"; }
+                "Mod+A F" { spawn "firefox"; }
+                "Mod+A W Q" { close-window; }
+                Mod+T { spawn "foot"; }
+            }
+            "#,
+        )
+        .unwrap();
+        let key = |s: &str| s.parse::<Key>().unwrap();
+
+        let binds = &config.binds.0;
+        assert_eq!(binds.len(), 2);
+        assert_eq!(binds[0].key, key("Mod+A"));
+        let Action::KeySequence(next) = &binds[0].action else {
+            panic!("expected a key sequence");
+        };
+        let keys: Vec<_> = next.iter().map(|bind| bind.key).collect();
+        assert_eq!(keys, [key("C"), key("F"), key("W")]);
+        let Action::KeySequence(last) = &next[2].action else {
+            panic!("expected a key sequence");
+        };
+        assert_eq!(last[0].key, key("Q"));
+        assert_eq!(last[0].action, Action::CloseWindow);
+
+        for invalid in [
+            "\"Mod+A C\" { spawn \"a\"; }\n\"Mod+A C\" { spawn \"b\"; }",
+            "Mod+A { spawn \"a\"; }\n\"Mod+A C\" { spawn \"b\"; }",
+            "\"Mod+A C\" { spawn \"a\"; }\nMod+A { spawn \"b\"; }",
+            "\"Mod+A MouseLeft\" { spawn \"a\"; }",
+        ] {
+            let config = format!("binds {{\n{invalid}\n}}");
+            assert!(Config::parse_mem(&config).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn merge_binds_keeps_other_key_sequences() {
+        let mut config = Config::parse_mem(
+            r#"
+            binds {
+                "Mod+A C" { spawn "alacritty"; }
+                "Mod+A F" { spawn "firefox"; }
+                "Mod+A W Q" { close-window; }
+                Mod+T { spawn "foot"; }
+            }
+            "#,
+        )
+        .unwrap();
+        let part = Config::parse_mem(
+            r#"
+            binds {
+                "Mod+A C" { spawn "kitty"; }
+                "Mod+A W E" { quit; }
+                "Mod+T X" { spawn "xterm"; }
+            }
+            "#,
+        )
+        .unwrap();
+        merge_binds(&mut config.binds.0, part.binds.0);
+        let key = |s: &str| s.parse::<Key>().unwrap();
+
+        let binds = &config.binds.0;
+        assert_eq!(binds.len(), 2);
+        let Action::KeySequence(next) = &binds[0].action else {
+            panic!("expected a key sequence");
+        };
+        let keys: Vec<_> = next.iter().map(|bind| bind.key).collect();
+        assert_eq!(keys, [key("F"), key("W"), key("C")]);
+        assert_eq!(next[2].action, Action::Spawn(vec![String::from("kitty")]));
+        let Action::KeySequence(last) = &next[1].action else {
+            panic!("expected a key sequence");
+        };
+        let keys: Vec<_> = last.iter().map(|bind| bind.key).collect();
+        assert_eq!(keys, [key("Q"), key("E")]);
+
+        // A sequence replaces a regular bind on its first key.
+        assert_eq!(binds[1].key, key("Mod+T"));
+        assert!(matches!(binds[1].action, Action::KeySequence(_)));
+    }
+
+    #[test]
+    fn bind_mode_needs_exit_bind() {
+        for valid in [
+            "Escape { exit-bind-mode; }",
+            "Return { focus-column-left; exit-bind-mode; }",
+            "\"Space Q\" { exit-bind-mode; }",
+        ] {
+            let config = format!("bind-mode \"test\" {{\n{valid}\n}}");
+            assert!(Config::parse_mem(&config).is_ok(), "{valid}");
+        }
+
+        for invalid in ["", "Escape { focus-column-left; }"] {
+            let config = format!("bind-mode \"test\" {{\n{invalid}\n}}");
+            assert!(Config::parse_mem(&config).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn parse_multiple_actions() {
+        let config = Config::parse_mem(
+            r#"
+            binds {
+                Mod+T { spawn "foot"; focus-column-last; set-column-width "50%"; }
+                Mod+Q { close-window; }
+            }
+            "#,
+        )
+        .unwrap();
+
+        let binds = &config.binds.0;
+        assert_eq!(
+            binds[0].action,
+            Action::Multiple(vec![
+                Action::Spawn(vec![String::from("foot")]),
+                Action::FocusColumnLast,
+                Action::SetColumnWidth(SizeChange::SetProportion(50.)),
+            ])
+        );
+        assert_eq!(binds[1].action, Action::CloseWindow);
+
+        for invalid in [
+            "Mod+T { spawn \"foot\"; not-an-action; }",
+            "Mod+T allow-when-locked=true { spawn \"foot\"; close-window; }",
+        ] {
+            let config = format!("binds {{\n{invalid}\n}}");
+            assert!(Config::parse_mem(&config).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn parse_mouse_buttons() {
+        let trigger = |s: &str| s.parse::<Key>().map(|key| key.trigger).ok();
+        let chord = |a, b| Some(Trigger::MouseChord(a, b));
+
+        assert_eq!(trigger("Mod+Button8"), Some(Trigger::MouseBack));
+        assert_eq!(trigger("button1"), Some(Trigger::MouseLeft));
+        assert_eq!(trigger("Button12"), Some(Trigger::MouseButton(12)));
+        assert_eq!(trigger("Button1+Button3"), chord(1, 3));
+        assert_eq!(trigger("MouseRight+MouseLeft"), chord(1, 3));
+        assert_eq!(trigger("Mod+MouseBack+Button10"), chord(8, 10));
+
+        for invalid in [
+            "Button4",
+            "Button0",
+            "Button1+Button1",
+            "Button1+Button2+Button3",
+            "Button1+T",
+        ] {
+            assert_eq!(trigger(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn parse_gestures() {
+        let trigger = |s: &str| s.parse::<Key>().map(|key| key.trigger).ok();
+
+        assert_eq!(
+            trigger("Mod+TouchpadSwipe3Up"),
+            Some(Trigger::TouchpadSwipe(3, SwipeDirection::Up))
+        );
+        assert_eq!(
+            trigger("touchpadswipe4left"),
+            Some(Trigger::TouchpadSwipe(4, SwipeDirection::Left))
+        );
+        assert_eq!(
+            trigger("TouchpadPinch2Out"),
+            Some(Trigger::TouchpadPinch(2, PinchDirection::Out))
+        );
+
+        for invalid in [
+            "TouchpadSwipe2Up",
+            "TouchpadSwipe3",
+            "TouchpadSwipeUp",
+            "TouchpadSwipe3In",
+            "TouchpadPinch4Up",
+            "TouchpadPinch1In",
+        ] {
+            assert_eq!(trigger(invalid), None, "{invalid}");
+        }
+    }
 
     #[test]
     fn parse_xf86_screensaver() {
@@ -1173,4 +2107,39 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn tablet_pad_binds_prefer_mode() {
+        let config = Config::parse_mem(
+            r#"
+            tablet-pad-binds {
+                button 1 { focus-workspace-down; }
+                button 1 mode=2 { focus-workspace-up; }
+                strip-up 1 { focus-column-left; }
+            }
+            "#,
+        )
+        .unwrap();
+        let binds = &config.tablet_pad_binds;
+        let action = |trigger, mode| binds.find(trigger, mode).map(|bind| &bind.action);
+
+        let button = TabletPadTrigger::Button(1);
+        assert_eq!(action(button, 0), Some(&Action::FocusWorkspaceDown));
+        assert_eq!(action(button, 2), Some(&Action::FocusWorkspaceUp));
+        assert_eq!(
+            action(TabletPadTrigger::StripUp(1), 0),
+            Some(&Action::FocusColumnLeft)
+        );
+        assert_eq!(action(TabletPadTrigger::StripUp(0), 0), None);
+
+        for invalid in [
+            "button { focus-workspace-down; }",
+            "button 1",
+            "knob 1 { focus-workspace-down; }",
+            "ring-cw { focus-column-right; }\nring-cw { focus-column-left; }",
+        ] {
+            let config = format!("tablet-pad-binds {{\n{invalid}\n}}");
+            assert!(Config::parse_mem(&config).is_err(), "{invalid}");
+        }
+    }
 }
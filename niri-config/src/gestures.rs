@@ -1,14 +1,19 @@
-use crate::utils::MergeWith;
-use crate::FloatOrInt;
+use knuffel::errors::DecodeError;
 
-#[derive(Debug, Default, Clone, Copy, PartialEq)]
+use crate::utils::{expect_only_children, Flag, MergeWith};
+use crate::{Action, FloatOrInt};
+
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Gestures {
     pub dnd_edge_view_scroll: DndEdgeViewScroll,
     pub dnd_edge_workspace_switch: DndEdgeWorkspaceSwitch,
     pub hot_corners: HotCorners,
+    pub pinch_column_width: PinchColumnWidth,
+    pub touch_edge_swipe: TouchEdgeSwipe,
+    pub touch_long_press_move: TouchLongPressMove,
 }
 
-#[derive(knuffel::Decode, Debug, Default, Clone, Copy, PartialEq)]
+#[derive(knuffel::Decode, Debug, Default, Clone, PartialEq)]
 pub struct GesturesPart {
     #[knuffel(child)]
     pub dnd_edge_view_scroll: Option<DndEdgeViewScrollPart>,
@@ -16,6 +21,12 @@ pub struct GesturesPart {
     pub dnd_edge_workspace_switch: Option<DndEdgeWorkspaceSwitchPart>,
     #[knuffel(child)]
     pub hot_corners: Option<HotCorners>,
+    #[knuffel(child)]
+    pub pinch_column_width: Option<PinchColumnWidthPart>,
+    #[knuffel(child)]
+    pub touch_edge_swipe: Option<TouchEdgeSwipePart>,
+    #[knuffel(child)]
+    pub touch_long_press_move: Option<TouchLongPressMovePart>,
 }
 
 impl MergeWith<GesturesPart> for Gestures {
@@ -24,6 +35,9 @@ impl MergeWith<GesturesPart> for Gestures {
             (self, part),
             dnd_edge_view_scroll,
             dnd_edge_workspace_switch,
+            pinch_column_width,
+            touch_edge_swipe,
+            touch_long_press_move,
         );
         merge_clone!((self, part), hot_corners);
     }
@@ -97,16 +111,205 @@ impl MergeWith<DndEdgeWorkspaceSwitchPart> for DndEdgeWorkspaceSwitch {
     }
 }
 
-#[derive(knuffel::Decode, Debug, Default, Clone, Copy, PartialEq)]
+#[derive(knuffel::Decode, Debug, Default, Clone, PartialEq)]
 pub struct HotCorners {
     #[knuffel(child)]
     pub off: bool,
     #[knuffel(child)]
-    pub top_left: bool,
+    pub top_left: Option<HotCorner>,
+    #[knuffel(child)]
+    pub top_right: Option<HotCorner>,
+    #[knuffel(child)]
+    pub bottom_left: Option<HotCorner>,
     #[knuffel(child)]
-    pub top_right: bool,
+    pub bottom_right: Option<HotCorner>,
+    /// How long the pointer must stay in a corner before it triggers.
+    #[knuffel(child, unwrap(argument))]
+    pub delay_ms: Option<u16>,
     #[knuffel(child)]
-    pub bottom_left: bool,
+    pub disable_while_fullscreen: Option<Flag>,
+}
+
+impl HotCorners {
+    pub fn disable_while_fullscreen(&self) -> bool {
+        self.disable_while_fullscreen.is_none_or(|flag| flag.0)
+    }
+}
+
+/// Action run by a hot corner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HotCorner {
+    pub action: Action,
+}
+
+impl Default for HotCorner {
+    fn default() -> Self {
+        Self {
+            action: Action::ToggleOverview,
+        }
+    }
+}
+
+impl<S> knuffel::Decode<S> for HotCorner
+where
+    S: knuffel::traits::ErrorSpan,
+{
+    fn decode_node(
+        node: &knuffel::ast::SpannedNode<S>,
+        ctx: &mut knuffel::decode::Context<S>,
+    ) -> Result<Self, DecodeError<S>> {
+        expect_only_children(node, ctx);
+
+        let mut children = node.children();
+
+        // A corner without an action toggles the overview.
+        let Some(child) = children.next() else {
+            return Ok(Self::default());
+        };
+
+        for unwanted_child in children {
+            ctx.emit_error(DecodeError::unexpected(
+                unwanted_child,
+                "node",
+                "only one action is allowed per hot corner",
+            ));
+        }
+
+        let action = Action::decode_node(child, ctx)?;
+        Ok(Self { action })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PinchColumnWidth {
+    pub on: bool,
+    pub fingers: u8,
+    pub snap_distance: f64,
+}
+
+impl Default for PinchColumnWidth {
+    fn default() -> Self {
+        Self {
+            on: false,
+            fingers: 3,
+            snap_distance: 30.,
+        }
+    }
+}
+
+#[derive(knuffel::Decode, Debug, Clone, Copy, PartialEq)]
+pub struct PinchColumnWidthPart {
     #[knuffel(child)]
-    pub bottom_right: bool,
+    pub on: Option<Flag>,
+    #[knuffel(child, unwrap(argument))]
+    pub fingers: Option<u8>,
+    #[knuffel(child, unwrap(argument))]
+    pub snap_distance: Option<FloatOrInt<0, 65535>>,
+}
+
+impl MergeWith<PinchColumnWidthPart> for PinchColumnWidth {
+    fn merge_with(&mut self, part: &PinchColumnWidthPart) {
+        merge!((self, part), on, snap_distance);
+        merge_clone!((self, part), fingers);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchEdgeSwipe {
+    pub on: bool,
+    /// Distance from the output edge where a touch starts an edge swipe.
+    pub trigger_size: f64,
+    pub top: TouchEdgeAction,
+    pub bottom: TouchEdgeAction,
+    pub left: TouchEdgeAction,
+    pub right: TouchEdgeAction,
+}
+
+impl Default for TouchEdgeSwipe {
+    fn default() -> Self {
+        Self {
+            on: false,
+            trigger_size: 20.,
+            top: TouchEdgeAction::Overview,
+            bottom: TouchEdgeAction::Overview,
+            left: TouchEdgeAction::WorkspaceSwitch,
+            right: TouchEdgeAction::WorkspaceSwitch,
+        }
+    }
+}
+
+#[derive(knuffel::Decode, Debug, Clone, Copy, PartialEq)]
+pub struct TouchEdgeSwipePart {
+    #[knuffel(child)]
+    pub off: bool,
+    #[knuffel(child)]
+    pub on: bool,
+    #[knuffel(child, unwrap(argument))]
+    pub trigger_size: Option<FloatOrInt<0, 65535>>,
+    #[knuffel(child, unwrap(argument))]
+    pub top: Option<TouchEdgeAction>,
+    #[knuffel(child, unwrap(argument))]
+    pub bottom: Option<TouchEdgeAction>,
+    #[knuffel(child, unwrap(argument))]
+    pub left: Option<TouchEdgeAction>,
+    #[knuffel(child, unwrap(argument))]
+    pub right: Option<TouchEdgeAction>,
+}
+
+impl MergeWith<TouchEdgeSwipePart> for TouchEdgeSwipe {
+    fn merge_with(&mut self, part: &TouchEdgeSwipePart) {
+        self.on |= part.on;
+        if part.off {
+            self.on = false;
+        }
+
+        merge!((self, part), trigger_size);
+        merge_clone!((self, part), top, bottom, left, right);
+    }
+}
+
+/// What a touch swipe from an output edge does.
+#[derive(knuffel::DecodeScalar, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchEdgeAction {
+    None,
+    /// Opens the overview, following the finger away from the edge.
+    Overview,
+    /// Switches workspaces, following the finger along the edge.
+    WorkspaceSwitch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchLongPressMove {
+    pub on: bool,
+    pub delay_ms: u16,
+}
+
+impl Default for TouchLongPressMove {
+    fn default() -> Self {
+        Self {
+            on: false,
+            delay_ms: 500,
+        }
+    }
+}
+
+#[derive(knuffel::Decode, Debug, Clone, Copy, PartialEq)]
+pub struct TouchLongPressMovePart {
+    #[knuffel(child)]
+    pub off: bool,
+    #[knuffel(child)]
+    pub on: bool,
+    #[knuffel(child, unwrap(argument))]
+    pub delay_ms: Option<u16>,
+}
+
+impl MergeWith<TouchLongPressMovePart> for TouchLongPressMove {
+    fn merge_with(&mut self, part: &TouchLongPressMovePart) {
+        self.on |= part.on;
+        if part.off {
+            self.on = false;
+        }
+
+        merge_clone!((self, part), delay_ms);
+    }
 }
@@ -1,4 +1,5 @@
 use std::str::FromStr;
+use std::time::Duration;
 
 use miette::miette;
 use smithay::input::keyboard::{keysyms, Keysym, XkbConfig};
@@ -8,7 +9,7 @@ use crate::binds::Modifiers;
 use crate::utils::{Flag, MergeWith, Percent};
 use crate::FloatOrInt;
 
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Input {
     pub keyboard: Keyboard,
     pub touchpad: Touchpad,
@@ -17,9 +18,11 @@ pub struct Input {
     pub trackball: Trackball,
     pub tablet: Tablet,
     pub touch: Touch,
+    pub devices: Vec<InputDevice>,
     pub disable_power_key_handling: bool,
     pub warp_mouse_to_focus: Option<WarpMouseToFocus>,
     pub focus_follows_mouse: Option<FocusFollowsMouse>,
+    pub resize_on_border: Option<ResizeOnBorder>,
     pub workspace_auto_back_and_forth: bool,
     pub mod_key: Option<ModKey>,
     pub mod_key_nested: Option<ModKey>,
@@ -41,6 +44,8 @@ pub struct InputPart {
     pub tablet: Option<Tablet>,
     #[knuffel(child)]
     pub touch: Option<Touch>,
+    #[knuffel(children(name = "device"))]
+    pub devices: Vec<InputDevice>,
     #[knuffel(child)]
     pub disable_power_key_handling: Option<Flag>,
     #[knuffel(child)]
@@ -48,6 +53,8 @@ pub struct InputPart {
     #[knuffel(child)]
     pub focus_follows_mouse: Option<FocusFollowsMouse>,
     #[knuffel(child)]
+    pub resize_on_border: Option<ResizeOnBorder>,
+    #[knuffel(child)]
     pub workspace_auto_back_and_forth: Option<Flag>,
     #[knuffel(child, unwrap(argument, str))]
     pub mod_key: Option<ModKey>,
@@ -78,19 +85,28 @@ impl MergeWith<InputPart> for Input {
             (self, part),
             warp_mouse_to_focus,
             focus_follows_mouse,
+            resize_on_border,
             mod_key,
             mod_key_nested,
         );
+
+        self.devices.extend(part.devices.iter().cloned());
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Keyboard {
     pub xkb: Xkb,
     pub repeat_delay: u16,
     pub repeat_rate: u8,
+    pub tap_interval_ms: u16,
+    /// How long a key sequence waits for its next key.
+    pub key_sequence_timeout_ms: u16,
     pub track_layout: TrackLayout,
     pub numlock: bool,
+    pub sticky_keys: bool,
+    /// How long a key must be held down before its press is accepted, 0 to turn slow keys off.
+    pub slow_keys_delay_ms: u16,
 }
 
 impl Default for Keyboard {
@@ -100,8 +116,12 @@ impl Default for Keyboard {
             // The defaults were chosen to match wlroots and sway.
             repeat_delay: 600,
             repeat_rate: 25,
+            tap_interval_ms: 300,
+            key_sequence_timeout_ms: 2000,
             track_layout: Default::default(),
             numlock: Default::default(),
+            sticky_keys: false,
+            slow_keys_delay_ms: 0,
         }
     }
 }
@@ -115,15 +135,32 @@ pub struct KeyboardPart {
     #[knuffel(child, unwrap(argument))]
     pub repeat_rate: Option<u8>,
     #[knuffel(child, unwrap(argument))]
+    pub tap_interval_ms: Option<u16>,
+    #[knuffel(child, unwrap(argument))]
+    pub key_sequence_timeout_ms: Option<u16>,
+    #[knuffel(child, unwrap(argument))]
     pub track_layout: Option<TrackLayout>,
     #[knuffel(child)]
     pub numlock: Option<Flag>,
+    #[knuffel(child)]
+    pub sticky_keys: Option<Flag>,
+    #[knuffel(child, unwrap(argument))]
+    pub slow_keys_delay_ms: Option<u16>,
 }
 
 impl MergeWith<KeyboardPart> for Keyboard {
     fn merge_with(&mut self, part: &KeyboardPart) {
-        merge_clone!((self, part), xkb, repeat_delay, repeat_rate, track_layout);
-        merge!((self, part), numlock);
+        merge_clone!(
+            (self, part),
+            xkb,
+            repeat_delay,
+            repeat_rate,
+            tap_interval_ms,
+            key_sequence_timeout_ms,
+            track_layout,
+            slow_keys_delay_ms,
+        );
+        merge!((self, part), numlock, sticky_keys);
     }
 }
 
@@ -291,6 +328,54 @@ pub struct Trackball {
     pub middle_emulation: bool,
 }
 
+/// Settings for specific devices, applied on top of the settings for their device type.
+#[derive(knuffel::Decode, Debug, Default, Clone, PartialEq)]
+pub struct InputDevice {
+    #[knuffel(argument)]
+    pub name: Option<String>,
+    #[knuffel(property)]
+    pub vendor: Option<u32>,
+    #[knuffel(property)]
+    pub product: Option<u32>,
+    #[knuffel(child)]
+    pub off: Option<Flag>,
+    #[knuffel(child)]
+    pub natural_scroll: Option<Flag>,
+    #[knuffel(child, unwrap(argument))]
+    pub accel_speed: Option<FloatOrInt<-1, 1>>,
+    #[knuffel(child, unwrap(argument, str))]
+    pub accel_profile: Option<AccelProfile>,
+    #[knuffel(child, unwrap(argument, str))]
+    pub scroll_method: Option<ScrollMethod>,
+    #[knuffel(child, unwrap(argument))]
+    pub scroll_button: Option<u32>,
+    #[knuffel(child)]
+    pub scroll_button_lock: Option<Flag>,
+    #[knuffel(child)]
+    pub left_handed: Option<Flag>,
+    #[knuffel(child)]
+    pub middle_emulation: Option<Flag>,
+    #[knuffel(child)]
+    pub tap: Option<Flag>,
+    #[knuffel(child)]
+    pub dwt: Option<Flag>,
+}
+
+impl InputDevice {
+    /// Returns whether this section applies to the device.
+    ///
+    /// Every criterion that is set must match. A section without any criteria matches nothing.
+    pub fn matches(&self, name: &str, vendor: u32, product: u32) -> bool {
+        if self.name.is_none() && self.vendor.is_none() && self.product.is_none() {
+            return false;
+        }
+
+        self.name.as_deref().is_none_or(|n| n == name)
+            && self.vendor.is_none_or(|v| v == vendor)
+            && self.product.is_none_or(|p| p == product)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ClickMethod {
     Clickfinger,
@@ -363,8 +448,97 @@ pub struct Tablet {
     pub calibration_matrix: Option<Vec<f32>>,
     #[knuffel(child, unwrap(argument))]
     pub map_to_output: Option<String>,
+    /// Part of the output (or of all outputs) to map the tablet to.
+    #[knuffel(child)]
+    pub map_to_region: Option<TabletRegion>,
+    #[knuffel(child)]
+    pub map_to_focused_window: bool,
     #[knuffel(child)]
     pub left_handed: bool,
+    /// Pressure curve for the tools without their own.
+    #[knuffel(child)]
+    pub pressure_curve: Option<PressureCurve>,
+    #[knuffel(children(name = "tool"))]
+    pub tools: Vec<TabletTool>,
+}
+
+impl Tablet {
+    /// Returns the pressure curve to use for the tool type.
+    pub fn pressure_curve(&self, tool_type: Option<TabletToolType>) -> Option<PressureCurve> {
+        tool_type
+            .and_then(|tool_type| self.tools.iter().find(|tool| tool.tool_type == tool_type))
+            .and_then(|tool| tool.pressure_curve)
+            .or(self.pressure_curve)
+    }
+}
+
+/// Rectangle in logical coordinates, relative to the top-left corner of the mapped area.
+#[derive(knuffel::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TabletRegion {
+    #[knuffel(property)]
+    pub x: i32,
+    #[knuffel(property)]
+    pub y: i32,
+    #[knuffel(property)]
+    pub width: u16,
+    #[knuffel(property)]
+    pub height: u16,
+}
+
+#[derive(knuffel::Decode, Debug, Clone, PartialEq)]
+pub struct TabletTool {
+    #[knuffel(argument)]
+    pub tool_type: TabletToolType,
+    #[knuffel(child)]
+    pub pressure_curve: Option<PressureCurve>,
+}
+
+#[derive(knuffel::DecodeScalar, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabletToolType {
+    Pen,
+    Eraser,
+    Brush,
+    Pencil,
+    Airbrush,
+    Mouse,
+    Lens,
+}
+
+/// Cubic Bézier curve from (0, 0) to (1, 1), given by its two middle control points.
+#[derive(knuffel::Decode, Debug, Clone, Copy, PartialEq)]
+pub struct PressureCurve {
+    #[knuffel(argument)]
+    pub x1: FloatOrInt<0, 1>,
+    #[knuffel(argument)]
+    pub y1: FloatOrInt<0, 1>,
+    #[knuffel(argument)]
+    pub x2: FloatOrInt<0, 1>,
+    #[knuffel(argument)]
+    pub y2: FloatOrInt<0, 1>,
+}
+
+impl PressureCurve {
+    /// Maps the physical pressure from 0 to 1 to the pressure sent to clients.
+    pub fn apply(self, pressure: f64) -> f64 {
+        fn bezier(p1: f64, p2: f64, t: f64) -> f64 {
+            let u = 1. - t;
+            3. * u * u * t * p1 + 3. * u * t * t * p2 + t * t * t
+        }
+
+        // With the control points within 0 to 1, x only grows with t, so bisect for t.
+        let pressure = pressure.clamp(0., 1.);
+        let (mut low, mut high) = (0., 1.);
+        for _ in 0..32 {
+            let mid = (low + high) / 2.;
+            if bezier(self.x1.0, self.x2.0, mid) < pressure {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        bezier(self.y1.0, self.y2.0, (low + high) / 2.)
+    }
 }
 
 #[derive(knuffel::Decode, Debug, Default, Clone, PartialEq)]
@@ -381,6 +555,32 @@ pub struct Touch {
 pub struct FocusFollowsMouse {
     #[knuffel(property, str)]
     pub max_scroll_amount: Option<Percent>,
+    /// Time in milliseconds that the pointer has to stay over a window before it's focused.
+    #[knuffel(property)]
+    pub delay_ms: Option<u16>,
+    /// Whether the delay restarts whenever the pointer moves.
+    #[knuffel(property, default)]
+    pub wait_for_pointer_stop: bool,
+}
+
+impl FocusFollowsMouse {
+    pub fn delay(self) -> Duration {
+        let default = if self.wait_for_pointer_stop { 100 } else { 0 };
+        Duration::from_millis(u64::from(self.delay_ms.unwrap_or(default)))
+    }
+}
+
+#[derive(knuffel::Decode, Debug, Clone, Copy, PartialEq)]
+pub struct ResizeOnBorder {
+    /// Distance from the window edge, in logical pixels, that starts a resize.
+    #[knuffel(property)]
+    pub width: Option<FloatOrInt<0, 65535>>,
+}
+
+impl ResizeOnBorder {
+    pub fn width(self) -> f64 {
+        self.width.map_or(8., |width| width.0)
+    }
 }
 
 #[derive(knuffel::Decode, Debug, PartialEq, Eq, Clone, Copy)]
@@ -758,4 +958,68 @@ mod tests {
         )
         ");
     }
+
+    #[test]
+    fn parse_tablet_tools() {
+        let parsed = do_parse(
+            r#"
+            tablet {
+                map-to-region x=100 y=0 width=800 height=600
+                pressure-curve 0 0.5 0.5 1
+                tool "eraser" {
+                    pressure-curve 0.5 0 1 0.5
+                }
+                tool "pen"
+            }
+            "#,
+        );
+
+        let tablet = &parsed.tablet;
+        assert_eq!(
+            tablet.map_to_region,
+            Some(TabletRegion {
+                x: 100,
+                y: 0,
+                width: 800,
+                height: 600,
+            })
+        );
+
+        let default = tablet.pressure_curve.unwrap();
+        let eraser = tablet.pressure_curve(Some(TabletToolType::Eraser)).unwrap();
+        assert_eq!(eraser.x1, FloatOrInt(0.5));
+
+        // Tools without their own curve use the default one.
+        assert_eq!(
+            tablet.pressure_curve(Some(TabletToolType::Pen)),
+            Some(default)
+        );
+        assert_eq!(
+            tablet.pressure_curve(Some(TabletToolType::Brush)),
+            Some(default)
+        );
+        assert_eq!(tablet.pressure_curve(None), Some(default));
+    }
+
+    #[test]
+    fn pressure_curve_apply() {
+        let curve = |x1, y1, x2, y2| PressureCurve {
+            x1: FloatOrInt(x1),
+            y1: FloatOrInt(y1),
+            x2: FloatOrInt(x2),
+            y2: FloatOrInt(y2),
+        };
+
+        // Control points on the diagonal give a linear curve.
+        let linear = curve(0.25, 0.25, 0.75, 0.75);
+        for pressure in [0., 0.1, 0.5, 0.9, 1.] {
+            assert!((linear.apply(pressure) - pressure).abs() < 1e-6);
+        }
+
+        let soft = curve(0., 0.5, 0.5, 1.);
+        assert!(soft.apply(0.).abs() < 1e-6);
+        assert!(soft.apply(0.5) > 0.7);
+        assert!((soft.apply(1.) - 1.).abs() < 1e-6);
+        assert!((soft.apply(2.) - 1.).abs() < 1e-6);
+    }
 }
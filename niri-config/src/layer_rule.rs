@@ -1,5 +1,5 @@
 use crate::appearance::{BlockOutFrom, CornerRadius, ShadowRule};
-use crate::utils::RegexEq;
+use crate::utils::{RegexEq, TimeRange};
 use crate::BlurRule;
 
 #[derive(knuffel::Decode, Debug, Default, Clone, PartialEq)]
@@ -23,6 +23,10 @@ pub struct LayerRule {
     pub place_within_backdrop: Option<bool>,
     #[knuffel(child, unwrap(argument))]
     pub baba_is_float: Option<bool>,
+    #[knuffel(child, unwrap(argument))]
+    pub open_close_animation: Option<LayerOpenCloseAnimation>,
+    #[knuffel(child, unwrap(argument))]
+    pub on_screen_keyboard: Option<bool>,
 }
 
 #[derive(knuffel::Decode, Debug, Default, Clone, PartialEq)]
@@ -31,4 +35,14 @@ pub struct Match {
     pub namespace: Option<RegexEq>,
     #[knuffel(property)]
     pub at_startup: Option<bool>,
+    #[knuffel(property, str)]
+    pub at: Option<TimeRange>,
+}
+
+#[derive(knuffel::DecodeScalar, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LayerOpenCloseAnimation {
+    #[default]
+    Off,
+    Fade,
+    Slide,
 }
@@ -51,23 +51,29 @@ pub use crate::debug::Debug;
 pub use crate::error::{ConfigIncludeError, ConfigParseResult};
 pub use crate::gestures::Gestures;
 pub use crate::input::{Input, ModKey, ScrollMethod, TrackLayout, WarpMouseToFocusMode, Xkb};
-pub use crate::layer_rule::LayerRule;
+pub use crate::layer_rule::{LayerOpenCloseAnimation, LayerRule};
 pub use crate::layout::*;
 pub use crate::misc::*;
-pub use crate::output::{Output, OutputName, Outputs, Position, Vrr};
+pub use crate::output::{
+    Hdr, Output, OutputName, OutputProfile, OutputProfileWorkspace, OutputProfiles, Outputs,
+    Position, Vrr,
+};
 use crate::recent_windows::RecentWindowsPart;
 pub use crate::recent_windows::{MruDirection, MruFilter, MruPreviews, MruScope, RecentWindows};
 pub use crate::utils::FloatOrInt;
 use crate::utils::{Flag, MergeWith as _};
-pub use crate::window_rule::{FloatingPosition, RelativeTo, WindowRule};
+pub use crate::window_rule::{
+    FloatingPosition, ForcePointerConstraint, KeyboardShortcutsInhibit, RelativeTo, WindowRule,
+};
 pub use crate::workspace::{Workspace, WorkspaceLayoutPart};
 
 const RECURSION_LIMIT: u8 = 10;
 
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Config {
     pub input: Input,
     pub outputs: Outputs,
+    pub output_profiles: OutputProfiles,
     pub spawn_at_startup: Vec<SpawnAtStartup>,
     pub spawn_sh_at_startup: Vec<SpawnShAtStartup>,
     pub layout: Layout,
@@ -85,10 +91,16 @@ pub struct Config {
     pub window_rules: Vec<WindowRule>,
     pub layer_rules: Vec<LayerRule>,
     pub binds: Binds,
+    pub bind_modes: Vec<BindMode>,
     pub switch_events: SwitchBinds,
+    pub tablet_pad_binds: TabletPadBinds,
     pub debug: Debug,
     pub workspaces: Vec<Workspace>,
     pub recent_windows: RecentWindows,
+    pub session: Session,
+    pub session_lock: SessionLock,
+    pub window_parking: WindowParking,
+    pub security_context: SecurityContext,
 }
 
 #[derive(Debug, Clone)]
@@ -199,12 +211,19 @@ where
                 "xwayland-satellite" => m_merge!(xwayland_satellite),
                 "switch-events" => m_merge!(switch_events),
                 "debug" => m_merge!(debug),
+                "session" => m_merge!(session),
+                "session-lock" => m_merge!(session_lock),
+                "window-parking" => m_merge!(window_parking),
 
                 // Multipart sections.
                 "output" => {
                     let part = Output::decode_node(node, ctx)?;
                     config.borrow_mut().outputs.0.push(part);
                 }
+                "output-profiles" => {
+                    let part = OutputProfiles::decode_node(node, ctx)?;
+                    config.borrow_mut().output_profiles.0.extend(part.0);
+                }
                 "spawn-at-startup" => m_push!(spawn_at_startup),
                 "spawn-sh-at-startup" => m_push!(spawn_sh_at_startup),
                 "window-rule" => m_push!(window_rules),
@@ -219,16 +238,45 @@ where
                     // where you import some preconfigured-dots.kdl, then override some binds with
                     // your own.
                     let mut config = config.borrow_mut();
-                    let binds = &mut config.binds.0;
-                    // Remove existing binds matching any new bind.
-                    binds.retain(|bind| !part.0.iter().any(|new| new.key == bind.key));
-                    // Add all new binds.
+                    merge_binds(&mut config.binds.0, part.0);
+                }
+                "bind-mode" => {
+                    let part = BindMode::decode_node(node, ctx)?;
+
+                    // Same as binds, later sections replace conflicting binds of the same mode.
+                    let mut config = config.borrow_mut();
+                    if let Some(mode) = config
+                        .bind_modes
+                        .iter_mut()
+                        .find(|mode| mode.name == part.name)
+                    {
+                        merge_binds(&mut mode.binds.0, part.binds.0);
+                    } else {
+                        config.bind_modes.push(part);
+                    }
+                }
+                "tablet-pad-binds" => {
+                    let part = TabletPadBinds::decode_node(node, ctx)?;
+
+                    // Same as binds, later sections replace conflicting binds.
+                    let mut config = config.borrow_mut();
+                    let binds = &mut config.tablet_pad_binds.0;
+                    binds.retain(|bind| {
+                        !part
+                            .0
+                            .iter()
+                            .any(|new| new.trigger == bind.trigger && new.mode == bind.mode)
+                    });
                     binds.extend(part.0);
                 }
                 "environment" => {
                     let part = Environment::decode_node(node, ctx)?;
                     config.borrow_mut().environment.0.extend(part.0);
                 }
+                "security-context" => {
+                    let part = SecurityContext::decode_node(node, ctx)?;
+                    config.borrow_mut().security_context.0.extend(part.0);
+                }
 
                 "prefer-no-csd" => {
                     config.borrow_mut().prefer_no_csd = Flag::decode_node(node, ctx)?.0
@@ -457,6 +505,13 @@ impl Config {
         res.config.unwrap()
     }
 
+    /// Returns the binds of a bind mode, or the regular binds if there's no such mode.
+    pub fn binds_in_mode(&self, bind_mode: Option<&str>) -> &Binds {
+        bind_mode
+            .and_then(|name| self.bind_modes.iter().find(|mode| mode.name == name))
+            .map_or(&self.binds, |mode| &mode.binds)
+    }
+
     pub fn load(path: &Path) -> ConfigParseResult<Self, miette::Report> {
         let contents = match fs::read_to_string(path) {
             Ok(x) => x,
@@ -646,6 +701,7 @@ mod tests {
                     repeat-delay 600
                     repeat-rate 25
                     track-layout "window"
+                    sticky-keys
                     xkb {
                         layout "us,ru"
                         options "grp:win_space_toggle"
@@ -709,10 +765,18 @@ mod tests {
                     map-to-output "eDP-1"
                 }
 
+                device "Logitech USB Receiver" vendor=0x046d {
+                    natural-scroll false
+                    accel-profile "flat"
+                    scroll-method "on-button-down"
+                    scroll-button-lock
+                }
+
                 disable-power-key-handling
 
                 warp-mouse-to-focus
-                focus-follows-mouse
+                focus-follows-mouse delay-ms=150 wait-for-pointer-stop=true
+                resize-on-border width=6
                 workspace-auto-back-and-forth
 
                 mod-key "Mod5"
@@ -726,13 +790,16 @@ mod tests {
                 position x=10 y=20
                 mode "1920x1080@144"
                 variable-refresh-rate on-demand=true
+                hdr max-luminance=1000 max-fall=400
                 background-color "rgba(25, 25, 102, 1.0)"
                 hot-corners {
                     off
                     top-left
-                    top-right
+                    top-right { spawn "fuzzel"; }
                     bottom-left
-                    bottom-right
+                    bottom-right { power-off-monitors; }
+                    delay-ms 150
+                    disable-while-fullscreen false
                 }
             }
 
@@ -744,6 +811,23 @@ mod tests {
                 modeline 173.00  1920 2048 2248 2576  1080 1083 1088 1120 "-hsync" "+vsync"
             }
 
+            output-profiles {
+                profile "docked" when-connected="DP-3" {
+                    output "eDP-1" {
+                        off
+                    }
+
+                    output "DP-3" {
+                        scale 1.5
+                        position x=0 y=0
+                    }
+
+                    workspace "chat" {
+                        open-on-output "DP-3"
+                    }
+                }
+            }
+
             layout {
                 focus-ring {
                     width 5
@@ -814,6 +898,11 @@ mod tests {
 
             clipboard {
                 disable-primary
+
+                history {
+                    on
+                    max-entries 20
+                }
             }
 
             hotkey-overlay {
@@ -848,6 +937,11 @@ mod tests {
                     trigger-width 10
                     max-speed 50
                 }
+
+                touch-edge-swipe {
+                    on
+                    left "none"
+                }
             }
 
             environment {
@@ -860,13 +954,14 @@ mod tests {
                 exclude title="~"
                 exclude is-active=true is-focused=false
 
-                open-on-output "eDP-1"
+                open-on-output "eDP-1" "HDMI-A-1"
                 open-maximized true
                 open-fullscreen false
                 open-floating false
                 open-focused true
                 default-window-height { fixed 500; }
                 default-floating-position x=100 y=-200 relative-to="bottom-left"
+                force-pointer-constraint "confine"
 
                 focus-ring {
                     off
@@ -884,7 +979,7 @@ mod tests {
             }
 
             layer-rule {
-                match namespace="^notifications$"
+                match namespace="^notifications$" at="22:00-06:00"
                 block-out-from "screencast"
             }
 
@@ -898,7 +993,7 @@ mod tests {
                 Mod+Ctrl+Shift+L { move-window-to-monitor-right; }
                 Mod+Ctrl+Alt+O { move-window-to-monitor "eDP-1"; }
                 Mod+Ctrl+Alt+P { move-column-to-monitor "DP-1"; }
-                Mod+Comma { consume-window-into-column; }
+                Mod+Comma repeat-delay=150 repeat-rate=40 { consume-window-into-column; }
                 Mod+1 { focus-workspace 1; }
                 Mod+Shift+1 { focus-workspace "workspace-1"; }
                 Mod+Shift+E allow-inhibiting=false { quit skip-confirmation=true; }
@@ -908,11 +1003,21 @@ mod tests {
                 Shift+Mod release=true allow-invalidation=false { toggle-window-floating; }
             }
 
+            bind-mode "resize" {
+                Left { set-column-width "-10%"; }
+                Escape { exit-bind-mode; }
+            }
+
             switch-events {
                 tablet-mode-on { spawn "bash" "-c" "gsettings set org.gnome.desktop.a11y.applications screen-keyboard-enabled true"; }
                 tablet-mode-off { spawn "bash" "-c" "gsettings set org.gnome.desktop.a11y.applications screen-keyboard-enabled false"; }
             }
 
+            tablet-pad-binds {
+                button 0 { focus-workspace-down; }
+                ring-cw mode=1 { emulate-key "Ctrl+plus"; }
+            }
+
             debug {
                 render-drm-device "/dev/dri/renderD129"
                 ignore-drm-device "/dev/dri/renderD128"
@@ -921,6 +1026,8 @@ mod tests {
 
             workspace "workspace-1" {
                 open-on-output "eDP-1"
+                icon "📫"
+                color "#ff0000"
             }
             workspace "workspace-2"
             workspace "workspace-3"
@@ -943,6 +1050,28 @@ mod tests {
                     Super+Tab { next-window scope="output"; }
                 }
             }
+
+            session {
+                restore-layout
+            }
+
+            session-lock {
+                backdrop-blur {
+                    on
+                    passes 2
+                }
+            }
+
+            window-parking {
+                inactive-after-minutes 240
+                workspace "parking"
+            }
+
+            security-context {
+                allow sandbox-engine="flatpak" app-id="com.obsproject.Studio" {
+                    screencopy
+                }
+            }
             "##,
         );
 
@@ -962,8 +1091,12 @@ mod tests {
                     },
                     repeat_delay: 600,
                     repeat_rate: 25,
+                    tap_interval_ms: 300,
+                    key_sequence_timeout_ms: 2000,
                     track_layout: Window,
                     numlock: false,
+                    sticky_keys: true,
+                    slow_keys_delay_ms: 0,
                 },
                 touchpad: Touchpad {
                     off: false,
@@ -1092,7 +1225,11 @@ mod tests {
                     map_to_output: Some(
                         "eDP-1",
                     ),
+                    map_to_region: None,
+                    map_to_focused_window: false,
                     left_handed: false,
+                    pressure_curve: None,
+                    tools: [],
                 },
                 touch: Touch {
                     off: false,
@@ -1101,6 +1238,40 @@ mod tests {
                         "eDP-1",
                     ),
                 },
+                devices: [
+                    InputDevice {
+                        name: Some(
+                            "Logitech USB Receiver",
+                        ),
+                        vendor: Some(
+                            1133,
+                        ),
+                        product: None,
+                        off: None,
+                        natural_scroll: Some(
+                            Flag(
+                                false,
+                            ),
+                        ),
+                        accel_speed: None,
+                        accel_profile: Some(
+                            Flat,
+                        ),
+                        scroll_method: Some(
+                            OnButtonDown,
+                        ),
+                        scroll_button: None,
+                        scroll_button_lock: Some(
+                            Flag(
+                                true,
+                            ),
+                        ),
+                        left_handed: None,
+                        middle_emulation: None,
+                        tap: None,
+                        dwt: None,
+                    },
+                ],
                 disable_power_key_handling: true,
                 warp_mouse_to_focus: Some(
                     WarpMouseToFocus {
@@ -1110,6 +1281,19 @@ mod tests {
                 focus_follows_mouse: Some(
                     FocusFollowsMouse {
                         max_scroll_amount: None,
+                        delay_ms: Some(
+                            150,
+                        ),
+                        wait_for_pointer_stop: true,
+                    },
+                ),
+                resize_on_border: Some(
+                    ResizeOnBorder {
+                        width: Some(
+                            FloatOrInt(
+                                6.0,
+                            ),
+                        ),
                     },
                 ),
                 workspace_auto_back_and_forth: true,
@@ -1155,6 +1339,18 @@ mod tests {
                                 on_demand: true,
                             },
                         ),
+                        hdr: Some(
+                            Hdr {
+                                max_luminance: Some(
+                                    1000,
+                                ),
+                                min_luminance: None,
+                                max_cll: None,
+                                max_fall: Some(
+                                    400,
+                                ),
+                            },
+                        ),
                         focus_at_startup: true,
                         background_color: Some(
                             Color {
@@ -1168,10 +1364,38 @@ mod tests {
                         hot_corners: Some(
                             HotCorners {
                                 off: true,
-                                top_left: true,
-                                top_right: true,
-                                bottom_left: true,
-                                bottom_right: true,
+                                top_left: Some(
+                                    HotCorner {
+                                        action: ToggleOverview,
+                                    },
+                                ),
+                                top_right: Some(
+                                    HotCorner {
+                                        action: Spawn(
+                                            [
+                                                "fuzzel",
+                                            ],
+                                        ),
+                                    },
+                                ),
+                                bottom_left: Some(
+                                    HotCorner {
+                                        action: ToggleOverview,
+                                    },
+                                ),
+                                bottom_right: Some(
+                                    HotCorner {
+                                        action: PowerOffMonitors,
+                                    },
+                                ),
+                                delay_ms: Some(
+                                    150,
+                                ),
+                                disable_while_fullscreen: Some(
+                                    Flag(
+                                        false,
+                                    ),
+                                ),
                             },
                         ),
                         layout: None,
@@ -1196,6 +1420,7 @@ mod tests {
                         ),
                         modeline: None,
                         variable_refresh_rate: None,
+                        hdr: None,
                         focus_at_startup: false,
                         background_color: None,
                         backdrop_color: None,
@@ -1225,6 +1450,7 @@ mod tests {
                             },
                         ),
                         variable_refresh_rate: None,
+                        hdr: None,
                         focus_at_startup: false,
                         background_color: None,
                         backdrop_color: None,
@@ -1233,6 +1459,63 @@ mod tests {
                     },
                 ],
             ),
+            output_profiles: OutputProfiles(
+                [
+                    OutputProfile {
+                        name: "docked",
+                        when_connected: "DP-3",
+                        outputs: [
+                            Output {
+                                off: true,
+                                name: "eDP-1",
+                                scale: None,
+                                transform: Normal,
+                                position: None,
+                                mode: None,
+                                modeline: None,
+                                variable_refresh_rate: None,
+                                hdr: None,
+                                focus_at_startup: false,
+                                background_color: None,
+                                backdrop_color: None,
+                                hot_corners: None,
+                                layout: None,
+                            },
+                            Output {
+                                off: false,
+                                name: "DP-3",
+                                scale: Some(
+                                    FloatOrInt(
+                                        1.5,
+                                    ),
+                                ),
+                                transform: Normal,
+                                position: Some(
+                                    Position {
+                                        x: 0,
+                                        y: 0,
+                                    },
+                                ),
+                                mode: None,
+                                modeline: None,
+                                variable_refresh_rate: None,
+                                hdr: None,
+                                focus_at_startup: false,
+                                background_color: None,
+                                backdrop_color: None,
+                                hot_corners: None,
+                                layout: None,
+                            },
+                        ],
+                        workspaces: [
+                            OutputProfileWorkspace {
+                                name: "chat",
+                                open_on_output: "DP-3",
+                            },
+                        ],
+                    },
+                ],
+            ),
             spawn_at_startup: [
                 SpawnAtStartup {
                     command: [
@@ -1372,6 +1655,8 @@ mod tests {
                     hide_when_single_tab: false,
                     hide_titles: false,
                     title_font_size: 12,
+                    title_debounce_ms: 250,
+                    title_fallback: AppId,
                     gap: 5.0,
                     width: 10.0,
                     length: TabIndicatorLength {
@@ -1494,6 +1779,11 @@ mod tests {
             ),
             clipboard: Clipboard {
                 disable_primary: true,
+                history: ClipboardHistory {
+                    on: true,
+                    max_entries: 20,
+                    max_entry_size_kb: 5120,
+                },
             },
             hotkey_overlay: HotkeyOverlay {
                 skip_at_startup: true,
@@ -1505,8 +1795,8 @@ mod tests {
             animations: Animations {
                 off: false,
                 slowdown: 2.0,
-                workspace_switch: WorkspaceSwitchAnim(
-                    Animation {
+                workspace_switch: WorkspaceSwitchAnim {
+                    anim: Animation {
                         off: false,
                         kind: Spring(
                             SpringParams {
@@ -1516,7 +1806,8 @@ mod tests {
                             },
                         ),
                     },
-                ),
+                    custom_shader: None,
+                },
                 window_open: WindowOpenAnim {
                     anim: Animation {
                         off: true,
@@ -1641,6 +1932,28 @@ mod tests {
                         ),
                     },
                 ),
+                layer_open: LayerOpenAnim(
+                    Animation {
+                        off: false,
+                        kind: Easing(
+                            EasingParams {
+                                duration_ms: 150,
+                                curve: EaseOutExpo,
+                            },
+                        ),
+                    },
+                ),
+                layer_close: LayerCloseAnim(
+                    Animation {
+                        off: false,
+                        kind: Easing(
+                            EasingParams {
+                                duration_ms: 150,
+                                curve: EaseOutQuad,
+                            },
+                        ),
+                    },
+                ),
             },
             gestures: Gestures {
                 dnd_edge_view_scroll: DndEdgeViewScroll {
@@ -1655,10 +1968,29 @@ mod tests {
                 },
                 hot_corners: HotCorners {
                     off: false,
-                    top_left: false,
-                    top_right: false,
-                    bottom_left: false,
-                    bottom_right: false,
+                    top_left: None,
+                    top_right: None,
+                    bottom_left: None,
+                    bottom_right: None,
+                    delay_ms: None,
+                    disable_while_fullscreen: None,
+                },
+                pinch_column_width: PinchColumnWidth {
+                    on: false,
+                    fingers: 3,
+                    snap_distance: 30.0,
+                },
+                touch_edge_swipe: TouchEdgeSwipe {
+                    on: true,
+                    trigger_size: 20.0,
+                    top: Overview,
+                    bottom: Overview,
+                    left: None,
+                    right: WorkspaceSwitch,
+                },
+                touch_long_press_move: TouchLongPressMove {
+                    on: false,
+                    delay_ms: 500,
                 },
             },
             overview: Overview {
@@ -1719,6 +2051,7 @@ mod tests {
                                 ),
                             ),
                             title: None,
+                            tag: None,
                             is_active: None,
                             is_focused: None,
                             is_active_in_column: None,
@@ -1726,6 +2059,7 @@ mod tests {
                             is_window_cast_target: None,
                             is_urgent: None,
                             at_startup: None,
+                            at: None,
                         },
                     ],
                     excludes: [
@@ -1738,6 +2072,7 @@ mod tests {
                                     ),
                                 ),
                             ),
+                            tag: None,
                             is_active: None,
                             is_focused: None,
                             is_active_in_column: None,
@@ -1745,10 +2080,12 @@ mod tests {
                             is_window_cast_target: None,
                             is_urgent: None,
                             at_startup: None,
+                            at: None,
                         },
                         Match {
                             app_id: None,
                             title: None,
+                            tag: None,
                             is_active: Some(
                                 true,
                             ),
@@ -1760,6 +2097,7 @@ mod tests {
                             is_window_cast_target: None,
                             is_urgent: None,
                             at_startup: None,
+                            at: None,
                         },
                     ],
                     default_column_width: None,
@@ -1773,7 +2111,10 @@ mod tests {
                         ),
                     ),
                     open_on_output: Some(
-                        "eDP-1",
+                        [
+                            "eDP-1",
+                            "HDMI-A-1",
+                        ],
                     ),
                     open_on_workspace: None,
                     open_maximized: Some(
@@ -1789,10 +2130,12 @@ mod tests {
                     open_focused: Some(
                         true,
                     ),
+                    reapply_rules_on_reload: None,
                     min_width: None,
                     min_height: None,
                     max_width: None,
                     max_height: None,
+                    force_aspect_ratio: None,
                     focus_ring: BorderRule {
                         off: true,
                         on: false,
@@ -1882,6 +2225,14 @@ mod tests {
                     ),
                     scroll_factor: None,
                     tiled_state: None,
+                    keep_rendering: None,
+                    inhibit_idle: None,
+                    allow_tearing: None,
+                    dim_parent: None,
+                    keyboard_shortcuts_inhibit: None,
+                    force_pointer_constraint: Some(
+                        Confine,
+                    ),
                 },
             ],
             layer_rules: [
@@ -1896,6 +2247,12 @@ mod tests {
                                 ),
                             ),
                             at_startup: None,
+                            at: Some(
+                                TimeRange {
+                                    start: 1320,
+                                    end: 360,
+                                },
+                            ),
                         },
                     ],
                     excludes: [],
@@ -1930,6 +2287,8 @@ mod tests {
                     geometry_corner_radius: None,
                     place_within_backdrop: None,
                     baba_is_float: None,
+                    open_close_animation: None,
+                    on_screen_keyboard: None,
                 },
             ],
             binds: Binds(
@@ -1945,7 +2304,10 @@ mod tests {
                         },
                         action: ToggleKeyboardShortcutsInhibit,
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: false,
@@ -1967,7 +2329,10 @@ mod tests {
                         },
                         action: ToggleKeyboardShortcutsInhibit,
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: false,
@@ -1989,7 +2354,10 @@ mod tests {
                             ],
                         ),
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: true,
                         allow_inhibiting: true,
@@ -2007,7 +2375,10 @@ mod tests {
                         },
                         action: CloseWindow,
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: true,
@@ -2027,7 +2398,10 @@ mod tests {
                         },
                         action: FocusMonitorLeft,
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: true,
@@ -2047,7 +2421,10 @@ mod tests {
                             "eDP-1",
                         ),
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: true,
@@ -2065,7 +2442,10 @@ mod tests {
                         },
                         action: MoveWindowToMonitorRight,
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: true,
@@ -2085,7 +2465,10 @@ mod tests {
                             "eDP-1",
                         ),
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: true,
@@ -2105,7 +2488,10 @@ mod tests {
                             "DP-1",
                         ),
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: true,
@@ -2123,7 +2509,14 @@ mod tests {
                         },
                         action: ConsumeWindowIntoColumn,
                         repeat: true,
+                        repeat_delay: Some(
+                            150,
+                        ),
+                        repeat_rate: Some(
+                            40,
+                        ),
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: true,
@@ -2145,7 +2538,10 @@ mod tests {
                             ),
                         ),
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: true,
@@ -2167,7 +2563,10 @@ mod tests {
                             ),
                         ),
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: true,
@@ -2187,7 +2586,10 @@ mod tests {
                             true,
                         ),
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: false,
@@ -2203,7 +2605,10 @@ mod tests {
                         },
                         action: FocusWorkspaceDown,
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: Some(
                             150ms,
                         ),
@@ -2225,7 +2630,10 @@ mod tests {
                             "pkill orca || exec orca",
                         ),
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: true,
                         allow_inhibiting: true,
@@ -2241,7 +2649,10 @@ mod tests {
                         },
                         action: ToggleOverview,
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: true,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: true,
@@ -2257,7 +2668,10 @@ mod tests {
                         },
                         action: ToggleWindowFloating,
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: true,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: true,
@@ -2266,6 +2680,61 @@ mod tests {
                     },
                 ],
             ),
+            bind_modes: [
+                BindMode {
+                    name: "resize",
+                    binds: Binds(
+                        [
+                            Bind {
+                                key: Key {
+                                    trigger: Keysym(
+                                        XK_Left,
+                                    ),
+                                    modifiers: Modifiers(
+                                        0x0,
+                                    ),
+                                },
+                                action: SetColumnWidth(
+                                    AdjustProportion(
+                                        -10.0,
+                                    ),
+                                ),
+                                repeat: true,
+                                repeat_delay: None,
+                                repeat_rate: None,
+                                release: false,
+                                taps: 1,
+                                cooldown: None,
+                                allow_when_locked: false,
+                                allow_inhibiting: true,
+                                allow_invalidation: true,
+                                hotkey_overlay_title: None,
+                            },
+                            Bind {
+                                key: Key {
+                                    trigger: Keysym(
+                                        XK_Escape,
+                                    ),
+                                    modifiers: Modifiers(
+                                        0x0,
+                                    ),
+                                },
+                                action: ExitBindMode,
+                                repeat: true,
+                                repeat_delay: None,
+                                repeat_rate: None,
+                                release: false,
+                                taps: 1,
+                                cooldown: None,
+                                allow_when_locked: false,
+                                allow_inhibiting: true,
+                                allow_invalidation: true,
+                                hotkey_overlay_title: None,
+                            },
+                        ],
+                    ),
+                },
+            ],
             switch_events: SwitchBinds {
                 lid_open: None,
                 lid_close: None,
@@ -2288,6 +2757,35 @@ mod tests {
                     },
                 ),
             },
+            tablet_pad_binds: TabletPadBinds(
+                [
+                    TabletPadBind {
+                        trigger: Button(
+                            0,
+                        ),
+                        mode: None,
+                        action: FocusWorkspaceDown,
+                    },
+                    TabletPadBind {
+                        trigger: RingClockwise(
+                            0,
+                        ),
+                        mode: Some(
+                            1,
+                        ),
+                        action: EmulateKey(
+                            Key {
+                                trigger: Keysym(
+                                    XK_plus,
+                                ),
+                                modifiers: Modifiers(
+                                    CTRL,
+                                ),
+                            },
+                        ),
+                    },
+                ],
+            ),
             debug: Debug {
                 preview_render: None,
                 dbus_interfaces_in_non_session_instances: false,
@@ -2315,6 +2813,8 @@ mod tests {
                 honor_xdg_activation_with_invalid_serial: false,
                 deactivate_unfocused_windows: false,
                 skip_cursor_only_updates_during_vrr: false,
+                allow_ipc_input_injection: false,
+                emit_frame_stats_events: false,
             },
             workspaces: [
                 Workspace {
@@ -2325,6 +2825,17 @@ mod tests {
                         "eDP-1",
                     ),
                     layout: None,
+                    icon: Some(
+                        "📫",
+                    ),
+                    color: Some(
+                        Color {
+                            r: 1.0,
+                            g: 0.0,
+                            b: 0.0,
+                            a: 1.0,
+                        },
+                    ),
                 },
                 Workspace {
                     name: WorkspaceName(
@@ -2332,6 +2843,8 @@ mod tests {
                     ),
                     open_on_output: None,
                     layout: None,
+                    icon: None,
+                    color: None,
                 },
                 Workspace {
                     name: WorkspaceName(
@@ -2339,6 +2852,8 @@ mod tests {
                     ),
                     open_on_output: None,
                     layout: None,
+                    icon: None,
+                    color: None,
                 },
             ],
             recent_windows: RecentWindows {
@@ -2383,7 +2898,10 @@ mod tests {
                             ),
                         },
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: true,
@@ -2407,7 +2925,10 @@ mod tests {
                             ),
                         },
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: true,
@@ -2433,7 +2954,10 @@ mod tests {
                             ),
                         },
                         repeat: true,
+                        repeat_delay: None,
+                        repeat_rate: None,
                         release: false,
+                        taps: 1,
                         cooldown: None,
                         allow_when_locked: false,
                         allow_inhibiting: true,
@@ -2442,6 +2966,70 @@ mod tests {
                     },
                 ],
             },
+            session: Session {
+                restore_layout: true,
+            },
+            session_lock: SessionLock {
+                backdrop_blur: Blur {
+                    on: true,
+                    passes: 2,
+                    radius: FloatOrInt(
+                        8.0,
+                    ),
+                    noise: FloatOrInt(
+                        0.0,
+                    ),
+                    fps: FloatOrInt(
+                        0.0,
+                    ),
+                    true_blur_fps: FloatOrInt(
+                        6.666_666_5,
+                    ),
+                    optimized_blur_fps: FloatOrInt(
+                        0.0,
+                    ),
+                    animation_blur_fps: FloatOrInt(
+                        60.0,
+                    ),
+                    optimized: true,
+                    brightness: FloatOrInt(
+                        1.0,
+                    ),
+                    contrast: FloatOrInt(
+                        1.0,
+                    ),
+                    saturation: FloatOrInt(
+                        1.0,
+                    ),
+                    ignore_alpha: FloatOrInt(
+                        0.0,
+                    ),
+                    x_ray: false,
+                },
+            },
+            window_parking: WindowParking {
+                inactive_after_minutes: Some(
+                    240,
+                ),
+                workspace: Some(
+                    "parking",
+                ),
+            },
+            security_context: SecurityContext(
+                [
+                    SecurityContextPolicy {
+                        sandbox_engine: Some(
+                            "flatpak",
+                        ),
+                        app_id: Some(
+                            "com.obsproject.Studio",
+                        ),
+                        screencopy: true,
+                        data_control: false,
+                        foreign_toplevel: false,
+                    },
+                ],
+            ),
         }
         "#);
     }
@@ -1,4 +1,6 @@
-use crate::appearance::{Color, WorkspaceShadow, WorkspaceShadowPart, DEFAULT_BACKDROP_COLOR};
+use crate::appearance::{
+    Blur, BlurRule, Color, WorkspaceShadow, WorkspaceShadowPart, DEFAULT_BACKDROP_COLOR,
+};
 use crate::utils::{Flag, MergeWith};
 use crate::FloatOrInt;
 
@@ -14,7 +16,7 @@ pub struct SpawnShAtStartup {
     pub command: String,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Cursor {
     pub xcursor_theme: String,
     pub xcursor_size: u8,
@@ -104,17 +106,62 @@ impl MergeWith<ConfigNotificationPart> for ConfigNotification {
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct Clipboard {
     pub disable_primary: bool,
+    pub history: ClipboardHistory,
 }
 
 #[derive(knuffel::Decode, Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct ClipboardPart {
     #[knuffel(child)]
     pub disable_primary: Option<Flag>,
+    #[knuffel(child)]
+    pub history: Option<ClipboardHistoryPart>,
 }
 
 impl MergeWith<ClipboardPart> for Clipboard {
     fn merge_with(&mut self, part: &ClipboardPart) {
-        merge!((self, part), disable_primary);
+        merge!((self, part), disable_primary, history);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClipboardHistory {
+    pub on: bool,
+    /// Number of selections to keep.
+    pub max_entries: u32,
+    /// Selections larger than this are not stored.
+    pub max_entry_size_kb: u32,
+}
+
+impl Default for ClipboardHistory {
+    fn default() -> Self {
+        Self {
+            on: false,
+            max_entries: 50,
+            max_entry_size_kb: 5 * 1024,
+        }
+    }
+}
+
+#[derive(knuffel::Decode, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ClipboardHistoryPart {
+    #[knuffel(child)]
+    pub off: bool,
+    #[knuffel(child)]
+    pub on: bool,
+    #[knuffel(child, unwrap(argument))]
+    pub max_entries: Option<u32>,
+    #[knuffel(child, unwrap(argument))]
+    pub max_entry_size_kb: Option<u32>,
+}
+
+impl MergeWith<ClipboardHistoryPart> for ClipboardHistory {
+    fn merge_with(&mut self, part: &ClipboardHistoryPart) {
+        self.on |= part.on;
+        if part.off {
+            self.on = false;
+        }
+
+        merge_clone!((self, part), max_entries, max_entry_size_kb);
     }
 }
 
@@ -163,6 +210,42 @@ pub struct EnvironmentVariable {
     pub value: Option<String>,
 }
 
+#[derive(knuffel::Decode, Debug, Default, Clone, PartialEq, Eq)]
+pub struct SecurityContext(#[knuffel(children(name = "allow"))] pub Vec<SecurityContextPolicy>);
+
+/// Privileged protocols granted to sandboxed clients matching the security context metadata.
+#[derive(knuffel::Decode, Debug, Default, Clone, PartialEq, Eq)]
+pub struct SecurityContextPolicy {
+    #[knuffel(property)]
+    pub sandbox_engine: Option<String>,
+    #[knuffel(property)]
+    pub app_id: Option<String>,
+    #[knuffel(child)]
+    pub screencopy: bool,
+    #[knuffel(child)]
+    pub data_control: bool,
+    #[knuffel(child)]
+    pub foreign_toplevel: bool,
+}
+
+impl SecurityContextPolicy {
+    pub fn matches(&self, sandbox_engine: Option<&str>, app_id: Option<&str>) -> bool {
+        if let Some(expected) = &self.sandbox_engine {
+            if sandbox_engine != Some(expected.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(expected) = &self.app_id {
+            if app_id != Some(expected.as_str()) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct XwaylandSatellite {
     pub off: bool,
@@ -198,3 +281,70 @@ impl MergeWith<XwaylandSatellitePart> for XwaylandSatellite {
         merge_clone!((self, part), path);
     }
 }
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Session {
+    pub restore_layout: bool,
+}
+
+#[derive(knuffel::Decode, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SessionPart {
+    #[knuffel(child)]
+    pub restore_layout: Option<Flag>,
+}
+
+impl MergeWith<SessionPart> for Session {
+    fn merge_with(&mut self, part: &SessionPart) {
+        merge!((self, part), restore_layout);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionLock {
+    /// Blur applied to the last frame before locking, shown behind the lock surface.
+    pub backdrop_blur: Blur,
+}
+
+impl Default for SessionLock {
+    fn default() -> Self {
+        Self {
+            backdrop_blur: Blur {
+                passes: 3,
+                radius: FloatOrInt(8.),
+                ..Blur::default()
+            },
+        }
+    }
+}
+
+#[derive(knuffel::Decode, Debug, Default, Clone, Copy, PartialEq)]
+pub struct SessionLockPart {
+    #[knuffel(child)]
+    pub backdrop_blur: Option<BlurRule>,
+}
+
+impl MergeWith<SessionLockPart> for SessionLock {
+    fn merge_with(&mut self, part: &SessionLockPart) {
+        merge!((self, part), backdrop_blur);
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct WindowParking {
+    pub inactive_after_minutes: Option<u32>,
+    pub workspace: Option<String>,
+}
+
+#[derive(knuffel::Decode, Debug, Default, Clone, PartialEq, Eq)]
+pub struct WindowParkingPart {
+    #[knuffel(child, unwrap(argument))]
+    pub inactive_after_minutes: Option<u32>,
+    #[knuffel(child, unwrap(argument))]
+    pub workspace: Option<String>,
+}
+
+impl MergeWith<WindowParkingPart> for WindowParking {
+    fn merge_with(&mut self, part: &WindowParkingPart) {
+        merge_clone_opt!((self, part), inactive_after_minutes, workspace);
+    }
+}
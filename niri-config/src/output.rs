@@ -66,6 +66,8 @@ pub struct Output {
     #[knuffel(child)]
     pub variable_refresh_rate: Option<Vrr>,
     #[knuffel(child)]
+    pub hdr: Option<Hdr>,
+    #[knuffel(child)]
     pub focus_at_startup: bool,
     // Deprecated; use layout.background_color.
     #[knuffel(child)]
@@ -104,6 +106,7 @@ impl Default for Output {
             mode: None,
             modeline: None,
             variable_refresh_rate: None,
+            hdr: None,
             background_color: None,
             backdrop_color: None,
             hot_corners: None,
@@ -134,6 +137,69 @@ pub struct Vrr {
     pub on_demand: bool,
 }
 
+/// Static HDR metadata of the display, sent along with the HDR signal.
+///
+/// Unset values are sent as unknown, letting the display pick its own defaults.
+#[derive(knuffel::Decode, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Hdr {
+    /// Peak luminance of the display in cd/m².
+    #[knuffel(property)]
+    pub max_luminance: Option<u16>,
+    /// Minimum luminance of the display in cd/m².
+    #[knuffel(property)]
+    pub min_luminance: Option<FloatOrInt<0, 6>>,
+    /// Maximum content light level in cd/m².
+    #[knuffel(property)]
+    pub max_cll: Option<u16>,
+    /// Maximum frame-average light level in cd/m².
+    #[knuffel(property)]
+    pub max_fall: Option<u16>,
+}
+
+#[derive(knuffel::Decode, Debug, Default, Clone, PartialEq)]
+pub struct OutputProfiles(#[knuffel(children(name = "profile"))] pub Vec<OutputProfile>);
+
+/// Output layout applied as a whole while a specific output is connected.
+#[derive(knuffel::Decode, Debug, Clone, PartialEq)]
+pub struct OutputProfile {
+    #[knuffel(argument)]
+    pub name: String,
+    #[knuffel(property)]
+    pub when_connected: String,
+    #[knuffel(children(name = "output"))]
+    pub outputs: Vec<Output>,
+    #[knuffel(children(name = "workspace"))]
+    pub workspaces: Vec<OutputProfileWorkspace>,
+}
+
+#[derive(knuffel::Decode, Debug, Clone, PartialEq)]
+pub struct OutputProfileWorkspace {
+    #[knuffel(argument)]
+    pub name: String,
+    #[knuffel(child, unwrap(argument))]
+    pub open_on_output: String,
+}
+
+impl OutputProfiles {
+    /// Returns the first profile whose output is connected.
+    pub fn find_active(&self, connected: &[OutputName]) -> Option<&OutputProfile> {
+        self.0.iter().find(|profile| {
+            connected
+                .iter()
+                .any(|name| name.matches(&profile.when_connected))
+        })
+    }
+}
+
+impl OutputProfile {
+    /// Returns the output config with this profile applied on top of `base`.
+    ///
+    /// Outputs from the profile take priority, and outputs it doesn't mention keep their config.
+    pub fn apply_to(&self, base: &Outputs) -> Outputs {
+        self.outputs.iter().chain(&base.0).cloned().collect()
+    }
+}
+
 impl FromIterator<Output> for Outputs {
     fn from_iter<T: IntoIterator<Item = Output>>(iter: T) -> Self {
         Self(Vec::from_iter(iter))
@@ -257,6 +323,101 @@ impl OutputName {
     }
 }
 
+/// Formats the current state of the outputs as config `output` sections.
+///
+/// This is used to save changes made at runtime, for example with `niri msg output`, so they can
+/// be included into the config. The current output `config` provides the settings that the IPC
+/// outputs don't carry, like on-demand VRR.
+pub fn format_outputs_kdl<'a>(
+    outputs: impl IntoIterator<Item = &'a niri_ipc::Output>,
+    config: &Outputs,
+) -> String {
+    let mut outputs: Vec<_> = outputs
+        .into_iter()
+        .map(|output| (OutputName::from_ipc_output(output), output))
+        .collect();
+    outputs.sort_by(|(a, _), (b, _)| a.compare(b));
+
+    let mut kdl = String::new();
+    for (name, output) in outputs {
+        if !kdl.is_empty() {
+            kdl.push('\n');
+        }
+
+        let vrr = match config.find(&name) {
+            Some(config) => config.variable_refresh_rate.clone(),
+            None => output.vrr_enabled.then(Vrr::default),
+        };
+
+        let name = name.format_make_model_serial_or_connector();
+        kdl.push_str(&format!("output {} {{\n", kdl_string(&name)));
+
+        let Some(logical) = &output.logical else {
+            kdl.push_str("    off\n}\n");
+            continue;
+        };
+
+        if let Some(mode) = output.current_mode.and_then(|idx| output.modes.get(idx)) {
+            let refresh = f64::from(mode.refresh_rate) / 1000.;
+            let custom = if output.is_custom_mode {
+                " custom=true"
+            } else {
+                ""
+            };
+            kdl.push_str(&format!(
+                "    mode{custom} \"{}x{}@{refresh:.3}\"\n",
+                mode.width, mode.height
+            ));
+        }
+        kdl.push_str(&format!("    scale {}\n", logical.scale));
+
+        let transform = match logical.transform {
+            Transform::Normal => "normal",
+            Transform::_90 => "90",
+            Transform::_180 => "180",
+            Transform::_270 => "270",
+            Transform::Flipped => "flipped",
+            Transform::Flipped90 => "flipped-90",
+            Transform::Flipped180 => "flipped-180",
+            Transform::Flipped270 => "flipped-270",
+        };
+        kdl.push_str(&format!("    transform \"{transform}\"\n"));
+        kdl.push_str(&format!("    position x={} y={}\n", logical.x, logical.y));
+
+        match vrr {
+            Some(Vrr { on_demand: true }) => {
+                kdl.push_str("    variable-refresh-rate on-demand=true\n");
+            }
+            Some(Vrr { on_demand: false }) => kdl.push_str("    variable-refresh-rate\n"),
+            None => (),
+        }
+
+        kdl.push_str("}\n");
+    }
+
+    kdl
+}
+
+/// Formats a KDL string literal.
+fn kdl_string(s: &str) -> String {
+    let mut rv = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => rv.push_str("\\\""),
+            '\\' => rv.push_str("\\\\"),
+            '\n' => rv.push_str("\\n"),
+            '\r' => rv.push_str("\\r"),
+            '\t' => rv.push_str("\\t"),
+            '\u{8}' => rv.push_str("\\b"),
+            '\u{c}' => rv.push_str("\\f"),
+            c if c.is_control() => rv.push_str(&format!("\\u{{{:x}}}", u32::from(c))),
+            c => rv.push(c),
+        }
+    }
+    rv.push('"');
+    rv
+}
+
 impl<S: ErrorSpan> knuffel::Decode<S> for Mode {
     fn decode_node(node: &SpannedNode<S>, ctx: &mut Context<S>) -> Result<Self, DecodeError<S>> {
         if let Some(type_name) = &node.type_name {
@@ -495,7 +656,7 @@ impl<S: ErrorSpan> Decode<S> for Modeline {
 
 #[cfg(test)]
 mod tests {
-    use insta::assert_debug_snapshot;
+    use insta::{assert_debug_snapshot, assert_snapshot};
 
     use super::*;
 
@@ -525,6 +686,115 @@ mod tests {
         assert!("1920x1080@60Hz".parse::<ConfiguredMode>().is_err());
     }
 
+    #[test]
+    fn format_outputs() {
+        let mode = niri_ipc::Mode {
+            width: 2560,
+            height: 1600,
+            refresh_rate: 165004,
+            is_preferred: true,
+        };
+        let outputs = [
+            niri_ipc::Output {
+                name: "HDMI-A-1".to_string(),
+                make: "Unknown".to_string(),
+                model: "Unknown".to_string(),
+                serial: None,
+                physical_size: None,
+                modes: vec![mode],
+                current_mode: None,
+                is_custom_mode: false,
+                vrr_supported: false,
+                vrr_enabled: false,
+                logical: None,
+            },
+            niri_ipc::Output {
+                name: "eDP-1".to_string(),
+                make: "Some Company".to_string(),
+                model: "Some Monitor".to_string(),
+                serial: Some("1234".to_string()),
+                physical_size: None,
+                modes: vec![mode],
+                current_mode: Some(0),
+                is_custom_mode: false,
+                vrr_supported: true,
+                vrr_enabled: true,
+                logical: Some(niri_ipc::LogicalOutput {
+                    x: 1280,
+                    y: -200,
+                    width: 1707,
+                    height: 1067,
+                    scale: 1.5,
+                    transform: Transform::_90,
+                }),
+            },
+            niri_ipc::Output {
+                name: "DP-2".to_string(),
+                make: "Foo".to_string(),
+                model: "Bar \"Pro\" \\ 1".to_string(),
+                serial: None,
+                physical_size: None,
+                modes: vec![mode],
+                current_mode: None,
+                is_custom_mode: false,
+                vrr_supported: true,
+                vrr_enabled: false,
+                logical: Some(niri_ipc::LogicalOutput {
+                    x: 0,
+                    y: 0,
+                    width: 2560,
+                    height: 1600,
+                    scale: 1.,
+                    transform: Transform::Normal,
+                }),
+            },
+        ];
+
+        let config = crate::Config::parse_mem(
+            r#"
+            output "DP-2" {
+                variable-refresh-rate on-demand=true
+            }
+            "#,
+        )
+        .unwrap();
+
+        let kdl = format_outputs_kdl(&outputs, &config.outputs);
+        assert_snapshot!(kdl, @r#"
+        output "Foo Bar \"Pro\" \\ 1 Unknown" {
+            scale 1
+            transform "normal"
+            position x=0 y=0
+            variable-refresh-rate on-demand=true
+        }
+
+        output "Some Company Some Monitor 1234" {
+            mode "2560x1600@165.004"
+            scale 1.5
+            transform "90"
+            position x=1280 y=-200
+            variable-refresh-rate
+        }
+
+        output "HDMI-A-1" {
+            off
+        }
+        "#);
+
+        // The result must be a valid config.
+        let config = crate::Config::parse_mem(&kdl).unwrap();
+        let output = &config.outputs.0[0];
+        assert_eq!(output.name, "Foo Bar \"Pro\" \\ 1 Unknown");
+        assert_eq!(output.mode, None);
+        assert!(!output.off);
+        assert!(output.is_vrr_on_demand());
+        let output = &config.outputs.0[1];
+        assert_eq!(output.scale, Some(FloatOrInt(1.5)));
+        assert_eq!(output.position, Some(Position { x: 1280, y: -200 }));
+        assert!(output.is_vrr_always_on());
+        assert!(config.outputs.0[2].off);
+    }
+
     fn make_output_name(
         connector: &str,
         make: Option<&str>,
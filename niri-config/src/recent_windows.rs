@@ -6,7 +6,7 @@ use smithay::input::keyboard::Keysym;
 use crate::utils::{expect_only_children, MergeWith};
 use crate::{Action, Bind, Color, FloatOrInt, Key, Modifiers, Trigger};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RecentWindows {
     pub on: bool,
     pub debounce_ms: u16,
@@ -67,7 +67,7 @@ impl MergeWith<RecentWindowsPart> for RecentWindows {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MruHighlight {
     pub active_color: Color,
     pub urgent_color: Color,
@@ -150,7 +150,10 @@ impl From<MruBind> for Bind {
             key: x.key,
             action: Action::from(x.action),
             repeat: true,
+            repeat_delay: None,
+            repeat_rate: None,
             release: false,
+            taps: 1,
             cooldown: None,
             allow_when_locked: false,
             allow_inhibiting: x.allow_inhibiting,
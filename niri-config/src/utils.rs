@@ -43,6 +43,65 @@ impl FromStr for RegexEq {
     }
 }
 
+/// Range of local time of day, like `09:00-17:00`.
+///
+/// The start is inclusive and the end is exclusive. A range where the end comes before the start,
+/// like `22:00-06:00`, wraps around midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+    /// Start, in minutes since midnight.
+    pub start: u16,
+    /// End, in minutes since midnight.
+    pub end: u16,
+}
+
+impl TimeRange {
+    /// Returns whether the range contains the given time, in minutes since midnight.
+    pub fn contains(&self, minute: u16) -> bool {
+        if self.start <= self.end {
+            self.start <= minute && minute < self.end
+        } else {
+            self.start <= minute || minute < self.end
+        }
+    }
+}
+
+impl FromStr for TimeRange {
+    type Err = miette::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn parse_time(s: &str) -> Result<u16, miette::Error> {
+            let (hours, minutes) = s
+                .trim()
+                .split_once(':')
+                .ok_or_else(|| miette!("time must be in the HH:MM format"))?;
+            let hours: u16 = hours.parse().map_err(|_| miette!("error parsing hours"))?;
+            let minutes: u16 = minutes
+                .parse()
+                .map_err(|_| miette!("error parsing minutes"))?;
+
+            // Allow 24:00 to be able to express ranges until the end of the day.
+            if minutes >= 60 || hours > 24 || (hours == 24 && minutes != 0) {
+                return Err(miette!("time must be between 00:00 and 24:00"));
+            }
+
+            Ok(hours * 60 + minutes)
+        }
+
+        let Some((start, end)) = s.split_once('-') else {
+            return Err(miette!("time range must be in the HH:MM-HH:MM format"));
+        };
+
+        let start = parse_time(start)?;
+        let end = parse_time(end)?;
+        if start == end {
+            return Err(miette!("time range must not be empty"));
+        }
+
+        Ok(Self { start, end })
+    }
+}
+
 impl FromStr for Percent {
     type Err = miette::Error;
 
@@ -1,6 +1,10 @@
+use std::str::FromStr;
+
+use miette::miette;
+
 use crate::appearance::{BlockOutFrom, BorderRule, CornerRadius, ShadowRule, TabIndicatorRule};
 use crate::layout::DefaultPresetSize;
-use crate::utils::RegexEq;
+use crate::utils::{RegexEq, TimeRange};
 use crate::{BlurRule, FloatOrInt};
 
 #[derive(knuffel::Decode, Debug, Default, Clone, PartialEq)]
@@ -15,10 +19,10 @@ pub struct WindowRule {
     pub default_column_width: Option<DefaultPresetSize>,
     #[knuffel(child)]
     pub default_window_height: Option<DefaultPresetSize>,
-    #[knuffel(child, unwrap(argument))]
-    pub open_on_output: Option<String>,
-    #[knuffel(child, unwrap(argument))]
-    pub open_on_workspace: Option<String>,
+    #[knuffel(child, unwrap(arguments))]
+    pub open_on_output: Option<Vec<String>>,
+    #[knuffel(child, unwrap(arguments))]
+    pub open_on_workspace: Option<Vec<String>>,
     #[knuffel(child, unwrap(argument))]
     pub open_maximized: Option<bool>,
     #[knuffel(child, unwrap(argument))]
@@ -29,6 +33,8 @@ pub struct WindowRule {
     pub open_floating: Option<bool>,
     #[knuffel(child, unwrap(argument))]
     pub open_focused: Option<bool>,
+    #[knuffel(child, unwrap(argument))]
+    pub reapply_rules_on_reload: Option<bool>,
 
     // Rules applied dynamically.
     #[knuffel(child, unwrap(argument))]
@@ -39,6 +45,8 @@ pub struct WindowRule {
     pub max_width: Option<u16>,
     #[knuffel(child, unwrap(argument))]
     pub max_height: Option<u16>,
+    #[knuffel(child, unwrap(argument, str))]
+    pub force_aspect_ratio: Option<AspectRatio>,
 
     #[knuffel(child, default)]
     pub focus_ring: BorderRule,
@@ -70,6 +78,18 @@ pub struct WindowRule {
     pub scroll_factor: Option<FloatOrInt<0, 100>>,
     #[knuffel(child, unwrap(argument))]
     pub tiled_state: Option<bool>,
+    #[knuffel(child, unwrap(argument))]
+    pub keep_rendering: Option<bool>,
+    #[knuffel(child, unwrap(argument))]
+    pub inhibit_idle: Option<bool>,
+    #[knuffel(child, unwrap(argument))]
+    pub allow_tearing: Option<bool>,
+    #[knuffel(child, unwrap(argument))]
+    pub dim_parent: Option<bool>,
+    #[knuffel(child, unwrap(argument))]
+    pub keyboard_shortcuts_inhibit: Option<KeyboardShortcutsInhibit>,
+    #[knuffel(child, unwrap(argument))]
+    pub force_pointer_constraint: Option<ForcePointerConstraint>,
 }
 
 #[derive(knuffel::Decode, Debug, Default, Clone, PartialEq)]
@@ -78,6 +98,8 @@ pub struct Match {
     pub app_id: Option<RegexEq>,
     #[knuffel(property, str)]
     pub title: Option<RegexEq>,
+    #[knuffel(property, str)]
+    pub tag: Option<RegexEq>,
     #[knuffel(property)]
     pub is_active: Option<bool>,
     #[knuffel(property)]
@@ -92,6 +114,46 @@ pub struct Match {
     pub is_urgent: Option<bool>,
     #[knuffel(property)]
     pub at_startup: Option<bool>,
+    #[knuffel(property, str)]
+    pub at: Option<TimeRange>,
+}
+
+/// Aspect ratio, like `16:9`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AspectRatio {
+    pub width: u16,
+    pub height: u16,
+}
+
+impl AspectRatio {
+    /// Returns the width divided by the height.
+    pub fn ratio(self) -> f64 {
+        f64::from(self.width) / f64::from(self.height)
+    }
+}
+
+impl FromStr for AspectRatio {
+    type Err = miette::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((width, height)) = s.split_once(':') else {
+            return Err(miette!("aspect ratio must be in the W:H format, like 16:9"));
+        };
+
+        let width: u16 = width
+            .trim()
+            .parse()
+            .map_err(|_| miette!("error parsing width"))?;
+        let height: u16 = height
+            .trim()
+            .parse()
+            .map_err(|_| miette!("error parsing height"))?;
+        if width == 0 || height == 0 {
+            return Err(miette!("aspect ratio parts must be greater than 0"));
+        }
+
+        Ok(Self { width, height })
+    }
 }
 
 #[derive(knuffel::Decode, Debug, Clone, Copy, PartialEq)]
@@ -117,3 +179,66 @@ pub enum RelativeTo {
     Right,
     Cursor,
 }
+
+#[derive(knuffel::DecodeScalar, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum KeyboardShortcutsInhibit {
+    /// Let the window inhibit shortcuts right away.
+    Allow,
+    /// Never let the window inhibit shortcuts.
+    Deny,
+    /// Wait for the user to allow it with the toggle-keyboard-shortcuts-inhibit bind.
+    #[default]
+    Ask,
+}
+
+#[derive(knuffel::DecodeScalar, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForcePointerConstraint {
+    /// Keep the pointer inside the window.
+    Confine,
+    /// Keep the pointer in place, sending only relative motion to the window.
+    Lock,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_aspect_ratio() {
+        let ratio: AspectRatio = "16:9".parse().unwrap();
+        assert_eq!(
+            ratio,
+            AspectRatio {
+                width: 16,
+                height: 9
+            }
+        );
+
+        let ratio: AspectRatio = " 4 : 3 ".parse().unwrap();
+        assert_eq!(ratio.ratio(), 4. / 3.);
+
+        assert!("16".parse::<AspectRatio>().is_err());
+        assert!("16:".parse::<AspectRatio>().is_err());
+        assert!("a:9".parse::<AspectRatio>().is_err());
+        assert!("16:0".parse::<AspectRatio>().is_err());
+        assert!("0:9".parse::<AspectRatio>().is_err());
+        assert!("-16:9".parse::<AspectRatio>().is_err());
+    }
+
+    #[test]
+    fn parse_force_aspect_ratio_rule() {
+        let rule: WindowRule = knuffel::parse("test.kdl", r#"force-aspect-ratio "21:9""#)
+            .map_err(miette::Report::new)
+            .unwrap();
+        assert_eq!(
+            rule.force_aspect_ratio,
+            Some(AspectRatio {
+                width: 21,
+                height: 9
+            })
+        );
+
+        let result = knuffel::parse::<WindowRule>("test.kdl", r#"force-aspect-ratio "21""#);
+        assert!(result.is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use knuffel::errors::DecodeError;
 
-use crate::LayoutPart;
+use crate::{Color, LayoutPart};
 
 #[derive(knuffel::Decode, Debug, Clone, PartialEq)]
 pub struct Workspace {
@@ -10,6 +10,10 @@ pub struct Workspace {
     pub open_on_output: Option<String>,
     #[knuffel(child)]
     pub layout: Option<WorkspaceLayoutPart>,
+    #[knuffel(child, unwrap(argument))]
+    pub icon: Option<String>,
+    #[knuffel(child)]
+    pub color: Option<Color>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -0,0 +1,379 @@
+//! Async client for the niri event stream.
+//!
+//! [`EventStreamClient`] connects to the niri socket, requests an event stream and keeps an
+//! [`EventStreamState`] up to date. When the connection drops, for example because niri was
+//! restarted, the client reconnects on the next read. Niri sends the full current state up-front on
+//! every new event stream, so the state is reset on reconnection and rebuilt from those events.
+//!
+//! The client is built on [async-io](https://lib.rs/crates/async-io), and can be driven by any
+//! executor.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use niri_ipc::client::{EventHandler, EventStreamClient};
+//! use niri_ipc::state::EventStreamState;
+//!
+//! struct Bar;
+//!
+//! impl EventHandler for Bar {
+//!     fn workspaces_changed(&mut self, state: &EventStreamState) {
+//!         let count = state.workspaces.workspaces.len();
+//!         println!("{count} workspaces");
+//!     }
+//! }
+//!
+//! fn main() -> std::io::Result<()> {
+//!     let mut client = EventStreamClient::new()?;
+//!     futures_lite::future::block_on(client.run(&mut Bar));
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::env;
+use std::io;
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use async_io::{Async, Timer};
+use futures_lite::io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader};
+
+use crate::socket::SOCKET_PATH_ENV;
+use crate::state::{EventStreamState, EventStreamStatePart as _};
+use crate::{Event, FrameStats, Reply, Request, Response};
+
+/// Default delay between reconnection attempts.
+pub const DEFAULT_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Async event stream client that reconnects when the connection is lost.
+#[derive(Debug)]
+pub struct EventStreamClient {
+    /// Path to the niri socket.
+    path: PathBuf,
+    /// Delay before reconnecting after an error.
+    reconnect_delay: Duration,
+    /// Current connection, if any.
+    stream: Option<BufReader<Async<UnixStream>>>,
+    /// Whether the last connection attempt or read had failed.
+    failed: bool,
+    /// State built from the events so far.
+    state: EventStreamState,
+    /// Buffer for reading lines.
+    buf: String,
+}
+
+/// Typed callbacks for the event stream.
+///
+/// All methods have empty default implementations, so you only need to implement the ones you
+/// care about. Every callback gets the state with the event already applied.
+pub trait EventHandler {
+    /// Called for every event, before the more specific callbacks.
+    fn event(&mut self, event: &Event, state: &EventStreamState) {
+        let _ = (event, state);
+    }
+
+    /// Called right before the first event of a new connection.
+    ///
+    /// The state was reset, and will be rebuilt from the following events.
+    fn connected(&mut self) {}
+
+    /// Called when connecting, reading or parsing an event fails.
+    ///
+    /// If the connection was lost, the client will wait for the reconnect delay and reconnect.
+    fn error(&mut self, error: &io::Error) {
+        let _ = error;
+    }
+
+    /// Called when the workspaces state changes.
+    fn workspaces_changed(&mut self, state: &EventStreamState) {
+        let _ = state;
+    }
+
+    /// Called when the windows state changes.
+    fn windows_changed(&mut self, state: &EventStreamState) {
+        let _ = state;
+    }
+
+    /// Called when the layer surfaces state changes.
+    fn layers_changed(&mut self, state: &EventStreamState) {
+        let _ = state;
+    }
+
+    /// Called when the keyboard layouts or the active layout change.
+    fn keyboard_layouts_changed(&mut self, state: &EventStreamState) {
+        let _ = state;
+    }
+
+    /// Called when the overview is opened or closed.
+    fn overview_changed(&mut self, state: &EventStreamState) {
+        let _ = state;
+    }
+
+    /// Called when a bind mode is entered or exited.
+    fn bind_mode_changed(&mut self, state: &EventStreamState) {
+        let _ = state;
+    }
+
+    /// Called when the config is loaded.
+    fn config_loaded(&mut self, state: &EventStreamState) {
+        let _ = state;
+    }
+
+    /// Called when a screenshot is captured.
+    fn screenshot_captured(&mut self, path: Option<&str>) {
+        let _ = path;
+    }
+
+    /// Called when inactive windows are moved to the parking workspace.
+    fn windows_parked(&mut self, ids: &[u64]) {
+        let _ = ids;
+    }
+
+    /// Called when a key grabbed with [`Request::GrabKey`] is pressed.
+    fn key_grab_triggered(&mut self, key: &str) {
+        let _ = key;
+    }
+
+    /// Called when a different output profile becomes active.
+    fn output_profile_activated(&mut self, state: &EventStreamState) {
+        let _ = state;
+    }
+
+    /// Called when periodic frame timing statistics arrive.
+    fn frame_stats_updated(&mut self, stats: &HashMap<String, FrameStats>) {
+        let _ = stats;
+    }
+}
+
+impl EventStreamClient {
+    /// Creates a client for the default niri IPC socket.
+    ///
+    /// The socket path is taken from the [`SOCKET_PATH_ENV`] environment variable. This does not
+    /// connect yet; the connection is made on the first read.
+    pub fn new() -> io::Result<Self> {
+        let socket_path = env::var_os(SOCKET_PATH_ENV).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{SOCKET_PATH_ENV} is not set, are you running this within niri?"),
+            )
+        })?;
+        Ok(Self::with_path(socket_path))
+    }
+
+    /// Creates a client for the niri IPC socket at the given path.
+    pub fn with_path(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            reconnect_delay: DEFAULT_RECONNECT_DELAY,
+            stream: None,
+            failed: false,
+            state: EventStreamState::default(),
+            buf: String::new(),
+        }
+    }
+
+    /// Sets the delay before reconnecting after an error.
+    pub fn reconnect_delay(mut self, delay: Duration) -> Self {
+        self.reconnect_delay = delay;
+        self
+    }
+
+    /// Returns the state built from the events so far.
+    pub fn state(&self) -> &EventStreamState {
+        &self.state
+    }
+
+    /// Returns whether the client is currently connected.
+    pub fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// Reads the next event and applies it to the state.
+    ///
+    /// Connects first if needed, waiting for the reconnect delay if the previous attempt had
+    /// failed. Returns an error when connecting or reading fails; calling this again will
+    /// reconnect.
+    ///
+    /// An event that fails to parse, for example one added in a newer niri version, is returned as
+    /// an [`io::ErrorKind::InvalidData`] error without dropping the connection.
+    pub async fn next_event(&mut self) -> io::Result<Event> {
+        if self.stream.is_none() {
+            if self.failed {
+                Timer::after(self.reconnect_delay).await;
+            }
+
+            let stream = match connect(&self.path).await {
+                Ok(stream) => stream,
+                Err(err) => {
+                    self.failed = true;
+                    return Err(err);
+                }
+            };
+
+            self.failed = false;
+            self.state = EventStreamState::default();
+            self.stream = Some(stream);
+        }
+        let stream = self.stream.as_mut().unwrap();
+
+        self.buf.clear();
+        match stream.read_line(&mut self.buf).await {
+            Ok(0) => {
+                self.stream = None;
+                self.failed = true;
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "niri closed the event stream",
+                ));
+            }
+            Ok(_) => (),
+            Err(err) => {
+                self.stream = None;
+                self.failed = true;
+                return Err(err);
+            }
+        }
+
+        let event: Event = serde_json::from_str(&self.buf)?;
+        self.state.apply(event.clone());
+        Ok(event)
+    }
+
+    /// Reads events forever, calling the handler for each of them.
+    ///
+    /// Errors are reported to [`EventHandler::error`], and the client keeps going, reconnecting if
+    /// the connection was lost.
+    pub async fn run(&mut self, handler: &mut impl EventHandler) {
+        loop {
+            let was_connected = self.is_connected();
+            match self.next_event().await {
+                Ok(event) => {
+                    if !was_connected {
+                        handler.connected();
+                    }
+                    dispatch(handler, &event, &self.state);
+                }
+                Err(err) => handler.error(&err),
+            }
+        }
+    }
+}
+
+async fn connect(path: &Path) -> io::Result<BufReader<Async<UnixStream>>> {
+    let mut stream = Async::<UnixStream>::connect(path).await?;
+
+    let mut buf = serde_json::to_string(&Request::EventStream).unwrap();
+    buf.push('\n');
+    stream.write_all(buf.as_bytes()).await?;
+    let _ = stream.get_ref().shutdown(Shutdown::Write);
+
+    let mut stream = BufReader::new(stream);
+    buf.clear();
+    stream.read_line(&mut buf).await?;
+
+    let reply: Reply = serde_json::from_str(&buf)?;
+    match reply {
+        Ok(Response::Handled) => Ok(stream),
+        Ok(response) => Err(io::Error::other(format!(
+            "unexpected response: expected Handled, got {response:?}"
+        ))),
+        Err(message) => Err(io::Error::other(message)),
+    }
+}
+
+fn dispatch(handler: &mut impl EventHandler, event: &Event, state: &EventStreamState) {
+    handler.event(event, state);
+
+    match event {
+        Event::WorkspacesChanged { .. }
+        | Event::WorkspaceUrgencyChanged { .. }
+        | Event::WorkspaceActivated { .. }
+        | Event::WorkspaceActiveWindowChanged { .. } => handler.workspaces_changed(state),
+        Event::WindowsChanged { .. }
+        | Event::WindowOpenedOrChanged { .. }
+        | Event::WindowClosed { .. }
+        | Event::WindowFocusChanged { .. }
+        | Event::WindowFocusTimestampChanged { .. }
+        | Event::WindowUrgencyChanged { .. }
+        | Event::WindowLayoutsChanged { .. } => handler.windows_changed(state),
+        Event::LayersChanged { .. }
+        | Event::LayerMappedOrChanged { .. }
+        | Event::LayerUnmapped { .. }
+        | Event::LayerExclusiveZoneChanged { .. } => handler.layers_changed(state),
+        Event::KeyboardLayoutsChanged { .. } | Event::KeyboardLayoutSwitched { .. } => {
+            handler.keyboard_layouts_changed(state)
+        }
+        Event::OverviewOpenedOrClosed { .. } => handler.overview_changed(state),
+        Event::ConfigLoaded { .. } => handler.config_loaded(state),
+        Event::BindModeChanged { .. } => handler.bind_mode_changed(state),
+        Event::ScreenshotCaptured { path } => handler.screenshot_captured(path.as_deref()),
+        Event::WindowsParked { ids } => handler.windows_parked(ids),
+        Event::KeyGrabTriggered { key } => handler.key_grab_triggered(key),
+        Event::OutputProfileActivated { .. } => handler.output_profile_activated(state),
+        Event::FrameStatsUpdated { stats } => handler.frame_stats_updated(stats),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+
+    use futures_lite::future::block_on;
+
+    use super::*;
+
+    #[test]
+    fn reconnects_and_resets_state() {
+        let path =
+            env::temp_dir().join(format!("niri-ipc-client-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = thread::spawn(move || {
+            for is_open in [true, false] {
+                let (stream, _) = listener.accept().unwrap();
+                let mut stream = BufReader::new(stream);
+
+                let mut line = String::new();
+                stream.read_line(&mut line).unwrap();
+                let request: Request = serde_json::from_str(&line).unwrap();
+                assert!(matches!(request, Request::EventStream));
+
+                let reply: Reply = Ok(Response::Handled);
+                let event = Event::OverviewOpenedOrClosed { is_open };
+                let stream = stream.get_mut();
+                writeln!(stream, "{}", serde_json::to_string(&reply).unwrap()).unwrap();
+                writeln!(stream, "{}", serde_json::to_string(&event).unwrap()).unwrap();
+            }
+        });
+
+        let mut client = EventStreamClient::with_path(&path).reconnect_delay(Duration::ZERO);
+        block_on(async {
+            let event = client.next_event().await.unwrap();
+            assert!(matches!(
+                event,
+                Event::OverviewOpenedOrClosed { is_open: true }
+            ));
+            assert!(client.state().overview.is_open);
+
+            let err = client.next_event().await.unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+            assert!(!client.is_connected());
+
+            let event = client.next_event().await.unwrap();
+            assert!(matches!(
+                event,
+                Event::OverviewOpenedOrClosed { is_open: false }
+            ));
+            assert!(!client.state().overview.is_open);
+        });
+
+        server.join().unwrap();
+        let _ = std::fs::remove_file(&path);
+    }
+}
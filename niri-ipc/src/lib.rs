@@ -19,9 +19,10 @@
 //!
 //! </div>
 //!
-//! You can use the [`socket::Socket`] helper if you're fine with blocking communication. However,
-//! it is a fairly simple helper, so if you need async, or if you're using a different language,
-//! you are encouraged to communicate with the socket manually.
+//! You can use the [`socket::Socket`] helper if you're fine with blocking communication. For the
+//! event stream, the `client::EventStreamClient` behind the `async-io` feature reads events
+//! asynchronously and reconnects when niri restarts. These are fairly simple helpers, so if you're
+//! using a different language, you are encouraged to communicate with the socket manually.
 //!
 //! 1. Read the socket filesystem path from [`socket::SOCKET_PATH_ENV`] (`$NIRI_SOCKET`).
 //! 2. Connect to the socket and write a JSON-formatted [`Request`] on a single line. You can follow
@@ -48,8 +49,10 @@
 //!
 //! This crate defines the following features:
 //! - `json-schema`: derives the [schemars](https://lib.rs/crates/schemars) `JsonSchema` trait for
-//!   the types.
+//!   the types, and enables [`IpcSchema::generate()`].
 //! - `clap`: derives the clap CLI parsing traits for some types. Used internally by niri itself.
+//! - `async-io`: enables the `client` module with an async event stream client built on
+//!   [async-io](https://lib.rs/crates/async-io).
 #![warn(missing_docs)]
 
 use std::collections::HashMap;
@@ -58,6 +61,8 @@ use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "async-io")]
+pub mod client;
 pub mod socket;
 pub mod state;
 
@@ -87,6 +92,12 @@ pub enum Request {
     PickColor,
     /// Perform an action.
     Action(Action),
+    /// Perform several actions in order.
+    ///
+    /// All actions run within the same compositor event loop iteration, so no frames are drawn
+    /// and no windows are configured in-between them. The whole batch is rejected if any of the
+    /// actions is invalid.
+    Batch(Vec<Action>),
     /// Change output configuration temporarily.
     ///
     /// The configuration is changed temporarily and not saved into the config file. If the output
@@ -98,6 +109,15 @@ pub enum Request {
         /// Configuration to apply.
         action: OutputAction,
     },
+    /// Save the current output configuration into a file.
+    ///
+    /// The file contains an `output` section for every connected output with its current mode,
+    /// scale, transform, position and VRR state, including any changes made with
+    /// [`Request::Output`]. Include it into the config to keep these settings across restarts.
+    SaveOutputConfig {
+        /// Absolute path to write the config to.
+        path: String,
+    },
     /// Start continuously receiving events from the compositor.
     ///
     /// The compositor should reply with `Reply::Ok(Response::Handled)`, then continuously send
@@ -117,6 +137,160 @@ pub enum Request {
     ReturnError,
     /// Request information about the overview.
     OverviewState,
+    /// Request the view position and the column geometry of a workspace's scrolling layout.
+    ///
+    /// Intended for overlays like minimaps that mirror the strip of columns.
+    ScrollingView {
+        /// Id of the workspace.
+        ///
+        /// If `None`, uses the focused workspace.
+        workspace_id: Option<u64>,
+    },
+    /// Request the effective blur settings.
+    ///
+    /// Without an output or a window, returns the global blur settings.
+    Blur {
+        /// Output name to get the settings for, including its output-specific overrides.
+        output: Option<String>,
+        /// Id of the window to get the settings for, including its window rules.
+        window: Option<u64>,
+    },
+    /// Change blur settings temporarily.
+    ///
+    /// Without an output or a window, changes the global blur settings.
+    ///
+    /// The settings are changed temporarily and not saved into the config file. Global and output
+    /// changes will be forgotten when the config file is reloaded. Window changes remain until the
+    /// window is closed.
+    SetBlur {
+        /// Output name to change the settings for.
+        output: Option<String>,
+        /// Id of the window to change the settings for.
+        window: Option<u64>,
+        /// Settings to change.
+        change: BlurChange,
+    },
+    /// Request which decorations are drawn for a window.
+    WindowDecorations {
+        /// Id of the window.
+        id: u64,
+    },
+    /// Toggle the decorations of a window at runtime.
+    ///
+    /// The changes apply on top of the window rules, and remain until the window is closed.
+    SetWindowDecorations {
+        /// Id of the window.
+        id: u64,
+        /// Decorations to change.
+        change: WindowDecorationsChange,
+    },
+    /// Request a downscaled thumbnail of a window.
+    ///
+    /// Intended for window switchers and docks showing live previews. Repeated requests for the
+    /// same window and size within a short interval return the same thumbnail.
+    WindowThumbnail {
+        /// Id of the window.
+        id: u64,
+        /// Maximum width and height of the thumbnail in pixels.
+        ///
+        /// The thumbnail keeps the window's aspect ratio, and is never larger than the window
+        /// itself.
+        max_size: u32,
+    },
+    /// Request the pointer position and the windows under the pointer and focused.
+    Pointer,
+    /// Request the entries of the built-in clipboard history.
+    ///
+    /// Use [`Action::SelectClipboardHistoryEntry`] to put one of them back into the clipboard.
+    ClipboardHistory,
+    /// Inject an input event as if it came from a real device.
+    ///
+    /// The event goes through the normal input handling, so it can trigger binds and change
+    /// focus. Intended for testing and automation. Requires `allow-ipc-input-injection` in the
+    /// `debug` config section, otherwise the request fails.
+    InjectInput(InjectedInput),
+    /// Grab a key combination, delivering its presses over the event stream.
+    ///
+    /// Niri consumes the presses of the grabbed key instead of sending them to the focused window,
+    /// and emits [`Event::KeyGrabTriggered`]. Binds from the config take precedence over grabs.
+    /// Grabs stay registered until [`Request::UngrabKey`] or until the client that made them
+    /// disconnects, so keep the connection open, for example by requesting an
+    /// [`Request::EventStream`] on it afterwards.
+    GrabKey {
+        /// Key combination written like in the config binds, for example `Mod+Shift+Space`.
+        key: String,
+    },
+    /// Remove a key grab previously registered with [`Request::GrabKey`].
+    UngrabKey {
+        /// Key combination to ungrab.
+        key: String,
+    },
+    /// Apply a config file temporarily, reverting it unless confirmed.
+    ///
+    /// The config from the file replaces the current config right away. Unless
+    /// [`Request::ConfirmConfig`] arrives within the timeout, niri reloads the regular config file,
+    /// undoing the changes.
+    TryConfig {
+        /// Absolute path to the config file to try.
+        path: String,
+        /// Seconds to wait for the confirmation before reverting.
+        timeout_secs: u64,
+    },
+    /// Keep the config applied with [`Request::TryConfig`].
+    ///
+    /// The config stays active until the regular config file is reloaded.
+    ConfirmConfig,
+    /// Request frame timing statistics for every output.
+    FrameStats,
+    /// Reload only some sections of the config file.
+    ///
+    /// The rest of the config stays as it is, even if it changed in the file.
+    ReloadConfig {
+        /// Sections to reload.
+        ///
+        /// When empty, the whole config is reloaded.
+        sections: Vec<ConfigSection>,
+    },
+    /// Request Wayland protocol usage statistics for every client.
+    ProtocolStats,
+    /// Request a machine-readable description of the IPC of the running niri.
+    ///
+    /// Describes all requests, actions, responses and events with their parameters as JSON
+    /// schemas, which can be used to generate or validate bindings in other languages.
+    Schema,
+    /// Create a compositor-rendered overlay widget, or replace the widget with the same id.
+    ///
+    /// Widgets stay until they are removed with [`Request::RemoveWidget`] or niri exits; they
+    /// don't go away when the IPC connection closes.
+    SetWidget(Widget),
+    /// Remove an overlay widget.
+    RemoveWidget {
+        /// Id of the widget to remove.
+        id: String,
+    },
+    /// Request the overlay widgets.
+    Widgets,
+    /// Add a window rule that stays until niri exits.
+    ///
+    /// The rule applies on top of the window rules from the config file, even after the config is
+    /// reloaded. Niri replies with [`Response::WindowRuleAdded`].
+    AddWindowRule {
+        /// Contents of a `window-rule {}` block in the config syntax, with matches and properties.
+        rule: String,
+    },
+    /// Remove a window rule previously added with [`Request::AddWindowRule`].
+    RemoveWindowRule {
+        /// Id of the window rule to remove.
+        id: u64,
+    },
+    /// Request the window rules added with [`Request::AddWindowRule`].
+    WindowRules,
+    /// Request a new xdg-activation token.
+    ///
+    /// Pass the token to an app that you spawn in the `XDG_ACTIVATION_TOKEN` environment variable
+    /// to let it take focus when it opens, just like apps spawned by niri itself. The token expires
+    /// after a few seconds.
+    ActivationToken,
 }
 
 /// Reply from niri to client.
@@ -161,6 +335,114 @@ pub enum Response {
     OutputConfigChanged(OutputConfigChanged),
     /// Information about the overview.
     OverviewState(Overview),
+    /// Scrolling layout view of a workspace.
+    ScrollingView(ScrollingView),
+    /// Effective blur settings.
+    Blur(Blur),
+    /// Decorations drawn for a window.
+    WindowDecorations(WindowDecorations),
+    /// Window thumbnail.
+    WindowThumbnail(WindowThumbnail),
+    /// Information about the pointer.
+    Pointer(Pointer),
+    /// Clipboard history entries, most recent first.
+    ClipboardHistory(Vec<ClipboardHistoryEntry>),
+    /// Frame timing statistics.
+    ///
+    /// Map from output name to its statistics.
+    FrameStats(HashMap<String, FrameStats>),
+    /// Wayland protocol usage statistics for every client.
+    ProtocolStats(Vec<ClientProtocolStats>),
+    /// Description of the IPC.
+    Schema(IpcSchema),
+    /// Overlay widgets, in the order they were created.
+    Widgets(Vec<Widget>),
+    /// Id of the window rule that was added.
+    WindowRuleAdded(u64),
+    /// Window rules added over IPC, in the order they were added.
+    WindowRules(Vec<RuntimeWindowRule>),
+    /// A new xdg-activation token.
+    ActivationToken(String),
+    /// Path where a screenshot was saved.
+    ///
+    /// Screenshot actions that write to disk reply with this once the file has been written.
+    ScreenshotSaved(String),
+}
+
+/// Machine-readable description of the IPC.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct IpcSchema {
+    /// Version of niri that generated the schemas.
+    pub version: String,
+    /// JSON schema of [`Request`].
+    ///
+    /// Includes the schema of [`Action`].
+    pub request: serde_json::Value,
+    /// JSON schema of [`Reply`].
+    pub reply: serde_json::Value,
+    /// JSON schema of [`Event`].
+    pub event: serde_json::Value,
+}
+
+#[cfg(feature = "json-schema")]
+impl IpcSchema {
+    /// Generates the schemas of the types in this crate.
+    pub fn generate(version: String) -> Self {
+        Self {
+            version,
+            request: schemars::schema_for!(Request).into(),
+            reply: schemars::schema_for!(Reply).into(),
+            event: schemars::schema_for!(Event).into(),
+        }
+    }
+}
+
+/// Input event to inject.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "clap", derive(clap::Parser))]
+#[cfg_attr(feature = "clap", command(subcommand_value_name = "EVENT"))]
+#[cfg_attr(feature = "clap", command(subcommand_help_heading = "Events"))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum InjectedInput {
+    /// Press or release a key.
+    ///
+    /// Keys stay pressed until released, so make sure to send a release for every press.
+    Key {
+        /// Evdev key code, for example 30 for `KEY_A`.
+        #[cfg_attr(feature = "clap", arg())]
+        keycode: u32,
+        /// Whether the key is pressed or released.
+        #[cfg_attr(feature = "clap", arg(short, long, action = clap::ArgAction::Set, default_value_t = true))]
+        pressed: bool,
+    },
+    /// Move the pointer by a relative amount in logical pixels.
+    PointerMotion {
+        /// Horizontal delta.
+        #[cfg_attr(feature = "clap", arg(allow_negative_numbers = true))]
+        dx: f64,
+        /// Vertical delta.
+        #[cfg_attr(feature = "clap", arg(allow_negative_numbers = true))]
+        dy: f64,
+    },
+    /// Move the pointer to a position in the global logical coordinate space.
+    PointerMotionAbsolute {
+        /// X position.
+        #[cfg_attr(feature = "clap", arg(allow_negative_numbers = true))]
+        x: f64,
+        /// Y position.
+        #[cfg_attr(feature = "clap", arg(allow_negative_numbers = true))]
+        y: f64,
+    },
+    /// Press or release a pointer button.
+    PointerButton {
+        /// Evdev button code, for example 272 for `BTN_LEFT`.
+        #[cfg_attr(feature = "clap", arg())]
+        button: u32,
+        /// Whether the button is pressed or released.
+        #[cfg_attr(feature = "clap", arg(short, long, action = clap::ArgAction::Set, default_value_t = true))]
+        pressed: bool,
+    },
 }
 
 /// Overview information.
@@ -171,6 +453,300 @@ pub struct Overview {
     pub is_open: bool,
 }
 
+/// View position and columns of a workspace's scrolling layout.
+///
+/// All positions are in logical pixels along the strip of columns, where the first column starts
+/// at 0. A column is displayed at `x - view_pos` from the left edge of the workspace.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ScrollingView {
+    /// Id of the workspace.
+    pub workspace_id: u64,
+    /// Current X position of the view.
+    ///
+    /// Changes continuously during view animations and gestures.
+    pub view_pos: f64,
+    /// X position that the view is moving to.
+    ///
+    /// Equal to `view_pos` when the view is not moving.
+    pub target_view_pos: f64,
+    /// Width of the view.
+    pub view_width: f64,
+    /// Index of the active column, if any.
+    pub active_column_idx: Option<usize>,
+    /// Columns, from left to right.
+    pub columns: Vec<ColumnGeometry>,
+}
+
+/// Position and width of a column in the scrolling layout.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ColumnGeometry {
+    /// X position of the column's left edge.
+    pub x: f64,
+    /// Width of the column.
+    pub width: f64,
+    /// Ids of the windows in the column, from top to bottom.
+    pub window_ids: Vec<u64>,
+}
+
+/// Pointer position and what is under it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct Pointer {
+    /// X position in the global logical coordinate space.
+    pub x: f64,
+    /// Y position in the global logical coordinate space.
+    pub y: f64,
+    /// Name of the output under the pointer, if any.
+    pub output: Option<String>,
+    /// Position relative to the top-left corner of the output under the pointer.
+    pub pos_in_output: Option<(f64, f64)>,
+    /// Id of the window under the pointer, if any.
+    pub window_id: Option<u64>,
+    /// Id of the focused window, if any.
+    ///
+    /// Can be `None` when a layer-shell surface has the keyboard focus.
+    pub focused_window_id: Option<u64>,
+}
+
+/// Frame timing statistics of an output.
+///
+/// Cover the last couple of seconds of rendered frames.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct FrameStats {
+    /// Number of frames the statistics cover.
+    pub frames: u32,
+    /// Average CPU time spent rendering a frame, in microseconds.
+    pub render_time_avg_us: u64,
+    /// Longest CPU time spent rendering a frame, in microseconds.
+    pub render_time_max_us: u64,
+    /// Average time from the end of rendering to the frame presentation, in microseconds.
+    ///
+    /// Includes the GPU rendering and waiting for the vblank. `None` if the backend does not
+    /// report presentation times.
+    pub presentation_latency_avg_us: Option<u64>,
+    /// Number of frames presented at least half a refresh cycle later than targeted.
+    pub missed_frames: u32,
+    /// Current refresh rate in millihertz, if known.
+    pub refresh_rate: Option<u32>,
+    /// Whether variable refresh rate is active.
+    pub vrr: bool,
+}
+
+/// Wayland protocol usage statistics of a client.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ClientProtocolStats {
+    /// Process ID of the client, if known.
+    pub pid: Option<i32>,
+    /// Process name of the client, if known.
+    pub name: Option<String>,
+    /// Number of live protocol objects of each interface, including the bound globals.
+    pub objects: HashMap<String, u32>,
+    /// Total number of surface commits.
+    pub commits: u64,
+    /// Number of surface commits during the last second.
+    pub commit_rate: u32,
+    /// Whether the client commits at an unreasonably high rate.
+    pub excessive_commit_rate: bool,
+    /// Logical sizes of the client's surfaces that are unreasonably large.
+    pub oversized_surfaces: Vec<(i32, i32)>,
+}
+
+/// Overlay widget rendered by niri on top of the windows.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct Widget {
+    /// Id of the widget, chosen by the client.
+    pub id: String,
+    /// Name of the output to show the widget on.
+    ///
+    /// If `None`, the widget is shown on all outputs.
+    pub output: Option<String>,
+    /// Point of the output that the widget is anchored to.
+    pub anchor: WidgetAnchor,
+    /// Logical offset of the widget from its anchor, as `(x, y)`.
+    ///
+    /// Positive values move the widget towards the center for the edge anchors.
+    pub offset: (f64, f64),
+    /// Text shown in the widget.
+    pub text: Option<String>,
+    /// Value of the progress bar, from 0 to 1.
+    ///
+    /// If `None`, there's no progress bar.
+    pub progress: Option<f64>,
+    /// Path to a PNG image shown to the left of the text.
+    pub icon: Option<String>,
+}
+
+/// Point of the output that an overlay widget is anchored to.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum WidgetAnchor {
+    /// Top-left corner.
+    TopLeft,
+    /// Middle of the top edge.
+    #[default]
+    Top,
+    /// Top-right corner.
+    TopRight,
+    /// Middle of the left edge.
+    Left,
+    /// Center of the output.
+    Center,
+    /// Middle of the right edge.
+    Right,
+    /// Bottom-left corner.
+    BottomLeft,
+    /// Middle of the bottom edge.
+    Bottom,
+    /// Bottom-right corner.
+    BottomRight,
+}
+
+/// Downscaled image of a window.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct WindowThumbnail {
+    /// Width of the image in pixels.
+    pub width: u32,
+    /// Height of the image in pixels.
+    pub height: u32,
+    /// The image encoded as PNG.
+    pub png: Vec<u8>,
+}
+
+/// Blur settings.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct Blur {
+    /// Whether the blur is enabled.
+    pub on: bool,
+    /// Number of blur passes.
+    pub passes: u32,
+    /// Blur radius.
+    pub radius: f64,
+    /// Amount of noise added to the blur.
+    pub noise: f64,
+    /// Brightness of the blurred background.
+    pub brightness: f64,
+    /// Contrast of the blurred background.
+    pub contrast: f64,
+    /// Saturation of the blurred background.
+    pub saturation: f64,
+}
+
+/// Blur settings to change.
+///
+/// Settings left unset keep their current values.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "clap", derive(clap::Args))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct BlurChange {
+    /// Whether to enable the blur.
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            long,
+            value_name = "ON|OFF",
+            action = clap::ArgAction::Set,
+            value_parser = clap::builder::BoolishValueParser::new(),
+            hide_possible_values = true,
+        ),
+    )]
+    pub on: Option<bool>,
+    /// Number of blur passes.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub passes: Option<u32>,
+    /// Blur radius.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub radius: Option<f64>,
+}
+
+/// Entry of the built-in clipboard history.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ClipboardHistoryEntry {
+    /// Id of the entry.
+    pub id: u64,
+    /// MIME type the entry was stored as.
+    pub mime_type: String,
+    /// Size of the entry in bytes.
+    pub size: u64,
+    /// Contents of the entry, if it is text.
+    pub text: Option<String>,
+}
+
+/// Window rule added over IPC.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct RuntimeWindowRule {
+    /// Id of the window rule.
+    pub id: u64,
+    /// Contents of the `window-rule {}` block, as it was added.
+    pub rule: String,
+}
+
+/// Decorations drawn for a window.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct WindowDecorations {
+    /// Whether the border is drawn.
+    pub border: bool,
+    /// Whether the shadow is drawn.
+    pub shadow: bool,
+    /// Whether the background behind the window is blurred.
+    pub blur: bool,
+}
+
+/// Window decorations to change.
+///
+/// Decorations left unset keep their current state.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::Args))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct WindowDecorationsChange {
+    /// Whether to draw the border.
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            long,
+            value_name = "ON|OFF",
+            action = clap::ArgAction::Set,
+            value_parser = clap::builder::BoolishValueParser::new(),
+            hide_possible_values = true,
+        ),
+    )]
+    pub border: Option<bool>,
+    /// Whether to draw the shadow.
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            long,
+            value_name = "ON|OFF",
+            action = clap::ArgAction::Set,
+            value_parser = clap::builder::BoolishValueParser::new(),
+            hide_possible_values = true,
+        ),
+    )]
+    pub shadow: Option<bool>,
+    /// Whether to blur the background behind the window.
+    #[cfg_attr(
+        feature = "clap",
+        arg(
+            long,
+            value_name = "ON|OFF",
+            action = clap::ArgAction::Set,
+            value_parser = clap::builder::BoolishValueParser::new(),
+            hide_possible_values = true,
+        ),
+    )]
+    pub blur: Option<bool>,
+}
+
 /// Color picked from the screen.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
@@ -198,6 +774,21 @@ pub enum Action {
     PowerOffMonitors {},
     /// Power on all monitors via DPMS.
     PowerOnMonitors {},
+    /// Power off a single monitor via DPMS.
+    ///
+    /// The monitor stays off until it's powered on with [`Action::PowerOnMonitor`], even when
+    /// input activity wakes up the other monitors.
+    PowerOffMonitor {
+        /// Name of the output to power off.
+        #[cfg_attr(feature = "clap", arg())]
+        output: String,
+    },
+    /// Power on a single monitor previously powered off with [`Action::PowerOffMonitor`].
+    PowerOnMonitor {
+        /// Name of the output to power on.
+        #[cfg_attr(feature = "clap", arg())]
+        output: String,
+    },
     /// Spawn a command.
     Spawn {
         /// Command to spawn.
@@ -247,6 +838,10 @@ pub enum Action {
         /// The path must be absolute, otherwise an error is returned.
         ///
         /// If `None`, the screenshot is saved according to the `screenshot-path` config setting.
+        ///
+        /// When writing to disk, niri replies with [`Response::ScreenshotSaved`] once the
+        /// screenshot is saved, and `niri msg` prints its path. Pass `-` to `niri msg` to write
+        /// the PNG image to stdout instead.
         #[cfg_attr(feature = "clap", arg(long, action = clap::ArgAction::Set))]
         path: Option<String>,
     },
@@ -271,16 +866,33 @@ pub enum Action {
         #[cfg_attr(feature = "clap", arg(short = 'p', long, action = clap::ArgAction::Set, default_value_t = false))]
         show_pointer: bool,
 
+        /// Whether to include the window border in the screenshot.
+        #[cfg_attr(feature = "clap", arg(long))]
+        include_border: bool,
+
+        /// Whether to include the window shadow in the screenshot.
+        #[cfg_attr(feature = "clap", arg(long))]
+        include_shadow: bool,
+
         /// Path to save the screenshot to.
         ///
         /// The path must be absolute, otherwise an error is returned.
         ///
         /// If `None`, the screenshot is saved according to the `screenshot-path` config setting.
+        ///
+        /// When writing to disk, niri replies with [`Response::ScreenshotSaved`] once the
+        /// screenshot is saved, and `niri msg` prints its path. Pass `-` to `niri msg` to write
+        /// the PNG image to stdout instead.
         #[cfg_attr(feature = "clap", arg(long, action = clap::ArgAction::Set))]
         path: Option<String>,
     },
     /// Enable or disable the keyboard shortcuts inhibitor (if any) for the focused surface.
     ToggleKeyboardShortcutsInhibit {},
+    /// Release or re-apply the forced pointer constraint of the focused window.
+    ///
+    /// The forced pointer constraint comes from the `force-pointer-constraint` window rule. It is
+    /// applied again when the focus changes.
+    ToggleForcedPointerConstraint {},
     /// Close a window.
     #[cfg_attr(feature = "clap", clap(about = "Close the focused window"))]
     CloseWindow {
@@ -753,6 +1365,11 @@ pub enum Action {
     },
     /// Expand the focused column to space not taken up by other fully visible columns.
     ExpandColumnToAvailableWidth {},
+    /// Resize the focused column to the width its windows ask for.
+    ///
+    /// This is the largest minimum width among the windows in the column, or the current width
+    /// for windows that don't set one.
+    FitColumnWidthToContent {},
     /// Switch between keyboard layouts.
     SwitchLayout {
         /// Layout to switch to.
@@ -795,6 +1412,8 @@ pub enum Action {
     DebugToggleOpaqueRegions {},
     /// Toggle visualization of output damage.
     DebugToggleDamage {},
+    /// Toggle highlighting of pixels with out-of-range premultiplied alpha.
+    DebugTogglePremultipliedAlpha {},
     /// Move the focused window between the floating and the tiling layout.
     ToggleWindowFloating {
         /// Id of the window to move.
@@ -847,6 +1466,11 @@ pub enum Action {
             arg(short, long, default_value = "+0", allow_hyphen_values = true)
         )]
         y: PositionChange,
+
+        /// Whether to animate the movement.
+        #[cfg_attr(feature = "clap", arg(long, action = clap::ArgAction::Set, default_value_t = true))]
+        #[serde(default = "default_true")]
+        animate: bool,
     },
     /// Toggle the opacity of a window.
     #[cfg_attr(
@@ -860,6 +1484,32 @@ pub enum Action {
         #[cfg_attr(feature = "clap", arg(long))]
         id: Option<u64>,
     },
+    /// Set the opacity of a window, overriding the opacity window rule.
+    #[cfg_attr(
+        feature = "clap",
+        clap(about = "Set the opacity of the focused window")
+    )]
+    SetWindowOpacity {
+        /// Id of the window.
+        ///
+        /// If `None`, uses the focused window.
+        #[cfg_attr(feature = "clap", arg(long))]
+        id: Option<u64>,
+        /// Opacity from 0 (fully transparent) to 1 (fully opaque).
+        opacity: f64,
+    },
+    /// Clear the opacity set with `SetWindowOpacity`, going back to the opacity window rule.
+    #[cfg_attr(
+        feature = "clap",
+        clap(about = "Clear the opacity override of the focused window")
+    )]
+    ClearWindowOpacity {
+        /// Id of the window.
+        ///
+        /// If `None`, uses the focused window.
+        #[cfg_attr(feature = "clap", arg(long))]
+        id: Option<u64>,
+    },
     /// Set the dynamic cast target to a window.
     #[cfg_attr(
         feature = "clap",
@@ -887,9 +1537,35 @@ pub enum Action {
     /// Clear the dynamic cast target, making it show nothing.
     ClearDynamicCastTarget {},
     /// Toggle (open/close) the Overview.
-    ToggleOverview {},
+    ToggleOverview {
+        /// Zoom of the workspaces in the overview, overriding the configured zoom.
+        #[cfg_attr(feature = "clap", arg(long))]
+        zoom: Option<f64>,
+        /// Reference (index or name) of the workspace to focus when opening the overview.
+        #[cfg_attr(feature = "clap", arg(long))]
+        workspace: Option<WorkspaceReferenceArg>,
+    },
+    /// Toggle (open/close) a grid of the windows in the focused tab group or column.
+    ToggleColumnGrid {},
+    /// Toggle (open/close) the clipboard history picker.
+    ///
+    /// Requires the clipboard history to be enabled in the config.
+    ToggleClipboardHistory {},
+    /// Set the clipboard to an entry from the clipboard history.
+    SelectClipboardHistoryEntry {
+        /// Id of the clipboard history entry.
+        #[cfg_attr(feature = "clap", arg(long))]
+        id: u64,
+    },
     /// Open the Overview.
-    OpenOverview {},
+    OpenOverview {
+        /// Zoom of the workspaces in the overview, overriding the configured zoom.
+        #[cfg_attr(feature = "clap", arg(long))]
+        zoom: Option<f64>,
+        /// Reference (index or name) of the workspace to focus when opening the overview.
+        #[cfg_attr(feature = "clap", arg(long))]
+        workspace: Option<WorkspaceReferenceArg>,
+    },
     /// Close the Overview.
     CloseOverview {},
     /// Toggle urgent status of a window.
@@ -910,11 +1586,61 @@ pub enum Action {
         #[cfg_attr(feature = "clap", arg(long))]
         id: u64,
     },
+    /// Add a mark to a window.
+    ///
+    /// A mark is on at most one window, so this takes the mark away from the window that had it.
+    #[cfg_attr(feature = "clap", clap(about = "Add a mark to the focused window"))]
+    MarkWindow {
+        /// Id of the window to mark.
+        ///
+        /// If `None`, uses the focused window.
+        #[cfg_attr(feature = "clap", arg(long))]
+        id: Option<u64>,
+        /// Mark to add.
+        mark: String,
+    },
+    /// Remove a mark from a window.
+    #[cfg_attr(
+        feature = "clap",
+        clap(about = "Remove a mark from the focused window")
+    )]
+    UnmarkWindow {
+        /// Id of the window to unmark.
+        ///
+        /// If `None`, uses the focused window.
+        #[cfg_attr(feature = "clap", arg(long))]
+        id: Option<u64>,
+        /// Mark to remove.
+        ///
+        /// If `None`, removes all marks of the window.
+        mark: Option<String>,
+    },
+    /// Focus the window with a mark.
+    FocusMark {
+        /// Mark of the window to focus.
+        mark: String,
+    },
+    /// Swap the focused window with the window with a mark.
+    ///
+    /// Both windows must be in the tiling layout of the same workspace.
+    SwapWithMark {
+        /// Mark of the window to swap with.
+        mark: String,
+    },
     /// Reload the config file.
     ///
     /// Can be useful for scripts changing the config file, to avoid waiting the small duration for
     /// niri's config file watcher to notice the changes.
     LoadConfigFile {},
+    /// Move the windows parked by the last inactive window parking back to where they were.
+    UnparkWindows {},
+    /// Switch to a bind mode from the config, replacing the regular binds with the mode's binds.
+    EnterBindMode {
+        /// Name of the bind mode.
+        name: String,
+    },
+    /// Go back from the current bind mode to the regular binds.
+    ExitBindMode {},
 }
 
 /// Change in window or column size.
@@ -1224,6 +1950,29 @@ pub struct LogicalOutput {
     pub transform: Transform,
 }
 
+/// Section of the config file.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum ConfigSection {
+    /// The `input` section.
+    Input,
+    /// The `output` sections and output profiles.
+    Outputs,
+    /// The `binds` and `switch-events` sections.
+    Binds,
+    /// The `layout` section.
+    Layout,
+    /// The `animations` section.
+    Animations,
+    /// The `cursor` section.
+    Cursor,
+    /// The `window-rule` sections.
+    WindowRules,
+    /// The `layer-rule` sections.
+    LayerRules,
+}
+
 /// Output transform, which goes counter-clockwise.
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
@@ -1269,6 +2018,11 @@ pub struct Window {
     pub title: Option<String>,
     /// Application ID, if set.
     pub app_id: Option<String>,
+    /// Title that niri shows for this window, for example in tabs.
+    ///
+    /// Same as [`Self::title`] unless the title is unset or empty, in which case this holds the
+    /// fallback configured with `title-fallback` in the tab indicator settings.
+    pub display_title: String,
     /// Process ID that created the Wayland connection for this window, if known.
     ///
     /// Currently, windows created by xdg-desktop-portal-gnome will have a `None` PID, but this may
@@ -1286,6 +2040,14 @@ pub struct Window {
     pub is_floating: bool,
     /// Whether this window requests your attention.
     pub is_urgent: bool,
+    /// Opacity of the window, from 0 (fully transparent) to 1 (fully opaque).
+    ///
+    /// Comes from the opacity window rule, or from the `SetWindowOpacity` action if it was used.
+    pub opacity: f64,
+    /// Marks of the window, in the order they were added.
+    ///
+    /// Every mark is on at most one window at a time.
+    pub marks: Vec<String>,
     /// Position- and size-related properties of the window.
     pub layout: WindowLayout,
     /// Timestamp when the window was most recently focused.
@@ -1345,6 +2107,9 @@ pub struct WindowLayout {
     /// Tile position within the current view of the workspace.
     ///
     /// This is the same "workspace view" as in gradients' `relative-to` in the niri config.
+    ///
+    /// For tiled windows, this uses the target view position, so it changes once when the view
+    /// starts scrolling rather than on every frame of the animation.
     pub tile_pos_in_workspace_view: Option<(f64, f64)>,
     /// Location of the window's visual geometry within its tile.
     ///
@@ -1387,6 +2152,14 @@ pub struct Workspace {
     pub idx: u8,
     /// Optional name of the workspace.
     pub name: Option<String>,
+    /// Optional icon of the workspace, as set in the config.
+    ///
+    /// This is an arbitrary string, usually an emoji or an icon font glyph.
+    pub icon: Option<String>,
+    /// Optional color of the workspace, as set in the config.
+    ///
+    /// Color values as red, green, blue, alpha, each ranging from 0.0 to 1.0.
+    pub color: Option<[f64; 4]>,
     /// Name of the output that the workspace is on.
     ///
     /// Can be `None` if no outputs are currently connected.
@@ -1445,6 +2218,10 @@ pub enum LayerSurfaceKeyboardInteractivity {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct LayerSurface {
+    /// Unique id of this layer surface.
+    ///
+    /// A surface that unmaps and maps again gets a new id.
+    pub id: u64,
     /// Namespace provided by the layer-shell client.
     pub namespace: String,
     /// Name of the output the surface is on.
@@ -1453,6 +2230,17 @@ pub struct LayerSurface {
     pub layer: Layer,
     /// The surface's keyboard interactivity mode.
     pub keyboard_interactivity: LayerSurfaceKeyboardInteractivity,
+    /// Position of the surface in the output's logical coordinate space.
+    ///
+    /// This is the position niri arranged the surface at according to its anchors and margins.
+    pub pos_in_output: (i32, i32),
+    /// Logical size of the surface.
+    pub size: (i32, i32),
+    /// Exclusive zone requested by the surface.
+    ///
+    /// Positive values reserve space at the anchored edge, `0` means that the surface is moved to
+    /// avoid the exclusive zones of other surfaces, and `-1` means that it ignores them.
+    pub exclusive_zone: i32,
 }
 
 /// A compositor event.
@@ -1540,6 +2328,9 @@ pub enum Event {
         urgent: bool,
     },
     /// The layout of one or more windows has changed.
+    ///
+    /// This is sent when a window's size or position changes. Changes of the workspace or the
+    /// floating state are sent as [`Event::WindowOpenedOrChanged`] instead.
     WindowLayoutsChanged {
         /// Pairs consisting of a window id and new layout information for the window.
         changes: Vec<(u64, WindowLayout)>,
@@ -1577,6 +2368,70 @@ pub enum Event {
         /// be converted to a `String` (e.g. contained invalid UTF-8 bytes).
         path: Option<String>,
     },
+    /// Inactive windows were moved to the parking workspace.
+    ///
+    /// The windows themselves are updated with separate [`Event::WindowOpenedOrChanged`] events.
+    WindowsParked {
+        /// Ids of the parked windows.
+        ids: Vec<u64>,
+    },
+    /// A key grabbed with [`Request::GrabKey`] was pressed.
+    KeyGrabTriggered {
+        /// The key combination, as it was written in the grab request.
+        key: String,
+    },
+    /// The active output profile changed.
+    ///
+    /// Emitted when outputs are connected or disconnected so that a different profile from the
+    /// `output-profiles` config section applies, or when the config is reloaded.
+    OutputProfileActivated {
+        /// Name of the newly active profile.
+        ///
+        /// If `None`, no profile matches the connected outputs, and the regular output config
+        /// applies.
+        name: Option<String>,
+    },
+    /// Periodic frame timing statistics.
+    ///
+    /// Only emitted once per second with `emit-frame-stats-events` in the `debug` config section.
+    FrameStatsUpdated {
+        /// Map from output name to its statistics.
+        stats: HashMap<String, FrameStats>,
+    },
+    /// The layer surface configuration has changed.
+    LayersChanged {
+        /// The new layer surface configuration.
+        ///
+        /// This configuration completely replaces the previous configuration. I.e. if any layer
+        /// surfaces are missing from here, then they were unmapped.
+        layers: Vec<LayerSurface>,
+    },
+    /// A new layer surface was mapped, or an existing one changed.
+    ///
+    /// Exclusive zone changes are sent as [`Event::LayerExclusiveZoneChanged`] instead.
+    LayerMappedOrChanged {
+        /// The new or updated layer surface.
+        layer: LayerSurface,
+    },
+    /// A layer surface was unmapped.
+    LayerUnmapped {
+        /// Id of the removed layer surface.
+        id: u64,
+    },
+    /// The exclusive zone of a layer surface changed.
+    LayerExclusiveZoneChanged {
+        /// Id of the layer surface.
+        id: u64,
+        /// The new exclusive zone.
+        exclusive_zone: i32,
+    },
+    /// A bind mode was entered or exited.
+    BindModeChanged {
+        /// Name of the current bind mode.
+        ///
+        /// If `None`, the regular binds are active.
+        name: Option<String>,
+    },
 }
 
 impl From<Duration> for Timestamp {
@@ -1814,6 +2669,20 @@ macro_rules! ensure {
     };
 }
 
+impl BlurChange {
+    /// Validates that the values are in range.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(radius) = self.radius {
+            ensure!(
+                (0. ..=1024.).contains(&radius),
+                "radius {} must be between 0 and 1024",
+                radius
+            );
+        }
+        Ok(())
+    }
+}
+
 impl OutputAction {
     /// Validates some required constraints on the modeline and custom mode.
     pub fn validate(&self) -> Result<(), String> {
@@ -1887,10 +2756,26 @@ impl OutputAction {
     }
 }
 
+fn default_true() -> bool {
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn move_floating_window_animates_by_default() {
+        let action: Action = serde_json::from_str(
+            r#"{"MoveFloatingWindow":{"id":null,"x":{"SetFixed":10.0},"y":{"AdjustFixed":0.0}}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            action,
+            Action::MoveFloatingWindow { animate: true, .. }
+        ));
+    }
+
     #[test]
     fn parse_size_change() {
         assert_eq!(
@@ -9,7 +9,7 @@
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 
-use crate::{Event, KeyboardLayouts, Window, Workspace};
+use crate::{Event, KeyboardLayouts, LayerSurface, Window, Workspace};
 
 /// Part of the state communicated via the event stream.
 pub trait EventStreamStatePart {
@@ -38,6 +38,9 @@ pub struct EventStreamState {
     /// State of workspaces.
     pub windows: WindowsState,
 
+    /// State of the layer surfaces.
+    pub layers: LayersState,
+
     /// State of the keyboard layouts.
     pub keyboard_layouts: KeyboardLayoutsState,
 
@@ -46,6 +49,12 @@ pub struct EventStreamState {
 
     /// State of the config.
     pub config: ConfigState,
+
+    /// State of the bind mode.
+    pub bind_mode: BindModeState,
+
+    /// State of the output profile.
+    pub output_profile: OutputProfileState,
 }
 
 /// The workspaces state communicated over the event stream.
@@ -62,6 +71,13 @@ pub struct WindowsState {
     pub windows: HashMap<u64, Window>,
 }
 
+/// The layer surfaces state communicated over the event stream.
+#[derive(Debug, Default)]
+pub struct LayersState {
+    /// Map from a layer surface id to the layer surface.
+    pub layers: HashMap<u64, LayerSurface>,
+}
+
 /// The keyboard layout state communicated over the event stream.
 #[derive(Debug, Default)]
 pub struct KeyboardLayoutsState {
@@ -83,23 +99,43 @@ pub struct ConfigState {
     pub failed: bool,
 }
 
+/// The bind mode state communicated over the event stream.
+#[derive(Debug, Default)]
+pub struct BindModeState {
+    /// Name of the current bind mode, or `None` if the regular binds are active.
+    pub name: Option<String>,
+}
+
+/// The output profile state communicated over the event stream.
+#[derive(Debug, Default)]
+pub struct OutputProfileState {
+    /// Name of the active output profile, or `None` if no profile matches the connected outputs.
+    pub name: Option<String>,
+}
+
 impl EventStreamStatePart for EventStreamState {
     fn replicate(&self) -> Vec<Event> {
         let mut events = Vec::new();
         events.extend(self.workspaces.replicate());
         events.extend(self.windows.replicate());
+        events.extend(self.layers.replicate());
         events.extend(self.keyboard_layouts.replicate());
         events.extend(self.overview.replicate());
         events.extend(self.config.replicate());
+        events.extend(self.bind_mode.replicate());
+        events.extend(self.output_profile.replicate());
         events
     }
 
     fn apply(&mut self, event: Event) -> Option<Event> {
         let event = self.workspaces.apply(event)?;
         let event = self.windows.apply(event)?;
+        let event = self.layers.apply(event)?;
         let event = self.keyboard_layouts.apply(event)?;
         let event = self.overview.apply(event)?;
         let event = self.config.apply(event)?;
+        let event = self.bind_mode.apply(event)?;
+        let event = self.output_profile.apply(event)?;
         Some(event)
     }
 }
@@ -225,6 +261,36 @@ impl EventStreamStatePart for WindowsState {
     }
 }
 
+impl EventStreamStatePart for LayersState {
+    fn replicate(&self) -> Vec<Event> {
+        let mut layers: Vec<_> = self.layers.values().cloned().collect();
+        layers.sort_by_key(|layer| layer.id);
+        vec![Event::LayersChanged { layers }]
+    }
+
+    fn apply(&mut self, event: Event) -> Option<Event> {
+        match event {
+            Event::LayersChanged { layers } => {
+                self.layers = layers.into_iter().map(|layer| (layer.id, layer)).collect();
+            }
+            Event::LayerMappedOrChanged { layer } => {
+                self.layers.insert(layer.id, layer);
+            }
+            Event::LayerUnmapped { id } => {
+                let layer = self.layers.remove(&id);
+                layer.expect("unmapped layer surface was missing from the map");
+            }
+            Event::LayerExclusiveZoneChanged { id, exclusive_zone } => {
+                let layer = self.layers.get_mut(&id);
+                let layer = layer.expect("changed layer surface was missing from the map");
+                layer.exclusive_zone = exclusive_zone;
+            }
+            event => return Some(event),
+        }
+        None
+    }
+}
+
 impl EventStreamStatePart for KeyboardLayoutsState {
     fn replicate(&self) -> Vec<Event> {
         if let Some(keyboard_layouts) = self.keyboard_layouts.clone() {
@@ -285,3 +351,39 @@ impl EventStreamStatePart for ConfigState {
         None
     }
 }
+
+impl EventStreamStatePart for BindModeState {
+    fn replicate(&self) -> Vec<Event> {
+        vec![Event::BindModeChanged {
+            name: self.name.clone(),
+        }]
+    }
+
+    fn apply(&mut self, event: Event) -> Option<Event> {
+        match event {
+            Event::BindModeChanged { name } => {
+                self.name = name;
+            }
+            event => return Some(event),
+        }
+        None
+    }
+}
+
+impl EventStreamStatePart for OutputProfileState {
+    fn replicate(&self) -> Vec<Event> {
+        vec![Event::OutputProfileActivated {
+            name: self.name.clone(),
+        }]
+    }
+
+    fn apply(&mut self, event: Event) -> Option<Event> {
+        match event {
+            Event::OutputProfileActivated { name } => {
+                self.name = name;
+            }
+            event => return Some(event),
+        }
+        None
+    }
+}
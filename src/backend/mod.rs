@@ -133,6 +133,15 @@ impl Backend {
         }
     }
 
+    /// Forgets the damage of all outputs, so that the next redraw repaints them in full.
+    pub fn reset_damage(&mut self) {
+        match self {
+            Backend::Tty(tty) => tty.reset_damage(),
+            Backend::Winit(winit) => winit.reset_damage(),
+            Backend::Headless(_) => (),
+        }
+    }
+
     pub fn import_dmabuf(&mut self, dmabuf: &Dmabuf) -> bool {
         match self {
             Backend::Tty(tty) => tty.import_dmabuf(dmabuf),
@@ -177,6 +186,14 @@ impl Backend {
         }
     }
 
+    pub fn set_output_powered(&mut self, niri: &mut Niri, output: &Output, powered: bool) {
+        match self {
+            Backend::Tty(tty) => tty.set_output_powered(niri, output, powered),
+            Backend::Winit(_) => (),
+            Backend::Headless(_) => (),
+        }
+    }
+
     pub fn set_output_on_demand_vrr(&mut self, niri: &mut Niri, output: &Output, enable_vrr: bool) {
         match self {
             Backend::Tty(tty) => tty.set_output_on_demand_vrr(niri, output, enable_vrr),
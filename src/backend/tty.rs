@@ -332,6 +332,10 @@ impl OutputDevice {
         };
 
         for plane in plane_handles {
+            // wp-color-representation advertises the default YUV encoding, so make sure that the
+            // previous DRM master didn't leave a different one on the plane.
+            reset_plane_color_encoding(&self.drm, plane, &mut req);
+
             let info = match self.drm.get_plane(plane) {
                 Ok(x) => x,
                 Err(err) => {
@@ -400,6 +404,16 @@ struct Surface {
     gamma_props: Option<GammaProps>,
     /// Gamma change to apply upon session resume.
     pending_gamma_change: Option<Option<Vec<u16>>>,
+    /// Whether the fullscreen window currently allows tearing page flips.
+    tearing: bool,
+    /// HDR settings from the output config.
+    hdr_config: Option<niri_config::Hdr>,
+    /// HDR metadata currently signaled to the display.
+    ///
+    /// This is only set while a frame with HDR content directly scanned out is being shown.
+    hdr: Option<niri_config::Hdr>,
+    /// Whether switching the display into HDR mode had failed.
+    hdr_failed: bool,
     /// Tracy frame that goes from vblank to vblank.
     vblank_frame: Option<tracy_client::Frame>,
     /// Frame name for the VBlank frame.
@@ -603,6 +617,31 @@ impl Tty {
                 self.libinput.suspend();
 
                 for device in self.devices.values_mut() {
+                    // Reset the gamma so that it doesn't tint other sessions. It is restored upon
+                    // resume.
+                    let surfaces = device.surfaces.iter_mut();
+                    for (crtc, surface) in surfaces.chain(device.powered_down_surfaces.iter_mut()) {
+                        if let Some(gamma_props) = &surface.gamma_props {
+                            if let Err(err) = gamma_props.reset_gamma(&device.drm) {
+                                debug!("couldn't reset gamma: {err:?}");
+                            }
+                        } else {
+                            // The legacy API has nothing to restore, so save the current gamma as
+                            // a pending change, unless a newer change is already waiting for the
+                            // resume.
+                            if surface.pending_gamma_change.is_none() {
+                                match get_gamma_for_crtc(&device.drm, *crtc) {
+                                    Ok(ramp) => surface.pending_gamma_change = Some(Some(ramp)),
+                                    Err(err) => debug!("couldn't get gamma: {err:?}"),
+                                }
+                            }
+
+                            if let Err(err) = set_gamma_for_crtc(&device.drm, *crtc, None) {
+                                debug!("couldn't reset gamma: {err:?}");
+                            }
+                        }
+                    }
+
                     device.drm.pause();
 
                     if let Some(lease_state) = &mut device.drm_lease_state {
@@ -698,6 +737,8 @@ impl Tty {
                                 Ok(()) => (),
                                 Err(err) => debug!("couldn't reset HDR properties: {err:?}"),
                             }
+                            // The next frame will switch back to HDR if needed.
+                            surface.hdr = None;
                         } else {
                             warn!("failed to get connector properties");
                         };
@@ -716,6 +757,9 @@ impl Tty {
                             if let Err(err) = gamma_props.restore_gamma(drm) {
                                 warn!("error restoring gamma: {err:?}");
                             }
+                        } else if let Err(err) = set_gamma_for_crtc(drm, *crtc, None) {
+                            // Don't keep the gamma that another session may have left behind.
+                            debug!("couldn't reset gamma: {err:?}");
                         }
                     }
                     device.surfaces = surfaces;
@@ -851,6 +895,9 @@ impl Tty {
             if let Some(src) = config.animations.window_open.custom_shader.as_deref() {
                 shaders::set_custom_open_program(gles_renderer, Some(src));
             }
+            if let Some(src) = config.animations.workspace_switch.custom_shader.as_deref() {
+                shaders::set_custom_workspace_switch_program(gles_renderer, Some(src));
+            }
             drop(config);
 
             niri.update_shaders();
@@ -1300,6 +1347,7 @@ impl Tty {
 
         let mut orientation = None;
         if let Ok(props) = ConnectorProperties::try_new(&device.drm, connector.handle()) {
+            // Start in SDR; HDR is signaled only while HDR content is directly scanned out.
             match reset_hdr(&props) {
                 Ok(()) => (),
                 Err(err) => debug!("couldn't reset HDR properties: {err:?}"),
@@ -1538,6 +1586,10 @@ impl Tty {
             dmabuf_feedback,
             gamma_props,
             pending_gamma_change: None,
+            tearing: false,
+            hdr_config: config.hdr,
+            hdr: None,
+            hdr_failed: false,
             vblank_frame: None,
             vblank_frame_name,
             time_since_presentation_plot_name,
@@ -1779,6 +1831,12 @@ impl Tty {
                         surface.presentation_misprediction_plot_name,
                         misprediction_s * 1000.,
                     );
+
+                    output_state.frame_stats.presented(
+                        presentation_time,
+                        target_presentation_time,
+                        refresh_interval,
+                    );
                 }
             }
             Ok(None) => (),
@@ -1920,6 +1978,16 @@ impl Tty {
             draw_damage(&mut output_state.debug_damage_tracker, &mut elements);
         }
 
+        let tearing = niri.output_wants_tearing(output);
+        if surface.tearing != tearing {
+            let word = if tearing { "allowing" } else { "disallowing" };
+            debug!("output {:?}: {word} tearing", surface.name.connector);
+            surface.tearing = tearing;
+        }
+
+        // FIXME: submit async page flips while tearing is allowed. DrmCompositor currently always
+        // queues vsynced flips.
+
         // Overlay planes are disabled by default as they cause weird performance issues on my
         // system.
         let flags = {
@@ -1942,6 +2010,16 @@ impl Tty {
             if debug.disable_cursor_plane {
                 flags.remove(FrameFlags::ALLOW_CURSOR_PLANE_SCANOUT);
             }
+            // niri composites in sRGB and doesn't convert it to PQ, so the display can only be in
+            // HDR mode while an HDR surface covers it on the primary plane. Anything on the other
+            // planes would be shown with the wrong transfer function, so keep it composited.
+            if niri.output_shows_hdr_content(output) {
+                flags.remove(FrameFlags::ALLOW_OVERLAY_PLANE_SCANOUT);
+                flags.remove(FrameFlags::ALLOW_CURSOR_PLANE_SCANOUT);
+                if surface.hdr_config.is_none() {
+                    flags.remove(primary_scanout_flag);
+                }
+            }
             if debug.skip_cursor_only_updates_during_vrr {
                 let output_state = niri.output_state.get(output).unwrap();
                 if output_state.frame_clock.vrr() {
@@ -1956,6 +2034,54 @@ impl Tty {
         let drm_compositor = &mut surface.compositor;
         match drm_compositor.render_frame::<_, _>(&mut renderer, &elements, [0.; 4], flags) {
             Ok(res) => {
+                // Switch the display into HDR mode only for frames where an HDR surface is
+                // directly scanned out. Composited frames are in sRGB.
+                let scanout_hdr = match &res.primary_element {
+                    PrimaryPlaneElement::Element(element) => {
+                        niri.is_hdr_surface_element(output, element.id())
+                    }
+                    PrimaryPlaneElement::Swapchain(_) => false,
+                };
+                let hdr = surface
+                    .hdr_config
+                    .filter(|_| scanout_hdr && !surface.hdr_failed);
+                if surface.hdr != hdr {
+                    // No page flip is pending here, so the connector properties can be changed
+                    // before queueing this frame.
+                    let result = ConnectorProperties::try_new(&device.drm, surface.connector)
+                        .and_then(|props| match &hdr {
+                            Some(hdr) => set_hdr(&props, hdr),
+                            None => reset_hdr(&props),
+                        });
+                    match result {
+                        Ok(()) => {
+                            debug!(
+                                "output {:?}: {} HDR",
+                                surface.name.connector,
+                                if hdr.is_some() { "enabled" } else { "disabled" }
+                            );
+                            surface.hdr = hdr;
+                        }
+                        Err(err) => {
+                            warn!(
+                                "output {:?}: couldn't switch HDR: {err:?}",
+                                surface.name.connector
+                            );
+                            // Don't retry every frame, and don't leave the connector half-way
+                            // switched.
+                            if hdr.is_some() {
+                                surface.hdr_failed = true;
+                                if let Ok(props) =
+                                    ConnectorProperties::try_new(&device.drm, surface.connector)
+                                {
+                                    let _ = reset_hdr(&props);
+                                }
+                            }
+                            surface.hdr = None;
+                        }
+                    }
+                }
+
                 let needs_sync = res.needs_sync()
                     || self
                         .config
@@ -2055,6 +2181,14 @@ impl Tty {
         }
     }
 
+    pub fn reset_damage(&mut self) {
+        for device in self.devices.values_mut() {
+            for (_crtc, surface) in device.all_surfaces_mut() {
+                surface.compositor.reset_buffers();
+            }
+        }
+    }
+
     pub fn import_dmabuf(&mut self, dmabuf: &Dmabuf) -> bool {
         let mut renderer = match self.gpu_manager.single_renderer(&self.primary_render_node) {
             Ok(renderer) => renderer,
@@ -2268,9 +2402,17 @@ impl Tty {
         // This variant removes and re-adds outputs like a config toggle, but without touching config.
         if active {
             self.outputs_suspended = false;
-            // Power on all outputs we previously powered off.
+            // Power on all outputs we previously powered off, except the ones that were powered
+            // off on their own.
             let outputs: Vec<_> = niri.global_space.outputs().cloned().collect();
             for output in outputs {
+                if niri
+                    .output_state
+                    .get(&output)
+                    .is_some_and(|s| s.powered_off)
+                {
+                    continue;
+                }
                 self.power_on_output(niri, &output);
             }
             // Re-scan connectors and reconnect surfaces as needed (handles hotplug while suspended).
@@ -2291,6 +2433,15 @@ impl Tty {
         }
     }
 
+    pub fn set_output_powered(&mut self, niri: &mut Niri, output: &Output, powered: bool) {
+        if !powered {
+            self.power_off_output(niri, output);
+        } else if !self.outputs_suspended {
+            // If all monitors are off, the output will power on together with the rest.
+            self.power_on_output(niri, output);
+        }
+    }
+
     pub fn power_off_output(&mut self, niri: &mut Niri, output: &Output) {
         let tty_state = output.user_data().get::<TtyOutputState>().unwrap();
         let Some(device) = self.devices.get_mut(&tty_state.node) else {
@@ -2482,6 +2633,29 @@ impl Tty {
         }
         self.update_output_config_on_resume = false;
 
+        // Pick the output profile before configuring anything, so that newly connected outputs
+        // come up with the profile applied instead of getting reconfigured right after.
+        let disable_monitor_names = self.config.borrow().debug.disable_monitor_names;
+        let connected: Vec<_> = self
+            .devices
+            .values()
+            .flat_map(|device| {
+                device
+                    .drm_scanner
+                    .crtcs()
+                    .filter(|(connector, crtc)| {
+                        connector.state() == connector::State::Connected
+                            && !device
+                                .non_desktop_connectors
+                                .contains(&(connector.handle(), *crtc))
+                    })
+                    .map(move |(connector, crtc)| {
+                        device.known_crtc_name(&crtc, connector, disable_monitor_names)
+                    })
+            })
+            .collect();
+        niri.select_output_profile(&connected);
+
         // Figure out if we should disable laptop panels.
         let disable_laptop_panels = self.should_disable_laptop_panels(niri.is_lid_closed);
         let should_disable = |connector: &str| disable_laptop_panels && is_laptop_panel(connector);
@@ -2514,6 +2688,12 @@ impl Tty {
                     continue;
                 };
 
+                // The next frame will switch HDR on or off according to the new settings.
+                if surface.hdr_config != config.hdr {
+                    surface.hdr_config = config.hdr;
+                    surface.hdr_failed = false;
+                }
+
                 let mut mode = None;
                 if let Some(modeline) = &config.modeline {
                     match calculate_drm_mode_from_modeline(modeline) {
@@ -2840,6 +3020,17 @@ impl GammaProps {
         Ok(())
     }
 
+    /// Resets the gamma while keeping the current blob around for restore_gamma().
+    fn reset_gamma(&self, device: &DrmDevice) -> anyhow::Result<()> {
+        let _span = tracy_client::span!("GammaProps::reset_gamma");
+
+        device
+            .set_property(self.crtc, self.gamma_lut, property::Value::Blob(0).into())
+            .context("error setting GAMMA_LUT")?;
+
+        Ok(())
+    }
+
     fn restore_gamma(&self, device: &DrmDevice) -> anyhow::Result<()> {
         let _span = tracy_client::span!("GammaProps::restore_gamma");
 
@@ -2991,6 +3182,31 @@ fn find_drm_property(
     })
 }
 
+/// Resets the YUV to RGB conversion of a plane to the kernel defaults.
+fn reset_plane_color_encoding(drm: &DrmDevice, plane: plane::Handle, req: &mut AtomicModeReq) {
+    let defaults = [
+        ("COLOR_ENCODING", c"ITU-R BT.601 YCbCr"),
+        ("COLOR_RANGE", c"YCbCr limited range"),
+    ];
+
+    for (name, default) in defaults {
+        // Only planes that support YUV formats have these properties.
+        let Some((handle, info, _)) = find_drm_property(drm, plane, name) else {
+            continue;
+        };
+        let property::ValueType::Enum(values) = info.value_type() else {
+            debug!("wrong {name} property type");
+            continue;
+        };
+        let Some(value) = values.values().1.iter().find(|v| v.name() == default) else {
+            debug!("plane doesn't support {default:?} for {name}");
+            continue;
+        };
+
+        req.add_property(plane, handle, property::Value::Enum(Some(value)));
+    }
+}
+
 fn get_drm_property(
     drm: &DrmDevice,
     resource: impl ResourceHandle,
@@ -3384,6 +3600,27 @@ impl<'a> ConnectorProperties<'a> {
 }
 
 const DRM_MODE_COLORIMETRY_DEFAULT: u64 = 0;
+const HDMI_STATIC_METADATA_TYPE1: u8 = 0;
+const HDMI_EOTF_SMPTE_ST2084: u8 = 2;
+
+/// `struct hdr_output_metadata` from the kernel UAPI.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct HdrOutputMetadata {
+    metadata_type: u32,
+    eotf: u8,
+    infoframe_metadata_type: u8,
+    /// Green, blue and red chromaticities in 0.00002 units, in the CTA-861 order.
+    display_primaries: [[u16; 2]; 3],
+    white_point: [u16; 2],
+    /// In cd/m².
+    max_display_mastering_luminance: u16,
+    /// In 0.0001 cd/m².
+    min_display_mastering_luminance: u16,
+    max_cll: u16,
+    max_fall: u16,
+    _padding: u16,
+}
 
 fn reset_hdr(props: &ConnectorProperties) -> anyhow::Result<()> {
     let (info, value) = props.find(c"HDR_OUTPUT_METADATA")?;
@@ -3412,6 +3649,71 @@ fn reset_hdr(props: &ConnectorProperties) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Switches the connector to the BT.2020 colorspace with the PQ transfer function.
+fn set_hdr(props: &ConnectorProperties, hdr: &niri_config::Hdr) -> anyhow::Result<()> {
+    let (metadata_info, _) = props.find(c"HDR_OUTPUT_METADATA")?;
+    let property::ValueType::Blob = metadata_info.value_type() else {
+        bail!("wrong property type")
+    };
+
+    let (colorspace_info, _) = props.find(c"Colorspace")?;
+    let property::ValueType::Enum(values) = colorspace_info.value_type() else {
+        bail!("wrong property type")
+    };
+    let bt2020 = values
+        .values()
+        .1
+        .iter()
+        .find(|value| value.name() == c"BT2020_RGB")
+        .context("connector doesn't support the BT2020_RGB colorspace")?
+        .value();
+
+    let metadata = HdrOutputMetadata {
+        metadata_type: u32::from(HDMI_STATIC_METADATA_TYPE1),
+        eotf: HDMI_EOTF_SMPTE_ST2084,
+        infoframe_metadata_type: HDMI_STATIC_METADATA_TYPE1,
+        // BT.2020 primaries with the D65 white point.
+        display_primaries: [[8500, 39850], [6550, 2300], [35400, 14600]],
+        white_point: [15635, 16450],
+        // Zero means unknown.
+        max_display_mastering_luminance: hdr.max_luminance.unwrap_or(0),
+        min_display_mastering_luminance: hdr
+            .min_luminance
+            .map_or(0, |lum| (lum.0 * 10000.).round() as u16),
+        max_cll: hdr.max_cll.unwrap_or(0),
+        max_fall: hdr.max_fall.unwrap_or(0),
+        _padding: 0,
+    };
+    let mut data = [metadata];
+    let data = cast_slice_mut(&mut data);
+
+    let blob = drm_ffi::mode::create_property_blob(props.device.as_fd(), data)
+        .context("error creating property blob")?;
+    let blob = u64::from(blob.blob_id);
+
+    let res = props
+        .device
+        .set_property(
+            props.connector,
+            metadata_info.handle(),
+            property::Value::Blob(blob).into(),
+        )
+        .context("error setting HDR_OUTPUT_METADATA");
+
+    // The connector state keeps its own reference to the blob.
+    if let Err(err) = props.device.destroy_property_blob(blob) {
+        warn!("error destroying HDR_OUTPUT_METADATA blob: {err:?}");
+    }
+    res?;
+
+    props
+        .device
+        .set_property(props.connector, colorspace_info.handle(), bt2020)
+        .context("error setting Colorspace")?;
+
+    Ok(())
+}
+
 fn set_max_bpc(props: &ConnectorProperties, bpc: u64) -> anyhow::Result<u64> {
     let (info, value) = props.find(c"max bpc")?;
     let property::ValueType::UnsignedRange(min, max) = info.value_type() else {
@@ -3459,6 +3761,22 @@ fn get_panel_orientation(props: &ConnectorProperties) -> anyhow::Result<Transfor
     }
 }
 
+fn get_gamma_for_crtc(device: &DrmDevice, crtc: crtc::Handle) -> anyhow::Result<Vec<u16>> {
+    let info = device.get_crtc(crtc).context("error getting crtc info")?;
+    let gamma_length = info.gamma_length() as usize;
+
+    ensure!(gamma_length != 0, "getting gamma is not supported");
+
+    let mut ramp = vec![0u16; gamma_length * 3];
+    let (red, rest) = ramp.split_at_mut(gamma_length);
+    let (green, blue) = rest.split_at_mut(gamma_length);
+    device
+        .get_gamma(crtc, red, green, blue)
+        .context("error getting gamma")?;
+
+    Ok(ramp)
+}
+
 pub fn set_gamma_for_crtc(
     device: &DrmDevice,
     crtc: crtc::Handle,
@@ -172,6 +172,9 @@ impl Winit {
         if let Some(src) = config.animations.window_open.custom_shader.as_deref() {
             shaders::set_custom_open_program(renderer, Some(src));
         }
+        if let Some(src) = config.animations.workspace_switch.custom_shader.as_deref() {
+            shaders::set_custom_workspace_switch_program(renderer, Some(src));
+        }
         drop(config);
 
         niri.update_shaders();
@@ -275,6 +278,10 @@ impl Winit {
         renderer.set_debug_flags(renderer.debug_flags() ^ DebugFlags::TINT);
     }
 
+    pub fn reset_damage(&mut self) {
+        self.damage_tracker = OutputDamageTracker::from_output(&self.output);
+    }
+
     pub fn import_dmabuf(&mut self, dmabuf: &Dmabuf) -> bool {
         match self.backend.renderer().import_dmabuf(dmabuf, None) {
             Ok(_texture) => true,
@@ -3,7 +3,10 @@ use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
 use clap_complete::Shell;
-use niri_ipc::{Action, OutputAction};
+use niri_ipc::{
+    Action, BlurChange, ConfigSection, InjectedInput, OutputAction, WidgetAnchor,
+    WindowDecorationsChange,
+};
 
 use crate::utils::version;
 
@@ -84,6 +87,14 @@ pub enum Msg {
         #[command(subcommand)]
         action: Action,
     },
+    /// Perform several actions at once, without drawing frames in-between.
+    Batch {
+        /// Actions to perform, one per argument, each written like for `niri msg action`.
+        ///
+        /// Action arguments are split like in a shell, e.g. "spawn -- notify-send 'Hello world'".
+        #[arg(required = true)]
+        actions: Vec<String>,
+    },
     /// Change output configuration temporarily.
     ///
     /// The configuration is changed temporarily and not saved into the config file. If the output
@@ -99,14 +110,234 @@ pub enum Msg {
         #[command(subcommand)]
         action: OutputAction,
     },
+    /// Save the current output configuration into a file that you can include in the config.
+    SaveOutputConfig {
+        /// Path to write the config to, e.g. ~/.config/niri/outputs.kdl.
+        path: String,
+    },
     /// Start continuously receiving events from the compositor.
     EventStream,
     /// Print the version of the running niri instance.
     Version,
+    /// Print a JSON schema description of the IPC of the running niri instance.
+    ///
+    /// Describes all requests, actions, responses and events with their parameters.
+    Schema,
     /// Request an error from the running niri instance.
     RequestError,
     /// Print the overview state.
     OverviewState,
+    /// Print the view position and the columns of a workspace's scrolling layout.
+    ScrollingView {
+        /// Id of the workspace.
+        ///
+        /// If not set, uses the focused workspace.
+        #[arg(long)]
+        workspace_id: Option<u64>,
+    },
+    /// Print the effective blur settings.
+    ///
+    /// Without an output or a window, prints the global blur settings.
+    Blur {
+        /// Output to print the settings for, including its output-specific overrides.
+        #[arg(long, conflicts_with = "window")]
+        output: Option<String>,
+        /// Id of the window to print the settings for, including its window rules.
+        ///
+        /// Run `niri msg windows` to see the window ids.
+        #[arg(long)]
+        window: Option<u64>,
+    },
+    /// Change blur settings temporarily.
+    ///
+    /// Without an output or a window, changes the global blur settings.
+    ///
+    /// The settings are changed temporarily and not saved into the config file. Global and output
+    /// changes will be forgotten when the config file is reloaded. Window changes remain until the
+    /// window is closed.
+    SetBlur {
+        /// Output to change the settings for.
+        #[arg(long, conflicts_with = "window")]
+        output: Option<String>,
+        /// Id of the window to change the settings for.
+        #[arg(long)]
+        window: Option<u64>,
+        /// Settings to change.
+        #[command(flatten)]
+        change: BlurChange,
+    },
+    /// Print which decorations are drawn for a window.
+    WindowDecorations {
+        /// Id of the window.
+        ///
+        /// Run `niri msg windows` to see the window ids.
+        #[arg(long)]
+        id: u64,
+    },
+    /// Toggle the border, shadow and blur of a window at runtime.
+    ///
+    /// The changes apply on top of the window rules, and remain until the window is closed.
+    SetWindowDecorations {
+        /// Id of the window.
+        #[arg(long)]
+        id: u64,
+        /// Decorations to change.
+        #[command(flatten)]
+        change: WindowDecorationsChange,
+    },
+    /// Print the pointer position and the windows under the pointer and focused.
+    Pointer,
+    /// List the entries of the built-in clipboard history.
+    ClipboardHistory,
+    /// Print a new xdg-activation token.
+    ///
+    /// Pass it to an app in the XDG_ACTIVATION_TOKEN environment variable to let the app take
+    /// focus when it opens.
+    ActivationToken,
+    /// Print frame timing statistics for every output.
+    FrameStats,
+    /// Inject an input event as if it came from a real device.
+    ///
+    /// Requires allow-ipc-input-injection in the debug config section.
+    InjectInput {
+        #[command(subcommand)]
+        event: InjectedInput,
+    },
+    /// Grab a key combination and print its presses until interrupted.
+    ///
+    /// Presses of the key are not sent to the focused window. Binds from the config take
+    /// precedence over grabs. The grab is released when this command exits.
+    GrabKey {
+        /// Key combination written like in the config binds, e.g. Mod+Shift+Space.
+        key: String,
+    },
+    /// Remove a key grab.
+    UngrabKey {
+        /// Key combination to ungrab.
+        key: String,
+    },
+    /// Save a downscaled thumbnail of a window as PNG.
+    WindowThumbnail {
+        /// Id of the window.
+        ///
+        /// Run `niri msg windows` to see the window ids.
+        #[arg(long)]
+        id: u64,
+        /// Maximum width and height of the thumbnail in pixels.
+        #[arg(long, default_value_t = 256)]
+        max_size: u32,
+        /// Path to save the PNG to.
+        ///
+        /// If not set, writes the PNG to stdout.
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+    /// Try out or confirm config changes.
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Print debugging information.
+    Debug {
+        #[command(subcommand)]
+        action: DebugAction,
+    },
+    /// Manage overlay widgets drawn by niri on top of the windows.
+    Widget {
+        #[command(subcommand)]
+        action: WidgetAction,
+    },
+    /// Manage window rules that stay until niri exits.
+    WindowRule {
+        #[command(subcommand)]
+        action: WindowRuleAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Apply a config file temporarily.
+    ///
+    /// The changes are reverted after the timeout unless confirmed with `niri msg config confirm`.
+    Try {
+        /// Path to the config file to try.
+        path: String,
+        /// Seconds to wait for the confirmation before reverting.
+        #[arg(long, default_value_t = 15)]
+        timeout: u64,
+    },
+    /// Keep the config applied with `niri msg config try`.
+    Confirm,
+    /// Reload the config file, optionally only some of its sections.
+    Reload {
+        /// Sections to reload; the whole config is reloaded if none are given.
+        #[arg(long, value_delimiter = ',')]
+        only: Vec<ConfigSection>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum WidgetAction {
+    /// Create a widget, or replace the widget with the same id.
+    Set {
+        /// Id of the widget.
+        id: String,
+        /// Text to show.
+        #[arg(long)]
+        text: Option<String>,
+        /// Value of the progress bar, from 0 to 1.
+        #[arg(long)]
+        progress: Option<f64>,
+        /// Path to a PNG image to show to the left of the text.
+        #[arg(long)]
+        icon: Option<String>,
+        /// Point of the output to anchor the widget to.
+        #[arg(long, value_enum, default_value = "top")]
+        anchor: WidgetAnchor,
+        /// Horizontal offset from the anchor, towards the center.
+        #[arg(long, default_value_t = 0., allow_negative_numbers = true)]
+        x: f64,
+        /// Vertical offset from the anchor, towards the center.
+        #[arg(long, default_value_t = 0., allow_negative_numbers = true)]
+        y: f64,
+        /// Name of the output to show the widget on; all outputs if not given.
+        #[arg(long)]
+        output: Option<String>,
+    },
+    /// Remove a widget.
+    Remove {
+        /// Id of the widget.
+        id: String,
+    },
+    /// List the widgets.
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum WindowRuleAction {
+    /// Add a window rule and print its id.
+    ///
+    /// The rule applies on top of the window rules from the config file, even after the config is
+    /// reloaded.
+    Add {
+        /// Contents of a window-rule {} block, for example 'match app-id="firefox"; opacity 0.9'.
+        rule: String,
+    },
+    /// Remove a window rule added with `niri msg window-rule add`.
+    Remove {
+        /// Id of the window rule.
+        id: u64,
+    },
+    /// List the window rules added with `niri msg window-rule add`.
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum DebugAction {
+    /// Print Wayland protocol usage statistics for every client.
+    ///
+    /// Includes the live protocol objects, the surface commit rates and oversized surfaces.
+    ProtocolStats,
 }
 
 #[derive(Clone, Debug, clap::ValueEnum)]
@@ -0,0 +1,311 @@
+//! Built-in clipboard history.
+//!
+//! When enabled, niri reads every new clipboard selection in a text or image format and keeps a
+//! copy, so that older selections can be put back into the clipboard without running a separate
+//! clipboard manager that holds on to the clipboard contents.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Read as _};
+use std::os::fd::{AsRawFd as _, OwnedFd};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Text formats in the order of preference for reading, also offered when restoring text.
+const TEXT_MIME_TYPES: &[&str] = &[
+    "text/plain;charset=utf-8",
+    "UTF8_STRING",
+    "text/plain",
+    "TEXT",
+    "STRING",
+];
+
+/// Image formats in the order of preference for reading.
+const IMAGE_MIME_TYPES: &[&str] = &["image/png", "image/jpeg", "image/webp"];
+
+/// Offered by password managers for selections that must not end up in the history.
+const SENSITIVE_MIME_TYPE: &str = "x-kde-passwordManagerHint";
+
+/// How long a client gets to send its selection before we give up on it.
+pub const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Default)]
+pub struct ClipboardHistory {
+    /// Entries, most recent first.
+    entries: VecDeque<Entry>,
+    next_id: u64,
+}
+
+#[derive(Debug)]
+pub struct Entry {
+    pub id: u64,
+    /// MIME type the data was read as.
+    pub mime_type: String,
+    pub data: Arc<[u8]>,
+}
+
+impl ClipboardHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the entries, most recent first.
+    pub fn entries(&self) -> impl ExactSizeIterator<Item = &Entry> {
+        self.entries.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// Adds a selection to the front of the history.
+    ///
+    /// If the same data is already in the history, moves that entry to the front instead.
+    pub fn push(&mut self, mime_type: String, data: Arc<[u8]>, max_entries: usize) {
+        if let Some(idx) = self
+            .entries
+            .iter()
+            .position(|entry| entry.mime_type == mime_type && entry.data == data)
+        {
+            let entry = self.entries.remove(idx).unwrap();
+            self.entries.push_front(entry);
+        } else {
+            let id = self.next_id;
+            self.next_id += 1;
+            self.entries.push_front(Entry {
+                id,
+                mime_type,
+                data,
+            });
+        }
+
+        self.truncate(max_entries);
+    }
+
+    /// Moves an entry to the front of the history.
+    pub fn promote(&mut self, id: u64) {
+        if let Some(idx) = self.entries.iter().position(|entry| entry.id == id) {
+            let entry = self.entries.remove(idx).unwrap();
+            self.entries.push_front(entry);
+        }
+    }
+
+    pub fn truncate(&mut self, max_entries: usize) {
+        self.entries.truncate(max_entries);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn to_ipc(&self) -> Vec<niri_ipc::ClipboardHistoryEntry> {
+        self.entries
+            .iter()
+            .map(|entry| niri_ipc::ClipboardHistoryEntry {
+                id: entry.id,
+                mime_type: entry.mime_type.clone(),
+                size: entry.data.len() as u64,
+                text: entry.text().map(String::from),
+            })
+            .collect()
+    }
+}
+
+impl Entry {
+    /// Returns the entry contents if it is text.
+    pub fn text(&self) -> Option<&str> {
+        if !is_text(&self.mime_type) {
+            return None;
+        }
+
+        std::str::from_utf8(&self.data).ok()
+    }
+
+    /// Returns the MIME types to offer when putting this entry back into the clipboard.
+    pub fn mime_types(&self) -> Vec<String> {
+        if is_text(&self.mime_type) {
+            TEXT_MIME_TYPES
+                .iter()
+                .map(|&mime| String::from(mime))
+                .collect()
+        } else {
+            vec![self.mime_type.clone()]
+        }
+    }
+}
+
+fn is_text(mime_type: &str) -> bool {
+    TEXT_MIME_TYPES.contains(&mime_type)
+}
+
+/// Picks the MIME type to store out of the ones offered by a new selection.
+///
+/// Returns `None` if the selection has no supported format, or if it is marked as sensitive.
+pub fn pick_mime_type(mime_types: &[String]) -> Option<&'static str> {
+    if mime_types.iter().any(|mime| mime == SENSITIVE_MIME_TYPE) {
+        return None;
+    }
+
+    TEXT_MIME_TYPES
+        .iter()
+        .chain(IMAGE_MIME_TYPES)
+        .find(|&&preferred| mime_types.iter().any(|mime| mime == preferred))
+        .copied()
+}
+
+/// Reads a selection from the pipe, giving up after `timeout` or past `max_size` bytes.
+///
+/// Returns `None` if the selection is empty or too large, or if the client didn't finish sending
+/// it in time.
+pub fn read_selection(
+    fd: OwnedFd,
+    max_size: u64,
+    timeout: Duration,
+) -> io::Result<Option<Vec<u8>>> {
+    let deadline = Instant::now() + timeout;
+    let mut file = File::from(fd);
+    let mut data = Vec::new();
+    let mut buf = [0; 64 * 1024];
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let timeout_ms = i32::try_from(remaining.as_millis()).unwrap_or(i32::MAX);
+        if timeout_ms == 0 {
+            return Ok(None);
+        }
+
+        let mut fds = [libc::pollfd {
+            fd: file.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        }];
+
+        // SAFETY: fds is a valid array of one pollfd that outlives the call, and file stays open
+        // until the end of this function.
+        let res = unsafe { libc::poll(fds.as_mut_ptr(), 1, timeout_ms) };
+        if res < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+        if res == 0 {
+            return Ok(None);
+        }
+
+        // POLLHUP with no data left reads as EOF below.
+        let n = match file.read(&mut buf) {
+            Ok(n) => n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        if n == 0 {
+            break;
+        }
+
+        data.extend_from_slice(&buf[..n]);
+        if data.len() as u64 > max_size {
+            return Ok(None);
+        }
+    }
+
+    if data.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use smithay::reexports::rustix::pipe::{pipe_with, PipeFlags};
+
+    use super::*;
+
+    fn text(s: &str) -> Arc<[u8]> {
+        Arc::from(s.as_bytes())
+    }
+
+    fn ids(history: &ClipboardHistory) -> Vec<u64> {
+        history.entries().map(|entry| entry.id).collect()
+    }
+
+    #[test]
+    fn push_dedups_and_truncates() {
+        let mut history = ClipboardHistory::new();
+        let mime = || String::from("text/plain");
+
+        history.push(mime(), text("a"), 3);
+        history.push(mime(), text("b"), 3);
+        history.push(mime(), text("c"), 3);
+        assert_eq!(ids(&history), [2, 1, 0]);
+
+        // Copying "a" again moves it to the front without a new id.
+        history.push(mime(), text("a"), 3);
+        assert_eq!(ids(&history), [0, 2, 1]);
+
+        history.push(mime(), text("d"), 3);
+        assert_eq!(ids(&history), [3, 0, 2]);
+    }
+
+    #[test]
+    fn pick_prefers_utf8_text() {
+        let offered = |types: &[&str]| types.iter().map(|&s| String::from(s)).collect::<Vec<_>>();
+
+        let types = offered(&["text/html", "text/plain", "text/plain;charset=utf-8"]);
+        assert_eq!(pick_mime_type(&types), Some("text/plain;charset=utf-8"));
+
+        let types = offered(&["image/png", "text/uri-list"]);
+        assert_eq!(pick_mime_type(&types), Some("image/png"));
+
+        let types = offered(&["text/plain", SENSITIVE_MIME_TYPE]);
+        assert_eq!(pick_mime_type(&types), None);
+
+        let types = offered(&["application/x-custom"]);
+        assert_eq!(pick_mime_type(&types), None);
+    }
+
+    fn pipe() -> (OwnedFd, File) {
+        let (read, write) = pipe_with(PipeFlags::CLOEXEC).unwrap();
+        (read, File::from(write))
+    }
+
+    #[test]
+    fn read_selection_reads_until_eof() {
+        let (read, mut write) = pipe();
+        write.write_all(b"hello").unwrap();
+        drop(write);
+
+        let data = read_selection(read, 16, READ_TIMEOUT).unwrap();
+        assert_eq!(data.as_deref(), Some(&b"hello"[..]));
+    }
+
+    #[test]
+    fn read_selection_skips_empty_and_too_large() {
+        let (read, write) = pipe();
+        drop(write);
+        assert_eq!(read_selection(read, 16, READ_TIMEOUT).unwrap(), None);
+
+        let (read, mut write) = pipe();
+        write.write_all(&[0; 17]).unwrap();
+        drop(write);
+        assert_eq!(read_selection(read, 16, READ_TIMEOUT).unwrap(), None);
+    }
+
+    #[test]
+    fn read_selection_times_out() {
+        // The client never closes its end of the pipe.
+        let (read, mut write) = pipe();
+        write.write_all(b"partial").unwrap();
+
+        let timeout = Duration::from_millis(50);
+        assert_eq!(read_selection(read, 16, timeout).unwrap(), None);
+        drop(write);
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Number of frames to keep the statistics for.
+const HISTORY_LEN: usize = 120;
+
+/// Rolling frame timing statistics for one output.
+#[derive(Debug, Default)]
+pub struct FrameStats {
+    frames: VecDeque<Frame>,
+    /// Time when the last frame finished rendering, waiting for its presentation.
+    pending_render_end: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    /// CPU time spent rendering the frame.
+    render_time: Duration,
+    /// Time from the end of rendering to the presentation, if known.
+    latency: Option<Duration>,
+    /// Whether the frame was presented later than targeted.
+    missed: bool,
+}
+
+impl FrameStats {
+    /// Records a rendered frame.
+    pub fn rendered(&mut self, render_time: Duration, render_end: Duration) {
+        if self.frames.len() == HISTORY_LEN {
+            self.frames.pop_front();
+        }
+
+        self.frames.push_back(Frame {
+            render_time,
+            latency: None,
+            missed: false,
+        });
+        self.pending_render_end = Some(render_end);
+    }
+
+    /// Records the presentation of the last rendered frame.
+    pub fn presented(
+        &mut self,
+        presentation_time: Duration,
+        target_presentation_time: Duration,
+        refresh_interval: Option<Duration>,
+    ) {
+        let Some(render_end) = self.pending_render_end.take() else {
+            return;
+        };
+        let Some(frame) = self.frames.back_mut() else {
+            return;
+        };
+
+        frame.latency = Some(presentation_time.saturating_sub(render_end));
+
+        // Count the frame as missed when it came at least half a refresh cycle late.
+        if let Some(refresh_interval) = refresh_interval {
+            frame.missed = presentation_time > target_presentation_time + refresh_interval / 2;
+        }
+    }
+
+    pub fn to_ipc(&self, refresh_interval: Option<Duration>, vrr: bool) -> niri_ipc::FrameStats {
+        let frames = self.frames.len() as u32;
+
+        let render_times = self.frames.iter().map(|frame| frame.render_time);
+        let render_time_max = render_times.clone().max().unwrap_or_default();
+        let render_time_avg = if frames == 0 {
+            Duration::ZERO
+        } else {
+            render_times.sum::<Duration>() / frames
+        };
+
+        let latencies: Vec<_> = self
+            .frames
+            .iter()
+            .filter_map(|frame| frame.latency)
+            .collect();
+        let latency_avg = if latencies.is_empty() {
+            None
+        } else {
+            Some(latencies.iter().sum::<Duration>() / latencies.len() as u32)
+        };
+
+        niri_ipc::FrameStats {
+            frames,
+            render_time_avg_us: render_time_avg.as_micros() as u64,
+            render_time_max_us: render_time_max.as_micros() as u64,
+            presentation_latency_avg_us: latency_avg.map(|x| x.as_micros() as u64),
+            missed_frames: self.frames.iter().filter(|frame| frame.missed).count() as u32,
+            refresh_rate: refresh_interval
+                .filter(|interval| !interval.is_zero())
+                .map(|interval| (1_000_000_000_000 / interval.as_nanos()) as u32),
+            vrr,
+        }
+    }
+}
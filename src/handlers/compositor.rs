@@ -1,7 +1,8 @@
 use std::collections::hash_map::Entry;
+use std::collections::HashSet;
 
 use niri_ipc::PositionChange;
-use smithay::backend::renderer::utils::on_commit_buffer_handler;
+use smithay::backend::renderer::utils::{on_commit_buffer_handler, with_renderer_surface_state};
 use smithay::input::pointer::{CursorImageStatus, CursorImageSurfaceData};
 use smithay::reexports::calloop::Interest;
 use smithay::reexports::wayland_server::protocol::wl_buffer;
@@ -23,7 +24,7 @@ use crate::handlers::XDG_ACTIVATION_TOKEN_TIMEOUT;
 use crate::layout::{ActivateWindow, AddWindowTarget, LayoutElement as _};
 use crate::niri::{CastTarget, ClientState, LockState, State};
 use crate::utils::transaction::Transaction;
-use crate::utils::{is_mapped, send_scale_transform};
+use crate::utils::{get_credentials_for_surface, is_mapped, send_scale_transform};
 use crate::window::{InitialConfigureState, Mapped, ResolvedWindowRules, Unmapped};
 
 impl CompositorHandler for State {
@@ -62,6 +63,20 @@ impl CompositorHandler for State {
         on_commit_buffer_handler::<Self>(surface);
         self.backend.early_import(surface);
 
+        if let Some(client) = surface.client() {
+            let size = with_renderer_surface_state(surface, |state| state.buffer_size()).flatten();
+            let data = client.get_data::<ClientState>().unwrap();
+            let rate = data
+                .protocol_stats
+                .lock()
+                .unwrap()
+                .commit(surface.id(), size);
+            if let Some(rate) = rate {
+                let pid = get_credentials_for_surface(surface).map(|creds| creds.pid);
+                warn!("client with pid {pid:?} is committing {rate} times per second");
+            }
+        }
+
         let mut root_surface = surface.clone();
         while let Some(parent) = get_parent(&root_surface) {
             root_surface = parent;
@@ -98,6 +113,7 @@ impl CompositorHandler for State {
                         is_full_width,
                         output,
                         workspace_id,
+                        restore,
                         is_pending_maximized,
                     ) = if let InitialConfigureState::Configured {
                         rules,
@@ -108,6 +124,7 @@ impl CompositorHandler for State {
                         is_full_width,
                         output,
                         workspace_name,
+                        restore,
                         is_pending_maximized,
                     } = state
                     {
@@ -120,6 +137,12 @@ impl CompositorHandler for State {
                             .as_deref()
                             .and_then(|n| self.niri.layout.find_workspace_by_name(n))
                             .map(|(_, ws)| ws.id());
+                        let restore = restore.filter(|r| {
+                            self.niri
+                                .layout
+                                .find_workspace_by_id(r.workspace_id)
+                                .is_some()
+                        });
 
                         (
                             rules,
@@ -128,6 +151,7 @@ impl CompositorHandler for State {
                             is_full_width,
                             output,
                             workspace_id,
+                            restore,
                             is_pending_maximized,
                         )
                     } else {
@@ -141,6 +165,7 @@ impl CompositorHandler for State {
                             false,
                             None,
                             None,
+                            None,
                             false,
                         )
                     };
@@ -150,6 +175,9 @@ impl CompositorHandler for State {
                     // moment, that is here.
                     let is_floating = rules.compute_open_floating(toplevel);
 
+                    // Windows opening in the background should still draw attention to themselves.
+                    let open_in_background = rules.open_focused == Some(false);
+
                     // Figure out if we should activate the window.
                     let activate = rules.open_focused.map(|focus| {
                         if focus {
@@ -195,12 +223,36 @@ impl CompositorHandler for State {
                     // The mapped pre-commit hook deals with dma-bufs on its own.
                     self.remove_default_dmabuf_pre_commit_hook(surface);
                     let hook = add_mapped_toplevel_pre_commit_hook(toplevel);
-                    let mapped = Mapped::new(window, rules, hook);
+                    let mut mapped = Mapped::new(window, rules, hook);
                     let window = mapped.window.clone();
 
+                    // Number untitled windows per app, reusing the numbers of closed windows.
+                    let app_id = mapped.app_id();
+                    let used: HashSet<_> = self
+                        .niri
+                        .layout
+                        .windows()
+                        .filter(|(_, w)| w.app_id() == app_id)
+                        .map(|(_, w)| w.untitled_number())
+                        .collect();
+                    let number = (1..).find(|n| !used.contains(n)).unwrap();
+                    mapped.set_untitled_number(number);
+
                     let target = if let Some(p) = &parent {
                         // Open dialogs next to their parent window.
                         AddWindowTarget::NextTo(p)
+                    } else if let Some(restore) = restore {
+                        // Put the window back where it was before the restart.
+                        match restore.pos_in_scrolling_layout {
+                            Some((column_idx, tile_idx)) if !is_floating => {
+                                AddWindowTarget::WorkspacePosition {
+                                    id: restore.workspace_id,
+                                    column_idx,
+                                    tile_idx,
+                                }
+                            }
+                            _ => AddWindowTarget::Workspace(restore.workspace_id),
+                        }
                     } else if let Some(id) = workspace_id {
                         AddWindowTarget::Workspace(id)
                     } else if let Some(output) = &output {
@@ -232,6 +284,14 @@ impl CompositorHandler for State {
                         error!("layout is missing the window that we just added");
                     }
 
+                    if open_in_background {
+                        if let Some((mapped, _)) =
+                            self.niri.layout.find_window_and_output_mut(surface)
+                        {
+                            mapped.set_urgent(true);
+                        }
+                    }
+
                     if let Some(output) = output {
                         self.niri.layout.start_open_animation_for_window(&window);
 
@@ -293,6 +353,8 @@ impl CompositorHandler for State {
                         .stop_casts_for_target(CastTarget::Window { id: id.get() });
 
                     self.niri.window_mru_ui.remove_window(id);
+                    self.niri.column_grid.remove_window(id);
+                    self.niri.forget_parked_window(&window);
                     self.niri.layout.remove_window(&window, transaction.clone());
                     self.add_default_dmabuf_pre_commit_hook(surface);
 
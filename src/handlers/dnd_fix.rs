@@ -1,18 +1,23 @@
 use std::any::Any;
-use std::sync::Arc;
+use std::os::fd::OwnedFd;
+use std::sync::{Arc, Mutex};
 
 use smithay::input::dnd::{DndFocus, OfferData, Source};
+use smithay::input::keyboard::ModifiersState;
 use smithay::input::pointer::{
-    AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent, GesturePinchBeginEvent,
-    GesturePinchEndEvent, GesturePinchUpdateEvent, GestureSwipeBeginEvent, GestureSwipeEndEvent,
-    GestureSwipeUpdateEvent, GrabStartData as PointerGrabStartData, MotionEvent as PointerMotionEvent,
-    PointerGrab, PointerInnerHandle, RelativeMotionEvent,
+    AxisFrame, ButtonEvent, CursorIcon, CursorImageStatus, GestureHoldBeginEvent,
+    GestureHoldEndEvent, GesturePinchBeginEvent, GesturePinchEndEvent, GesturePinchUpdateEvent,
+    GestureSwipeBeginEvent, GestureSwipeEndEvent, GestureSwipeUpdateEvent,
+    GrabStartData as PointerGrabStartData, MotionEvent as PointerMotionEvent, PointerGrab,
+    PointerInnerHandle, RelativeMotionEvent,
 };
 use smithay::input::{Seat, SeatHandler};
-use smithay::reexports::wayland_server::DisplayHandle;
+use smithay::reexports::wayland_server::protocol::wl_data_device_manager::DndAction;
 use smithay::reexports::wayland_server::protocol::wl_data_source::WlDataSource;
+use smithay::reexports::wayland_server::DisplayHandle;
 use smithay::reexports::wayland_server::Resource;
 use smithay::utils::{IsAlive, Logical, Point, Serial};
+use smithay::wayland::selection::data_device::SourceMetadata;
 
 use crate::handlers::State;
 
@@ -21,13 +26,16 @@ type PointerFocus = <State as SeatHandler>::PointerFocus;
 /// Copy of smithay's pointer DnD grab with a workaround for smithay#1887.
 ///
 /// We mark drops as not validated to avoid Chromium/Electron freezing when self-dropping links.
+///
+/// Additionally, the held keyboard modifiers pick the DnD action like on other desktops: Ctrl
+/// copies, Shift moves, and Ctrl+Shift asks.
 pub struct WorkaroundDndGrab<S: Source + Any> {
     dh: DisplayHandle,
     pointer_start_data: PointerGrabStartData<State>,
     last_position: Point<f64, Logical>,
-    data_source: Arc<S>,
+    data_source: Arc<ModifierActionSource<S>>,
     current_focus: Option<PointerFocus>,
-    offer_data: Option<<PointerFocus as DndFocus<State>>::OfferData<S>>,
+    offer_data: Option<<PointerFocus as DndFocus<State>>::OfferData<ModifierActionSource<S>>>,
     seat: Seat<State>,
 }
 
@@ -43,13 +51,43 @@ impl<S: Source + Any> WorkaroundDndGrab<S> {
             dh: dh.clone(),
             pointer_start_data: start_data,
             last_position,
-            data_source: Arc::new(source),
+            data_source: Arc::new(ModifierActionSource {
+                inner: source,
+                action: Mutex::new(DndAction::empty()),
+            }),
             current_focus: None,
             offer_data: None,
             seat,
         }
     }
 
+    /// Picks the action for the current keyboard modifiers, returns whether it changed.
+    fn update_modifier_action(&mut self, data: &mut State) -> bool {
+        let Some(keyboard) = self.seat.get_keyboard() else {
+            return false;
+        };
+
+        let action = modifier_action(&keyboard.modifier_state());
+        if !self.data_source.set_action(action) {
+            return false;
+        }
+
+        let icon = if action == DndAction::Copy {
+            CursorIcon::Copy
+        } else if action == DndAction::Move {
+            CursorIcon::Move
+        } else if action == DndAction::Ask {
+            CursorIcon::ContextMenu
+        } else {
+            CursorIcon::Default
+        };
+        data.niri
+            .cursor_manager
+            .set_cursor_image(CursorImageStatus::Named(icon));
+
+        true
+    }
+
     fn update_focus(
         &mut self,
         data: &mut State,
@@ -115,7 +153,7 @@ impl<S: Source + Any> WorkaroundDndGrab<S> {
         // cancelled (validated = false). Cross-client drops keep normal validation.
         let same_client = self.current_focus.as_ref().and_then(|focus| {
             focus.client().and_then(|surface_client| {
-                (&*self.data_source as &dyn Any)
+                (&self.data_source.inner as &dyn Any)
                     .downcast_ref::<WlDataSource>()
                     .and_then(|source| source.client())
                     .map(|src_client| src_client.id() == surface_client.id())
@@ -177,6 +215,22 @@ impl<S: Source> PointerGrab<State> for WorkaroundDndGrab<S> {
 
         self.last_position = event.location;
 
+        if self.update_modifier_action(data) {
+            // The destination only learns the source actions when entering, so enter it anew.
+            if let Some(focus) = self.current_focus.take() {
+                <PointerFocus as DndFocus<State>>::leave(
+                    &focus,
+                    data,
+                    self.offer_data.as_mut(),
+                    &self.seat,
+                );
+
+                if let Some(offer_data) = self.offer_data.take() {
+                    offer_data.disable();
+                }
+            }
+        }
+
         self.update_focus(data, focus, event.location, event.serial, event.time);
     }
 
@@ -286,3 +340,74 @@ impl<S: Source> PointerGrab<State> for WorkaroundDndGrab<S> {
         self.finish_drop(data);
     }
 }
+
+/// Returns the DnD action picked with the keyboard modifiers.
+fn modifier_action(mods: &ModifiersState) -> DndAction {
+    match (mods.ctrl, mods.shift) {
+        (true, true) => DndAction::Ask,
+        (true, false) => DndAction::Copy,
+        (false, true) => DndAction::Move,
+        (false, false) => DndAction::empty(),
+    }
+}
+
+/// DnD source offering only the action picked with the keyboard modifiers.
+///
+/// With no modifiers held, or when the source doesn't support the picked action, all source
+/// actions are offered as usual.
+struct ModifierActionSource<S> {
+    inner: S,
+    action: Mutex<DndAction>,
+}
+
+impl<S> ModifierActionSource<S> {
+    /// Sets the picked action, returns whether it changed.
+    fn set_action(&self, action: DndAction) -> bool {
+        let mut current = self.action.lock().unwrap();
+        if *current == action {
+            return false;
+        }
+
+        *current = action;
+        true
+    }
+}
+
+impl<S: Source> IsAlive for ModifierActionSource<S> {
+    fn alive(&self) -> bool {
+        self.inner.alive()
+    }
+}
+
+impl<S: Source> Source for ModifierActionSource<S> {
+    fn metadata(&self) -> Option<SourceMetadata> {
+        let mut metadata = self.inner.metadata()?;
+
+        let action = *self.action.lock().unwrap();
+        if !action.is_empty() && metadata.dnd_action.contains(action) {
+            metadata.dnd_action = action;
+        }
+
+        Some(metadata)
+    }
+
+    fn choose_action(&self, action: DndAction) {
+        self.inner.choose_action(action);
+    }
+
+    fn send(&self, mime_type: &str, fd: OwnedFd) {
+        self.inner.send(mime_type, fd);
+    }
+
+    fn drop_performed(&self) {
+        self.inner.drop_performed();
+    }
+
+    fn cancel(&self) {
+        self.inner.cancel();
+    }
+
+    fn finished(&self) {
+        self.inner.finished();
+    }
+}
@@ -1,16 +1,23 @@
+use std::mem;
+
+use niri_config::LayerOpenCloseAnimation;
 use smithay::delegate_layer_shell;
 use smithay::desktop::{layer_map_for_output, LayerSurface, PopupKind, WindowSurfaceType};
 use smithay::output::Output;
 use smithay::reexports::wayland_server::protocol::wl_output::WlOutput;
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
-use smithay::wayland::compositor::{get_parent, with_states};
+use smithay::utils::{IsAlive, Logical, Rectangle};
+use smithay::wayland::compositor::{
+    add_pre_commit_hook, get_parent, with_states, BufferAssignment, SurfaceAttributes,
+};
 use smithay::wayland::shell::wlr_layer::{
     self, Layer, LayerSurface as WlrLayerSurface, LayerSurfaceData, WlrLayerShellHandler,
     WlrLayerShellState,
 };
 use smithay::wayland::shell::xdg::PopupSurface;
+use smithay::wayland::text_input::TextInputSeat;
 
-use crate::layer::{MappedLayer, ResolvedLayerRules};
+use crate::layer::{ClosingLayer, MappedLayer, ResolvedLayerRules};
 use crate::niri::State;
 use crate::render_helpers::blur::EffectsFramebuffers;
 use crate::utils::{is_mapped, output_size, send_scale_transform};
@@ -43,6 +50,9 @@ impl WlrLayerShellHandler for State {
         }
 
         let wl_surface = surface.wl_surface().clone();
+        add_pre_commit_hook::<State, _>(&wl_surface, |state, _dh, surface| {
+            state.layer_shell_pre_commit(surface);
+        });
         let is_new = self.niri.unmapped_layer_surfaces.insert(wl_surface);
         assert!(is_new);
 
@@ -54,6 +64,9 @@ impl WlrLayerShellHandler for State {
     fn layer_destroyed(&mut self, surface: WlrLayerSurface) {
         let wl_surface = surface.wl_surface();
         self.niri.unmapped_layer_surfaces.remove(wl_surface);
+        self.niri
+            .hidden_on_screen_keyboards
+            .retain(|(_, layer)| layer.layer_surface() != &surface);
 
         let output = if let Some((output, mut map, layer)) =
             self.niri.layout.outputs().find_map(|o| {
@@ -68,8 +81,13 @@ impl WlrLayerShellHandler for State {
                 EffectsFramebuffers::set_dirty(&output);
             }
 
+            let geo = map.layer_geometry(&layer);
             map.unmap_layer(&layer);
-            self.niri.mapped_layer_surfaces.remove(&layer);
+            drop(map);
+
+            if let Some(mapped) = self.niri.mapped_layer_surfaces.remove(&layer) {
+                self.start_layer_close_animation(&output, &mapped, geo);
+            }
             Some(output)
         } else {
             None
@@ -86,6 +104,72 @@ impl WlrLayerShellHandler for State {
 delegate_layer_shell!(State);
 
 impl State {
+    fn start_layer_close_animation(
+        &mut self,
+        output: &Output,
+        mapped: &MappedLayer,
+        geo: Option<Rectangle<i32, Logical>>,
+    ) {
+        if mapped.rules().open_close_animation == LayerOpenCloseAnimation::Off {
+            return;
+        }
+
+        let anim_config = self.niri.config.borrow().animations.layer_close.0;
+        if anim_config.off {
+            return;
+        }
+
+        let Some(geo) = geo else {
+            return;
+        };
+
+        let location = geo.loc.to_f64();
+        let scale = output.current_scale().fractional_scale();
+        let clock = self.niri.clock.clone();
+        let closing = self.backend.with_primary_renderer(|renderer| {
+            ClosingLayer::new(renderer, mapped, location, scale, clock, anim_config)
+        });
+        let Some(closing) = closing else {
+            return;
+        };
+
+        if let Some(state) = self.niri.output_state.get_mut(output) {
+            state.closing_layers.push(closing);
+        }
+        self.niri.queue_redraw(output);
+    }
+
+    /// Starts the close animation when a mapped layer surface unmaps itself with a null commit.
+    ///
+    /// This must happen before the commit, which releases the buffer that we snapshot.
+    fn layer_shell_pre_commit(&mut self, surface: &WlSurface) {
+        let got_unmapped = with_states(surface, |states| {
+            let mut guard = states.cached_state.get::<SurfaceAttributes>();
+            matches!(guard.pending().buffer, Some(BufferAssignment::Removed))
+        });
+        if !got_unmapped {
+            return;
+        }
+
+        let found = self.niri.layout.outputs().find_map(|o| {
+            let map = layer_map_for_output(o);
+            let layer = map
+                .layer_for_surface(surface, WindowSurfaceType::TOPLEVEL)?
+                .clone();
+            let geo = map.layer_geometry(&layer);
+            Some((o.clone(), layer, geo))
+        });
+        let Some((output, layer, geo)) = found else {
+            return;
+        };
+
+        // The commit handler removes the mapped layer once the surface is actually unmapped.
+        if let Some(mapped) = self.niri.mapped_layer_surfaces.remove(&layer) {
+            self.start_layer_close_animation(&output, &mapped, geo);
+            self.niri.mapped_layer_surfaces.insert(layer, mapped);
+        }
+    }
+
     pub fn layer_shell_handle_commit(&mut self, surface: &WlSurface) -> bool {
         let mut root_surface = surface.clone();
         while let Some(parent) = get_parent(&root_surface) {
@@ -103,7 +187,7 @@ impl State {
             })
             .cloned();
         let Some(output) = output else {
-            return false;
+            return self.hidden_on_screen_keyboard_handle_commit(&root_surface);
         };
 
         if surface != &root_surface {
@@ -216,4 +300,132 @@ impl State {
 
         true
     }
+
+    /// Shows or hides the on-screen keyboards depending on whether a text input needs them.
+    ///
+    /// Hidden keyboards are taken out of their layer map, so they aren't rendered, don't receive
+    /// input, and their exclusive zone doesn't shrink the working area.
+    pub fn refresh_on_screen_keyboard(&mut self) {
+        let _span = tracy_client::span!("State::refresh_on_screen_keyboard");
+
+        let wanted = self.on_screen_keyboard_wanted();
+        let was_wanted = mem::replace(&mut self.niri.on_screen_keyboard_wanted, wanted);
+
+        if wanted {
+            if !self.niri.hidden_on_screen_keyboards.is_empty() {
+                self.show_on_screen_keyboards();
+            }
+            return;
+        }
+
+        let to_hide: Vec<_> = self
+            .niri
+            .mapped_layer_surfaces
+            .iter()
+            .filter(|(_, mapped)| mapped.rules().on_screen_keyboard)
+            .map(|(layer, _)| layer.clone())
+            .collect();
+
+        // Don't animate keyboards that map while hidden, they were never shown.
+        for layer in to_hide {
+            self.hide_on_screen_keyboard(&layer, was_wanted);
+        }
+    }
+
+    /// Returns whether the focused surface has an enabled text input and there's no physical
+    /// keyboard to type into it.
+    fn on_screen_keyboard_wanted(&self) -> bool {
+        // Power buttons, lid switches and the like show up as keyboards too, so look for a
+        // letter key.
+        const KEY_A: u32 = 30;
+        let has_physical_keyboard = self.niri.devices.iter().any(|device| {
+            device.has_capability(input::DeviceCapability::Keyboard)
+                && device.keyboard_has_key(KEY_A) == Ok(true)
+        });
+        if has_physical_keyboard {
+            return false;
+        }
+
+        let mut wanted = false;
+        self.niri
+            .seat
+            .text_input()
+            .with_active_text_input(|_, _| wanted = true);
+        wanted
+    }
+
+    fn hide_on_screen_keyboard(&mut self, layer: &LayerSurface, animate: bool) {
+        let output = self
+            .niri
+            .layout
+            .outputs()
+            .find(|o| layer_map_for_output(o).layers().any(|l| l == layer))
+            .cloned();
+        let Some(output) = output else {
+            return;
+        };
+
+        let mut map = layer_map_for_output(&output);
+        let geo = map.layer_geometry(layer);
+        map.unmap_layer(layer);
+        drop(map);
+
+        if let Some(mapped) = self.niri.mapped_layer_surfaces.remove(layer) {
+            if animate {
+                self.start_layer_close_animation(&output, &mapped, geo);
+            }
+        }
+
+        self.niri
+            .hidden_on_screen_keyboards
+            .push((output.clone(), layer.clone()));
+        self.niri.output_resized(&output);
+    }
+
+    /// Puts all hidden on-screen keyboards back into their layer maps.
+    pub fn show_on_screen_keyboards(&mut self) {
+        for (output, layer) in mem::take(&mut self.niri.hidden_on_screen_keyboards) {
+            if !layer.alive() || !self.niri.layout.outputs().any(|o| *o == output) {
+                continue;
+            }
+
+            let mut map = layer_map_for_output(&output);
+            if let Err(err) = map.map_layer(&layer) {
+                warn!("error showing on-screen keyboard: {err:?}");
+                continue;
+            }
+            drop(map);
+
+            // Go through the regular mapping path, which resolves the rules anew and starts the
+            // open animation.
+            let surface = layer.wl_surface().clone();
+            self.niri.unmapped_layer_surfaces.insert(surface.clone());
+            self.layer_shell_handle_commit(&surface);
+        }
+    }
+
+    fn hidden_on_screen_keyboard_handle_commit(&mut self, root_surface: &WlSurface) -> bool {
+        let Some(idx) = self
+            .niri
+            .hidden_on_screen_keyboards
+            .iter()
+            .position(|(_, layer)| layer.wl_surface() == root_surface)
+        else {
+            return false;
+        };
+
+        // Keep hidden surfaces as they are until shown.
+        if is_mapped(root_surface) {
+            return true;
+        }
+
+        // The keyboard unmapped itself via a null commit. Return it to the layer map so that it
+        // goes through the initial commit as usual.
+        let (output, layer) = self.niri.hidden_on_screen_keyboards.remove(idx);
+        layer_map_for_output(&output).map_layer(&layer).unwrap();
+        self.niri
+            .unmapped_layer_surfaces
+            .insert(root_surface.clone());
+        self.layer_shell_handle_commit(root_surface)
+    }
 }
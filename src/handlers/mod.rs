@@ -13,17 +13,21 @@ use std::time::Duration;
 use smithay::backend::allocator::dmabuf::Dmabuf;
 use smithay::backend::drm::DrmNode;
 use smithay::backend::input::{InputEvent, TabletToolDescriptor};
-use smithay::desktop::{PopupKind, PopupManager};
+use smithay::desktop::utils::surface_primary_scanout_output;
+use smithay::desktop::{layer_map_for_output, PopupKind, PopupManager, WindowSurfaceType};
 use smithay::input::dnd::{self, DnDGrab, DndGrabHandler, DndTarget};
 use smithay::input::pointer::{CursorIcon, CursorImageStatus, Focus, PointerHandle};
 use smithay::input::{keyboard, Seat, SeatHandler, SeatState};
 use smithay::output::Output;
 use smithay::reexports::rustix::fs::{fcntl_setfl, OFlags};
+use smithay::reexports::rustix::pipe::{pipe_with, PipeFlags};
+use smithay::reexports::wayland_protocols::xdg::shell::server::xdg_toplevel::XdgToplevel;
 use smithay::reexports::wayland_protocols_wlr::screencopy::v1::server::zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1;
 use smithay::reexports::wayland_server::protocol::wl_output::WlOutput;
+use smithay::reexports::wayland_server::protocol::wl_pointer::WlPointer;
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
 use smithay::reexports::wayland_server::Resource;
-use smithay::utils::{Logical, Point, Rectangle, Serial};
+use smithay::utils::{Logical, Physical, Point, Rectangle, Scale, Serial, Size};
 use smithay::wayland::compositor::{get_parent, with_states};
 use smithay::wayland::dmabuf::{DmabufGlobal, DmabufHandler, DmabufState, ImportNotifier};
 use smithay::wayland::drm_lease::{
@@ -37,12 +41,15 @@ use smithay::wayland::keyboard_shortcuts_inhibit::{
     KeyboardShortcutsInhibitHandler, KeyboardShortcutsInhibitState, KeyboardShortcutsInhibitor,
 };
 use smithay::wayland::output::OutputHandler;
-use smithay::wayland::pointer_constraints::{with_pointer_constraint, PointerConstraintsHandler};
+use smithay::wayland::pointer_constraints::{
+    with_pointer_constraint, PointerConstraint, PointerConstraintsHandler,
+};
 use smithay::wayland::security_context::{
     SecurityContext, SecurityContextHandler, SecurityContextListenerSource,
 };
 use smithay::wayland::selection::data_device::{
-    set_data_device_focus, DataDeviceHandler, DataDeviceState, WaylandDndGrabHandler,
+    request_data_device_client_selection, set_data_device_focus, DataDeviceHandler,
+    DataDeviceState, WaylandDndGrabHandler,
 };
 use smithay::wayland::selection::ext_data_control::{
     DataControlHandler as ExtDataControlHandler, DataControlState as ExtDataControlState,
@@ -53,7 +60,7 @@ use smithay::wayland::selection::primary_selection::{
 use smithay::wayland::selection::wlr_data_control::{
     DataControlHandler as WlrDataControlHandler, DataControlState as WlrDataControlState,
 };
-use smithay::wayland::selection::{SelectionHandler, SelectionTarget};
+use smithay::wayland::selection::{SelectionHandler, SelectionSource, SelectionTarget};
 use smithay::wayland::session_lock::{
     LockSurface, SessionLockHandler, SessionLockManagerState, SessionLocker,
 };
@@ -62,42 +69,57 @@ use smithay::wayland::xdg_activation::{
     XdgActivationHandler, XdgActivationState, XdgActivationToken, XdgActivationTokenData,
 };
 use smithay::{
-    delegate_cursor_shape, delegate_data_control, delegate_data_device, delegate_dmabuf,
-    delegate_drm_lease, delegate_ext_data_control, delegate_fractional_scale,
-    delegate_idle_inhibit, delegate_idle_notify, delegate_input_method_manager,
-    delegate_keyboard_shortcuts_inhibit, delegate_output, delegate_pointer_constraints,
-    delegate_pointer_gestures, delegate_presentation, delegate_primary_selection,
-    delegate_relative_pointer, delegate_seat, delegate_security_context, delegate_session_lock,
-    delegate_single_pixel_buffer, delegate_tablet_manager, delegate_text_input_manager,
-    delegate_viewporter, delegate_virtual_keyboard_manager, delegate_xdg_activation,
+    delegate_commit_timing, delegate_cursor_shape, delegate_data_control, delegate_data_device,
+    delegate_dmabuf, delegate_drm_lease, delegate_ext_data_control, delegate_fifo,
+    delegate_fractional_scale, delegate_idle_inhibit, delegate_idle_notify,
+    delegate_input_method_manager, delegate_keyboard_shortcuts_inhibit, delegate_output,
+    delegate_pointer_constraints, delegate_pointer_gestures, delegate_presentation,
+    delegate_primary_selection, delegate_relative_pointer, delegate_seat,
+    delegate_security_context, delegate_session_lock, delegate_single_pixel_buffer,
+    delegate_tablet_manager, delegate_text_input_manager, delegate_viewporter,
+    delegate_virtual_keyboard_manager, delegate_xdg_activation,
 };
 
+use crate::backend::Backend;
+use crate::clipboard_history;
 pub use crate::handlers::xdg_shell::KdeDecorationsModeState;
 use crate::layout::workspace::WorkspaceId;
 use crate::layout::{ActivateWindow, LayoutElement as _};
-use crate::niri::{DndIcon, NewClient, State};
+use crate::niri::{DndIcon, LockState, NewClient, State};
+use crate::protocols::color_management::{
+    ColorManagementHandler, ColorManagementState, ImageDescription,
+};
 use crate::protocols::ext_background_effect::ExtBackgroundEffectManagerHandler;
+use crate::protocols::ext_foreign_toplevel_list::{
+    ExtForeignToplevelListHandler, ExtForeignToplevelListState,
+};
 use crate::protocols::ext_workspace::{self, ExtWorkspaceHandler, ExtWorkspaceManagerState};
 use crate::protocols::foreign_toplevel::{
     self, ForeignToplevelHandler, ForeignToplevelManagerState,
 };
 use crate::protocols::gamma_control::{GammaControlHandler, GammaControlManagerState};
+use crate::protocols::image_copy_capture::{ImageCopyCaptureHandler, ImageCopyCaptureState};
 use crate::protocols::kde_blur::OrgKdeKwinBlurManagerHandler;
 use crate::protocols::mutter_x11_interop::MutterX11InteropHandler;
 use crate::protocols::output_management::{OutputManagementHandler, OutputManagementManagerState};
+use crate::protocols::pointer_warp::PointerWarpHandler;
 use crate::protocols::screencopy::{Screencopy, ScreencopyHandler, ScreencopyManagerState};
 use crate::protocols::virtual_pointer::{
     VirtualPointerAxisEvent, VirtualPointerButtonEvent, VirtualPointerHandler,
     VirtualPointerInputBackend, VirtualPointerManagerState, VirtualPointerMotionAbsoluteEvent,
     VirtualPointerMotionEvent,
 };
+use crate::protocols::xdg_toplevel_tag::{self, XdgToplevelTagHandler};
 use crate::utils::{output_size, send_scale_transform};
 use crate::{
-    delegate_ext_background_effect, delegate_ext_workspace, delegate_foreign_toplevel,
-    delegate_gamma_control, delegate_mutter_x11_interop, delegate_org_kde_kwin_blur,
-    delegate_output_management, delegate_screencopy, delegate_virtual_pointer,
+    delegate_color_management, delegate_color_representation, delegate_ext_background_effect,
+    delegate_ext_foreign_toplevel_list, delegate_ext_workspace, delegate_foreign_toplevel,
+    delegate_gamma_control, delegate_image_copy_capture, delegate_mutter_x11_interop,
+    delegate_org_kde_kwin_blur, delegate_output_management, delegate_pointer_warp,
+    delegate_screencopy, delegate_tearing_control, delegate_virtual_pointer,
+    delegate_xdg_toplevel_tag,
 };
-use dnd_fix::WorkaroundDndGrab;
+pub use dnd_fix::WorkaroundDndGrab;
 
 pub const XDG_ACTIVATION_TOKEN_TIMEOUT: Duration = Duration::from_secs(10);
 
@@ -176,7 +198,12 @@ impl PointerConstraintsHandler for State {
             constraint.is_some_and(|c| c.is_active())
         });
 
-        if !is_constraint_active {
+        // A lock forced by a window rule keeps the pointer in place just like an active lock, so
+        // follow the hints of the client's own lock too.
+        let is_lock_forced = self.niri.forced_pointer_constraint()
+            == Some(niri_config::ForcePointerConstraint::Lock);
+
+        if !is_constraint_active && !is_lock_forced {
             return;
         }
 
@@ -222,6 +249,58 @@ impl PointerConstraintsHandler for State {
 }
 delegate_pointer_constraints!(State);
 
+impl PointerWarpHandler for State {
+    fn warp_pointer(
+        &mut self,
+        surface: &WlSurface,
+        _pointer: &WlPointer,
+        location: Point<f64, Logical>,
+        serial: Serial,
+    ) {
+        let pointer = self.niri.seat.get_pointer().unwrap();
+
+        // The serial must match the pointer enter on this surface.
+        let last_enter = pointer.last_enter();
+        if !last_enter.is_some_and(|last_enter| serial.is_no_older_than(&last_enter)) {
+            return;
+        }
+
+        // FIXME: like in cursor_position_hint(), use the pointer focus somehow, rather than
+        // pointer contents.
+        let Some((ref surface_under_pointer, origin)) = self.niri.pointer_contents.surface else {
+            return;
+        };
+
+        if surface_under_pointer != surface {
+            return;
+        }
+
+        // A locked pointer doesn't move, and a confined pointer can't leave its region.
+        let mut allowed = true;
+        with_pointer_constraint(surface, &pointer, |constraint| {
+            let Some(constraint) = constraint.filter(|c| c.is_active()) else {
+                return;
+            };
+
+            match &*constraint {
+                PointerConstraint::Locked(_) => allowed = false,
+                PointerConstraint::Confined(confine) => {
+                    if let Some(region) = confine.region() {
+                        allowed = region.contains(location.to_i32_round());
+                    }
+                }
+            }
+        });
+
+        if !allowed {
+            return;
+        }
+
+        self.move_cursor(origin + location);
+    }
+}
+delegate_pointer_warp!(State);
+
 impl InputMethodHandler for State {
     fn new_popup(&mut self, surface: PopupSurface) {
         let popup = PopupKind::InputMethod(surface);
@@ -253,10 +332,20 @@ impl InputMethodHandler for State {
     }
 
     fn parent_geometry(&self, parent: &WlSurface) -> Rectangle<i32, Logical> {
+        if let Some((mapped, _)) = self.niri.layout.find_window_and_output(parent) {
+            return mapped.window.geometry();
+        }
+
+        // Text inputs in layer-shell surfaces, like launchers, also get IME popups.
         self.niri
             .layout
-            .find_window_and_output(parent)
-            .map(|(mapped, _)| mapped.window.geometry())
+            .outputs()
+            .find_map(|o| {
+                let map = layer_map_for_output(o);
+                let layer = map.layer_for_surface(parent, WindowSurfaceType::TOPLEVEL)?;
+                let geo = map.layer_geometry(layer)?;
+                Some(Rectangle::from_size(geo.size))
+            })
             .unwrap_or_default()
     }
 }
@@ -267,17 +356,32 @@ impl KeyboardShortcutsInhibitHandler for State {
     }
 
     fn new_inhibitor(&mut self, inhibitor: KeyboardShortcutsInhibitor) {
-        // FIXME: show a confirmation dialog with a "remember for this application" kind of toggle.
-        inhibitor.activate();
+        // Asking windows wait for the user to toggle the inhibitor on; the indicator tells them to.
+        let surface = inhibitor.wl_surface();
+        match self.niri.keyboard_shortcuts_inhibit_rule(surface) {
+            Some(niri_config::KeyboardShortcutsInhibit::Allow) => inhibitor.activate(),
+            Some(_) => (),
+            // Decide once the window rules are resolved.
+            None => {
+                self.niri
+                    .keyboard_shortcuts_inhibitors_pending
+                    .insert(surface.clone());
+            }
+        }
         self.niri
             .keyboard_shortcuts_inhibiting_surfaces
             .insert(inhibitor.wl_surface().clone(), inhibitor);
+        self.niri.keyboard_shortcuts_inhibit_dirty = true;
     }
 
     fn inhibitor_destroyed(&mut self, inhibitor: KeyboardShortcutsInhibitor) {
         self.niri
             .keyboard_shortcuts_inhibiting_surfaces
             .remove(&inhibitor.wl_surface().clone());
+        self.niri
+            .keyboard_shortcuts_inhibitors_pending
+            .remove(inhibitor.wl_surface());
+        self.niri.keyboard_shortcuts_inhibit_dirty = true;
     }
 }
 
@@ -288,6 +392,62 @@ delegate_virtual_keyboard_manager!(State);
 impl SelectionHandler for State {
     type SelectionUserData = Arc<[u8]>;
 
+    fn new_selection(
+        &mut self,
+        ty: SelectionTarget,
+        source: Option<SelectionSource>,
+        seat: Seat<Self>,
+    ) {
+        if !matches!(ty, SelectionTarget::Clipboard) {
+            return;
+        }
+
+        let history = self.niri.config.borrow().clipboard.history;
+        if !history.on {
+            return;
+        }
+
+        let Some(source) = source else {
+            return;
+        };
+        let Some(mime_type) = clipboard_history::pick_mime_type(&source.mime_types()) else {
+            return;
+        };
+
+        // The new selection is stored in the seat only after this handler returns.
+        self.niri.event_loop.insert_idle(move |state| {
+            let (read, write) = match pipe_with(PipeFlags::CLOEXEC) {
+                Ok(fds) => fds,
+                Err(err) => {
+                    warn!("error creating a pipe for the clipboard history: {err:?}");
+                    return;
+                }
+            };
+
+            let mime_type = String::from(mime_type);
+            if let Err(err) = request_data_device_client_selection(&seat, mime_type.clone(), write)
+            {
+                debug!("error requesting the selection for the clipboard history: {err:?}");
+                return;
+            }
+
+            let max_size = u64::from(history.max_entry_size_kb) * 1024;
+            let tx = state.niri.clipboard_history_tx.clone();
+            thread::spawn(move || {
+                let timeout = clipboard_history::READ_TIMEOUT;
+                match clipboard_history::read_selection(read, max_size, timeout) {
+                    Ok(Some(data)) => {
+                        let _ = tx.send((mime_type, data));
+                    }
+                    Ok(None) => (),
+                    Err(err) => {
+                        warn!("error reading the selection for the clipboard history: {err:?}");
+                    }
+                }
+            });
+        });
+    }
+
     fn send_selection(
         &mut self,
         _ty: SelectionTarget,
@@ -428,6 +588,10 @@ delegate_output!(State);
 
 delegate_presentation!(State);
 
+delegate_fifo!(State);
+
+delegate_commit_timing!(State);
+
 impl DmabufHandler for State {
     fn dmabuf_state(&mut self) -> &mut DmabufState {
         &mut self.niri.dmabuf_state
@@ -454,6 +618,12 @@ impl SessionLockHandler for State {
     }
 
     fn lock(&mut self, confirmation: SessionLocker) {
+        // Snapshot the screen while it still shows the unlocked session.
+        if matches!(self.niri.lock_state, LockState::Unlocked) {
+            self.backend
+                .with_primary_renderer(|renderer| self.niri.capture_lock_backdrops(renderer));
+        }
+
         self.niri.lock(confirmation);
     }
 
@@ -499,6 +669,7 @@ impl SecurityContextHandler for State {
                     client,
                     restricted: true,
                     credentials_unknown: false,
+                    security_context: Some(context.clone()),
                 });
             })
             .unwrap();
@@ -907,3 +1078,73 @@ impl ExtBackgroundEffectManagerHandler for State {
     }
 }
 delegate_ext_background_effect!(State);
+
+impl XdgToplevelTagHandler for State {
+    fn set_toplevel_tag(&mut self, toplevel: &XdgToplevel, tag: String) {
+        let Some(toplevel) = self
+            .niri
+            .xdg_shell_state
+            .toplevel_surfaces()
+            .iter()
+            .find(|t| t.xdg_toplevel() == toplevel)
+            .cloned()
+        else {
+            return;
+        };
+
+        if xdg_toplevel_tag::set_toplevel_tag(toplevel.wl_surface(), tag) {
+            self.update_window_rules(&toplevel);
+        }
+    }
+}
+delegate_xdg_toplevel_tag!(State);
+
+delegate_tearing_control!(State);
+
+impl ExtForeignToplevelListHandler for State {
+    fn ext_foreign_toplevel_list_state(&mut self) -> &mut ExtForeignToplevelListState {
+        &mut self.niri.ext_foreign_toplevel_list_state
+    }
+}
+delegate_ext_foreign_toplevel_list!(State);
+
+impl ImageCopyCaptureHandler for State {
+    fn image_copy_capture_state(&mut self) -> &mut ImageCopyCaptureState {
+        &mut self.niri.image_copy_capture_state
+    }
+
+    fn window_capture_size(&mut self, surface: &WlSurface) -> Option<Size<i32, Physical>> {
+        let (mapped, output) = self.niri.layout.find_window_and_output(surface)?;
+        let scale = output.map_or(1., |output| output.current_scale().fractional_scale());
+        let bbox = mapped
+            .window
+            .bbox_with_popups()
+            .to_physical_precise_up(Scale::from(scale));
+        Some(bbox.size)
+    }
+}
+delegate_image_copy_capture!(State);
+
+impl ColorManagementHandler for State {
+    fn color_management_state(&mut self) -> &mut ColorManagementState {
+        &mut self.niri.color_management_state
+    }
+
+    fn output_image_description(&mut self, output: &Output) -> ImageDescription {
+        // Only the TTY backend can switch the display into HDR mode.
+        if !matches!(self.backend, Backend::Tty(_)) {
+            return ImageDescription::SRGB;
+        }
+
+        self.niri.output_image_description(output)
+    }
+
+    fn surface_primary_output(&mut self, surface: &WlSurface) -> Option<Output> {
+        with_states(surface, |states| {
+            surface_primary_scanout_output(surface, states)
+        })
+    }
+}
+delegate_color_management!(State);
+
+delegate_color_representation!(State);
@@ -27,13 +27,15 @@ use smithay::wayland::input_method::InputMethodSeat;
 use smithay::wayland::shell::kde::decoration::{KdeDecorationHandler, KdeDecorationState};
 use smithay::wayland::shell::wlr_layer::{self, Layer};
 use smithay::wayland::shell::xdg::decoration::XdgDecorationHandler;
+use smithay::wayland::shell::xdg::dialog::XdgDialogHandler;
 use smithay::wayland::shell::xdg::{
     PopupSurface, PositionerState, ToplevelSurface, XdgShellHandler, XdgShellState,
     XdgToplevelSurfaceData,
 };
 use smithay::wayland::xdg_foreign::{XdgForeignHandler, XdgForeignState};
 use smithay::{
-    delegate_kde_decoration, delegate_xdg_decoration, delegate_xdg_foreign, delegate_xdg_shell,
+    delegate_kde_decoration, delegate_xdg_decoration, delegate_xdg_dialog, delegate_xdg_foreign,
+    delegate_xdg_shell,
 };
 use tracing::field::Empty;
 
@@ -45,7 +47,8 @@ use crate::layout::ActivateWindow;
 use crate::niri::{CastTarget, PopupGrabState, State};
 use crate::utils::transaction::Transaction;
 use crate::utils::{
-    get_monotonic_time, output_matches_name, send_scale_transform, update_tiled_state, ResizeEdge,
+    get_monotonic_time, is_modal_dialog, output_matches_name, send_scale_transform,
+    set_modal_dialog, update_tiled_state, with_toplevel_role, ResizeEdge,
 };
 use crate::window::{InitialConfigureState, ResolvedWindowRules, Unmapped, WindowRef};
 
@@ -133,13 +136,17 @@ impl XdgShellHandler for State {
 
         match &start_data {
             PointerOrTouchStartData::Pointer(_) => {
-                if let Some(grab) = MoveGrab::new(self, start_data, window.clone(), true, None) {
+                if let Some(grab) =
+                    MoveGrab::new(self, start_data, window.clone(), true, false, None)
+                {
                     pointer.set_grab(self, grab, serial, Focus::Clear);
                 }
             }
             PointerOrTouchStartData::Touch(_) => {
                 let touch = self.niri.seat.get_touch().unwrap();
-                if let Some(grab) = MoveGrab::new(self, start_data, window.clone(), true, None) {
+                if let Some(grab) =
+                    MoveGrab::new(self, start_data, window.clone(), true, false, None)
+                {
                     touch.set_grab(self, grab, serial);
                 }
             }
@@ -518,6 +525,7 @@ impl XdgShellHandler for State {
                     is_full_width,
                     output,
                     workspace_name,
+                    restore: _,
                     is_pending_maximized,
                 } => {
                     // Figure out the monitor following a similar logic to initial configure.
@@ -729,6 +737,7 @@ impl XdgShellHandler for State {
                     is_full_width,
                     output,
                     workspace_name,
+                    restore: _,
                     is_pending_maximized,
                 } => {
                     // Figure out the monitor following a similar logic to initial configure.
@@ -862,6 +871,8 @@ impl XdgShellHandler for State {
         let was_active = active_window == Some(&window);
 
         self.niri.window_mru_ui.remove_window(id);
+        self.niri.column_grid.remove_window(id);
+        self.niri.forget_parked_window(&window);
         self.niri.layout.remove_window(&window, transaction.clone());
         self.add_default_dmabuf_pre_commit_hook(surface.wl_surface());
 
@@ -900,6 +911,10 @@ impl XdgShellHandler for State {
             return;
         };
 
+        if is_modal_dialog(&toplevel) {
+            self.place_modal_dialog(&toplevel);
+        }
+
         if let Some((mapped, output)) = self.niri.layout.find_window_and_output_mut(&parent) {
             let output = output.cloned();
             let window = mapped.window.clone();
@@ -1017,7 +1032,49 @@ impl XdgForeignHandler for State {
 }
 delegate_xdg_foreign!(State);
 
+impl XdgDialogHandler for State {
+    fn modal_changed(&mut self, toplevel: ToplevelSurface, is_modal: bool) {
+        set_modal_dialog(&toplevel, is_modal);
+
+        if is_modal {
+            self.place_modal_dialog(&toplevel);
+        }
+
+        // The focus and the parent dimming are updated in refresh_modal_dialogs().
+        self.niri.queue_redraw_all();
+    }
+}
+delegate_xdg_dialog!(State);
+
 impl State {
+    /// Moves a mapped modal dialog on top of its parent window.
+    ///
+    /// Dialogs that are modal when they are mapped open on top of the parent already.
+    fn place_modal_dialog(&mut self, toplevel: &ToplevelSurface) {
+        let Some(parent) = toplevel.parent() else {
+            return;
+        };
+        let Some((parent, _)) = self.niri.layout.find_window_and_output(&parent) else {
+            return;
+        };
+        let parent = parent.window.clone();
+        let Some((mapped, output)) = self
+            .niri
+            .layout
+            .find_window_and_output(toplevel.wl_surface())
+        else {
+            return;
+        };
+        let window = mapped.window.clone();
+        let output = output.cloned();
+
+        if self.niri.layout.center_window_on_parent(&window, &parent) {
+            if let Some(output) = output {
+                self.niri.queue_redraw(&output);
+            }
+        }
+    }
+
     pub fn send_initial_configure(&mut self, toplevel: &ToplevelSurface) {
         let _span = tracy_client::span!("State::send_initial_configure");
 
@@ -1027,12 +1084,33 @@ impl State {
         };
 
         let config = self.niri.config.borrow();
-        let rules = ResolvedWindowRules::compute(
+        let mut rules = ResolvedWindowRules::compute(
             &config.window_rules,
             WindowRef::Unmapped(unmapped),
             self.niri.is_at_startup,
         );
 
+        // Put the window back where it was before the restart.
+        let mut restore = None;
+        if self.niri.is_at_startup {
+            if let Some(session) = &mut self.niri.saved_session {
+                let saved = with_toplevel_role(toplevel, |role| {
+                    let app_id = role.app_id.as_deref()?;
+                    session.take_match(app_id, role.title.as_deref())
+                });
+                if let Some(saved) = saved {
+                    debug!("restoring {} from saved session", saved.app_id);
+                    saved.apply_to_rules(&mut rules);
+                    restore = saved.placement(&self.niri.layout);
+                }
+
+                // Every saved window found its place.
+                if session.windows.is_empty() {
+                    self.niri.saved_session = None;
+                }
+            }
+        }
+
         let Unmapped { window, state, .. } = unmapped;
 
         let InitialConfigureState::NotConfigured {
@@ -1045,23 +1123,24 @@ impl State {
         };
 
         // Pick the target monitor. First, check if we had a workspace set in the window rules.
-        let mon = rules
-            .open_on_workspace
-            .as_deref()
-            .and_then(|name| self.niri.layout.monitor_for_workspace(name));
+        // Take the first one that exists.
+        let open_on_workspace = rules.open_on_workspace.iter().find_map(|name| {
+            let mon = self.niri.layout.monitor_for_workspace(name)?;
+            Some((name.as_str(), mon))
+        });
+        let mon = open_on_workspace.map(|(_, mon)| mon);
 
-        // If not, check if we had an output set in the window rules.
+        // If not, check if we had an output set in the window rules, taking the first connected
+        // one.
         let mon = mon.or_else(|| {
-            rules
-                .open_on_output
-                .as_deref()
-                .and_then(|name| {
-                    self.niri
-                        .global_space
-                        .outputs()
-                        .find(|output| output_matches_name(output, name))
-                })
-                .and_then(|o| self.niri.layout.monitor_for_output(o))
+            rules.open_on_output.iter().find_map(|name| {
+                let output = self
+                    .niri
+                    .global_space
+                    .outputs()
+                    .find(|output| output_matches_name(output, name))?;
+                self.niri.layout.monitor_for_output(output)
+            })
         });
 
         // If not, check if the window requested one for fullscreen.
@@ -1106,13 +1185,16 @@ impl State {
         let is_floating = rules.compute_open_floating(toplevel);
 
         // Tell the surface the preferred size and bounds for its likely output.
-        let ws = rules
-            .open_on_workspace
-            .as_deref()
-            .and_then(|name| mon.map(|mon| mon.find_named_workspace(name)))
-            .unwrap_or_else(|| {
-                mon.map(|mon| mon.active_workspace_ref())
-                    .or_else(|| self.niri.layout.active_workspace())
+        let ws = restore
+            .and_then(|r| self.niri.layout.find_workspace_by_id(r.workspace_id))
+            .map(|(_, ws)| ws)
+            .or_else(|| {
+                open_on_workspace
+                    .and_then(|(name, _)| mon.map(|mon| mon.find_named_workspace(name)))
+                    .unwrap_or_else(|| {
+                        mon.map(|mon| mon.active_workspace_ref())
+                            .or_else(|| self.niri.layout.active_workspace())
+                    })
             });
 
         let mut is_pending_maximized = false;
@@ -1168,9 +1250,19 @@ impl State {
             is_full_width,
             output,
             workspace_name: ws.and_then(|w| w.name().cloned()),
+            restore,
             is_pending_maximized,
         };
 
+        // Shortcut inhibitors created before now can follow the resolved rules.
+        if self
+            .niri
+            .keyboard_shortcuts_inhibitors_pending
+            .contains(toplevel.wl_surface())
+        {
+            self.niri.keyboard_shortcuts_inhibit_dirty = true;
+        }
+
         trace!(surface = %toplevel.wl_surface().id(), "sending initial configure");
         toplevel.send_configure();
     }
@@ -1359,6 +1451,8 @@ impl State {
     }
 
     pub fn update_window_rules(&mut self, toplevel: &ToplevelSurface) {
+        self.niri.keyboard_shortcuts_inhibit_dirty = true;
+
         let config = self.niri.config.borrow();
         let window_rules = &config.window_rules;
 
@@ -0,0 +1,303 @@
+//! Input events injected through IPC.
+//!
+//! These go through the same pipeline as events from real devices, so they trigger binds, gestures
+//! and focus changes just like physical input would.
+
+use niri_ipc::InjectedInput;
+use smithay::backend::input::{
+    AbsolutePositionEvent, ButtonState, Device, DeviceCapability, Event, InputBackend, InputEvent,
+    KeyState, KeyboardKeyEvent, Keycode, PointerButtonEvent, PointerMotionAbsoluteEvent,
+    PointerMotionEvent, UnusedEvent,
+};
+use smithay::output::Output;
+
+use super::backend_ext::NiriInputDevice;
+use crate::niri::State;
+use crate::utils::get_monotonic_time;
+
+pub struct InjectedInputBackend;
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub struct InjectedDevice;
+
+impl Device for InjectedDevice {
+    fn id(&self) -> String {
+        String::from("niri ipc injected input")
+    }
+
+    fn name(&self) -> String {
+        String::from("injected input")
+    }
+
+    fn has_capability(&self, capability: DeviceCapability) -> bool {
+        matches!(
+            capability,
+            DeviceCapability::Keyboard | DeviceCapability::Pointer
+        )
+    }
+
+    fn usb_id(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    fn syspath(&self) -> Option<std::path::PathBuf> {
+        None
+    }
+}
+
+impl NiriInputDevice for InjectedDevice {
+    fn output(&self, _state: &State) -> Option<Output> {
+        // Absolute positions are in global coordinates.
+        None
+    }
+}
+
+pub struct InjectedKeyEvent {
+    time: u64,
+    /// XKB keycode.
+    keycode: Keycode,
+    state: KeyState,
+}
+
+impl InjectedKeyEvent {
+    /// Creates an event for a key that niri received before, like a key held back by slow keys.
+    pub fn new(key_code: Keycode, state: KeyState) -> Self {
+        Self {
+            time: get_monotonic_time().as_micros() as u64,
+            keycode: key_code,
+            state,
+        }
+    }
+}
+
+impl Event<InjectedInputBackend> for InjectedKeyEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> InjectedDevice {
+        InjectedDevice
+    }
+}
+
+impl KeyboardKeyEvent<InjectedInputBackend> for InjectedKeyEvent {
+    fn key_code(&self) -> Keycode {
+        self.keycode
+    }
+
+    fn state(&self) -> KeyState {
+        self.state
+    }
+
+    fn count(&self) -> u32 {
+        u32::from(self.state == KeyState::Pressed)
+    }
+}
+
+pub struct InjectedMotionEvent {
+    time: u64,
+    dx: f64,
+    dy: f64,
+}
+
+impl Event<InjectedInputBackend> for InjectedMotionEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> InjectedDevice {
+        InjectedDevice
+    }
+}
+
+impl PointerMotionEvent<InjectedInputBackend> for InjectedMotionEvent {
+    fn delta_x(&self) -> f64 {
+        self.dx
+    }
+
+    fn delta_y(&self) -> f64 {
+        self.dy
+    }
+
+    fn delta_x_unaccel(&self) -> f64 {
+        self.dx
+    }
+
+    fn delta_y_unaccel(&self) -> f64 {
+        self.dy
+    }
+}
+
+pub struct InjectedMotionAbsoluteEvent {
+    time: u64,
+    /// Position relative to the bounding rectangle of all outputs, from 0 to 1.
+    x: f64,
+    y: f64,
+}
+
+impl Event<InjectedInputBackend> for InjectedMotionAbsoluteEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> InjectedDevice {
+        InjectedDevice
+    }
+}
+
+impl AbsolutePositionEvent<InjectedInputBackend> for InjectedMotionAbsoluteEvent {
+    fn x(&self) -> f64 {
+        self.x
+    }
+
+    fn y(&self) -> f64 {
+        self.y
+    }
+
+    fn x_transformed(&self, width: i32) -> f64 {
+        self.x * f64::from(width)
+    }
+
+    fn y_transformed(&self, height: i32) -> f64 {
+        self.y * f64::from(height)
+    }
+}
+
+impl PointerMotionAbsoluteEvent<InjectedInputBackend> for InjectedMotionAbsoluteEvent {}
+
+pub struct InjectedButtonEvent {
+    time: u64,
+    button: u32,
+    state: ButtonState,
+}
+
+impl InjectedButtonEvent {
+    /// Creates an event for a button that niri received before, like a held back chord press.
+    pub fn new(button: u32, state: ButtonState) -> Self {
+        Self {
+            time: get_monotonic_time().as_micros() as u64,
+            button,
+            state,
+        }
+    }
+}
+
+impl Event<InjectedInputBackend> for InjectedButtonEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> InjectedDevice {
+        InjectedDevice
+    }
+}
+
+impl PointerButtonEvent<InjectedInputBackend> for InjectedButtonEvent {
+    fn button_code(&self) -> u32 {
+        self.button
+    }
+
+    fn state(&self) -> ButtonState {
+        self.state
+    }
+}
+
+impl InputBackend for InjectedInputBackend {
+    type Device = InjectedDevice;
+
+    type KeyboardKeyEvent = InjectedKeyEvent;
+    type PointerAxisEvent = UnusedEvent;
+    type PointerButtonEvent = InjectedButtonEvent;
+    type PointerMotionEvent = InjectedMotionEvent;
+    type PointerMotionAbsoluteEvent = InjectedMotionAbsoluteEvent;
+
+    type GestureSwipeBeginEvent = UnusedEvent;
+    type GestureSwipeUpdateEvent = UnusedEvent;
+    type GestureSwipeEndEvent = UnusedEvent;
+    type GesturePinchBeginEvent = UnusedEvent;
+    type GesturePinchUpdateEvent = UnusedEvent;
+    type GesturePinchEndEvent = UnusedEvent;
+    type GestureHoldBeginEvent = UnusedEvent;
+    type GestureHoldEndEvent = UnusedEvent;
+
+    type TouchDownEvent = UnusedEvent;
+    type TouchUpEvent = UnusedEvent;
+    type TouchMotionEvent = UnusedEvent;
+    type TouchCancelEvent = UnusedEvent;
+    type TouchFrameEvent = UnusedEvent;
+    type TabletToolAxisEvent = UnusedEvent;
+    type TabletToolProximityEvent = UnusedEvent;
+    type TabletToolTipEvent = UnusedEvent;
+    type TabletToolButtonEvent = UnusedEvent;
+
+    type SwitchToggleEvent = UnusedEvent;
+
+    type SpecialEvent = UnusedEvent;
+}
+
+impl State {
+    pub fn inject_input(&mut self, input: InjectedInput) -> Result<(), String> {
+        if !self.niri.config.borrow().debug.allow_ipc_input_injection {
+            return Err(String::from(
+                "input injection is disabled, enable it with allow-ipc-input-injection \
+                 in the debug section of the config",
+            ));
+        }
+
+        let time = get_monotonic_time().as_micros() as u64;
+
+        let event = match input {
+            InjectedInput::Key { keycode, pressed } => {
+                // Convert from evdev to XKB.
+                let keycode = keycode
+                    .checked_add(8)
+                    .map(Keycode::new)
+                    .ok_or_else(|| format!("keycode {keycode} is out of range"))?;
+                let state = if pressed {
+                    KeyState::Pressed
+                } else {
+                    KeyState::Released
+                };
+                InputEvent::<InjectedInputBackend>::Keyboard {
+                    event: InjectedKeyEvent {
+                        time,
+                        keycode,
+                        state,
+                    },
+                }
+            }
+            InjectedInput::PointerMotion { dx, dy } => InputEvent::PointerMotion {
+                event: InjectedMotionEvent { time, dx, dy },
+            },
+            InjectedInput::PointerMotionAbsolute { x, y } => {
+                // With no device output, absolute motion maps onto the bounding rectangle of all
+                // outputs, so convert the global position into that.
+                let geo = self
+                    .global_bounding_rectangle()
+                    .ok_or_else(|| String::from("no outputs"))?;
+                let x = (x - f64::from(geo.loc.x)) / f64::from(geo.size.w);
+                let y = (y - f64::from(geo.loc.y)) / f64::from(geo.size.h);
+                InputEvent::PointerMotionAbsolute {
+                    event: InjectedMotionAbsoluteEvent { time, x, y },
+                }
+            }
+            InjectedInput::PointerButton { button, pressed } => {
+                let state = if pressed {
+                    ButtonState::Pressed
+                } else {
+                    ButtonState::Released
+                };
+                InputEvent::PointerButton {
+                    event: InjectedButtonEvent {
+                        time,
+                        button,
+                        state,
+                    },
+                }
+            }
+        };
+
+        self.process_input_event(event);
+        Ok(())
+    }
+}
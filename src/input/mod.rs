@@ -2,12 +2,21 @@ use std::any::Any;
 use std::cmp::min;
 use std::collections::hash_map::Entry;
 use std::collections::HashSet;
+use std::mem;
 use std::time::Duration;
 
 use calloop::timer::{TimeoutAction, Timer};
 use input::event::gesture::GestureEventCoordinates as _;
+use input::event::tablet_pad::{
+    ButtonState as PadButtonState, TabletPadEvent, TabletPadEventTrait as _,
+};
+use niri_config::gestures::TouchEdgeAction;
+use niri_config::input::{TabletRegion, TabletToolType};
+use niri_config::utils::Flag;
 use niri_config::{
-    Action, Bind, Binds, Config, Key, ModKey, Modifiers, MruDirection, SwitchBinds, Trigger,
+    Action, Bind, Binds, Config, ForcePointerConstraint, Key, KeyboardShortcutsInhibit, ModKey,
+    Modifiers, MruDirection, PinchDirection, SwipeDirection, SwitchBinds, TabletPadTrigger,
+    Trigger, WorkspaceReference,
 };
 use niri_ipc::LayoutSwitchTarget;
 use smithay::backend::input::{
@@ -19,8 +28,9 @@ use smithay::backend::input::{
     TabletToolTipState, TouchEvent,
 };
 use smithay::backend::libinput::LibinputInputBackend;
+use smithay::desktop::Window;
 use smithay::input::dnd::DnDGrab;
-use smithay::input::keyboard::{keysyms, Keysym, Layout, ModifiersState};
+use smithay::input::keyboard::{keysyms, FilterResult, Keysym, Layout, ModifiersState};
 use smithay::input::pointer::{
     AxisFrame, ButtonEvent, CursorIcon, CursorImageStatus, Focus, GestureHoldBeginEvent,
     GestureHoldEndEvent, GesturePinchBeginEvent, GesturePinchEndEvent, GesturePinchUpdateEvent,
@@ -34,26 +44,34 @@ use smithay::input::SeatHandler;
 use smithay::output::Output;
 use smithay::reexports::wayland_server::protocol::wl_data_source::WlDataSource;
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
-use smithay::utils::{Logical, Point, Rectangle, Transform, SERIAL_COUNTER};
+use smithay::utils::{Logical, Point, Rectangle, Size, Transform, SERIAL_COUNTER};
 use smithay::wayland::keyboard_shortcuts_inhibit::KeyboardShortcutsInhibitor;
 use smithay::wayland::pointer_constraints::{with_pointer_constraint, PointerConstraint};
+use smithay::wayland::shell::wlr_layer::Layer;
 use smithay::wayland::tablet_manager::{TabletDescriptor, TabletSeatTrait};
+use touch_gesture_grab::{Edge, TouchGestureGrab};
 use touch_overview_grab::TouchOverviewGrab;
 
+use self::injected::{InjectedButtonEvent, InjectedInputBackend, InjectedKeyEvent};
 use self::move_grab::MoveGrab;
 use self::resize_grab::ResizeGrab;
 use self::spatial_movement_grab::SpatialMovementGrab;
+use self::sticky_keys::StickyKeysAction;
+use self::tablet_pad::PadAxis;
 #[cfg(feature = "dbus")]
 use crate::dbus::freedesktop_a11y::KbMonBlock;
+use crate::handlers::WorkaroundDndGrab;
 use crate::layout::scrolling::ScrollDirection;
-use crate::layout::{ActivateWindow, LayoutElement as _};
+use crate::layout::{ActivateWindow, HitType, LayoutElement as _};
 use crate::niri::{CastTarget, PointerVisibility, State};
 use crate::ui::mru::{WindowMru, WindowMruUi};
 use crate::ui::screenshot_ui::ScreenshotUi;
 use crate::utils::spawning::{spawn, spawn_sh};
-use crate::utils::{center, get_monotonic_time, ResizeEdge};
+use crate::utils::{center, get_monotonic_time, output_size, ResizeEdge};
 
 pub mod backend_ext;
+pub mod injected;
+pub mod modifier_taps;
 pub mod move_grab;
 pub mod pick_color_grab;
 pub mod pick_window_grab;
@@ -61,7 +79,10 @@ pub mod resize_grab;
 pub mod scroll_swipe_gesture;
 pub mod scroll_tracker;
 pub mod spatial_movement_grab;
+pub mod sticky_keys;
 pub mod swipe_tracker;
+pub mod tablet_pad;
+pub mod touch_gesture_grab;
 pub mod touch_overview_grab;
 pub mod touch_resize_grab;
 
@@ -69,6 +90,12 @@ use backend_ext::{NiriInputBackend as InputBackend, NiriInputDevice as _};
 
 pub const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
 
+/// How long the first press of a mouse chord waits for the second one.
+const MOUSE_CHORD_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Scale a touchpad pinch must reach to trigger a pinch out bind, or its inverse for pinch in.
+const PINCH_BIND_SCALE: f64 = 1.25;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TabletData {
     pub aspect_ratio: f64,
@@ -182,7 +209,7 @@ impl State {
             TouchCancel { event } => self.on_touch_cancel::<I>(event),
             TouchFrame { event } => self.on_touch_frame::<I>(event),
             SwitchToggle { event } => self.on_switch_toggle::<I>(event),
-            Special(_) => (),
+            Special(event) => self.on_special::<I>(event),
         }
 
         // Don't hide overlays if consumed by a11y, so that you can use the screen reader
@@ -300,27 +327,46 @@ impl State {
     where
         I::Device: 'static,
     {
+        let config = self.niri.config.borrow();
+        let tablet_config = &config.input.tablet;
+
         let device_output = event.device().output(self);
         let device_output = device_output.as_ref();
-        let (target_geo, keep_ratio, px, transform) =
-            if let Some(output) = device_output.or_else(|| self.niri.output_for_tablet()) {
-                (
-                    self.niri.global_space.output_geometry(output).unwrap(),
-                    true,
-                    1. / output.current_scale().fractional_scale(),
-                    output.current_transform(),
-                )
-            } else {
-                let geo = self.global_bounding_rectangle()?;
+        let focused_window = tablet_config
+            .map_to_focused_window
+            .then(|| self.niri.focused_tile_global_rect())
+            .flatten();
+        let (target_geo, keep_ratio, px, transform) = if let Some((output, rect)) = focused_window {
+            (
+                rect.to_i32_round(),
+                true,
+                1. / output.current_scale().fractional_scale(),
+                output.current_transform(),
+            )
+        } else if let Some(output) = device_output.or_else(|| self.niri.output_for_tablet()) {
+            let geo = self.niri.global_space.output_geometry(output).unwrap();
+            (
+                tablet_region(geo, tablet_config.map_to_region),
+                true,
+                1. / output.current_scale().fractional_scale(),
+                output.current_transform(),
+            )
+        } else {
+            let geo = self.global_bounding_rectangle()?;
 
-                // FIXME: this 1 px size should ideally somehow be computed for the rightmost output
-                // corresponding to the position on the right when clamping.
-                let output = self.niri.global_space.outputs().next().unwrap();
-                let scale = output.current_scale().fractional_scale();
+            // FIXME: this 1 px size should ideally somehow be computed for the rightmost output
+            // corresponding to the position on the right when clamping.
+            let output = self.niri.global_space.outputs().next().unwrap();
+            let scale = output.current_scale().fractional_scale();
 
-                // Do not keep ratio for the unified mode as this is what OpenTabletDriver expects.
-                (geo, false, 1. / scale, Transform::Normal)
-            };
+            // Do not keep ratio for the unified mode as this is what OpenTabletDriver expects.
+            (
+                tablet_region(geo, tablet_config.map_to_region),
+                false,
+                1. / scale,
+                Transform::Normal,
+            )
+        };
 
         let mut pos = {
             let size = transform.invert().transform_size(target_geo.size);
@@ -372,6 +418,171 @@ impl State {
         &mut self,
         event: I::KeyboardKeyEvent,
         consumed_by_a11y: &mut bool,
+    ) {
+        let key_code = event.key_code();
+        let pressed = event.state() == KeyState::Pressed;
+
+        if pressed {
+            let delay = self.niri.config.borrow().input.keyboard.slow_keys_delay_ms;
+            if delay > 0 {
+                // Hold back the press until the key is held down for long enough.
+                let timer = Timer::from_duration(Duration::from_millis(u64::from(delay)));
+                let token = self
+                    .niri
+                    .event_loop
+                    .insert_source(timer, move |_, _, state| {
+                        state.niri.slow_keys_timers.remove(&key_code);
+                        let event = InjectedKeyEvent::new(key_code, KeyState::Pressed);
+                        state.on_keyboard_sticky::<InjectedInputBackend>(event, &mut false);
+                        TimeoutAction::Drop
+                    })
+                    .unwrap();
+                if let Some(token) = self.niri.slow_keys_timers.insert(key_code, token) {
+                    self.niri.event_loop.remove(token);
+                }
+                return;
+            }
+        } else if let Some(token) = self.niri.slow_keys_timers.remove(&key_code) {
+            // Released before slow keys accepted the press.
+            self.niri.event_loop.remove(token);
+            return;
+        }
+
+        self.on_keyboard_sticky::<I>(event, consumed_by_a11y);
+    }
+
+    /// Handles a key event that passed slow keys, applying sticky keys.
+    fn on_keyboard_sticky<I: InputBackend>(
+        &mut self,
+        event: I::KeyboardKeyEvent,
+        consumed_by_a11y: &mut bool,
+    ) {
+        if !self.niri.config.borrow().input.keyboard.sticky_keys {
+            self.on_keyboard_key::<I>(event, consumed_by_a11y);
+            return;
+        }
+
+        let key_code = event.key_code();
+        let pressed = event.state() == KeyState::Pressed;
+
+        let keyboard = self.niri.seat.get_keyboard().unwrap();
+        let is_modifier = keyboard.with_xkb_state(self, |context| {
+            let xkb = context.xkb().lock().unwrap();
+            // SAFETY: we're not changing the ref count.
+            let state = unsafe { xkb.state() };
+            state.key_get_one_sym(key_code).is_modifier_key()
+        });
+
+        let action = self
+            .niri
+            .sticky_keys
+            .process_key(key_code, is_modifier, pressed);
+        match action {
+            StickyKeysAction::Pass => self.on_keyboard_key::<I>(event, consumed_by_a11y),
+            StickyKeysAction::Ignore => (),
+            StickyKeysAction::PassAndRelease(key_codes) => {
+                self.on_keyboard_key::<I>(event, consumed_by_a11y);
+                for key_code in key_codes {
+                    self.release_key(key_code);
+                }
+            }
+            StickyKeysAction::Release(key_code) => self.release_key(key_code),
+        }
+    }
+
+    /// Releases a key that niri holds pressed, like a modifier latched by sticky keys.
+    pub fn release_key(&mut self, key_code: Keycode) {
+        let event = InjectedKeyEvent::new(key_code, KeyState::Released);
+        self.on_keyboard_key::<InjectedInputBackend>(event, &mut false);
+    }
+
+    /// Types a key combination, sending it to the focused client without triggering binds.
+    pub fn emulate_key(&mut self, key: Key) {
+        let Trigger::Keysym(keysym) = key.trigger else {
+            warn!("emulate-key needs a keyboard key, not {:?}", key.trigger);
+            return;
+        };
+
+        let mod_key = self.backend.mod_key(&self.niri.config.borrow());
+        let mut modifiers = key.modifiers;
+        if modifiers.contains(Modifiers::COMPOSITOR) {
+            modifiers |= mod_key.to_modifiers();
+        }
+
+        let mut keysyms = [
+            (Modifiers::CTRL, Keysym::Control_L),
+            (Modifiers::SHIFT, Keysym::Shift_L),
+            (Modifiers::ALT, Keysym::Alt_L),
+            (Modifiers::SUPER, Keysym::Super_L),
+            (Modifiers::ISO_LEVEL3_SHIFT, Keysym::ISO_Level3_Shift),
+            (Modifiers::ISO_LEVEL5_SHIFT, Keysym::ISO_Level5_Shift),
+        ]
+        .into_iter()
+        .filter(|(modifier, _)| modifiers.contains(*modifier))
+        .map(|(_, keysym)| keysym)
+        .collect::<Vec<_>>();
+
+        let keyboard = self.niri.seat.get_keyboard().unwrap();
+        let key_codes = keyboard.with_xkb_state(self, |context| {
+            let xkb = context.xkb().lock().unwrap();
+            let layout = xkb.active_layout().0;
+            // SAFETY: we only read the keymap.
+            let keymap = unsafe { xkb.keymap() };
+
+            let codes = keymap.min_keycode().raw()..=keymap.max_keycode().raw();
+            let find = |keysym, level| {
+                codes.clone().map(Keycode::new).find(|&code| {
+                    keymap
+                        .key_get_syms_by_level(code, layout, level)
+                        .contains(&keysym)
+                })
+            };
+
+            // Keysyms like "plus" can be on the second level, then they need Shift.
+            let code = match find(keysym, 0) {
+                Some(code) => code,
+                None => {
+                    if !keysyms.contains(&Keysym::Shift_L) {
+                        keysyms.push(Keysym::Shift_L);
+                    }
+                    find(keysym, 1)?
+                }
+            };
+
+            let mut key_codes = keysyms
+                .iter()
+                .map(|&keysym| find(keysym, 0))
+                .collect::<Option<Vec<_>>>()?;
+            key_codes.push(code);
+            Some(key_codes)
+        });
+        let Some(key_codes) = key_codes else {
+            warn!("the keymap has no key for {key:?}");
+            return;
+        };
+
+        let time = get_monotonic_time().as_millis() as u32;
+        let presses = key_codes.iter().map(|&code| (code, KeyState::Pressed));
+        let releases = key_codes
+            .iter()
+            .rev()
+            .map(|&code| (code, KeyState::Released));
+        for (code, state) in presses.chain(releases) {
+            keyboard.input::<(), _>(
+                self,
+                code,
+                state,
+                SERIAL_COUNTER.next_serial(),
+                time,
+                |_, _, _| FilterResult::Forward,
+            );
+        }
+    }
+
+    fn on_keyboard_key<I: InputBackend>(
+        &mut self,
+        event: I::KeyboardKeyEvent,
+        consumed_by_a11y: &mut bool,
     ) {
         let mod_key = self.backend.mod_key(&self.niri.config.borrow());
 
@@ -475,6 +686,16 @@ impl State {
                     }
                 }
 
+                let tap_bind = this.process_modifier_tap(
+                    key_code,
+                    modified,
+                    raw,
+                    *mods,
+                    pressed,
+                    time,
+                    is_inhibiting_shortcuts,
+                );
+
                 if this.niri.exit_confirm_dialog.is_open() && pressed {
                     if raw == Some(Keysym::Return) {
                         info!("quitting after confirming exit dialog");
@@ -486,6 +707,25 @@ impl State {
                     return ShouldInterceptResult::InterceptOnly;
                 }
 
+                // Keys with modifiers go to the binds, so that e.g. Mod+Left still works.
+                if this.niri.column_grid.is_open() && pressed && modifiers.is_empty() {
+                    if let Some(handled) = raw.map(|raw| this.handle_column_grid_key(raw)) {
+                        if handled {
+                            this.niri.suppressed_keys.insert(key_code);
+                            return ShouldInterceptResult::InterceptOnly;
+                        }
+                    }
+                }
+
+                if this.niri.clipboard_picker.is_open() && pressed {
+                    if let Some(handled) = raw.map(|raw| this.handle_clipboard_picker_key(raw)) {
+                        if handled {
+                            this.niri.suppressed_keys.insert(key_code);
+                            return ShouldInterceptResult::InterceptOnly;
+                        }
+                    }
+                }
+
                 // Check if all modifiers were released while the MRU UI was open. If so, close the
                 // UI (which will also transfer the focus to the current MRU UI selection).
                 if this.niri.window_mru_ui.is_open() && !pressed && modifiers.is_empty() {
@@ -517,10 +757,44 @@ impl State {
                     this.niri.screenshot_ui.set_space_down(pressed);
                 }
 
+                // The next key of a pending sequence either continues it or cancels it.
+                if this.niri.key_sequence_hint.is_pending()
+                    && pressed
+                    && !modified.is_modifier_key()
+                {
+                    let bind = find_bind(
+                        this.niri.key_sequence_hint.binds(),
+                        mod_key,
+                        modified,
+                        raw,
+                        *mods,
+                        this.niri.config.borrow().input.disable_power_key_handling,
+                        pressed,
+                    );
+
+                    let continues = bind
+                        .as_ref()
+                        .is_some_and(|bind| matches!(bind.action, Action::KeySequence(_)));
+                    if !continues {
+                        this.niri.cancel_key_sequence();
+                    }
+
+                    this.niri.suppressed_keys.insert(key_code);
+                    return match bind {
+                        Some(bind) => ShouldInterceptResult::InterceptAndHandle(bind),
+                        None => ShouldInterceptResult::InterceptOnly,
+                    };
+                }
+
                 let res = {
                     let config = this.niri.config.borrow();
-                    let bindings =
-                        make_binds_iter(&config, &mut this.niri.window_mru_ui, modifiers);
+                    let bindings = make_binds_iter(
+                        &config,
+                        &this.niri.ipc_key_grabs,
+                        this.niri.bind_mode.as_deref(),
+                        &mut this.niri.window_mru_ui,
+                        modifiers,
+                    );
 
                     should_intercept_key(
                         &mut this.niri.suppressed_keys,
@@ -553,6 +827,11 @@ impl State {
                     this.niri.mru_apply_keyboard_commit();
                 }
 
+                // A completed multi-tap takes over any single release bind on the same modifier.
+                if let Some(bind) = tap_bind {
+                    return ShouldInterceptResult::ForwardAndHandle(bind);
+                }
+
                 res
             },
         );
@@ -590,6 +869,17 @@ impl State {
             }
             ShouldInterceptResult::InterceptOnly => {}
         }
+
+        // Let an ongoing DnD pick the action for the new modifiers.
+        if mods_changed {
+            let pointer = self.niri.seat.get_pointer().unwrap();
+            let is_dnd_grab = pointer
+                .with_grab(|_, grab| Self::is_dnd_grab(grab.as_any()))
+                .unwrap_or(false);
+            if is_dnd_grab {
+                self.move_cursor(pointer.current_location());
+            }
+        }
     }
 
     fn start_key_repeat(&mut self, bind: Bind) {
@@ -605,14 +895,15 @@ impl State {
         let config = self.niri.config.borrow();
         let config = &config.input.keyboard;
 
-        let repeat_rate = config.repeat_rate;
+        // The bind can override the keyboard repeat settings.
+        let repeat_rate = bind.repeat_rate.unwrap_or(config.repeat_rate);
         if repeat_rate == 0 {
             return;
         }
         let repeat_duration = Duration::from_secs_f64(1. / f64::from(repeat_rate));
 
-        let repeat_timer =
-            Timer::from_duration(Duration::from_millis(u64::from(config.repeat_delay)));
+        let repeat_delay = bind.repeat_delay.unwrap_or(config.repeat_delay);
+        let repeat_timer = Timer::from_duration(Duration::from_millis(u64::from(repeat_delay)));
 
         let token = self
             .niri
@@ -626,6 +917,83 @@ impl State {
         self.niri.bind_repeat_timer = Some(token);
     }
 
+    /// Tracks taps of lone modifier keys, returning a multi-tap bind to run right away.
+    ///
+    /// When binds for more taps of the same modifier exist, the bind for the current number of
+    /// taps is deferred until no more taps follow within the tap interval.
+    #[allow(clippy::too_many_arguments)]
+    fn process_modifier_tap(
+        &mut self,
+        key_code: Keycode,
+        modified: Keysym,
+        raw: Option<Keysym>,
+        mods: ModifiersState,
+        pressed: bool,
+        time: u32,
+        is_inhibiting_shortcuts: bool,
+    ) -> Option<Bind> {
+        if pressed {
+            // A new key press means that the pending bind is not getting any more taps.
+            if let Some(token) = self.niri.modifier_tap_timer.take() {
+                self.niri.event_loop.remove(token);
+            }
+        }
+
+        let config = self.niri.config.borrow();
+        let interval = Duration::from_millis(u64::from(config.input.keyboard.tap_interval_ms));
+        let time = Duration::from_millis(u64::from(time));
+
+        let taps = self.niri.modifier_taps.process_key(
+            key_code,
+            modified.is_modifier_key(),
+            pressed,
+            time,
+            interval,
+        )?;
+        if taps < 2 {
+            return None;
+        }
+
+        let raw = raw?;
+        let mod_key = self.backend.mod_key(&config);
+        let trigger = if mod_key.matches_keysym(raw) {
+            Trigger::KeyCompositor
+        } else {
+            Trigger::Keysym(raw)
+        };
+
+        let bindings = make_binds_iter(
+            &config,
+            &self.niri.ipc_key_grabs,
+            self.niri.bind_mode.as_deref(),
+            &mut self.niri.window_mru_ui,
+            modifiers_from_state(mods),
+        );
+        let (bind, more_taps) = find_tap_bind(bindings, mod_key, trigger, mods, taps);
+        let bind = bind.filter(|bind| !(is_inhibiting_shortcuts && bind.allow_inhibiting));
+
+        if !more_taps {
+            self.niri.modifier_taps.reset();
+            return bind;
+        }
+
+        if let Some(bind) = bind {
+            let timer = Timer::from_duration(interval);
+            let token = self
+                .niri
+                .event_loop
+                .insert_source(timer, move |_, _, state| {
+                    state.niri.modifier_tap_timer = None;
+                    state.handle_bind(bind.clone());
+                    TimeoutAction::Drop
+                })
+                .unwrap();
+            self.niri.modifier_tap_timer = Some(token);
+        }
+
+        None
+    }
+
     fn hide_cursor_if_needed(&mut self) {
         // If the pointer is already invisible, don't reset it back to Hidden causing one frame
         // of hover.
@@ -648,7 +1016,66 @@ impl State {
         self.niri.queue_redraw_all();
     }
 
+    /// Handles a key press while the column grid is open.
+    ///
+    /// Returns `true` if the key was handled by the grid.
+    fn handle_column_grid_key(&mut self, raw: Keysym) -> bool {
+        match raw {
+            Keysym::Left | Keysym::h => self.niri.column_grid.move_selection(-1, 0),
+            Keysym::Right | Keysym::l => self.niri.column_grid.move_selection(1, 0),
+            Keysym::Up | Keysym::k => self.niri.column_grid.move_selection(0, -1),
+            Keysym::Down | Keysym::j => self.niri.column_grid.move_selection(0, 1),
+            Keysym::Return | Keysym::KP_Enter | Keysym::space => {
+                self.confirm_column_grid();
+                return true;
+            }
+            Keysym::Escape => {
+                self.niri.close_column_grid();
+                return true;
+            }
+            _ => return false,
+        }
+
+        self.niri.queue_redraw_column_grid_output();
+        true
+    }
+
+    /// Handles a key press while the clipboard history picker is open.
+    ///
+    /// Returns `true` if the key was handled by the picker.
+    fn handle_clipboard_picker_key(&mut self, raw: Keysym) -> bool {
+        match raw {
+            Keysym::Up | Keysym::k => self.niri.clipboard_picker.move_selection(-1),
+            Keysym::Down | Keysym::j => self.niri.clipboard_picker.move_selection(1),
+            Keysym::Page_Up => self.niri.clipboard_picker.move_selection(-10),
+            Keysym::Page_Down => self.niri.clipboard_picker.move_selection(10),
+            Keysym::Return | Keysym::KP_Enter | Keysym::space => {
+                if let Some(id) = self.niri.close_clipboard_picker() {
+                    self.niri.select_clipboard_history_entry(id);
+                }
+                return true;
+            }
+            Keysym::Escape => {
+                self.niri.close_clipboard_picker();
+                return true;
+            }
+            _ => return false,
+        }
+
+        self.niri.queue_redraw_clipboard_picker_output();
+        true
+    }
+
     pub fn handle_bind(&mut self, bind: Bind) {
+        if let Action::KeySequence(binds) = bind.action {
+            if self.niri.is_locked() && !bind.allow_when_locked {
+                return;
+            }
+
+            self.niri.advance_key_sequence(bind.key, binds);
+            return;
+        }
+
         let Some(cooldown) = bind.cooldown else {
             self.do_action(bind.action, bind.allow_when_locked);
             return;
@@ -718,6 +1145,16 @@ impl State {
             Action::PowerOnMonitors => {
                 self.niri.activate_monitors(&mut self.backend);
             }
+            Action::PowerOffMonitor(output) => {
+                if let Some(output) = self.niri.output_by_name_match(&output).cloned() {
+                    self.niri.power_off_output(&mut self.backend, &output);
+                }
+            }
+            Action::PowerOnMonitor(output) => {
+                if let Some(output) = self.niri.output_by_name_match(&output).cloned() {
+                    self.niri.power_on_output(&mut self.backend, &output);
+                }
+            }
             Action::ToggleDebugTint => {
                 self.backend.toggle_debug_tint();
                 self.niri.queue_redraw_all();
@@ -729,6 +1166,13 @@ impl State {
             Action::DebugToggleDamage => {
                 self.niri.debug_toggle_damage();
             }
+            Action::DebugTogglePremultipliedAlpha => {
+                self.niri.debug_draw_premultiplied_alpha =
+                    !self.niri.debug_draw_premultiplied_alpha;
+                // The tint only changes a shader uniform, which damage tracking can't see.
+                self.backend.reset_damage();
+                self.niri.queue_redraw_all();
+            }
             Action::Spawn(command) => {
                 let (token, _) = self.niri.activation_state.create_external_token(None);
                 spawn(command, Some(token.clone()));
@@ -743,6 +1187,7 @@ impl State {
                 });
             }
             Action::ScreenshotScreen(write_to_disk, show_pointer, path) => {
+                let reply = self.niri.ipc_screenshot_reply.take();
                 let active = self.niri.layout.active_output().cloned();
                 if let Some(active) = active {
                     self.backend.with_primary_renderer(|renderer| {
@@ -752,6 +1197,7 @@ impl State {
                             write_to_disk,
                             show_pointer,
                             path,
+                            reply,
                         ) {
                             warn!("error taking screenshot: {err:?}");
                         }
@@ -780,7 +1226,14 @@ impl State {
                 self.open_screenshot_ui(show_cursor, path);
                 self.niri.cancel_mru();
             }
-            Action::ScreenshotWindow(write_to_disk, show_pointer, path) => {
+            Action::ScreenshotWindow(
+                write_to_disk,
+                show_pointer,
+                include_border,
+                include_shadow,
+                path,
+            ) => {
+                let reply = self.niri.ipc_screenshot_reply.take();
                 let focus = self.niri.layout.focus_with_output();
                 if let Some((mapped, output)) = focus {
                     self.backend.with_primary_renderer(|renderer| {
@@ -790,7 +1243,10 @@ impl State {
                             mapped,
                             write_to_disk,
                             show_pointer,
+                            include_border,
+                            include_shadow,
                             path,
+                            reply,
                         ) {
                             warn!("error taking screenshot: {err:?}");
                         }
@@ -801,8 +1257,11 @@ impl State {
                 id,
                 write_to_disk,
                 show_pointer,
+                include_border,
+                include_shadow,
                 path,
             } => {
+                let reply = self.niri.ipc_screenshot_reply.take();
                 let mut windows = self.niri.layout.windows();
                 let window = windows.find(|(_, m)| m.id().get() == id);
                 if let Some((Some(monitor), mapped)) = window {
@@ -814,7 +1273,10 @@ impl State {
                             mapped,
                             write_to_disk,
                             show_pointer,
+                            include_border,
+                            include_shadow,
                             path,
+                            reply,
                         ) {
                             warn!("error taking screenshot: {err:?}");
                         }
@@ -822,18 +1284,31 @@ impl State {
                 }
             }
             Action::ToggleKeyboardShortcutsInhibit => {
-                if let Some(inhibitor) = self.niri.keyboard_focus.surface().and_then(|surface| {
-                    self.niri
+                if let Some(surface) = self.niri.keyboard_focus.surface() {
+                    if let Some(inhibitor) = self
+                        .niri
                         .keyboard_shortcuts_inhibiting_surfaces
                         .get(surface)
-                }) {
-                    if inhibitor.is_active() {
-                        inhibitor.inactivate();
-                    } else {
-                        inhibitor.activate();
+                    {
+                        if inhibitor.is_active() {
+                            inhibitor.inactivate();
+                        } else if self.niri.keyboard_shortcuts_inhibit_rule(surface)
+                            != Some(KeyboardShortcutsInhibit::Deny)
+                        {
+                            inhibitor.activate();
+                        }
+                        self.niri.keyboard_shortcuts_inhibit_dirty = true;
                     }
                 }
             }
+            Action::ToggleForcedPointerConstraint => {
+                // Only toggle when there's a constraint to release, otherwise the next window
+                // with the rule would start out released.
+                if self.niri.has_forced_pointer_constraint() {
+                    self.niri.forced_pointer_constraint_released =
+                        !self.niri.forced_pointer_constraint_released;
+                }
+            }
             Action::CloseWindow => {
                 if let Some(mapped) = self.niri.layout.focus() {
                     mapped.toplevel().send_close();
@@ -868,6 +1343,23 @@ impl State {
                 self.niri.layout.toggle_group(None);
                 self.niri.queue_redraw_all();
             }
+            Action::ToggleColumnGrid => {
+                if self.niri.column_grid.is_open() {
+                    self.niri.close_column_grid();
+                } else {
+                    self.niri.open_column_grid();
+                }
+            }
+            Action::ToggleClipboardHistory => {
+                if self.niri.clipboard_picker.is_open() {
+                    self.niri.close_clipboard_picker();
+                } else {
+                    self.niri.open_clipboard_picker();
+                }
+            }
+            Action::SelectClipboardHistoryEntry(id) => {
+                self.niri.select_clipboard_history_entry(id);
+            }
             Action::FocusNextWindow => {
                 self.niri.layout.focus_next();
             }
@@ -2073,6 +2565,9 @@ impl State {
             Action::ExpandColumnToAvailableWidth => {
                 self.niri.layout.expand_column_to_available_width();
             }
+            Action::FitColumnWidthToContent => {
+                self.niri.layout.fit_column_width_to_content();
+            }
             Action::ShowHotkeyOverlay => {
                 if self.niri.hotkey_overlay.show() {
                     self.niri.queue_redraw_all();
@@ -2220,7 +2715,7 @@ impl State {
                 // FIXME: granular
                 self.niri.queue_redraw_all();
             }
-            Action::MoveFloatingWindowById { id, x, y } => {
+            Action::MoveFloatingWindowById { id, x, y, animate } => {
                 let window = if let Some(id) = id {
                     let window = self.niri.layout.windows().find(|(_, m)| m.id().get() == id);
                     let window = window.map(|(_, m)| m.window.clone());
@@ -2234,7 +2729,7 @@ impl State {
 
                 self.niri
                     .layout
-                    .move_floating_window(window.as_ref(), x, y, true);
+                    .move_floating_window(window.as_ref(), x, y, animate);
                 // FIXME: granular
                 self.niri.queue_redraw_all();
             }
@@ -2266,6 +2761,19 @@ impl State {
                     }
                 }
             }
+            Action::SetWindowOpacity(opacity) => {
+                self.niri.set_window_opacity_override(None, Some(opacity));
+            }
+            Action::SetWindowOpacityById { id, opacity } => {
+                self.niri
+                    .set_window_opacity_override(Some(id), Some(opacity));
+            }
+            Action::ClearWindowOpacity => {
+                self.niri.set_window_opacity_override(None, None);
+            }
+            Action::ClearWindowOpacityById(id) => {
+                self.niri.set_window_opacity_override(Some(id), None);
+            }
             Action::SetDynamicCastWindow => {
                 let id = self
                     .niri
@@ -2300,11 +2808,25 @@ impl State {
                 self.niri.layout.toggle_overview();
                 self.niri.queue_redraw_all();
             }
+            Action::ToggleOverviewWith { zoom, workspace } => {
+                if self.niri.layout.is_overview_open() {
+                    self.niri.layout.close_overview();
+                } else {
+                    self.open_overview_with(zoom, workspace);
+                }
+                self.niri.queue_redraw_all();
+            }
             Action::OpenOverview => {
                 if self.niri.layout.open_overview() {
                     self.niri.queue_redraw_all();
                 }
             }
+            Action::OpenOverviewWith { zoom, workspace } => {
+                if !self.niri.layout.is_overview_open() {
+                    self.open_overview_with(zoom, workspace);
+                    self.niri.queue_redraw_all();
+                }
+            }
             Action::CloseOverview => {
                 if self.niri.layout.close_overview() {
                     self.niri.queue_redraw_all();
@@ -2344,11 +2866,45 @@ impl State {
                 }
                 self.niri.queue_redraw_all();
             }
+            Action::MarkWindow(mark) => {
+                self.niri.mark_window(None, mark);
+            }
+            Action::MarkWindowById { id, mark } => {
+                self.niri.mark_window(Some(id), mark);
+            }
+            Action::UnmarkWindow(mark) => {
+                self.niri.unmark_window(None, mark.as_deref());
+            }
+            Action::UnmarkWindowById { id, mark } => {
+                self.niri.unmark_window(Some(id), mark.as_deref());
+            }
+            Action::FocusMark(mark) => {
+                if let Some(window) = self.niri.window_by_mark(&mark) {
+                    self.focus_window(&window);
+                }
+            }
+            Action::SwapWithMark(mark) => {
+                let Some(marked) = self.niri.window_by_mark(&mark) else {
+                    return;
+                };
+                let focused = self.niri.layout.focus().map(|mapped| mapped.window.clone());
+                let Some(focused) = focused else {
+                    return;
+                };
+                if self.niri.layout.swap_windows(&focused, &marked) {
+                    self.maybe_warp_cursor_to_focus();
+                    // FIXME: granular
+                    self.niri.queue_redraw_all();
+                }
+            }
             Action::LoadConfigFile => {
                 if let Some(watcher) = &self.niri.config_file_watcher {
                     watcher.load_config();
                 }
             }
+            Action::UnparkWindows => {
+                self.niri.unpark_windows();
+            }
             Action::MruConfirm => {
                 self.confirm_mru();
             }
@@ -2433,9 +2989,71 @@ impl State {
                     self.niri.queue_redraw_mru_output();
                 }
             }
+            Action::IpcKeyGrab(key) => {
+                self.ipc_key_grab_triggered(key);
+            }
+            // Handled in handle_bind() since it needs the bind key.
+            Action::KeySequence(_) => (),
+            Action::Multiple(actions) => {
+                // Run the actions in order, the same way as separate binds would.
+                for action in actions {
+                    self.do_action(action, allow_when_locked);
+                }
+            }
+            Action::EnterBindMode(name) => {
+                self.set_bind_mode(Some(name));
+            }
+            Action::ExitBindMode => {
+                self.set_bind_mode(None);
+            }
+            Action::EmulateKey(key) => {
+                self.emulate_key(key);
+            }
         }
     }
 
+    /// Switches to the binds of a bind mode, or back to the regular binds.
+    pub fn set_bind_mode(&mut self, name: Option<String>) {
+        let config = self.niri.config.borrow();
+        if let Some(name) = &name {
+            if !config.bind_modes.iter().any(|mode| mode.name == *name) {
+                warn!("bind mode {name:?} is not in the config");
+                return;
+            }
+        }
+
+        if self.niri.bind_mode == name {
+            return;
+        }
+
+        let mod_key = self.backend.mod_key(&config);
+        let binds = config.binds_in_mode(name.as_deref());
+        self.niri.mods_with_mouse_binds = mods_with_mouse_binds(mod_key, binds);
+        self.niri.mods_with_wheel_binds = mods_with_wheel_binds(mod_key, binds);
+        self.niri.mods_with_finger_scroll_binds = mods_with_finger_scroll_binds(mod_key, binds);
+        drop(config);
+
+        self.niri.bind_mode = name.clone();
+        self.niri.cancel_key_sequence();
+        self.niri.hotkey_overlay.set_bind_mode(name);
+        self.niri.queue_redraw_all();
+        self.ipc_refresh_bind_mode();
+    }
+
+    fn open_overview_with(&mut self, zoom: Option<f64>, workspace: Option<WorkspaceReference>) {
+        let mut ws_idx = None;
+        if let Some(reference) = workspace {
+            if let Some((output, index)) = self.niri.find_output_and_workspace_index(reference) {
+                if let Some(output) = output {
+                    self.niri.layout.focus_output(&output);
+                }
+                ws_idx = Some(index);
+            }
+        }
+
+        self.niri.layout.open_overview_to_workspace(ws_idx, zoom);
+    }
+
     fn on_pointer_motion<I: InputBackend>(&mut self, event: I::PointerMotionEvent) {
         let was_inside_hot_corner = self.niri.pointer_inside_hot_corner;
         // Any of the early returns here mean that the pointer is not inside the hot corner.
@@ -2463,6 +3081,7 @@ impl State {
         //
         // FIXME: ideally this should use the pointer focus with up-to-date global location.
         let mut pointer_confined = None;
+        let mut pointer_confined_to_window = false;
         if let Some(under) = &self.niri.pointer_contents.surface {
             // No need to check if the pointer focus surface matches, because here we're checking
             // for an already-active constraint, and the constraint is deactivated when the focused
@@ -2493,6 +3112,21 @@ impl State {
                 }
             });
 
+            // Without an active constraint from the client, apply the one forced by a window rule,
+            // unless a grab is moving the pointer.
+            if !pointer_locked && pointer_confined.is_none() && !pointer.is_grabbed() {
+                match self.niri.forced_pointer_constraint() {
+                    Some(ForcePointerConstraint::Lock) => pointer_locked = true,
+                    Some(ForcePointerConstraint::Confine) => {
+                        // Confine to the whole window rather than the surface under the pointer,
+                        // so that the pointer can move between its subsurfaces and popups.
+                        pointer_confined = Some((under.clone(), None));
+                        pointer_confined_to_window = true;
+                    }
+                    None => (),
+                }
+            }
+
             // If the pointer is locked, only send relative motion.
             if pointer_locked {
                 pointer.relative_motion(
@@ -2592,14 +3226,34 @@ impl State {
             }
         }
 
+        if let Some(grid_output) = self.niri.column_grid.output() {
+            if let Some((output, pos_within_output)) = self.niri.output_under(new_pos) {
+                if grid_output == output
+                    && self
+                        .niri
+                        .column_grid
+                        .pointer_motion(pos_within_output)
+                        .is_some()
+                {
+                    self.niri.queue_redraw_column_grid_output();
+                }
+            }
+        }
+
         let under = self.niri.contents_under(new_pos);
 
         // Handle confined pointer.
         if let Some((focus_surface, region)) = pointer_confined {
             let mut prevent = false;
 
-            // Prevent the pointer from leaving the focused surface.
-            if Some(&focus_surface.0) != under.surface.as_ref().map(|(s, _)| s) {
+            if pointer_confined_to_window {
+                // Prevent the pointer from leaving the focused window.
+                let window = self.niri.pointer_contents.window.as_ref().map(|(w, _)| w);
+                if under.window.as_ref().map(|(w, _)| w) != window {
+                    prevent = true;
+                }
+            } else if Some(&focus_surface.0) != under.surface.as_ref().map(|(s, _)| s) {
+                // Prevent the pointer from leaving the focused surface.
                 prevent = true;
             }
 
@@ -2662,13 +3316,14 @@ impl State {
                     .with_grab(|_, grab| grab_allows_hot_corner(grab))
                     .unwrap_or(true)
             {
-                self.niri.layout.toggle_overview();
+                self.trigger_hot_corner();
             }
             self.niri.pointer_inside_hot_corner = true;
         }
 
         // Activate a new confinement if necessary.
         self.niri.maybe_activate_pointer_constraint();
+        self.update_border_resize_cursor();
 
         // Inform the layout of an ongoing DnD operation.
         let is_dnd_grab = pointer
@@ -2729,6 +3384,20 @@ impl State {
             }
         }
 
+        if let Some(grid_output) = self.niri.column_grid.output() {
+            if let Some((output, pos_within_output)) = self.niri.output_under(pos) {
+                if grid_output == output
+                    && self
+                        .niri
+                        .column_grid
+                        .pointer_motion(pos_within_output)
+                        .is_some()
+                {
+                    self.niri.queue_redraw_column_grid_output();
+                }
+            }
+        }
+
         let under = self.niri.contents_under(pos);
 
         self.niri.handle_focus_follows_mouse(&under);
@@ -2755,12 +3424,13 @@ impl State {
                     .with_grab(|_, grab| grab_allows_hot_corner(grab))
                     .unwrap_or(true)
             {
-                self.niri.layout.toggle_overview();
+                self.trigger_hot_corner();
             }
             self.niri.pointer_inside_hot_corner = true;
         }
 
         self.niri.maybe_activate_pointer_constraint();
+        self.update_border_resize_cursor();
 
         // We moved the pointer, show it.
         self.niri.pointer_visibility = PointerVisibility::Visible;
@@ -2784,7 +3454,172 @@ impl State {
         self.niri.queue_redraw_all();
     }
 
+    /// Runs the action of the hot corner under the pointer, after its delay if it has one.
+    fn trigger_hot_corner(&mut self) {
+        if let Some(token) = self.niri.hot_corner_timer.take() {
+            self.niri.event_loop.remove(token);
+        }
+
+        let pos = self.niri.seat.get_pointer().unwrap().current_location();
+        let Some((output, pos_within_output)) = self.niri.output_under(pos) else {
+            return;
+        };
+        let Some((action, delay)) = self.niri.hot_corner_under(output, pos_within_output) else {
+            return;
+        };
+
+        if delay.is_zero() {
+            self.do_action(action, false);
+            return;
+        }
+
+        // Only run the action if the pointer stays in the corner for the whole delay. Entering a
+        // corner again restarts the timer.
+        let timer = Timer::from_duration(delay);
+        let token = self
+            .niri
+            .event_loop
+            .insert_source(timer, move |_, _, state| {
+                state.niri.hot_corner_timer = None;
+                if state.niri.pointer_inside_hot_corner {
+                    state.do_action(action.clone(), false);
+                }
+                TimeoutAction::Drop
+            })
+            .unwrap();
+        self.niri.hot_corner_timer = Some(token);
+    }
+
+    /// Returns the window to resize and its edges if the pointer is on a window border with
+    /// `resize-on-border` enabled.
+    fn border_resize_edges_under_cursor(&self) -> Option<(Window, ResizeEdge)> {
+        let width = self.niri.config.borrow().input.resize_on_border?.width();
+
+        // Only trigger when nothing else would take the click.
+        let under = &self.niri.pointer_contents;
+        if under.hot_corner {
+            return None;
+        }
+        if let Some(layer) = &under.layer {
+            if !matches!(layer.layer(), Layer::Background | Layer::Bottom) {
+                return None;
+            }
+        }
+        match &under.window {
+            None => (),
+            Some((_, HitType::Activate { is_tab_indicator })) if !is_tab_indicator => (),
+            Some(_) => return None,
+        }
+
+        let pos = self.niri.seat.get_pointer().unwrap().current_location();
+        let (output, pos_within_output) = self.niri.output_under(pos)?;
+        let (mapped, edges) =
+            self.niri
+                .layout
+                .border_resize_edges_under(output, pos_within_output, width)?;
+        Some((mapped.window.clone(), edges))
+    }
+
+    /// Shows the resize cursor while the pointer is on a window border.
+    fn update_border_resize_cursor(&mut self) {
+        // Grabs are in charge of the cursor, and reset it when they end.
+        if self.niri.seat.get_pointer().unwrap().is_grabbed() {
+            self.niri.pointer_resize_border_edges = None;
+            return;
+        }
+
+        let edges = self
+            .border_resize_edges_under_cursor()
+            .map(|(_, edges)| edges);
+        if self.niri.pointer_resize_border_edges == edges {
+            return;
+        }
+        self.niri.pointer_resize_border_edges = edges;
+
+        let status = match edges {
+            Some(edges) => CursorImageStatus::Named(edges.cursor_icon()),
+            None => CursorImageStatus::default_named(),
+        };
+        self.niri.cursor_manager.set_cursor_image(status);
+    }
+
     fn on_pointer_button<I: InputBackend>(&mut self, event: I::PointerButtonEvent) {
+        let button_code = event.button_code();
+        let pressed = event.state() == ButtonState::Pressed;
+
+        // The next button event resolves a held back press: either the two buttons make a chord,
+        // or the held press goes through first.
+        if let Some((held, token)) = self.niri.held_chord_press.take() {
+            self.niri.event_loop.remove(token);
+
+            if pressed && self.has_mouse_chord_bind(held, Some(button_code)) {
+                // The chord bind consumes both presses, so the held one never reaches the window.
+                self.niri.pressed_buttons.insert(held);
+                self.niri.suppressed_buttons.insert(held);
+            } else {
+                let event = InjectedButtonEvent::new(held, ButtonState::Pressed);
+                self.on_pointer_button_inner::<InjectedInputBackend>(event);
+            }
+        } else if pressed
+            && self.niri.pressed_buttons.is_empty()
+            && self.has_mouse_chord_bind(button_code, None)
+        {
+            // Hold back the press so that the window doesn't see a click when it starts a chord.
+            let timer = Timer::from_duration(MOUSE_CHORD_TIMEOUT);
+            let token = self
+                .niri
+                .event_loop
+                .insert_source(timer, move |_, _, state| {
+                    state.niri.held_chord_press = None;
+                    let event = InjectedButtonEvent::new(button_code, ButtonState::Pressed);
+                    state.on_pointer_button_inner::<InjectedInputBackend>(event);
+                    TimeoutAction::Drop
+                })
+                .unwrap();
+            self.niri.held_chord_press = Some((button_code, token));
+            return;
+        }
+
+        self.on_pointer_button_inner::<I>(event);
+    }
+
+    /// Returns whether there's a chord bind for the current modifiers with this button.
+    ///
+    /// With `other`, the chord must be made of these two buttons.
+    fn has_mouse_chord_bind(&mut self, button_code: u32, other: Option<u32>) -> bool {
+        let Some(number) = mouse_button_number(button_code) else {
+            return false;
+        };
+        let other = match other.map(mouse_button_number) {
+            Some(None) => return false,
+            other => other.flatten(),
+        };
+
+        let mods = self.niri.seat.get_keyboard().unwrap().modifier_state();
+        let mod_key = self.backend.mod_key(&self.niri.config.borrow());
+        let modifiers = modifiers_with_compositor(mod_key, mods);
+
+        let config = self.niri.config.borrow();
+        let mut bindings = make_binds_iter(
+            &config,
+            &self.niri.ipc_key_grabs,
+            self.niri.bind_mode.as_deref(),
+            &mut self.niri.window_mru_ui,
+            modifiers_from_state(mods),
+        );
+        bindings.any(|bind| {
+            let Trigger::MouseChord(a, b) = bind.key.trigger else {
+                return false;
+            };
+            let matches = match other {
+                Some(other) => (a, b) == (number.min(other), number.max(other)),
+                None => a == number || b == number,
+            };
+            matches && !bind.release && bind.taps == 1 && bind_modifiers(bind, mod_key) == modifiers
+        })
+    }
+
+    fn on_pointer_button_inner<I: InputBackend>(&mut self, event: I::PointerButtonEvent) {
         let pointer = self.niri.seat.get_pointer().unwrap();
 
         let serial = SERIAL_COUNTER.next_serial();
@@ -2797,6 +3632,15 @@ impl State {
 
         let mod_key = self.backend.mod_key(&self.niri.config.borrow());
 
+        let other_pressed_buttons = if ButtonState::Pressed == button_state {
+            let other: Vec<_> = self.niri.pressed_buttons.iter().copied().collect();
+            self.niri.pressed_buttons.insert(button_code);
+            other
+        } else {
+            self.niri.pressed_buttons.remove(&button_code);
+            Vec::new()
+        };
+
         // Ignore release events for mouse clicks that triggered a bind.
         if self.niri.suppressed_buttons.remove(&button_code) {
             return;
@@ -2807,6 +3651,28 @@ impl State {
             let modifiers = modifiers_from_state(mods);
 
             self.niri.valid_release_trigger = None;
+            self.niri.modifier_taps.reset();
+
+            if let Some(grid_output) = self.niri.column_grid.output() {
+                if let Some(MouseButton::Left) = button {
+                    let location = pointer.current_location();
+                    let (output, pos_within_output) = self.niri.output_under(location).unwrap();
+                    if grid_output == output
+                        && self
+                            .niri
+                            .column_grid
+                            .pointer_motion(pos_within_output)
+                            .is_some()
+                    {
+                        self.confirm_column_grid();
+                    } else {
+                        self.niri.close_column_grid();
+                    }
+
+                    self.niri.suppressed_buttons.insert(button_code);
+                    return;
+                }
+            }
 
             let mut is_mru_open = false;
             if let Some(mru_output) = self.niri.window_mru_ui.output() {
@@ -2831,18 +3697,25 @@ impl State {
             }
 
             if is_mru_open || self.niri.mods_with_mouse_binds.contains(&modifiers) {
-                if let Some(bind) = match button {
-                    Some(MouseButton::Left) => Some(Trigger::MouseLeft),
-                    Some(MouseButton::Right) => Some(Trigger::MouseRight),
-                    Some(MouseButton::Middle) => Some(Trigger::MouseMiddle),
-                    Some(MouseButton::Back) => Some(Trigger::MouseBack),
-                    Some(MouseButton::Forward) => Some(Trigger::MouseForward),
-                    _ => None,
-                }
-                .and_then(|trigger| {
+                let number = mouse_button_number(button_code);
+
+                // Chords with a button that is already held take precedence over the button alone.
+                let chords = other_pressed_buttons.iter().filter_map(|&other| {
+                    let a = number?;
+                    let b = mouse_button_number(other)?;
+                    Some(Trigger::MouseChord(a.min(b), a.max(b)))
+                });
+                let single = number.and_then(Trigger::mouse_button);
+
+                if let Some(bind) = chords.chain(single).find_map(|trigger| {
                     let config = self.niri.config.borrow();
-                    let bindings =
-                        make_binds_iter(&config, &mut self.niri.window_mru_ui, modifiers);
+                    let bindings = make_binds_iter(
+                        &config,
+                        &self.niri.ipc_key_grabs,
+                        self.niri.bind_mode.as_deref(),
+                        &mut self.niri.window_mru_ui,
+                        modifiers,
+                    );
                     find_configured_bind(bindings, mod_key, trigger, mods, true)
                 }) {
                     self.niri.suppressed_buttons.insert(button_code);
@@ -2926,15 +3799,49 @@ impl State {
                 }
             }
 
+            // Check if we need to start an interactive resize from a window border.
+            if button == Some(MouseButton::Left) && !pointer.is_grabbed() && !is_overview_open {
+                if let Some((window, edges)) = self.border_resize_edges_under_cursor() {
+                    self.niri.layout.activate_window(&window);
+
+                    if self
+                        .niri
+                        .layout
+                        .interactive_resize_begin(window.clone(), edges)
+                    {
+                        let start_data = PointerGrabStartData {
+                            focus: None,
+                            button: button_code,
+                            location: pointer.current_location(),
+                        };
+                        let grab = ResizeGrab::new(start_data, window);
+                        pointer.set_grab(self, grab, serial, Focus::Clear);
+                        self.niri
+                            .cursor_manager
+                            .set_cursor_image(CursorImageStatus::Named(edges.cursor_icon()));
+                    }
+
+                    // FIXME: granular.
+                    self.niri.queue_redraw_all();
+                    return;
+                }
+            }
+
             if let Some(mapped) = self.niri.window_under_cursor() {
                 let window = mapped.window.clone();
 
                 // Check if we need to start an interactive move.
                 if button == Some(MouseButton::Left) && !pointer.is_grabbed() {
-                    let mod_down = modifiers_from_state(mods).contains(mod_key.to_modifiers());
+                    let modifiers = modifiers_from_state(mods);
+                    let mod_down = modifiers.contains(mod_key.to_modifiers());
                     if is_overview_open || mod_down {
                         let location = pointer.current_location();
 
+                        // Holding Ctrl in addition to Mod drags the whole column.
+                        let move_whole_column = mod_down
+                            && modifiers.contains(Modifiers::CTRL)
+                            && !mod_key.to_modifiers().contains(Modifiers::CTRL);
+
                         if !is_overview_open {
                             self.niri.layout.activate_window(&window);
                         }
@@ -2946,9 +3853,14 @@ impl State {
                         };
                         let start_data = PointerOrTouchStartData::Pointer(start_data);
                         let icon = CursorIcon::Grabbing;
-                        if let Some(grab) =
-                            MoveGrab::new(self, start_data, window.clone(), false, Some(icon))
-                        {
+                        if let Some(grab) = MoveGrab::new(
+                            self,
+                            start_data,
+                            window.clone(),
+                            false,
+                            move_whole_column,
+                            Some(icon),
+                        ) {
                             pointer.set_grab(self, grab, serial, Focus::Clear);
 
                             // Set the cursor to Grabbing right away for Mod+LMB since it doesn't
@@ -3174,7 +4086,10 @@ impl State {
                                 },
                                 action: Action::FocusColumnLeftUnderMouse,
                                 repeat: true,
+                                repeat_delay: None,
+                                repeat_rate: None,
                                 release: false,
+                                taps: 1,
                                 cooldown: None,
                                 allow_when_locked: false,
                                 allow_inhibiting: false,
@@ -3188,7 +4103,10 @@ impl State {
                                 },
                                 action: Action::FocusColumnRightUnderMouse,
                                 repeat: true,
+                                repeat_delay: None,
+                                repeat_rate: None,
                                 release: false,
+                                taps: 1,
                                 cooldown: None,
                                 allow_when_locked: false,
                                 allow_inhibiting: false,
@@ -3198,8 +4116,13 @@ impl State {
                             (bind_left, bind_right)
                         } else {
                             let config = self.niri.config.borrow();
-                            let bindings =
-                                make_binds_iter(&config, &mut self.niri.window_mru_ui, modifiers);
+                            let bindings = make_binds_iter(
+                                &config,
+                                &self.niri.ipc_key_grabs,
+                                self.niri.bind_mode.as_deref(),
+                                &mut self.niri.window_mru_ui,
+                                modifiers,
+                            );
                             let bind_left = find_configured_bind(
                                 bindings.clone(),
                                 mod_key,
@@ -3241,7 +4164,10 @@ impl State {
                             },
                             action: Action::FocusWorkspaceUpUnderMouse,
                             repeat: true,
+                            repeat_delay: None,
+                            repeat_rate: None,
                             release: false,
+                            taps: 1,
                             cooldown: Some(Duration::from_millis(50)),
                             allow_when_locked: false,
                             allow_inhibiting: false,
@@ -3255,7 +4181,10 @@ impl State {
                             },
                             action: Action::FocusWorkspaceDownUnderMouse,
                             repeat: true,
+                            repeat_delay: None,
+                            repeat_rate: None,
                             release: false,
+                            taps: 1,
                             cooldown: Some(Duration::from_millis(50)),
                             allow_when_locked: false,
                             allow_inhibiting: false,
@@ -3271,7 +4200,10 @@ impl State {
                             },
                             action: Action::FocusColumnLeftUnderMouse,
                             repeat: true,
+                            repeat_delay: None,
+                            repeat_rate: None,
                             release: false,
+                            taps: 1,
                             cooldown: Some(Duration::from_millis(50)),
                             allow_when_locked: false,
                             allow_inhibiting: false,
@@ -3285,7 +4217,10 @@ impl State {
                             },
                             action: Action::FocusColumnRightUnderMouse,
                             repeat: true,
+                            repeat_delay: None,
+                            repeat_rate: None,
                             release: false,
+                            taps: 1,
                             cooldown: Some(Duration::from_millis(50)),
                             allow_when_locked: false,
                             allow_inhibiting: false,
@@ -3295,8 +4230,13 @@ impl State {
                         (bind_up, bind_down)
                     } else {
                         let config = self.niri.config.borrow();
-                        let bindings =
-                            make_binds_iter(&config, &mut self.niri.window_mru_ui, modifiers);
+                        let bindings = make_binds_iter(
+                            &config,
+                            &self.niri.ipc_key_grabs,
+                            self.niri.bind_mode.as_deref(),
+                            &mut self.niri.window_mru_ui,
+                            modifiers,
+                        );
                         let bind_up = find_configured_bind(
                             bindings.clone(),
                             mod_key,
@@ -3447,8 +4387,13 @@ impl State {
                     .accumulate(horizontal);
                 if ticks != 0 {
                     let config = self.niri.config.borrow();
-                    let bindings =
-                        make_binds_iter(&config, &mut self.niri.window_mru_ui, modifiers);
+                    let bindings = make_binds_iter(
+                        &config,
+                        &self.niri.ipc_key_grabs,
+                        self.niri.bind_mode.as_deref(),
+                        &mut self.niri.window_mru_ui,
+                        modifiers,
+                    );
                     let bind_left = find_configured_bind(
                         bindings.clone(),
                         mod_key,
@@ -3483,8 +4428,13 @@ impl State {
                     .accumulate(vertical);
                 if ticks != 0 {
                     let config = self.niri.config.borrow();
-                    let bindings =
-                        make_binds_iter(&config, &mut self.niri.window_mru_ui, modifiers);
+                    let bindings = make_binds_iter(
+                        &config,
+                        &self.niri.ipc_key_grabs,
+                        self.niri.bind_mode.as_deref(),
+                        &mut self.niri.window_mru_ui,
+                        modifiers,
+                    );
                     let bind_up = find_configured_bind(
                         bindings.clone(),
                         mod_key,
@@ -3623,6 +4573,20 @@ impl State {
             }
         }
 
+        if let Some(grid_output) = self.niri.column_grid.output() {
+            if let Some((output, pos_within_output)) = self.niri.output_under(pos) {
+                if grid_output == output
+                    && self
+                        .niri
+                        .column_grid
+                        .pointer_motion(pos_within_output)
+                        .is_some()
+                {
+                    self.niri.queue_redraw_column_grid_output();
+                }
+            }
+        }
+
         let under = self.niri.contents_under(pos);
 
         let tablet_seat = self.niri.seat.tablet_seat();
@@ -3630,7 +4594,15 @@ impl State {
         let tool = tablet_seat.get_tool(&event.tool());
         if let (Some(tablet), Some(tool)) = (tablet, tool) {
             if event.pressure_has_changed() {
-                tool.pressure(event.pressure());
+                let mut pressure = event.pressure();
+
+                let config = self.niri.config.borrow();
+                let tool_type = tablet_tool_type(event.tool().tool_type);
+                if let Some(curve) = config.input.tablet.pressure_curve(tool_type) {
+                    pressure = curve.apply(pressure);
+                }
+
+                tool.pressure(pressure);
             }
             if event.distance_has_changed() {
                 tool.distance(event.distance());
@@ -3826,18 +4798,87 @@ impl State {
         }
     }
 
+    fn on_special<I: InputBackend + 'static>(&mut self, event: I::SpecialEvent) {
+        // Tablet pads only come from libinput, as special events.
+        let event = (&event as &dyn Any).downcast_ref::<input::Event>();
+        if let Some(input::Event::TabletPad(event)) = event {
+            self.on_tablet_pad(event);
+        }
+    }
+
+    fn on_tablet_pad(&mut self, event: &TabletPadEvent) {
+        let mode = event.mode();
+        let triggers = match event {
+            TabletPadEvent::Button(event) => {
+                if event.button_state() != PadButtonState::Pressed {
+                    return;
+                }
+                vec![TabletPadTrigger::Button(event.button_number())]
+            }
+            TabletPadEvent::Ring(event) => {
+                let number = event.number();
+                let axes = &mut self.niri.tablet_pad_axes;
+                let steps = axes.update(PadAxis::Ring, number, event.position());
+                let trigger = if steps > 0 {
+                    TabletPadTrigger::RingClockwise(number)
+                } else {
+                    TabletPadTrigger::RingCounterClockwise(number)
+                };
+                vec![trigger; steps.unsigned_abs() as usize]
+            }
+            TabletPadEvent::Strip(event) => {
+                let number = event.number();
+                let axes = &mut self.niri.tablet_pad_axes;
+                let steps = axes.update(PadAxis::Strip, number, event.position());
+                let trigger = if steps > 0 {
+                    TabletPadTrigger::StripDown(number)
+                } else {
+                    TabletPadTrigger::StripUp(number)
+                };
+                vec![trigger; steps.unsigned_abs() as usize]
+            }
+            _ => return,
+        };
+
+        for trigger in triggers {
+            let action = {
+                let config = self.niri.config.borrow();
+                let bind = config.tablet_pad_binds.find(trigger, mode);
+                bind.map(|bind| bind.action.clone())
+            };
+
+            if let Some(action) = action {
+                self.do_action(action, false);
+            }
+        }
+    }
+
     fn on_gesture_swipe_begin<I: InputBackend>(&mut self, event: I::GestureSwipeBeginEvent) {
         if self.niri.window_mru_ui.is_open() {
             // Don't start swipe gestures while in the MRU.
             return;
         }
 
-        if event.fingers() == 3 {
-            self.niri.gesture_swipe_3f_cumulative = Some((0., 0.));
+        let fingers = u8::try_from(event.fingers()).unwrap_or(u8::MAX);
+        let has_binds = [
+            SwipeDirection::Up,
+            SwipeDirection::Down,
+            SwipeDirection::Left,
+            SwipeDirection::Right,
+        ]
+        .into_iter()
+        .any(|direction| {
+            self.find_gesture_bind(Trigger::TouchpadSwipe(fingers, direction))
+                .is_some()
+        });
+
+        if fingers == 3 || has_binds {
+            // Wait until the gesture moves far enough to know its direction.
+            self.niri.gesture_swipe_pending = Some((fingers, 0., 0.));
 
             // We handled this event.
             return;
-        } else if event.fingers() == 4 {
+        } else if fingers == 4 {
             self.niri.layout.overview_gesture_begin();
             self.niri.queue_redraw_all();
 
@@ -3878,6 +4919,7 @@ impl State {
             delta_y = libinput_event.dy_unaccelerated();
         }
 
+        let uninverted_delta_x = delta_x;
         let uninverted_delta_y = delta_y;
 
         let device = event.device();
@@ -3890,43 +4932,78 @@ impl State {
 
         let is_overview_open = self.niri.layout.is_overview_open();
 
-        if let Some((cx, cy)) = &mut self.niri.gesture_swipe_3f_cumulative {
-            *cx += delta_x;
-            *cy += delta_y;
+        if let Some((fingers, cx, cy)) = &mut self.niri.gesture_swipe_pending {
+            // Use the deltas without natural scroll to get the direction the fingers move in.
+            *cx += uninverted_delta_x;
+            *cy += uninverted_delta_y;
 
             // Check if the gesture moved far enough to decide. Threshold copied from GNOME Shell.
-            let (cx, cy) = (*cx, *cy);
+            let (fingers, cx, cy) = (*fingers, *cx, *cy);
             if cx * cx + cy * cy >= 16. * 16. {
-                self.niri.gesture_swipe_3f_cumulative = None;
+                self.niri.gesture_swipe_pending = None;
 
-                if let Some(output) = self.niri.output_under_cursor() {
-                    if cx.abs() > cy.abs() {
-                        let output_ws = if is_overview_open {
-                            self.niri.workspace_under_cursor(true)
-                        } else {
-                            // We don't want to accidentally "catch" the wrong workspace during
-                            // animations.
-                            self.niri.output_under_cursor().and_then(|output| {
-                                let mon = self.niri.layout.monitor_for_output(&output)?;
-                                Some((output, mon.active_workspace_ref()))
-                            })
-                        };
+                let direction = if cx.abs() > cy.abs() {
+                    if cx > 0. {
+                        SwipeDirection::Right
+                    } else {
+                        SwipeDirection::Left
+                    }
+                } else if cy > 0. {
+                    SwipeDirection::Down
+                } else {
+                    SwipeDirection::Up
+                };
 
-                        if let Some((output, ws)) = output_ws {
-                            let ws_idx = self.niri.layout.find_workspace_by_id(ws.id()).unwrap().0;
+                if let Some(bind) =
+                    self.find_gesture_bind(Trigger::TouchpadSwipe(fingers, direction))
+                {
+                    self.niri.gesture_swallowed = true;
+                    self.handle_bind(bind);
+                } else if fingers == 3 {
+                    if let Some(output) = self.niri.output_under_cursor() {
+                        if cx.abs() > cy.abs() {
+                            let output_ws = if is_overview_open {
+                                self.niri.workspace_under_cursor(true)
+                            } else {
+                                // We don't want to accidentally "catch" the wrong workspace during
+                                // animations.
+                                self.niri.output_under_cursor().and_then(|output| {
+                                    let mon = self.niri.layout.monitor_for_output(&output)?;
+                                    Some((output, mon.active_workspace_ref()))
+                                })
+                            };
+
+                            if let Some((output, ws)) = output_ws {
+                                let ws_idx =
+                                    self.niri.layout.find_workspace_by_id(ws.id()).unwrap().0;
+                                self.niri.layout.view_offset_gesture_begin(
+                                    &output,
+                                    Some(ws_idx),
+                                    true,
+                                );
+                            }
+                        } else {
                             self.niri
                                 .layout
-                                .view_offset_gesture_begin(&output, Some(ws_idx), true);
+                                .workspace_switch_gesture_begin(&output, true);
                         }
-                    } else {
-                        self.niri
-                            .layout
-                            .workspace_switch_gesture_begin(&output, true);
                     }
+                } else if fingers == 4 {
+                    self.niri.layout.overview_gesture_begin();
+                    self.niri.queue_redraw_all();
+                } else {
+                    // The gesture began without being sent to the client, so don't send the rest
+                    // of it either.
+                    self.niri.gesture_swallowed = true;
                 }
             }
         }
 
+        if self.niri.gesture_swipe_pending.is_some() || self.niri.gesture_swallowed {
+            // We handled this event.
+            return;
+        }
+
         let timestamp = Duration::from_micros(event.time());
 
         let mut handled = false;
@@ -3984,7 +5061,11 @@ impl State {
     }
 
     fn on_gesture_swipe_end<I: InputBackend>(&mut self, event: I::GestureSwipeEndEvent) {
-        self.niri.gesture_swipe_3f_cumulative = None;
+        let pending = self.niri.gesture_swipe_pending.take();
+        if pending.is_some() || mem::take(&mut self.niri.gesture_swallowed) {
+            // We handled this event.
+            return;
+        }
 
         let mut handled = false;
         let res = self.niri.layout.workspace_switch_gesture_end(Some(true));
@@ -4028,6 +5109,48 @@ impl State {
     }
 
     fn on_gesture_pinch_begin<I: InputBackend>(&mut self, event: I::GesturePinchBeginEvent) {
+        let fingers = u8::try_from(event.fingers()).unwrap_or(u8::MAX);
+        let has_binds = [PinchDirection::In, PinchDirection::Out]
+            .into_iter()
+            .any(|direction| {
+                self.find_gesture_bind(Trigger::TouchpadPinch(fingers, direction))
+                    .is_some()
+            });
+        if has_binds {
+            // Binds take precedence over the pinch column width gesture.
+            self.niri.gesture_pinch_pending = Some(fingers);
+
+            // We handled this event.
+            return;
+        }
+
+        let config = self.niri.config.borrow().gestures.pinch_column_width;
+        if config.on
+            && event.fingers() == u32::from(config.fingers)
+            && !self.niri.window_mru_ui.is_open()
+            && !self.niri.layout.is_overview_open()
+        {
+            let window = self
+                .niri
+                .layout
+                .focus()
+                .filter(|mapped| !mapped.is_floating())
+                .map(|mapped| mapped.window.clone());
+
+            if let Some(window) = window {
+                if self
+                    .niri
+                    .layout
+                    .interactive_resize_begin(window.clone(), ResizeEdge::RIGHT)
+                {
+                    self.niri.gesture_pinch_resize_window = Some(window);
+
+                    // We handled this event.
+                    return;
+                }
+            }
+        }
+
         let serial = SERIAL_COUNTER.next_serial();
         let pointer = self.niri.seat.get_pointer().unwrap();
 
@@ -4046,6 +5169,52 @@ impl State {
     }
 
     fn on_gesture_pinch_update<I: InputBackend>(&mut self, event: I::GesturePinchUpdateEvent) {
+        if let Some(fingers) = self.niri.gesture_pinch_pending {
+            let scale = event.scale();
+            let direction = if scale >= PINCH_BIND_SCALE {
+                Some(PinchDirection::Out)
+            } else if scale <= 1. / PINCH_BIND_SCALE {
+                Some(PinchDirection::In)
+            } else {
+                None
+            };
+
+            if let Some(direction) = direction {
+                self.niri.gesture_pinch_pending = None;
+                self.niri.gesture_swallowed = true;
+
+                if let Some(bind) =
+                    self.find_gesture_bind(Trigger::TouchpadPinch(fingers, direction))
+                {
+                    self.handle_bind(bind);
+                }
+            }
+
+            // We handled this event.
+            return;
+        }
+
+        if self.niri.gesture_swallowed {
+            // We handled this event.
+            return;
+        }
+
+        if let Some(window) = self.niri.gesture_pinch_resize_window.clone() {
+            let config = self.niri.config.borrow().gestures.pinch_column_width;
+            let ongoing = self.niri.layout.interactive_resize_scale_width(
+                &window,
+                event.scale(),
+                config.snap_distance,
+            );
+            if !ongoing {
+                self.niri.layout.interactive_resize_end(&window);
+                self.niri.gesture_pinch_resize_window = None;
+            }
+
+            // We handled this event.
+            return;
+        }
+
         let pointer = self.niri.seat.get_pointer().unwrap();
 
         if self.update_pointer_contents() {
@@ -4064,6 +5233,19 @@ impl State {
     }
 
     fn on_gesture_pinch_end<I: InputBackend>(&mut self, event: I::GesturePinchEndEvent) {
+        let pending = self.niri.gesture_pinch_pending.take();
+        if pending.is_some() || mem::take(&mut self.niri.gesture_swallowed) {
+            // We handled this event.
+            return;
+        }
+
+        if let Some(window) = self.niri.gesture_pinch_resize_window.take() {
+            self.niri.layout.interactive_resize_end(&window);
+
+            // We handled this event.
+            return;
+        }
+
         let serial = SERIAL_COUNTER.next_serial();
         let pointer = self.niri.seat.get_pointer().unwrap();
 
@@ -4081,6 +5263,22 @@ impl State {
         );
     }
 
+    /// Finds the bind for a touchpad gesture with the currently pressed modifiers.
+    fn find_gesture_bind(&mut self, trigger: Trigger) -> Option<Bind> {
+        let mod_key = self.backend.mod_key(&self.niri.config.borrow());
+        let mods = self.niri.seat.get_keyboard().unwrap().modifier_state();
+
+        let config = self.niri.config.borrow();
+        let bindings = make_binds_iter(
+            &config,
+            &self.niri.ipc_key_grabs,
+            self.niri.bind_mode.as_deref(),
+            &mut self.niri.window_mru_ui,
+            modifiers_from_state(mods),
+        );
+        find_configured_bind(bindings, mod_key, trigger, mods, true)
+    }
+
     fn on_gesture_hold_begin<I: InputBackend>(&mut self, event: I::GestureHoldBeginEvent) {
         let serial = SERIAL_COUNTER.next_serial();
         let pointer = self.niri.seat.get_pointer().unwrap();
@@ -4197,6 +5395,8 @@ impl State {
             let mods = modifiers_from_state(mods);
             let mod_down = mods.contains(mod_key.to_modifiers());
 
+            let long_press_window = under.window.as_ref().map(|(window, _)| window.clone());
+
             if self.niri.layout.is_overview_open()
                 && !mod_down
                 && under.layer.is_none()
@@ -4243,7 +5443,8 @@ impl State {
                         location: pos,
                     };
                     let start_data = PointerOrTouchStartData::Touch(start_data);
-                    if let Some(grab) = MoveGrab::new(self, start_data, window.clone(), true, None)
+                    if let Some(grab) =
+                        MoveGrab::new(self, start_data, window.clone(), true, false, None)
                     {
                         handle.set_grab(self, grab, serial);
                     }
@@ -4257,6 +5458,45 @@ impl State {
                 // FIXME: granular.
                 self.niri.queue_redraw_all();
             }
+
+            // Check if we need to start recognizing edge swipes and long presses.
+            if !handle.is_grabbed() && !self.niri.layout.is_overview_open() {
+                if let Some((output, pos_within_output)) = self.niri.output_under(pos) {
+                    let output = output.clone();
+                    let gestures = self.niri.config.borrow().gestures.clone();
+
+                    let edge_swipe = gestures.touch_edge_swipe;
+                    let trigger_size = edge_swipe.trigger_size;
+                    let edge = Edge::at(pos_within_output, output_size(&output), trigger_size)
+                        .filter(|_| edge_swipe.on)
+                        .map(|edge| (edge, edge.action(&edge_swipe)))
+                        .filter(|(_, action)| *action != TouchEdgeAction::None);
+
+                    let long_press = gestures.touch_long_press_move;
+                    let delay = Duration::from_millis(u64::from(long_press.delay_ms));
+                    let long_press = long_press_window
+                        .filter(|_| long_press.on)
+                        .map(|window| (window, delay));
+
+                    if edge.is_some() || long_press.is_some() {
+                        let start_data = TouchGrabStartData {
+                            focus: None,
+                            slot,
+                            location: pos,
+                        };
+                        let grab = TouchGestureGrab::new(
+                            &self.niri.event_loop,
+                            start_data,
+                            output,
+                            pos_within_output,
+                            edge,
+                            long_press,
+                        );
+                        handle.set_grab(self, grab, serial);
+                    }
+                }
+            }
+
             self.niri.focus_layer_surface_if_on_demand(under.layer);
         };
 
@@ -4382,8 +5622,10 @@ impl State {
     pub fn is_dnd_grab(grab: &dyn Any) -> bool {
         // Normal DnD
         grab.is::<DnDGrab<Self, WlDataSource, WlSurface>>()
+            || grab.is::<WorkaroundDndGrab<WlDataSource>>()
             // Null-source DnD: weston-dnd --self-only
             || grab.is::<DnDGrab<Self, WlSurface, WlSurface>>()
+            || grab.is::<WorkaroundDndGrab<WlSurface>>()
     }
 }
 
@@ -4447,7 +5689,10 @@ fn should_intercept_key<'a>(
                     },
                     action,
                     repeat: true,
+                    repeat_delay: None,
+                    repeat_rate: None,
                     release,
+                    taps: 1,
                     cooldown: None,
                     allow_when_locked: false,
                     // The screenshot UI owns the focus anyway, so this doesn't really matter.
@@ -4545,7 +5790,10 @@ fn find_bind<'a>(
             },
             action,
             repeat: true,
+            repeat_delay: None,
+            repeat_rate: None,
             release: false,
+            taps: 1,
             cooldown: None,
             allow_when_locked: false,
             // In a worst-case scenario, the user has no way to unlock the compositor and a
@@ -4585,30 +5833,23 @@ fn find_configured_bind<'a>(
     pressed: bool,
 ) -> Option<Bind> {
     // Handle configured binds.
-    let mut modifiers = modifiers_from_state(mods);
-
-    let mod_down = modifiers_from_state(mods).contains(mod_key.to_modifiers());
-    if mod_down {
-        modifiers |= Modifiers::COMPOSITOR;
-    }
+    let modifiers = modifiers_with_compositor(mod_key, mods);
 
     for bind in bindings {
         if bind.release == pressed {
             continue;
         }
 
-        if bind.key.trigger != trigger {
+        // Multi-tap binds are handled separately.
+        if bind.taps > 1 {
             continue;
         }
 
-        let mut bind_modifiers = bind.key.modifiers;
-        if bind_modifiers.contains(Modifiers::COMPOSITOR) {
-            bind_modifiers |= mod_key.to_modifiers();
-        } else if bind_modifiers.contains(mod_key.to_modifiers()) {
-            bind_modifiers |= Modifiers::COMPOSITOR;
+        if bind.key.trigger != trigger {
+            continue;
         }
 
-        if bind_modifiers == modifiers {
+        if bind_modifiers(bind, mod_key) == modifiers {
             return Some(bind.clone());
         }
     }
@@ -4616,6 +5857,57 @@ fn find_configured_bind<'a>(
     None
 }
 
+/// Finds the bind for the given number of taps of a lone modifier.
+///
+/// Also returns whether there are binds for more taps of the same modifier.
+fn find_tap_bind<'a>(
+    bindings: impl IntoIterator<Item = &'a Bind>,
+    mod_key: ModKey,
+    trigger: Trigger,
+    mods: ModifiersState,
+    taps: u8,
+) -> (Option<Bind>, bool) {
+    let modifiers = modifiers_with_compositor(mod_key, mods);
+
+    let mut found = None;
+    let mut more_taps = false;
+    for bind in bindings {
+        if bind.taps < 2 || bind.key.trigger != trigger {
+            continue;
+        }
+
+        if bind_modifiers(bind, mod_key) != modifiers {
+            continue;
+        }
+
+        if bind.taps == taps {
+            found.get_or_insert_with(|| bind.clone());
+        } else if bind.taps > taps {
+            more_taps = true;
+        }
+    }
+
+    (found, more_taps)
+}
+
+fn modifiers_with_compositor(mod_key: ModKey, mods: ModifiersState) -> Modifiers {
+    let mut modifiers = modifiers_from_state(mods);
+    if modifiers.contains(mod_key.to_modifiers()) {
+        modifiers |= Modifiers::COMPOSITOR;
+    }
+    modifiers
+}
+
+fn bind_modifiers(bind: &Bind, mod_key: ModKey) -> Modifiers {
+    let mut modifiers = bind.key.modifiers;
+    if modifiers.contains(Modifiers::COMPOSITOR) {
+        modifiers |= mod_key.to_modifiers();
+    } else if modifiers.contains(mod_key.to_modifiers()) {
+        modifiers |= Modifiers::COMPOSITOR;
+    }
+    modifiers
+}
+
 fn find_configured_switch_action(
     bindings: &SwitchBinds,
     switch: Switch,
@@ -4736,6 +6028,8 @@ fn allowed_when_locked(action: &Action) -> bool {
             | Action::Suspend
             | Action::PowerOffMonitors
             | Action::PowerOnMonitors
+            | Action::PowerOffMonitor(_)
+            | Action::PowerOnMonitor(_)
             | Action::SwitchLayout(_)
             | Action::ToggleKeyboardShortcutsInhibit
     )
@@ -4749,6 +6043,8 @@ fn allowed_during_screenshot(action: &Action) -> bool {
             | Action::Suspend
             | Action::PowerOffMonitors
             | Action::PowerOnMonitors
+            | Action::PowerOffMonitor(_)
+            | Action::PowerOnMonitor(_)
             // The screenshot UI can handle these.
             | Action::MoveColumnLeft
             | Action::MoveColumnLeftOrToMonitorLeft
@@ -4806,7 +6102,10 @@ fn hardcoded_overview_bind(raw: Keysym, mods: ModifiersState) -> Option<Bind> {
         },
         action,
         repeat,
+        repeat_delay: None,
+        repeat_rate: None,
         release: false,
+        taps: 1,
         cooldown: None,
         allow_when_locked: false,
         allow_inhibiting: false,
@@ -4853,27 +6152,13 @@ pub fn apply_libinput_settings(config: &niri_config::Input, device: &mut input::
             let _ = device.config_scroll_set_method(method.into());
 
             if method == niri_config::ScrollMethod::OnButtonDown {
-                if let Some(button) = c.scroll_button {
-                    let _ = device.config_scroll_set_button(button);
-                }
-                let _ = device.config_scroll_set_button_lock(if c.scroll_button_lock {
-                    input::ScrollButtonLockState::Enabled
-                } else {
-                    input::ScrollButtonLockState::Disabled
-                });
+                apply_scroll_button_settings(device, c.scroll_button, c.scroll_button_lock);
             }
         } else if let Some(default) = device.config_scroll_default_method() {
             let _ = device.config_scroll_set_method(default);
 
             if default == input::ScrollMethod::OnButtonDown {
-                if let Some(button) = c.scroll_button {
-                    let _ = device.config_scroll_set_button(button);
-                }
-                let _ = device.config_scroll_set_button_lock(if c.scroll_button_lock {
-                    input::ScrollButtonLockState::Enabled
-                } else {
-                    input::ScrollButtonLockState::Disabled
-                });
+                apply_scroll_button_settings(device, c.scroll_button, c.scroll_button_lock);
             }
         }
 
@@ -4931,27 +6216,13 @@ pub fn apply_libinput_settings(config: &niri_config::Input, device: &mut input::
             let _ = device.config_scroll_set_method(method.into());
 
             if method == niri_config::ScrollMethod::OnButtonDown {
-                if let Some(button) = c.scroll_button {
-                    let _ = device.config_scroll_set_button(button);
-                }
-                let _ = device.config_scroll_set_button_lock(if c.scroll_button_lock {
-                    input::ScrollButtonLockState::Enabled
-                } else {
-                    input::ScrollButtonLockState::Disabled
-                });
+                apply_scroll_button_settings(device, c.scroll_button, c.scroll_button_lock);
             }
         } else if let Some(default) = device.config_scroll_default_method() {
             let _ = device.config_scroll_set_method(default);
 
             if default == input::ScrollMethod::OnButtonDown {
-                if let Some(button) = c.scroll_button {
-                    let _ = device.config_scroll_set_button(button);
-                }
-                let _ = device.config_scroll_set_button_lock(if c.scroll_button_lock {
-                    input::ScrollButtonLockState::Enabled
-                } else {
-                    input::ScrollButtonLockState::Disabled
-                });
+                apply_scroll_button_settings(device, c.scroll_button, c.scroll_button_lock);
             }
         }
     }
@@ -4978,27 +6249,13 @@ pub fn apply_libinput_settings(config: &niri_config::Input, device: &mut input::
             let _ = device.config_scroll_set_method(method.into());
 
             if method == niri_config::ScrollMethod::OnButtonDown {
-                if let Some(button) = c.scroll_button {
-                    let _ = device.config_scroll_set_button(button);
-                }
-                let _ = device.config_scroll_set_button_lock(if c.scroll_button_lock {
-                    input::ScrollButtonLockState::Enabled
-                } else {
-                    input::ScrollButtonLockState::Disabled
-                });
+                apply_scroll_button_settings(device, c.scroll_button, c.scroll_button_lock);
             }
         } else if let Some(default) = device.config_scroll_default_method() {
             let _ = device.config_scroll_set_method(default);
 
             if default == input::ScrollMethod::OnButtonDown {
-                if let Some(button) = c.scroll_button {
-                    let _ = device.config_scroll_set_button(button);
-                }
-                let _ = device.config_scroll_set_button_lock(if c.scroll_button_lock {
-                    input::ScrollButtonLockState::Enabled
-                } else {
-                    input::ScrollButtonLockState::Disabled
-                });
+                apply_scroll_button_settings(device, c.scroll_button, c.scroll_button_lock);
             }
         }
     }
@@ -5025,27 +6282,13 @@ pub fn apply_libinput_settings(config: &niri_config::Input, device: &mut input::
             let _ = device.config_scroll_set_method(method.into());
 
             if method == niri_config::ScrollMethod::OnButtonDown {
-                if let Some(button) = c.scroll_button {
-                    let _ = device.config_scroll_set_button(button);
-                }
-                let _ = device.config_scroll_set_button_lock(if c.scroll_button_lock {
-                    input::ScrollButtonLockState::Enabled
-                } else {
-                    input::ScrollButtonLockState::Disabled
-                });
+                apply_scroll_button_settings(device, c.scroll_button, c.scroll_button_lock);
             }
         } else if let Some(default) = device.config_scroll_default_method() {
             let _ = device.config_scroll_set_method(default);
 
             if default == input::ScrollMethod::OnButtonDown {
-                if let Some(button) = c.scroll_button {
-                    let _ = device.config_scroll_set_button(button);
-                }
-                let _ = device.config_scroll_set_button_lock(if c.scroll_button_lock {
-                    input::ScrollButtonLockState::Enabled
-                } else {
-                    input::ScrollButtonLockState::Disabled
-                });
+                apply_scroll_button_settings(device, c.scroll_button, c.scroll_button_lock);
             }
         }
     }
@@ -5099,12 +6342,82 @@ pub fn apply_libinput_settings(config: &niri_config::Input, device: &mut input::
                 .unwrap_or(IDENTITY_MATRIX),
         );
     }
+
+    // Finally, apply the device-specific sections on top, later ones taking precedence.
+    let name = device.name().to_owned();
+    let (vendor, product) = (device.id_vendor(), device.id_product());
+    for c in &config.devices {
+        if c.matches(&name, vendor, product) {
+            apply_device_settings(c, device);
+        }
+    }
+}
+
+/// Applies the scroll button settings for scrolling on button down.
+///
+/// Without a configured button, the device's default button is restored.
+fn apply_scroll_button_settings(device: &mut input::Device, button: Option<u32>, lock: bool) {
+    let button = button.unwrap_or_else(|| device.config_scroll_default_button());
+    let _ = device.config_scroll_set_button(button);
+    let _ = device.config_scroll_set_button_lock(if lock {
+        input::ScrollButtonLockState::Enabled
+    } else {
+        input::ScrollButtonLockState::Disabled
+    });
+}
+
+fn apply_device_settings(c: &niri_config::InputDevice, device: &mut input::Device) {
+    if let Some(Flag(off)) = c.off {
+        let _ = device.config_send_events_set_mode(if off {
+            input::SendEventsMode::DISABLED
+        } else {
+            input::SendEventsMode::ENABLED
+        });
+    }
+    if let Some(Flag(natural_scroll)) = c.natural_scroll {
+        let _ = device.config_scroll_set_natural_scroll_enabled(natural_scroll);
+    }
+    if let Some(accel_speed) = c.accel_speed {
+        let _ = device.config_accel_set_speed(accel_speed.0);
+    }
+    if let Some(accel_profile) = c.accel_profile {
+        let _ = device.config_accel_set_profile(accel_profile.into());
+    }
+    if let Some(method) = c.scroll_method {
+        let _ = device.config_scroll_set_method(method.into());
+    }
+    if let Some(button) = c.scroll_button {
+        let _ = device.config_scroll_set_button(button);
+    }
+    if let Some(Flag(lock)) = c.scroll_button_lock {
+        let _ = device.config_scroll_set_button_lock(if lock {
+            input::ScrollButtonLockState::Enabled
+        } else {
+            input::ScrollButtonLockState::Disabled
+        });
+    }
+    if let Some(Flag(left_handed)) = c.left_handed {
+        let _ = device.config_left_handed_set(left_handed);
+    }
+    if let Some(Flag(middle_emulation)) = c.middle_emulation {
+        let _ = device.config_middle_emulation_set_enabled(middle_emulation);
+    }
+    if let Some(Flag(tap)) = c.tap {
+        let _ = device.config_tap_set_enabled(tap);
+    }
+    if let Some(Flag(dwt)) = c.dwt {
+        let _ = device.config_dwt_set_enabled(dwt);
+    }
 }
 
-pub fn mods_with_binds(mod_key: ModKey, binds: &Binds, triggers: &[Trigger]) -> HashSet<Modifiers> {
+pub fn mods_with_binds(
+    mod_key: ModKey,
+    binds: &Binds,
+    is_trigger: impl Fn(&Trigger) -> bool,
+) -> HashSet<Modifiers> {
     let mut rv = HashSet::new();
     for bind in &binds.0 {
-        if !triggers.contains(&bind.key.trigger) {
+        if !is_trigger(&bind.key.trigger) {
             continue;
         }
 
@@ -5121,43 +6434,85 @@ pub fn mods_with_binds(mod_key: ModKey, binds: &Binds, triggers: &[Trigger]) ->
 }
 
 pub fn mods_with_mouse_binds(mod_key: ModKey, binds: &Binds) -> HashSet<Modifiers> {
-    mods_with_binds(
-        mod_key,
-        binds,
-        &[
-            Trigger::MouseLeft,
-            Trigger::MouseRight,
-            Trigger::MouseMiddle,
-            Trigger::MouseBack,
-            Trigger::MouseForward,
-        ],
-    )
+    mods_with_binds(mod_key, binds, Trigger::is_mouse_button)
 }
 
 pub fn mods_with_wheel_binds(mod_key: ModKey, binds: &Binds) -> HashSet<Modifiers> {
-    mods_with_binds(
-        mod_key,
-        binds,
-        &[
-            Trigger::WheelScrollUp,
-            Trigger::WheelScrollDown,
-            Trigger::WheelScrollLeft,
-            Trigger::WheelScrollRight,
-        ],
-    )
+    mods_with_binds(mod_key, binds, |trigger| {
+        matches!(
+            trigger,
+            Trigger::WheelScrollUp
+                | Trigger::WheelScrollDown
+                | Trigger::WheelScrollLeft
+                | Trigger::WheelScrollRight
+        )
+    })
 }
 
 pub fn mods_with_finger_scroll_binds(mod_key: ModKey, binds: &Binds) -> HashSet<Modifiers> {
-    mods_with_binds(
-        mod_key,
-        binds,
-        &[
-            Trigger::TouchpadScrollUp,
-            Trigger::TouchpadScrollDown,
-            Trigger::TouchpadScrollLeft,
-            Trigger::TouchpadScrollRight,
-        ],
-    )
+    mods_with_binds(mod_key, binds, |trigger| {
+        matches!(
+            trigger,
+            Trigger::TouchpadScrollUp
+                | Trigger::TouchpadScrollDown
+                | Trigger::TouchpadScrollLeft
+                | Trigger::TouchpadScrollRight
+        )
+    })
+}
+
+fn tablet_tool_type(tool_type: smithay::backend::input::TabletToolType) -> Option<TabletToolType> {
+    use smithay::backend::input::TabletToolType as Tool;
+
+    match tool_type {
+        Tool::Pen => Some(TabletToolType::Pen),
+        Tool::Eraser => Some(TabletToolType::Eraser),
+        Tool::Brush => Some(TabletToolType::Brush),
+        Tool::Pencil => Some(TabletToolType::Pencil),
+        Tool::Airbrush => Some(TabletToolType::Airbrush),
+        Tool::Mouse => Some(TabletToolType::Mouse),
+        Tool::Lens => Some(TabletToolType::Lens),
+        _ => None,
+    }
+}
+
+/// Returns the part of `geo` that the tablet maps to.
+///
+/// Falls back to the whole `geo` if the region lies outside of it.
+fn tablet_region(
+    geo: Rectangle<i32, Logical>,
+    region: Option<TabletRegion>,
+) -> Rectangle<i32, Logical> {
+    let Some(region) = region else {
+        return geo;
+    };
+
+    let rect = Rectangle::new(
+        geo.loc + Point::from((region.x, region.y)),
+        Size::from((i32::from(region.width), i32::from(region.height))),
+    );
+    rect.intersection(geo)
+        .filter(|rect| !rect.is_empty())
+        .unwrap_or(geo)
+}
+
+/// Returns the number like in X11 of a mouse button from its evdev code.
+fn mouse_button_number(code: u32) -> Option<u32> {
+    const BTN_LEFT: u32 = 0x110;
+    const BTN_RIGHT: u32 = 0x111;
+    const BTN_MIDDLE: u32 = 0x112;
+    const BTN_SIDE: u32 = 0x113;
+    // Last code of the mouse button range.
+    const BTN_MOUSE_LAST: u32 = 0x11f;
+
+    match code {
+        BTN_LEFT => Some(1),
+        BTN_MIDDLE => Some(2),
+        BTN_RIGHT => Some(3),
+        // Numbers 4 to 7 are taken by the scroll wheel.
+        BTN_SIDE..=BTN_MOUSE_LAST => Some(8 + code - BTN_SIDE),
+        _ => None,
+    }
 }
 
 fn grab_allows_hot_corner(grab: &(dyn PointerGrab<State> + 'static)) -> bool {
@@ -5190,11 +6545,14 @@ fn grab_allows_hot_corner(grab: &(dyn PointerGrab<State> + 'static)) -> bool {
 /// Includes dynamically populated bindings like the MRU UI.
 fn make_binds_iter<'a>(
     config: &'a Config,
+    ipc_key_grabs: &'a [Bind],
+    bind_mode: Option<&str>,
     mru: &'a mut WindowMruUi,
     mods: Modifiers,
 ) -> impl Iterator<Item = &'a Bind> + Clone {
     // Figure out the binds to use depending on whether the MRU is enabled and/or open.
-    let general_binds = (!mru.is_open()).then_some(config.binds.0.iter());
+    let general_binds = config.binds_in_mode(bind_mode);
+    let general_binds = (!mru.is_open()).then_some(general_binds.0.iter());
     let general_binds = general_binds.into_iter().flatten();
 
     let mru_binds =
@@ -5204,8 +6562,15 @@ fn make_binds_iter<'a>(
     let mru_open_binds = mru.is_open().then(|| mru.opened_bindings(mods));
     let mru_open_binds = mru_open_binds.into_iter().flatten();
 
+    // Binds grabbed through IPC don't override the config.
+    let ipc_binds = (!mru.is_open()).then_some(ipc_key_grabs.iter());
+    let ipc_binds = ipc_binds.into_iter().flatten();
+
     // General binds take precedence over the MRU binds.
-    general_binds.chain(mru_binds).chain(mru_open_binds)
+    general_binds
+        .chain(ipc_binds)
+        .chain(mru_binds)
+        .chain(mru_open_binds)
 }
 
 #[cfg(test)]
@@ -5350,7 +6715,10 @@ mod tests {
             },
             action: Action::CloseWindow,
             repeat: true,
+            repeat_delay: None,
+            repeat_rate: None,
             release: false,
+            taps: 1,
             cooldown: None,
             allow_when_locked: false,
             allow_inhibiting: true,
@@ -5491,7 +6859,10 @@ mod tests {
                 },
                 action: Action::ToggleOverview,
                 repeat: true,
+                repeat_delay: None,
+                repeat_rate: None,
                 release: true,
+                taps: 1,
                 cooldown: None,
                 allow_when_locked: false,
                 allow_inhibiting: true,
@@ -5506,7 +6877,10 @@ mod tests {
                 },
                 action: Action::CloseWindow,
                 repeat: true,
+                repeat_delay: None,
+                repeat_rate: None,
                 release: true,
+                taps: 1,
                 cooldown: None,
                 allow_when_locked: false,
                 allow_inhibiting: true,
@@ -5521,7 +6895,10 @@ mod tests {
                 },
                 action: Action::CenterColumn,
                 repeat: true,
+                repeat_delay: None,
+                repeat_rate: None,
                 release: false,
+                taps: 1,
                 cooldown: None,
                 allow_when_locked: false,
                 allow_inhibiting: true,
@@ -5649,7 +7026,10 @@ mod tests {
                 },
                 action: Action::ToggleOverview,
                 repeat: true,
+                repeat_delay: None,
+                repeat_rate: None,
                 release: true,
+                taps: 1,
                 cooldown: None,
                 allow_when_locked: false,
                 allow_inhibiting: true,
@@ -5664,7 +7044,10 @@ mod tests {
                 },
                 action: Action::CloseWindow,
                 repeat: true,
+                repeat_delay: None,
+                repeat_rate: None,
                 release: true,
+                taps: 1,
                 cooldown: None,
                 allow_when_locked: false,
                 allow_inhibiting: true,
@@ -5730,7 +7113,10 @@ mod tests {
             },
             action: Action::CloseWindow,
             repeat: true,
+            repeat_delay: None,
+            repeat_rate: None,
             release: false,
+            taps: 1,
             cooldown: None,
             allow_when_locked: false,
             allow_inhibiting: false, // This binding cannot be inhibited
@@ -5776,7 +7162,10 @@ mod tests {
                 },
                 action: Action::CloseWindow,
                 repeat: true,
+                repeat_delay: None,
+                repeat_rate: None,
                 release: false,
+                taps: 1,
                 cooldown: None,
                 allow_when_locked: false,
                 allow_inhibiting: true,
@@ -5790,7 +7179,10 @@ mod tests {
                 },
                 action: Action::FocusColumnLeft,
                 repeat: true,
+                repeat_delay: None,
+                repeat_rate: None,
                 release: false,
+                taps: 1,
                 cooldown: None,
                 allow_when_locked: false,
                 allow_inhibiting: true,
@@ -5804,7 +7196,10 @@ mod tests {
                 },
                 action: Action::FocusWindowDown,
                 repeat: true,
+                repeat_delay: None,
+                repeat_rate: None,
                 release: false,
+                taps: 1,
                 cooldown: None,
                 allow_when_locked: false,
                 allow_inhibiting: true,
@@ -5818,7 +7213,10 @@ mod tests {
                 },
                 action: Action::FocusWindowUp,
                 repeat: true,
+                repeat_delay: None,
+                repeat_rate: None,
                 release: false,
+                taps: 1,
                 cooldown: None,
                 allow_when_locked: false,
                 allow_inhibiting: true,
@@ -5832,7 +7230,10 @@ mod tests {
                 },
                 action: Action::FocusColumnRight,
                 repeat: true,
+                repeat_delay: None,
+                repeat_rate: None,
                 release: false,
+                taps: 1,
                 cooldown: None,
                 allow_when_locked: false,
                 allow_inhibiting: true,
@@ -5846,7 +7247,10 @@ mod tests {
                 },
                 action: Action::ToggleOverview,
                 repeat: false,
+                repeat_delay: None,
+                repeat_rate: None,
                 release: true,
+                taps: 1,
                 cooldown: None,
                 allow_when_locked: false,
                 allow_inhibiting: true,
@@ -6019,4 +7423,28 @@ mod tests {
             None,
         );
     }
+
+    #[test]
+    fn tablet_region_is_within_geo() {
+        let geo = Rectangle::new(Point::from((1920, 0)), Size::from((1280, 720)));
+        let region = |x, y, width, height| {
+            Some(TabletRegion {
+                x,
+                y,
+                width,
+                height,
+            })
+        };
+
+        assert_eq!(tablet_region(geo, None), geo);
+        assert_eq!(
+            tablet_region(geo, region(100, 50, 640, 360)),
+            Rectangle::new(Point::from((2020, 50)), Size::from((640, 360)))
+        );
+        assert_eq!(
+            tablet_region(geo, region(1000, 0, 640, 360)),
+            Rectangle::new(Point::from((2920, 0)), Size::from((280, 360)))
+        );
+        assert_eq!(tablet_region(geo, region(2000, 0, 640, 360)), geo);
+    }
 }
@@ -0,0 +1,147 @@
+//! Tracking of quick taps of a lone modifier key, for binds like double-tap Super.
+
+use std::time::Duration;
+
+use smithay::backend::input::Keycode;
+
+#[derive(Debug, Default)]
+pub struct ModifierTaps {
+    /// Modifier key being tapped.
+    key_code: Option<Keycode>,
+    /// When the modifier was pressed, while it is held down.
+    pressed_at: Option<Duration>,
+    /// When the last tap ended.
+    released_at: Duration,
+    /// Number of taps so far.
+    count: u8,
+}
+
+impl ModifierTaps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets the taps so far.
+    ///
+    /// Called for anything that makes the modifier a part of some other input, like pressing a
+    /// mouse button while holding it.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Processes a key event, returning the number of taps when a tap completes.
+    ///
+    /// A tap counts when the modifier is pressed and released on its own, without holding it for
+    /// longer than `interval`. Consecutive taps need to follow each other within `interval`.
+    pub fn process_key(
+        &mut self,
+        key_code: Keycode,
+        is_modifier: bool,
+        pressed: bool,
+        time: Duration,
+        interval: Duration,
+    ) -> Option<u8> {
+        if pressed {
+            if !is_modifier {
+                self.reset();
+                return None;
+            }
+
+            let continues = self.key_code == Some(key_code)
+                && self.pressed_at.is_none()
+                && time.saturating_sub(self.released_at) <= interval;
+            if !continues {
+                self.reset();
+                self.key_code = Some(key_code);
+            }
+
+            self.pressed_at = Some(time);
+            return None;
+        }
+
+        if self.key_code != Some(key_code) {
+            return None;
+        }
+
+        let pressed_at = self.pressed_at.take()?;
+        if time.saturating_sub(pressed_at) > interval {
+            self.reset();
+            return None;
+        }
+
+        self.count = self.count.saturating_add(1);
+        self.released_at = time;
+        Some(self.count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUPER: Keycode = Keycode::new(133);
+    const SHIFT: Keycode = Keycode::new(50);
+    const A: Keycode = Keycode::new(38);
+    const INTERVAL: Duration = Duration::from_millis(300);
+
+    fn key(taps: &mut ModifierTaps, code: Keycode, pressed: bool, ms: u64) -> Option<u8> {
+        let is_modifier = code != A;
+        let time = Duration::from_millis(ms);
+        taps.process_key(code, is_modifier, pressed, time, INTERVAL)
+    }
+
+    #[test]
+    fn counts_quick_taps() {
+        let mut taps = ModifierTaps::new();
+        assert_eq!(key(&mut taps, SUPER, true, 0), None);
+        assert_eq!(key(&mut taps, SUPER, false, 50), Some(1));
+        assert_eq!(key(&mut taps, SUPER, true, 150), None);
+        assert_eq!(key(&mut taps, SUPER, false, 200), Some(2));
+        assert_eq!(key(&mut taps, SUPER, true, 300), None);
+        assert_eq!(key(&mut taps, SUPER, false, 350), Some(3));
+    }
+
+    #[test]
+    fn slow_taps_start_over() {
+        let mut taps = ModifierTaps::new();
+        key(&mut taps, SUPER, true, 0);
+        assert_eq!(key(&mut taps, SUPER, false, 50), Some(1));
+        key(&mut taps, SUPER, true, 1000);
+        assert_eq!(key(&mut taps, SUPER, false, 1050), Some(1));
+    }
+
+    #[test]
+    fn long_hold_is_not_a_tap() {
+        let mut taps = ModifierTaps::new();
+        key(&mut taps, SUPER, true, 0);
+        assert_eq!(key(&mut taps, SUPER, false, 1000), None);
+    }
+
+    #[test]
+    fn other_keys_break_the_sequence() {
+        let mut taps = ModifierTaps::new();
+        key(&mut taps, SUPER, true, 0);
+        assert_eq!(key(&mut taps, SUPER, false, 50), Some(1));
+        key(&mut taps, SUPER, true, 100);
+        key(&mut taps, A, true, 120);
+        key(&mut taps, A, false, 140);
+        assert_eq!(key(&mut taps, SUPER, false, 160), None);
+
+        key(&mut taps, SUPER, true, 200);
+        assert_eq!(key(&mut taps, SUPER, false, 250), Some(1));
+        key(&mut taps, SHIFT, true, 300);
+        assert_eq!(key(&mut taps, SHIFT, false, 350), Some(1));
+        key(&mut taps, SUPER, true, 400);
+        assert_eq!(key(&mut taps, SUPER, false, 450), Some(1));
+    }
+
+    #[test]
+    fn reset_breaks_the_sequence() {
+        let mut taps = ModifierTaps::new();
+        key(&mut taps, SUPER, true, 0);
+        assert_eq!(key(&mut taps, SUPER, false, 50), Some(1));
+        key(&mut taps, SUPER, true, 100);
+        taps.reset();
+        assert_eq!(key(&mut taps, SUPER, false, 150), None);
+    }
+}
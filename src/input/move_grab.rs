@@ -28,6 +28,8 @@ pub struct MoveGrab {
     window: Window,
     gesture: GestureState,
     enable_view_offset: bool,
+    /// Whether to move the whole column rather than just the window.
+    move_whole_column: bool,
     move_icon: CursorIcon,
 
     // Accumulated and applied in frame().
@@ -49,6 +51,7 @@ impl MoveGrab {
         start_data: PointerOrTouchStartData<State>,
         window: Window,
         enable_view_offset: bool,
+        move_whole_column: bool,
         move_icon: Option<CursorIcon>,
     ) -> Option<Self> {
         let location = start_data.location();
@@ -62,6 +65,7 @@ impl MoveGrab {
             window,
             gesture: GestureState::Recognizing,
             enable_view_offset,
+            move_whole_column,
             // Moving windows by their titlebars uses the default cursor by default.
             move_icon: move_icon.unwrap_or(CursorIcon::Default),
             new_location: location,
@@ -116,11 +120,21 @@ impl MoveGrab {
     }
 
     fn begin_move(&mut self, data: &mut State) -> bool {
-        if !data.niri.layout.interactive_move_begin(
-            self.window.clone(),
-            &self.start_output,
-            self.start_pos_within_output,
-        ) {
+        let layout = &mut data.niri.layout;
+        let started = if self.move_whole_column {
+            layout.interactive_move_column_begin(
+                self.window.clone(),
+                &self.start_output,
+                self.start_pos_within_output,
+            )
+        } else {
+            layout.interactive_move_begin(
+                self.window.clone(),
+                &self.start_output,
+                self.start_pos_within_output,
+            )
+        };
+        if !started {
             // Can no longer start the move.
             return false;
         }
@@ -0,0 +1,215 @@
+//! Sticky keys, where modifiers pressed on their own stay active for the next key.
+//!
+//! Tapping a modifier latches it: niri holds back the release, so the modifier stays pressed for
+//! the bindings and the clients until the next key is released. Tapping a latched modifier again
+//! locks it until it is tapped once more.
+
+use smithay::backend::input::Keycode;
+
+#[derive(Debug, Default)]
+pub struct StickyKeys {
+    /// Modifiers held down physically, and whether another key was pressed while holding them.
+    held: Vec<(Keycode, bool)>,
+    /// Modifiers kept pressed after they were released physically.
+    latched: Vec<(Keycode, Latch)>,
+    /// Modifiers whose next physical release must not reach the keyboard.
+    ignore_release: Vec<Keycode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Latch {
+    /// Active until the next key is released.
+    Once,
+    /// Active until the modifier is tapped again.
+    Locked,
+}
+
+/// What to do with a key event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StickyKeysAction {
+    /// Process the event as usual.
+    Pass,
+    /// Drop the event.
+    Ignore,
+    /// Process the event as usual, then release these modifiers.
+    PassAndRelease(Vec<Keycode>),
+    /// Drop the event and release this modifier.
+    Release(Keycode),
+}
+
+impl StickyKeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Processes a physical key event.
+    pub fn process_key(
+        &mut self,
+        key_code: Keycode,
+        is_modifier: bool,
+        pressed: bool,
+    ) -> StickyKeysAction {
+        if pressed {
+            // Anything pressed while holding a modifier makes it a regular chord.
+            for (_, chorded) in &mut self.held {
+                *chorded = true;
+            }
+
+            if !is_modifier {
+                return StickyKeysAction::Pass;
+            }
+
+            let latched = self.latched.iter().position(|(code, _)| *code == key_code);
+            if let Some(idx) = latched {
+                // The modifier is still pressed as far as the keyboard knows, so this press and
+                // its release must not reach it.
+                self.ignore_release.push(key_code);
+
+                return match self.latched[idx].1 {
+                    Latch::Once => {
+                        self.latched[idx].1 = Latch::Locked;
+                        StickyKeysAction::Ignore
+                    }
+                    Latch::Locked => {
+                        self.latched.remove(idx);
+                        StickyKeysAction::Release(key_code)
+                    }
+                };
+            }
+
+            let chorded = !self.held.is_empty();
+            self.held.push((key_code, chorded));
+            return StickyKeysAction::Pass;
+        }
+
+        let ignored = self
+            .ignore_release
+            .iter()
+            .position(|code| *code == key_code);
+        if let Some(idx) = ignored {
+            self.ignore_release.remove(idx);
+            return StickyKeysAction::Ignore;
+        }
+
+        if let Some(idx) = self.held.iter().position(|(code, _)| *code == key_code) {
+            let (_, chorded) = self.held.remove(idx);
+            if chorded {
+                return StickyKeysAction::Pass;
+            }
+
+            self.latched.push((key_code, Latch::Once));
+            return StickyKeysAction::Ignore;
+        }
+
+        if is_modifier {
+            return StickyKeysAction::Pass;
+        }
+
+        let mut released = Vec::new();
+        self.latched.retain(|(code, latch)| {
+            if *latch == Latch::Once {
+                released.push(*code);
+                false
+            } else {
+                true
+            }
+        });
+
+        if released.is_empty() {
+            StickyKeysAction::Pass
+        } else {
+            StickyKeysAction::PassAndRelease(released)
+        }
+    }
+
+    /// Forgets all state, returning the modifiers that need to be released.
+    ///
+    /// Called when sticky keys are turned off.
+    pub fn reset(&mut self) -> Vec<Keycode> {
+        let released = self.latched.drain(..).map(|(code, _)| code).collect();
+        *self = Self::default();
+        released
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIFT: Keycode = Keycode::new(50);
+    const CTRL: Keycode = Keycode::new(37);
+    const A: Keycode = Keycode::new(38);
+
+    fn key(sticky: &mut StickyKeys, code: Keycode, pressed: bool) -> StickyKeysAction {
+        sticky.process_key(code, code != A, pressed)
+    }
+
+    #[test]
+    fn tap_latches_for_one_key() {
+        let mut sticky = StickyKeys::new();
+        assert_eq!(key(&mut sticky, SHIFT, true), StickyKeysAction::Pass);
+        assert_eq!(key(&mut sticky, SHIFT, false), StickyKeysAction::Ignore);
+        assert_eq!(key(&mut sticky, A, true), StickyKeysAction::Pass);
+        assert_eq!(
+            key(&mut sticky, A, false),
+            StickyKeysAction::PassAndRelease(vec![SHIFT])
+        );
+        assert_eq!(key(&mut sticky, A, true), StickyKeysAction::Pass);
+        assert_eq!(key(&mut sticky, A, false), StickyKeysAction::Pass);
+    }
+
+    #[test]
+    fn latches_combine() {
+        let mut sticky = StickyKeys::new();
+        key(&mut sticky, CTRL, true);
+        key(&mut sticky, CTRL, false);
+        key(&mut sticky, SHIFT, true);
+        assert_eq!(key(&mut sticky, SHIFT, false), StickyKeysAction::Ignore);
+        key(&mut sticky, A, true);
+        assert_eq!(
+            key(&mut sticky, A, false),
+            StickyKeysAction::PassAndRelease(vec![CTRL, SHIFT])
+        );
+    }
+
+    #[test]
+    fn double_tap_locks() {
+        let mut sticky = StickyKeys::new();
+        key(&mut sticky, SHIFT, true);
+        key(&mut sticky, SHIFT, false);
+        assert_eq!(key(&mut sticky, SHIFT, true), StickyKeysAction::Ignore);
+        assert_eq!(key(&mut sticky, SHIFT, false), StickyKeysAction::Ignore);
+
+        key(&mut sticky, A, true);
+        assert_eq!(key(&mut sticky, A, false), StickyKeysAction::Pass);
+
+        assert_eq!(
+            key(&mut sticky, SHIFT, true),
+            StickyKeysAction::Release(SHIFT)
+        );
+        assert_eq!(key(&mut sticky, SHIFT, false), StickyKeysAction::Ignore);
+    }
+
+    #[test]
+    fn held_modifier_works_as_usual() {
+        let mut sticky = StickyKeys::new();
+        key(&mut sticky, SHIFT, true);
+        key(&mut sticky, A, true);
+        key(&mut sticky, A, false);
+        assert_eq!(key(&mut sticky, SHIFT, false), StickyKeysAction::Pass);
+
+        key(&mut sticky, CTRL, true);
+        key(&mut sticky, SHIFT, true);
+        assert_eq!(key(&mut sticky, SHIFT, false), StickyKeysAction::Pass);
+        assert_eq!(key(&mut sticky, CTRL, false), StickyKeysAction::Pass);
+    }
+
+    #[test]
+    fn reset_releases_latched() {
+        let mut sticky = StickyKeys::new();
+        key(&mut sticky, SHIFT, true);
+        key(&mut sticky, SHIFT, false);
+        assert_eq!(sticky.reset(), vec![SHIFT]);
+        assert_eq!(key(&mut sticky, A, false), StickyKeysAction::Pass);
+    }
+}
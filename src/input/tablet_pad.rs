@@ -0,0 +1,94 @@
+//! Tablet pad rings and strips, turned into steps for pad binds.
+//!
+//! Rings and strips report the absolute finger position, so niri keeps the last position of each
+//! one and counts a step whenever the finger moved far enough since the previous step.
+
+use std::collections::HashMap;
+
+/// Degrees the finger moves along a ring for one step.
+const RING_STEP: f64 = 15.;
+
+/// Fraction of the strip length the finger moves for one step.
+const STRIP_STEP: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PadAxis {
+    Ring,
+    Strip,
+}
+
+#[derive(Debug, Default)]
+pub struct TabletPadAxes {
+    /// Last position, and the movement not yet counted as a step, per ring or strip.
+    axes: HashMap<(PadAxis, u32), (f64, f64)>,
+}
+
+impl TabletPadAxes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Processes a new position, returning the number of steps.
+    ///
+    /// Steps are positive clockwise on rings and downwards on strips.
+    pub fn update(&mut self, axis: PadAxis, number: u32, position: f64) -> i32 {
+        // Lifting the finger reports a negative position.
+        if position < 0. {
+            self.axes.remove(&(axis, number));
+            return 0;
+        }
+
+        let Some((last, acc)) = self.axes.get_mut(&(axis, number)) else {
+            self.axes.insert((axis, number), (position, 0.));
+            return 0;
+        };
+
+        let mut delta = position - *last;
+        *last = position;
+
+        let step = match axis {
+            PadAxis::Ring => {
+                // Take the short way around when crossing the top of the ring.
+                if delta > 180. {
+                    delta -= 360.;
+                } else if delta < -180. {
+                    delta += 360.;
+                }
+                RING_STEP
+            }
+            PadAxis::Strip => STRIP_STEP,
+        };
+
+        *acc += delta;
+        let steps = (*acc / step).trunc();
+        *acc -= steps * step;
+        steps as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_steps_wrap_around() {
+        let mut axes = TabletPadAxes::new();
+        assert_eq!(axes.update(PadAxis::Ring, 0, 350.), 0);
+        assert_eq!(axes.update(PadAxis::Ring, 0, 5.), 1);
+        assert_eq!(axes.update(PadAxis::Ring, 0, 40.), 2);
+        assert_eq!(axes.update(PadAxis::Ring, 0, 10.), -1);
+
+        // Lifting the finger forgets the position.
+        assert_eq!(axes.update(PadAxis::Ring, 0, -1.), 0);
+        assert_eq!(axes.update(PadAxis::Ring, 0, 180.), 0);
+    }
+
+    #[test]
+    fn strips_are_separate() {
+        let mut axes = TabletPadAxes::new();
+        assert_eq!(axes.update(PadAxis::Strip, 0, 0.5), 0);
+        assert_eq!(axes.update(PadAxis::Strip, 1, 0.), 0);
+        assert_eq!(axes.update(PadAxis::Strip, 0, 0.25), -2);
+        assert_eq!(axes.update(PadAxis::Strip, 1, 0.31), 3);
+    }
+}
@@ -0,0 +1,425 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use niri_config::gestures::{TouchEdgeAction, TouchEdgeSwipe};
+use smithay::desktop::Window;
+use smithay::input::touch::{
+    DownEvent, GrabStartData as TouchGrabStartData, MotionEvent, OrientationEvent, ShapeEvent,
+    TouchGrab, TouchInnerHandle, UpEvent,
+};
+use smithay::input::SeatHandler;
+use smithay::output::Output;
+use smithay::reexports::calloop::timer::{TimeoutAction, Timer};
+use smithay::reexports::calloop::{LoopHandle, RegistrationToken};
+use smithay::utils::{IsAlive, Logical, Point, Serial, Size};
+
+use crate::niri::State;
+
+/// Touch grab outside the overview that recognizes edge swipes and long presses.
+///
+/// Events go to the client until a gesture is recognized, at which point the client touch is
+/// cancelled. If the touch moves without matching a gesture, the grab ends and the client keeps
+/// receiving the touch as usual.
+pub struct TouchGestureGrab {
+    start_data: TouchGrabStartData<State>,
+    last_location: Point<f64, Logical>,
+    output: Output,
+    start_pos_within_output: Point<f64, Logical>,
+    /// Output edge the touch started at, and what swiping from it does.
+    edge: Option<(Edge, TouchEdgeAction)>,
+    /// Window to move after a long press.
+    long_press: Option<Window>,
+    /// Timer that starts the interactive move once the touch is held long enough.
+    long_press_timer: Option<(RegistrationToken, Rc<Cell<LongPressState>>)>,
+    gesture: GestureState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LongPressState {
+    Pending,
+    Started,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum GestureState {
+    Recognizing,
+    Overview,
+    WorkspaceSwitch,
+    InteractiveMove,
+}
+
+impl Edge {
+    /// Returns the output edge that the position is within `trigger_size` of.
+    ///
+    /// In the corners, the top and bottom edges win.
+    pub fn at(
+        pos_within_output: Point<f64, Logical>,
+        output_size: Size<f64, Logical>,
+        trigger_size: f64,
+    ) -> Option<Self> {
+        if pos_within_output.y < trigger_size {
+            Some(Self::Top)
+        } else if pos_within_output.y >= output_size.h - trigger_size {
+            Some(Self::Bottom)
+        } else if pos_within_output.x < trigger_size {
+            Some(Self::Left)
+        } else if pos_within_output.x >= output_size.w - trigger_size {
+            Some(Self::Right)
+        } else {
+            None
+        }
+    }
+
+    pub fn action(self, config: &TouchEdgeSwipe) -> TouchEdgeAction {
+        match self {
+            Self::Top => config.top,
+            Self::Bottom => config.bottom,
+            Self::Left => config.left,
+            Self::Right => config.right,
+        }
+    }
+
+    /// Returns how far the delta moves away from the edge.
+    fn distance_from(self, delta: Point<f64, Logical>) -> f64 {
+        match self {
+            Self::Top => delta.y,
+            Self::Bottom => -delta.y,
+            Self::Left => delta.x,
+            Self::Right => -delta.x,
+        }
+    }
+}
+
+impl TouchGestureGrab {
+    pub fn new(
+        event_loop: &LoopHandle<'static, State>,
+        start_data: TouchGrabStartData<State>,
+        output: Output,
+        start_pos_within_output: Point<f64, Logical>,
+        edge: Option<(Edge, TouchEdgeAction)>,
+        long_press: Option<(Window, Duration)>,
+    ) -> Self {
+        let long_press_timer = long_press.as_ref().map(|(window, delay)| {
+            let long_press_state = Rc::new(Cell::new(LongPressState::Pending));
+
+            let window = window.clone();
+            let output = output.clone();
+            let state = long_press_state.clone();
+            let token = event_loop
+                .insert_source(Timer::from_duration(*delay), move |_, _, data| {
+                    let layout = &mut data.niri.layout;
+                    if window.alive()
+                        && layout.interactive_move_begin(
+                            window.clone(),
+                            &output,
+                            start_pos_within_output,
+                        )
+                    {
+                        state.set(LongPressState::Started);
+                        data.niri.queue_redraw_all();
+                    } else {
+                        state.set(LongPressState::Failed);
+                    }
+                    TimeoutAction::Drop
+                })
+                .unwrap();
+
+            (token, long_press_state)
+        });
+
+        Self {
+            last_location: start_data.location,
+            start_data,
+            output,
+            start_pos_within_output,
+            edge,
+            long_press: long_press.map(|(window, _)| window),
+            long_press_timer,
+            gesture: GestureState::Recognizing,
+        }
+    }
+
+    /// Picks up an interactive move started by the long press timer.
+    ///
+    /// Returns `true` if the touch just became an interactive move.
+    fn check_long_press(&mut self) -> bool {
+        let Some((_, long_press_state)) = &self.long_press_timer else {
+            return false;
+        };
+
+        if !matches!(self.gesture, GestureState::Recognizing)
+            || long_press_state.get() != LongPressState::Started
+        {
+            return false;
+        }
+
+        self.long_press_timer = None;
+        self.gesture = GestureState::InteractiveMove;
+        true
+    }
+
+    fn cancel_long_press(&mut self, state: &mut State) {
+        if let Some((token, long_press_state)) = self.long_press_timer.take() {
+            if long_press_state.get() == LongPressState::Pending {
+                state.niri.event_loop.remove(token);
+            }
+        }
+    }
+
+    /// Tries to recognize a gesture, returns `false` if the touch can't become one anymore.
+    fn recognize(&mut self, data: &mut State, event: &MotionEvent) -> bool {
+        let c = event.location - self.start_data.location;
+
+        // Threshold copied from libadwaita.
+        if c.x * c.x + c.y * c.y < 16. * 16. {
+            return true;
+        }
+
+        // Moved too far to be a long press.
+        self.cancel_long_press(data);
+
+        let layout = &mut data.niri.layout;
+        match self.edge.map(|(_, action)| action) {
+            Some(TouchEdgeAction::Overview) => {
+                layout.overview_gesture_begin();
+                self.gesture = GestureState::Overview;
+            }
+            Some(TouchEdgeAction::WorkspaceSwitch) => {
+                layout.workspace_switch_gesture_begin(&self.output, false);
+                self.gesture = GestureState::WorkspaceSwitch;
+            }
+            Some(TouchEdgeAction::None) | None => return false,
+        }
+
+        true
+    }
+
+    fn on_ungrab(&mut self, state: &mut State) {
+        self.check_long_press();
+        self.cancel_long_press(state);
+
+        let layout = &mut state.niri.layout;
+        match self.gesture {
+            GestureState::Recognizing => return,
+            GestureState::Overview => {
+                layout.overview_gesture_end();
+            }
+            GestureState::WorkspaceSwitch => {
+                layout.workspace_switch_gesture_end(Some(false));
+            }
+            GestureState::InteractiveMove => {
+                if let Some(window) = &self.long_press {
+                    layout.interactive_move_end(window);
+                }
+            }
+        }
+
+        state.niri.queue_redraw_all();
+    }
+}
+
+impl TouchGrab<State> for TouchGestureGrab {
+    fn down(
+        &mut self,
+        data: &mut State,
+        handle: &mut TouchInnerHandle<'_, State>,
+        focus: Option<(<State as SeatHandler>::TouchFocus, Point<f64, Logical>)>,
+        event: &DownEvent,
+        seq: Serial,
+    ) {
+        if !matches!(self.gesture, GestureState::Recognizing) {
+            handle.down(data, None, event, seq);
+            return;
+        }
+
+        handle.down(data, focus, event, seq);
+
+        // A second finger means this is a multi-touch gesture for the client.
+        if event.slot != self.start_data.slot {
+            handle.unset_grab(self, data);
+        }
+    }
+
+    fn up(
+        &mut self,
+        data: &mut State,
+        handle: &mut TouchInnerHandle<'_, State>,
+        event: &UpEvent,
+        seq: Serial,
+    ) {
+        if event.slot == self.start_data.slot && self.check_long_press() {
+            // The touch became an interactive move without moving, take it away from the client.
+            handle.cancel(data, seq);
+            handle.unset_grab(self, data);
+            return;
+        }
+
+        handle.up(data, event, seq);
+
+        if event.slot != self.start_data.slot {
+            return;
+        }
+
+        handle.unset_grab(self, data);
+    }
+
+    fn motion(
+        &mut self,
+        data: &mut State,
+        handle: &mut TouchInnerHandle<'_, State>,
+        focus: Option<(<State as SeatHandler>::TouchFocus, Point<f64, Logical>)>,
+        event: &MotionEvent,
+        seq: Serial,
+    ) {
+        if event.slot != self.start_data.slot {
+            handle.motion(data, focus, event, seq);
+            return;
+        }
+
+        if self.check_long_press() {
+            // We took over the touch, take it away from the client.
+            handle.cancel(data, seq);
+        } else if matches!(self.gesture, GestureState::Recognizing) {
+            if !self.recognize(data, event) {
+                handle.motion(data, focus, event, seq);
+                handle.unset_grab(self, data);
+                return;
+            }
+
+            if matches!(self.gesture, GestureState::Recognizing) {
+                handle.motion(data, focus, event, seq);
+                return;
+            }
+
+            // We took over the touch, take it away from the client.
+            handle.cancel(data, seq);
+        }
+
+        let timestamp = Duration::from_millis(u64::from(event.time));
+        let delta = event.location - self.last_location;
+        self.last_location = event.location;
+
+        let layout = &mut data.niri.layout;
+        let ongoing = match self.gesture {
+            GestureState::Recognizing => unreachable!(),
+            GestureState::Overview => {
+                let (edge, _) = self.edge.unwrap();
+                layout
+                    .overview_gesture_update(edge.distance_from(delta), timestamp)
+                    .is_some()
+            }
+            GestureState::WorkspaceSwitch => layout
+                .workspace_switch_gesture_update(-delta.y, timestamp, false)
+                .is_some(),
+            GestureState::InteractiveMove => {
+                let window = self.long_press.as_ref().unwrap();
+                if let Some((output, pos_within_output)) = data.niri.output_under(event.location) {
+                    let output = output.clone();
+                    data.niri.layout.interactive_move_update(
+                        window,
+                        delta,
+                        output,
+                        pos_within_output,
+                    )
+                } else {
+                    false
+                }
+            }
+        };
+
+        if ongoing {
+            data.niri.queue_redraw_all();
+        } else {
+            handle.unset_grab(self, data);
+        }
+    }
+
+    fn frame(&mut self, data: &mut State, handle: &mut TouchInnerHandle<'_, State>, seq: Serial) {
+        handle.frame(data, seq);
+    }
+
+    fn cancel(&mut self, data: &mut State, handle: &mut TouchInnerHandle<'_, State>, seq: Serial) {
+        handle.cancel(data, seq);
+        handle.unset_grab(self, data);
+    }
+
+    fn shape(
+        &mut self,
+        data: &mut State,
+        handle: &mut TouchInnerHandle<'_, State>,
+        event: &ShapeEvent,
+        seq: Serial,
+    ) {
+        handle.shape(data, event, seq);
+    }
+
+    fn orientation(
+        &mut self,
+        data: &mut State,
+        handle: &mut TouchInnerHandle<'_, State>,
+        event: &OrientationEvent,
+        seq: Serial,
+    ) {
+        handle.orientation(data, event, seq);
+    }
+
+    fn start_data(&self) -> &TouchGrabStartData<State> {
+        &self.start_data
+    }
+
+    fn unset(&mut self, data: &mut State) {
+        self.on_ungrab(data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge_at(x: f64, y: f64) -> Option<Edge> {
+        Edge::at(Point::from((x, y)), Size::from((1920., 1080.)), 20.)
+    }
+
+    #[test]
+    fn edge_at_edges() {
+        assert_eq!(edge_at(960., 0.), Some(Edge::Top));
+        assert_eq!(edge_at(960., 19.9), Some(Edge::Top));
+        assert_eq!(edge_at(960., 1060.), Some(Edge::Bottom));
+        assert_eq!(edge_at(960., 1079.), Some(Edge::Bottom));
+        assert_eq!(edge_at(0., 540.), Some(Edge::Left));
+        assert_eq!(edge_at(1900., 540.), Some(Edge::Right));
+    }
+
+    #[test]
+    fn edge_at_outside_trigger_size() {
+        assert_eq!(edge_at(960., 540.), None);
+        assert_eq!(edge_at(960., 20.), None);
+        assert_eq!(edge_at(20., 540.), None);
+        assert_eq!(edge_at(1899.9, 540.), None);
+    }
+
+    #[test]
+    fn edge_at_corners_prefer_top_and_bottom() {
+        assert_eq!(edge_at(0., 0.), Some(Edge::Top));
+        assert_eq!(edge_at(1919., 0.), Some(Edge::Top));
+        assert_eq!(edge_at(0., 1079.), Some(Edge::Bottom));
+        assert_eq!(edge_at(1919., 1079.), Some(Edge::Bottom));
+    }
+
+    #[test]
+    fn edge_at_zero_trigger_size() {
+        let at = |x: f64, y: f64| Edge::at(Point::from((x, y)), Size::from((1920., 1080.)), 0.);
+        assert_eq!(at(0., 0.), None);
+        assert_eq!(at(960., 1079.), None);
+    }
+}
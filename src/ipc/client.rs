@@ -1,21 +1,51 @@
-use std::io::ErrorKind;
+use std::io::{ErrorKind, Write as _};
 use std::iter::Peekable;
 use std::path::Path;
-use std::{env, slice};
+use std::{env, fs, io, iter, process, slice};
 
 use anyhow::{anyhow, bail, Context};
+use clap::Parser as _;
 use niri_config::OutputName;
 use niri_ipc::socket::Socket;
 use niri_ipc::{
-    Action, Event, KeyboardLayouts, LogicalOutput, Mode, Output, OutputConfigChanged, Overview,
-    Request, Response, Transform, Window, WindowLayout,
+    Action, Blur, ClientProtocolStats, Event, FrameStats, KeyboardLayouts, LogicalOutput, Mode,
+    Output, OutputConfigChanged, Overview, Pointer, Request, Response, Transform, Widget, Window,
+    WindowLayout,
 };
 use serde_json::json;
 
-use crate::cli::Msg;
+use crate::cli::{ConfigAction, DebugAction, Msg, WidgetAction, WindowRuleAction};
 use crate::utils::version;
 
 pub fn handle_msg(mut msg: Msg, json: bool) -> anyhow::Result<()> {
+    // Screenshots to stdout are saved to a temporary file first, then copied.
+    let mut screenshot_to_stdout = false;
+    if let Msg::Action {
+        action:
+            Action::ScreenshotScreen {
+                path,
+                write_to_disk,
+                ..
+            }
+            | Action::ScreenshotWindow {
+                path,
+                write_to_disk,
+                ..
+            },
+    } = &mut msg
+    {
+        if path.as_deref() == Some("-") {
+            let temp = env::temp_dir().join(format!("niri-msg-screenshot-{}.png", process::id()));
+            let temp = temp
+                .into_os_string()
+                .into_string()
+                .map_err(|p| anyhow!("couldn't convert temporary path to string: {p:?}"))?;
+            *path = Some(temp);
+            *write_to_disk = true;
+            screenshot_to_stdout = true;
+        }
+    }
+
     // For actions taking paths, prepend the niri CLI's working directory.
     if let Msg::Action {
         action:
@@ -28,15 +58,34 @@ pub fn handle_msg(mut msg: Msg, json: bool) -> anyhow::Result<()> {
             ensure_absolute_path(path).context("error making the path absolute")?;
         }
     }
+    if let Msg::SaveOutputConfig { path } = &mut msg {
+        ensure_absolute_path(path).context("error making the path absolute")?;
+    }
+    if let Msg::Config {
+        action: ConfigAction::Try { path, .. },
+    } = &mut msg
+    {
+        ensure_absolute_path(path).context("error making the path absolute")?;
+    }
+    if let Msg::Widget {
+        action: WidgetAction::Set {
+            icon: Some(path), ..
+        },
+    } = &mut msg
+    {
+        ensure_absolute_path(path).context("error making the path absolute")?;
+    }
 
     let request = match &msg {
         Msg::Version => Request::Version,
+        Msg::Schema => Request::Schema,
         Msg::Outputs => Request::Outputs,
         Msg::FocusedWindow => Request::FocusedWindow,
         Msg::FocusedOutput => Request::FocusedOutput,
         Msg::PickWindow => Request::PickWindow,
         Msg::PickColor => Request::PickColor,
         Msg::Action { action } => Request::Action(action.clone()),
+        Msg::Batch { actions } => Request::Batch(parse_batch_actions(actions)?),
         Msg::Output { output, action } => Request::Output {
             output: output.clone(),
             action: action.clone(),
@@ -45,9 +94,82 @@ pub fn handle_msg(mut msg: Msg, json: bool) -> anyhow::Result<()> {
         Msg::Windows => Request::Windows,
         Msg::Layers => Request::Layers,
         Msg::KeyboardLayouts => Request::KeyboardLayouts,
+        Msg::SaveOutputConfig { path } => Request::SaveOutputConfig { path: path.clone() },
         Msg::EventStream => Request::EventStream,
         Msg::RequestError => Request::ReturnError,
         Msg::OverviewState => Request::OverviewState,
+        Msg::ScrollingView { workspace_id } => Request::ScrollingView {
+            workspace_id: *workspace_id,
+        },
+        Msg::Blur { output, window } => Request::Blur {
+            output: output.clone(),
+            window: *window,
+        },
+        Msg::SetBlur {
+            output,
+            window,
+            change,
+        } => Request::SetBlur {
+            output: output.clone(),
+            window: *window,
+            change: *change,
+        },
+        Msg::WindowDecorations { id } => Request::WindowDecorations { id: *id },
+        Msg::SetWindowDecorations { id, change } => Request::SetWindowDecorations {
+            id: *id,
+            change: *change,
+        },
+        Msg::WindowThumbnail { id, max_size, .. } => Request::WindowThumbnail {
+            id: *id,
+            max_size: *max_size,
+        },
+        Msg::Pointer => Request::Pointer,
+        Msg::ClipboardHistory => Request::ClipboardHistory,
+        Msg::ActivationToken => Request::ActivationToken,
+        Msg::FrameStats => Request::FrameStats,
+        Msg::InjectInput { event } => Request::InjectInput(*event),
+        Msg::GrabKey { key } => Request::GrabKey { key: key.clone() },
+        Msg::UngrabKey { key } => Request::UngrabKey { key: key.clone() },
+        Msg::Config { action } => match action {
+            ConfigAction::Try { path, timeout } => Request::TryConfig {
+                path: path.clone(),
+                timeout_secs: *timeout,
+            },
+            ConfigAction::Confirm => Request::ConfirmConfig,
+            ConfigAction::Reload { only } => Request::ReloadConfig {
+                sections: only.clone(),
+            },
+        },
+        Msg::Debug { action } => match action {
+            DebugAction::ProtocolStats => Request::ProtocolStats,
+        },
+        Msg::Widget { action } => match action {
+            WidgetAction::Set {
+                id,
+                text,
+                progress,
+                icon,
+                anchor,
+                x,
+                y,
+                output,
+            } => Request::SetWidget(Widget {
+                id: id.clone(),
+                output: output.clone(),
+                anchor: *anchor,
+                offset: (*x, *y),
+                text: text.clone(),
+                progress: *progress,
+                icon: icon.clone(),
+            }),
+            WidgetAction::Remove { id } => Request::RemoveWidget { id: id.clone() },
+            WidgetAction::List => Request::Widgets,
+        },
+        Msg::WindowRule { action } => match action {
+            WindowRuleAction::Add { rule } => Request::AddWindowRule { rule: rule.clone() },
+            WindowRuleAction::Remove { id } => Request::RemoveWindowRule { id: *id },
+            WindowRuleAction::List => Request::WindowRules,
+        },
     };
 
     let mut socket = Socket::connect().context("error connecting to the niri socket")?;
@@ -113,6 +235,16 @@ pub fn handle_msg(mut msg: Msg, json: bool) -> anyhow::Result<()> {
         Msg::RequestError => {
             bail!("unexpected response: expected an error, got {response:?}");
         }
+        Msg::Schema => {
+            let Response::Schema(schema) = response else {
+                bail!("unexpected response: expected Schema, got {response:?}");
+            };
+
+            // The schema is only readable as JSON anyway.
+            let schema =
+                serde_json::to_string_pretty(&schema).context("error formatting schema")?;
+            println!("{schema}");
+        }
         Msg::Version => {
             let Response::Version(compositor_version) = response else {
                 bail!("unexpected response: expected Version, got {response:?}");
@@ -227,6 +359,12 @@ pub fn handle_msg(mut msg: Msg, json: bool) -> anyhow::Result<()> {
                     niri_ipc::LayerSurfaceKeyboardInteractivity::OnDemand => "on-demand",
                 };
                 println!("      Keyboard interactivity: {interactivity}");
+
+                let (x, y) = surface.pos_in_output;
+                let (width, height) = surface.size;
+                println!("      Position in output: {x}, {y}");
+                println!("      Size: {width} x {height}");
+                println!("      Exclusive zone: {}", surface.exclusive_zone);
             };
 
             let print_layer = |iter: &mut Peekable<slice::Iter<niri_ipc::LayerSurface>>,
@@ -316,7 +454,26 @@ pub fn handle_msg(mut msg: Msg, json: bool) -> anyhow::Result<()> {
                 println!("No color was picked.");
             }
         }
-        Msg::Action { .. } => {
+        Msg::Action { .. } => match response {
+            Response::Handled => (),
+            // Screenshots written to disk reply once the file is saved.
+            Response::ScreenshotSaved(path) => {
+                if screenshot_to_stdout {
+                    let data = fs::read(&path).context("error reading the screenshot");
+                    let _ = fs::remove_file(&path);
+                    io::stdout()
+                        .write_all(&data?)
+                        .context("error writing the screenshot to stdout")?;
+                } else if json {
+                    let path = serde_json::to_string(&path).context("error formatting path")?;
+                    println!("{path}");
+                } else {
+                    println!("{path}");
+                }
+            }
+            _ => bail!("unexpected response: expected Handled, got {response:?}"),
+        },
+        Msg::Batch { .. } => {
             let Response::Handled = response else {
                 bail!("unexpected response: expected Handled, got {response:?}");
             };
@@ -383,7 +540,12 @@ pub fn handle_msg(mut msg: Msg, json: bool) -> anyhow::Result<()> {
                 } else {
                     String::new()
                 };
-                println!("{is_active}{idx}{name}");
+                let icon = if let Some(icon) = ws.icon.as_deref() {
+                    format!(" {icon}")
+                } else {
+                    String::new()
+                };
+                println!("{is_active}{idx}{name}{icon}");
             }
         }
         Msg::KeyboardLayouts => {
@@ -496,6 +658,54 @@ pub fn handle_msg(mut msg: Msg, json: bool) -> anyhow::Result<()> {
                         let description = parts.join(" and ");
                         println!("Screenshot captured: {description}");
                     }
+                    Event::WindowsParked { ids } => {
+                        println!("Windows parked: {ids:?}");
+                    }
+                    Event::KeyGrabTriggered { key } => {
+                        println!("Key grab triggered: {key}");
+                    }
+                    Event::FrameStatsUpdated { stats } => {
+                        let mut stats = stats.into_iter().collect::<Vec<_>>();
+                        stats.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+                        for (name, stats) in stats {
+                            let FrameStats {
+                                render_time_avg_us,
+                                missed_frames,
+                                frames,
+                                ..
+                            } = stats;
+                            println!(
+                                "Frame stats for {name}: {render_time_avg_us} µs average render \
+                                 time, {missed_frames} of {frames} frames missed"
+                            );
+                        }
+                    }
+                    Event::LayersChanged { layers } => {
+                        println!("Layers changed: {layers:?}");
+                    }
+                    Event::LayerMappedOrChanged { layer } => {
+                        println!("Layer mapped or changed: {layer:?}");
+                    }
+                    Event::LayerUnmapped { id } => {
+                        println!("Layer unmapped: {id}");
+                    }
+                    Event::LayerExclusiveZoneChanged { id, exclusive_zone } => {
+                        println!("Layer {id}: exclusive zone changed to {exclusive_zone}");
+                    }
+                    Event::OutputProfileActivated { name } => {
+                        if let Some(name) = name {
+                            println!("Output profile activated: {name}");
+                        } else {
+                            println!("Output profile deactivated");
+                        }
+                    }
+                    Event::BindModeChanged { name } => {
+                        if let Some(name) = name {
+                            println!("Bind mode entered: {name}");
+                        } else {
+                            println!("Bind mode exited");
+                        }
+                    }
                 }
             }
         }
@@ -518,6 +728,386 @@ pub fn handle_msg(mut msg: Msg, json: bool) -> anyhow::Result<()> {
                 println!("Overview is closed.");
             }
         }
+        Msg::ScrollingView { .. } => {
+            let Response::ScrollingView(response) = response else {
+                bail!("unexpected response: expected ScrollingView, got {response:?}");
+            };
+
+            if json {
+                let response =
+                    serde_json::to_string(&response).context("error formatting response")?;
+                println!("{response}");
+                return Ok(());
+            }
+
+            println!("Workspace ID: {}", response.workspace_id);
+            println!("View position: {}", fmt_rounded(response.view_pos));
+            if response.target_view_pos != response.view_pos {
+                println!(
+                    "Target view position: {}",
+                    fmt_rounded(response.target_view_pos)
+                );
+            }
+            println!("View width: {}", fmt_rounded(response.view_width));
+
+            if response.columns.is_empty() {
+                println!("No columns.");
+            }
+
+            for (idx, column) in response.columns.iter().enumerate() {
+                let active = if response.active_column_idx == Some(idx) {
+                    " (active)"
+                } else {
+                    ""
+                };
+                println!("Column {}{active}:", idx + 1);
+                println!("  X: {}", fmt_rounded(column.x));
+                println!("  Width: {}", fmt_rounded(column.width));
+
+                let ids = column.window_ids.iter().map(|id| id.to_string());
+                println!("  Window IDs: {}", ids.collect::<Vec<_>>().join(", "));
+            }
+        }
+        Msg::Blur { .. } => {
+            let Response::Blur(response) = response else {
+                bail!("unexpected response: expected Blur, got {response:?}");
+            };
+
+            if json {
+                let response =
+                    serde_json::to_string(&response).context("error formatting response")?;
+                println!("{response}");
+                return Ok(());
+            }
+
+            let Blur {
+                on,
+                passes,
+                radius,
+                noise,
+                brightness,
+                contrast,
+                saturation,
+            } = response;
+            println!("Blur: {}", if on { "on" } else { "off" });
+            println!("  Passes: {passes}");
+            println!("  Radius: {radius}");
+            println!("  Noise: {noise}");
+            println!("  Brightness: {brightness}");
+            println!("  Contrast: {contrast}");
+            println!("  Saturation: {saturation}");
+        }
+        Msg::SetBlur { .. } => {
+            let Response::Handled = response else {
+                bail!("unexpected response: expected Handled, got {response:?}");
+            };
+        }
+        Msg::WindowDecorations { .. } => {
+            let Response::WindowDecorations(response) = response else {
+                bail!("unexpected response: expected WindowDecorations, got {response:?}");
+            };
+
+            if json {
+                let response =
+                    serde_json::to_string(&response).context("error formatting response")?;
+                println!("{response}");
+                return Ok(());
+            }
+
+            let on_off = |on| if on { "on" } else { "off" };
+            println!("Border: {}", on_off(response.border));
+            println!("Shadow: {}", on_off(response.shadow));
+            println!("Blur: {}", on_off(response.blur));
+        }
+        Msg::SetWindowDecorations { .. } => {
+            let Response::Handled = response else {
+                bail!("unexpected response: expected Handled, got {response:?}");
+            };
+        }
+        Msg::WindowThumbnail { path, .. } => {
+            let Response::WindowThumbnail(response) = response else {
+                bail!("unexpected response: expected WindowThumbnail, got {response:?}");
+            };
+
+            if json {
+                let response =
+                    serde_json::to_string(&response).context("error formatting response")?;
+                println!("{response}");
+                return Ok(());
+            }
+
+            if let Some(path) = path {
+                fs::write(path, &response.png).context("error writing the thumbnail")?;
+            } else {
+                io::stdout()
+                    .write_all(&response.png)
+                    .context("error writing the thumbnail to stdout")?;
+            }
+        }
+        Msg::Pointer => {
+            let Response::Pointer(response) = response else {
+                bail!("unexpected response: expected Pointer, got {response:?}");
+            };
+
+            if json {
+                let response =
+                    serde_json::to_string(&response).context("error formatting response")?;
+                println!("{response}");
+                return Ok(());
+            }
+
+            let Pointer {
+                x,
+                y,
+                output,
+                pos_in_output,
+                window_id,
+                focused_window_id,
+            } = response;
+
+            println!("Position: {}, {}", fmt_rounded(x), fmt_rounded(y));
+
+            if let (Some(output), Some((x, y))) = (output, pos_in_output) {
+                println!(
+                    "Output: \"{output}\" at {}, {}",
+                    fmt_rounded(x),
+                    fmt_rounded(y)
+                );
+            } else {
+                println!("Output: (none)");
+            }
+
+            if let Some(id) = window_id {
+                println!("Window under pointer: {id}");
+            } else {
+                println!("Window under pointer: (none)");
+            }
+
+            if let Some(id) = focused_window_id {
+                println!("Focused window: {id}");
+            } else {
+                println!("Focused window: (none)");
+            }
+        }
+        Msg::FrameStats => {
+            let Response::FrameStats(response) = response else {
+                bail!("unexpected response: expected FrameStats, got {response:?}");
+            };
+
+            if json {
+                let response =
+                    serde_json::to_string(&response).context("error formatting response")?;
+                println!("{response}");
+                return Ok(());
+            }
+
+            let mut stats = response.into_iter().collect::<Vec<_>>();
+            stats.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+            for (name, stats) in stats {
+                print_frame_stats(&name, &stats);
+                println!();
+            }
+        }
+        Msg::InjectInput { .. } | Msg::UngrabKey { .. } => {
+            let Response::Handled = response else {
+                bail!("unexpected response: expected Handled, got {response:?}");
+            };
+        }
+        Msg::GrabKey { key } => {
+            let Response::Handled = response else {
+                bail!("unexpected response: expected Handled, got {response:?}");
+            };
+
+            // The grab lasts as long as this connection, so keep it open and print the presses.
+            let reply = socket
+                .send(Request::EventStream)
+                .context("error communicating with niri")?;
+            reply.map_err(|err_msg| anyhow!(err_msg).context("niri returned an error"))?;
+
+            if !json {
+                println!("Grabbed {key}, press Ctrl+C to release it.");
+            }
+
+            let mut read_event = socket.read_events();
+            loop {
+                let event = read_event().context("error reading event from niri")?;
+                let Event::KeyGrabTriggered { key: pressed } = &event else {
+                    continue;
+                };
+                if pressed != key {
+                    continue;
+                }
+
+                if json {
+                    let event = serde_json::to_string(&event).context("error formatting event")?;
+                    println!("{event}");
+                } else {
+                    println!("{pressed} pressed");
+                }
+            }
+        }
+        Msg::SaveOutputConfig { path } => {
+            let Response::Handled = response else {
+                bail!("unexpected response: expected Handled, got {response:?}");
+            };
+
+            if !json {
+                println!("Saved output config to {path}");
+            }
+        }
+        Msg::Config { action } => {
+            let Response::Handled = response else {
+                bail!("unexpected response: expected Handled, got {response:?}");
+            };
+
+            if !json {
+                match action {
+                    ConfigAction::Try { timeout, .. } => println!(
+                        "Config applied, run `niri msg config confirm` within {timeout} seconds \
+                         to keep it"
+                    ),
+                    ConfigAction::Confirm => println!("Config confirmed"),
+                    ConfigAction::Reload { .. } => println!("Config reloaded"),
+                }
+            }
+        }
+        Msg::Debug {
+            action: DebugAction::ProtocolStats,
+        } => {
+            let Response::ProtocolStats(mut response) = response else {
+                bail!("unexpected response: expected ProtocolStats, got {response:?}");
+            };
+
+            if json {
+                let response =
+                    serde_json::to_string(&response).context("error formatting response")?;
+                println!("{response}");
+                return Ok(());
+            }
+
+            // Show the busiest clients first.
+            response.sort_unstable_by(|a, b| b.commit_rate.cmp(&a.commit_rate));
+
+            for stats in response {
+                print_protocol_stats(&stats);
+                println!();
+            }
+        }
+        Msg::Widget {
+            action: WidgetAction::List,
+        } => {
+            let Response::Widgets(response) = response else {
+                bail!("unexpected response: expected Widgets, got {response:?}");
+            };
+
+            if json {
+                let response =
+                    serde_json::to_string(&response).context("error formatting response")?;
+                println!("{response}");
+                return Ok(());
+            }
+
+            for widget in response {
+                print_widget(&widget);
+                println!();
+            }
+        }
+        Msg::Widget { .. } => {
+            let Response::Handled = response else {
+                bail!("unexpected response: expected Handled, got {response:?}");
+            };
+        }
+        Msg::WindowRule {
+            action: WindowRuleAction::Add { .. },
+        } => {
+            let Response::WindowRuleAdded(id) = response else {
+                bail!("unexpected response: expected WindowRuleAdded, got {response:?}");
+            };
+
+            if json {
+                let response = serde_json::to_string(&id).context("error formatting response")?;
+                println!("{response}");
+                return Ok(());
+            }
+
+            println!("{id}");
+        }
+        Msg::WindowRule {
+            action: WindowRuleAction::List,
+        } => {
+            let Response::WindowRules(response) = response else {
+                bail!("unexpected response: expected WindowRules, got {response:?}");
+            };
+
+            if json {
+                let response =
+                    serde_json::to_string(&response).context("error formatting response")?;
+                println!("{response}");
+                return Ok(());
+            }
+
+            if response.is_empty() {
+                println!("No window rules added over IPC.");
+            }
+
+            for rule in response {
+                println!("Window rule {}:", rule.id);
+                for line in rule.rule.lines() {
+                    println!("  {line}");
+                }
+                println!();
+            }
+        }
+        Msg::ClipboardHistory => {
+            let Response::ClipboardHistory(response) = response else {
+                bail!("unexpected response: expected ClipboardHistory, got {response:?}");
+            };
+
+            if json {
+                let response =
+                    serde_json::to_string(&response).context("error formatting response")?;
+                println!("{response}");
+                return Ok(());
+            }
+
+            if response.is_empty() {
+                println!("The clipboard history is empty.");
+            }
+
+            for entry in response {
+                let text = match &entry.text {
+                    Some(text) => {
+                        let line = text.lines().next().unwrap_or_default();
+                        let mut preview: String = line.chars().take(60).collect();
+                        if preview.len() < text.len() {
+                            preview.push('…');
+                        }
+                        format!("{preview:?}")
+                    }
+                    None => format!("{}, {} bytes", entry.mime_type, entry.size),
+                };
+                println!("{}: {text}", entry.id);
+            }
+        }
+        Msg::ActivationToken => {
+            let Response::ActivationToken(token) = response else {
+                bail!("unexpected response: expected ActivationToken, got {response:?}");
+            };
+
+            if json {
+                let token = serde_json::to_string(&token).context("error formatting response")?;
+                println!("{token}");
+                return Ok(());
+            }
+
+            println!("{token}");
+        }
+        Msg::WindowRule { .. } => {
+            let Response::Handled = response else {
+                bail!("unexpected response: expected Handled, got {response:?}");
+            };
+        }
     }
 
     Ok(())
@@ -637,6 +1227,34 @@ fn print_output(output: Output) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn print_widget(widget: &Widget) {
+    println!("Widget \"{}\":", widget.id);
+
+    if let Some(output) = &widget.output {
+        println!("  Output: {output}");
+    } else {
+        println!("  Output: (all)");
+    }
+
+    let (x, y) = widget.offset;
+    println!(
+        "  Anchor: {:?}, offset {}, {}",
+        widget.anchor,
+        fmt_rounded(x),
+        fmt_rounded(y)
+    );
+
+    if let Some(text) = &widget.text {
+        println!("  Text: \"{text}\"");
+    }
+    if let Some(progress) = widget.progress {
+        println!("  Progress: {}", fmt_rounded(progress));
+    }
+    if let Some(icon) = &widget.icon {
+        println!("  Icon: {icon}");
+    }
+}
+
 fn print_window(window: &Window) {
     let focused = if window.is_focused { " (focused)" } else { "" };
     let urgent = if window.is_urgent { " (urgent)" } else { "" };
@@ -648,6 +1266,10 @@ fn print_window(window: &Window) {
         println!("  Title: (unset)");
     }
 
+    if window.title.as_ref() != Some(&window.display_title) {
+        println!("  Display title: \"{}\"", window.display_title);
+    }
+
     if let Some(app_id) = &window.app_id {
         println!("  App ID: \"{app_id}\"");
     } else {
@@ -671,6 +1293,12 @@ fn print_window(window: &Window) {
         println!("  Workspace ID: (none)");
     }
 
+    println!("  Opacity: {}", fmt_rounded(window.opacity));
+
+    if !window.marks.is_empty() {
+        println!("  Marks: {}", window.marks.join(", "));
+    }
+
     let WindowLayout {
         pos_in_scrolling_layout,
         tile_size,
@@ -715,6 +1343,73 @@ fn fmt_rounded(x: f64) -> String {
     }
 }
 
+fn print_frame_stats(name: &str, stats: &FrameStats) {
+    let FrameStats {
+        frames,
+        render_time_avg_us,
+        render_time_max_us,
+        presentation_latency_avg_us,
+        missed_frames,
+        refresh_rate,
+        vrr,
+    } = stats;
+
+    println!("Output \"{name}\":");
+
+    if let Some(refresh_rate) = refresh_rate {
+        let refresh = *refresh_rate as f64 / 1000.;
+        let vrr = if *vrr { " (VRR)" } else { "" };
+        println!("  Refresh rate: {refresh:.3} Hz{vrr}");
+    } else {
+        println!("  Refresh rate: unknown");
+    }
+
+    println!("  Frames: {frames}");
+    println!("  Missed frames: {missed_frames}");
+    println!("  Render time: {render_time_avg_us} µs average, {render_time_max_us} µs max");
+
+    if let Some(latency) = presentation_latency_avg_us {
+        println!("  Render to presentation: {latency} µs average");
+    } else {
+        println!("  Render to presentation: unknown");
+    }
+}
+
+fn print_protocol_stats(stats: &ClientProtocolStats) {
+    let ClientProtocolStats {
+        pid,
+        name,
+        objects,
+        commits,
+        commit_rate,
+        excessive_commit_rate,
+        oversized_surfaces,
+    } = stats;
+
+    let name = name.as_deref().unwrap_or("(unknown)");
+    if let Some(pid) = pid {
+        println!("Client \"{name}\" (PID {pid}):");
+    } else {
+        println!("Client \"{name}\":");
+    }
+
+    println!("  Commits: {commits} total, {commit_rate} in the last second");
+    if *excessive_commit_rate {
+        println!("  Warning: the client is committing at an excessive rate");
+    }
+
+    for (w, h) in oversized_surfaces {
+        println!("  Warning: oversized surface of {w}×{h}");
+    }
+
+    println!("  Objects:");
+    let mut objects = objects.iter().collect::<Vec<_>>();
+    objects.sort_unstable();
+    for (interface, count) in objects {
+        println!("    {interface}: {count}");
+    }
+}
+
 fn ensure_absolute_path(path: &mut String) -> anyhow::Result<()> {
     let p = Path::new(path);
     if p.is_relative() {
@@ -728,6 +1423,31 @@ fn ensure_absolute_path(path: &mut String) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Parses the `niri msg batch` arguments, each written like a `niri msg action` command.
+fn parse_batch_actions(actions: &[String]) -> anyhow::Result<Vec<Action>> {
+    actions
+        .iter()
+        .map(|words| {
+            let args = shlex::split(words)
+                .ok_or_else(|| anyhow!("error parsing action {words:?}: unbalanced quotes"))?;
+            let mut action =
+                Action::try_parse_from(iter::once("niri msg batch".to_owned()).chain(args))
+                    .map_err(|err| anyhow!("error parsing action {words:?}: {err}"))?;
+
+            if let Action::Screenshot { path, .. }
+            | Action::ScreenshotScreen { path, .. }
+            | Action::ScreenshotWindow { path, .. } = &mut action
+            {
+                if let Some(path) = path {
+                    ensure_absolute_path(path).context("error making the path absolute")?;
+                }
+            }
+
+            Ok(action)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use insta::assert_snapshot;
@@ -744,4 +1464,27 @@ mod tests {
         assert_snapshot!(fmt_rounded(2.006), @"2.01");
         assert_snapshot!(fmt_rounded(2.1), @"2.10");
     }
+
+    #[test]
+    fn test_parse_batch_actions() {
+        let actions =
+            parse_batch_actions(&["focus-workspace 2".into(), "center-column".into()]).unwrap();
+        assert!(matches!(
+            actions[..],
+            [Action::FocusWorkspace { .. }, Action::CenterColumn {}]
+        ));
+
+        assert!(parse_batch_actions(&["no-such-action".into()]).is_err());
+        assert!(parse_batch_actions(&["spawn \"echo".into()]).is_err());
+    }
+
+    #[test]
+    fn test_parse_batch_actions_quoted() {
+        let actions =
+            parse_batch_actions(&["spawn -- sh -c 'notify-send \"a b\"'".into()]).unwrap();
+        let [Action::Spawn { command }] = &actions[..] else {
+            panic!("unexpected actions: {actions:?}");
+        };
+        assert_eq!(command, &["sh", "-c", "notify-send \"a b\""]);
+    }
 }
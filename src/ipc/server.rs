@@ -4,8 +4,10 @@ use std::ffi::OsStr;
 use std::os::unix::net::{UnixListener, UnixStream};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::{env, io, process};
+use std::time::Duration;
+use std::{env, fs, io, process, thread};
 
 use anyhow::Context;
 use async_channel::{Receiver, Sender, TrySendError};
@@ -14,11 +16,11 @@ use calloop::io::Async;
 use directories::BaseDirs;
 use futures_util::io::{AsyncReadExt, BufReader};
 use futures_util::{select_biased, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, FutureExt as _};
-use niri_config::OutputName;
+use niri_config::{OutputName, TitleFallback};
 use niri_ipc::state::{EventStreamState, EventStreamStatePart as _};
 use niri_ipc::{
-    Action, Event, KeyboardLayouts, OutputConfigChanged, Overview, Reply, Request, Response,
-    Timestamp, WindowLayout, Workspace,
+    Action, Event, IpcSchema, KeyboardLayouts, OutputConfigChanged, Overview, Reply, Request,
+    Response, Timestamp, WindowLayout, Workspace,
 };
 use smithay::desktop::layer_map_for_output;
 use smithay::input::pointer::{
@@ -27,12 +29,14 @@ use smithay::input::pointer::{
 use smithay::reexports::calloop::generic::Generic;
 use smithay::reexports::calloop::{Interest, LoopHandle, Mode, PostAction};
 use smithay::reexports::rustix::fs::unlink;
-use smithay::utils::SERIAL_COUNTER;
-use smithay::wayland::shell::wlr_layer::{KeyboardInteractivity, Layer};
+use smithay::utils::{Logical, Rectangle, SERIAL_COUNTER};
+use smithay::wayland::shell::wlr_layer::{ExclusiveZone, KeyboardInteractivity, Layer};
 
 use crate::backend::IpcOutputMap;
 use crate::input::pick_window_grab::PickWindowGrab;
+use crate::layer::MappedLayer;
 use crate::layout::workspace::WorkspaceId;
+use crate::layout::LayoutElement as _;
 use crate::niri::State;
 use crate::utils::{version, with_toplevel_role};
 use crate::window::Mapped;
@@ -51,6 +55,8 @@ pub struct IpcServer {
 }
 
 struct ClientCtx {
+    /// Unique id of this client connection, which owns the key grabs it makes.
+    client_id: u64,
     event_loop: LoopHandle<'static, State>,
     scheduler: Scheduler<()>,
     ipc_outputs: Arc<Mutex<IpcOutputMap>>,
@@ -58,6 +64,20 @@ struct ClientCtx {
     event_stream_state: Rc<RefCell<EventStreamState>>,
 }
 
+/// Releases the key grabs of an IPC client when dropped, i.e. once its connection closes.
+struct KeyGrabsGuard {
+    event_loop: LoopHandle<'static, State>,
+    client_id: u64,
+}
+
+impl Drop for KeyGrabsGuard {
+    fn drop(&mut self) {
+        let client_id = self.client_id;
+        self.event_loop
+            .insert_idle(move |state| state.niri.release_ipc_key_grabs(client_id));
+    }
+}
+
 struct EventStreamClient {
     events: Receiver<Event>,
     disconnect: Receiver<()>,
@@ -166,7 +186,10 @@ fn on_new_ipc_client(state: &mut State, stream: UnixStream) {
 
     let ipc_server = state.niri.ipc_server.as_ref().unwrap();
 
+    static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(0);
+
     let ctx = ClientCtx {
+        client_id: NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed),
         event_loop: state.niri.event_loop.clone(),
         scheduler: state.niri.scheduler.clone(),
         ipc_outputs: state.backend.ipc_outputs(),
@@ -188,6 +211,11 @@ async fn handle_client(ctx: ClientCtx, stream: Async<'static, UnixStream>) -> an
     let (read, mut write) = stream.split();
     let mut read = BufReader::new(read);
 
+    let key_grabs = KeyGrabsGuard {
+        event_loop: ctx.event_loop.clone(),
+        client_id: ctx.client_id,
+    };
+
     loop {
         // Don't keep buf around to avoid clients wasting RAM by filling it with bogus data.
         let mut buf = Vec::new();
@@ -238,6 +266,8 @@ async fn handle_client(ctx: ClientCtx, stream: Async<'static, UnixStream>) -> an
                 if let Err(err) = handle_event_stream_client(client).await {
                     warn!("error handling IPC event stream client: {err:?}");
                 }
+                // Event stream clients keep their key grabs until they stop reading events.
+                drop(key_grabs);
             };
             if let Err(err) = ctx.scheduler.schedule(future) {
                 warn!("error scheduling IPC event stream future: {err:?}");
@@ -288,44 +318,14 @@ async fn process(ctx: &ClientCtx, request: Request) -> Reply {
             Response::Windows(windows)
         }
         Request::Layers => {
-            let (tx, rx) = async_channel::bounded(1);
-            ctx.event_loop.insert_idle(move |state| {
-                let mut layers = Vec::new();
-                for output in state.niri.global_space.outputs() {
-                    let name = output.name();
-                    for surface in layer_map_for_output(output).layers() {
-                        let layer = match surface.layer() {
-                            Layer::Background => niri_ipc::Layer::Background,
-                            Layer::Bottom => niri_ipc::Layer::Bottom,
-                            Layer::Top => niri_ipc::Layer::Top,
-                            Layer::Overlay => niri_ipc::Layer::Overlay,
-                        };
-                        let keyboard_interactivity =
-                            match surface.cached_state().keyboard_interactivity {
-                                KeyboardInteractivity::None => {
-                                    niri_ipc::LayerSurfaceKeyboardInteractivity::None
-                                }
-                                KeyboardInteractivity::Exclusive => {
-                                    niri_ipc::LayerSurfaceKeyboardInteractivity::Exclusive
-                                }
-                                KeyboardInteractivity::OnDemand => {
-                                    niri_ipc::LayerSurfaceKeyboardInteractivity::OnDemand
-                                }
-                            };
-
-                        layers.push(niri_ipc::LayerSurface {
-                            namespace: surface.namespace().to_owned(),
-                            output: name.clone(),
-                            layer,
-                            keyboard_interactivity,
-                        });
-                    }
-                }
-
-                let _ = tx.send_blocking(layers);
+            let state = ctx.event_stream_state.borrow();
+            let mut layers: Vec<_> = state.layers.layers.values().cloned().collect();
+            // The state is a map, so sort for a stable order between requests.
+            layers.sort_by(|a, b| {
+                Ord::cmp(&a.output, &b.output)
+                    .then_with(|| Ord::cmp(&a.layer, &b.layer))
+                    .then_with(|| Ord::cmp(&a.id, &b.id))
             });
-            let result = rx.recv().await;
-            let layers = result.map_err(|_| String::from("error getting layers info"))?;
             Response::Layers(layers)
         }
         Request::KeyboardLayouts => {
@@ -381,6 +381,20 @@ async fn process(ctx: &ClientCtx, request: Request) -> Reply {
         Request::Action(action) => {
             validate_action(&action)?;
 
+            // Screenshots written to disk reply with the path once the file is saved.
+            let saves_screenshot = matches!(
+                action,
+                Action::ScreenshotScreen {
+                    write_to_disk: true,
+                    ..
+                } | Action::ScreenshotWindow {
+                    write_to_disk: true,
+                    ..
+                }
+            );
+            let (screenshot_tx, screenshot_rx) = async_channel::bounded(1);
+            let screenshot_tx = saves_screenshot.then_some(screenshot_tx);
+
             let (tx, rx) = async_channel::bounded(1);
 
             let action = niri_config::Action::from(action);
@@ -388,13 +402,44 @@ async fn process(ctx: &ClientCtx, request: Request) -> Reply {
                 // Make sure some logic like workspace clean-up has a chance to run before doing
                 // actions.
                 state.niri.advance_animations();
+                state.niri.ipc_screenshot_reply = screenshot_tx;
                 state.do_action(action, false);
+                // Drop the reply if the action didn't get to taking the screenshot.
+                state.niri.ipc_screenshot_reply = None;
                 let _ = tx.send_blocking(());
             });
 
             // Wait until the action has been processed before returning. This is important for a
             // few actions, for instance for DoScreenTransition this wait ensures that the screen
             // contents were sampled into the texture.
+            let _ = rx.recv().await;
+
+            if saves_screenshot {
+                let result = screenshot_rx.recv().await;
+                let path = result.map_err(|_| String::from("no screenshot was taken"))??;
+                Response::ScreenshotSaved(path)
+            } else {
+                Response::Handled
+            }
+        }
+        Request::Batch(actions) => {
+            for action in &actions {
+                validate_action(action)?;
+            }
+
+            let (tx, rx) = async_channel::bounded(1);
+
+            let actions: Vec<_> = actions.into_iter().map(niri_config::Action::from).collect();
+            ctx.event_loop.insert_idle(move |state| {
+                state.niri.advance_animations();
+                // Running everything in one idle callback means that no frames are rendered and no
+                // configures are sent until the whole batch is done.
+                for action in actions {
+                    state.do_action(action, false);
+                }
+                let _ = tx.send_blocking(());
+            });
+
             let _ = rx.recv().await;
             Response::Handled
         }
@@ -418,6 +463,40 @@ async fn process(ctx: &ClientCtx, request: Request) -> Reply {
 
             Response::OutputConfigChanged(response)
         }
+        Request::SaveOutputConfig { path } => {
+            let path = PathBuf::from(path);
+            if !path.is_absolute() {
+                return Err(format!("path must be absolute: {}", path.display()));
+            }
+
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let config = state.niri.config.borrow();
+                let ipc_outputs = state.backend.ipc_outputs();
+                let ipc_outputs = ipc_outputs.lock().unwrap();
+                let kdl =
+                    niri_config::output::format_outputs_kdl(ipc_outputs.values(), &config.outputs);
+                let _ = tx.send_blocking(kdl);
+            });
+            let kdl = rx.recv().await;
+            let kdl = kdl.map_err(|_| String::from("error getting the output config"))?;
+
+            // Don't block the compositor on a slow filesystem.
+            let (tx, rx) = async_channel::bounded(1);
+            let res = thread::Builder::new()
+                .name("Output Config Writer".to_owned())
+                .spawn(move || {
+                    let _ = tx.send_blocking(write_output_config(&path, kdl));
+                });
+            if let Err(err) = res {
+                return Err(format!("error spawning the writer thread: {err}"));
+            }
+
+            let res = rx.recv().await;
+            res.map_err(|_| String::from("error writing the output config"))??;
+
+            Response::Handled
+        }
         Request::FocusedOutput => {
             let (tx, rx) = async_channel::bounded(1);
             ctx.event_loop.insert_idle(move |state| {
@@ -450,11 +529,268 @@ async fn process(ctx: &ClientCtx, request: Request) -> Reply {
             let is_open = state.overview.is_open;
             Response::OverviewState(Overview { is_open })
         }
+        Request::ScrollingView { workspace_id } => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.niri.ipc_scrolling_view(workspace_id));
+            });
+            let result = rx.recv().await;
+            let view = result.map_err(|_| String::from("error getting scrolling view"))??;
+            Response::ScrollingView(view)
+        }
+        Request::Blur { output, window } => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let result = state.ipc_blur(output.as_deref(), window);
+                let _ = tx.send_blocking(result);
+            });
+            let result = rx.recv().await;
+            let blur = result.map_err(|_| String::from("error getting blur settings"))??;
+            Response::Blur(blur)
+        }
+        Request::SetBlur {
+            output,
+            window,
+            change,
+        } => {
+            change.validate()?;
+
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let result = state.apply_transient_blur_config(output, window, change);
+                let _ = tx.send_blocking(result);
+            });
+            let result = rx.recv().await;
+            result.map_err(|_| String::from("error changing blur settings"))??;
+            Response::Handled
+        }
+        Request::WindowDecorations { id } => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let result = state.ipc_window_decorations(id);
+                let _ = tx.send_blocking(result);
+            });
+            let result = rx.recv().await;
+            let decorations =
+                result.map_err(|_| String::from("error getting window decorations"))??;
+            Response::WindowDecorations(decorations)
+        }
+        Request::SetWindowDecorations { id, change } => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let result = state.set_window_decorations(id, change);
+                let _ = tx.send_blocking(result);
+            });
+            let result = rx.recv().await;
+            result.map_err(|_| String::from("error changing window decorations"))??;
+            Response::Handled
+        }
+        Request::WindowThumbnail { id, max_size } => {
+            if max_size == 0 {
+                return Err(String::from("max size must be greater than 0"));
+            }
+
+            let client_id = ctx.client_id;
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let result = state.window_thumbnail(client_id, id, max_size);
+                let _ = tx.send_blocking(result);
+            });
+            let result = rx.recv().await;
+            let thumbnail =
+                result.map_err(|_| String::from("error getting window thumbnail"))??;
+            Response::WindowThumbnail(thumbnail)
+        }
+        Request::Pointer => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.niri.ipc_pointer());
+            });
+            let result = rx.recv().await;
+            let pointer = result.map_err(|_| String::from("error getting pointer info"))?;
+            Response::Pointer(pointer)
+        }
+        Request::InjectInput(input) => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.inject_input(input));
+            });
+            let result = rx.recv().await;
+            result.map_err(|_| String::from("error injecting input"))??;
+            Response::Handled
+        }
+        Request::GrabKey { key } => {
+            let parsed: niri_config::Key = key
+                .parse()
+                .map_err(|err| format!("invalid key {key}: {err}"))?;
+
+            let client_id = ctx.client_id;
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.niri.grab_ipc_key(parsed, key, client_id));
+            });
+            let result = rx.recv().await;
+            result.map_err(|_| String::from("error grabbing key"))??;
+            Response::Handled
+        }
+        Request::UngrabKey { key } => {
+            let parsed: niri_config::Key = key
+                .parse()
+                .map_err(|err| format!("invalid key {key}: {err}"))?;
+
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.niri.ungrab_ipc_key(parsed, &key));
+            });
+            let result = rx.recv().await;
+            result.map_err(|_| String::from("error ungrabbing key"))??;
+            Response::Handled
+        }
+        Request::TryConfig { path, timeout_secs } => {
+            let path = Path::new(&path);
+            if !path.is_absolute() {
+                return Err(format!("path must be absolute: {}", path.display()));
+            }
+
+            let config = niri_config::Config::load(path)
+                .config
+                .map_err(|err| format!("{err:?}"))?;
+            let timeout = Duration::from_secs(timeout_secs);
+
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.try_config(config, timeout));
+            });
+            let result = rx.recv().await;
+            result.map_err(|_| String::from("error trying config"))??;
+            Response::Handled
+        }
+        Request::ConfirmConfig => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.confirm_config());
+            });
+            let result = rx.recv().await;
+            result.map_err(|_| String::from("error confirming config"))??;
+            Response::Handled
+        }
+        Request::ReloadConfig { sections } => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.reload_config_sections(&sections));
+            });
+            let result = rx.recv().await;
+            result.map_err(|_| String::from("error reloading config"))??;
+            Response::Handled
+        }
+        Request::FrameStats => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.niri.ipc_frame_stats());
+            });
+            let result = rx.recv().await;
+            let stats = result.map_err(|_| String::from("error getting frame stats"))?;
+            Response::FrameStats(stats)
+        }
+        Request::ProtocolStats => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.niri.ipc_protocol_stats());
+            });
+            let result = rx.recv().await;
+            let stats = result.map_err(|_| String::from("error getting protocol stats"))?;
+            Response::ProtocolStats(stats)
+        }
+        Request::Schema => Response::Schema(IpcSchema::generate(version())),
+        Request::SetWidget(widget) => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.niri.set_overlay_widget(widget));
+            });
+            let result = rx.recv().await;
+            result.map_err(|_| String::from("error setting widget"))??;
+            Response::Handled
+        }
+        Request::RemoveWidget { id } => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.niri.remove_overlay_widget(&id));
+            });
+            let result = rx.recv().await;
+            result.map_err(|_| String::from("error removing widget"))??;
+            Response::Handled
+        }
+        Request::Widgets => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let widgets = state.niri.overlay_widgets.widgets().cloned().collect();
+                let _ = tx.send_blocking(widgets);
+            });
+            let result = rx.recv().await;
+            let widgets = result.map_err(|_| String::from("error getting widgets"))?;
+            Response::Widgets(widgets)
+        }
+        Request::AddWindowRule { rule } => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.add_window_rule(rule));
+            });
+            let result = rx.recv().await;
+            let id = result.map_err(|_| String::from("error adding window rule"))??;
+            Response::WindowRuleAdded(id)
+        }
+        Request::RemoveWindowRule { id } => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.remove_window_rule(id));
+            });
+            let result = rx.recv().await;
+            result.map_err(|_| String::from("error removing window rule"))??;
+            Response::Handled
+        }
+        Request::WindowRules => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let rules = state.niri.ipc_window_rules.iter();
+                let rules = rules.map(|(rule, _)| rule.clone()).collect();
+                let _ = tx.send_blocking(rules);
+            });
+            let result = rx.recv().await;
+            let rules = result.map_err(|_| String::from("error getting window rules"))?;
+            Response::WindowRules(rules)
+        }
+        Request::ClipboardHistory => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.niri.clipboard_history.to_ipc());
+            });
+            let result = rx.recv().await;
+            let entries = result.map_err(|_| String::from("error getting clipboard history"))?;
+            Response::ClipboardHistory(entries)
+        }
+        Request::ActivationToken => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let (token, _) = state.niri.activation_state.create_external_token(None);
+                let _ = tx.send_blocking(token.as_str().to_owned());
+            });
+            let result = rx.recv().await;
+            let token = result.map_err(|_| String::from("error creating activation token"))?;
+            Response::ActivationToken(token)
+        }
     };
 
     Ok(response)
 }
 
+fn write_output_config(path: &Path, kdl: String) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("error creating {}: {err}", parent.display()))?;
+    }
+    fs::write(path, kdl).map_err(|err| format!("error writing {}: {err}", path.display()))
+}
+
 fn validate_action(action: &Action) -> Result<(), String> {
     if let Action::Screenshot { path, .. }
     | Action::ScreenshotScreen { path, .. }
@@ -503,21 +839,70 @@ fn make_ipc_window(
     mapped: &Mapped,
     workspace_id: Option<WorkspaceId>,
     layout: WindowLayout,
+    title_fallback: TitleFallback,
 ) -> niri_ipc::Window {
     with_toplevel_role(mapped.toplevel(), |role| niri_ipc::Window {
         id: mapped.id().get(),
         title: role.title.clone(),
         app_id: role.app_id.clone(),
+        display_title: title_fallback.apply(
+            role.title.as_deref(),
+            role.app_id.as_deref(),
+            mapped.untitled_number(),
+        ),
         pid: mapped.credentials().map(|c| c.pid),
         workspace_id: workspace_id.map(|id| id.get()),
         is_focused: mapped.is_focused(),
         is_floating: mapped.is_floating(),
         is_urgent: mapped.is_urgent(),
+        opacity: f64::from(mapped.opacity()),
+        marks: mapped.marks().to_vec(),
         layout,
         focus_timestamp: mapped.get_focus_timestamp().map(Timestamp::from),
     })
 }
 
+fn make_ipc_layer(
+    mapped: &MappedLayer,
+    output_name: &str,
+    geo: Rectangle<i32, Logical>,
+) -> niri_ipc::LayerSurface {
+    let surface = mapped.surface();
+    let layer = match surface.layer() {
+        Layer::Background => niri_ipc::Layer::Background,
+        Layer::Bottom => niri_ipc::Layer::Bottom,
+        Layer::Top => niri_ipc::Layer::Top,
+        Layer::Overlay => niri_ipc::Layer::Overlay,
+    };
+
+    let cached = surface.cached_state();
+    let keyboard_interactivity = match cached.keyboard_interactivity {
+        KeyboardInteractivity::None => niri_ipc::LayerSurfaceKeyboardInteractivity::None,
+        KeyboardInteractivity::Exclusive => niri_ipc::LayerSurfaceKeyboardInteractivity::Exclusive,
+        KeyboardInteractivity::OnDemand => niri_ipc::LayerSurfaceKeyboardInteractivity::OnDemand,
+    };
+    let exclusive_zone = match cached.exclusive_zone {
+        ExclusiveZone::Exclusive(x) => i32::try_from(x).unwrap_or(i32::MAX),
+        ExclusiveZone::Neutral => 0,
+        ExclusiveZone::DontCare => -1,
+    };
+
+    niri_ipc::LayerSurface {
+        id: mapped.id(),
+        namespace: surface.namespace().to_owned(),
+        output: output_name.to_owned(),
+        layer,
+        keyboard_interactivity,
+        pos_in_output: (geo.loc.x, geo.loc.y),
+        size: (geo.size.w, geo.size.h),
+        exclusive_zone,
+    }
+}
+
+fn ipc_color(color: niri_config::Color) -> [f64; 4] {
+    color.to_array_unpremul().map(f64::from)
+}
+
 impl State {
     pub fn ipc_keyboard_layouts_changed(&mut self) {
         let keyboard = self.niri.seat.get_keyboard().unwrap();
@@ -604,6 +989,8 @@ impl State {
             let output_name = mon.map(|mon| mon.output_name());
             if ipc_ws.idx != u8::try_from(ws_idx + 1).unwrap_or(u8::MAX)
                 || ipc_ws.name.as_ref() != ws.name()
+                || ipc_ws.icon.as_ref() != ws.icon()
+                || ipc_ws.color != ws.color().map(ipc_color)
                 || ipc_ws.output.as_ref() != output_name
             {
                 need_workspaces_changed = true;
@@ -654,6 +1041,8 @@ impl State {
                         id,
                         idx: u8::try_from(ws_idx + 1).unwrap_or(u8::MAX),
                         name: ws.name().cloned(),
+                        icon: ws.icon().cloned(),
+                        color: ws.color().map(ipc_color),
                         output: mon.map(|mon| mon.output_name().clone()),
                         is_urgent: ws.is_urgent(),
                         is_active: mon.is_some_and(|mon| mon.active_workspace_idx() == ws_idx),
@@ -684,6 +1073,13 @@ impl State {
 
         let mut events = Vec::new();
         let layout = &self.niri.layout;
+        let title_fallback = self
+            .niri
+            .config
+            .borrow()
+            .layout
+            .tab_indicator
+            .title_fallback;
 
         let mut batch_change_layouts: Vec<(u64, WindowLayout)> = Vec::new();
 
@@ -699,7 +1095,7 @@ impl State {
             }
 
             let Some(ipc_win) = state.windows.get(&id) else {
-                let window = make_ipc_window(mapped, ws_id, window_layout);
+                let window = make_ipc_window(mapped, ws_id, window_layout, title_fallback);
                 events.push(Event::WindowOpenedOrChanged { window });
                 return;
             };
@@ -707,13 +1103,20 @@ impl State {
             let workspace_id = ws_id.map(|id| id.get());
             let mut changed =
                 ipc_win.workspace_id != workspace_id || ipc_win.is_floating != mapped.is_floating();
+            changed |= ipc_win.opacity != f64::from(mapped.opacity());
+            changed |= ipc_win.marks != mapped.marks();
 
             changed |= with_toplevel_role(mapped.toplevel(), |role| {
                 ipc_win.title != role.title || ipc_win.app_id != role.app_id
             });
 
+            // The fallback title can also change with the config.
+            if !changed && ipc_win.title.as_deref().is_none_or(str::is_empty) {
+                changed = ipc_win.display_title != mapped.display_title(title_fallback);
+            }
+
             if changed {
-                let window = make_ipc_window(mapped, ws_id, window_layout);
+                let window = make_ipc_window(mapped, ws_id, window_layout, title_fallback);
                 events.push(Event::WindowOpenedOrChanged { window });
                 return;
             }
@@ -775,6 +1178,64 @@ impl State {
         }
     }
 
+    pub fn ipc_refresh_layers(&mut self) {
+        let Some(server) = &self.niri.ipc_server else {
+            return;
+        };
+
+        let _span = tracy_client::span!("State::ipc_refresh_layers");
+
+        let mut state = server.event_stream_state.borrow_mut();
+        let state = &mut state.layers;
+
+        let mut events = Vec::new();
+        let mut seen = HashSet::new();
+        for output in self.niri.global_space.outputs() {
+            let name = output.name();
+            let map = layer_map_for_output(output);
+            for surface in map.layers() {
+                let Some(mapped) = self.niri.mapped_layer_surfaces.get(surface) else {
+                    continue;
+                };
+
+                let geo = map.layer_geometry(surface).unwrap_or_default();
+                let layer = make_ipc_layer(mapped, &name, geo);
+                let id = layer.id;
+                seen.insert(id);
+
+                let Some(ipc_layer) = state.layers.get(&id) else {
+                    events.push(Event::LayerMappedOrChanged { layer });
+                    continue;
+                };
+
+                let exclusive_zone = layer.exclusive_zone;
+                let zone_changed = ipc_layer.exclusive_zone != exclusive_zone;
+                let other_changed = niri_ipc::LayerSurface {
+                    exclusive_zone: ipc_layer.exclusive_zone,
+                    ..layer.clone()
+                } != *ipc_layer;
+
+                if other_changed {
+                    events.push(Event::LayerMappedOrChanged { layer });
+                } else if zone_changed {
+                    events.push(Event::LayerExclusiveZoneChanged { id, exclusive_zone });
+                }
+            }
+        }
+
+        // Check for unmapped layer surfaces.
+        for id in state.layers.keys() {
+            if !seen.contains(id) {
+                events.push(Event::LayerUnmapped { id: *id });
+            }
+        }
+
+        for event in events {
+            state.apply(event.clone());
+            server.send_event(event);
+        }
+    }
+
     pub fn ipc_refresh_overview(&mut self) {
         let Some(server) = &self.niri.ipc_server else {
             return;
@@ -793,6 +1254,25 @@ impl State {
         server.send_event(event);
     }
 
+    pub fn ipc_refresh_bind_mode(&mut self) {
+        let Some(server) = &self.niri.ipc_server else {
+            return;
+        };
+
+        let mut state = server.event_stream_state.borrow_mut();
+        let state = &mut state.bind_mode;
+
+        if state.name == self.niri.bind_mode {
+            return;
+        }
+
+        let event = Event::BindModeChanged {
+            name: self.niri.bind_mode.clone(),
+        };
+        state.apply(event.clone());
+        server.send_event(event);
+    }
+
     pub fn ipc_config_loaded(&mut self, failed: bool) {
         let Some(server) = &self.niri.ipc_server else {
             return;
@@ -814,4 +1294,57 @@ impl State {
         state.apply(event.clone());
         server.send_event(event);
     }
+
+    pub fn ipc_windows_parked(&mut self, ids: Vec<u64>) {
+        let Some(server) = &self.niri.ipc_server else {
+            return;
+        };
+        let mut state = server.event_stream_state.borrow_mut();
+
+        let event = Event::WindowsParked { ids };
+        state.apply(event.clone());
+        server.send_event(event);
+    }
+
+    pub fn ipc_key_grab_triggered(&mut self, key: String) {
+        let Some(server) = &self.niri.ipc_server else {
+            return;
+        };
+        let mut state = server.event_stream_state.borrow_mut();
+
+        let event = Event::KeyGrabTriggered { key };
+        state.apply(event.clone());
+        server.send_event(event);
+    }
+
+    pub fn ipc_frame_stats_updated(&mut self) {
+        let Some(server) = &self.niri.ipc_server else {
+            return;
+        };
+        let mut state = server.event_stream_state.borrow_mut();
+
+        let stats = self.niri.ipc_frame_stats();
+        let event = Event::FrameStatsUpdated { stats };
+        state.apply(event.clone());
+        server.send_event(event);
+    }
+
+    pub fn ipc_output_profile_activated(&mut self) {
+        let Some(server) = &self.niri.ipc_server else {
+            return;
+        };
+
+        let mut state = server.event_stream_state.borrow_mut();
+        let state = &mut state.output_profile;
+
+        if state.name == self.niri.active_output_profile {
+            return;
+        }
+
+        let event = Event::OutputProfileActivated {
+            name: self.niri.active_output_profile.clone(),
+        };
+        state.apply(event.clone());
+        server.send_event(event);
+    }
 }
@@ -0,0 +1,112 @@
+use niri_config::{BlockOutFrom, LayerOpenCloseAnimation};
+use smithay::backend::renderer::element::Kind;
+use smithay::backend::renderer::gles::{GlesRenderer, GlesTexture};
+use smithay::utils::{Logical, Point, Scale, Size};
+use smithay::wayland::shell::wlr_layer::{Anchor, Layer};
+
+use super::mapped::LayerSurfaceRenderElement;
+use super::{open_close_transform, MappedLayer};
+use crate::animation::{Animation, Clock};
+use crate::render_helpers::renderer::NiriRenderer;
+use crate::render_helpers::surface::render_snapshot_from_surface_tree;
+use crate::render_helpers::texture::TextureBuffer;
+use crate::render_helpers::{BakedBuffer, RenderTarget, ToRenderElement};
+
+/// A layer surface playing its close animation after it was destroyed.
+#[derive(Debug)]
+pub struct ClosingLayer {
+    /// Contents of the surface at the time it was destroyed.
+    buffers: Vec<BakedBuffer<TextureBuffer<GlesTexture>>>,
+
+    /// Layer the surface was on.
+    layer: Layer,
+
+    /// Whether the surface was placed within the overview backdrop.
+    place_within_backdrop: bool,
+
+    /// Location of the surface on its output.
+    location: Point<f64, Logical>,
+
+    size: Size<f64, Logical>,
+    anchor: Anchor,
+    animation_kind: LayerOpenCloseAnimation,
+    opacity: f32,
+    block_out_from: Option<BlockOutFrom>,
+    scale: f64,
+
+    /// The close animation, going from the open progress down to 0.
+    anim: Animation,
+}
+
+impl ClosingLayer {
+    pub fn new(
+        renderer: &mut GlesRenderer,
+        mapped: &MappedLayer,
+        location: Point<f64, Logical>,
+        scale: f64,
+        clock: Clock,
+        config: niri_config::Animation,
+    ) -> Self {
+        let mut buffers = Vec::new();
+        render_snapshot_from_surface_tree(
+            renderer,
+            mapped.surface().wl_surface(),
+            Point::from((0., 0.)),
+            &mut buffers,
+        );
+
+        let rules = mapped.rules();
+        Self {
+            buffers,
+            layer: mapped.surface().layer(),
+            place_within_backdrop: mapped.place_within_backdrop(),
+            location: location + mapped.bob_offset(),
+            size: mapped.size(),
+            anchor: mapped.surface().cached_state().anchor,
+            animation_kind: rules.open_close_animation,
+            opacity: rules.opacity.unwrap_or(1.).clamp(0., 1.),
+            block_out_from: rules.block_out_from,
+            scale,
+            anim: Animation::new(clock, mapped.open_progress(), 0., 0., config),
+        }
+    }
+
+    pub fn layer(&self) -> Layer {
+        self.layer
+    }
+
+    pub fn place_within_backdrop(&self) -> bool {
+        self.place_within_backdrop
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.anim.is_done()
+    }
+
+    pub fn render<R: NiriRenderer>(
+        &self,
+        target: RenderTarget,
+        push: &mut dyn FnMut(LayerSurfaceRenderElement<R>),
+    ) {
+        if target.should_block_out(self.block_out_from) {
+            return;
+        }
+
+        let (offset, alpha) = open_close_transform(
+            self.animation_kind,
+            self.anchor,
+            self.size,
+            self.anim.value(),
+        );
+        let location = (self.location + offset)
+            .to_physical_precise_round(self.scale)
+            .to_logical(self.scale);
+        let alpha = self.opacity * alpha;
+
+        let scale = Scale::from(self.scale);
+        for buffer in &self.buffers {
+            let elem = buffer.to_render_element(location, scale, alpha, Kind::Unspecified);
+            push(elem.into());
+        }
+    }
+}
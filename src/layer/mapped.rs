@@ -1,5 +1,5 @@
 use niri_config::utils::MergeWith as _;
-use niri_config::{Config, LayerRule};
+use niri_config::{Config, LayerOpenCloseAnimation, LayerRule};
 use smithay::backend::allocator::Fourcc;
 use smithay::backend::renderer::element::surface::WaylandSurfaceRenderElement;
 use smithay::backend::renderer::element::Kind;
@@ -8,25 +8,32 @@ use smithay::desktop::{LayerSurface, PopupManager};
 use smithay::utils::{Logical, Point, Rectangle, Scale, Size, Transform};
 use smithay::wayland::shell::wlr_layer::{ExclusiveZone, Layer};
 
-use super::ResolvedLayerRules;
-use crate::animation::Clock;
+use super::{open_close_transform, ResolvedLayerRules};
+use crate::animation::{Animation, Clock};
 use crate::layout::shadow::Shadow;
 use crate::niri_render_elements;
 use crate::render_helpers::blur::element::{Blur, BlurRenderElement, CommitTracker};
 use crate::render_helpers::blur::{EffectsFramebuffersUserData, OverviewZoom};
 use crate::render_helpers::clipped_surface::ClippedSurfaceRenderElement;
+use crate::render_helpers::primary_gpu_texture::PrimaryGpuTextureRenderElement;
 use crate::render_helpers::renderer::NiriRenderer;
 use crate::render_helpers::shadow::ShadowRenderElement;
 use crate::render_helpers::solid_color::{SolidColorBuffer, SolidColorRenderElement};
 use crate::render_helpers::surface::push_elements_from_surface_tree;
 use crate::render_helpers::{render_to_texture, RenderTarget};
+use crate::utils::id::IdCounter;
 use crate::utils::{baba_is_float_offset, round_logical_in_physical};
 
+static LAYER_ID_COUNTER: IdCounter = IdCounter::new();
+
 #[derive(Debug)]
 pub struct MappedLayer {
     /// The surface itself.
     surface: LayerSurface,
 
+    /// Unique ID of this mapped layer surface, used over IPC.
+    id: u64,
+
     /// Up-to-date rules.
     rules: ResolvedLayerRules,
 
@@ -49,6 +56,9 @@ pub struct MappedLayer {
     /// Scale of the output the layer surface is on (and rounds its sizes to).
     scale: f64,
 
+    /// Ongoing open animation, if any.
+    open_animation: Option<Animation>,
+
     /// Clock for driving animations.
     clock: Clock,
 }
@@ -60,6 +70,7 @@ niri_render_elements! {
         Shadow = ShadowRenderElement,
         Blur = BlurRenderElement,
         ClippedBlur = ClippedSurfaceRenderElement<BlurRenderElement>,
+        Texture = PrimaryGpuTextureRenderElement,
     }
 }
 
@@ -81,13 +92,18 @@ impl MappedLayer {
         blur_config.on = false;
         blur_config.merge_with(&rules.blur);
 
+        let open_animation = (rules.open_close_animation != LayerOpenCloseAnimation::Off)
+            .then(|| Animation::new(clock.clone(), 0., 1., 0., config.animations.layer_open.0));
+
         Self {
             surface,
+            id: LAYER_ID_COUNTER.next(),
             rules,
             block_out_buffer: SolidColorBuffer::new((0., 0.), [0., 0., 0., 1.]),
             view_size,
             scale,
             shadow: Shadow::new(shadow_config),
+            open_animation,
             clock,
             blur: Blur::new(blur_config),
             size: Size::default(),
@@ -111,6 +127,11 @@ impl MappedLayer {
         self.shadow.update_shaders();
     }
 
+    /// Drops the cached true blur texture, returning whether there was one.
+    pub fn release_caches(&self) -> bool {
+        self.blur.release_cache()
+    }
+
     pub fn update_sizes(&mut self, view_size: Size<f64, Logical>, scale: f64) {
         self.view_size = view_size;
         self.scale = scale;
@@ -134,14 +155,55 @@ impl MappedLayer {
         self.blur.update_render_elements(self.rules.blur.on);
     }
 
+    pub fn advance_animations(&mut self) {
+        if self
+            .open_animation
+            .as_ref()
+            .is_some_and(|anim| anim.is_done())
+        {
+            self.open_animation = None;
+        }
+    }
+
     pub fn are_animations_ongoing(&self) -> bool {
-        self.rules.baba_is_float
+        self.rules.baba_is_float || self.open_animation.is_some()
+    }
+
+    /// Returns the progress of the open animation, 1 when the surface is fully open.
+    pub fn open_progress(&self) -> f64 {
+        self.open_animation.as_ref().map_or(1., |anim| anim.value())
+    }
+
+    pub fn size(&self) -> Size<f64, Logical> {
+        self.size
+    }
+
+    fn open_transform(&self) -> (Point<f64, Logical>, f32) {
+        if self.open_animation.is_none() {
+            return (Point::from((0., 0.)), 1.);
+        }
+
+        let anchor = self.surface.cached_state().anchor;
+        let (offset, alpha) = open_close_transform(
+            self.rules.open_close_animation,
+            anchor,
+            self.size,
+            self.open_progress(),
+        );
+        let offset = offset
+            .to_physical_precise_round(self.scale)
+            .to_logical(self.scale);
+        (offset, alpha)
     }
 
     pub fn surface(&self) -> &LayerSurface {
         &self.surface
     }
 
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
     pub fn rules(&self) -> &ResolvedLayerRules {
         &self.rules
     }
@@ -193,8 +255,9 @@ impl MappedLayer {
         fx_buffers: Option<EffectsFramebuffersUserData>,
     ) {
         let scale = Scale::from(self.scale);
-        let alpha = self.rules.opacity.unwrap_or(1.).clamp(0., 1.);
-        let location = location + self.bob_offset();
+        let (anim_offset, anim_alpha) = self.open_transform();
+        let alpha = self.rules.opacity.unwrap_or(1.).clamp(0., 1.) * anim_alpha;
+        let location = location + self.bob_offset() + anim_offset;
 
         let mut elems: Vec<LayerSurfaceRenderElement<R>> = Vec::new();
 
@@ -277,8 +340,7 @@ impl MappedLayer {
                 }
             }
 
-            let blur_sample_area = Rectangle::new(location, self.size).to_i32_round();
-            let geo = Rectangle::new(location, blur_sample_area.size.to_f64());
+            let blur_sample_area = Rectangle::new(location, self.size);
 
             self.blur
                 .render(
@@ -287,10 +349,10 @@ impl MappedLayer {
                     blur_sample_area,
                     self.rules.geometry_corner_radius.unwrap_or_default(),
                     self.scale,
-                    geo,
+                    blur_sample_area,
                     false,
                     !self.rules.blur.x_ray.unwrap_or_default(),
-                    blur_sample_area.loc.to_f64(),
+                    blur_sample_area.loc,
                     OverviewZoom::default(),
                 )
                 .map(Into::into)
@@ -316,8 +378,9 @@ impl MappedLayer {
         push: &mut dyn FnMut(LayerSurfaceRenderElement<R>),
     ) {
         let scale = Scale::from(self.scale);
-        let alpha = self.rules.opacity.unwrap_or(1.).clamp(0., 1.);
-        let location = location + self.bob_offset();
+        let (anim_offset, anim_alpha) = self.open_transform();
+        let alpha = self.rules.opacity.unwrap_or(1.).clamp(0., 1.) * anim_alpha;
+        let location = location + self.bob_offset() + anim_offset;
 
         if target.should_block_out(self.rules.block_out_from) {
             return;
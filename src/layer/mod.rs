@@ -1,7 +1,16 @@
+use std::cell::OnceCell;
+
 use niri_config::layer_rule::{LayerRule, Match};
 use niri_config::utils::MergeWith as _;
-use niri_config::{BlockOutFrom, BlurRule, CornerRadius, ShadowRule};
+use niri_config::{BlockOutFrom, BlurRule, CornerRadius, LayerOpenCloseAnimation, ShadowRule};
 use smithay::desktop::LayerSurface;
+use smithay::utils::{Logical, Point, Size};
+use smithay::wayland::shell::wlr_layer::Anchor;
+
+use crate::utils::local_time_of_day;
+
+pub mod closing;
+pub use closing::ClosingLayer;
 
 pub mod mapped;
 pub use mapped::MappedLayer;
@@ -29,6 +38,12 @@ pub struct ResolvedLayerRules {
 
     /// Whether to bob this window up and down.
     pub baba_is_float: bool,
+
+    /// Animation to play when this layer surface maps and unmaps.
+    pub open_close_animation: LayerOpenCloseAnimation,
+
+    /// Whether this layer surface is an on-screen keyboard.
+    pub on_screen_keyboard: bool,
 }
 
 impl ResolvedLayerRules {
@@ -66,6 +81,8 @@ impl ResolvedLayerRules {
             geometry_corner_radius: None,
             place_within_backdrop: false,
             baba_is_float: false,
+            open_close_animation: LayerOpenCloseAnimation::Off,
+            on_screen_keyboard: false,
         }
     }
 
@@ -74,6 +91,8 @@ impl ResolvedLayerRules {
 
         let mut resolved = ResolvedLayerRules::empty();
 
+        let now = OnceCell::new();
+
         for rule in rules {
             let matches = |m: &Match| {
                 if let Some(at_startup) = m.at_startup {
@@ -82,6 +101,13 @@ impl ResolvedLayerRules {
                     }
                 }
 
+                if let Some(at) = m.at {
+                    let now = now.get_or_init(|| local_time_of_day().map(|(minute, _)| minute));
+                    if !now.is_some_and(|minute| at.contains(minute)) {
+                        return false;
+                    }
+                }
+
                 surface_matches(surface, m)
             };
 
@@ -108,6 +134,12 @@ impl ResolvedLayerRules {
             if let Some(x) = rule.baba_is_float {
                 resolved.baba_is_float = x;
             }
+            if let Some(x) = rule.open_close_animation {
+                resolved.open_close_animation = x;
+            }
+            if let Some(x) = rule.on_screen_keyboard {
+                resolved.on_screen_keyboard = x;
+            }
 
             resolved.shadow.merge_with(&rule.shadow);
             resolved.blur.merge_with(&rule.blur);
@@ -126,3 +158,110 @@ fn surface_matches(surface: &LayerSurface, m: &Match) -> bool {
 
     true
 }
+
+/// Computes the offset and alpha of a layer surface at this open/close animation progress.
+///
+/// At `progress` 0 the surface is fully hidden, and at 1 it is in its normal place.
+pub fn open_close_transform(
+    animation: LayerOpenCloseAnimation,
+    anchor: Anchor,
+    size: Size<f64, Logical>,
+    progress: f64,
+) -> (Point<f64, Logical>, f32) {
+    match animation {
+        LayerOpenCloseAnimation::Off => (Point::from((0., 0.)), 1.),
+        LayerOpenCloseAnimation::Fade => (Point::from((0., 0.)), progress.clamp(0., 1.) as f32),
+        LayerOpenCloseAnimation::Slide => {
+            let hidden = 1. - progress;
+
+            let top = anchor.contains(Anchor::TOP);
+            let bottom = anchor.contains(Anchor::BOTTOM);
+            let left = anchor.contains(Anchor::LEFT);
+            let right = anchor.contains(Anchor::RIGHT);
+
+            // Slide from the edge that the surface is attached to: a bar anchored to the top and
+            // both sides slides from the top, while a notification in the top-right corner slides
+            // from the right. Surfaces without a single such edge fade instead.
+            let offset = if top != bottom && left == right {
+                if top {
+                    (0., -size.h * hidden)
+                } else {
+                    (0., size.h * hidden)
+                }
+            } else if left != right {
+                if left {
+                    (-size.w * hidden, 0.)
+                } else {
+                    (size.w * hidden, 0.)
+                }
+            } else {
+                return (Point::from((0., 0.)), progress.clamp(0., 1.) as f32);
+            };
+
+            (Point::from(offset), 1.)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transform(
+        animation: LayerOpenCloseAnimation,
+        anchor: Anchor,
+        progress: f64,
+    ) -> ((f64, f64), f32) {
+        let size = Size::from((200., 50.));
+        let (offset, alpha) = open_close_transform(animation, anchor, size, progress);
+        ((offset.x, offset.y), alpha)
+    }
+
+    #[test]
+    fn open_close_transform_off_and_fade() {
+        let anchor = Anchor::TOP;
+        let off = LayerOpenCloseAnimation::Off;
+        assert_eq!(transform(off, anchor, 0.), ((0., 0.), 1.));
+        assert_eq!(transform(off, anchor, 0.5), ((0., 0.), 1.));
+
+        let fade = LayerOpenCloseAnimation::Fade;
+        assert_eq!(transform(fade, anchor, 0.), ((0., 0.), 0.));
+        assert_eq!(transform(fade, anchor, 0.5), ((0., 0.), 0.5));
+        assert_eq!(transform(fade, anchor, 1.), ((0., 0.), 1.));
+
+        // Spring animations overshoot.
+        assert_eq!(transform(fade, anchor, -0.1), ((0., 0.), 0.));
+        assert_eq!(transform(fade, anchor, 1.1), ((0., 0.), 1.));
+    }
+
+    #[test]
+    fn open_close_transform_slide_from_edge() {
+        let slide = LayerOpenCloseAnimation::Slide;
+
+        // A top bar slides down from the top.
+        let bar = Anchor::TOP | Anchor::LEFT | Anchor::RIGHT;
+        assert_eq!(transform(slide, bar, 0.), ((0., -50.), 1.));
+        assert_eq!(transform(slide, bar, 0.5), ((0., -25.), 1.));
+        assert_eq!(transform(slide, bar, 1.), ((0., 0.), 1.));
+
+        let dock = Anchor::BOTTOM | Anchor::LEFT | Anchor::RIGHT;
+        assert_eq!(transform(slide, dock, 0.), ((0., 50.), 1.));
+
+        // A notification in the top-right corner slides from the right.
+        let notification = Anchor::TOP | Anchor::RIGHT;
+        assert_eq!(transform(slide, notification, 0.), ((200., 0.), 1.));
+
+        let sidebar = Anchor::LEFT | Anchor::TOP | Anchor::BOTTOM;
+        assert_eq!(transform(slide, sidebar, 0.), ((-200., 0.), 1.));
+    }
+
+    #[test]
+    fn open_close_transform_slide_without_edge_fades() {
+        let slide = LayerOpenCloseAnimation::Slide;
+
+        for anchor in [Anchor::empty(), Anchor::all(), Anchor::TOP | Anchor::BOTTOM] {
+            assert_eq!(transform(slide, anchor, 0.), ((0., 0.), 0.));
+            assert_eq!(transform(slide, anchor, 0.5), ((0., 0.), 0.5));
+        }
+    }
+}
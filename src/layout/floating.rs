@@ -1087,6 +1087,16 @@ impl<W: LayoutElement> FloatingSpace<W> {
         self.move_to(idx, new_pos, true);
     }
 
+    /// Centers the window on the area, for example a dialog on its parent window.
+    pub fn center_window_on(&mut self, id: &W::Id, area: Rectangle<f64, Logical>) {
+        let idx = self.idx_of(id).unwrap();
+
+        let size = self.data[idx].size;
+        let pos = area.loc + (area.size.to_point() - size.to_point()).downscale(2.);
+        let pos = self.clamp_within_working_area(pos, size);
+        self.move_to(idx, pos, true);
+    }
+
     pub fn descendants_added(&mut self, id: &W::Id) -> bool {
         let Some(idx) = self.idx_of(id) else {
             return false;
@@ -39,7 +39,7 @@ use std::time::Duration;
 use monitor::{InsertHint, InsertPosition, InsertWorkspace, MonitorAddWindowTarget};
 use niri_config::utils::MergeWith as _;
 use niri_config::{
-    Config, CornerRadius, LayoutPart, PresetSize, WindowMoveDirection,
+    Config, CornerRadius, LayoutPart, OutputName, PresetSize, TitleFallback, WindowMoveDirection,
     Workspace as WorkspaceConfig, WorkspaceReference,
 };
 use niri_ipc::{PositionChange, SizeChange, WindowLayout};
@@ -99,6 +99,12 @@ const INTERACTIVE_MOVE_START_THRESHOLD: f64 = 256. * 256.;
 /// Opacity of interactively moved tiles targeting the scrolling layout.
 const INTERACTIVE_MOVE_ALPHA: f64 = 0.75;
 
+/// How long a window must stay over a new output before it enters it.
+///
+/// Avoids making clients rescale back and forth as the window is dragged along an output edge, or
+/// as its workspace moves across several outputs in a row.
+const OUTPUT_SETTLE_DELAY: Duration = Duration::from_millis(200);
+
 /// Amount of touchpad movement to toggle the overview.
 const OVERVIEW_GESTURE_MOVEMENT: f64 = 300.;
 
@@ -298,6 +304,11 @@ pub trait LayoutElement {
         None
     }
 
+    /// The title to show for this layout element, with a fallback if it has no title
+    fn display_title(&self, fallback: TitleFallback) -> String {
+        fallback.apply(self.title().as_deref(), None, 1)
+    }
+
     fn set_proto_wants_blur(&mut self, _new_blurred: bool) {}
 
     fn wants_blur(&self) -> bool {
@@ -314,7 +325,11 @@ pub struct Layout<W: LayoutElement> {
     /// This normally indicates that the layout has keyboard focus, but not always. E.g. when the
     /// screenshot UI is open, it keeps the layout drawing as active.
     is_active: bool,
-    /// Map from monitor name to id of its last active workspace.
+    /// Map from monitor identity to id of its last active workspace.
+    ///
+    /// The key is the monitor's make/model/serial (or connector name if those are unknown), so
+    /// that the active workspace is restored even if the monitor reconnects to a different
+    /// connector.
     ///
     /// This data is stored upon monitor removal and is used to restore the active workspace when
     /// the monitor is reconnected.
@@ -337,6 +352,8 @@ pub struct Layout<W: LayoutElement> {
     overview_open: bool,
     /// The overview zoom progress.
     overview_progress: Option<OverviewProgress>,
+    /// Zoom that the overview was opened with, overriding the configured zoom.
+    overview_zoom: Option<f64>,
     /// Configurable properties of the layout.
     options: Rc<Options>,
 }
@@ -384,6 +401,8 @@ enum InteractiveMoveState<W: LayoutElement> {
         ///
         /// This helps the pointer remain inside the window as it resizes.
         pointer_ratio_within_window: (f64, f64),
+        /// Whether to move the whole column of the window.
+        move_whole_column: bool,
     },
     /// Moving; the window is no longer in the layout.
     Moving(InteractiveMoveData<W>),
@@ -395,6 +414,12 @@ struct InteractiveMoveData<W: LayoutElement> {
     pub(self) tile: Tile<W>,
     /// Output where the window is currently located/rendered.
     pub(self) output: Output,
+    /// Output that the window has entered.
+    ///
+    /// Lags behind `output` until the window settles over the new output.
+    pub(self) entered_output: Output,
+    /// Time when `output` last changed.
+    pub(self) output_changed_at: Duration,
     /// Current pointer position within output.
     pub(self) pointer_pos_within_output: Point<f64, Logical>,
     /// Window column width.
@@ -417,6 +442,13 @@ struct InteractiveMoveData<W: LayoutElement> {
     /// config overrides for the workspace where the move originated from. As soon as the window
     /// moves over some different workspace though, this override will reset.
     pub(self) workspace_config: Option<(WorkspaceId, niri_config::LayoutPart)>,
+    /// Windows of the column moved together with this window, top to bottom.
+    ///
+    /// Empty when moving a single window. The other windows of the column remain in the layout
+    /// during the move, and join the moved window in its new column when it is dropped.
+    pub(self) column: Vec<W::Id>,
+    /// Ongoing hold over a workspace in the overview to activate it.
+    pub(self) hold: Option<DndHold<W>>,
 }
 
 #[derive(Debug)]
@@ -493,6 +525,12 @@ pub enum AddWindowTarget<'a, W: LayoutElement> {
     Output(&'a Output),
     /// On this workspace.
     Workspace(WorkspaceId),
+    /// On this workspace, at this column and tile index in the scrolling layout.
+    WorkspacePosition {
+        id: WorkspaceId,
+        column_idx: usize,
+        tile_idx: usize,
+    },
     /// Next to this existing window.
     NextTo(&'a W::Id),
 }
@@ -577,6 +615,32 @@ impl<W: LayoutElement> InteractiveMoveData<W> {
         // Round to physical pixels.
         pos.to_physical_precise_round(scale).to_logical(scale)
     }
+
+    /// Makes the window enter the output it's currently over, if it hasn't already.
+    fn settle_output(&mut self) {
+        if self.entered_output == self.output {
+            return;
+        }
+
+        let win = self.tile.focused_window();
+        win.output_leave(&self.entered_output);
+        win.output_enter(&self.output);
+        win.set_preferred_scale_transform(
+            self.output.current_scale(),
+            self.output.current_transform(),
+        );
+        self.entered_output = self.output.clone();
+    }
+
+    fn adjust_insert_position(&self, position: InsertPosition) -> InsertPosition {
+        match position {
+            // A whole column can only be dropped as a new column.
+            InsertPosition::InColumn(column_idx, _) if !self.column.is_empty() => {
+                InsertPosition::NewColumn(column_idx)
+            }
+            position => position,
+        }
+    }
 }
 
 impl ActivateWindow {
@@ -627,7 +691,7 @@ impl Options {
         Self {
             layout: config.layout.clone(),
             animations: config.animations.clone(),
-            gestures: config.gestures,
+            gestures: config.gestures.clone(),
             overview: config.overview,
             disable_resize_throttling: config.debug.disable_resize_throttling,
             disable_transactions: config.debug.disable_transactions,
@@ -678,6 +742,7 @@ impl<W: LayoutElement> Layout<W> {
             update_render_elements_time: Duration::ZERO,
             overview_open: false,
             overview_progress: None,
+            overview_zoom: None,
             options: Rc::new(options),
         }
     }
@@ -703,6 +768,7 @@ impl<W: LayoutElement> Layout<W> {
             update_render_elements_time: Duration::ZERO,
             overview_open: false,
             overview_progress: None,
+            overview_zoom: None,
             options: opts,
         }
     }
@@ -772,7 +838,7 @@ impl<W: LayoutElement> Layout<W> {
 
                 workspaces.reverse();
 
-                let ws_id_to_activate = self.last_active_workspace_id.remove(&output.name());
+                let ws_id_to_activate = self.take_last_active_workspace_id(&output);
 
                 let mut monitor = Monitor::new(
                     output,
@@ -783,6 +849,7 @@ impl<W: LayoutElement> Layout<W> {
                     layout_config,
                 );
                 monitor.overview_open = self.overview_open;
+                monitor.overview_zoom = self.overview_zoom;
                 monitor.set_overview_progress(self.overview_progress.as_ref());
                 monitors.push(monitor);
 
@@ -793,7 +860,7 @@ impl<W: LayoutElement> Layout<W> {
                 }
             }
             MonitorSet::NoOutputs { workspaces } => {
-                let ws_id_to_activate = self.last_active_workspace_id.remove(&output.name());
+                let ws_id_to_activate = self.take_last_active_workspace_id(&output);
 
                 let mut monitor = Monitor::new(
                     output,
@@ -804,6 +871,7 @@ impl<W: LayoutElement> Layout<W> {
                     layout_config,
                 );
                 monitor.overview_open = self.overview_open;
+                monitor.overview_zoom = self.overview_zoom;
                 monitor.set_overview_progress(self.overview_progress.as_ref());
 
                 MonitorSet::Normal {
@@ -828,8 +896,9 @@ impl<W: LayoutElement> Layout<W> {
                     .expect("trying to remove non-existing output");
                 let monitor = monitors.remove(idx);
 
+                let output_name = monitor.output.user_data().get::<OutputName>().unwrap();
                 self.last_active_workspace_id.insert(
-                    monitor.output_name().clone(),
+                    output_name.format_make_model_serial_or_connector(),
                     monitor.workspaces[monitor.active_workspace_idx].id(),
                 );
 
@@ -873,6 +942,16 @@ impl<W: LayoutElement> Layout<W> {
         }
     }
 
+    fn take_last_active_workspace_id(&mut self, output: &Output) -> Option<WorkspaceId> {
+        let output_name = output.user_data().get::<OutputName>().unwrap();
+        let key = self
+            .last_active_workspace_id
+            .keys()
+            .find(|key| output_name.matches(key))?
+            .clone();
+        self.last_active_workspace_id.remove(&key)
+    }
+
     pub fn add_column_by_idx(
         &mut self,
         monitor_idx: usize,
@@ -944,6 +1023,25 @@ impl<W: LayoutElement> Layout<W> {
                             },
                         )
                     }
+                    AddWindowTarget::WorkspacePosition {
+                        id,
+                        column_idx,
+                        tile_idx,
+                    } => {
+                        let mon_idx = monitors
+                            .iter()
+                            .position(|mon| mon.workspaces.iter().any(|ws| ws.id() == id))
+                            .unwrap();
+
+                        (
+                            mon_idx,
+                            MonitorAddWindowTarget::WorkspacePosition {
+                                id,
+                                column_idx,
+                                tile_idx,
+                            },
+                        )
+                    }
                     AddWindowTarget::NextTo(next_to) => {
                         if let Some(output) = self
                             .interactive_move
@@ -1027,6 +1125,20 @@ impl<W: LayoutElement> Layout<W> {
                         let ws_idx = workspaces.iter().position(|ws| ws.id() == ws_id).unwrap();
                         (ws_idx, WorkspaceAddWindowTarget::Auto)
                     }
+                    AddWindowTarget::WorkspacePosition {
+                        id,
+                        column_idx,
+                        tile_idx,
+                    } => {
+                        let ws_idx = workspaces.iter().position(|ws| ws.id() == id).unwrap();
+                        (
+                            ws_idx,
+                            WorkspaceAddWindowTarget::Position {
+                                column_idx,
+                                tile_idx,
+                            },
+                        )
+                    }
                     AddWindowTarget::NextTo(next_to) => {
                         if self
                             .interactive_move
@@ -1108,6 +1220,8 @@ impl<W: LayoutElement> Layout<W> {
                             unreachable!()
                         };
 
+                        move_.settle_output();
+
                         for mon in self.monitors_mut() {
                             mon.dnd_scroll_gesture_end();
                         }
@@ -1222,6 +1336,16 @@ impl<W: LayoutElement> Layout<W> {
         false
     }
 
+    pub fn center_window_on_parent(&mut self, id: &W::Id, parent: &W::Id) -> bool {
+        for ws in self.workspaces_mut() {
+            if ws.center_window_on_parent(id, parent) {
+                return true;
+            }
+        }
+
+        false
+    }
+
     pub fn update_window(&mut self, window: &W::Id, serial: Option<Serial>) {
         if let Some(InteractiveMoveState::Moving(move_)) = &mut self.interactive_move {
             if move_.tile.focused_window().id() == window {
@@ -1370,6 +1494,35 @@ impl<W: LayoutElement> Layout<W> {
         }
     }
 
+    /// Sets whether to keep the workspace around when it's empty, like a named workspace.
+    pub fn set_keep_workspace_when_empty(&mut self, id: WorkspaceId, keep: bool) {
+        match &mut self.monitor_set {
+            MonitorSet::Normal { monitors, .. } => {
+                for mon in monitors {
+                    if let Some(ws) = mon.workspaces.iter_mut().find(|ws| ws.id() == id) {
+                        ws.keep_when_empty = keep;
+
+                        if !keep && mon.workspace_switch.is_none() {
+                            mon.clean_up_workspaces();
+                        }
+
+                        return;
+                    }
+                }
+            }
+            MonitorSet::NoOutputs { workspaces } => {
+                if let Some(idx) = workspaces.iter().position(|ws| ws.id() == id) {
+                    workspaces[idx].keep_when_empty = keep;
+
+                    // Clean up empty workspaces.
+                    if !workspaces[idx].has_windows_or_name() {
+                        workspaces.remove(idx);
+                    }
+                }
+            }
+        }
+    }
+
     pub fn find_window_and_output(&self, wl_surface: &WlSurface) -> Option<(&W, Option<&Output>)> {
         if let Some(InteractiveMoveState::Moving(move_)) = &self.interactive_move {
             if let Some(window) = move_.tile.windows().find(|w| w.is_wl_surface(wl_surface)) {
@@ -1742,6 +1895,24 @@ impl<W: LayoutElement> Layout<W> {
         }
     }
 
+    /// Releases cached textures of all tiles and the workspace snapshots of all monitors,
+    /// returning the number of textures released.
+    pub fn release_caches(&mut self) -> usize {
+        let mut released: usize = self.monitors().map(Monitor::release_caches).sum();
+
+        if let Some(InteractiveMoveState::Moving(move_)) = &mut self.interactive_move {
+            released += move_.tile.release_caches();
+        }
+
+        for ws in self.workspaces_mut() {
+            for tile in ws.tiles_mut() {
+                released += tile.release_caches();
+            }
+        }
+
+        released
+    }
+
     fn active_monitor(&mut self) -> Option<&mut Monitor<W>> {
         let MonitorSet::Normal {
             monitors,
@@ -2313,6 +2484,16 @@ impl<W: LayoutElement> Layout<W> {
         workspace.swap_window_in_direction(direction);
     }
 
+    /// Swaps the positions of two tiled windows on the same workspace.
+    ///
+    /// Returns whether the windows were swapped.
+    pub fn swap_windows(&mut self, window: &W::Id, other: &W::Id) -> bool {
+        let Some(workspace) = self.workspaces_mut().find(|ws| ws.has_window(window)) else {
+            return false;
+        };
+        workspace.swap_windows(window, other)
+    }
+
     pub fn center_column(&mut self) {
         let Some(workspace) = self.active_workspace_mut() else {
             return;
@@ -2433,6 +2614,23 @@ impl<W: LayoutElement> Layout<W> {
         mon.resize_edges_under(pos_within_output)
     }
 
+    pub fn border_resize_edges_under(
+        &self,
+        output: &Output,
+        pos_within_output: Point<f64, Logical>,
+        width: f64,
+    ) -> Option<(&W, ResizeEdge)> {
+        if self
+            .interactive_moved_window_under(output, pos_within_output)
+            .is_some()
+        {
+            return None;
+        }
+
+        let mon = self.monitor_for_output(output)?;
+        mon.border_resize_edges_under(pos_within_output, width)
+    }
+
     pub fn workspace_under(
         &self,
         extended_bounds: bool,
@@ -2456,7 +2654,7 @@ impl<W: LayoutElement> Layout<W> {
 
     pub fn overview_zoom(&self) -> f64 {
         let progress = self.overview_progress.as_ref().map(|p| p.value());
-        compute_overview_zoom(&self.options, progress)
+        compute_overview_zoom(&self.options, self.overview_zoom, progress)
     }
 
     #[cfg(test)]
@@ -2474,6 +2672,7 @@ impl<W: LayoutElement> Layout<W> {
                     window_id,
                     pointer_delta: _,
                     pointer_ratio_within_window: _,
+                    move_whole_column: _,
                 } => {
                     assert!(
                         self.has_window(window_id),
@@ -2681,6 +2880,7 @@ impl<W: LayoutElement> Layout<W> {
 
         let mut dnd_scroll = None;
         let mut is_dnd = false;
+        let mut is_move = false;
         if let Some(dnd) = &self.dnd {
             dnd_scroll = Some((dnd.output.clone(), dnd.pointer_pos_within_output, true));
             is_dnd = true;
@@ -2695,6 +2895,7 @@ impl<W: LayoutElement> Layout<W> {
                     move_.pointer_pos_within_output,
                     !move_.is_floating,
                 ));
+                is_move = true;
             }
         }
 
@@ -2724,73 +2925,88 @@ impl<W: LayoutElement> Layout<W> {
                     }
                 }
 
-                if scrolled {
-                    // Don't trigger DnD hold while scrolling.
-                    if let Some(dnd) = &mut self.dnd {
-                        dnd.hold = None;
-                    }
-                } else if is_dnd {
-                    let target = mon
-                        .window_under(pos_within_output)
+                // A moved window can only be held over other workspaces in the overview, since it
+                // would be held over its own drop target otherwise.
+                let hold_target = if is_dnd {
+                    mon.window_under(pos_within_output)
                         .map(|(win, _)| DndHoldTarget::Window(win.id().clone()))
                         .or_else(|| {
                             mon.workspace_under_narrow(pos_within_output)
                                 .map(|ws| DndHoldTarget::Workspace(ws.id()))
-                        });
+                        })
+                } else if is_move && is_overview_open {
+                    let active_ws_id = mon.active_workspace_ref().id();
+                    mon.workspace_under_narrow(pos_within_output)
+                        .map(|ws| ws.id())
+                        .filter(|id| *id != active_ws_id)
+                        .map(DndHoldTarget::Workspace)
+                } else {
+                    None
+                };
 
-                    let dnd = self.dnd.as_mut().unwrap();
-                    if let Some(target) = target {
-                        let now = self.clock.now_unadjusted();
-                        let start_time = if let Some(hold) = &mut dnd.hold {
-                            if hold.target != target {
-                                hold.start_time = now;
-                            }
-                            hold.target = target;
-                            hold.start_time
-                        } else {
-                            let hold = dnd.hold.insert(DndHold {
-                                start_time: now,
-                                target,
-                            });
-                            hold.start_time
-                        };
+                let hold_state = if let Some(dnd) = &mut self.dnd {
+                    &mut dnd.hold
+                } else if let Some(InteractiveMoveState::Moving(move_)) = &mut self.interactive_move
+                {
+                    &mut move_.hold
+                } else {
+                    unreachable!()
+                };
 
-                        // Delay copied from gnome-shell.
-                        let delay = Duration::from_millis(750);
-                        if delay <= now.saturating_sub(start_time) {
-                            let hold = dnd.hold.take().unwrap();
+                if scrolled {
+                    // Don't trigger DnD hold while scrolling.
+                    *hold_state = None;
+                } else if let Some(target) = hold_target {
+                    let now = self.clock.now_unadjusted();
+                    let start_time = if let Some(hold) = hold_state {
+                        if hold.target != target {
+                            hold.start_time = now;
+                        }
+                        hold.target = target;
+                        hold.start_time
+                    } else {
+                        let hold = hold_state.insert(DndHold {
+                            start_time: now,
+                            target,
+                        });
+                        hold.start_time
+                    };
 
-                            // Synchronize workspace switch to overview close to get a monotonic
-                            // animation.
-                            let config = is_overview_open
-                                .then_some(self.options.animations.overview_open_close.0);
+                    // Delay copied from gnome-shell.
+                    let delay = Duration::from_millis(750);
+                    if delay <= now.saturating_sub(start_time) {
+                        let hold = hold_state.take().unwrap();
 
-                            let mon = self.monitor_for_output_mut(&output).unwrap();
+                        // Synchronize workspace switch to overview close to get a monotonic
+                        // animation.
+                        let config = is_overview_open
+                            .then_some(self.options.animations.overview_open_close.0);
 
-                            let ws_idx = match hold.target {
-                                DndHoldTarget::Window(id) => mon
-                                    .workspaces
-                                    .iter_mut()
-                                    .position(|ws| ws.activate_window(&id))
-                                    .unwrap(),
-                                DndHoldTarget::Workspace(id) => {
-                                    mon.workspaces.iter().position(|ws| ws.id() == id).unwrap()
-                                }
-                            };
+                        let mon = self.monitor_for_output_mut(&output).unwrap();
 
-                            mon.dnd_scroll_gesture_end();
-                            mon.activate_workspace_with_anim_config(ws_idx, config);
+                        let ws_idx = match hold.target {
+                            DndHoldTarget::Window(id) => mon
+                                .workspaces
+                                .iter_mut()
+                                .position(|ws| ws.activate_window(&id))
+                                .unwrap(),
+                            DndHoldTarget::Workspace(id) => {
+                                mon.workspaces.iter().position(|ws| ws.id() == id).unwrap()
+                            }
+                        };
+
+                        mon.dnd_scroll_gesture_end();
+                        mon.activate_workspace_with_anim_config(ws_idx, config);
 
-                            self.focus_output(&output);
+                        self.focus_output(&output);
 
-                            if is_overview_open {
-                                self.close_overview();
-                            }
+                        if is_overview_open {
+                            self.close_overview();
                         }
-                    } else {
-                        // No target, reset the hold timer.
-                        dnd.hold = None;
                     }
+                } else {
+                    // No target, reset the hold timer.
+                    *hold_state = None;
                 }
             }
         }
@@ -2834,6 +3050,11 @@ impl<W: LayoutElement> Layout<W> {
                     return true;
                 }
 
+                // Keep refreshing until the window enters the new output.
+                if move_.entered_output != move_.output {
+                    return true;
+                }
+
                 // Keep advancing animations if we might need to scroll the view.
                 if !move_.is_floating || self.overview_open {
                     return true;
@@ -2956,6 +3177,7 @@ impl<W: LayoutElement> Layout<W> {
                     } else {
                         ws.scrolling_insert_position(pos_within_workspace)
                     };
+                    let position = move_.adjust_insert_position(position);
 
                     let rules = move_.tile.focused_window().rules();
                     let border_width = move_.tile.effective_border_width().unwrap_or(0.);
@@ -3036,10 +3258,15 @@ impl<W: LayoutElement> Layout<W> {
             let Some(name) = ws.name() else { continue };
             if let Some(config) = config.workspaces.iter().find(|w| &w.name.0 == name) {
                 ws.update_layout_config(config.layout.clone().map(|x| x.0));
+                ws.update_metadata(config.icon.clone(), config.color);
             }
         }
 
-        self.update_options(Options::from_config(config));
+        // Avoid going through every tile when a config reload didn't touch the layout.
+        let options = Options::from_config(config);
+        if *self.options != options {
+            self.update_options(options);
+        }
     }
 
     fn update_options(&mut self, options: Options) {
@@ -3214,6 +3441,13 @@ impl<W: LayoutElement> Layout<W> {
         workspace.expand_column_to_available_width();
     }
 
+    pub fn fit_column_width_to_content(&mut self) {
+        let Some(workspace) = self.active_workspace_mut() else {
+            return;
+        };
+        workspace.fit_column_width_to_content();
+    }
+
     pub fn toggle_window_floating(&mut self, window: Option<&W::Id>) {
         if let Some(InteractiveMoveState::Moving(move_)) = &mut self.interactive_move {
             if window.is_none_or(|window| window == move_.tile.focused_window().id()) {
@@ -3808,6 +4042,9 @@ impl<W: LayoutElement> Layout<W> {
     }
 
     pub fn overview_gesture_begin(&mut self) {
+        if !self.overview_open {
+            self.overview_zoom = None;
+        }
         self.overview_open = true;
 
         let value = self.overview_progress.take().map_or(0., |p| p.value());
@@ -3883,6 +4120,28 @@ impl<W: LayoutElement> Layout<W> {
         window_id: W::Id,
         output: &Output,
         start_pos_within_output: Point<f64, Logical>,
+    ) -> bool {
+        self.interactive_move_begin_inner(window_id, output, start_pos_within_output, false)
+    }
+
+    /// Begins an interactive move of the whole column containing the window.
+    ///
+    /// Floating windows are moved on their own.
+    pub fn interactive_move_column_begin(
+        &mut self,
+        window_id: W::Id,
+        output: &Output,
+        start_pos_within_output: Point<f64, Logical>,
+    ) -> bool {
+        self.interactive_move_begin_inner(window_id, output, start_pos_within_output, true)
+    }
+
+    fn interactive_move_begin_inner(
+        &mut self,
+        window_id: W::Id,
+        output: &Output,
+        start_pos_within_output: Point<f64, Logical>,
+        move_whole_column: bool,
     ) -> bool {
         if self.interactive_move.is_some() {
             return false;
@@ -3932,6 +4191,7 @@ impl<W: LayoutElement> Layout<W> {
             window_id,
             pointer_delta: Point::from((0., 0.)),
             pointer_ratio_within_window,
+            move_whole_column,
         });
 
         for mon in self.monitors_mut() {
@@ -3964,12 +4224,14 @@ impl<W: LayoutElement> Layout<W> {
                 window_id,
                 mut pointer_delta,
                 pointer_ratio_within_window,
+                move_whole_column,
             } => {
                 if window_id != *window {
                     self.interactive_move = Some(InteractiveMoveState::Starting {
                         window_id,
                         pointer_delta,
                         pointer_ratio_within_window,
+                        move_whole_column,
                     });
                     return false;
                 }
@@ -4009,6 +4271,7 @@ impl<W: LayoutElement> Layout<W> {
                     window_id: window_id.clone(),
                     pointer_delta,
                     pointer_ratio_within_window,
+                    move_whole_column,
                 });
 
                 if !is_floating && sq_dist < INTERACTIVE_MOVE_START_THRESHOLD {
@@ -4057,6 +4320,15 @@ impl<W: LayoutElement> Layout<W> {
                 ws.set_fullscreen(window, false);
                 ws.set_maximized(window, false);
 
+                // Remember the rest of the column to move it together with the window.
+                let column = if move_whole_column && !ws.is_floating(window) {
+                    ws.column_window_ids(window)
+                        .filter(|ids| ids.len() > 1)
+                        .unwrap_or_default()
+                } else {
+                    Vec::new()
+                };
+
                 let RemovedTile {
                     mut tile,
                     width,
@@ -4098,6 +4370,8 @@ impl<W: LayoutElement> Layout<W> {
 
                 let mut data = InteractiveMoveData {
                     tile,
+                    entered_output: output.clone(),
+                    output_changed_at: self.clock.now_unadjusted(),
                     output,
                     pointer_pos_within_output,
                     width,
@@ -4106,6 +4380,8 @@ impl<W: LayoutElement> Layout<W> {
                     pointer_ratio_within_window,
                     output_config,
                     workspace_config,
+                    column,
+                    hold: None,
                 };
 
                 if let Some((tile_pos, zoom)) = tile_pos {
@@ -4140,13 +4416,9 @@ impl<W: LayoutElement> Layout<W> {
                 }
 
                 if output != move_.output {
-                    move_.tile.focused_window().output_leave(&move_.output);
-                    move_.tile.focused_window().output_enter(&output);
-                    move_.tile.focused_window().set_preferred_scale_transform(
-                        output.current_scale(),
-                        output.current_transform(),
-                    );
+                    // The window enters the new output in refresh() once it settles there.
                     move_.output = output.clone();
+                    move_.output_changed_at = self.clock.now_unadjusted();
                     self.focus_output(&output);
 
                     move_.output_config = self
@@ -4231,6 +4503,8 @@ impl<W: LayoutElement> Layout<W> {
             unreachable!()
         };
 
+        move_.settle_output();
+
         for mon in self.monitors_mut() {
             mon.dnd_scroll_gesture_end();
         }
@@ -4252,6 +4526,8 @@ impl<W: LayoutElement> Layout<W> {
         // Dragging in the overview shouldn't switch the workspace and so on.
         let allow_to_activate_workspace = !self.overview_open;
 
+        let win_id = move_.tile.focused_window().id().clone();
+
         match &mut self.monitor_set {
             MonitorSet::Normal {
                 monitors,
@@ -4311,7 +4587,8 @@ impl<W: LayoutElement> Layout<W> {
                         (mon, insert_ws, position, Some(ws_geo.loc), zoom)
                     };
 
-                let win_id = move_.tile.focused_window().id().clone();
+                let position = move_.adjust_insert_position(position);
+
                 let tile_render_loc = move_.tile_render_location(zoom);
 
                 let ws_idx = match insert_ws {
@@ -4444,6 +4721,73 @@ impl<W: LayoutElement> Layout<W> {
                 );
             }
         }
+
+        if !move_.column.is_empty() {
+            self.interactive_move_gather_column(&win_id, move_.column);
+        }
+    }
+
+    /// Moves the rest of an interactively moved column next to the moved window, and puts the
+    /// window back into it.
+    ///
+    /// The column itself moves, so it keeps its width and the heights of its windows.
+    fn interactive_move_gather_column(&mut self, window: &W::Id, column: Vec<W::Id>) {
+        let Some(moved_idx) = column.iter().position(|id| id == window) else {
+            return;
+        };
+
+        // The window must have been dropped as a new column of its own.
+        let is_alone_in_column = self.workspaces().any(|(_, _, ws)| {
+            ws.column_window_ids(window)
+                .is_some_and(|ids| ids.len() == 1)
+        });
+        if !is_alone_in_column {
+            return;
+        }
+
+        // The rest of the column could have been closed or moved elsewhere during the move.
+        let Some(rest) = column.iter().find(|id| {
+            *id != window
+                && self
+                    .workspaces()
+                    .any(|(_, _, ws)| ws.column_window_ids(id).is_some())
+        }) else {
+            return;
+        };
+
+        let removed = match &mut self.monitor_set {
+            MonitorSet::Normal { monitors, .. } => monitors.iter_mut().find_map(|mon| {
+                let ws = mon.workspaces.iter_mut().find(|ws| ws.has_window(rest))?;
+                let removed = ws.remove_column_of(rest);
+
+                if mon.workspace_switch.is_none() {
+                    mon.clean_up_workspaces();
+                }
+
+                removed
+            }),
+            MonitorSet::NoOutputs { workspaces } => {
+                let idx = workspaces.iter().position(|ws| ws.has_window(rest));
+                idx.and_then(|idx| {
+                    let removed = workspaces[idx].remove_column_of(rest);
+
+                    if !workspaces[idx].has_windows_or_name() {
+                        workspaces.remove(idx);
+                    }
+
+                    removed
+                })
+            }
+        };
+        let Some(rest_column) = removed else {
+            return;
+        };
+
+        let ws = self
+            .workspaces_mut()
+            .find(|ws| ws.has_window(window))
+            .unwrap();
+        ws.replace_column_of(window, rest_column, moved_idx);
     }
 
     pub fn interactive_move_is_moving_above_output(&self, output: &Output) -> bool {
@@ -4546,6 +4890,20 @@ impl<W: LayoutElement> Layout<W> {
         false
     }
 
+    /// Updates an interactive resize of a tiled window from a pinch gesture.
+    pub fn interactive_resize_scale_width(
+        &mut self,
+        window: &W::Id,
+        scale: f64,
+        snap_distance: f64,
+    ) -> bool {
+        let Some(ws) = self.workspaces_mut().find(|ws| ws.has_window(window)) else {
+            return false;
+        };
+
+        ws.interactive_resize_scale_width(window, scale, snap_distance)
+    }
+
     pub fn interactive_resize_end(&mut self, window: &W::Id) {
         if let Some(InteractiveMoveState::Moving(move_)) = &self.interactive_move {
             if move_.tile.focused_window().id() == window {
@@ -4691,13 +5049,24 @@ impl<W: LayoutElement> Layout<W> {
 
         for mon in monitors {
             mon.overview_open = self.overview_open;
+            mon.overview_zoom = self.overview_zoom;
             mon.set_overview_progress(self.overview_progress.as_ref());
         }
     }
 
     pub fn toggle_overview(&mut self) {
+        self.toggle_overview_with_zoom(None);
+    }
+
+    /// Toggles the overview, opening it with `zoom` rather than the configured zoom.
+    pub fn toggle_overview_with_zoom(&mut self, zoom: Option<f64>) {
         self.overview_open = !self.overview_open;
 
+        // Keep the zoom when closing, so that the animation doesn't jump.
+        if self.overview_open {
+            self.overview_zoom = zoom;
+        }
+
         let from = self.overview_progress.take().map_or(0., |p| p.value());
         let to = if self.overview_open { 1. } else { 0. };
 
@@ -4738,6 +5107,24 @@ impl<W: LayoutElement> Layout<W> {
         self.toggle_overview();
     }
 
+    /// Opens the overview with `zoom` rather than the configured zoom, activating the workspace
+    /// at `ws_idx` on the active monitor.
+    pub fn open_overview_to_workspace(&mut self, ws_idx: Option<usize>, zoom: Option<f64>) -> bool {
+        if self.overview_open {
+            return false;
+        }
+
+        if let Some(ws_idx) = ws_idx {
+            let config = self.options.animations.overview_open_close.0;
+            if let Some(mon) = self.active_monitor() {
+                mon.activate_workspace_with_anim_config(ws_idx, Some(config));
+            }
+        }
+
+        self.toggle_overview_with_zoom(zoom);
+        true
+    }
+
     pub fn start_open_animation_for_window(&mut self, window: &W::Id) {
         if let Some(InteractiveMoveState::Moving(move_)) = &self.interactive_move {
             if move_.tile.focused_window().id() == window {
@@ -4934,6 +5321,11 @@ impl<W: LayoutElement> Layout<W> {
         let mut ongoing_scrolling_dnd = self.dnd.is_some().then_some(true);
 
         if let Some(InteractiveMoveState::Moving(move_)) = &mut self.interactive_move {
+            let now = self.clock.now_unadjusted();
+            if now.saturating_sub(move_.output_changed_at) >= OUTPUT_SETTLE_DELAY {
+                move_.settle_output();
+            }
+
             let win = move_.tile.focused_window_mut();
 
             win.set_active_in_column(true);
@@ -5067,6 +5459,23 @@ impl<W: LayoutElement> Layout<W> {
         iter_normal.chain(iter_no_outputs)
     }
 
+    /// Returns when the earliest debounced tab title change is due to be applied.
+    ///
+    /// Title changes are applied on redraw, so the redraw must be scheduled by then.
+    pub fn pending_tab_title_deadline(&self) -> Option<Duration> {
+        let moving_tile = self
+            .interactive_move
+            .as_ref()
+            .and_then(|x| x.moving())
+            .map(|move_| &move_.tile);
+
+        moving_tile
+            .into_iter()
+            .chain(self.workspaces().flat_map(|(_, _, ws)| ws.tiles()))
+            .filter_map(|tile| tile.pending_tab_title_deadline())
+            .min()
+    }
+
     pub fn windows(&self) -> impl Iterator<Item = (Option<&Monitor<W>>, &W)> {
         let moving_window = self
             .interactive_move
@@ -5091,6 +5500,26 @@ impl<W: LayoutElement> Layout<W> {
         self.windows().any(|(_, win)| win.id() == window)
     }
 
+    /// Returns the tile containing the given window.
+    pub fn tile_for_window(&self, window: &W::Id) -> Option<&Tile<W>> {
+        let moving_tile = self
+            .interactive_move
+            .as_ref()
+            .and_then(|x| x.moving())
+            .map(|move_| &move_.tile);
+
+        moving_tile
+            .into_iter()
+            .chain(self.workspaces().flat_map(|(_, _, ws)| ws.tiles()))
+            .find(|tile| tile.has_window(window))
+    }
+
+    /// Returns the scrolling layout column containing the given window.
+    pub fn column_for_window(&self, window: &W::Id) -> Option<&Column<W>> {
+        self.workspaces()
+            .find_map(|(_, _, ws)| ws.column_for_window(window))
+    }
+
     pub fn is_overview_open(&self) -> bool {
         self.overview_open
     }
@@ -5102,9 +5531,13 @@ impl<W: LayoutElement> Default for MonitorSet<W> {
     }
 }
 
-fn compute_overview_zoom(options: &Options, overview_progress: Option<f64>) -> f64 {
+fn compute_overview_zoom(
+    options: &Options,
+    zoom: Option<f64>,
+    overview_progress: Option<f64>,
+) -> f64 {
     // Clamp to some sane values.
-    let zoom = options.overview.zoom.clamp(0.0001, 0.75);
+    let zoom = zoom.unwrap_or(options.overview.zoom).clamp(0.0001, 0.75);
 
     if let Some(p) = overview_progress {
         (1. - p * (1. - zoom)).max(0.0001)
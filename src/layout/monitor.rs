@@ -1,14 +1,20 @@
 use std::cmp::min;
+use std::collections::HashMap;
 use std::iter::zip;
 use std::rc::Rc;
 use std::time::Duration;
 
+use anyhow::Context as _;
+use glam::{Mat3, Vec2};
 use niri_config::{CornerRadius, LayoutPart};
 use smithay::backend::renderer::element::utils::{
     CropRenderElement, Relocate, RelocateRenderElement, RescaleRenderElement,
 };
+use smithay::backend::renderer::element::{Kind, RenderElement};
+use smithay::backend::renderer::gles::{GlesRenderer, Uniform};
+use smithay::backend::renderer::Texture as _;
 use smithay::output::Output;
-use smithay::utils::{Logical, Point, Rectangle, Size};
+use smithay::utils::{Logical, Physical, Point, Rectangle, Scale, Size};
 
 use super::insert_hint_element::{InsertHintElement, InsertHintRenderElement};
 use super::scrolling::{Column, ColumnWidth};
@@ -21,7 +27,10 @@ use super::{compute_overview_zoom, ActivateWindow, HitType, LayoutElement, Optio
 use crate::animation::{Animation, Clock};
 use crate::input::swipe_tracker::SwipeTracker;
 use crate::niri_render_elements;
+use crate::render_helpers::offscreen::OffscreenBuffer;
 use crate::render_helpers::renderer::NiriRenderer;
+use crate::render_helpers::shader_element::ShaderRenderElement;
+use crate::render_helpers::shaders::{mat3_uniform, ProgramType};
 use crate::render_helpers::shadow::ShadowRenderElement;
 use crate::render_helpers::solid_color::SolidColorRenderElement;
 use crate::render_helpers::RenderTarget;
@@ -79,6 +88,8 @@ pub struct Monitor<W: LayoutElement> {
     pub(super) overview_open: bool,
     /// Progress of the overview zoom animation, 1 is fully in overview.
     overview_progress: Option<OverviewProgress>,
+    /// Zoom that the overview was opened with, overriding the configured zoom.
+    pub(super) overview_zoom: Option<f64>,
     /// Clock for driving animations.
     pub(super) clock: Clock,
     /// Configurable properties of the layout as received from the parent layout.
@@ -87,6 +98,8 @@ pub struct Monitor<W: LayoutElement> {
     pub(super) options: Rc<Options>,
     /// Layout config overrides for this monitor.
     layout_config: Option<niri_config::LayoutPart>,
+    /// Buffers for the workspace snapshots of the custom workspace switch shader.
+    workspace_switch_buffers: [OffscreenBuffer; 2],
 }
 
 #[derive(Debug)]
@@ -170,6 +183,12 @@ pub enum MonitorAddWindowTarget<'a, W: LayoutElement> {
         /// Override where the window will open as a new column.
         column_idx: Option<usize>,
     },
+    /// On this workspace, at this column and tile index in the scrolling layout.
+    WorkspacePosition {
+        id: WorkspaceId,
+        column_idx: usize,
+        tile_idx: usize,
+    },
     /// Next to this existing window.
     NextTo(&'a W::Id),
 }
@@ -189,6 +208,7 @@ niri_render_elements! {
         UncroppedInsertHint = InsertHintRenderElement,
         Shadow = ShadowRenderElement,
         SolidColor = SolidColorRenderElement,
+        WorkspaceSwitchShader = ShaderRenderElement,
     }
 }
 
@@ -340,11 +360,13 @@ impl<W: LayoutElement> Monitor<W> {
             insert_hint_render_loc: None,
             overview_open: false,
             overview_progress: None,
+            overview_zoom: None,
             workspace_switch: None,
             clock,
             base_options,
             options,
             layout_config,
+            workspace_switch_buffers: Default::default(),
         }
     }
 
@@ -448,7 +470,7 @@ impl<W: LayoutElement> Monitor<W> {
         let prev_active_idx = self.active_workspace_idx;
         self.active_workspace_idx = idx;
 
-        let config = config.unwrap_or(self.options.animations.workspace_switch.0);
+        let config = config.unwrap_or(self.options.animations.workspace_switch.anim);
 
         match &mut self.workspace_switch {
             // During a DnD scroll, we want to visually animate even if idx matches the active idx.
@@ -498,6 +520,18 @@ impl<W: LayoutElement> Monitor<W> {
                 };
                 (idx, target)
             }
+            MonitorAddWindowTarget::WorkspacePosition {
+                id,
+                column_idx,
+                tile_idx,
+            } => {
+                let idx = self.workspaces.iter().position(|ws| ws.id() == id).unwrap();
+                let target = WorkspaceAddWindowTarget::Position {
+                    column_idx,
+                    tile_idx,
+                };
+                (idx, target)
+            }
             MonitorAddWindowTarget::NextTo(win_id) => {
                 let idx = self
                     .workspaces
@@ -539,7 +573,7 @@ impl<W: LayoutElement> Monitor<W> {
     pub fn add_column(&mut self, mut workspace_idx: usize, column: Column<W>, activate: bool) {
         let workspace = &mut self.workspaces[workspace_idx];
 
-        workspace.add_column(column, activate);
+        workspace.add_column(None, column, activate);
 
         // After adding a new window, workspace becomes this output's own.
         if workspace.name().is_none() {
@@ -633,6 +667,20 @@ impl<W: LayoutElement> Monitor<W> {
         }
     }
 
+    /// Releases the workspace snapshots kept from the last workspace switch, returning the number
+    /// of textures released.
+    pub fn release_caches(&self) -> usize {
+        // The snapshots are still in use during the switch.
+        if self.workspace_switch.is_some() {
+            return 0;
+        }
+
+        self.workspace_switch_buffers
+            .iter()
+            .filter(|buffer| buffer.release())
+            .count()
+    }
+
     pub fn clean_up_workspaces(&mut self) {
         assert!(self.workspace_switch.is_none());
 
@@ -1157,6 +1205,7 @@ impl<W: LayoutElement> Monitor<W> {
                                 view_rect,
                                 hint.corner_radius,
                                 scale,
+                                1.,
                             );
                             self.insert_hint_render_loc = Some(InsertHintRenderLoc {
                                 workspace: hint.workspace,
@@ -1197,6 +1246,7 @@ impl<W: LayoutElement> Monitor<W> {
                         view_rect,
                         CornerRadius::default(),
                         scale,
+                        1.,
                     );
                     self.insert_hint_render_loc = Some(InsertHintRenderLoc {
                         workspace: hint.workspace,
@@ -1396,7 +1446,7 @@ impl<W: LayoutElement> Monitor<W> {
 
     pub fn overview_zoom(&self) -> f64 {
         let progress = self.overview_progress.as_ref().map(|p| p.value());
-        compute_overview_zoom(&self.options, progress)
+        compute_overview_zoom(&self.options, self.overview_zoom, progress)
     }
 
     pub fn workspace_switch_in_progress(&self) -> bool {
@@ -1479,7 +1529,8 @@ impl<W: LayoutElement> Monitor<W> {
                 // - first_y = to * from_height - switch_anim.value() * from_height - to * current_height
                 // - first_y = -switch_anim.value() * from_height + to * (from_height - current_height)
                 let from = progress_anim.from();
-                let from_zoom = compute_overview_zoom(&self.options, Some(from));
+                let from_zoom =
+                    compute_overview_zoom(&self.options, self.overview_zoom, Some(from));
                 let from_ws_height_with_gap = self.workspace_size_with_gap(from_zoom).h;
 
                 let zoom = self.overview_zoom();
@@ -1605,6 +1656,19 @@ impl<W: LayoutElement> Monitor<W> {
         ws.resize_edges_under(pos_within_output - geo.loc)
     }
 
+    pub fn border_resize_edges_under(
+        &self,
+        pos_within_output: Point<f64, Logical>,
+        width: f64,
+    ) -> Option<(&W, ResizeEdge)> {
+        if self.overview_progress.is_some() {
+            return None;
+        }
+
+        let (ws, geo) = self.workspace_under(pos_within_output)?;
+        ws.border_resize_edges_under(pos_within_output - geo.loc, width)
+    }
+
     pub(super) fn insert_position(
         &self,
         pos_within_output: Point<f64, Logical>,
@@ -1724,60 +1788,182 @@ impl<W: LayoutElement> Monitor<W> {
             )
         };
 
+        for (ws, geo) in self.workspaces_with_render_geo() {
+            self.render_workspace_at(renderer, ws, geo, crop_bounds, target, focus_ring, push);
+        }
+    }
+
+    /// Renders a single workspace at the origin, cropped to the monitor bounds.
+    pub fn render_workspace<R: NiriRenderer>(
+        &self,
+        renderer: &mut R,
+        ws: &Workspace<W>,
+        target: RenderTarget,
+        focus_ring: bool,
+        push: &mut dyn FnMut(MonitorRenderElement<R>),
+    ) {
+        let scale = self.scale.fractional_scale();
+        let geo = Rectangle::from_size(self.view_size);
+        let crop_bounds = geo.to_physical_precise_round(scale);
+        self.render_workspace_at(renderer, ws, geo, crop_bounds, target, focus_ring, push);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_workspace_at<R: NiriRenderer>(
+        &self,
+        renderer: &mut R,
+        ws: &Workspace<W>,
+        geo: Rectangle<f64, Logical>,
+        crop_bounds: Rectangle<i32, Physical>,
+        target: RenderTarget,
+        focus_ring: bool,
+        push: &mut dyn FnMut(MonitorRenderElement<R>),
+    ) {
+        let scale = self.scale.fractional_scale();
         let zoom = self.overview_zoom();
-        let insert_hint_render_loc = self.insert_hint_render_loc;
         let overview_open = self.overview_progress.is_some();
 
-        for ((_idx, ws), geo) in self.workspaces_with_render_geo_idx() {
-            let force_optimized_blur = self.are_animations_ongoing() || overview_open;
-            let overview_zoom_offset = Some(geo.loc);
-            // Macro instead of closure because ws and insert hint have different elem types.
-            macro_rules! push_elem {
-                () => {{
-                    &mut |elem| {
-                        let elem = CropRenderElement::from_element(elem, scale, crop_bounds);
-                        if let Some(elem) = elem {
-                            let elem = MonitorInnerRenderElement::from(elem);
-                            let elem =
-                                RescaleRenderElement::from_element(elem, Point::from((0, 0)), zoom);
-                            let elem = RelocateRenderElement::from_element(
-                                elem,
-                                geo.loc.to_physical_precise_round(scale),
-                                Relocate::Relative,
-                            );
-                            push(elem);
-                        }
+        let force_optimized_blur = self.are_animations_ongoing() || overview_open;
+        let overview_zoom_offset = Some(geo.loc);
+        // Macro instead of closure because ws and insert hint have different elem types.
+        macro_rules! push_elem {
+            () => {{
+                &mut |elem| {
+                    let elem = CropRenderElement::from_element(elem, scale, crop_bounds);
+                    if let Some(elem) = elem {
+                        let elem = MonitorInnerRenderElement::from(elem);
+                        let elem =
+                            RescaleRenderElement::from_element(elem, Point::from((0, 0)), zoom);
+                        let elem = RelocateRenderElement::from_element(
+                            elem,
+                            geo.loc.to_physical_precise_round(scale),
+                            Relocate::Relative,
+                        );
+                        push(elem);
                     }
-                }};
-            }
+                }
+            }};
+        }
 
-            ws.render_floating(
-                renderer,
-                target,
-                focus_ring,
-                push_elem!(),
-                zoom,
-                force_optimized_blur,
-                overview_zoom_offset,
-            );
+        ws.render_floating(
+            renderer,
+            target,
+            focus_ring,
+            push_elem!(),
+            zoom,
+            force_optimized_blur,
+            overview_zoom_offset,
+        );
 
-            if let Some(loc) = insert_hint_render_loc {
-                if loc.workspace == InsertWorkspace::Existing(ws.id()) {
-                    self.insert_hint_element
-                        .render(renderer, loc.location, push_elem!());
-                }
+        if let Some(loc) = self.insert_hint_render_loc {
+            if loc.workspace == InsertWorkspace::Existing(ws.id()) {
+                self.insert_hint_element
+                    .render(renderer, loc.location, push_elem!());
             }
+        }
 
-            ws.render_scrolling(
-                renderer,
-                target,
-                focus_ring,
-                push_elem!(),
-                zoom,
-                force_optimized_blur,
-                overview_zoom_offset,
-            );
+        ws.render_scrolling(
+            renderer,
+            target,
+            focus_ring,
+            push_elem!(),
+            zoom,
+            force_optimized_blur,
+            overview_zoom_offset,
+        );
+    }
+
+    /// Returns the workspaces to draw with the custom workspace switch shader.
+    ///
+    /// These are the two workspaces around the current switch position, the upper one first,
+    /// along with the position between them: 0 shows the upper workspace and 1 the lower one.
+    pub fn workspace_switch_shader_workspaces(
+        &self,
+    ) -> Option<(&Workspace<W>, &Workspace<W>, f64)> {
+        // In the overview, the workspaces are drawn zoomed out next to each other.
+        if self.workspace_switch.is_none() || self.overview_progress.is_some() {
+            return None;
+        }
+
+        let render_idx = self.workspace_render_idx();
+        let last_idx = self.workspaces.len() - 1;
+        let from_idx = min(
+            render_idx.max(0.).floor() as usize,
+            last_idx.saturating_sub(1),
+        );
+        let to_idx = min(from_idx + 1, last_idx);
+        let progress = render_idx - from_idx as f64;
+
+        Some((
+            &self.workspaces[from_idx],
+            &self.workspaces[to_idx],
+            progress,
+        ))
+    }
+
+    /// Renders the workspace switch with the custom shader from the two workspace snapshots.
+    pub fn render_workspace_switch_shader<R: NiriRenderer, E: RenderElement<GlesRenderer>>(
+        &self,
+        renderer: &mut GlesRenderer,
+        from: &[E],
+        to: &[E],
+        progress: f64,
+    ) -> anyhow::Result<MonitorRenderElement<R>> {
+        let scale = Scale::from(self.scale.fractional_scale());
+        let geo_size = Vec2::new(self.view_size.w as f32, self.view_size.h as f32);
+
+        let mut uniforms = vec![
+            mat3_uniform("niri_input_to_geo", Mat3::IDENTITY),
+            Uniform::new("niri_geo_size", geo_size.to_array()),
+            Uniform::new("niri_progress", progress as f32),
+            Uniform::new("niri_clamped_progress", progress.clamp(0., 1.) as f32),
+        ];
+        let mut textures = HashMap::new();
+
+        let snapshots = [
+            (from, "niri_tex_from", "niri_geo_to_tex_from"),
+            (to, "niri_tex_to", "niri_geo_to_tex_to"),
+        ];
+        for (buffer, (elements, tex_name, matrix_name)) in
+            zip(&self.workspace_switch_buffers, snapshots)
+        {
+            // The snapshots are only drawn through the shader, so their render element states
+            // aren't needed.
+            let (elem, _sync_point, _data) = buffer
+                .render(renderer, scale, elements)
+                .context("error rendering workspace to offscreen buffer")?;
+
+            // OffscreenBuffer renders with Transform::Normal and the scale that we passed, so we
+            // can assume that below.
+            let offset = elem.offset();
+            let texture = elem.texture();
+
+            let tex_scale = Vec2::new(scale.x as f32, scale.y as f32);
+            let tex_loc = Vec2::new(offset.x as f32, offset.y as f32);
+            let tex_size = Vec2::new(texture.width() as f32, texture.height() as f32) / tex_scale;
+
+            let geo_to_tex =
+                Mat3::from_translation(-tex_loc / tex_size) * Mat3::from_scale(geo_size / tex_size);
+
+            uniforms.push(mat3_uniform(matrix_name, geo_to_tex));
+            textures.insert(String::from(tex_name), texture.clone());
         }
+
+        let elem = ShaderRenderElement::new(
+            ProgramType::WorkspaceSwitch,
+            self.view_size,
+            None,
+            scale.x as f32,
+            1.,
+            Rc::from(uniforms),
+            textures,
+            Kind::Unspecified,
+        );
+
+        let elem = MonitorInnerRenderElement::WorkspaceSwitchShader(elem);
+        let elem = RescaleRenderElement::from_element(elem, Point::default(), 1.);
+        let elem = RelocateRenderElement::from_element(elem, Point::default(), Relocate::Relative);
+        Ok(elem)
     }
 
     pub fn render_workspace_shadows<R: NiriRenderer>(
@@ -2048,7 +2234,7 @@ impl<W: LayoutElement> Monitor<W> {
             gesture.current_idx,
             new_idx as f64,
             velocity,
-            self.options.animations.workspace_switch.0,
+            self.options.animations.workspace_switch.anim,
         )));
 
         true
@@ -1,16 +1,18 @@
 use std::cmp::{max, min};
 use std::iter::{self, zip};
+use std::mem;
 use std::rc::Rc;
 use std::time::Duration;
 
 use niri_config::utils::MergeWith as _;
-use niri_config::{CenterFocusedColumn, PresetSize, Struts, WindowMoveDirection};
+use niri_config::{CenterFocusedColumn, CornerRadius, PresetSize, Struts, WindowMoveDirection};
 use niri_ipc::{SizeChange, WindowLayout};
 use ordered_float::NotNan;
 use smithay::backend::renderer::gles::GlesRenderer;
 use smithay::utils::{Logical, Point, Rectangle, Scale, Serial, Size};
 
 use super::closing_window::{ClosingWindow, ClosingWindowRenderElement};
+use super::insert_hint_element::{InsertHintElement, InsertHintRenderElement};
 use super::monitor::InsertPosition;
 use super::tile::{Tile, TileRenderElement, TileRenderSnapshot};
 use super::workspace::{InteractiveResize, ResolvedSize};
@@ -69,6 +71,12 @@ pub struct ScrollingSpace<W: LayoutElement> {
     /// Windows in the closing animation.
     closing_windows: Vec<ClosingWindow>,
 
+    /// Hint showing where the last consumed or expelled window lands.
+    consume_preview: Option<ConsumePreview<W>>,
+
+    /// Element for drawing the consume preview.
+    consume_preview_element: InsertHintElement,
+
     /// View size for this space.
     view_size: Size<f64, Logical>,
 
@@ -97,6 +105,7 @@ niri_render_elements! {
     ScrollingSpaceRenderElement<R> => {
         Tile = TileRenderElement<R>,
         ClosingWindow = ClosingWindowRenderElement,
+        ConsumePreview = InsertHintRenderElement,
     }
 }
 
@@ -107,6 +116,13 @@ struct ColumnData {
     width: f64,
 }
 
+/// View position saved for a later restoration.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct SavedView {
+    active_column_idx: usize,
+    view_offset: f64,
+}
+
 #[derive(Debug, Clone, Copy)]
 struct CenteringTarget {
     view_offset: f64,
@@ -274,6 +290,17 @@ pub enum ScrollDirection {
     Right,
 }
 
+/// Translucent hint over the spot where a consumed or expelled window lands.
+#[derive(Debug)]
+struct ConsumePreview<W: LayoutElement> {
+    /// Window that was consumed or expelled.
+    window: W::Id,
+    /// Fade-out animation, from 1 to 0.
+    anim: Animation,
+    /// Location of the hint relative to the view, if it is visible.
+    render_loc: Option<Point<f64, Logical>>,
+}
+
 #[derive(Debug)]
 struct MoveAnimation {
     anim: Animation,
@@ -299,6 +326,8 @@ impl<W: LayoutElement> ScrollingSpace<W> {
             activate_prev_column_on_removal: None,
             view_offset_to_restore: None,
             closing_windows: Vec::new(),
+            consume_preview: None,
+            consume_preview_element: InsertHintElement::new(options.layout.insert_hint),
             view_size,
             working_area,
             parent_area,
@@ -322,6 +351,12 @@ impl<W: LayoutElement> ScrollingSpace<W> {
             data.update(column);
         }
 
+        self.consume_preview_element
+            .update_config(options.layout.insert_hint);
+        if options.layout.insert_hint.off {
+            self.consume_preview = None;
+        }
+
         self.view_size = view_size;
         self.working_area = working_area;
         self.parent_area = parent_area;
@@ -338,6 +373,8 @@ impl<W: LayoutElement> ScrollingSpace<W> {
         for col in &mut self.columns {
             col.update_shaders();
         }
+
+        self.consume_preview_element.update_shaders();
     }
 
     pub fn advance_animations(&mut self) {
@@ -380,12 +417,21 @@ impl<W: LayoutElement> ScrollingSpace<W> {
             closing.advance_animations();
             closing.are_animations_ongoing()
         });
+
+        if self
+            .consume_preview
+            .as_ref()
+            .is_some_and(|preview| preview.anim.is_done())
+        {
+            self.consume_preview = None;
+        }
     }
 
     pub fn are_animations_ongoing(&self) -> bool {
         self.view_offset.is_animation_ongoing()
             || self.columns.iter().any(Column::are_animations_ongoing)
             || !self.closing_windows.is_empty()
+            || self.consume_preview.is_some()
     }
 
     pub fn are_transitions_ongoing(&self) -> bool {
@@ -405,6 +451,82 @@ impl<W: LayoutElement> ScrollingSpace<W> {
             let view_rect = Rectangle::new(col_pos, view_size);
             col.update_render_elements(is_active, view_rect);
         }
+
+        self.update_consume_preview_render_elements();
+    }
+
+    fn update_consume_preview_render_elements(&mut self) {
+        let Some(preview) = &self.consume_preview else {
+            return;
+        };
+
+        let area = self.consume_preview_area(&preview.window);
+        let alpha = preview.anim.clamped_value().clamp(0., 1.) as f32;
+        let view_size = self.view_size;
+
+        let preview = self.consume_preview.as_mut().unwrap();
+        preview.render_loc = None;
+
+        let Some((area, radius)) = area else {
+            return;
+        };
+
+        // Round to physical pixels.
+        let area = area
+            .to_physical_precise_round(self.scale)
+            .to_logical(self.scale);
+
+        let view_rect = Rectangle::new(area.loc.upscale(-1.), view_size);
+        self.consume_preview_element
+            .update_render_elements(area.size, view_rect, radius, self.scale, alpha);
+        preview.render_loc = Some(area.loc);
+    }
+
+    /// Returns the final area of the given window's tile relative to the view, along with its
+    /// corner radius.
+    fn consume_preview_area(
+        &self,
+        window: &W::Id,
+    ) -> Option<(Rectangle<f64, Logical>, CornerRadius)> {
+        let col_idx = self.columns.iter().position(|col| col.contains(window))?;
+        let col = &self.columns[col_idx];
+        let tile_idx = col.position(window)?;
+        let tile = &col.tiles[tile_idx];
+
+        let mut loc = col.tile_offset(tile_idx);
+        loc.x += self.column_x(col_idx) - self.view_pos();
+        let area = Rectangle::new(loc, tile.tile_size());
+
+        let border_width = tile.effective_border_width().unwrap_or(0.);
+        let radius = tile
+            .focused_window()
+            .rules()
+            .geometry_corner_radius
+            .map_or(CornerRadius::default(), |radius| {
+                radius.expanded_by(border_width as f32)
+            });
+
+        Some((area, radius))
+    }
+
+    /// Starts showing where the given consumed or expelled window lands.
+    fn start_consume_preview(&mut self, window: W::Id) {
+        if self.options.layout.insert_hint.off {
+            return;
+        }
+
+        let anim = Animation::new(
+            self.clock.clone(),
+            1.,
+            0.,
+            0.,
+            self.options.animations.window_movement.0,
+        );
+        self.consume_preview = Some(ConsumePreview {
+            window,
+            anim,
+            render_loc: None,
+        });
     }
 
     pub fn tiles(&self) -> impl Iterator<Item = &Tile<W>> + '_ {
@@ -2426,6 +2548,10 @@ impl<W: LayoutElement> ScrollingSpace<W> {
 
         let source_column = &self.columns[source_col_idx];
         let prev_off = source_column.tile_offset(source_tile_idx);
+        let window_id = source_column.tiles[source_tile_idx]
+            .focused_window()
+            .id()
+            .clone();
 
         let source_tile_was_active = self.active_column_idx == source_col_idx
             && source_column.active_tile_idx == source_tile_idx;
@@ -2510,6 +2636,7 @@ impl<W: LayoutElement> ScrollingSpace<W> {
         }
 
         self.clamp_view_right_edge_if_overflowing();
+        self.start_consume_preview(window_id);
     }
 
     pub fn consume_or_expel_window_right(&mut self, window: Option<&W::Id>) {
@@ -2539,6 +2666,10 @@ impl<W: LayoutElement> ScrollingSpace<W> {
         let source_column = &self.columns[source_col_idx];
         let mut offset = Point::from((source_column.render_offset().x, 0.));
         let prev_off = source_column.tile_offset(source_tile_idx);
+        let window_id = source_column.tiles[source_tile_idx]
+            .focused_window()
+            .id()
+            .clone();
 
         let source_tile_was_active = self.active_column_idx == source_col_idx
             && source_column.active_tile_idx == source_tile_idx;
@@ -2604,6 +2735,7 @@ impl<W: LayoutElement> ScrollingSpace<W> {
         }
 
         self.clamp_view_right_edge_if_overflowing();
+        self.start_consume_preview(window_id);
     }
 
     pub fn consume_into_column(&mut self) {
@@ -2625,6 +2757,7 @@ impl<W: LayoutElement> ScrollingSpace<W> {
         let prev_off = self.columns[source_column_idx].tile_offset(0);
 
         let removed = self.remove_tile_by_idx(source_column_idx, 0, Transaction::new(), None);
+        let window_id = removed.tile.focused_window().id().clone();
         self.add_tile_to_column(target_column_idx, None, removed.tile, false);
 
         let target_column = &mut self.columns[target_column_idx];
@@ -2633,6 +2766,8 @@ impl<W: LayoutElement> ScrollingSpace<W> {
 
         let new_tile = target_column.tiles.last_mut().unwrap();
         new_tile.animate_move_from(offset);
+
+        self.start_consume_preview(window_id);
     }
 
     pub fn expel_from_column(&mut self) {
@@ -2656,6 +2791,7 @@ impl<W: LayoutElement> ScrollingSpace<W> {
 
         let removed =
             self.remove_tile_by_idx(source_col_idx, source_tile_idx, Transaction::new(), None);
+        let window_id = removed.tile.focused_window().id().clone();
 
         self.add_tile(
             Some(target_col_idx),
@@ -2671,6 +2807,8 @@ impl<W: LayoutElement> ScrollingSpace<W> {
         let new_col = &mut self.columns[target_col_idx];
         offset += prev_off - new_col.tile_offset(0);
         new_col.tiles[0].animate_move_from(offset);
+
+        self.start_consume_preview(window_id);
     }
 
     pub fn swap_window_in_direction(&mut self, direction: ScrollDirection) {
@@ -2801,6 +2939,77 @@ impl<W: LayoutElement> ScrollingSpace<W> {
         self.activate_column(target_column_idx);
     }
 
+    /// Swaps the tiles of two windows, leaving the columns and their widths in place.
+    ///
+    /// Returns `false` if either window is missing or is in a fullscreen or maximized column.
+    pub fn swap_windows(&mut self, window: &W::Id, other: &W::Id) -> bool {
+        let find = |id: &W::Id| {
+            self.columns
+                .iter()
+                .enumerate()
+                .find_map(|(col_idx, col)| Some((col_idx, col.position(id)?)))
+        };
+        let (Some(a), Some(b)) = (find(window), find(other)) else {
+            return false;
+        };
+        if a == b {
+            return false;
+        }
+        if self.columns[a.0].sizing_mode() != SizingMode::Normal
+            || self.columns[b.0].sizing_mode() != SizingMode::Normal
+        {
+            return false;
+        }
+
+        let tile_pos = |this: &Self, (col_idx, tile_idx): (usize, usize)| {
+            let col = &this.columns[col_idx];
+            let mut pos = col.render_offset() + col.tile_offset(tile_idx);
+            pos.x += this.column_x(col_idx);
+            pos
+        };
+        let (prev_a, prev_b) = (tile_pos(self, a), tile_pos(self, b));
+
+        let active = self.active_window().map(|win| win.id().clone());
+
+        if a.0 == b.0 {
+            let col = &mut self.columns[a.0];
+            col.tiles.swap(a.1, b.1);
+            col.data.swap(a.1, b.1);
+        } else {
+            let (first, second) = if a.0 < b.0 { (a, b) } else { (b, a) };
+            let (left, right) = self.columns.split_at_mut(second.0);
+            let (col_first, col_second) = (&mut left[first.0], &mut right[0]);
+            mem::swap(
+                &mut col_first.tiles[first.1],
+                &mut col_second.tiles[second.1],
+            );
+            mem::swap(&mut col_first.data[first.1], &mut col_second.data[second.1]);
+        }
+
+        for col_idx in [a.0, b.0] {
+            let col = &mut self.columns[col_idx];
+            cancel_resize_for_column(&mut self.interactive_resize, col);
+            col.update_tile_sizes(true);
+            self.data[col_idx].update(col);
+        }
+
+        // The tile at a now came from b, and the other way around.
+        let (new_a, new_b) = (tile_pos(self, a), tile_pos(self, b));
+        let tile = &mut self.columns[a.0].tiles[a.1];
+        tile.animate_move_from(prev_b - new_a);
+        tile.ensure_alpha_animates_to_1();
+        let tile = &mut self.columns[b.0].tiles[b.1];
+        tile.animate_move_from(prev_a - new_b);
+        tile.ensure_alpha_animates_to_1();
+
+        // Keep the focus on the same window at its new place.
+        if let Some(active) = active {
+            self.activate_window(&active);
+        }
+
+        true
+    }
+
     pub fn center_column(&mut self) {
         if self.columns.is_empty() {
             return;
@@ -2908,6 +3117,30 @@ impl<W: LayoutElement> ScrollingSpace<W> {
         self.column_x(self.active_column_idx) + self.view_offset.target()
     }
 
+    pub(super) fn save_view(&self) -> Option<SavedView> {
+        if self.columns.is_empty() {
+            return None;
+        }
+
+        Some(SavedView {
+            active_column_idx: self.active_column_idx,
+            view_offset: self.view_offset.stationary(),
+        })
+    }
+
+    pub(super) fn restore_view(&mut self, view: SavedView) {
+        // If the active column changed in the meantime, the saved view offset is meaningless.
+        if self.columns.is_empty() || self.active_column_idx != view.active_column_idx {
+            return;
+        }
+
+        if self.view_offset.is_gesture() {
+            return;
+        }
+
+        self.view_offset = ViewOffset::Static(view.view_offset);
+    }
+
     // HACK: pass a self.data iterator in manually as a workaround for the lack of method partial
     // borrowing. Note that this method's return value does not borrow the entire &Self!
     fn column_xs(&self, data: impl Iterator<Item = ColumnData>) -> impl Iterator<Item = f64> {
@@ -2947,6 +3180,16 @@ impl<W: LayoutElement> ScrollingSpace<W> {
         self.columns.iter()
     }
 
+    pub fn column_for_window(&self, window: &W::Id) -> Option<&Column<W>> {
+        self.columns.iter().find(|col| col.contains(window))
+    }
+
+    /// Returns the columns with their X positions and widths.
+    pub fn columns_with_geometry(&self) -> impl Iterator<Item = (&Column<W>, f64, f64)> {
+        let offsets = self.column_xs(self.data.iter().copied());
+        zip(zip(&self.columns, offsets), &self.data).map(|((col, x), data)| (col, x, data.width))
+    }
+
     pub fn columns_mut(&mut self) -> impl Iterator<Item = (&mut Column<W>, f64)> + '_ {
         let offsets = self.column_xs(self.data.iter().copied());
         zip(&mut self.columns, offsets)
@@ -3024,18 +3267,29 @@ impl<W: LayoutElement> ScrollingSpace<W> {
     }
 
     pub fn tiles_with_ipc_layouts(&self) -> impl Iterator<Item = (&Tile<W>, WindowLayout)> {
-        self.columns
-            .iter()
+        let scale = self.scale;
+        // Use the target view position and skip render offsets to avoid IPC spam during
+        // animations.
+        let view_off = Point::from((-self.target_view_pos(), 0.));
+        zip(&self.columns, self.column_xs(self.data.iter().copied()))
             .enumerate()
-            .flat_map(move |(col_idx, col)| {
-                col.tiles().enumerate().map(move |(tile_idx, (tile, _))| {
-                    let layout = WindowLayout {
-                        // Our indices are 1-based, consistent with the actions.
-                        pos_in_scrolling_layout: Some((col_idx + 1, tile_idx + 1)),
-                        ..tile.ipc_layout_template()
-                    };
-                    (tile, layout)
-                })
+            .flat_map(move |(col_idx, (col, col_x))| {
+                let col_off = Point::from((col_x, 0.));
+                col.tiles()
+                    .enumerate()
+                    .map(move |(tile_idx, (tile, tile_off))| {
+                        let pos = view_off + col_off + tile_off;
+                        // Round to physical pixels.
+                        let pos = pos.to_physical_precise_round(scale).to_logical(scale);
+
+                        let layout = WindowLayout {
+                            // Our indices are 1-based, consistent with the actions.
+                            pos_in_scrolling_layout: Some((col_idx + 1, tile_idx + 1)),
+                            tile_pos_in_workspace_view: Some(pos.into()),
+                            ..tile.ipc_layout_template()
+                        };
+                        (tile, layout)
+                    })
             })
     }
 
@@ -3411,6 +3665,42 @@ impl<W: LayoutElement> ScrollingSpace<W> {
         self.animate_view_offset_to_column(None, self.active_column_idx, None);
     }
 
+    pub fn fit_column_width_to_content(&mut self) {
+        if self.columns.is_empty() {
+            return;
+        }
+
+        let col = &mut self.columns[self.active_column_idx];
+        if !col.pending_sizing_mode().is_normal() {
+            return;
+        }
+
+        // Use the min width hint where the window sets one, and its current width otherwise.
+        // The latter covers windows that ignore our configure and pick their own width.
+        let width = col
+            .tiles
+            .iter()
+            .map(|tile| {
+                let window = tile.focused_window();
+                let min_w = window.min_size().w;
+                let w = if min_w > 0 { min_w } else { window.size().w };
+                tile.tile_width_for_window_width(f64::from(w))
+            })
+            .fold(0., f64::max);
+        if width <= 0. {
+            return;
+        }
+
+        cancel_resize_for_column(&mut self.interactive_resize, col);
+
+        col.width = ColumnWidth::Fixed(width);
+        col.preset_width_idx = None;
+        col.is_full_width = false;
+        col.update_tile_sizes(true);
+
+        self.animate_view_offset_to_column(None, self.active_column_idx, None);
+    }
+
     pub fn set_fullscreen(&mut self, window: &W::Id, is_fullscreen: bool) -> bool {
         let mut col_idx = self
             .columns
@@ -3510,6 +3800,12 @@ impl<W: LayoutElement> ScrollingSpace<W> {
             return;
         }
 
+        // Draw the consume preview on top of the tiles, like the interactive move insert hint.
+        if let Some(loc) = self.consume_preview.as_ref().and_then(|p| p.render_loc) {
+            self.consume_preview_element
+                .render(renderer, loc, &mut |elem| push(elem.into()));
+        }
+
         let mut first = true;
 
         // This matches self.tiles_in_render_order().
@@ -4163,6 +4459,42 @@ impl<W: LayoutElement> ScrollingSpace<W> {
         true
     }
 
+    /// Updates an interactive resize by scaling the original window width.
+    ///
+    /// Widths within `snap_distance` of a preset column width snap to that preset.
+    pub fn interactive_resize_scale_width(
+        &mut self,
+        window: &W::Id,
+        scale: f64,
+        snap_distance: f64,
+    ) -> bool {
+        let Some(resize) = &self.interactive_resize else {
+            return false;
+        };
+
+        if window != &resize.window {
+            return false;
+        }
+
+        let window_width = resize.original_window_size.w * scale;
+
+        let col = self
+            .columns
+            .iter_mut()
+            .find(|col| col.contains(window))
+            .unwrap();
+
+        let tile_idx = col
+            .tiles
+            .iter()
+            .position(|tile| tile.has_window(window))
+            .unwrap();
+
+        col.set_column_width_snapped(window_width, tile_idx, snap_distance);
+
+        true
+    }
+
     pub fn interactive_resize_end(&mut self, window: Option<&W::Id>) {
         let Some(resize) = &self.interactive_resize else {
             return;
@@ -4761,6 +5093,15 @@ impl<W: LayoutElement> Column<W> {
         self.tiles.iter().position(|t| t.has_window(window))
     }
 
+    /// Returns the window currently shown in the active tile of this column.
+    pub fn active_window(&self) -> &W {
+        self.tiles[self.active_tile_idx].focused_window()
+    }
+
+    pub fn windows(&self) -> impl Iterator<Item = &W> + '_ {
+        self.tiles.iter().flat_map(|tile| tile.windows())
+    }
+
     fn activate_idx(&mut self, idx: usize) -> bool {
         if self.active_tile_idx == idx {
             return false;
@@ -5261,6 +5602,37 @@ impl<W: LayoutElement> Column<W> {
         self.preset_width_idx = Some(preset_idx);
     }
 
+    fn set_column_width_snapped(&mut self, window_width: f64, tile_idx: usize, snap_distance: f64) {
+        let tile = &self.tiles[tile_idx];
+
+        let closest = self
+            .options
+            .layout
+            .preset_column_widths
+            .iter()
+            .enumerate()
+            .map(|(idx, preset)| {
+                let preset_width = match self.resolve_preset_width(*preset) {
+                    ResolvedSize::Tile(width) => tile.window_width_for_tile_width(width),
+                    ResolvedSize::Window(width) => width,
+                };
+                (idx, (preset_width - window_width).abs())
+            })
+            .filter(|(_, distance)| *distance <= snap_distance)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        if let Some((preset_idx, _)) = closest {
+            if self.preset_width_idx != Some(preset_idx) {
+                let preset = self.options.layout.preset_column_widths[preset_idx];
+                self.set_column_width(SizeChange::from(preset), Some(tile_idx), false);
+                self.preset_width_idx = Some(preset_idx);
+            }
+        } else {
+            let width = window_width.round() as i32;
+            self.set_column_width(SizeChange::SetFixed(width), Some(tile_idx), false);
+        }
+    }
+
     fn toggle_full_width(&mut self) {
         if self.is_pending_maximized {
             // Treat it as unmaximize.
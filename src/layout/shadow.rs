@@ -26,6 +26,10 @@ impl Shadow {
         self.config = config;
     }
 
+    pub fn config(&self) -> &niri_config::Shadow {
+        &self.config
+    }
+
     pub fn update_shaders(&mut self) {
         for elem in &mut self.shaders {
             elem.damage_all();
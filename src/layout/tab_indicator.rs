@@ -2,6 +2,7 @@ use std::cell::RefCell;
 use std::cmp::min;
 use std::iter::zip;
 use std::mem;
+use std::time::Duration;
 
 use anyhow::ensure;
 use itertools::izip;
@@ -61,6 +62,10 @@ niri_render_elements! {
 #[derive(Debug, Default)]
 struct TitleTexture {
     title: String,
+    // newer title waiting for the debounce to pass before replacing the rendered one
+    pending_title: Option<String>,
+    // when the title was last replaced
+    title_changed_at: Duration,
     scale: f64,
     max_size: Size<f64, Logical>,
     // cached result of the rendered title texture
@@ -86,6 +91,14 @@ impl TabIndicator {
         self.config = config;
     }
 
+    /// Drops the rendered title textures, returning how many were dropped.
+    pub fn release_title_textures(&mut self) -> usize {
+        self.title_textures
+            .iter()
+            .filter(|title| title.texture.take().is_some())
+            .count()
+    }
+
     pub fn update_shaders(&mut self) {
         for elem in &mut self.shaders {
             elem.damage_all();
@@ -104,6 +117,16 @@ impl TabIndicator {
         self.open_anim.is_some()
     }
 
+    /// Returns when the earliest held back title change is due to be applied.
+    pub fn pending_title_deadline(&self) -> Option<Duration> {
+        let debounce = Duration::from_millis(u64::from(self.config.title_debounce_ms));
+        self.title_textures
+            .iter()
+            .filter(|tex| tex.pending_title.is_some())
+            .map(|tex| tex.title_changed_at + debounce)
+            .min()
+    }
+
     pub fn start_open_animation(&mut self, clock: Clock, config: niri_config::Animation) {
         self.open_anim = Some(Animation::new(clock, 0., 1., 0., config));
     }
@@ -183,6 +206,7 @@ impl TabIndicator {
         area_view_rect: Rectangle<f64, Logical>,
         is_active: bool,
         scale: f64,
+        now: Duration,
     ) {
         self.tabs = tabs;
         let tab_count = self.tabs.len();
@@ -214,6 +238,7 @@ impl TabIndicator {
                 .map(|(t, rect)| {
                     TitleTexture::new(
                         t.title.clone(),
+                        now,
                         scale,
                         Size::new((rect.size.w - 20.).max(0.), 24.),
                         self.config.title_font_size,
@@ -227,8 +252,9 @@ impl TabIndicator {
                 rects.iter(),
             )
             .for_each(|(tex, t, rect)| {
+                let debounce = Duration::from_millis(u64::from(self.config.title_debounce_ms));
+                tex.update_title(&t.title, now, debounce);
                 tex.update_config(
-                    Some(t.title.clone()),
                     Some(scale),
                     Some(Size::new((rect.size.w - MIN_DIST_TO_EDGES).max(0.), 16384.)),
                     Some(self.config.title_font_size),
@@ -529,15 +555,23 @@ impl TabInfo {
         TabInfo {
             gradient,
             geometry,
-            title: window.title().unwrap_or_default(),
+            title: window.display_title(config.title_fallback),
         }
     }
 }
 
 impl TitleTexture {
-    fn new(title: String, scale: f64, max_size: Size<f64, Logical>, font_size: u32) -> Self {
+    fn new(
+        title: String,
+        now: Duration,
+        scale: f64,
+        max_size: Size<f64, Logical>,
+        font_size: u32,
+    ) -> Self {
         Self {
             title,
+            pending_title: None,
+            title_changed_at: now,
             scale,
             texture: Default::default(),
             max_size,
@@ -546,9 +580,34 @@ impl TitleTexture {
         }
     }
 
+    /// Updates the title, coalescing rapid changes.
+    ///
+    /// Some clients change the title many times per second (e.g. to show progress), and every
+    /// change means rasterizing the title anew. After a change, further changes are held back
+    /// until the debounce has passed, and only the latest one is applied.
+    fn update_title(&mut self, new_title: &str, now: Duration, debounce: Duration) {
+        if new_title == self.title {
+            self.pending_title = None;
+            return;
+        }
+
+        let is_rendered = self.texture.borrow().is_some();
+        if is_rendered && now.saturating_sub(self.title_changed_at) < debounce {
+            if self.pending_title.as_deref() != Some(new_title) {
+                self.pending_title = Some(new_title.to_owned());
+            }
+            return;
+        }
+
+        self.texture.set(None);
+        self.wanted_size.set(None);
+        self.title = new_title.to_owned();
+        self.pending_title = None;
+        self.title_changed_at = now;
+    }
+
     fn update_config(
         &mut self,
-        new_title: Option<String>,
         new_scale: Option<f64>,
         new_max_size: Option<Size<f64, Logical>>,
         new_font_size: Option<u32>,
@@ -560,14 +619,6 @@ impl TitleTexture {
             }
             self.font_size = new_font_size;
         }
-        if let Some(new_title) = new_title {
-            if new_title != self.title {
-                self.texture.set(None);
-                self.wanted_size.set(None);
-            }
-            self.title = new_title;
-        }
-
         if let Some(new_scale) = new_scale {
             if new_scale != self.scale {
                 self.texture.set(None);
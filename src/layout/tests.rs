@@ -518,6 +518,12 @@ enum Op {
     ConsumeWindowIntoColumn,
     ExpelWindowFromColumn,
     SwapWindowInDirection(#[proptest(strategy = "arbitrary_scroll_direction()")] ScrollDirection),
+    SwapWindows {
+        #[proptest(strategy = "1..=5usize")]
+        window: usize,
+        #[proptest(strategy = "1..=5usize")]
+        other: usize,
+    },
     CenterColumn,
     CenterWindow {
         #[proptest(strategy = "proptest::option::of(1..=5usize)")]
@@ -620,6 +626,7 @@ enum Op {
         id: Option<usize>,
     },
     ExpandColumnToAvailableWidth,
+    FitColumnWidthToContent,
     ToggleWindowFloating {
         #[proptest(strategy = "proptest::option::of(1..=5usize)")]
         id: Option<usize>,
@@ -647,6 +654,7 @@ enum Op {
         #[proptest(strategy = "prop::option::of(1..=5usize)")]
         new_parent_id: Option<usize>,
     },
+    CenterWindowOnParent(#[proptest(strategy = "1..=5usize")] usize),
     SetForcedSize {
         #[proptest(strategy = "1..=5usize")]
         id: usize,
@@ -710,6 +718,16 @@ enum Op {
         #[proptest(strategy = "-20000f64..20000f64")]
         py: f64,
     },
+    InteractiveMoveColumnBegin {
+        #[proptest(strategy = "1..=5usize")]
+        window: usize,
+        #[proptest(strategy = "1..=5usize")]
+        output_idx: usize,
+        #[proptest(strategy = "-20000f64..20000f64")]
+        px: f64,
+        #[proptest(strategy = "-20000f64..20000f64")]
+        py: f64,
+    },
     InteractiveMoveUpdate {
         #[proptest(strategy = "1..=5usize")]
         window: usize,
@@ -751,6 +769,14 @@ enum Op {
         #[proptest(strategy = "-20000f64..20000f64")]
         dy: f64,
     },
+    InteractiveResizeScaleWidth {
+        #[proptest(strategy = "1..=5usize")]
+        window: usize,
+        #[proptest(strategy = "0f64..10f64")]
+        scale: f64,
+        #[proptest(strategy = "0f64..100f64")]
+        snap_distance: f64,
+    },
     InteractiveResizeEnd {
         #[proptest(strategy = "1..=5usize")]
         window: usize,
@@ -868,6 +894,8 @@ impl Op {
                     name: WorkspaceName(format!("ws{ws_name}")),
                     open_on_output: output_name.map(|name| format!("output{name}")),
                     layout: layout_config.map(|x| niri_config::WorkspaceLayoutPart(*x)),
+                    icon: None,
+                    color: None,
                 });
             }
             Op::UnnameWorkspace { ws_name } => {
@@ -1181,6 +1209,9 @@ impl Op {
             Op::ConsumeWindowIntoColumn => layout.consume_into_column(),
             Op::ExpelWindowFromColumn => layout.expel_from_column(),
             Op::SwapWindowInDirection(direction) => layout.swap_window_in_direction(direction),
+            Op::SwapWindows { window, other } => {
+                layout.swap_windows(&window, &other);
+            }
             Op::CenterColumn => layout.center_column(),
             Op::CenterWindow { id } => {
                 let id = id.filter(|id| layout.has_window(id));
@@ -1355,6 +1386,7 @@ impl Op {
                 layout.reset_window_height(id.as_ref());
             }
             Op::ExpandColumnToAvailableWidth => layout.expand_column_to_available_width(),
+            Op::FitColumnWidthToContent => layout.fit_column_width_to_content(),
             Op::ToggleWindowFloating { id } => {
                 let id = id.filter(|id| layout.has_window(id));
                 layout.toggle_window_floating(id.as_ref());
@@ -1432,6 +1464,17 @@ impl Op {
                     }
                 }
             }
+            Op::CenterWindowOnParent(id) => {
+                let Some(parent_id) = layout
+                    .windows()
+                    .find(|(_, win)| win.0.id == id)
+                    .and_then(|(_, win)| win.0.parent_id.get())
+                else {
+                    return;
+                };
+
+                layout.center_window_on_parent(&id, &parent_id);
+            }
             Op::SetForcedSize { id, size } => {
                 for (_mon, win) in layout.windows() {
                     if win.0.id == id {
@@ -1581,6 +1624,18 @@ impl Op {
                 };
                 layout.interactive_move_begin(window, &output, Point::from((px, py)));
             }
+            Op::InteractiveMoveColumnBegin {
+                window,
+                output_idx,
+                px,
+                py,
+            } => {
+                let name = format!("output{output_idx}");
+                let Some(output) = layout.outputs().find(|o| o.name() == name).cloned() else {
+                    return;
+                };
+                layout.interactive_move_column_begin(window, &output, Point::from((px, py)));
+            }
             Op::InteractiveMoveUpdate {
                 window,
                 dx,
@@ -1619,6 +1674,13 @@ impl Op {
             Op::InteractiveResizeUpdate { window, dx, dy } => {
                 layout.interactive_resize_update(&window, Point::from((dx, dy)));
             }
+            Op::InteractiveResizeScaleWidth {
+                window,
+                scale,
+                snap_distance,
+            } => {
+                layout.interactive_resize_scale_width(&window, scale, snap_distance);
+            }
             Op::InteractiveResizeEnd { window } => {
                 layout.interactive_resize_end(&window);
             }
@@ -2167,6 +2229,56 @@ fn workspaces_update_original_output_on_moving_to_same_monitor() {
     assert_eq!(mon.output_name(), "output2");
 }
 
+#[test]
+fn workspace_view_position_is_restored_on_reconnect() {
+    let ops = [
+        Op::AddOutput(1),
+        Op::AddWindow {
+            params: TestWindowParams::new(1),
+        },
+        Op::AddWindow {
+            params: TestWindowParams::new(2),
+        },
+        Op::AddWindow {
+            params: TestWindowParams::new(3),
+        },
+        Op::Communicate(1),
+        Op::Communicate(2),
+        Op::Communicate(3),
+        Op::FocusColumn(1),
+        Op::FocusColumn(2),
+        Op::CompleteAnimations,
+    ];
+
+    let mut layout = check_ops(ops);
+
+    let view_pos = |layout: &Layout<TestWindow>| {
+        let (mon, _, ws) = layout
+            .workspaces()
+            .find(|(_, _, ws)| ws.has_window(&1))
+            .unwrap();
+        let output_name = mon.map(|mon| mon.output_name().clone());
+        (output_name, ws.scrolling().target_view_pos())
+    };
+    let before = view_pos(&layout);
+
+    let ops = [
+        Op::AddScaledOutput {
+            id: 2,
+            scale: 2.,
+            layout_config: None,
+        },
+        Op::RemoveOutput(1),
+        Op::CompleteAnimations,
+        Op::AddOutput(1),
+        Op::CompleteAnimations,
+    ];
+    check_ops_on_layout(&mut layout, ops);
+
+    assert_eq!(view_pos(&layout), before);
+    assert_eq!(before.0.as_deref(), Some("output1"));
+}
+
 #[test]
 fn large_negative_height_change() {
     let ops = [
@@ -2703,6 +2815,164 @@ fn interactive_move_onto_first_empty_workspace() {
     check_ops_with_options(options, ops);
 }
 
+/// Starts moving window 1 in the overview, with windows 0 and 1 on the first two workspaces.
+fn interactive_move_in_overview() -> Layout<TestWindow> {
+    let ops = [
+        Op::AddOutput(1),
+        Op::AddWindow {
+            params: TestWindowParams::new(0),
+        },
+        Op::FocusWorkspaceDown,
+        Op::AddWindow {
+            params: TestWindowParams::new(1),
+        },
+        Op::ToggleOverview,
+        Op::CompleteAnimations,
+        Op::InteractiveMoveBegin {
+            window: 1,
+            output_idx: 1,
+            px: 0.,
+            py: 0.,
+        },
+        Op::InteractiveMoveUpdate {
+            window: 1,
+            dx: 1000.,
+            dy: 0.,
+            output_idx: 1,
+            px: 0.,
+            py: 0.,
+        },
+    ];
+
+    let mut options = Options::default();
+    // Keep the overview from scrolling while the window is held near the output edges.
+    options.gestures.dnd_edge_workspace_switch.trigger_height = 0.;
+
+    let mut layout = Layout::with_options(Clock::with_time(Duration::ZERO), options);
+    check_ops_on_layout(&mut layout, ops);
+    layout
+}
+
+/// Returns a point within the visible part of the workspace in the overview.
+fn overview_point_on_workspace(layout: &Layout<TestWindow>, idx: usize) -> (f64, f64) {
+    let output = layout.outputs().next().unwrap();
+    let mon = layout.monitor_for_output(output).unwrap();
+    let (_, geo) = mon
+        .workspaces_with_render_geo_idx()
+        .find(|((ws_idx, _), _)| *ws_idx == idx)
+        .unwrap();
+
+    let view = Rectangle::from_size(mon.view_size);
+    let visible = geo.intersection(view).unwrap();
+    let center = visible.loc + visible.size.downscale(2.).to_point();
+    (center.x, center.y)
+}
+
+fn active_workspace_idx(layout: &Layout<TestWindow>) -> usize {
+    let output = layout.outputs().next().unwrap();
+    layout
+        .monitor_for_output(output)
+        .unwrap()
+        .active_workspace_idx()
+}
+
+fn move_window_1_to(px: f64, py: f64) -> Op {
+    Op::InteractiveMoveUpdate {
+        window: 1,
+        dx: 0.,
+        dy: 0.,
+        output_idx: 1,
+        px,
+        py,
+    }
+}
+
+#[test]
+fn interactive_move_hold_activates_workspace_in_overview() {
+    let mut layout = interactive_move_in_overview();
+    let (px, py) = overview_point_on_workspace(&layout, 0);
+
+    let ops = [
+        move_window_1_to(px, py),
+        Op::AdvanceAnimations { msec_delta: 0 },
+        Op::AdvanceAnimations { msec_delta: 500 },
+        // Moving within the same workspace keeps the hold going.
+        move_window_1_to(px + 10., py),
+        Op::AdvanceAnimations { msec_delta: 300 },
+    ];
+    check_ops_on_layout(&mut layout, ops);
+
+    assert!(!layout.is_overview_open());
+    assert_eq!(active_workspace_idx(&layout), 0);
+
+    check_ops_on_layout(&mut layout, [Op::InteractiveMoveEnd { window: 1 }]);
+}
+
+#[test]
+fn interactive_move_hold_resets_on_target_change() {
+    let mut layout = interactive_move_in_overview();
+    let (px0, py0) = overview_point_on_workspace(&layout, 0);
+    let (px2, py2) = overview_point_on_workspace(&layout, 2);
+
+    let ops = [
+        move_window_1_to(px0, py0),
+        Op::AdvanceAnimations { msec_delta: 0 },
+        Op::AdvanceAnimations { msec_delta: 500 },
+        move_window_1_to(px2, py2),
+        Op::AdvanceAnimations { msec_delta: 500 },
+    ];
+    check_ops_on_layout(&mut layout, ops);
+
+    // Neither workspace was held over for long enough.
+    assert!(layout.is_overview_open());
+    assert_eq!(active_workspace_idx(&layout), 1);
+
+    check_ops_on_layout(&mut layout, [Op::AdvanceAnimations { msec_delta: 300 }]);
+    assert!(!layout.is_overview_open());
+    assert_eq!(active_workspace_idx(&layout), 2);
+}
+
+#[test]
+fn interactive_move_hold_ignores_own_workspace() {
+    let mut layout = interactive_move_in_overview();
+    let (px, py) = overview_point_on_workspace(&layout, 1);
+
+    let ops = [
+        move_window_1_to(px, py),
+        Op::AdvanceAnimations { msec_delta: 0 },
+        Op::AdvanceAnimations { msec_delta: 1000 },
+    ];
+    check_ops_on_layout(&mut layout, ops);
+
+    assert!(layout.is_overview_open());
+    assert_eq!(active_workspace_idx(&layout), 1);
+}
+
+#[test]
+fn dnd_hold_resets_on_motion() {
+    let ops = [
+        Op::AddOutput(1),
+        Op::DndUpdate {
+            output_idx: 1,
+            px: 640.,
+            py: 360.,
+        },
+        Op::AdvanceAnimations { msec_delta: 0 },
+        Op::AdvanceAnimations { msec_delta: 500 },
+        // Data DnD restarts the hold on every motion.
+        Op::DndUpdate {
+            output_idx: 1,
+            px: 650.,
+            py: 360.,
+        },
+        Op::AdvanceAnimations { msec_delta: 500 },
+    ];
+    let layout = check_ops(ops);
+
+    // The hold was restarted by the second motion, so it's still pending.
+    assert!(layout.dnd.as_ref().unwrap().hold.is_some());
+}
+
 #[test]
 fn output_active_workspace_is_preserved() {
     let ops = [
@@ -3061,6 +3331,112 @@ fn interactive_move_from_workspace_with_layout_config() {
     check_ops(ops);
 }
 
+#[test]
+fn interactive_move_whole_column() {
+    let ops = [
+        Op::AddOutput(1),
+        Op::AddWindow {
+            params: TestWindowParams::new(0),
+        },
+        Op::AddWindow {
+            params: TestWindowParams::new(1),
+        },
+        Op::ConsumeOrExpelWindowLeft { id: None },
+        Op::SetColumnWidth(SizeChange::SetFixed(500)),
+        Op::SetWindowHeight {
+            id: Some(0),
+            change: SizeChange::SetFixed(200),
+        },
+        Op::AddWindow {
+            params: TestWindowParams::new(2),
+        },
+        Op::InteractiveMoveColumnBegin {
+            window: 1,
+            output_idx: 1,
+            px: 0.,
+            py: 0.,
+        },
+        Op::InteractiveMoveUpdate {
+            window: 1,
+            dx: 1000.,
+            dy: 0.,
+            output_idx: 1,
+            px: 1000.,
+            py: 300.,
+        },
+        Op::InteractiveMoveEnd { window: 1 },
+        Op::CompleteAnimations,
+    ];
+
+    let layout = check_ops(ops);
+
+    let (_, _, ws) = layout
+        .workspaces()
+        .find(|(_, _, ws)| ws.has_window(&1))
+        .unwrap();
+    assert_eq!(ws.column_window_ids(&1), Some(vec![0, 1]));
+    assert_eq!(ws.column_window_ids(&2), Some(vec![2]));
+    assert_eq!(ws.active_window().map(|win| *win.id()), Some(1));
+
+    // The column keeps its width and window heights.
+    let win = layout.windows().find(|(_, win)| *win.id() == 0).unwrap().1;
+    assert_eq!(win.requested_size().unwrap(), Size::from((500, 200)));
+    let win = layout.windows().find(|(_, win)| *win.id() == 1).unwrap().1;
+    assert_eq!(win.requested_size().unwrap().w, 500);
+}
+
+#[test]
+fn workspace_moved_to_output_settles_there() {
+    let ops = [
+        Op::AddOutput(1),
+        Op::AddOutput(2),
+        Op::AddWindow {
+            params: TestWindowParams::new(1),
+        },
+        Op::MoveWorkspaceToOutput(2),
+        Op::CompleteAnimations,
+    ];
+
+    let mut layout = check_ops(ops);
+    // The windows haven't entered the new output yet.
+    assert!(layout.are_animations_ongoing(None));
+
+    check_ops_on_layout(
+        &mut layout,
+        [
+            Op::AdvanceAnimations { msec_delta: 200 },
+            Op::CompleteAnimations,
+        ],
+    );
+    assert!(!layout.are_animations_ongoing(None));
+}
+
+#[test]
+fn kept_workspace_survives_becoming_empty() {
+    let ops = [
+        Op::AddOutput(1),
+        Op::AddWindow {
+            params: TestWindowParams::new(1),
+        },
+        Op::FocusWorkspaceDown,
+    ];
+
+    let mut layout = check_ops(ops);
+    let (_, _, ws) = layout
+        .workspaces()
+        .find(|(_, _, ws)| ws.has_window(&1))
+        .unwrap();
+    let ws_id = ws.id();
+    layout.set_keep_workspace_when_empty(ws_id, true);
+
+    check_ops_on_layout(&mut layout, [Op::CloseWindow(1)]);
+    assert!(layout.find_workspace_by_id(ws_id).is_some());
+
+    layout.set_keep_workspace_when_empty(ws_id, false);
+    layout.verify_invariants();
+    assert!(layout.find_workspace_by_id(ws_id).is_none());
+}
+
 #[test]
 fn set_width_fixed_negative() {
     let ops = [
@@ -3111,6 +3487,140 @@ fn interactive_resize_to_negative() {
     check_ops(ops);
 }
 
+#[test]
+fn interactive_resize_scale_width_snaps_and_shrinks() {
+    let ops = [
+        Op::AddOutput(1),
+        Op::AddWindow {
+            params: TestWindowParams::new(1),
+        },
+        Op::SetColumnWidth(SizeChange::SetFixed(800)),
+        Op::Communicate(1),
+        Op::InteractiveResizeBegin {
+            window: 1,
+            edges: ResizeEdge::RIGHT,
+        },
+        // 960 px is within the snap distance of the 1000 px preset.
+        Op::InteractiveResizeScaleWidth {
+            window: 1,
+            scale: 1.2,
+            snap_distance: 100.,
+        },
+    ];
+
+    let options = Options {
+        layout: niri_config::Layout {
+            preset_column_widths: vec![PresetSize::Fixed(500), PresetSize::Fixed(1000)],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut layout = check_ops_with_options(options, ops);
+
+    let win = layout.windows().next().unwrap().1;
+    assert_eq!(win.requested_size().unwrap().w, 1000);
+
+    // The scale is relative to the width at the start of the resize.
+    assert!(layout.interactive_resize_scale_width(&1, 0.5, 0.));
+    let win = layout.windows().next().unwrap().1;
+    assert_eq!(win.requested_size().unwrap().w, 400);
+
+    // Shrinking all the way stops at the minimum width.
+    assert!(layout.interactive_resize_scale_width(&1, 0., 0.));
+    let win = layout.windows().next().unwrap().1;
+    assert_eq!(win.requested_size().unwrap().w, 1);
+
+    layout.interactive_resize_end(&1);
+    layout.verify_invariants();
+}
+
+#[test]
+fn add_window_at_workspace_position() {
+    let ops = [
+        Op::AddOutput(1),
+        Op::AddWindow {
+            params: TestWindowParams::new(1),
+        },
+        Op::AddWindow {
+            params: TestWindowParams::new(2),
+        },
+    ];
+
+    let mut layout = check_ops(ops);
+    let ws_id = layout.active_workspace().unwrap().id();
+
+    let mut add = |id, column_idx, tile_idx| {
+        layout.add_window(
+            TestWindow::new(TestWindowParams::new(id)),
+            AddWindowTarget::WorkspacePosition {
+                id: ws_id,
+                column_idx,
+                tile_idx,
+            },
+            None,
+            None,
+            false,
+            false,
+            ActivateWindow::default(),
+            None,
+        );
+    };
+    // Into the existing first column.
+    add(3, 0, 1);
+    // As a new first column.
+    add(4, 0, 0);
+    // Past the last column.
+    add(5, 10, 2);
+    layout.verify_invariants();
+
+    let scrolling = layout.active_workspace().unwrap().scrolling();
+    let ids: Vec<Vec<usize>> = scrolling
+        .columns()
+        .map(|col| {
+            col.tiles()
+                .map(|(tile, _)| *tile.focused_window().id())
+                .collect()
+        })
+        .collect();
+    assert_eq!(ids, [vec![4], vec![1, 3], vec![2], vec![5]]);
+}
+
+#[test]
+fn swap_windows_keeps_focus_on_moved_window() {
+    let ops = [
+        Op::AddOutput(1),
+        Op::AddWindow {
+            params: TestWindowParams::new(1),
+        },
+        Op::AddWindow {
+            params: TestWindowParams::new(2),
+        },
+        Op::AddWindow {
+            params: TestWindowParams::new(3),
+        },
+        Op::FocusColumnLeft,
+        Op::ConsumeWindowIntoColumn,
+        Op::SwapWindows {
+            window: 2,
+            other: 1,
+        },
+    ];
+
+    let layout = check_ops(ops);
+    assert_eq!(layout.focus().map(|win| *win.id()), Some(2));
+
+    let scrolling = layout.active_workspace().unwrap().scrolling();
+    let ids: Vec<Vec<usize>> = scrolling
+        .columns()
+        .map(|col| {
+            col.tiles()
+                .map(|(tile, _)| *tile.focused_window().id())
+                .collect()
+        })
+        .collect();
+    assert_eq!(ids, [vec![2], vec![1, 3]]);
+}
+
 #[test]
 fn windows_on_other_workspaces_remain_activated() {
     let ops = [
@@ -3239,6 +3749,57 @@ fn stacking_set_parent_brings_up_child() {
     check_ops(ops);
 }
 
+#[test]
+fn center_window_on_parent() {
+    let ops = [
+        Op::AddOutput(0),
+        Op::AddWindow {
+            params: TestWindowParams {
+                is_floating: true,
+                ..TestWindowParams::new(0)
+            },
+        },
+        Op::AddWindow {
+            params: TestWindowParams {
+                is_floating: true,
+                ..TestWindowParams::new(1)
+            },
+        },
+        Op::Communicate(0),
+        Op::Communicate(1),
+        Op::MoveFloatingWindow {
+            id: Some(0),
+            x: PositionChange::SetFixed(300.),
+            y: PositionChange::SetFixed(200.),
+            animate: false,
+        },
+        Op::MoveFloatingWindow {
+            id: Some(1),
+            x: PositionChange::SetFixed(0.),
+            y: PositionChange::SetFixed(0.),
+            animate: false,
+        },
+        Op::FocusWindow(0),
+        Op::SetParent {
+            id: 1,
+            new_parent_id: Some(0),
+        },
+        Op::CenterWindowOnParent(1),
+        Op::CompleteAnimations,
+    ];
+
+    let layout = check_ops(ops);
+    let mut centers = Vec::new();
+    layout.with_windows(|win, _, _, layout| {
+        let (x, y) = layout.tile_pos_in_workspace_view.unwrap();
+        let (w, h) = layout.tile_size;
+        centers.push((*win.id(), (x + w / 2., y + h / 2.)));
+    });
+    centers.sort_by_key(|(id, _)| *id);
+
+    assert_eq!(centers[0].1, centers[1].1);
+}
+
 #[test]
 fn move_window_to_workspace_with_different_active_output() {
     let ops = [
@@ -3438,6 +3999,67 @@ fn preset_column_width_reset_after_set_width() {
     assert_eq!(win.requested_size().unwrap().w, 500);
 }
 
+#[test]
+fn fit_column_width_to_content_uses_largest_min_width() {
+    let ops = [
+        Op::AddOutput(0),
+        Op::AddWindow {
+            params: TestWindowParams {
+                min_max_size: (Size::from((300, 0)), Size::from((0, 0))),
+                ..TestWindowParams::new(0)
+            },
+        },
+        Op::AddWindow {
+            params: TestWindowParams {
+                min_max_size: (Size::from((400, 0)), Size::from((0, 0))),
+                ..TestWindowParams::new(1)
+            },
+        },
+        Op::ConsumeOrExpelWindowLeft { id: None },
+        Op::FitColumnWidthToContent,
+        Op::Communicate(0),
+        Op::Communicate(1),
+    ];
+
+    let mut options = Options::default();
+    options.layout.border.off = false;
+    options.layout.border.width = 5.;
+
+    let layout = check_ops_with_options(options, ops);
+    for (_, win) in layout.windows() {
+        assert_eq!(win.requested_size().unwrap().w, 400);
+    }
+}
+
+#[test]
+fn tiled_windows_have_ipc_positions() {
+    let ops = [
+        Op::AddOutput(1),
+        Op::AddWindow {
+            params: TestWindowParams::new(0),
+        },
+        Op::AddWindow {
+            params: TestWindowParams::new(1),
+        },
+        Op::Communicate(0),
+        Op::Communicate(1),
+        Op::FocusColumnLeft,
+    ];
+
+    let layout = check_ops(ops);
+    let mut positions = Vec::new();
+    layout.with_windows(|win, _, _, layout| {
+        positions.push((*win.id(), layout.tile_pos_in_workspace_view.unwrap()));
+    });
+    positions.sort_by_key(|(id, _)| *id);
+
+    let [(_, (x0, y0)), (_, (x1, y1))] = positions[..] else {
+        panic!("expected two windows, got {positions:?}");
+    };
+    assert_eq!(y0, y1);
+    assert!(x0 < x1);
+}
+
 #[test]
 fn move_column_to_workspace_unfocused_with_multiple_monitors() {
     let ops = [
@@ -3824,8 +4446,9 @@ prop_compose! {
     }
 }
 
-proptest! {
-    #![proptest_config(ProptestConfig {
+/// Proptest config that only runs the cases with `RUN_SLOW_TESTS` set.
+fn slow_proptest_config() -> ProptestConfig {
+    ProptestConfig {
         cases: if std::env::var_os("RUN_SLOW_TESTS").is_none() {
             eprintln!("ignoring slow test");
             0
@@ -3833,7 +4456,11 @@ proptest! {
             ProptestConfig::default().cases
         },
         ..ProptestConfig::default()
-    })]
+    }
+}
+
+proptest! {
+    #![proptest_config(slow_proptest_config())]
 
     #[test]
     fn random_operations_dont_panic(
@@ -1,5 +1,5 @@
-
 use insta::assert_snapshot;
+use proptest::prelude::*;
 
 use super::*;
 
@@ -479,3 +479,66 @@ fn unfullscreen_preserves_view_pos() {
     // View pos is back to showing the first window.
     assert_snapshot!(layout.active_workspace().unwrap().scrolling().view_pos(), @"-16");
 }
+
+/// Op focusing on what tends to break fullscreen: output hotplug, fullscreen toggles, column
+/// changes and interactive moves, interleaved with the clients catching up.
+fn fullscreen_heavy_op() -> impl Strategy<Value = Op> {
+    let relevant = any::<Op>().prop_filter("not a fullscreen-related op", |op| {
+        matches!(
+            op,
+            Op::AddOutput(_)
+                | Op::AddScaledOutput { .. }
+                | Op::RemoveOutput(_)
+                | Op::FocusOutput(_)
+                | Op::AddWindow { .. }
+                | Op::CloseWindow(_)
+                | Op::FullscreenWindow(_)
+                | Op::SetFullscreenWindow { .. }
+                | Op::ToggleWindowedFullscreen(_)
+                | Op::MaximizeColumn
+                | Op::MaximizeWindowToEdges { .. }
+                | Op::ConsumeOrExpelWindowLeft { .. }
+                | Op::ConsumeOrExpelWindowRight { .. }
+                | Op::ExpelWindowFromColumn
+                | Op::FocusColumnLeft
+                | Op::FocusColumnRight
+                | Op::MoveWindowToOutput { .. }
+                | Op::MoveColumnToOutput { .. }
+                | Op::MoveWorkspaceToOutput(_)
+                | Op::ToggleWindowFloating { .. }
+                | Op::SetColumnWidth(_)
+                | Op::ViewOffsetGestureBegin { .. }
+                | Op::ViewOffsetGestureUpdate { .. }
+                | Op::ViewOffsetGestureEnd { .. }
+                | Op::InteractiveMoveBegin { .. }
+                | Op::InteractiveMoveUpdate { .. }
+                | Op::InteractiveMoveEnd { .. }
+                | Op::Communicate(_)
+                | Op::AdvanceAnimations { .. }
+                | Op::CompleteAnimations
+        )
+    });
+
+    prop_oneof![
+        4 => relevant,
+        1 => any::<Op>(),
+    ]
+}
+
+proptest! {
+    #![proptest_config(slow_proptest_config())]
+
+    // Checks the layout invariants after every op. On failure, proptest shrinks the sequence down
+    // to a minimal one, ready to be pasted into a regression test above.
+    #[test]
+    fn random_fullscreen_operations_keep_invariants(
+        ops in prop::collection::vec(fullscreen_heavy_op(), 1..100),
+    ) {
+        let mut layout = check_ops(ops);
+
+        // Let everything settle and check again, since some state is only reconciled once the
+        // windows respond and the animations finish.
+        let settle = (1..=5).map(Op::Communicate).chain([Op::CompleteAnimations]);
+        check_ops_on_layout(&mut layout, settle);
+    }
+}
@@ -1,6 +1,7 @@
 use core::f64;
 use std::rc::Rc;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
 
 use niri_config::utils::MergeWith as _;
 use niri_config::{Color, CornerRadius, GradientInterpolation};
@@ -39,6 +40,7 @@ use crate::utils::transaction::Transaction;
 use crate::utils::{
     baba_is_float_offset, round_logical_in_physical, round_logical_in_physical_max1,
 };
+use crate::window::ResolvedWindowRules;
 
 #[derive(Debug)]
 struct WindowSizeOverride {
@@ -488,10 +490,7 @@ impl<W: LayoutElement> Tile<W> {
         let sizing_mode = window.sizing_mode();
         let tab_indicator_config = options.layout.tab_indicator;
 
-        // Blur needs to be enabled explicitly
-        let mut blur_config = options.layout.blur;
-        blur_config.on = false;
-        blur_config.merge_with(&rules.blur);
+        let blur_config = window_blur_config(&options, rules);
 
         Self {
             window: WindowInner::Single(Some(window)),
@@ -563,11 +562,8 @@ impl<W: LayoutElement> Tile<W> {
         self.tab_indicator
             .update_config(self.options.layout.tab_indicator);
 
-        // Blur needs to be enabled explicitly
-        let mut blur_config = self.options.layout.blur;
-        blur_config.on = false;
-        blur_config.merge_with(&rules.blur);
-        self.blur.update_config(blur_config);
+        self.blur
+            .update_config(window_blur_config(&self.options, rules));
     }
 
     pub fn update_shaders(&mut self) {
@@ -718,6 +714,9 @@ impl<W: LayoutElement> Tile<W> {
         let shadow_config = self.options.layout.shadow.merged_with(&rules.shadow);
         self.shadow.update_config(shadow_config);
 
+        self.blur
+            .update_config(window_blur_config(&self.options, &rules));
+
         let window_size = self.window_size();
         let radius = rules
             .geometry_corner_radius
@@ -861,6 +860,7 @@ impl<W: LayoutElement> Tile<W> {
                     view_rect,
                     is_active,
                     self.scale,
+                    self.clock.now_unadjusted(),
                 );
             }
             WindowInner::Multiple { windows, focus_idx } => {
@@ -887,6 +887,7 @@ impl<W: LayoutElement> Tile<W> {
                     view_rect,
                     is_active,
                     self.scale,
+                    self.clock.now_unadjusted(),
                 );
             }
         }
@@ -1605,6 +1606,8 @@ impl<W: LayoutElement> Tile<W> {
         location: Point<f64, Logical>,
         real_location: Point<f64, Logical>,
         focus_ring: bool,
+        border: bool,
+        shadow: bool,
         target: RenderTarget,
         push: &mut dyn FnMut(TileRenderElement<R>),
         force_optimized_blur_global: bool,
@@ -1865,7 +1868,7 @@ impl<W: LayoutElement> Tile<W> {
             }
         }
 
-        if let Some(width) = self.visual_border_width() {
+        if let Some(width) = self.visual_border_width().filter(|_| border) {
             self.border.render(
                 renderer,
                 location + Point::from((width, width)),
@@ -1889,7 +1892,7 @@ impl<W: LayoutElement> Tile<W> {
             if let Some(elem) = self.blur.render(
                 renderer.as_gles_renderer(),
                 fx_buffers.clone(),
-                blur_sample_area.to_i32_round(),
+                blur_sample_area,
                 radius,
                 self.scale,
                 animated_geo,
@@ -1903,7 +1906,7 @@ impl<W: LayoutElement> Tile<W> {
             }
         }
 
-        if expanded_progress < 1. {
+        if shadow && expanded_progress < 1. {
             self.shadow
                 .render(renderer, location, &mut |elem| push(elem.into()));
         }
@@ -1940,6 +1943,8 @@ impl<W: LayoutElement> Tile<W> {
                 Point::from((0., 0.)),
                 location,
                 focus_ring,
+                true,
+                true,
                 target,
                 &mut |elem| elements.push(elem),
                 force_optimized_blur_global,
@@ -1971,6 +1976,8 @@ impl<W: LayoutElement> Tile<W> {
                 Point::from((0., 0.)),
                 location,
                 focus_ring,
+                true,
+                true,
                 target,
                 &mut |elem| elements.push(elem),
                 force_optimized_blur_global,
@@ -1998,6 +2005,8 @@ impl<W: LayoutElement> Tile<W> {
                 location,
                 location,
                 focus_ring,
+                true,
+                true,
                 target,
                 push,
                 force_optimized_blur_global,
@@ -2014,6 +2023,34 @@ impl<W: LayoutElement> Tile<W> {
         self.unmap_snapshot = Some(self.render_snapshot(renderer));
     }
 
+    /// Renders the tile for a window screenshot, optionally with the border and the shadow.
+    ///
+    /// Unlike [`Tile::render()`], this skips the focus ring, blur and the open animation.
+    pub fn render_for_screenshot(
+        &self,
+        renderer: &mut GlesRenderer,
+        include_border: bool,
+        include_shadow: bool,
+        push: &mut dyn FnMut(TileRenderElement<GlesRenderer>),
+    ) {
+        let _span = tracy_client::span!("Tile::render_for_screenshot");
+
+        let location = Point::from((0., 0.));
+        self.render_inner(
+            renderer,
+            location,
+            location,
+            false,
+            include_border,
+            include_shadow,
+            RenderTarget::ScreenCapture,
+            push,
+            false,
+            None,
+            OverviewZoom::default(),
+        );
+    }
+
     fn render_snapshot(&self, renderer: &mut GlesRenderer) -> TileRenderSnapshot {
         let _span = tracy_client::span!("Tile::render_snapshot");
 
@@ -2064,6 +2101,26 @@ impl<W: LayoutElement> Tile<W> {
         &self.focus_ring
     }
 
+    pub fn shadow(&self) -> &Shadow {
+        &self.shadow
+    }
+
+    pub fn blur_config(&self) -> niri_config::Blur {
+        self.blur.config()
+    }
+
+    /// Returns when the earliest debounced tab title change is due to be applied.
+    pub fn pending_tab_title_deadline(&self) -> Option<Duration> {
+        self.tab_indicator.pending_title_deadline()
+    }
+
+    /// Releases cached title and blur textures, returning the number of textures released.
+    ///
+    /// They are re-rendered on the next use.
+    pub fn release_caches(&mut self) -> usize {
+        self.tab_indicator.release_title_textures() + usize::from(self.blur.release_cache())
+    }
+
     pub fn options(&self) -> &Rc<Options> {
         &self.options
     }
@@ -2086,3 +2143,13 @@ impl<W: LayoutElement> Tile<W> {
         assert_abs_diff_eq!(size.h, rounded.h, epsilon = 1e-5);
     }
 }
+
+/// Computes the blur config for a window.
+///
+/// Blur needs to be enabled explicitly with a window rule.
+fn window_blur_config(options: &Options, rules: &ResolvedWindowRules) -> niri_config::Blur {
+    let mut config = options.layout.blur;
+    config.on = false;
+    config.merge_with(&rules.blur);
+    config
+}
@@ -1,10 +1,10 @@
-use std::cmp::max;
+use std::cmp::{max, min};
 use std::rc::Rc;
 use std::time::Duration;
 
 use niri_config::utils::MergeWith as _;
 use niri_config::{
-    CenterFocusedColumn, CornerRadius, OutputName, PresetSize, WindowMoveDirection,
+    CenterFocusedColumn, Color, CornerRadius, OutputName, PresetSize, WindowMoveDirection,
     Workspace as WorkspaceConfig,
 };
 use niri_ipc::{PositionChange, SizeChange, WindowLayout};
@@ -20,13 +20,13 @@ use smithay::wayland::shell::xdg::SurfaceCachedState;
 
 use super::floating::{FloatingSpace, FloatingSpaceRenderElement};
 use super::scrolling::{
-    Column, ColumnWidth, ScrollDirection, ScrollingSpace, ScrollingSpaceRenderElement,
+    Column, ColumnWidth, SavedView, ScrollDirection, ScrollingSpace, ScrollingSpaceRenderElement,
 };
 use super::shadow::Shadow;
 use super::tile::{Tile, TileRenderSnapshot};
 use super::{
     ActivateWindow, HitType, InsertPosition, InteractiveResizeData, LayoutElement, Options,
-    RemovedTile, SizeFrac,
+    RemovedTile, SizeFrac, OUTPUT_SETTLE_DELAY,
 };
 use crate::animation::Clock;
 use crate::niri_render_elements;
@@ -60,9 +60,23 @@ pub struct Workspace<W: LayoutElement> {
     /// disconnection, it may remain pointing to the disconnected output.
     pub(super) original_output: OutputId,
 
+    /// View position on the original output, saved when the workspace leaves it.
+    ///
+    /// This is restored when the workspace comes back to that output, for example when a
+    /// disconnected monitor is reconnected.
+    original_output_view: Option<(OutputId, SavedView)>,
+
     /// Current output of this workspace.
     output: Option<Output>,
 
+    /// Output that the windows of this workspace have entered.
+    ///
+    /// Lags behind `output` when the workspace moves to another output, until it settles there.
+    entered_output: Option<Output>,
+
+    /// Time when `output` last changed.
+    output_changed_at: Duration,
+
     /// Latest known output scale for this workspace.
     ///
     /// This should be set from the current workspace output, or, if all outputs have been
@@ -107,9 +121,20 @@ pub struct Workspace<W: LayoutElement> {
     /// Optional name of this workspace.
     pub(super) name: Option<String>,
 
+    /// Whether to keep this workspace when it's empty, like a named workspace.
+    ///
+    /// Set while windows parked away from this workspace can come back to it.
+    pub(super) keep_when_empty: bool,
+
     /// Layout config overrides for this workspace.
     layout_config: Option<niri_config::LayoutPart>,
 
+    /// Icon of this workspace from the config, for bars and pagers.
+    icon: Option<String>,
+
+    /// Color of this workspace from the config, for bars and pagers.
+    color: Option<Color>,
+
     /// Unique ID of this workspace.
     id: WorkspaceId,
 }
@@ -189,6 +214,11 @@ pub enum WorkspaceAddWindowTarget<'a, W: LayoutElement> {
     Auto,
     /// As a new column at this index.
     NewColumnAt(usize),
+    /// At this column and tile index in the scrolling layout.
+    ///
+    /// Tile index 0 opens a new column at the column index. Otherwise, the window goes into the
+    /// existing column, or into a new last column if there are not enough columns.
+    Position { column_idx: usize, tile_idx: usize },
     /// Next to this existing window.
     NextTo(&'a W::Id),
 }
@@ -224,6 +254,8 @@ impl<W: LayoutElement> Workspace<W> {
             .unwrap_or(OutputId::new(&output));
 
         let layout_config = config.as_mut().and_then(|c| c.layout.take().map(|x| x.0));
+        let icon = config.as_mut().and_then(|c| c.icon.take());
+        let color = config.as_ref().and_then(|c| c.color);
 
         let scale = output.current_scale();
         let options = Rc::new(
@@ -259,18 +291,24 @@ impl<W: LayoutElement> Workspace<W> {
             floating,
             floating_is_active: FloatingActive::No,
             original_output,
+            original_output_view: None,
             scale,
             transform: output.current_transform(),
             view_size,
             working_area,
             shadow: Shadow::new(shadow_config),
             background_buffer: SolidColorBuffer::new(view_size, options.layout.background_color),
+            entered_output: Some(output.clone()),
+            output_changed_at: clock.now_unadjusted(),
             output: Some(output),
             clock,
             base_options,
             options,
             name: config.map(|c| c.name.0),
+            keep_when_empty: false,
             layout_config,
+            icon,
+            color,
             id: WorkspaceId::next(),
         }
     }
@@ -288,6 +326,8 @@ impl<W: LayoutElement> Workspace<W> {
         );
 
         let layout_config = config.as_mut().and_then(|c| c.layout.take().map(|x| x.0));
+        let icon = config.as_mut().and_then(|c| c.icon.take());
+        let color = config.as_ref().and_then(|c| c.color);
 
         let scale = smithay::output::Scale::Integer(1);
         let options = Rc::new(
@@ -323,9 +363,12 @@ impl<W: LayoutElement> Workspace<W> {
             floating,
             floating_is_active: FloatingActive::No,
             output: None,
+            entered_output: None,
+            output_changed_at: clock.now_unadjusted(),
             scale,
             transform: Transform::Normal,
             original_output,
+            original_output_view: None,
             view_size,
             working_area,
             shadow: Shadow::new(shadow_config),
@@ -334,7 +377,10 @@ impl<W: LayoutElement> Workspace<W> {
             base_options,
             options,
             name: config.map(|c| c.name.0),
+            keep_when_empty: false,
             layout_config,
+            icon,
+            color,
             id: WorkspaceId::next(),
         }
     }
@@ -353,10 +399,25 @@ impl<W: LayoutElement> Workspace<W> {
 
     pub fn unname(&mut self) {
         self.name = None;
+        self.icon = None;
+        self.color = None;
+    }
+
+    pub fn icon(&self) -> Option<&String> {
+        self.icon.as_ref()
+    }
+
+    pub fn color(&self) -> Option<Color> {
+        self.color
+    }
+
+    pub fn update_metadata(&mut self, icon: Option<String>, color: Option<Color>) {
+        self.icon = icon;
+        self.color = color;
     }
 
     pub fn has_windows_or_name(&self) -> bool {
-        self.has_windows() || self.name.is_some()
+        self.has_windows() || self.name.is_some() || self.keep_when_empty
     }
 
     pub fn scale(&self) -> smithay::output::Scale {
@@ -366,10 +427,18 @@ impl<W: LayoutElement> Workspace<W> {
     pub fn advance_animations(&mut self) {
         self.scrolling.advance_animations();
         self.floating.advance_animations();
+
+        let now = self.clock.now_unadjusted();
+        if now.saturating_sub(self.output_changed_at) >= OUTPUT_SETTLE_DELAY {
+            self.settle_output();
+        }
     }
 
     pub fn are_animations_ongoing(&self) -> bool {
-        self.scrolling.are_animations_ongoing() || self.floating.are_animations_ongoing()
+        self.scrolling.are_animations_ongoing()
+            || self.floating.are_animations_ongoing()
+            // Keep going until the windows enter the new output.
+            || self.entered_output != self.output
     }
 
     pub fn are_transitions_ongoing(&self) -> bool {
@@ -455,6 +524,11 @@ impl<W: LayoutElement> Workspace<W> {
         scrolling.chain(floating)
     }
 
+    /// Returns the scrolling layout column containing the given window.
+    pub fn column_for_window(&self, window: &W::Id) -> Option<&Column<W>> {
+        self.scrolling.column_for_window(window)
+    }
+
     pub fn tiles_mut(&mut self) -> impl Iterator<Item = &mut Tile<W>> + '_ {
         let scrolling = self.scrolling.tiles_mut();
         let floating = self.floating.tiles_mut();
@@ -505,12 +579,16 @@ impl<W: LayoutElement> Workspace<W> {
         }
 
         if let Some(output) = self.output.take() {
-            for win in self.windows() {
-                win.output_leave(&output);
+            if self.original_output.matches(&output) {
+                self.original_output_view = self
+                    .scrolling
+                    .save_view()
+                    .map(|view| (OutputId::new(&output), view));
             }
         }
 
         self.output = output;
+        self.output_changed_at = self.clock.now_unadjusted();
 
         if let Some(output) = &self.output {
             // Normalize original output: possibly replace connector with make/model/serial.
@@ -520,15 +598,49 @@ impl<W: LayoutElement> Workspace<W> {
 
             self.update_output_size();
 
+            // Coming back to the original output: restore the view position from before.
+            if self.original_output.matches(output)
+                && self
+                    .original_output_view
+                    .as_ref()
+                    .is_some_and(|(id, _)| id.matches(output))
+            {
+                let (_, view) = self.original_output_view.take().unwrap();
+                self.scrolling.restore_view(view);
+            }
+        }
+
+        // Windows enter the new output once the workspace settles there, so that moving it across
+        // several outputs in a row doesn't make clients rescale for every one of them. There's
+        // nothing to wait for when the workspace gets its first output or loses its output.
+        if self.entered_output.is_none() || self.output.is_none() {
+            self.settle_output();
+        }
+    }
+
+    /// Makes the windows enter the current output, if they haven't already.
+    fn settle_output(&mut self) {
+        if self.entered_output == self.output {
+            return;
+        }
+
+        if let Some(output) = self.entered_output.take() {
             for win in self.windows() {
-                self.enter_output_for_window(win);
+                win.output_leave(&output);
             }
         }
+
+        self.entered_output = self.output.clone();
+
+        for win in self.windows() {
+            self.enter_output_for_window(win);
+        }
     }
 
     fn enter_output_for_window(&self, window: &W) {
-        if let Some(output) = &self.output {
-            window.set_preferred_scale_transform(self.scale, self.transform);
+        if let Some(output) = &self.entered_output {
+            window
+                .set_preferred_scale_transform(output.current_scale(), output.current_transform());
             window.output_enter(output);
         }
     }
@@ -588,7 +700,8 @@ impl<W: LayoutElement> Workspace<W> {
 
         self.background_buffer.resize(size);
 
-        if scale_transform_changed {
+        // While moving to another output, the windows get the new scale once they enter it.
+        if scale_transform_changed && self.entered_output == self.output {
             for window in self.windows() {
                 window.set_preferred_scale_transform(self.scale, self.transform);
             }
@@ -654,6 +767,45 @@ impl<W: LayoutElement> Workspace<W> {
                     self.floating_is_active = FloatingActive::No;
                 }
             }
+            WorkspaceAddWindowTarget::Position {
+                column_idx,
+                tile_idx,
+            } => {
+                let activate = activate.map_smart(|| false);
+                let column_count = self.scrolling.columns().count();
+
+                let column_len = self
+                    .scrolling
+                    .columns()
+                    .nth(column_idx)
+                    .map(|col| col.tiles().count());
+                match column_len {
+                    Some(len) if tile_idx > 0 => {
+                        let tile_idx = min(tile_idx, len);
+                        self.scrolling.add_tile_to_column(
+                            column_idx,
+                            Some(tile_idx),
+                            tile,
+                            activate,
+                        );
+                    }
+                    _ => {
+                        let col_idx = min(column_idx, column_count);
+                        self.scrolling.add_tile(
+                            Some(col_idx),
+                            tile,
+                            activate,
+                            width,
+                            is_full_width,
+                            None,
+                        );
+                    }
+                }
+
+                if activate {
+                    self.floating_is_active = FloatingActive::No;
+                }
+            }
             WorkspaceAddWindowTarget::NextTo(next_to) => {
                 let activate = activate.map_smart(|| {
                     self.active_window()
@@ -726,12 +878,39 @@ impl<W: LayoutElement> Workspace<W> {
         }
     }
 
-    pub fn add_column(&mut self, column: Column<W>, activate: bool) {
+    /// Replaces the scrolling column of the given window with another column, putting the window
+    /// into it at `tile_idx`.
+    ///
+    /// The window must be alone in its column.
+    pub fn replace_column_of(&mut self, window: &W::Id, column: Column<W>, tile_idx: usize) {
+        let col_idx = self
+            .scrolling
+            .columns()
+            .position(|col| col.contains(window))
+            .unwrap();
+        let tile_idx = min(tile_idx, column.tiles().count());
+
+        self.add_column(Some(col_idx), column, false);
+
+        let removed = self.remove_tile(window, Transaction::new());
+        self.add_tile_to_column(col_idx, Some(tile_idx), removed.tile, true);
+    }
+
+    /// Returns the windows of the scrolling column containing the given window, top to bottom.
+    pub fn column_window_ids(&self, window: &W::Id) -> Option<Vec<W::Id>> {
+        let col = self.scrolling.columns().find(|col| col.contains(window))?;
+        let ids = col
+            .tiles()
+            .map(|(tile, _)| tile.focused_window().id().clone());
+        Some(ids.collect())
+    }
+
+    pub fn add_column(&mut self, col_idx: Option<usize>, column: Column<W>, activate: bool) {
         for (tile, _) in column.tiles() {
             self.enter_output_for_window(tile.focused_window());
         }
 
-        self.scrolling.add_column(None, column, activate, None);
+        self.scrolling.add_column(col_idx, column, activate, None);
 
         if activate {
             self.floating_is_active = FloatingActive::No;
@@ -780,7 +959,7 @@ impl<W: LayoutElement> Workspace<W> {
                 return None;
             };
 
-        if let Some(output) = &self.output {
+        if let Some(output) = &self.entered_output {
             if let Some(w) = tile.windows().find(|w| w.id() == id) {
                 w.output_leave(output)
             }
@@ -819,7 +998,7 @@ impl<W: LayoutElement> Workspace<W> {
             self.scrolling.remove_tile(id, transaction)
         };
 
-        if let Some(output) = &self.output {
+        if let Some(output) = &self.entered_output {
             removed.tile.windows().for_each(|w| w.output_leave(output));
         }
 
@@ -836,7 +1015,7 @@ impl<W: LayoutElement> Workspace<W> {
             self.scrolling.remove_active_tile(transaction)?
         };
 
-        if let Some(output) = &self.output {
+        if let Some(output) = &self.entered_output {
             removed.tile.windows().for_each(|w| w.output_leave(output));
         }
 
@@ -845,6 +1024,25 @@ impl<W: LayoutElement> Workspace<W> {
         Some(removed)
     }
 
+    /// Removes the scrolling column containing the given window.
+    pub fn remove_column_of(&mut self, window: &W::Id) -> Option<Column<W>> {
+        let col_idx = self
+            .scrolling
+            .columns()
+            .position(|col| col.contains(window))?;
+        let column = self.scrolling.remove_column_by_idx(col_idx, None);
+
+        if let Some(output) = &self.entered_output {
+            for (tile, _) in column.tiles() {
+                tile.windows().for_each(|w| w.output_leave(output));
+            }
+        }
+
+        self.update_focus_floating_tiling_after_removing(false);
+
+        Some(column)
+    }
+
     pub fn remove_active_column(&mut self) -> Option<Column<W>> {
         let from_floating = self.floating_is_active.get();
         if from_floating {
@@ -853,7 +1051,7 @@ impl<W: LayoutElement> Workspace<W> {
 
         let column = self.scrolling.remove_active_column()?;
 
-        if let Some(output) = &self.output {
+        if let Some(output) = &self.entered_output {
             for (tile, _) in column.tiles() {
                 tile.windows().for_each(|w| w.output_leave(output));
             }
@@ -925,7 +1123,7 @@ impl<W: LayoutElement> Workspace<W> {
             size.h = max(size.h, min_size.h);
         }
 
-        size
+        rules.apply_aspect_ratio(size)
     }
 
     pub fn configure_new_window(
@@ -1259,6 +1457,11 @@ impl<W: LayoutElement> Workspace<W> {
         self.scrolling.swap_window_in_direction(direction);
     }
 
+    /// Swaps two tiled windows, see [`ScrollingSpace::swap_windows`].
+    pub fn swap_windows(&mut self, window: &W::Id, other: &W::Id) -> bool {
+        self.scrolling.swap_windows(window, other)
+    }
+
     pub fn center_column(&mut self) {
         if self.floating_is_active.get() {
             self.floating.center_window(None);
@@ -1365,6 +1568,13 @@ impl<W: LayoutElement> Workspace<W> {
         self.scrolling.expand_column_to_available_width();
     }
 
+    pub fn fit_column_width_to_content(&mut self) {
+        if self.floating_is_active.get() {
+            return;
+        }
+        self.scrolling.fit_column_width_to_content();
+    }
+
     pub fn set_fullscreen(&mut self, window: &W::Id, is_fullscreen: bool) {
         let mut restore_to_floating = false;
         if self.floating.has_window(window) {
@@ -1918,10 +2128,88 @@ impl<W: LayoutElement> Workspace<W> {
             })
     }
 
+    /// Returns the window whose border the position is within `width` of, and the edges to resize.
+    ///
+    /// Positions within the window input region don't count, so that the window keeps receiving
+    /// clicks near its edges.
+    pub fn border_resize_edges_under(
+        &self,
+        pos: Point<f64, Logical>,
+        width: f64,
+    ) -> Option<(&W, ResizeEdge)> {
+        for (tile, tile_pos, visible) in self.tiles_with_render_positions() {
+            if !visible {
+                continue;
+            }
+
+            let pos_within_tile = pos - tile_pos;
+
+            // Tiles on top take the position even when it isn't on their border.
+            match tile.hit(pos_within_tile) {
+                None => (),
+                Some((_, HitType::Activate { is_tab_indicator })) if !is_tab_indicator => (),
+                Some(_) => return None,
+            }
+
+            let size = tile.tile_size().to_f64();
+            let mut area = Rectangle::from_size(size);
+            area.loc -= Point::from((width, width));
+            area.size += Size::from((width * 2., width * 2.));
+            if !area.contains(pos_within_tile) {
+                continue;
+            }
+
+            let mut edges = ResizeEdge::empty();
+            if pos_within_tile.x < width {
+                edges |= ResizeEdge::LEFT;
+            } else if size.w - width <= pos_within_tile.x {
+                edges |= ResizeEdge::RIGHT;
+            }
+            if pos_within_tile.y < width {
+                edges |= ResizeEdge::TOP;
+            } else if size.h - width <= pos_within_tile.y {
+                edges |= ResizeEdge::BOTTOM;
+            }
+
+            if !edges.is_empty() {
+                return Some((tile.focused_window(), edges));
+            }
+        }
+
+        None
+    }
+
     pub fn descendants_added(&mut self, id: &W::Id) -> bool {
         self.floating.descendants_added(id)
     }
 
+    /// Stacks a floating window above its parent and centers it on the parent.
+    ///
+    /// Returns `false` if either window isn't on this workspace or the window isn't floating.
+    pub fn center_window_on_parent(&mut self, id: &W::Id, parent: &W::Id) -> bool {
+        if !self.floating.has_window(id) {
+            return false;
+        }
+
+        let find = |(tile, pos): (&Tile<W>, Point<f64, Logical>)| {
+            tile.has_window(parent)
+                .then(|| Rectangle::new(pos, tile.tile_size()))
+        };
+        let parent_area = if self.floating.has_window(parent) {
+            self.floating.descendants_added(parent);
+            self.floating.tiles_with_offsets().find_map(find)
+        } else {
+            // FIXME: use static pos
+            self.scrolling.tiles_with_render_positions().find_map(find)
+        };
+        let Some(parent_area) = parent_area else {
+            return false;
+        };
+
+        self.floating.center_window_on(id, parent_area);
+        true
+    }
+
     pub fn update_window(&mut self, window: &W::Id, serial: Option<Serial>) {
         if !self.floating.update_window(window, serial) {
             self.scrolling.update_window(window, serial);
@@ -2063,6 +2351,20 @@ impl<W: LayoutElement> Workspace<W> {
         }
     }
 
+    pub fn interactive_resize_scale_width(
+        &mut self,
+        window: &W::Id,
+        scale: f64,
+        snap_distance: f64,
+    ) -> bool {
+        if self.floating.has_window(window) {
+            false
+        } else {
+            self.scrolling
+                .interactive_resize_scale_width(window, scale, snap_distance)
+        }
+    }
+
     pub fn interactive_resize_end(&mut self, window: Option<&W::Id>) {
         if let Some(window) = window {
             if self.floating.has_window(window) {
@@ -6,10 +6,12 @@ pub mod a11y;
 pub mod animation;
 pub mod backend;
 pub mod cli;
+pub mod clipboard_history;
 pub mod cursor;
 #[cfg(feature = "dbus")]
 pub mod dbus;
 pub mod frame_clock;
+pub mod frame_stats;
 pub mod handlers;
 pub mod input;
 pub mod ipc;
@@ -21,6 +23,7 @@ pub mod render_helpers;
 pub mod rubber_band;
 #[cfg(feature = "xdp-gnome-screencast")]
 pub mod screencasting;
+pub mod session;
 pub mod ui;
 pub mod utils;
 pub mod window;
@@ -19,11 +19,12 @@ use niri::cli::{Cli, CompletionShell, Sub};
 use niri::dbus;
 use niri::ipc::client::handle_msg;
 use niri::niri::State;
+use niri::session::SavedSession;
 use niri::utils::spawning::{
     spawn, spawn_sh, store_and_increase_nofile_rlimit, CHILD_DISPLAY, CHILD_ENV,
     REMOVE_ENV_RUST_BACKTRACE, REMOVE_ENV_RUST_LIB_BACKTRACE,
 };
-use niri::utils::{cause_panic, version, watcher, xwayland, IS_SYSTEMD_SERVICE};
+use niri::utils::{cause_panic, memory_pressure, version, watcher, xwayland, IS_SYSTEMD_SERVICE};
 use niri_config::{Config, ConfigPath};
 use niri_ipc::socket::SOCKET_PATH_ENV;
 use portable_atomic::Ordering;
@@ -241,6 +242,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     watcher::setup(&mut state, &config_path, config_includes);
+    memory_pressure::setup(&mut state);
 
     // Spawn commands from cli and auto-start.
     spawn(cli.command, None);
@@ -265,6 +267,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .run(None, &mut state, |state| state.refresh_and_flush_clients())
         .unwrap();
 
+    if state.niri.config.borrow().session.restore_layout {
+        if let Err(err) = SavedSession::capture(&state.niri.layout).save() {
+            warn!("error saving session: {err:?}");
+        }
+    }
+
     Ok(())
 }
 
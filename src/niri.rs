@@ -8,14 +8,17 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use std::{env, mem, thread};
+use std::{env, iter, mem, thread};
 
 use _server_decoration::server::org_kde_kwin_server_decoration_manager::Mode as KdeDecorationsMode;
 use anyhow::{bail, ensure, Context};
 use calloop::futures::Scheduler;
 use niri_config::debug::PreviewRender;
+use niri_config::gestures::HotCorner;
+use niri_config::utils::MergeWith as _;
 use niri_config::{
-    Config, FloatOrInt, Key, Modifiers, OutputName, TrackLayout, WarpMouseToFocusMode,
+    Action, Bind, BlurRule, Config, FloatOrInt, ForcePointerConstraint, Key,
+    KeyboardShortcutsInhibit, Modifiers, OutputName, TrackLayout, WarpMouseToFocusMode,
     WorkspaceReference, Xkb,
 };
 use smithay::backend::allocator::Fourcc;
@@ -31,7 +34,7 @@ use smithay::backend::renderer::element::{
     default_primary_scanout_output_compare, Element, Id, Kind, PrimaryScanoutOutput,
     RenderElementStates,
 };
-use smithay::backend::renderer::gles::GlesRenderer;
+use smithay::backend::renderer::gles::{GlesRenderer, GlesTexture};
 use smithay::backend::renderer::sync::SyncPoint;
 use smithay::backend::renderer::Color32F;
 use smithay::desktop::utils::{
@@ -61,7 +64,7 @@ use smithay::reexports::wayland_protocols::xdg::shell::server::xdg_toplevel::WmC
 use smithay::reexports::wayland_protocols_misc::server_decoration as _server_decoration;
 use smithay::reexports::wayland_protocols_wlr::screencopy::v1::server::zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1;
 use smithay::reexports::wayland_server::backend::{
-    ClientData, ClientId, DisconnectReason, GlobalId,
+    ClientData, ClientId, DisconnectReason, GlobalId, ObjectId,
 };
 use smithay::reexports::wayland_server::protocol::wl_shm;
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
@@ -70,12 +73,14 @@ use smithay::utils::{
     ClockSource, IsAlive as _, Logical, Monotonic, Physical, Point, Rectangle, Scale, Size,
     Transform, SERIAL_COUNTER,
 };
+use smithay::wayland::commit_timing::{CommitTimerBarrierStateUserData, CommitTimingManagerState};
 use smithay::wayland::compositor::{
-    with_states, with_surface_tree_downward, CompositorClientState, CompositorHandler,
+    get_parent, with_states, with_surface_tree_downward, CompositorClientState, CompositorHandler,
     CompositorState, HookId, SurfaceData, TraversalAction,
 };
 use smithay::wayland::cursor_shape::CursorShapeManagerState;
 use smithay::wayland::dmabuf::DmabufState;
+use smithay::wayland::fifo::{FifoBarrierCachedState, FifoManagerState};
 use smithay::wayland::fractional_scale::FractionalScaleManagerState;
 use smithay::wayland::idle_inhibit::IdleInhibitManagerState;
 use smithay::wayland::idle_notify::IdleNotifierState;
@@ -88,7 +93,7 @@ use smithay::wayland::pointer_constraints::{with_pointer_constraint, PointerCons
 use smithay::wayland::pointer_gestures::PointerGesturesState;
 use smithay::wayland::presentation::PresentationState;
 use smithay::wayland::relative_pointer::RelativePointerManagerState;
-use smithay::wayland::security_context::SecurityContextState;
+use smithay::wayland::security_context::{SecurityContext, SecurityContextState};
 use smithay::wayland::selection::data_device::{set_data_device_selection, DataDeviceState};
 use smithay::wayland::selection::ext_data_control::DataControlState as ExtDataControlState;
 use smithay::wayland::selection::primary_selection::PrimarySelectionState;
@@ -97,6 +102,7 @@ use smithay::wayland::session_lock::{LockSurface, SessionLockManagerState, Sessi
 use smithay::wayland::shell::kde::decoration::KdeDecorationState;
 use smithay::wayland::shell::wlr_layer::{self, Layer, WlrLayerShellState};
 use smithay::wayland::shell::xdg::decoration::XdgDecorationState;
+use smithay::wayland::shell::xdg::dialog::XdgDialogState;
 use smithay::wayland::shell::xdg::XdgShellState;
 use smithay::wayland::shm::ShmState;
 #[cfg(test)]
@@ -114,6 +120,7 @@ use crate::a11y::A11y;
 use crate::animation::Clock;
 use crate::backend::tty::SurfaceDmabufFeedback;
 use crate::backend::{Backend, Headless, RenderResult, Tty, Winit};
+use crate::clipboard_history::ClipboardHistory;
 use crate::cursor::{CursorManager, CursorTextureCache, RenderCursor, XCursor};
 #[cfg(feature = "dbus")]
 use crate::dbus::freedesktop_locale1::Locale1ToNiri;
@@ -124,51 +131,74 @@ use crate::dbus::gnome_shell_introspect::{self, IntrospectToNiri, NiriToIntrospe
 #[cfg(feature = "dbus")]
 use crate::dbus::gnome_shell_screenshot::{NiriToScreenshot, ScreenshotToNiri};
 use crate::frame_clock::FrameClock;
+use crate::frame_stats::FrameStats;
 use crate::handlers::{configure_lock_surface, XDG_ACTIVATION_TOKEN_TIMEOUT};
+use crate::input::modifier_taps::ModifierTaps;
 use crate::input::pick_color_grab::PickColorGrab;
 use crate::input::scroll_swipe_gesture::ScrollSwipeGesture;
 use crate::input::scroll_tracker::ScrollTracker;
+use crate::input::sticky_keys::StickyKeys;
+use crate::input::tablet_pad::TabletPadAxes;
 use crate::input::{
     apply_libinput_settings, mods_with_finger_scroll_binds, mods_with_mouse_binds,
     mods_with_wheel_binds, TabletData,
 };
 use crate::ipc::server::IpcServer;
 use crate::layer::mapped::LayerSurfaceRenderElement;
-use crate::layer::MappedLayer;
+use crate::layer::{ClosingLayer, MappedLayer};
 use crate::layout::tile::TileRenderElement;
 use crate::layout::workspace::{Workspace, WorkspaceId};
 use crate::layout::{
-    HitType, Layout, LayoutElement as _, LayoutElementRenderElement, MonitorRenderElement,
+    ActivateWindow, HitType, Layout, LayoutElement as _, LayoutElementRenderElement,
+    MonitorRenderElement,
 };
 use crate::niri_render_elements;
+use crate::protocols::color_management::{
+    self, surface_image_description, ColorManagementState, ImageDescription, Primaries,
+    TransferFunction,
+};
+use crate::protocols::color_representation::ColorRepresentationManagerState;
 use crate::protocols::ext_background_effect::ExtBackgroundEffectManagerState;
+use crate::protocols::ext_foreign_toplevel_list::{self, ExtForeignToplevelListState};
 use crate::protocols::ext_workspace::{self, ExtWorkspaceManagerState};
 use crate::protocols::foreign_toplevel::{self, ForeignToplevelManagerState};
 use crate::protocols::gamma_control::GammaControlManagerState;
+use crate::protocols::image_copy_capture::{self, FailureReason, ImageCopyCaptureState};
 use crate::protocols::kde_blur::OrgKdeKwinBlurManagerState;
 use crate::protocols::mutter_x11_interop::MutterX11InteropManagerState;
 use crate::protocols::output_management::OutputManagementManagerState;
+use crate::protocols::pointer_warp::PointerWarpManagerState;
 use crate::protocols::screencopy::{Screencopy, ScreencopyBuffer, ScreencopyManagerState};
+use crate::protocols::tearing_control::{self, TearingControlManagerState};
 use crate::protocols::virtual_pointer::VirtualPointerManagerState;
+use crate::protocols::xdg_toplevel_tag::XdgToplevelTagManagerState;
 use crate::render_helpers::blur::{EffectsFramebuffers, EffectsFramebuffersUserData};
 use crate::render_helpers::debug::draw_opaque_regions;
 use crate::render_helpers::primary_gpu_texture::PrimaryGpuTextureRenderElement;
 use crate::render_helpers::renderer::NiriRenderer;
+use crate::render_helpers::shaders::ProgramType;
 use crate::render_helpers::solid_color::{SolidColorBuffer, SolidColorRenderElement};
 use crate::render_helpers::surface::push_elements_from_surface_tree;
-use crate::render_helpers::texture::TextureBuffer;
+use crate::render_helpers::texture::{TextureBuffer, TextureRenderElement};
 use crate::render_helpers::{
     encompassing_geo, render_to_dmabuf, render_to_encompassing_texture, render_to_shm,
     render_to_texture, render_to_vec, shaders, RenderTarget,
 };
 #[cfg(feature = "xdp-gnome-screencast")]
 use crate::screencasting::Screencasting;
+use crate::session::SavedSession;
+use crate::ui::clipboard_picker::ClipboardPicker;
+use crate::ui::column_grid::{ColumnGrid, ColumnGridRenderElement};
 use crate::ui::config_error_notification::ConfigErrorNotification;
 use crate::ui::exit_confirm_dialog::{ExitConfirmDialog, ExitConfirmDialogRenderElement};
 use crate::ui::hotkey_overlay::HotkeyOverlay;
+use crate::ui::key_sequence_hint::KeySequenceHint;
 use crate::ui::mru::{MruCloseRequest, WindowMruUi, WindowMruUiRenderElement};
+use crate::ui::overlay_widgets::OverlayWidgets;
 use crate::ui::screen_transition::{self, ScreenTransition};
 use crate::ui::screenshot_ui::{OutputScreenshot, ScreenshotUi, ScreenshotUiRenderElement};
+use crate::ui::shortcuts_inhibit_indicator::{IndicatorState, ShortcutsInhibitIndicator};
+use crate::utils::protocol_stats::ClientStats;
 use crate::utils::scale::{closest_representable_scale, guess_monitor_scale};
 use crate::utils::spawning::{CHILD_DISPLAY, CHILD_ENV};
 use crate::utils::vblank_throttle::VBlankThrottle;
@@ -176,8 +206,8 @@ use crate::utils::watcher::Watcher;
 use crate::utils::xwayland::satellite::Satellite;
 use crate::utils::{
     center, center_f64, expand_home, get_monotonic_time, ipc_transform_to_smithay, is_mapped,
-    logical_output, make_screenshot_path, output_matches_name, output_size, panel_orientation,
-    send_scale_transform, write_png_rgba8, xwayland,
+    is_modal_dialog, local_time_of_day, logical_output, make_screenshot_path, output_matches_name,
+    output_size, panel_orientation, send_scale_transform, write_png_rgba8, xwayland, ResizeEdge,
 };
 use crate::window::mapped::MappedId;
 use crate::window::{InitialConfigureState, Mapped, ResolvedWindowRules, Unmapped, WindowRef};
@@ -189,6 +219,11 @@ const CLEAR_COLOR_LOCKED: [f32; 4] = [0.3, 0.1, 0.1, 1.];
 // should be ~1.995 seconds.
 const FRAME_CALLBACK_THROTTLE: Option<Duration> = Some(Duration::from_millis(995));
 
+/// Each IPC client can have a window thumbnail rendered at most once per this interval.
+///
+/// Repeated requests within the interval are served from the cache.
+const WINDOW_THUMBNAIL_INTERVAL: Duration = Duration::from_millis(250);
+
 pub struct Niri {
     pub config: Rc<RefCell<Config>>,
 
@@ -198,8 +233,22 @@ pub struct Niri {
     /// reloading the config from disk to determine if the output configuration should be reloaded
     /// (and transient changes dropped).
     pub config_file_output_config: niri_config::Outputs,
+    /// Name of the output profile applied on top of the output config from the config file.
+    pub active_output_profile: Option<String>,
+    /// Whether the active output profile changed since the last
+    /// [`State::refresh_output_profile()`].
+    pub output_profile_changed: bool,
+    /// Window rules added over IPC.
+    ///
+    /// These stay until niri exits, and are kept appended to the window rules from the config
+    /// file, so they take precedence over them.
+    pub ipc_window_rules: Vec<(niri_ipc::RuntimeWindowRule, niri_config::WindowRule)>,
+    /// Id for the next window rule added over IPC.
+    pub next_ipc_window_rule_id: u64,
 
     pub config_file_watcher: Option<Watcher>,
+    /// Timer reverting the config applied with `niri msg config try`, if one is active.
+    pub config_preview_timer: Option<RegistrationToken>,
 
     pub event_loop: LoopHandle<'static, State>,
     pub scheduler: Scheduler<()>,
@@ -218,6 +267,13 @@ pub struct Niri {
 
     /// Whether the at-startup=true window rules are active.
     pub is_at_startup: bool,
+    /// Whether the time conditions in window and layer rules currently match, in config order.
+    pub active_time_conditions: Vec<bool>,
+    /// Windows moved by inactive window parking, with their original workspaces.
+    pub parked_windows: Vec<(Window, WorkspaceId)>,
+    /// Recently rendered window thumbnails by IPC client id and window id, with their time and
+    /// max size.
+    pub window_thumbnails: HashMap<(u64, u64), (Instant, u32, niri_ipc::WindowThumbnail)>,
 
     /// Clock for driving animations.
     pub clock: Clock,
@@ -225,6 +281,14 @@ pub struct Niri {
     // Each workspace corresponds to a Space. Each workspace generally has one Output mapped to it,
     // however it may have none (when there are no outputs connected) or multiple (when mirroring).
     pub layout: Layout<Mapped>,
+    /// Layout saved on the previous exit, for restoring windows as they reopen.
+    pub saved_session: Option<SavedSession>,
+    /// Timer waking up the event loop to send window size configures that were batched.
+    pub configure_batch_timer: Option<RegistrationToken>,
+    /// Timer waking up the event loop to redraw debounced tab titles, with its time.
+    pub tab_title_timer: Option<(RegistrationToken, Duration)>,
+    /// Timer waking up the event loop to release the next wp-commit-timing commit, with its time.
+    pub commit_timer_wakeup: Option<(RegistrationToken, Duration)>,
 
     // This space does not actually contain any windows, but all outputs are mapped into it
     // according to their global position.
@@ -274,12 +338,15 @@ pub struct Niri {
     pub compositor_state: CompositorState,
     pub xdg_shell_state: XdgShellState,
     pub xdg_decoration_state: XdgDecorationState,
+    pub xdg_dialog_state: XdgDialogState,
     pub kde_decoration_state: KdeDecorationState,
     pub layer_shell_state: WlrLayerShellState,
     pub session_lock_state: SessionLockManagerState,
     pub foreign_toplevel_state: ForeignToplevelManagerState,
+    pub ext_foreign_toplevel_list_state: ExtForeignToplevelListState,
     pub ext_workspace_state: ExtWorkspaceManagerState,
     pub screencopy_state: ScreencopyManagerState,
+    pub image_copy_capture_state: ImageCopyCaptureState,
     pub output_management_state: OutputManagementManagerState,
     pub viewporter_state: ViewporterState,
     pub xdg_foreign_state: XdgForeignState,
@@ -303,15 +370,25 @@ pub struct Niri {
     pub primary_selection_state: PrimarySelectionState,
     pub wlr_data_control_state: WlrDataControlState,
     pub ext_data_control_state: ExtDataControlState,
+    pub clipboard_history: ClipboardHistory,
+    /// Sends selections read by the clipboard history threads back to the main thread.
+    pub clipboard_history_tx: calloop::channel::Sender<(String, Vec<u8>)>,
     pub popups: PopupManager,
     pub popup_grab: Option<PopupGrabState>,
     pub presentation_state: PresentationState,
+    pub fifo_manager_state: FifoManagerState,
+    pub commit_timing_manager_state: CommitTimingManagerState,
     pub security_context_state: SecurityContextState,
     pub gamma_control_manager_state: GammaControlManagerState,
     pub activation_state: XdgActivationState,
     pub mutter_x11_interop_state: MutterX11InteropManagerState,
     pub org_kde_kwin_blur_manager_state: OrgKdeKwinBlurManagerState,
     pub ext_background_effect_manager_state: ExtBackgroundEffectManagerState,
+    pub xdg_toplevel_tag_manager_state: XdgToplevelTagManagerState,
+    pub tearing_control_manager_state: TearingControlManagerState,
+    pub pointer_warp_manager_state: PointerWarpManagerState,
+    pub color_management_state: ColorManagementState,
+    pub color_representation_manager_state: ColorRepresentationManagerState,
 
     // This will not work as is outside of tests, so it is gated with #[cfg(test)] for now. In
     // particular, shaders will need to learn about the single pixel buffer. Also, it must be
@@ -325,16 +402,43 @@ pub struct Niri {
     pub seat: Seat<State>,
     /// Scancodes of the keys to suppress.
     pub suppressed_keys: HashSet<Keycode>,
+    /// Binds registered by IPC clients, delivered over the event stream.
+    pub ipc_key_grabs: Vec<Bind>,
+    /// Ids of the IPC clients owning the key grabs, released when the client disconnects.
+    pub ipc_key_grab_clients: HashMap<Key, u64>,
     /// Button codes of the mouse buttons to suppress.
     pub suppressed_buttons: HashSet<u32>,
+    /// Mouse buttons currently held down, to detect chords.
+    pub pressed_buttons: HashSet<u32>,
+    /// Button code of a press held back until it's clear whether it starts a chord, and the
+    /// timer that lets it through.
+    pub held_chord_press: Option<(u32, RegistrationToken)>,
     pub valid_release_trigger: Option<Keycode>,
     pub bind_cooldown_timers: HashMap<Key, RegistrationToken>,
     pub bind_repeat_timer: Option<RegistrationToken>,
+    pub modifier_taps: ModifierTaps,
+    /// Timer running a multi-tap bind once it's clear that no more taps follow.
+    pub modifier_tap_timer: Option<RegistrationToken>,
+    pub sticky_keys: StickyKeys,
+    pub tablet_pad_axes: TabletPadAxes,
+    /// Timers accepting the presses of keys held back by slow keys.
+    pub slow_keys_timers: HashMap<Keycode, RegistrationToken>,
     pub keyboard_focus: KeyboardFocus,
     pub layer_shell_on_demand_focus: Option<LayerSurface>,
+    /// On-screen keyboards taken out of their output's layer map while no text input needs them.
+    pub hidden_on_screen_keyboards: Vec<(Output, LayerSurface)>,
+    /// Whether the focused surface needed an on-screen keyboard at the last refresh.
+    pub on_screen_keyboard_wanted: bool,
     pub idle_inhibiting_surfaces: HashSet<WlSurface>,
     pub is_fdo_idle_inhibited: Arc<AtomicBool>,
     pub keyboard_shortcuts_inhibiting_surfaces: HashMap<WlSurface, KeyboardShortcutsInhibitor>,
+    /// Surfaces whose inhibitors were created before their window rules were resolved.
+    ///
+    /// These get activated or left alone according to the rules once they are known.
+    pub keyboard_shortcuts_inhibitors_pending: HashSet<WlSurface>,
+    /// Whether the focus, the inhibitors or the window rules changed since the inhibitors were
+    /// last checked against the rules.
+    pub keyboard_shortcuts_inhibit_dirty: bool,
 
     /// Most recent XKB settings from org.freedesktop.locale1.
     pub xkb_from_locale1: Option<Xkb>,
@@ -370,8 +474,25 @@ pub struct Niri {
     /// resolution mice.
     pub notified_activity_this_iteration: bool,
     pub pointer_inside_hot_corner: bool,
+    /// Timer running the hot corner action once the pointer stayed in the corner long enough.
+    pub hot_corner_timer: Option<RegistrationToken>,
+    /// Timer focusing the window under the pointer for a delayed focus-follows-mouse.
+    pub focus_follows_mouse_timer: Option<(Window, RegistrationToken)>,
+    /// Whether the forced pointer constraint of the focused window was released with a bind.
+    ///
+    /// Reset when the keyboard focus changes.
+    pub forced_pointer_constraint_released: bool,
+    /// Edges shown by the cursor while the pointer is on a window border for resize-on-border.
+    pub pointer_resize_border_edges: Option<ResizeEdge>,
     pub tablet_cursor_location: Option<Point<f64, Logical>>,
-    pub gesture_swipe_3f_cumulative: Option<(f64, f64)>,
+    /// Finger count and cumulative movement of a swipe that hasn't picked a direction yet.
+    pub gesture_swipe_pending: Option<(u8, f64, f64)>,
+    /// Finger count of a pinch that triggers a bind once it scales far enough.
+    pub gesture_pinch_pending: Option<u8>,
+    /// Whether the rest of the ongoing touchpad gesture is ignored, e.g. after it triggered a bind.
+    pub gesture_swallowed: bool,
+    /// Window being resized with a touchpad pinch gesture.
+    pub gesture_pinch_resize_window: Option<Window>,
     pub overview_scroll_swipe_gesture: ScrollSwipeGesture,
     pub vertical_wheel_tracker: ScrollTracker,
     pub horizontal_wheel_tracker: ScrollTracker,
@@ -389,16 +510,28 @@ pub struct Niri {
     pub screenshot_ui: ScreenshotUi,
     pub config_error_notification: ConfigErrorNotification,
     pub hotkey_overlay: HotkeyOverlay,
+    pub shortcuts_inhibit_indicator: ShortcutsInhibitIndicator,
+    /// Name of the bind mode whose binds replace the regular binds.
+    pub bind_mode: Option<String>,
+    pub key_sequence_hint: KeySequenceHint,
+    /// Timer cancelling the pending key sequence.
+    pub key_sequence_timer: Option<RegistrationToken>,
     pub exit_confirm_dialog: ExitConfirmDialog,
+    pub overlay_widgets: OverlayWidgets,
 
     pub window_mru_ui: WindowMruUi,
+    pub column_grid: ColumnGrid,
+    pub clipboard_picker: ClipboardPicker,
     pub pending_mru_commit: Option<PendingMruCommit>,
 
     pub pick_window: Option<async_channel::Sender<Option<MappedId>>>,
     pub pick_color: Option<async_channel::Sender<Option<niri_ipc::PickedColor>>>,
+    /// Where to reply once the screenshot is saved, for screenshot actions coming from IPC.
+    pub ipc_screenshot_reply: Option<async_channel::Sender<Result<String, String>>>,
 
     pub debug_draw_opaque_regions: bool,
     pub debug_draw_damage: bool,
+    pub debug_draw_premultiplied_alpha: bool,
 
     #[cfg(feature = "dbus")]
     pub dbus: Option<crate::dbus::DBusServers>,
@@ -480,11 +613,18 @@ pub struct OutputState {
     /// tracking issues and make screenshots easier.
     pub backdrop_buffer: SolidColorBuffer,
     pub lock_render_state: LockRenderState,
+    /// Whether this output was powered off on its own, apart from the other outputs.
+    pub powered_off: bool,
     pub lock_surface: Option<LockSurface>,
     pub lock_color_buffer: SolidColorBuffer,
+    /// Blurred snapshot of the output from right before locking, drawn behind the lock surface.
+    pub lock_backdrop: Option<TextureBuffer<GlesTexture>>,
     screen_transition: Option<ScreenTransition>,
+    /// Layer surfaces playing their close animation.
+    pub closing_layers: Vec<ClosingLayer>,
     /// Damage tracker used for the debug damage visualization.
     pub debug_damage_tracker: OutputDamageTracker,
+    pub frame_stats: FrameStats,
 }
 
 #[derive(Debug, Default)]
@@ -521,6 +661,8 @@ pub enum KeyboardFocus {
     ExitConfirmDialog,
     Overview,
     Mru,
+    ColumnGrid,
+    ClipboardPicker,
 }
 
 #[derive(Default, Clone, PartialEq)]
@@ -586,6 +728,19 @@ pub enum CastTarget {
     Nothing,
     Output(WeakOutput),
     Window { id: u64 },
+    // Scrolling layout column, showing whichever of its windows is active. The id is the window
+    // currently cast, updated as the active window changes.
+    Column { id: u64 },
+}
+
+impl CastTarget {
+    /// Returns the id of the window shown by this target, if any.
+    pub fn window_id(&self) -> Option<u64> {
+        match self {
+            CastTarget::Window { id } | CastTarget::Column { id } => Some(*id),
+            CastTarget::Nothing | CastTarget::Output(_) => None,
+        }
+    }
 }
 
 /// Pending update to a window's focus timestamp.
@@ -645,6 +800,8 @@ impl KeyboardFocus {
             KeyboardFocus::ExitConfirmDialog => None,
             KeyboardFocus::Overview => None,
             KeyboardFocus::Mru => None,
+            KeyboardFocus::ColumnGrid => None,
+            KeyboardFocus::ClipboardPicker => None,
         }
     }
 
@@ -657,6 +814,8 @@ impl KeyboardFocus {
             KeyboardFocus::ExitConfirmDialog => None,
             KeyboardFocus::Overview => None,
             KeyboardFocus::Mru => None,
+            KeyboardFocus::ColumnGrid => None,
+            KeyboardFocus::ClipboardPicker => None,
         }
     }
 
@@ -769,14 +928,21 @@ impl State {
     fn refresh(&mut self) {
         let _span = tracy_client::span!("State::refresh");
 
-        // Handle commits for surfaces whose blockers cleared this cycle. This should happen before
-        // layout.refresh() since this is where these surfaces handle commits.
+        // Release timed commits that are due by the next presentation, then handle commits for
+        // surfaces whose blockers cleared this cycle. This should happen before layout.refresh()
+        // since this is where these surfaces handle commits.
+        self.niri.signal_commit_timers();
         self.notify_blocker_cleared();
 
         // These should be called periodically, before flushing the clients.
         self.niri.popups.cleanup();
         self.refresh_popup_grab();
+        // Should be called before update_keyboard_focus() so that the focus goes to the dialog.
+        self.niri.refresh_modal_dialogs();
         self.update_keyboard_focus();
+        // Needs to be called after updating the keyboard focus, and before refresh_layout() so
+        // that windows get configured for the final working area.
+        self.refresh_on_screen_keyboard();
 
         // Should be called before refresh_layout() because that one will refresh other window
         // states and then send a pending configure.
@@ -789,10 +955,21 @@ impl State {
         self.niri.refresh_pointer_outputs();
         self.niri.global_space.refresh();
         self.niri.refresh_idle_inhibit();
+        self.niri.refresh_keyboard_shortcuts_inhibit();
         self.refresh_pointer_contents();
         foreign_toplevel::refresh(self);
+        ext_foreign_toplevel_list::refresh(self);
         ext_workspace::refresh(self);
+        color_management::refresh(self);
+        image_copy_capture::refresh(self);
+        if self.niri.image_copy_capture_state.has_pending_frames() {
+            self.backend.with_primary_renderer(|renderer| {
+                self.niri.render_for_image_copy_capture(renderer);
+            });
+        }
 
+        #[cfg(feature = "xdp-gnome-screencast")]
+        self.niri.refresh_column_casts();
         #[cfg(feature = "xdp-gnome-screencast")]
         self.niri.refresh_mapped_cast_outputs();
         // Should happen before refresh_window_rules(), but after anything that can start or stop
@@ -803,6 +980,7 @@ impl State {
         self.niri.refresh_window_rules();
         self.refresh_ipc_outputs();
         self.ipc_refresh_layout();
+        self.ipc_refresh_layers();
         self.ipc_refresh_keyboard_layout_index();
 
         // Needs to be called after updating the keyboard focus.
@@ -895,30 +1073,15 @@ impl State {
     }
 
     pub fn move_cursor_to_focused_tile(&mut self, mode: CenterCoords) -> bool {
-        if !self.niri.keyboard_focus.is_layout() {
-            return false;
-        }
-
         if self.niri.tablet_cursor_location.is_some() {
             return false;
         }
 
-        let Some(output) = self.niri.layout.active_output() else {
+        let Some((_, rect)) = self.niri.focused_tile_global_rect() else {
             return false;
         };
-        let monitor = self.niri.layout.monitor_for_output(output).unwrap();
-
-        let mut rv = false;
-        let rect = monitor.active_tile_visual_rectangle();
-
-        if let Some(rect) = rect {
-            let output_geo = self.niri.global_space.output_geometry(output).unwrap();
-            let mut rect = rect;
-            rect.loc += output_geo.loc.to_f64();
-            rv = self.move_cursor_to_rect(rect, mode);
-        }
 
-        rv
+        self.move_cursor_to_rect(rect, mode)
     }
 
     pub fn focus_default_monitor(&mut self) {
@@ -970,6 +1133,12 @@ impl State {
         }
     }
 
+    pub fn confirm_column_grid(&mut self) {
+        if let Some(window) = self.niri.close_column_grid() {
+            self.focus_window(&window);
+        }
+    }
+
     pub fn maybe_warp_cursor_to_focus(&mut self) -> bool {
         let focused = match self.niri.config.borrow().input.warp_mouse_to_focus {
             None => return false,
@@ -1119,6 +1288,10 @@ impl State {
             KeyboardFocus::ScreenshotUi
         } else if self.niri.window_mru_ui.is_open() {
             KeyboardFocus::Mru
+        } else if self.niri.column_grid.is_open() {
+            KeyboardFocus::ColumnGrid
+        } else if self.niri.clipboard_picker.is_open() {
+            KeyboardFocus::ClipboardPicker
         } else if let Some(output) = self.niri.layout.active_output() {
             let mon = self.niri.layout.monitor_for_output(output).unwrap();
             let layers = layer_map_for_output(output);
@@ -1280,6 +1453,9 @@ impl State {
                 }
             }
 
+            // A released forced pointer constraint only stays released for the same focus.
+            self.niri.forced_pointer_constraint_released = false;
+
             if let Some(grab) = self.niri.popup_grab.as_mut() {
                 if grab.has_keyboard_grab && Some(&grab.root) != focus.surface() {
                     trace!(
@@ -1335,6 +1511,7 @@ impl State {
             }
 
             self.niri.keyboard_focus.clone_from(&focus);
+            self.niri.keyboard_shortcuts_inhibit_dirty = true;
             keyboard.set_focus(self, focus.into_surface(), SERIAL_COUNTER.next_serial());
 
             // FIXME: can be more granular.
@@ -1401,6 +1578,14 @@ impl State {
 
         self.niri.config_error_notification.hide();
 
+        // Keep the window rules added over IPC.
+        let ipc_rules = self
+            .niri
+            .ipc_window_rules
+            .iter()
+            .map(|(_, rule)| rule.clone());
+        config.window_rules.extend(ipc_rules);
+
         // Find & orphan removed named workspaces.
         let mut removed_workspaces: Vec<String> = vec![];
         for ws in &self.niri.config.borrow().workspaces {
@@ -1412,9 +1597,19 @@ impl State {
             self.niri.layout.unname_workspace(&name);
         }
 
+        // Skip the layer surfaces unless their config changed, since updating them recreates the
+        // blur buffers.
+        let layer_config_changed = {
+            let old_config = self.niri.config.borrow();
+            config.layout.shadow != old_config.layout.shadow
+                || config.layout.blur != old_config.layout.blur
+        };
+
         self.niri.layout.update_config(&config);
-        for mapped in self.niri.mapped_layer_surfaces.values_mut() {
-            mapped.update_config(&config);
+        if layer_config_changed {
+            for mapped in self.niri.mapped_layer_surfaces.values_mut() {
+                mapped.update_config(&config);
+            }
         }
 
         // Create new named workspaces.
@@ -1431,9 +1626,11 @@ impl State {
         *CHILD_ENV.write().unwrap() = mem::take(&mut config.environment);
 
         let mut reload_xkb = None;
+        let mut release_sticky_keys = false;
         let mut libinput_config_changed = false;
         let mut output_config_changed = false;
         let mut preserved_output_config = None;
+        let mut output_profiles_reload = false;
         let mut window_rules_changed = false;
         let mut layer_rules_changed = false;
         let mut shaders_changed = false;
@@ -1455,6 +1652,11 @@ impl State {
             reload_xkb = Some(config.input.keyboard.xkb.clone());
         }
 
+        // Release the modifiers latched by sticky keys when turning them off.
+        if !config.input.keyboard.sticky_keys && old_config.input.keyboard.sticky_keys {
+            release_sticky_keys = true;
+        }
+
         // Reload the repeat info.
         if config.input.keyboard.repeat_rate != old_config.input.keyboard.repeat_rate
             || config.input.keyboard.repeat_delay != old_config.input.keyboard.repeat_delay
@@ -1472,6 +1674,7 @@ impl State {
             || config.input.trackpoint != old_config.input.trackpoint
             || config.input.tablet != old_config.input.tablet
             || config.input.touch != old_config.input.touch
+            || config.input.devices != old_config.input.devices
         {
             libinput_config_changed = true;
         }
@@ -1481,25 +1684,51 @@ impl State {
 
         if config.outputs != self.niri.config_file_output_config {
             output_config_changed = true;
+            output_profiles_reload = true;
             self.niri
                 .config_file_output_config
                 .clone_from(&config.outputs);
+        } else if config.output_profiles != old_config.output_profiles {
+            output_config_changed = true;
+            output_profiles_reload = true;
         } else {
             // Output config did not change from the last disk load, so we need to preserve the
             // transient changes.
             preserved_output_config = Some(mem::take(&mut old_config.outputs));
         }
 
-        let binds_changed = config.binds != old_config.binds;
+        let binds_changed =
+            config.binds != old_config.binds || config.bind_modes != old_config.bind_modes;
+
+        // Leave the bind mode if it's gone from the config.
+        let bind_mode_removed = self
+            .niri
+            .bind_mode
+            .as_ref()
+            .is_some_and(|name| !config.bind_modes.iter().any(|mode| mode.name == *name));
+        if bind_mode_removed {
+            self.niri.bind_mode = None;
+            self.niri.hotkey_overlay.set_bind_mode(None);
+        }
+
         let new_mod_key = self.backend.mod_key(&config);
         if new_mod_key != self.backend.mod_key(&old_config) || binds_changed {
             self.niri
                 .hotkey_overlay
                 .on_hotkey_config_updated(new_mod_key);
-            self.niri.mods_with_mouse_binds = mods_with_mouse_binds(new_mod_key, &config.binds);
-            self.niri.mods_with_wheel_binds = mods_with_wheel_binds(new_mod_key, &config.binds);
+            self.niri
+                .shortcuts_inhibit_indicator
+                .on_hotkey_config_updated(new_mod_key);
+            self.niri
+                .key_sequence_hint
+                .on_hotkey_config_updated(new_mod_key);
+            // The pending key sequence may be gone from the new binds.
+            self.niri.cancel_key_sequence();
+            let binds = config.binds_in_mode(self.niri.bind_mode.as_deref());
+            self.niri.mods_with_mouse_binds = mods_with_mouse_binds(new_mod_key, binds);
+            self.niri.mods_with_wheel_binds = mods_with_wheel_binds(new_mod_key, binds);
             self.niri.mods_with_finger_scroll_binds =
-                mods_with_finger_scroll_binds(new_mod_key, &config.binds);
+                mods_with_finger_scroll_binds(new_mod_key, binds);
         }
 
         if config.window_rules != old_config.window_rules {
@@ -1540,6 +1769,16 @@ impl State {
             shaders_changed = true;
         }
 
+        if config.animations.workspace_switch.custom_shader
+            != old_config.animations.workspace_switch.custom_shader
+        {
+            let src = config.animations.workspace_switch.custom_shader.as_deref();
+            self.backend.with_primary_renderer(|renderer| {
+                shaders::set_custom_workspace_switch_program(renderer, src);
+            });
+            shaders_changed = true;
+        }
+
         if config.cursor.hide_after_inactive_ms != old_config.cursor.hide_after_inactive_ms {
             cursor_inactivity_timeout_changed = true;
         }
@@ -1561,6 +1800,19 @@ impl State {
         if config.layout.background_color != old_config.layout.background_color {
             output_config_changed = true;
         }
+        // Workspaces can override the background color, which shows through the blur.
+        let workspace_background_colors = |config: &Config| {
+            config
+                .workspaces
+                .iter()
+                .map(|ws| {
+                    let color = ws.layout.as_ref().and_then(|l| l.0.background_color);
+                    (ws.name.0.clone(), color)
+                })
+                .collect::<Vec<_>>()
+        };
+        let workspace_background_changed =
+            workspace_background_colors(&config) != workspace_background_colors(&old_config);
 
         if config.recent_windows != old_config.recent_windows {
             recent_windows_changed = true;
@@ -1570,6 +1822,16 @@ impl State {
             xwls_changed = true;
         }
 
+        if config.clipboard.history != old_config.clipboard.history {
+            let history = config.clipboard.history;
+            if history.on {
+                let max_entries = history.max_entries as usize;
+                self.niri.clipboard_history.truncate(max_entries);
+            } else {
+                self.niri.clipboard_history.clear();
+            }
+        }
+
         *old_config = config;
 
         if let Some(outputs) = preserved_output_config {
@@ -1579,6 +1841,12 @@ impl State {
         // Release the borrow.
         drop(old_config);
 
+        if release_sticky_keys {
+            for key_code in self.niri.sticky_keys.reset() {
+                self.release_key(key_code);
+            }
+        }
+
         // Now with a &mut self we can reload the xkb config.
         if let Some(mut xkb) = reload_xkb {
             let mut set_xkb_config = true;
@@ -1618,16 +1886,35 @@ impl State {
             self.backend.update_ignored_nodes_config(&mut self.niri);
         }
 
+        if output_profiles_reload {
+            // Put the active profile back on top of the new output config.
+            self.niri.apply_output_profile();
+        }
+
         if output_config_changed {
             self.reload_output_config();
         }
 
+        if output_profiles_reload {
+            // The profiles themselves may have changed, so a different one may match now.
+            self.refresh_output_profile();
+        }
+
         if window_rules_changed {
             self.niri.recompute_window_rules();
+            self.niri.reapply_window_locations();
         }
 
         if layer_rules_changed {
             self.niri.recompute_layer_rules();
+            // Hidden surfaces may no longer be on-screen keyboards. The ones that still are get
+            // hidden again on the next refresh.
+            self.show_on_screen_keyboards();
+        }
+
+        if window_rules_changed || layer_rules_changed {
+            let config = self.niri.config.borrow();
+            self.niri.active_time_conditions = compute_active_time_conditions(&config);
         }
 
         if shaders_changed {
@@ -1644,6 +1931,10 @@ impl State {
             self.niri.window_mru_ui.update_binds();
         }
 
+        if bind_mode_removed {
+            self.ipc_refresh_bind_mode();
+        }
+
         if recent_windows_changed {
             self.niri.window_mru_ui.update_config();
         }
@@ -1672,10 +1963,16 @@ impl State {
             *CHILD_DISPLAY.write().unwrap() = display_name;
         }
 
-        self.niri
-            .global_space
-            .outputs()
-            .for_each(EffectsFramebuffers::set_dirty);
+        if layer_config_changed
+            || layer_rules_changed
+            || output_config_changed
+            || workspace_background_changed
+        {
+            self.niri
+                .global_space
+                .outputs()
+                .for_each(EffectsFramebuffers::set_dirty);
+        }
 
         // Can't really update xdg-decoration settings since we have to hide the globals for CSD
         // due to the SDL2 bug... I don't imagine clients are prepared for the xdg-decoration
@@ -1685,6 +1982,116 @@ impl State {
         self.niri.queue_redraw_all();
     }
 
+    /// Applies a config temporarily, reverting to the config file after the timeout.
+    pub fn try_config(&mut self, config: Config, timeout: Duration) -> Result<(), String> {
+        if self.niri.config_file_watcher.is_none() {
+            return Err(String::from("no config file to revert to"));
+        }
+
+        info!("trying config, reverting in {timeout:?} unless confirmed");
+
+        self.reload_config(Ok(config));
+        self.ipc_config_loaded(false);
+
+        let timer = Timer::from_duration(timeout);
+        let token = self
+            .niri
+            .event_loop
+            .insert_source(timer, |_, _, state| {
+                state.niri.config_preview_timer = None;
+
+                info!("config was not confirmed, reverting");
+                if let Some(watcher) = &state.niri.config_file_watcher {
+                    watcher.load_config();
+                }
+
+                TimeoutAction::Drop
+            })
+            .unwrap();
+        if let Some(token) = self.niri.config_preview_timer.replace(token) {
+            self.niri.event_loop.remove(token);
+        }
+
+        Ok(())
+    }
+
+    /// Keeps the config applied with [`State::try_config()`].
+    pub fn confirm_config(&mut self) -> Result<(), String> {
+        let token = self
+            .niri
+            .config_preview_timer
+            .take()
+            .ok_or_else(|| String::from("no config is being tried"))?;
+        self.niri.event_loop.remove(token);
+
+        info!("tried config confirmed");
+        Ok(())
+    }
+
+    /// Reloads the given sections of the config file, keeping the rest of the config.
+    pub fn reload_config_sections(
+        &mut self,
+        sections: &[niri_ipc::ConfigSection],
+    ) -> Result<(), String> {
+        use niri_ipc::ConfigSection;
+
+        let watcher = self
+            .niri
+            .config_file_watcher
+            .as_ref()
+            .ok_or_else(|| String::from("no config file to reload"))?;
+        let mut new = watcher
+            .path()
+            .load()
+            .config
+            .map_err(|err| format!("{err:?}"))?;
+
+        if sections.is_empty() {
+            self.reload_config(Ok(new));
+            self.ipc_config_loaded(false);
+            return Ok(());
+        }
+
+        let mut config = self.niri.config.borrow().clone();
+        // Compare against the outputs from disk to keep the transient output changes, and restore
+        // the environment which reload_config() moves out of the config.
+        config
+            .outputs
+            .clone_from(&self.niri.config_file_output_config);
+        config.environment = CHILD_ENV.read().unwrap().clone();
+        // Drop the window rules added over IPC, reload_config() appends them again.
+        let file_rules = config.window_rules.len() - self.niri.ipc_window_rules.len();
+        config.window_rules.truncate(file_rules);
+
+        for section in sections {
+            match section {
+                ConfigSection::Input => config.input = mem::take(&mut new.input),
+                ConfigSection::Outputs => {
+                    config.outputs = mem::take(&mut new.outputs);
+                    config.output_profiles = mem::take(&mut new.output_profiles);
+                }
+                ConfigSection::Binds => {
+                    config.binds = mem::take(&mut new.binds);
+                    config.bind_modes = mem::take(&mut new.bind_modes);
+                    config.switch_events = mem::take(&mut new.switch_events);
+                    config.tablet_pad_binds = mem::take(&mut new.tablet_pad_binds);
+                }
+                ConfigSection::Layout => config.layout = new.layout.clone(),
+                ConfigSection::Animations => config.animations = new.animations.clone(),
+                ConfigSection::Cursor => config.cursor = new.cursor.clone(),
+                ConfigSection::WindowRules => {
+                    config.window_rules = mem::take(&mut new.window_rules);
+                }
+                ConfigSection::LayerRules => config.layer_rules = mem::take(&mut new.layer_rules),
+            }
+        }
+
+        info!("reloading config sections: {sections:?}");
+        self.reload_config(Ok(config));
+        self.ipc_config_loaded(false);
+        Ok(())
+    }
+
     pub fn reload_output_config(&mut self) {
         let mut resized_outputs = vec![];
         let mut recolored_outputs = vec![];
@@ -1896,122 +2303,534 @@ impl State {
         self.reload_output_config();
     }
 
-    pub fn refresh_ipc_outputs(&mut self) {
-        if !self.niri.ipc_outputs_changed {
-            return;
-        }
-        self.niri.ipc_outputs_changed = false;
-
-        let _span = tracy_client::span!("State::refresh_ipc_outputs");
+    pub fn ipc_blur(
+        &self,
+        output: Option<&str>,
+        window: Option<u64>,
+    ) -> Result<niri_ipc::Blur, String> {
+        let config = self.niri.config.borrow();
 
-        for ipc_output in self.backend.ipc_outputs().lock().unwrap().values_mut() {
-            let logical = self
-                .niri
-                .global_space
-                .outputs()
-                .find(|output| output.name() == ipc_output.name)
-                .map(logical_output);
-            ipc_output.logical = logical;
-        }
+        let blur = match (output, window) {
+            (Some(_), Some(_)) => {
+                return Err(String::from("cannot specify both an output and a window"));
+            }
+            (Some(name), None) => {
+                let output = self
+                    .niri
+                    .output_by_name_match(name)
+                    .ok_or_else(|| format!("output \"{name}\" not found"))?;
+                let output_name = output.user_data().get::<OutputName>().unwrap();
+
+                let mut blur = config.layout.blur;
+                let rule = config
+                    .outputs
+                    .find(output_name)
+                    .and_then(|c| c.layout.as_ref())
+                    .and_then(|layout| layout.blur.as_ref());
+                if let Some(rule) = rule {
+                    blur.merge_with(rule);
+                }
+                blur
+            }
+            (None, Some(id)) => {
+                let (mapped, tile) = self
+                    .niri
+                    .layout
+                    .windows()
+                    .find(|(_, mapped)| mapped.id().get() == id)
+                    .and_then(|(_, mapped)| {
+                        let tile = self.niri.layout.tile_for_window(&mapped.window)?;
+                        Some((mapped, tile))
+                    })
+                    .ok_or_else(|| format!("window {id} not found"))?;
 
-        #[cfg(feature = "dbus")]
-        self.niri.on_ipc_outputs_changed();
+                let mut blur = tile.blur_config();
+                blur.on = mapped.wants_blur();
+                blur
+            }
+            (None, None) => config.layout.blur,
+        };
 
-        let new_config = self.backend.ipc_outputs().lock().unwrap().clone();
-        self.niri.output_management_state.notify_changes(new_config);
+        Ok(niri_ipc::Blur {
+            on: blur.on,
+            passes: blur.passes,
+            radius: blur.radius.0,
+            noise: blur.noise.0,
+            brightness: blur.brightness.0,
+            contrast: blur.contrast.0,
+            saturation: blur.saturation.0,
+        })
     }
 
-    pub fn open_screenshot_ui(&mut self, show_pointer: bool, path: Option<String>) {
-        if self.niri.is_locked() || self.niri.screenshot_ui.is_open() {
-            return;
-        }
-
-        let default_output = self
-            .niri
-            .output_under_cursor()
-            .or_else(|| self.niri.layout.active_output().cloned());
-        let Some(default_output) = default_output else {
-            return;
+    pub fn apply_transient_blur_config(
+        &mut self,
+        output: Option<String>,
+        window: Option<u64>,
+        change: niri_ipc::BlurChange,
+    ) -> Result<(), String> {
+        let apply_change = |rule: &mut BlurRule| {
+            if let Some(on) = change.on {
+                rule.on = on;
+                rule.off = !on;
+            }
+            if let Some(passes) = change.passes {
+                rule.passes = Some(passes);
+            }
+            if let Some(radius) = change.radius {
+                rule.radius = Some(FloatOrInt(radius));
+            }
         };
 
-        self.niri.update_render_elements(None);
+        match (output, window) {
+            (Some(_), Some(_)) => {
+                return Err(String::from("cannot specify both an output and a window"));
+            }
+            (Some(name), None) => {
+                self.modify_output_config(&name, |config| {
+                    let layout = config.layout.get_or_insert_with(Default::default);
+                    apply_change(layout.blur.get_or_insert_with(Default::default));
+                });
+                self.reload_output_config();
+            }
+            (None, Some(id)) => {
+                let window_rules = &self.niri.config.borrow().window_rules;
+                let is_at_startup = self.niri.is_at_startup;
 
-        let Some(screenshots) = self
-            .backend
-            .with_primary_renderer(|renderer| self.niri.capture_screenshots(renderer).collect())
-        else {
-            return;
-        };
+                let mut window = None;
+                self.niri.layout.with_windows_mut(|mapped, _| {
+                    if mapped.id().get() != id {
+                        return;
+                    }
 
-        // Now that we captured the screenshots, clear grabs like drag-and-drop, etc.
-        self.niri.seat.get_pointer().unwrap().unset_grab(
-            self,
-            SERIAL_COUNTER.next_serial(),
-            get_monotonic_time().as_millis() as u32,
-        );
-        if let Some(touch) = self.niri.seat.get_touch() {
-            touch.unset_grab(self);
-        }
+                    let mut blur = mapped.blur_override();
+                    apply_change(&mut blur);
+                    mapped.set_blur_override(blur, window_rules, is_at_startup);
+                    window = Some(mapped.window.clone());
+                });
 
-        self.backend.with_primary_renderer(|renderer| {
-            self.niri
-                .screenshot_ui
-                .open(renderer, screenshots, default_output, show_pointer, path)
-        });
+                let window = window.ok_or_else(|| format!("window {id} not found"))?;
+                self.niri.layout.update_window(&window, None);
+            }
+            (None, None) => {
+                let mut rule = BlurRule::default();
+                apply_change(&mut rule);
+                self.niri.config.borrow_mut().layout.blur.merge_with(&rule);
+
+                let config = self.niri.config.borrow();
+                self.niri.layout.update_config(&config);
+                for mapped in self.niri.mapped_layer_surfaces.values_mut() {
+                    mapped.update_config(&config);
+                }
+            }
+        }
 
         self.niri
-            .cursor_manager
-            .set_cursor_image(CursorImageStatus::Named(CursorIcon::Crosshair));
+            .global_space
+            .outputs()
+            .for_each(EffectsFramebuffers::set_dirty);
         self.niri.queue_redraw_all();
+
+        Ok(())
     }
 
-    pub fn handle_pick_color(&mut self, tx: async_channel::Sender<Option<niri_ipc::PickedColor>>) {
-        let pointer = self.niri.seat.get_pointer().unwrap();
-        let start_data = PointerGrabStartData {
-            focus: None,
-            button: 0,
-            location: pointer.current_location(),
-        };
-        let grab = PickColorGrab::new(start_data);
-        pointer.set_grab(self, grab, SERIAL_COUNTER.next_serial(), Focus::Clear);
-        self.niri.pick_color = Some(tx);
+    pub fn add_window_rule(&mut self, rule: String) -> Result<u64, String> {
+        let parsed = parse_window_rule(&rule)?;
+
+        let id = self.niri.next_ipc_window_rule_id;
+        self.niri.next_ipc_window_rule_id += 1;
+
         self.niri
-            .cursor_manager
-            .set_cursor_image(CursorImageStatus::Named(CursorIcon::Crosshair));
-        self.niri.queue_redraw_all();
+            .config
+            .borrow_mut()
+            .window_rules
+            .push(parsed.clone());
+        self.niri
+            .ipc_window_rules
+            .push((niri_ipc::RuntimeWindowRule { id, rule }, parsed));
+
+        self.niri.recompute_window_rules();
+        Ok(id)
     }
 
-    pub fn confirm_screenshot(&mut self, write_to_disk: bool) {
-        let ScreenshotUi::Open { path, .. } = &mut self.niri.screenshot_ui else {
-            return;
-        };
-        let path = path.take();
+    pub fn remove_window_rule(&mut self, id: u64) -> Result<(), String> {
+        let idx = self
+            .niri
+            .ipc_window_rules
+            .iter()
+            .position(|(rule, _)| rule.id == id)
+            .ok_or_else(|| format!("window rule {id} not found"))?;
 
-        self.backend.with_primary_renderer(|renderer| {
-            match self.niri.screenshot_ui.capture(renderer) {
-                Ok((size, pixels)) => {
-                    if let Err(err) = self.niri.save_screenshot(size, pixels, write_to_disk, path) {
-                        warn!("error saving screenshot: {err:?}");
-                    }
-                }
-                Err(err) => {
-                    warn!("error capturing screenshot: {err:?}");
-                }
-            }
-        });
+        {
+            let mut config = self.niri.config.borrow_mut();
+            let file_rules = config.window_rules.len() - self.niri.ipc_window_rules.len();
+            config.window_rules.remove(file_rules + idx);
+        }
+        self.niri.ipc_window_rules.remove(idx);
 
-        self.niri.screenshot_ui.close();
-        self.niri
-            .cursor_manager
-            .set_cursor_image(CursorImageStatus::default_named());
-        self.niri.queue_redraw_all();
+        self.niri.recompute_window_rules();
+        Ok(())
     }
 
-    #[cfg(not(feature = "xdp-gnome-screencast"))]
-    pub fn set_dynamic_cast_target(&mut self, _target: CastTarget) {}
+    pub fn ipc_window_decorations(&self, id: u64) -> Result<niri_ipc::WindowDecorations, String> {
+        let (mapped, tile) = self
+            .niri
+            .layout
+            .windows()
+            .find(|(_, mapped)| mapped.id().get() == id)
+            .and_then(|(_, mapped)| {
+                let tile = self.niri.layout.tile_for_window(&mapped.window)?;
+                Some((mapped, tile))
+            })
+            .ok_or_else(|| format!("window {id} not found"))?;
 
-    #[cfg(feature = "dbus")]
-    pub fn on_screen_shot_msg(
+        Ok(niri_ipc::WindowDecorations {
+            border: !tile.border().is_off(),
+            shadow: tile.shadow().config().on,
+            blur: mapped.wants_blur(),
+        })
+    }
+
+    pub fn set_window_decorations(
+        &mut self,
+        id: u64,
+        change: niri_ipc::WindowDecorationsChange,
+    ) -> Result<(), String> {
+        let mut window = None;
+        {
+            let window_rules = &self.niri.config.borrow().window_rules;
+            let is_at_startup = self.niri.is_at_startup;
+
+            self.niri.layout.with_windows_mut(|mapped, _| {
+                if mapped.id().get() != id {
+                    return;
+                }
+
+                mapped.set_decorations_override(change, window_rules, is_at_startup);
+                window = Some(mapped.window.clone());
+            });
+        }
+
+        let window = window.ok_or_else(|| format!("window {id} not found"))?;
+        self.niri.layout.update_window(&window, None);
+
+        if change.blur.is_some() {
+            self.niri
+                .global_space
+                .outputs()
+                .for_each(EffectsFramebuffers::set_dirty);
+        }
+        // FIXME: granular.
+        self.niri.queue_redraw_all();
+
+        Ok(())
+    }
+
+    /// Moves windows that weren't focused for a while to the parking workspace.
+    ///
+    /// Skips the focused window and windows on active workspaces, since those are visible.
+    pub fn park_inactive_windows(&mut self) {
+        let _span = tracy_client::span!("State::park_inactive_windows");
+
+        let config = self.niri.config.borrow();
+        let parking = &config.window_parking;
+        let (Some(minutes), Some(name)) = (parking.inactive_after_minutes, &parking.workspace)
+        else {
+            return;
+        };
+        let threshold = Duration::from_secs(u64::from(minutes) * 60);
+
+        let Some((parking_idx, parking_ws)) = self.niri.layout.find_workspace_by_name(name) else {
+            return;
+        };
+        let Some(output) = parking_ws.current_output().cloned() else {
+            return;
+        };
+        let parking_id = parking_ws.id();
+        drop(config);
+
+        let now = get_monotonic_time();
+        let mut parked = Vec::new();
+        let mut ids = Vec::new();
+        for (mon, idx, ws) in self.niri.layout.workspaces() {
+            if ws.id() == parking_id {
+                continue;
+            }
+            if mon.is_some_and(|mon| mon.active_workspace_idx() == idx) {
+                continue;
+            }
+
+            for mapped in ws.windows() {
+                if mapped.is_focused() {
+                    continue;
+                }
+                if now.saturating_sub(mapped.last_interaction()) < threshold {
+                    continue;
+                }
+                parked.push((mapped.window.clone(), ws.id()));
+                ids.push(mapped.id().get());
+            }
+        }
+
+        if parked.is_empty() {
+            return;
+        }
+
+        // Keep the workspaces that parking empties, so that the windows can come back to them.
+        for (_, ws_id) in &parked {
+            self.niri.layout.set_keep_workspace_when_empty(*ws_id, true);
+        }
+
+        for (window, _) in &parked {
+            // Re-resolve the index since moving windows can remove empty workspaces.
+            let idx = self
+                .niri
+                .layout
+                .find_workspace_by_id(parking_id)
+                .map_or(parking_idx, |(idx, _)| idx);
+            self.niri
+                .layout
+                .move_to_output(Some(window), &output, Some(idx), ActivateWindow::No);
+        }
+
+        self.ipc_windows_parked(ids);
+
+        self.niri.parked_windows.extend(parked);
+        self.niri.queue_redraw_all();
+    }
+
+    pub fn window_thumbnail(
+        &mut self,
+        client_id: u64,
+        id: u64,
+        max_size: u32,
+    ) -> Result<niri_ipc::WindowThumbnail, String> {
+        let _span = tracy_client::span!("State::window_thumbnail");
+
+        let now = Instant::now();
+        let thumbnails = &mut self.niri.window_thumbnails;
+        thumbnails.retain(|_, (time, _, _)| now.duration_since(*time) < WINDOW_THUMBNAIL_INTERVAL);
+
+        // Any client's recent render of this window at this size will do.
+        let cached = thumbnails
+            .iter()
+            .find(|((_, window_id), (_, size, _))| *window_id == id && *size == max_size);
+        if let Some((_, (_, _, thumbnail))) = cached {
+            return Ok(thumbnail.clone());
+        }
+
+        // Otherwise, don't let a client render the same window again too soon, for example by
+        // changing the max size on every request.
+        if thumbnails.contains_key(&(client_id, id)) {
+            return Err(format!(
+                "thumbnail of window {id} requested again within {}ms",
+                WINDOW_THUMBNAIL_INTERVAL.as_millis()
+            ));
+        }
+
+        let (mon, mapped) = self
+            .niri
+            .layout
+            .windows()
+            .find(|(_, mapped)| mapped.id().get() == id)
+            .ok_or_else(|| format!("window {id} not found"))?;
+        let output_scale = mon.map_or(1., |mon| mon.output().current_scale().fractional_scale());
+
+        let niri = &self.niri;
+        let thumbnail = self
+            .backend
+            .with_primary_renderer(|renderer| {
+                niri.render_window_thumbnail(renderer, mapped, output_scale, max_size)
+            })
+            .ok_or_else(|| String::from("no renderer available"))?
+            .map_err(|err| format!("error rendering window thumbnail: {err:?}"))?;
+
+        self.niri
+            .window_thumbnails
+            .insert((client_id, id), (now, max_size, thumbnail.clone()));
+        Ok(thumbnail)
+    }
+
+    pub fn refresh_ipc_outputs(&mut self) {
+        if !self.niri.ipc_outputs_changed {
+            return;
+        }
+        self.niri.ipc_outputs_changed = false;
+
+        let _span = tracy_client::span!("State::refresh_ipc_outputs");
+
+        for ipc_output in self.backend.ipc_outputs().lock().unwrap().values_mut() {
+            let logical = self
+                .niri
+                .global_space
+                .outputs()
+                .find(|output| output.name() == ipc_output.name)
+                .map(logical_output);
+            ipc_output.logical = logical;
+        }
+
+        #[cfg(feature = "dbus")]
+        self.niri.on_ipc_outputs_changed();
+
+        let new_config = self.backend.ipc_outputs().lock().unwrap().clone();
+        self.niri.output_management_state.notify_changes(new_config);
+
+        self.refresh_output_profile();
+    }
+
+    /// Activates the output profile matching the connected outputs, if it changed.
+    pub fn refresh_output_profile(&mut self) {
+        let connected: Vec<_> = self
+            .backend
+            .ipc_outputs()
+            .lock()
+            .unwrap()
+            .values()
+            .map(OutputName::from_ipc_output)
+            .collect();
+
+        // The backend may have already selected the profile before configuring new outputs, in
+        // which case this is a no-op and only the flag remains set.
+        self.niri.select_output_profile(&connected);
+        if !mem::take(&mut self.niri.output_profile_changed) {
+            return;
+        }
+
+        let config = self.niri.config.borrow();
+        let name = self.niri.active_output_profile.clone();
+        let workspaces = name
+            .as_ref()
+            .and_then(|name| {
+                config
+                    .output_profiles
+                    .0
+                    .iter()
+                    .find(|profile| profile.name == *name)
+            })
+            .map(|profile| profile.workspaces.clone())
+            .unwrap_or_default();
+        drop(config);
+
+        if let Some(name) = &name {
+            info!("activated output profile {name}");
+        } else {
+            info!("deactivated output profile");
+        }
+
+        self.reload_output_config();
+
+        for ws in workspaces {
+            let Some(output) = self.niri.output_by_name_match(&ws.open_on_output).cloned() else {
+                continue;
+            };
+
+            let reference = WorkspaceReference::Name(ws.name);
+            if let Some((old_output, old_idx)) =
+                self.niri.find_output_and_workspace_index(reference)
+            {
+                self.niri
+                    .layout
+                    .move_workspace_to_output_by_id(old_idx, old_output, &output);
+            }
+        }
+        self.niri.queue_redraw_all();
+
+        self.ipc_output_profile_activated();
+    }
+
+    pub fn open_screenshot_ui(&mut self, show_pointer: bool, path: Option<String>) {
+        if self.niri.is_locked() || self.niri.screenshot_ui.is_open() {
+            return;
+        }
+
+        let default_output = self
+            .niri
+            .output_under_cursor()
+            .or_else(|| self.niri.layout.active_output().cloned());
+        let Some(default_output) = default_output else {
+            return;
+        };
+
+        self.niri.update_render_elements(None);
+
+        let Some(screenshots) = self
+            .backend
+            .with_primary_renderer(|renderer| self.niri.capture_screenshots(renderer).collect())
+        else {
+            return;
+        };
+
+        // Now that we captured the screenshots, clear grabs like drag-and-drop, etc.
+        self.niri.seat.get_pointer().unwrap().unset_grab(
+            self,
+            SERIAL_COUNTER.next_serial(),
+            get_monotonic_time().as_millis() as u32,
+        );
+        if let Some(touch) = self.niri.seat.get_touch() {
+            touch.unset_grab(self);
+        }
+
+        self.backend.with_primary_renderer(|renderer| {
+            self.niri
+                .screenshot_ui
+                .open(renderer, screenshots, default_output, show_pointer, path)
+        });
+
+        self.niri
+            .cursor_manager
+            .set_cursor_image(CursorImageStatus::Named(CursorIcon::Crosshair));
+        self.niri.queue_redraw_all();
+    }
+
+    pub fn handle_pick_color(&mut self, tx: async_channel::Sender<Option<niri_ipc::PickedColor>>) {
+        let pointer = self.niri.seat.get_pointer().unwrap();
+        let start_data = PointerGrabStartData {
+            focus: None,
+            button: 0,
+            location: pointer.current_location(),
+        };
+        let grab = PickColorGrab::new(start_data);
+        pointer.set_grab(self, grab, SERIAL_COUNTER.next_serial(), Focus::Clear);
+        self.niri.pick_color = Some(tx);
+        self.niri
+            .cursor_manager
+            .set_cursor_image(CursorImageStatus::Named(CursorIcon::Crosshair));
+        self.niri.queue_redraw_all();
+    }
+
+    pub fn confirm_screenshot(&mut self, write_to_disk: bool) {
+        let ScreenshotUi::Open { path, .. } = &mut self.niri.screenshot_ui else {
+            return;
+        };
+        let path = path.take();
+
+        self.backend.with_primary_renderer(|renderer| {
+            match self.niri.screenshot_ui.capture(renderer) {
+                Ok((size, pixels)) => {
+                    let res = self
+                        .niri
+                        .save_screenshot(size, pixels, write_to_disk, path, None);
+                    if let Err(err) = res {
+                        warn!("error saving screenshot: {err:?}");
+                    }
+                }
+                Err(err) => {
+                    warn!("error capturing screenshot: {err:?}");
+                }
+            }
+        });
+
+        self.niri.screenshot_ui.close();
+        self.niri
+            .cursor_manager
+            .set_cursor_image(CursorImageStatus::default_named());
+        self.niri.queue_redraw_all();
+    }
+
+    #[cfg(not(feature = "xdp-gnome-screencast"))]
+    pub fn set_dynamic_cast_target(&mut self, _target: CastTarget) {}
+
+    #[cfg(feature = "dbus")]
+    pub fn on_screen_shot_msg(
         &mut self,
         to_screenshot: &async_channel::Sender<NiriToScreenshot>,
         msg: ScreenshotToNiri,
@@ -2089,9 +2908,19 @@ impl State {
             },
         );
 
+        // Forget the special windows for columns that are gone.
+        #[cfg(feature = "xdp-gnome-screencast")]
+        {
+            let layout = &self.niri.layout;
+            self.niri
+                .casting
+                .column_ids_for_portal
+                .retain(|_, id| layout.column_for_window(id).is_some());
+        }
+
         self.niri.layout.with_windows(|mapped, _, _, _| {
             let id = mapped.id().get();
-            let props = with_toplevel_role(mapped.toplevel(), |role| {
+            let mut props = with_toplevel_role(mapped.toplevel(), |role| {
                 gnome_shell_introspect::WindowProperties {
                     title: role.title.clone().unwrap_or_default(),
                     app_id: role
@@ -2105,6 +2934,30 @@ impl State {
                 }
             });
 
+            // Mark the tabs of a group with their position, since only one of them is visible.
+            let group = self.niri.layout.tile_for_window(&mapped.id());
+            if let Some(tile) = group.filter(|tile| tile.is_grouped_tile()) {
+                let count = tile.windows().count();
+                let idx = tile.windows().position(|win| win.id() == mapped.id());
+                let idx = idx.unwrap_or(0) + 1;
+                props.title = format!("{} (tab {idx} of {count})", props.title);
+            }
+
+            // Offer a special window for every column with several windows, which keeps
+            // following the active window of that column.
+            #[cfg(feature = "xdp-gnome-screencast")]
+            if let Some(column) = self.niri.layout.column_for_window(&mapped.id()) {
+                if column.active_window().id() == mapped.id() && column.windows().nth(1).is_some() {
+                    windows.insert(
+                        self.niri.casting.column_id_for_portal(mapped.id()),
+                        gnome_shell_introspect::WindowProperties {
+                            title: format!("Column: {}", props.title),
+                            app_id: props.app_id.clone(),
+                        },
+                    );
+                }
+            }
+
             windows.insert(id, props);
         });
 
@@ -2170,12 +3023,33 @@ impl Niri {
 
         let layout = Layout::new(animation_clock.clone(), &config_);
 
+        let saved_session = if config_.session.restore_layout {
+            SavedSession::load()
+        } else {
+            None
+        };
+
         let (blocker_cleared_tx, blocker_cleared_rx) = mpsc::channel();
 
         fn client_is_unrestricted(client: &Client) -> bool {
             !client.get_data::<ClientState>().unwrap().restricted
         }
 
+        fn client_can_screencopy(client: &Client) -> bool {
+            let data = client.get_data::<ClientState>().unwrap();
+            !data.restricted || data.allow_screencopy
+        }
+
+        fn client_can_data_control(client: &Client) -> bool {
+            let data = client.get_data::<ClientState>().unwrap();
+            !data.restricted || data.allow_data_control
+        }
+
+        fn client_can_list_toplevels(client: &Client) -> bool {
+            let data = client.get_data::<ClientState>().unwrap();
+            !data.restricted || data.allow_foreign_toplevel
+        }
+
         let compositor_state = CompositorState::new_v6::<State>(&display_handle);
         let xdg_shell_state = XdgShellState::new_with_capabilities::<State>(
             &display_handle,
@@ -2188,6 +3062,7 @@ impl Niri {
                     .unwrap()
                     .can_view_decoration_globals
             });
+        let xdg_dialog_state = XdgDialogState::new::<State>(&display_handle);
         let kde_decoration_state = KdeDecorationState::new_with_filter::<State, _>(
             &display_handle,
             // If we want CSD we will hide the global.
@@ -2232,15 +3107,27 @@ impl Niri {
         let wlr_data_control_state = WlrDataControlState::new::<State, _>(
             &display_handle,
             Some(&primary_selection_state),
-            client_is_unrestricted,
+            client_can_data_control,
         );
         let ext_data_control_state = ExtDataControlState::new::<State, _>(
             &display_handle,
             Some(&primary_selection_state),
-            client_is_unrestricted,
+            client_can_data_control,
         );
+
+        let (clipboard_history_tx, rx) = calloop::channel::channel();
+        event_loop
+            .insert_source(rx, |event, _, state| {
+                if let calloop::channel::Event::Msg((mime_type, data)) = event {
+                    state.niri.add_clipboard_history_entry(mime_type, data);
+                }
+            })
+            .unwrap();
+
         let presentation_state =
             PresentationState::new::<State>(&display_handle, Monotonic::ID as u32);
+        let fifo_manager_state = FifoManagerState::new::<State>(&display_handle);
+        let commit_timing_manager_state = CommitTimingManagerState::new::<State>(&display_handle);
         let security_context_state =
             SecurityContextState::new::<State, _>(&display_handle, client_is_unrestricted);
 
@@ -2253,15 +3140,23 @@ impl Niri {
             VirtualKeyboardManagerState::new::<State, _>(&display_handle, client_is_unrestricted);
         let virtual_pointer_state =
             VirtualPointerManagerState::new::<State, _>(&display_handle, client_is_unrestricted);
-        let foreign_toplevel_state =
-            ForeignToplevelManagerState::new::<State, _>(&display_handle, client_is_unrestricted);
-        let ext_workspace_state =
-            ExtWorkspaceManagerState::new::<State, _>(&display_handle, client_is_unrestricted);
+        let foreign_toplevel_state = ForeignToplevelManagerState::new::<State, _>(
+            &display_handle,
+            client_can_list_toplevels,
+        );
+        let ext_foreign_toplevel_list_state = ExtForeignToplevelListState::new::<State, _>(
+            &display_handle,
+            client_can_list_toplevels,
+        );
+        let ext_workspace_state =
+            ExtWorkspaceManagerState::new::<State, _>(&display_handle, client_is_unrestricted);
         let mut output_management_state =
             OutputManagementManagerState::new::<State, _>(&display_handle, client_is_unrestricted);
         output_management_state.on_config_changed(config_.outputs.clone());
         let screencopy_state =
-            ScreencopyManagerState::new::<State, _>(&display_handle, client_is_unrestricted);
+            ScreencopyManagerState::new::<State, _>(&display_handle, client_can_screencopy);
+        let image_copy_capture_state =
+            ImageCopyCaptureState::new::<State, _>(&display_handle, client_can_screencopy);
         let viewporter_state = ViewporterState::new::<State>(&display_handle);
         let xdg_foreign_state = XdgForeignState::new::<State>(&display_handle);
 
@@ -2292,6 +3187,21 @@ impl Niri {
         let ext_background_effect_manager_state =
             ExtBackgroundEffectManagerState::new::<State, _>(&display_handle, |_| true);
 
+        let xdg_toplevel_tag_manager_state =
+            XdgToplevelTagManagerState::new::<State, _>(&display_handle, |_| true);
+
+        let tearing_control_manager_state =
+            TearingControlManagerState::new::<State, _>(&display_handle, |_| true);
+
+        let pointer_warp_manager_state =
+            PointerWarpManagerState::new::<State, _>(&display_handle, |_| true);
+
+        let color_management_state =
+            ColorManagementState::new::<State, _>(&display_handle, |_| true);
+
+        let color_representation_manager_state =
+            ColorRepresentationManagerState::new::<State, _>(&display_handle, |_| true);
+
         #[cfg(test)]
         let single_pixel_buffer_state = SinglePixelBufferState::new::<State>(&display_handle);
 
@@ -2334,6 +3244,7 @@ impl Niri {
 
         let screenshot_ui = ScreenshotUi::new(animation_clock.clone(), config.clone());
         let window_mru_ui = WindowMruUi::new(config.clone());
+        let column_grid = ColumnGrid::new(config.clone());
         let config_error_notification =
             ConfigErrorNotification::new(animation_clock.clone(), config.clone());
 
@@ -2342,6 +3253,8 @@ impl Niri {
             hotkey_overlay.show();
         }
 
+        let shortcuts_inhibit_indicator = ShortcutsInhibitIndicator::new(config.clone(), mod_key);
+
         let exit_confirm_dialog = ExitConfirmDialog::new(animation_clock.clone(), config.clone());
 
         #[cfg(feature = "dbus")]
@@ -2380,6 +3293,18 @@ impl Niri {
             })
             .unwrap();
 
+        event_loop
+            .insert_source(
+                Timer::from_duration(Duration::from_secs(1)),
+                |_, _, state| {
+                    if state.niri.config.borrow().debug.emit_frame_stats_events {
+                        state.ipc_frame_stats_updated();
+                    }
+                    TimeoutAction::ToDuration(Duration::from_secs(1))
+                },
+            )
+            .unwrap();
+
         let socket_name = create_wayland_socket.then(|| {
             let socket_source = ListeningSocketSource::new_auto().unwrap();
             let socket_name = socket_source.socket_name().to_os_string();
@@ -2389,6 +3314,7 @@ impl Niri {
                         client,
                         restricted: false,
                         credentials_unknown: false,
+                        security_context: None,
                     });
                 })
                 .unwrap();
@@ -2430,11 +3356,37 @@ impl Niri {
             )
             .unwrap();
 
+        let until_next_minute = local_time_of_day().map_or(Duration::from_secs(60), |(_, x)| x);
+        event_loop
+            .insert_source(Timer::from_duration(until_next_minute), |_, _, state| {
+                state.niri.update_time_conditions();
+
+                let until_next_minute =
+                    local_time_of_day().map_or(Duration::from_secs(60), |(_, x)| x);
+                TimeoutAction::ToDuration(until_next_minute)
+            })
+            .unwrap();
+        event_loop
+            .insert_source(
+                Timer::from_duration(Duration::from_secs(60)),
+                |_, _, state| {
+                    state.park_inactive_windows();
+                    TimeoutAction::ToDuration(Duration::from_secs(60))
+                },
+            )
+            .unwrap();
+        let active_time_conditions = compute_active_time_conditions(&config_);
+
         drop(config_);
         let mut niri = Self {
             config,
             config_file_output_config,
+            active_output_profile: None,
+            output_profile_changed: false,
+            ipc_window_rules: Vec::new(),
+            next_ipc_window_rule_id: 1,
             config_file_watcher: None,
+            config_preview_timer: None,
 
             event_loop,
             scheduler,
@@ -2444,9 +3396,16 @@ impl Niri {
             is_session_instance,
             start_time: Instant::now(),
             is_at_startup: true,
+            active_time_conditions,
+            parked_windows: Vec::new(),
+            window_thumbnails: HashMap::new(),
             clock: animation_clock,
 
             layout,
+            saved_session,
+            configure_batch_timer: None,
+            tab_title_timer: None,
+            commit_timer_wakeup: None,
             global_space: Space::default(),
             sorted_outputs: Vec::default(),
             output_state: HashMap::new(),
@@ -2468,13 +3427,16 @@ impl Niri {
             compositor_state,
             xdg_shell_state,
             xdg_decoration_state,
+            xdg_dialog_state,
             kde_decoration_state,
             layer_shell_state,
             session_lock_state,
             foreign_toplevel_state,
+            ext_foreign_toplevel_list_state,
             ext_workspace_state,
             output_management_state,
             screencopy_state,
+            image_copy_capture_state,
             viewporter_state,
             xdg_foreign_state,
             text_input_state,
@@ -2497,29 +3459,51 @@ impl Niri {
             primary_selection_state,
             wlr_data_control_state,
             ext_data_control_state,
+            clipboard_history: ClipboardHistory::new(),
+            clipboard_history_tx,
             popups: PopupManager::default(),
             popup_grab: None,
             suppressed_keys: HashSet::new(),
+            ipc_key_grabs: Vec::new(),
+            ipc_key_grab_clients: HashMap::new(),
             suppressed_buttons: HashSet::new(),
+            pressed_buttons: HashSet::new(),
+            held_chord_press: None,
             valid_release_trigger: None,
             bind_cooldown_timers: HashMap::new(),
             bind_repeat_timer: Option::default(),
+            modifier_taps: ModifierTaps::new(),
+            modifier_tap_timer: None,
+            sticky_keys: StickyKeys::new(),
+            tablet_pad_axes: TabletPadAxes::new(),
+            slow_keys_timers: HashMap::new(),
             presentation_state,
+            fifo_manager_state,
+            commit_timing_manager_state,
             security_context_state,
             gamma_control_manager_state,
             activation_state,
             mutter_x11_interop_state,
             org_kde_kwin_blur_manager_state,
             ext_background_effect_manager_state,
+            xdg_toplevel_tag_manager_state,
+            tearing_control_manager_state,
+            pointer_warp_manager_state,
+            color_management_state,
+            color_representation_manager_state,
             #[cfg(test)]
             single_pixel_buffer_state,
 
             seat,
             keyboard_focus: KeyboardFocus::Layout { surface: None },
             layer_shell_on_demand_focus: None,
+            hidden_on_screen_keyboards: Vec::new(),
+            on_screen_keyboard_wanted: false,
             idle_inhibiting_surfaces: HashSet::new(),
             is_fdo_idle_inhibited: Arc::new(AtomicBool::new(false)),
             keyboard_shortcuts_inhibiting_surfaces: HashMap::new(),
+            keyboard_shortcuts_inhibitors_pending: HashSet::new(),
+            keyboard_shortcuts_inhibit_dirty: true,
             xkb_from_locale1: None,
             cursor_manager,
             cursor_texture_cache: Default::default(),
@@ -2531,8 +3515,15 @@ impl Niri {
             pointer_inactivity_timer_got_reset: false,
             notified_activity_this_iteration: false,
             pointer_inside_hot_corner: false,
+            hot_corner_timer: None,
+            focus_follows_mouse_timer: None,
+            forced_pointer_constraint_released: false,
+            pointer_resize_border_edges: None,
             tablet_cursor_location: None,
-            gesture_swipe_3f_cumulative: None,
+            gesture_swipe_pending: None,
+            gesture_pinch_pending: None,
+            gesture_swallowed: false,
+            gesture_pinch_resize_window: None,
             overview_scroll_swipe_gesture: ScrollSwipeGesture::new(),
             vertical_wheel_tracker: ScrollTracker::new(120),
             horizontal_wheel_tracker: ScrollTracker::new(120),
@@ -2550,16 +3541,25 @@ impl Niri {
             screenshot_ui,
             config_error_notification,
             hotkey_overlay,
+            shortcuts_inhibit_indicator,
+            bind_mode: None,
+            key_sequence_hint: KeySequenceHint::new(mod_key),
+            key_sequence_timer: None,
             exit_confirm_dialog,
+            overlay_widgets: OverlayWidgets::default(),
 
             window_mru_ui,
+            column_grid,
+            clipboard_picker: ClipboardPicker::new(),
             pending_mru_commit: None,
 
             pick_window: None,
             pick_color: None,
+            ipc_screenshot_reply: None,
 
             debug_draw_opaque_regions: false,
             debug_draw_damage: false,
+            debug_draw_premultiplied_alpha: false,
 
             #[cfg(feature = "dbus")]
             dbus: None,
@@ -2589,15 +3589,37 @@ impl Niri {
             client,
             restricted,
             credentials_unknown,
+            security_context,
         } = client;
 
         let config = self.config.borrow();
+
+        // Policies are evaluated once on connection, so config changes apply to new clients.
+        let mut allow_screencopy = false;
+        let mut allow_data_control = false;
+        let mut allow_foreign_toplevel = false;
+        if let Some(context) = &security_context {
+            let sandbox_engine = context.sandbox_engine.as_deref();
+            let app_id = context.app_id.as_deref();
+            for policy in &config.security_context.0 {
+                if policy.matches(sandbox_engine, app_id) {
+                    allow_screencopy |= policy.screencopy;
+                    allow_data_control |= policy.data_control;
+                    allow_foreign_toplevel |= policy.foreign_toplevel;
+                }
+            }
+        }
+
         let data = Arc::new(ClientState {
             compositor_state: Default::default(),
             can_view_decoration_globals: config.prefer_no_csd,
             primary_selection_disabled: config.clipboard.disable_primary,
             restricted,
             credentials_unknown,
+            allow_screencopy,
+            allow_data_control,
+            allow_foreign_toplevel,
+            protocol_stats: Mutex::new(ClientStats::new()),
         });
 
         if let Err(err) = self.display_handle.insert_client(client, data) {
@@ -2817,10 +3839,14 @@ impl Niri {
             frame_callback_sequence: 0,
             backdrop_buffer: SolidColorBuffer::new(size, backdrop_color),
             lock_render_state,
+            powered_off: false,
             lock_surface: None,
             lock_color_buffer: SolidColorBuffer::new(size, CLEAR_COLOR_LOCKED),
+            lock_backdrop: None,
             screen_transition: None,
+            closing_layers: Vec::new(),
             debug_damage_tracker: OutputDamageTracker::from_output(&output),
+            frame_stats: FrameStats::default(),
         };
         let rv = self.output_state.insert(output.clone(), state);
         assert!(rv.is_none(), "output was already tracked");
@@ -2902,6 +3928,18 @@ impl Niri {
         if self.window_mru_ui.output() == Some(output) {
             self.cancel_mru();
         }
+
+        if self.column_grid.output() == Some(output) {
+            self.close_column_grid();
+        }
+
+        if self.clipboard_picker.output() == Some(output) {
+            self.close_clipboard_picker();
+        }
+
+        if self.key_sequence_hint.output() == Some(output) {
+            self.cancel_key_sequence();
+        }
     }
 
     pub fn output_resized(&mut self, output: &Output) {
@@ -2929,6 +3967,8 @@ impl Niri {
             state.backdrop_buffer.resize(output_size);
 
             state.lock_color_buffer.resize(output_size);
+            // The snapshot no longer matches the output.
+            state.lock_backdrop = None;
             if let Some(lock_surface) = &state.lock_surface {
                 configure_lock_surface(lock_surface, output);
             }
@@ -2974,6 +4014,27 @@ impl Niri {
         self.queue_redraw_all();
     }
 
+    pub fn power_off_output(&mut self, backend: &mut Backend, output: &Output) {
+        let state = self.output_state.get_mut(output).unwrap();
+        if state.powered_off {
+            return;
+        }
+        state.powered_off = true;
+
+        backend.set_output_powered(self, output, false);
+    }
+
+    pub fn power_on_output(&mut self, backend: &mut Backend, output: &Output) {
+        let state = self.output_state.get_mut(output).unwrap();
+        if !state.powered_off {
+            return;
+        }
+        state.powered_off = false;
+
+        backend.set_output_powered(self, output, true);
+        self.queue_redraw(output);
+    }
+
     pub fn output_under(&self, pos: Point<f64, Logical>) -> Option<(&Output, Point<f64, Logical>)> {
         let output = self.global_space.output_under(pos).next()?;
         let pos_within_output = pos
@@ -2988,16 +4049,33 @@ impl Niri {
     }
 
     fn is_inside_hot_corner(&self, output: &Output, pos: Point<f64, Logical>) -> bool {
+        self.hot_corner_under(output, pos).is_some()
+    }
+
+    /// Returns the action of the hot corner at the position, and how long the pointer must stay
+    /// there before it runs.
+    pub fn hot_corner_under(
+        &self,
+        output: &Output,
+        pos: Point<f64, Logical>,
+    ) -> Option<(Action, Duration)> {
         let config = self.config.borrow();
         let hot_corners = output
             .user_data()
             .get::<OutputName>()
             .and_then(|name| config.outputs.find(name))
-            .and_then(|c| c.hot_corners)
-            .unwrap_or(config.gestures.hot_corners);
+            .and_then(|c| c.hot_corners.as_ref())
+            .unwrap_or(&config.gestures.hot_corners);
 
         if hot_corners.off {
-            return false;
+            return None;
+        }
+
+        if hot_corners.disable_while_fullscreen() {
+            let mon = self.layout.monitor_for_output(output)?;
+            if mon.render_above_top_layer() {
+                return None;
+            }
         }
 
         // Use size from the ceiled output geometry, since that's what we currently use for pointer
@@ -3009,25 +4087,29 @@ impl Niri {
             Rectangle::new(corner, Size::new(1., 1.)).contains(pos)
         };
 
-        if hot_corners.top_right && contains(Point::new(size.w - 1., 0.)) {
-            return true;
-        }
-        if hot_corners.bottom_left && contains(Point::new(0., size.h - 1.)) {
-            return true;
-        }
-        if hot_corners.bottom_right && contains(Point::new(size.w - 1., size.h - 1.)) {
-            return true;
-        }
-
         // If the user didn't explicitly set any corners, we default to top-left.
-        if (hot_corners.top_left
-            || !(hot_corners.top_right || hot_corners.bottom_right || hot_corners.bottom_left))
-            && contains(Point::new(0., 0.))
-        {
-            return true;
-        }
+        let default_top_left = HotCorner::default();
+        let top_left = hot_corners.top_left.as_ref().or_else(|| {
+            let any_set = hot_corners.top_right.is_some()
+                || hot_corners.bottom_right.is_some()
+                || hot_corners.bottom_left.is_some();
+            (!any_set).then_some(&default_top_left)
+        });
 
-        false
+        let right = size.w - 1.;
+        let bottom = size.h - 1.;
+        let corners = [
+            (hot_corners.top_right.as_ref(), Point::new(right, 0.)),
+            (hot_corners.bottom_left.as_ref(), Point::new(0., bottom)),
+            (hot_corners.bottom_right.as_ref(), Point::new(right, bottom)),
+            (top_left, Point::new(0., 0.)),
+        ];
+        let corner = corners
+            .into_iter()
+            .find_map(|(corner, loc)| corner.filter(|_| contains(loc)))?;
+
+        let delay = Duration::from_millis(u64::from(hot_corners.delay_ms.unwrap_or(0)));
+        Some((corner.action.clone(), delay))
     }
 
     pub fn is_sticky_obscured_under(
@@ -3068,15 +4150,15 @@ impl Niri {
             return true;
         }
 
+        if self.is_inside_hot_corner(output, pos_within_output) {
+            return true;
+        }
+
         let mon = self.layout.monitor_for_output(output).unwrap();
         if mon.render_above_top_layer() {
             return false;
         }
 
-        if self.is_inside_hot_corner(output, pos_within_output) {
-            return true;
-        }
-
         if layer_popup_under(Layer::Top) || layer_toplevel_under(Layer::Top) {
             return true;
         }
@@ -3337,6 +4419,11 @@ impl Niri {
 
         let is_overview_open = self.layout.is_overview_open();
 
+        if self.is_inside_hot_corner(output, pos_within_output) {
+            rv.hot_corner = true;
+            return rv;
+        }
+
         // When rendering above the top layer, we put the regular monitor elements first.
         // Otherwise, we will render all layer-shell pop-ups and the top layer on top.
         if mon.render_above_top_layer() {
@@ -3350,11 +4437,6 @@ impl Niri {
                 .or_else(|| layer_toplevel_under(Layer::Bottom))
                 .or_else(|| layer_toplevel_under(Layer::Background));
         } else {
-            if self.is_inside_hot_corner(output, pos_within_output) {
-                rv.hot_corner = true;
-                return rv;
-            }
-
             under = under
                 .or_else(|| layer_popup_under(Layer::Top))
                 .or_else(|| layer_toplevel_under(Layer::Top));
@@ -3542,6 +4624,21 @@ impl Niri {
         map_to_output.and_then(|name| self.output_by_name_match(name))
     }
 
+    /// Returns the output of the focused tile, and its visual rectangle in the global space.
+    pub fn focused_tile_global_rect(&self) -> Option<(&Output, Rectangle<f64, Logical>)> {
+        if !self.keyboard_focus.is_layout() {
+            return None;
+        }
+
+        let output = self.layout.active_output()?;
+        let monitor = self.layout.monitor_for_output(output).unwrap();
+        let mut rect = monitor.active_tile_visual_rectangle()?;
+
+        let output_geo = self.global_space.output_geometry(output).unwrap();
+        rect.loc += output_geo.loc.to_f64();
+        Some((output, rect))
+    }
+
     pub fn output_for_touch(&self) -> Option<&Output> {
         let config = self.config.borrow();
         let map_to_output = config.input.touch.map_to_output.as_ref();
@@ -3550,6 +4647,46 @@ impl Niri {
             .or_else(|| self.global_space.outputs().next())
     }
 
+    /// Selects the output profile matching the connected outputs.
+    ///
+    /// If a different profile matches, this recomputes the output config so that outputs get
+    /// configured with the profile applied right away. The rest of the activation happens in
+    /// [`State::refresh_output_profile()`].
+    pub fn select_output_profile(&mut self, connected: &[OutputName]) {
+        let config = self.config.borrow();
+        let profile = config.output_profiles.find_active(connected);
+        let name = profile.map(|profile| profile.name.clone());
+        drop(config);
+
+        if name == self.active_output_profile {
+            return;
+        }
+
+        // Switching profiles replaces the whole output config at once, dropping any transient
+        // changes made via IPC.
+        self.active_output_profile = name;
+        self.apply_output_profile();
+        self.output_profile_changed = true;
+    }
+
+    /// Recomputes the output config from the config file and the active output profile.
+    pub fn apply_output_profile(&mut self) {
+        let mut config = self.config.borrow_mut();
+        let profile = self.active_output_profile.as_ref().and_then(|name| {
+            config
+                .output_profiles
+                .0
+                .iter()
+                .find(|profile| profile.name == *name)
+        });
+
+        let outputs = match profile {
+            Some(profile) => profile.apply_to(&self.config_file_output_config),
+            None => self.config_file_output_config.clone(),
+        };
+        config.outputs = outputs;
+    }
+
     pub fn output_by_name_match(&self, target: &str) -> Option<&Output> {
         self.global_space
             .outputs()
@@ -3914,9 +5051,157 @@ impl Niri {
             KeyboardFocus::ExitConfirmDialog => true,
             KeyboardFocus::Overview => true,
             KeyboardFocus::Mru => true,
+            KeyboardFocus::ColumnGrid => true,
+            KeyboardFocus::ClipboardPicker => true,
         };
 
         self.layout.refresh(layout_is_active);
+        self.schedule_batched_configures();
+        self.schedule_tab_title_redraw();
+    }
+
+    /// Makes sure the event loop wakes up to send size configures that are currently batched.
+    ///
+    /// Batched configures are sent from layout refresh, which normally happens often enough
+    /// during resizes, but the final size must not get stuck when nothing else wakes us up.
+    fn schedule_batched_configures(&mut self) {
+        if self.configure_batch_timer.is_some() {
+            return;
+        }
+
+        let deadline = self
+            .layout
+            .windows()
+            .filter_map(|(_, mapped)| mapped.batched_configure_deadline())
+            .min();
+        let Some(deadline) = deadline else {
+            return;
+        };
+
+        let timer = Timer::from_duration(deadline.saturating_sub(get_monotonic_time()));
+        let token = self
+            .event_loop
+            .insert_source(timer, |_, _, state| {
+                // The layout refresh at the end of this loop iteration will send the configures.
+                state.niri.configure_batch_timer = None;
+                TimeoutAction::Drop
+            })
+            .unwrap();
+        self.configure_batch_timer = Some(token);
+    }
+
+    /// Makes sure that debounced tab title changes get drawn once their debounce passes.
+    fn schedule_tab_title_redraw(&mut self) {
+        let Some(deadline) = self.layout.pending_tab_title_deadline() else {
+            return;
+        };
+
+        if let Some((token, current)) = self.tab_title_timer {
+            if current <= deadline {
+                return;
+            }
+            self.event_loop.remove(token);
+        }
+
+        let timer = Timer::from_duration(deadline.saturating_sub(get_monotonic_time()));
+        let token = self
+            .event_loop
+            .insert_source(timer, |_, _, state| {
+                state.niri.tab_title_timer = None;
+                state.niri.queue_redraw_all();
+                TimeoutAction::Drop
+            })
+            .unwrap();
+        self.tab_title_timer = Some((token, deadline));
+    }
+
+    /// Releases wp-commit-timing commits whose target time is due by the next presentation on the
+    /// surface's output.
+    ///
+    /// Commits timed further ahead are left alone, and a timer wakes us up once they are due.
+    pub fn signal_commit_timers(&mut self) {
+        let _span = tracy_client::span!("Niri::signal_commit_timers");
+
+        let now = get_monotonic_time();
+        let targets: HashMap<_, _> = self
+            .output_state
+            .iter()
+            .map(|(output, state)| {
+                let clock = &state.frame_clock;
+                let target = clock.next_presentation_time();
+                (output.clone(), (target, clock.refresh_interval()))
+            })
+            .collect();
+        let mut next_wakeup: Option<Duration> = None;
+
+        let blocker_cleared_tx = &self.blocker_cleared_tx;
+        let mut signal = |surface: &WlSurface, states: &SurfaceData| {
+            let Some(timer) = states.data_map.get::<CommitTimerBarrierStateUserData>() else {
+                return;
+            };
+            let mut timer = timer.lock().unwrap();
+            let Some(deadline) = timer.next_deadline() else {
+                return;
+            };
+            let deadline = Duration::from(deadline);
+
+            // Surfaces that aren't visible anywhere get their commits released right on time.
+            let output = surface_primary_scanout_output(surface, states);
+            let (target, refresh) = output
+                .as_ref()
+                .and_then(|o| targets.get(o))
+                .copied()
+                .unwrap_or((now, None));
+
+            if target < deadline {
+                // Wake up once the presentation showing this commit is the next one.
+                let wakeup = deadline.saturating_sub(refresh.unwrap_or_default());
+                let wakeup = wakeup.max(target);
+                next_wakeup = Some(next_wakeup.map_or(wakeup, |next| next.min(wakeup)));
+                return;
+            }
+
+            timer.signal_until(target);
+            drop(timer);
+
+            if let Some(client) = surface.client() {
+                let _ = blocker_cleared_tx.send(client);
+            }
+        };
+
+        self.layout.with_windows(|mapped, _, _, _| {
+            mapped.window.with_surfaces(&mut signal);
+        });
+
+        for output in targets.keys() {
+            for surface in layer_map_for_output(output).layers() {
+                surface.with_surfaces(&mut signal);
+            }
+        }
+
+        if let Some(wakeup) = next_wakeup {
+            self.schedule_commit_timer_wakeup(wakeup, now);
+        }
+    }
+
+    fn schedule_commit_timer_wakeup(&mut self, wakeup: Duration, now: Duration) {
+        if let Some((token, scheduled)) = self.commit_timer_wakeup {
+            if scheduled <= wakeup {
+                return;
+            }
+            self.event_loop.remove(token);
+        }
+
+        let timer = Timer::from_duration(wakeup.saturating_sub(now));
+        let token = self
+            .event_loop
+            .insert_source(timer, |_, _, state| {
+                // The refresh at the end of this loop iteration will release the commits.
+                state.niri.commit_timer_wakeup = None;
+                TimeoutAction::Drop
+            })
+            .unwrap();
+        self.commit_timer_wakeup = Some((token, wakeup));
     }
 
     pub fn refresh_idle_inhibit(&mut self) {
@@ -3924,12 +5209,39 @@ impl Niri {
 
         self.idle_inhibiting_surfaces.retain(|s| s.is_alive());
 
-        let is_inhibited = self.is_fdo_idle_inhibited.load(Ordering::SeqCst)
+        let is_visible = |surface: &WlSurface| {
+            with_states(surface, |states| {
+                surface_primary_scanout_output(surface, states).is_some()
+            })
+        };
+
+        let mut is_inhibited = self.is_fdo_idle_inhibited.load(Ordering::SeqCst)
             || self.idle_inhibiting_surfaces.iter().any(|surface| {
-                with_states(surface, |states| {
-                    surface_primary_scanout_output(surface, states).is_some()
-                })
+                let mut root = surface.clone();
+                while let Some(parent) = get_parent(&root) {
+                    root = parent;
+                }
+
+                // The inhibit-idle window rule can make niri ignore the window's inhibitors.
+                if let Some((mapped, _)) = self.layout.find_window_and_output(&root) {
+                    if mapped.rules().inhibit_idle == Some(false) {
+                        return false;
+                    }
+                }
+
+                is_visible(surface)
             });
+
+        if !is_inhibited {
+            self.layout.with_windows(|mapped, _, _, _| {
+                if mapped.rules().inhibit_idle == Some(true)
+                    && is_visible(mapped.toplevel().wl_surface())
+                {
+                    is_inhibited = true;
+                }
+            });
+        }
+
         self.idle_notifier_state.set_is_inhibited(is_inhibited);
     }
 
@@ -3967,6 +5279,10 @@ impl Niri {
         });
         drop(config);
 
+        if !windows.is_empty() {
+            self.keyboard_shortcuts_inhibit_dirty = true;
+        }
+
         for win in windows {
             self.layout.update_window(&win, None);
             win.toplevel()
@@ -3978,38 +5294,182 @@ impl Niri {
         }
     }
 
-    pub fn advance_animations(&mut self) {
-        let _span = tracy_client::span!("Niri::advance_animations");
+    /// Dims the parents of modal dialogs and moves the focus from them to the dialogs.
+    pub fn refresh_modal_dialogs(&mut self) {
+        let _span = tracy_client::span!("Niri::refresh_modal_dialogs");
 
-        self.layout.advance_animations();
-        self.config_error_notification.advance_animations();
-        self.exit_confirm_dialog.advance_animations();
-        self.screenshot_ui.advance_animations();
-        self.window_mru_ui.advance_animations();
+        // Parent surface -> (dialog window, dialog surface, whether to dim the parent).
+        let mut modal_parents = HashMap::new();
+        self.layout.with_windows(|mapped, _, _, _| {
+            let toplevel = mapped.toplevel();
+            if !is_modal_dialog(toplevel) {
+                return;
+            }
 
-        for state in self.output_state.values_mut() {
-            if let Some(transition) = &mut state.screen_transition {
-                if transition.is_done() {
-                    state.screen_transition = None;
+            if let Some(parent) = toplevel.parent() {
+                let dialog = (
+                    mapped.window.clone(),
+                    toplevel.wl_surface().clone(),
+                    mapped.rules().dim_parent,
+                );
+                modal_parents.insert(parent, dialog);
+            }
+        });
+
+        let mut outputs = HashSet::new();
+        self.layout.with_windows_mut(|mapped, output| {
+            let dim = modal_parents
+                .get(mapped.toplevel().wl_surface())
+                .is_some_and(|(_, _, dim)| *dim);
+            if mapped.set_is_dimmed_by_modal_dialog(dim) {
+                if let Some(output) = output {
+                    outputs.insert(output.clone());
                 }
             }
+        });
+        for output in outputs {
+            self.queue_redraw(&output);
+        }
+
+        // Follow nested modal dialogs up to the topmost one. The iteration limit guards against
+        // parent cycles.
+        let mut target = None;
+        if let Some(focus) = self.layout.focus() {
+            let mut surface = focus.toplevel().wl_surface().clone();
+            for _ in 0..modal_parents.len() {
+                let Some((window, dialog_surface, _)) = modal_parents.get(&surface) else {
+                    break;
+                };
+                target = Some(window.clone());
+                surface = dialog_surface.clone();
+            }
+        }
+
+        if let Some(window) = target {
+            trace!("redirecting focus to the modal dialog");
+            self.layout.activate_window(&window);
         }
     }
 
-    pub fn update_render_elements(&mut self, output: Option<&Output>) {
-        self.layout.update_render_elements(output);
+    /// Returns whether the window rules let this surface inhibit keyboard shortcuts.
+    ///
+    /// Returns `None` for windows whose rules aren't resolved yet, before the initial configure.
+    pub fn keyboard_shortcuts_inhibit_rule(
+        &self,
+        surface: &WlSurface,
+    ) -> Option<KeyboardShortcutsInhibit> {
+        if let Some((mapped, _)) = self.layout.find_window_and_output(surface) {
+            return Some(mapped.rules().keyboard_shortcuts_inhibit);
+        }
 
-        for (out, state) in self.output_state.iter_mut() {
-            if output.is_none_or(|output| out == output) {
-                let scale = Scale::from(out.current_scale().fractional_scale());
-                let transform = out.current_transform();
+        if let Some(unmapped) = self.unmapped_windows.get(surface) {
+            if let InitialConfigureState::Configured { rules, .. } = &unmapped.state {
+                return Some(rules.keyboard_shortcuts_inhibit);
+            }
+            return None;
+        }
 
-                if let Some(transition) = &mut state.screen_transition {
-                    transition.update_render_elements(scale, transform);
-                }
+        // Not a window, for example a layer-shell surface.
+        Some(KeyboardShortcutsInhibit::default())
+    }
 
-                let layer_map = layer_map_for_output(out);
-                for surface in layer_map.layers() {
+    /// Applies the window rules to shortcut inhibitors and updates the on-screen indicator.
+    ///
+    /// Only does anything after the focus, the inhibitors or the window rules changed.
+    pub fn refresh_keyboard_shortcuts_inhibit(&mut self) {
+        let _span = tracy_client::span!("Niri::refresh_keyboard_shortcuts_inhibit");
+
+        if !mem::take(&mut self.keyboard_shortcuts_inhibit_dirty) {
+            return;
+        }
+
+        // Inhibitors created before the window rules were resolved start out inactive. Activate
+        // the allowed ones once the rules are known; the denied ones are handled below.
+        let mut pending = mem::take(&mut self.keyboard_shortcuts_inhibitors_pending);
+        pending.retain(|surface| {
+            let Some(rule) = self.keyboard_shortcuts_inhibit_rule(surface) else {
+                return true;
+            };
+
+            if rule == KeyboardShortcutsInhibit::Allow {
+                if let Some(inhibitor) = self.keyboard_shortcuts_inhibiting_surfaces.get(surface) {
+                    inhibitor.activate();
+                }
+            }
+            false
+        });
+        self.keyboard_shortcuts_inhibitors_pending = pending;
+
+        // The window rules may have changed since the inhibitor was activated.
+        for (surface, inhibitor) in &self.keyboard_shortcuts_inhibiting_surfaces {
+            if inhibitor.is_active()
+                && self.keyboard_shortcuts_inhibit_rule(surface)
+                    == Some(KeyboardShortcutsInhibit::Deny)
+            {
+                inhibitor.inactivate();
+            }
+        }
+
+        let state = self
+            .keyboard_focus
+            .surface()
+            .and_then(|surface| {
+                let inhibitor = self.keyboard_shortcuts_inhibiting_surfaces.get(surface)?;
+                if inhibitor.is_active() {
+                    Some(IndicatorState::Inhibiting)
+                } else if self.keyboard_shortcuts_inhibit_rule(surface)
+                    == Some(KeyboardShortcutsInhibit::Ask)
+                {
+                    Some(IndicatorState::Asking)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(IndicatorState::Hidden);
+
+        if self.shortcuts_inhibit_indicator.set_state(state) {
+            self.queue_redraw_all();
+        }
+    }
+
+    pub fn advance_animations(&mut self) {
+        let _span = tracy_client::span!("Niri::advance_animations");
+
+        self.layout.advance_animations();
+        self.config_error_notification.advance_animations();
+        self.exit_confirm_dialog.advance_animations();
+        self.screenshot_ui.advance_animations();
+        self.window_mru_ui.advance_animations();
+
+        for state in self.output_state.values_mut() {
+            if let Some(transition) = &mut state.screen_transition {
+                if transition.is_done() {
+                    state.screen_transition = None;
+                }
+            }
+
+            state.closing_layers.retain(|closing| !closing.is_done());
+        }
+
+        for mapped in self.mapped_layer_surfaces.values_mut() {
+            mapped.advance_animations();
+        }
+    }
+
+    pub fn update_render_elements(&mut self, output: Option<&Output>) {
+        self.layout.update_render_elements(output);
+
+        for (out, state) in self.output_state.iter_mut() {
+            if output.is_none_or(|output| out == output) {
+                let scale = Scale::from(out.current_scale().fractional_scale());
+                let transform = out.current_transform();
+
+                if let Some(transition) = &mut state.screen_transition {
+                    transition.update_render_elements(scale, transform);
+                }
+
+                let layer_map = layer_map_for_output(out);
+                for surface in layer_map.layers() {
                     let Some(mapped) = self.mapped_layer_surfaces.get_mut(surface) else {
                         continue;
                     };
@@ -4038,6 +5498,11 @@ impl Niri {
         include_pointer: bool,
         target: RenderTarget,
     ) -> Vec<OutputRenderElements<R>> {
+        // Shaders live in the renderer, which may be a different one for every output.
+        shaders::Shaders::get(renderer)
+            .debug_premultiplied_alpha
+            .set(self.debug_draw_premultiplied_alpha);
+
         let mut elements = Vec::new();
         self.render_inner(renderer, output, include_pointer, target, &mut |elem| {
             elements.push(elem)
@@ -4107,6 +5572,20 @@ impl Niri {
                     Kind::ScanoutCandidate,
                     &mut |elem| push(elem.into()),
                 );
+
+                // Draw the blurred snapshot behind the lock surface. Keep it out of screencasts
+                // and screen captures since it was rendered without their block-out rules.
+                if let (RenderTarget::Output, Some(buffer)) = (target, &state.lock_backdrop) {
+                    let elem = TextureRenderElement::from_texture_buffer(
+                        buffer.clone(),
+                        (0., 0.),
+                        1.,
+                        None,
+                        None,
+                        Kind::Unspecified,
+                    );
+                    push(PrimaryGpuTextureRenderElement(elem).into());
+                }
             }
 
             // Draw the solid color background.
@@ -4149,12 +5628,42 @@ impl Niri {
             push(element.into());
         }
 
+        // Then, the clipboard history picker. Keep the clipboard contents out of screencasts and
+        // screen captures.
+        if target == RenderTarget::Output {
+            if let Some(element) = self.clipboard_picker.render(renderer, output) {
+                push(element.into());
+            }
+        }
+
+        // Then, the keyboard shortcuts inhibit indicator on the active output.
+        if self.layout.active_output() == Some(output) {
+            if let Some(element) = self.shortcuts_inhibit_indicator.render(renderer, output) {
+                push(element.into());
+            }
+        }
+
+        // Then, the pending key sequence hint.
+        if let Some(element) = self.key_sequence_hint.render(renderer, output) {
+            push(element.into());
+        }
+
         // Then, the Alt-Tab switcher.
         self.window_mru_ui
             .render_output(self, output, renderer, target, &mut |elem| {
                 push(elem.into())
             });
 
+        // And the column grid.
+        self.column_grid
+            .render_output(self, output, renderer, target, &mut |elem| {
+                push(elem.into())
+            });
+
+        // Then, the overlay widgets.
+        self.overlay_widgets
+            .render(renderer, output, &mut |elem| push(elem.into()));
+
         // Don't draw the focus ring on the workspaces while interactively moving above those
         // workspaces, since the interactively-moved window already has a focus ring.
         let focus_ring = !self.layout.interactive_move_is_moving_above_output(output);
@@ -4187,6 +5696,7 @@ impl Niri {
             ($layer:expr, $backdrop:expr, $push:expr) => {{
                 self.render_layer_normal(
                     renderer,
+                    output,
                     target,
                     &layer_map,
                     $layer,
@@ -4256,18 +5766,29 @@ impl Niri {
                 }};
             }
 
-            for (_ws, geo) in mon.workspaces_with_render_geo() {
-                push_popups_from_layer!(Layer::Bottom, process!(geo));
-                push_popups_from_layer!(Layer::Background, process!(geo));
-            }
+            if let Some(elem) = self.render_workspace_switch_shader(
+                renderer,
+                output,
+                target,
+                focus_ring,
+                &layer_map,
+                fx_buffers.clone(),
+            ) {
+                push(elem.into());
+            } else {
+                for (_ws, geo) in mon.workspaces_with_render_geo() {
+                    push_popups_from_layer!(Layer::Bottom, process!(geo));
+                    push_popups_from_layer!(Layer::Background, process!(geo));
+                }
 
-            mon.render_workspaces(renderer, target, focus_ring, &mut |elem| push(elem.into()));
+                mon.render_workspaces(renderer, target, focus_ring, &mut |elem| push(elem.into()));
 
-            for (ws, geo) in mon.workspaces_with_render_geo() {
-                push_normal_from_layer!(Layer::Bottom, process!(geo));
-                push_normal_from_layer!(Layer::Background, process!(geo));
+                for (ws, geo) in mon.workspaces_with_render_geo() {
+                    push_normal_from_layer!(Layer::Bottom, process!(geo));
+                    push_normal_from_layer!(Layer::Background, process!(geo));
 
-                process!(geo)(ws.render_background());
+                    process!(geo)(ws.render_background());
+                }
             }
         }
 
@@ -4328,6 +5849,7 @@ impl Niri {
                     ($layer:expr, $backdrop:expr, $push:expr) => {{
                         self.render_layer_normal(
                             gles_renderer,
+                            output,
                             target,
                             &layer_map,
                             $layer,
@@ -4451,9 +5973,11 @@ impl Niri {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn render_layer_normal<R: NiriRenderer>(
         &self,
         renderer: &mut R,
+        output: &Output,
         target: RenderTarget,
         layer_map: &LayerMap,
         layer: Layer,
@@ -4461,6 +5985,15 @@ impl Niri {
         push: &mut dyn FnMut(LayerSurfaceRenderElement<R>),
         fx_buffers: Option<EffectsFramebuffersUserData>,
     ) {
+        // Closing layers go on top since they were most likely the last ones to be mapped.
+        if let Some(state) = self.output_state.get(output) {
+            for closing in &state.closing_layers {
+                if closing.layer() == layer && closing.place_within_backdrop() == for_backdrop {
+                    closing.render(target, push);
+                }
+            }
+        }
+
         for (mapped, geo) in self.layers_in_render_order(layer_map, layer, for_backdrop) {
             mapped.render_normal(renderer, geo.loc.to_f64(), target, push, fx_buffers.clone());
         }
@@ -4480,6 +6013,87 @@ impl Niri {
         }
     }
 
+    /// Renders the ongoing workspace switch with the custom workspace switch shader.
+    ///
+    /// Returns `None` if the workspaces should be rendered normally.
+    fn render_workspace_switch_shader<R: NiriRenderer>(
+        &self,
+        renderer: &mut R,
+        output: &Output,
+        target: RenderTarget,
+        focus_ring: bool,
+        layer_map: &LayerMap,
+        fx_buffers: Option<EffectsFramebuffersUserData>,
+    ) -> Option<MonitorRenderElement<R>> {
+        let mon = self.layout.monitor_for_output(output)?;
+        let (from, to, progress) = mon.workspace_switch_shader_workspaces()?;
+        if shaders::Shaders::get(renderer)
+            .program(ProgramType::WorkspaceSwitch)
+            .is_none()
+        {
+            return None;
+        }
+
+        let _span = tracy_client::span!("Niri::render_workspace_switch_shader");
+
+        let renderer = renderer.as_gles_renderer();
+        let output_scale = Scale::from(output.current_scale().fractional_scale());
+        let geo = Rectangle::from_size(mon.view_size());
+
+        // Snapshot the workspaces with their bottom and background layers, in the same order as
+        // they are rendered without the shader.
+        let mut snapshot = |ws: &Workspace<Mapped>| {
+            let mut elements: Vec<OutputRenderElements<GlesRenderer>> = Vec::new();
+
+            // Macro instead of closure to avoid borrowing elements.
+            macro_rules! process {
+                () => {{
+                    &mut |elem| {
+                        if let Some(elem) = scale_relocate_crop(elem, output_scale, 1., geo) {
+                            elements.push(elem.into());
+                        }
+                    }
+                }};
+            }
+
+            for layer in [Layer::Bottom, Layer::Background] {
+                self.render_layer_popups(renderer, target, layer_map, layer, false, process!());
+            }
+
+            mon.render_workspace(renderer, ws, target, focus_ring, &mut |elem| {
+                elements.push(elem.into())
+            });
+
+            for layer in [Layer::Bottom, Layer::Background] {
+                self.render_layer_normal(
+                    renderer,
+                    output,
+                    target,
+                    layer_map,
+                    layer,
+                    false,
+                    process!(),
+                    fx_buffers.clone(),
+                );
+            }
+
+            process!()(ws.render_background());
+
+            elements
+        };
+
+        let from = snapshot(from);
+        let to = snapshot(to);
+
+        match mon.render_workspace_switch_shader(renderer, &from, &to, progress) {
+            Ok(elem) => Some(elem),
+            Err(err) => {
+                warn!("error rendering workspace switch shader: {err:?}");
+                None
+            }
+        }
+    }
+
     fn redraw(&mut self, backend: &mut Backend, output: &Output) {
         let _span = tracy_client::span!("Niri::redraw");
 
@@ -4507,6 +6121,7 @@ impl Niri {
             state.unfinished_animations_remain |= self.screenshot_ui.are_animations_ongoing();
             state.unfinished_animations_remain |= self.window_mru_ui.are_animations_ongoing();
             state.unfinished_animations_remain |= state.screen_transition.is_some();
+            state.unfinished_animations_remain |= !state.closing_layers.is_empty();
 
             // Also keep redrawing if the current cursor is animated.
             state.unfinished_animations_remain |= self
@@ -4522,7 +6137,16 @@ impl Niri {
             }
 
             // Render.
+            let render_start = get_monotonic_time();
             res = backend.render(self, output, target_presentation_time);
+
+            if res == RenderResult::Submitted {
+                let render_end = get_monotonic_time();
+                let state = self.output_state.get_mut(output).unwrap();
+                state
+                    .frame_stats
+                    .rendered(render_end.saturating_sub(render_start), render_end);
+            }
         }
 
         let is_locked = self.is_locked();
@@ -4543,7 +6167,7 @@ impl Niri {
         // Update the lock render state on successful render, or if monitors are inactive. When
         // monitors are inactive on a TTY, they have no framebuffer attached, so no sensitive data
         // from a last render will be visible.
-        if res != RenderResult::Skipped || !self.monitors_active {
+        if res != RenderResult::Skipped || !self.monitors_active || state.powered_off {
             state.lock_render_state = if is_locked {
                 LockRenderState::Locked
             } else {
@@ -4640,6 +6264,80 @@ impl Niri {
         backend.set_output_on_demand_vrr(self, output, current);
     }
 
+    /// Returns whether the output can present with tearing page flips.
+    ///
+    /// This is the case when a fullscreen window with the allow-tearing rule is in view and it
+    /// asked for async presentation through the tearing-control protocol.
+    pub fn output_wants_tearing(&self, output: &Output) -> bool {
+        let Some(mon) = self.layout.monitor_for_output(output) else {
+            return false;
+        };
+
+        // render_above_top_layer() checks that the active window is fullscreen and the view is
+        // stationary.
+        if !mon.render_above_top_layer() {
+            return false;
+        }
+
+        let Some(mapped) = mon.active_window() else {
+            return false;
+        };
+
+        mapped.rules().allow_tearing
+            && tearing_control::wants_tearing(mapped.toplevel().wl_surface())
+    }
+
+    /// Returns the image description of the signal that the output sends to the display.
+    pub fn output_image_description(&self, output: &Output) -> ImageDescription {
+        let name = output.user_data().get::<OutputName>().unwrap();
+        let config = self.config.borrow();
+        let Some(hdr) = config.outputs.find(name).and_then(|c| c.hdr) else {
+            return ImageDescription::SRGB;
+        };
+
+        // With PQ, the luminances are fixed by the transfer function, and the display range is
+        // described by the mastering luminance.
+        let min_lum = hdr
+            .min_luminance
+            .map_or(50, |lum| (lum.0 * 10000.).round() as u32);
+        let max_lum = hdr.max_luminance.map_or(1000, u32::from);
+        ImageDescription {
+            tf: TransferFunction::St2084Pq,
+            primaries: Primaries::Bt2020,
+            luminances: None,
+            mastering_primaries: None,
+            mastering_luminance: Some((min_lum, max_lum)),
+            max_cll: hdr.max_cll.map(u32::from),
+            max_fall: hdr.max_fall.map(u32::from),
+        }
+    }
+
+    /// Returns whether a window mainly shown on this output tagged its content as HDR.
+    pub fn output_shows_hdr_content(&self, output: &Output) -> bool {
+        let mut hdr = false;
+        for mapped in self.layout.windows_for_output(output) {
+            mapped.window.with_surfaces(|surface, states| {
+                if surface_primary_scanout_output(surface, states).as_ref() == Some(output) {
+                    hdr |= surface_image_description(states).is_some_and(|d| d.is_hdr());
+                }
+            });
+        }
+        hdr
+    }
+
+    /// Returns whether the element is the surface of a window on this output tagged as HDR.
+    pub fn is_hdr_surface_element(&self, output: &Output, id: &Id) -> bool {
+        let mut hdr = false;
+        for mapped in self.layout.windows_for_output(output) {
+            mapped.window.with_surfaces(|surface, states| {
+                if Id::from_wayland_resource(surface) == *id {
+                    hdr |= surface_image_description(states).is_some_and(|d| d.is_hdr());
+                }
+            });
+        }
+        hdr
+    }
+
     pub fn update_primary_scanout_output(
         &self,
         output: &Output,
@@ -4868,17 +6566,9 @@ impl Niri {
 
         let state = self.output_state.get(output).unwrap();
         let sequence = state.frame_callback_sequence;
+        let blocker_cleared_tx = &self.blocker_cleared_tx;
 
-        let should_send = |surface: &WlSurface, states: &SurfaceData| {
-            // Do the standard primary scanout output check. For pointer surfaces it deduplicates
-            // the frame callbacks across potentially multiple outputs, and for regular windows and
-            // layer-shell surfaces it avoids sending frame callbacks to invisible surfaces.
-            let current_primary_output = surface_primary_scanout_output(surface, states);
-            if current_primary_output.as_ref() != Some(output) {
-                return None;
-            }
-
-            // Next, check the throttling status.
+        let throttle = |surface: &WlSurface, states: &SurfaceData| {
             let frame_throttling_state = states
                 .data_map
                 .get_or_insert(SurfaceFrameThrottlingState::default);
@@ -4896,21 +6586,56 @@ impl Niri {
 
             if send {
                 *last_sent_at = Some((output.clone(), sequence));
+
+                // A new refresh cycle also lets through the next FIFO commit.
+                signal_fifo_barrier(surface, states, blocker_cleared_tx);
+
                 Some(output.clone())
             } else {
                 None
             }
         };
 
+        let should_send = |surface: &WlSurface, states: &SurfaceData| {
+            // Do the standard primary scanout output check. For pointer surfaces it deduplicates
+            // the frame callbacks across potentially multiple outputs, and for regular windows and
+            // layer-shell surfaces it avoids sending frame callbacks to invisible surfaces.
+            let current_primary_output = surface_primary_scanout_output(surface, states);
+            if current_primary_output.as_ref() != Some(output) {
+                return None;
+            }
+
+            // Next, check the throttling status.
+            throttle(surface, states)
+        };
+
+        // Windows with the keep-rendering rule get frame callbacks at the refresh rate of their
+        // output, even when they are occluded or on a hidden workspace.
+        let should_send_keep_rendering = throttle;
+
         let frame_callback_time = get_monotonic_time();
 
         for mapped in self.layout.windows_for_output_mut(output) {
-            mapped.send_frame(
-                output,
-                frame_callback_time,
-                FRAME_CALLBACK_THROTTLE,
-                should_send,
-            );
+            // Captured windows need to keep drawing for the capture to keep going.
+            let is_captured = mapped.is_window_cast_target()
+                || self
+                    .image_copy_capture_state
+                    .is_capturing(mapped.toplevel().wl_surface());
+            if mapped.rules().keep_rendering || is_captured {
+                mapped.send_frame(
+                    output,
+                    frame_callback_time,
+                    FRAME_CALLBACK_THROTTLE,
+                    should_send_keep_rendering,
+                );
+            } else {
+                mapped.send_frame(
+                    output,
+                    frame_callback_time,
+                    FRAME_CALLBACK_THROTTLE,
+                    should_send,
+                );
+            }
         }
 
         for surface in layer_map_for_output(output).layers() {
@@ -5012,12 +6737,22 @@ impl Niri {
 
         let frame_callback_time = get_monotonic_time();
 
+        // Surfaces that aren't visible anywhere never see a refresh cycle, so let their FIFO
+        // commits through here to avoid stalling them indefinitely.
+        let blocker_cleared_tx = &self.blocker_cleared_tx;
+        let signal_hidden = |surface: &WlSurface, states: &SurfaceData| -> Option<Output> {
+            if surface_primary_scanout_output(surface, states).is_none() {
+                signal_fifo_barrier(surface, states, blocker_cleared_tx);
+            }
+            None
+        };
+
         self.layout.with_windows_mut(|mapped, _| {
             mapped.send_frame(
                 output,
                 frame_callback_time,
                 FRAME_CALLBACK_THROTTLE,
-                |_, _| None,
+                signal_hidden,
             );
         });
 
@@ -5027,7 +6762,7 @@ impl Niri {
                     output,
                     frame_callback_time,
                     FRAME_CALLBACK_THROTTLE,
-                    |_, _| None,
+                    signal_hidden,
                 );
             }
 
@@ -5037,7 +6772,7 @@ impl Niri {
                     output,
                     frame_callback_time,
                     FRAME_CALLBACK_THROTTLE,
-                    |_, _| None,
+                    signal_hidden,
                 );
             }
         }
@@ -5048,7 +6783,7 @@ impl Niri {
                 output,
                 frame_callback_time,
                 FRAME_CALLBACK_THROTTLE,
-                |_, _| None,
+                signal_hidden,
             );
         }
 
@@ -5058,7 +6793,7 @@ impl Niri {
                 output,
                 frame_callback_time,
                 FRAME_CALLBACK_THROTTLE,
-                |_, _| None,
+                signal_hidden,
             );
         }
     }
@@ -5298,6 +7033,69 @@ impl Niri {
         Ok((sync, damages))
     }
 
+    pub fn render_for_image_copy_capture(&mut self, renderer: &mut GlesRenderer) {
+        let _span = tracy_client::span!("Niri::render_for_image_copy_capture");
+
+        let protocol_state = &mut self.image_copy_capture_state;
+        for frame in protocol_state.take_frames() {
+            let Some(session) = protocol_state.session_mut(frame.session()) else {
+                frame.fail(FailureReason::Stopped);
+                continue;
+            };
+
+            let Some((mapped, output)) = self.layout.find_window_and_output(session.surface())
+            else {
+                // The session will be stopped on the next refresh.
+                frame.fail(FailureReason::Stopped);
+                continue;
+            };
+
+            let scale = output.map_or(1., |output| output.current_scale().fractional_scale());
+            let scale = Scale::from(scale);
+            let bbox = mapped
+                .window
+                .bbox_with_popups()
+                .to_physical_precise_up(scale);
+            let size = bbox.size;
+
+            if !session.update_size(size, scale) {
+                frame.fail(FailureReason::BufferConstraints);
+                continue;
+            }
+
+            let mut elements = Vec::new();
+            mapped.render_for_screen_cast(renderer, scale, &mut |elem| elements.push(elem));
+
+            // Wait until the window draws something new.
+            let damage_tracker = session.damage_tracker();
+            let Ok((damage, _)) = damage_tracker.damage_output(1, &elements) else {
+                frame.fail(FailureReason::Unknown);
+                continue;
+            };
+            if damage.is_none() {
+                protocol_state.push_frame(frame);
+                continue;
+            }
+
+            let res = render_to_shm(
+                renderer,
+                frame.buffer(),
+                size,
+                scale,
+                Transform::Normal,
+                elements.iter().rev(),
+            );
+            match res {
+                Ok(()) => frame.submit(size),
+                Err(err) => {
+                    warn!("error rendering for image copy capture: {err:?}");
+                    session.reset_damage();
+                    frame.fail(FailureReason::Unknown);
+                }
+            }
+        }
+    }
+
     #[cfg(not(feature = "xdp-gnome-screencast"))]
     pub fn stop_casts_for_target(&mut self, _target: CastTarget) {}
 
@@ -5403,6 +7201,7 @@ impl Niri {
         write_to_disk: bool,
         include_pointer: bool,
         path: Option<String>,
+        reply: Option<async_channel::Sender<Result<String, String>>>,
     ) -> anyhow::Result<()> {
         let _span = tracy_client::span!("Niri::screenshot");
 
@@ -5429,10 +7228,11 @@ impl Niri {
             elements,
         )?;
 
-        self.save_screenshot(size, pixels, write_to_disk, path)
+        self.save_screenshot(size, pixels, write_to_disk, path, reply)
             .context("error saving screenshot")
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn screenshot_window(
         &self,
         renderer: &mut GlesRenderer,
@@ -5440,7 +7240,10 @@ impl Niri {
         mapped: &Mapped,
         write_to_disk: bool,
         show_pointer: bool,
+        include_border: bool,
+        include_shadow: bool,
         path: Option<String>,
+        reply: Option<async_channel::Sender<Result<String, String>>>,
     ) -> anyhow::Result<()> {
         let _span = tracy_client::span!("Niri::screenshot_window");
 
@@ -5452,6 +7255,17 @@ impl Niri {
                 mapped.rules().opacity.unwrap_or(1.).clamp(0., 1.)
             };
 
+        // To include the decorations, render the whole tile rather than just the window.
+        let tile = (include_border || include_shadow)
+            .then(|| self.layout.tile_for_window(&mapped.window))
+            .flatten()
+            .filter(|tile| tile.focused_window().window == mapped.window);
+
+        // Position of the window buffer in the rendered elements.
+        let buf_loc = tile.map_or(Point::from((0., 0.)), |tile| {
+            tile.window_loc() - mapped.window.geometry().loc.to_f64()
+        });
+
         let mut elements: Vec<WindowScreenshotRenderElement<GlesRenderer>> = Vec::new();
 
         // Add pointer if requested and it's over this window.
@@ -5459,7 +7273,9 @@ impl Niri {
             if let Some((_, win_pos)) = self.pointer_pos_for_window_cast(mapped) {
                 // Pointer elements are at output-local physical coords.
                 // Relocate by -win_pos to make them window-relative.
-                let pos = win_pos.to_physical_precise_round(scale).upscale(-1);
+                let pos = (win_pos - buf_loc)
+                    .to_physical_precise_round(scale)
+                    .upscale(-1);
                 self.render_pointer(renderer, output, &mut |elem| {
                     let elem = RelocateRenderElement::from_element(elem, pos, Relocate::Relative);
                     elements.push(elem.into());
@@ -5468,20 +7284,26 @@ impl Niri {
         }
         let pointer_count = elements.len();
 
-        mapped.render(
-            renderer,
-            mapped.window.geometry().loc.to_f64(),
-            scale,
-            alpha,
-            RenderTarget::ScreenCapture,
-            &mut |elem| elements.push(elem.into()),
-        );
-
-        // The pointer is not included in encompassing_geo because we don't want it to expand the
-        // screenshot size.
-        let geo = encompassing_geo(scale, elements.iter().skip(pointer_count));
-        let elements = elements.iter().rev().map(|elem| {
-            RelocateRenderElement::from_element(elem, geo.loc.upscale(-1), Relocate::Relative)
+        if let Some(tile) = tile {
+            tile.render_for_screenshot(renderer, include_border, include_shadow, &mut |elem| {
+                elements.push(elem.into())
+            });
+        } else {
+            mapped.render(
+                renderer,
+                mapped.window.geometry().loc.to_f64(),
+                scale,
+                alpha,
+                RenderTarget::ScreenCapture,
+                &mut |elem| elements.push(elem.into()),
+            );
+        }
+
+        // The pointer is not included in encompassing_geo because we don't want it to expand the
+        // screenshot size.
+        let geo = encompassing_geo(scale, elements.iter().skip(pointer_count));
+        let elements = elements.iter().rev().map(|elem| {
+            RelocateRenderElement::from_element(elem, geo.loc.upscale(-1), Relocate::Relative)
         });
         let pixels = render_to_vec(
             renderer,
@@ -5492,16 +7314,66 @@ impl Niri {
             elements,
         )?;
 
-        self.save_screenshot(geo.size, pixels, write_to_disk, path)
+        self.save_screenshot(geo.size, pixels, write_to_disk, path, reply)
             .context("error saving screenshot")
     }
 
+    pub fn render_window_thumbnail(
+        &self,
+        renderer: &mut GlesRenderer,
+        mapped: &Mapped,
+        output_scale: f64,
+        max_size: u32,
+    ) -> anyhow::Result<niri_ipc::WindowThumbnail> {
+        let _span = tracy_client::span!("Niri::render_window_thumbnail");
+
+        let size = mapped.size().to_f64();
+        ensure!(size.w > 0. && size.h > 0., "window has no size");
+
+        // Scale the window down to fit, but never render above the output scale.
+        let max_size = f64::from(max_size);
+        let scale = f64::min(max_size / size.w, max_size / size.h).min(output_scale);
+        let scale = Scale::from(scale);
+
+        let mut elements = Vec::new();
+        mapped.render_normal(
+            renderer,
+            mapped.window.geometry().loc.to_f64(),
+            scale,
+            1.,
+            RenderTarget::ScreenCapture,
+            &mut |elem| elements.push(elem),
+        );
+
+        let geo = encompassing_geo(scale, elements.iter());
+        ensure!(!geo.is_empty(), "window has nothing to render");
+        let elements = elements.iter().rev().map(|elem| {
+            RelocateRenderElement::from_element(elem, geo.loc.upscale(-1), Relocate::Relative)
+        });
+        let pixels = render_to_vec(
+            renderer,
+            geo.size,
+            scale,
+            Transform::Normal,
+            Fourcc::Abgr8888,
+            elements,
+        )?;
+
+        let width = u32::try_from(geo.size.w).context("invalid thumbnail width")?;
+        let height = u32::try_from(geo.size.h).context("invalid thumbnail height")?;
+        let mut png = Vec::new();
+        write_png_rgba8(&mut png, width, height, &pixels).context("error encoding thumbnail")?;
+
+        Ok(niri_ipc::WindowThumbnail { width, height, png })
+    }
+
     pub fn save_screenshot(
         &self,
         size: Size<i32, Physical>,
         pixels: Vec<u8>,
         write_to_disk: bool,
         path_arg: Option<String>,
+        reply: Option<async_channel::Sender<Result<String, String>>>,
     ) -> anyhow::Result<()> {
         let path = write_to_disk
             .then(|| {
@@ -5553,6 +7425,9 @@ impl Niri {
             let w = std::io::Cursor::new(&mut buf);
             if let Err(err) = write_png_rgba8(w, size.w as u32, size.h as u32, &pixels) {
                 warn!("error encoding screenshot image: {err:?}");
+                if let Some(reply) = reply {
+                    let _ = reply.send_blocking(Err(format!("error encoding screenshot: {err}")));
+                }
                 return;
             }
 
@@ -5560,6 +7435,7 @@ impl Niri {
             let _ = tx.send(buf.clone());
 
             let mut image_path = None;
+            let mut save_error = None;
 
             if let Some((path, create_parent)) = path {
                 debug!("saving screenshot to {path:?}");
@@ -5581,6 +7457,7 @@ impl Niri {
                     Ok(()) => image_path = Some(path),
                     Err(err) => {
                         warn!("error saving screenshot image: {err:?}");
+                        save_error = Some(format!("error saving screenshot to {path:?}: {err}"));
                     }
                 }
             } else {
@@ -5597,6 +7474,16 @@ impl Niri {
                 .as_ref()
                 .and_then(|p| p.to_str())
                 .map(|s| s.to_owned());
+
+            if let Some(reply) = reply {
+                let res = match (&path_string, save_error) {
+                    (Some(path), _) => Ok(path.clone()),
+                    (None, Some(err)) => Err(err),
+                    (None, None) => Err(String::from("the screenshot was not saved to disk")),
+                };
+                let _ = reply.send_blocking(res);
+            }
+
             let _ = event_tx.send(path_string);
         });
 
@@ -5809,10 +7696,90 @@ impl Niri {
 
         for output_state in self.output_state.values_mut() {
             output_state.lock_surface = None;
+            output_state.lock_backdrop = None;
         }
         self.queue_redraw_all();
     }
 
+    /// Captures a blurred snapshot of every output to draw behind the lock surfaces.
+    ///
+    /// The snapshot is taken once, so nothing that happens on screen after locking shows through.
+    pub fn capture_lock_backdrops(&mut self, renderer: &mut GlesRenderer) {
+        let _span = tracy_client::span!("Niri::capture_lock_backdrops");
+
+        let blur = self.config.borrow().session_lock.backdrop_blur;
+        if !blur.on || blur.passes == 0 || blur.radius.0 <= 0. {
+            return;
+        }
+
+        self.update_render_elements(None);
+
+        let outputs: Vec<_> = self.output_state.keys().cloned().collect();
+        for output in outputs {
+            let Some(fx_buffers) = EffectsFramebuffers::get_user_data(&output) else {
+                continue;
+            };
+
+            let size = output.current_mode().unwrap().size;
+            let transform = output.current_transform();
+            let scale = Scale::from(output.current_scale().fractional_scale());
+
+            // Render the frame into a texture first, so that the elements are done with the effects
+            // buffers by the time we use them for blurring.
+            let elements =
+                self.render::<GlesRenderer>(renderer, &output, false, RenderTarget::Output);
+            let res = render_to_texture(
+                renderer,
+                size,
+                scale,
+                transform,
+                Fourcc::Abgr8888,
+                elements.iter().rev(),
+            );
+            let texture = match res {
+                Ok((texture, _)) => texture,
+                Err(err) => {
+                    warn!("error rendering output {}: {err:?}", output.name());
+                    continue;
+                }
+            };
+
+            let buffer =
+                TextureBuffer::from_texture(renderer, texture, scale, transform, Vec::new());
+            let elem = TextureRenderElement::from_texture_buffer(
+                buffer,
+                (0., 0.),
+                1.,
+                None,
+                None,
+                Kind::Unspecified,
+            );
+
+            let res = fx_buffers.borrow_mut().render_blurred_texture(
+                renderer,
+                scale,
+                blur,
+                iter::once(elem),
+            );
+            let texture = match res {
+                Ok(texture) => texture,
+                Err(err) => {
+                    warn!("error blurring output {}: {err:?}", output.name());
+                    continue;
+                }
+            };
+
+            let buffer = TextureBuffer::from_texture(
+                renderer,
+                texture,
+                scale,
+                Transform::Normal,
+                Vec::new(),
+            );
+            self.output_state.get_mut(&output).unwrap().lock_backdrop = Some(buffer);
+        }
+    }
+
     #[cfg(feature = "dbus")]
     fn update_locked_hint(&mut self) {
         use std::sync::LazyLock;
@@ -5949,6 +7916,44 @@ impl Niri {
         });
     }
 
+    /// Returns the pointer constraint that the focused window's rules force, and the window.
+    fn focus_forced_pointer_constraint(&self) -> Option<(ForcePointerConstraint, &Window)> {
+        let KeyboardFocus::Layout {
+            surface: Some(surface),
+        } = &self.keyboard_focus
+        else {
+            return None;
+        };
+
+        let (mapped, _) = self.layout.find_window_and_output(surface)?;
+        let constraint = mapped.rules().force_pointer_constraint?;
+        Some((constraint, &mapped.window))
+    }
+
+    /// Returns whether the focused window has a rule forcing a pointer constraint.
+    pub fn has_forced_pointer_constraint(&self) -> bool {
+        self.focus_forced_pointer_constraint().is_some()
+    }
+
+    /// Returns the pointer constraint forced by the focused window's rules, if it applies.
+    ///
+    /// The constraint applies once the pointer is inside the focused window, regardless of
+    /// whether the window uses the pointer-constraints protocol.
+    pub fn forced_pointer_constraint(&self) -> Option<ForcePointerConstraint> {
+        if self.forced_pointer_constraint_released {
+            return None;
+        }
+
+        let (constraint, focus) = self.focus_forced_pointer_constraint()?;
+
+        let (window, _) = self.pointer_contents.window.as_ref()?;
+        if window != focus {
+            return None;
+        }
+
+        Some(constraint)
+    }
+
     pub fn focus_layer_surface_if_on_demand(&mut self, surface: Option<LayerSurface>) {
         if let Some(surface) = surface {
             if surface.cached_state().keyboard_interactivity
@@ -6042,6 +8047,14 @@ impl Niri {
             return;
         }
 
+        // Any motion restarts the wait for the pointer to stop.
+        if ffm.wait_for_pointer_stop {
+            if let Some((window, token)) = self.focus_follows_mouse_timer.take() {
+                self.event_loop.remove(token);
+                self.start_focus_follows_mouse_timer(window, ffm.delay());
+            }
+        }
+
         // Recompute the current pointer focus because we don't update it during animations.
         let current_focus = self.contents_under(pointer.current_location());
 
@@ -6075,8 +8088,17 @@ impl Niri {
                     }
                 }
 
-                self.layout.activate_window_without_raising(window);
-                self.layer_shell_on_demand_focus = None;
+                if let Some((_, token)) = self.focus_follows_mouse_timer.take() {
+                    self.event_loop.remove(token);
+                }
+
+                let delay = ffm.delay();
+                if delay.is_zero() {
+                    self.layout.activate_window_without_raising(window);
+                    self.layer_shell_on_demand_focus = None;
+                } else {
+                    self.start_focus_follows_mouse_timer(window.clone(), delay);
+                }
             }
         }
 
@@ -6087,6 +8109,39 @@ impl Niri {
         }
     }
 
+    fn start_focus_follows_mouse_timer(&mut self, window: Window, delay: Duration) {
+        let timer = Timer::from_duration(delay);
+        let token = self
+            .event_loop
+            .insert_source(timer, {
+                let window = window.clone();
+                move |_, _, state| {
+                    state.niri.focus_follows_mouse_timer = None;
+                    state.niri.finish_focus_follows_mouse(&window);
+                    TimeoutAction::Drop
+                }
+            })
+            .unwrap();
+        self.focus_follows_mouse_timer = Some((window, token));
+    }
+
+    /// Focuses the window that focus-follows-mouse waited for, if the pointer is still over it.
+    fn finish_focus_follows_mouse(&mut self, window: &Window) {
+        let pointer = &self.seat.get_pointer().unwrap();
+        if pointer.is_grabbed() || self.window_mru_ui.is_open() || self.layout.is_overview_open() {
+            return;
+        }
+
+        let under = self.contents_under(pointer.current_location());
+        if under.window.as_ref().map(|(window, _)| window) != Some(window) {
+            return;
+        }
+
+        self.layout.activate_window_without_raising(window);
+        self.layer_shell_on_demand_focus = None;
+        self.queue_redraw_all();
+    }
+
     pub fn do_screen_transition(&mut self, renderer: &mut GlesRenderer, delay_ms: Option<u16>) {
         let _span = tracy_client::span!("Niri::do_screen_transition");
 
@@ -6192,6 +8247,441 @@ impl Niri {
             changed
         };
 
+        // Unmapped windows don't report changes, so always check the inhibitors.
+        self.keyboard_shortcuts_inhibit_dirty = true;
+
+        if changed {
+            // FIXME: granular.
+            self.queue_redraw_all();
+        }
+    }
+
+    /// Moves windows with the `reapply-rules-on-reload` window rule to the location from their
+    /// `open-on-workspace` or `open-on-output` rules.
+    pub fn reapply_window_locations(&mut self) {
+        let _span = tracy_client::span!("Niri::reapply_window_locations");
+
+        let windows: Vec<_> = self
+            .layout
+            .windows()
+            .filter(|(_, mapped)| mapped.rules().reapply_rules_on_reload == Some(true))
+            .map(|(_, mapped)| {
+                let rules = mapped.rules();
+                (
+                    mapped.window.clone(),
+                    rules.open_on_workspace.clone(),
+                    rules.open_on_output.clone(),
+                )
+            })
+            .collect();
+
+        let mut changed = false;
+        for (window, open_on_workspace, open_on_output) in windows {
+            // Resolve the target the same way as when opening the window: the first existing
+            // workspace takes priority over the first connected output. Re-resolve for every window
+            // since moving the previous ones could have changed the workspace indices.
+            let target = open_on_workspace.iter().find_map(|name| {
+                let (idx, ws) = self.layout.find_workspace_by_name(name)?;
+                Some((ws.current_output()?.clone(), Some(idx)))
+            });
+            let target = target.or_else(|| {
+                let current_output = self
+                    .layout
+                    .windows()
+                    .find(|(_, mapped)| mapped.window == window)
+                    .and_then(|(mon, _)| mon)
+                    .map(|mon| mon.output());
+
+                let output = open_on_output.iter().find_map(|name| {
+                    self.global_space
+                        .outputs()
+                        .find(|output| output_matches_name(output, name))
+                })?;
+
+                // Keep the window on its current workspace if it's already on the right output.
+                (current_output != Some(output)).then(|| (output.clone(), None))
+            });
+
+            if let Some((output, ws_idx)) = target {
+                self.layout
+                    .move_to_output(Some(&window), &output, ws_idx, ActivateWindow::No);
+                changed = true;
+            }
+        }
+
+        if changed {
+            // FIXME: granular.
+            self.queue_redraw_all();
+        }
+    }
+
+    /// Re-resolves window and layer rules when any of their time conditions starts or stops
+    /// matching.
+    pub fn update_time_conditions(&mut self) {
+        let _span = tracy_client::span!("Niri::update_time_conditions");
+
+        let active = compute_active_time_conditions(&self.config.borrow());
+        if active == self.active_time_conditions {
+            return;
+        }
+        self.active_time_conditions = active;
+
+        self.recompute_window_rules();
+        self.reapply_window_locations();
+        self.recompute_layer_rules();
+    }
+
+    /// Sets or clears the runtime opacity override of a window.
+    ///
+    /// If `id` is `None`, uses the focused window.
+    pub fn set_window_opacity_override(&mut self, id: Option<u64>, opacity: Option<f32>) {
+        let opacity = opacity.filter(|x| !x.is_nan()).map(|x| x.clamp(0., 1.));
+
+        let mut window = None;
+        {
+            let window_rules = &self.config.borrow().window_rules;
+            let is_at_startup = self.is_at_startup;
+
+            self.layout.with_windows_mut(|mapped, _| {
+                let matches = match id {
+                    Some(id) => mapped.id().get() == id,
+                    None => mapped.is_focused(),
+                };
+                if matches && mapped.set_opacity_override(opacity, window_rules, is_at_startup) {
+                    window = Some(mapped.window.clone());
+                }
+            });
+        }
+
+        if let Some(window) = window {
+            self.layout.update_window(&window, None);
+            // FIXME: granular.
+            self.queue_redraw_all();
+        }
+    }
+
+    /// Adds a mark to a window, or to the focused window if `id` is `None`.
+    ///
+    /// A mark is on at most one window, so it is taken away from the window that had it before.
+    pub fn mark_window(&mut self, id: Option<u64>, mark: String) {
+        let matches = |mapped: &Mapped| match id {
+            Some(id) => mapped.id().get() == id,
+            None => mapped.is_focused(),
+        };
+
+        // Don't take the mark away if there's no window to give it to.
+        if !self.layout.windows().any(|(_, mapped)| matches(mapped)) {
+            return;
+        }
+
+        self.layout.with_windows_mut(|mapped, _| {
+            if matches(mapped) {
+                mapped.add_mark(mark.clone());
+            } else {
+                mapped.remove_mark(&mark);
+            }
+        });
+    }
+
+    /// Removes a mark, or all marks if `mark` is `None`, from a window.
+    pub fn unmark_window(&mut self, id: Option<u64>, mark: Option<&str>) {
+        self.layout.with_windows_mut(|mapped, _| {
+            let matches = match id {
+                Some(id) => mapped.id().get() == id,
+                None => mapped.is_focused(),
+            };
+            if matches {
+                match mark {
+                    Some(mark) => mapped.remove_mark(mark),
+                    None => mapped.clear_marks(),
+                };
+            }
+        });
+    }
+
+    pub fn window_by_mark(&self, mark: &str) -> Option<Window> {
+        self.layout
+            .windows()
+            .find(|(_, mapped)| mapped.has_mark(mark))
+            .map(|(_, mapped)| mapped.window.clone())
+    }
+
+    /// Registers a key grab for an IPC client.
+    ///
+    /// `name` is the key as written by the client, which is sent back in the event. The grab is
+    /// released when the client with `client_id` disconnects.
+    pub fn grab_ipc_key(&mut self, key: Key, name: String, client_id: u64) -> Result<(), String> {
+        if self.ipc_key_grabs.iter().any(|bind| bind.key == key) {
+            return Err(format!("{name} is already grabbed"));
+        }
+
+        self.ipc_key_grab_clients.insert(key, client_id);
+
+        self.ipc_key_grabs.push(Bind {
+            key,
+            action: Action::IpcKeyGrab(name),
+            repeat: false,
+            repeat_delay: None,
+            repeat_rate: None,
+            release: false,
+            taps: 1,
+            cooldown: None,
+            allow_when_locked: false,
+            allow_inhibiting: true,
+            allow_invalidation: true,
+            hotkey_overlay_title: None,
+        });
+        Ok(())
+    }
+
+    pub fn ungrab_ipc_key(&mut self, key: Key, name: &str) -> Result<(), String> {
+        let len = self.ipc_key_grabs.len();
+        self.ipc_key_grabs.retain(|bind| bind.key != key);
+
+        if self.ipc_key_grabs.len() == len {
+            return Err(format!("{name} is not grabbed"));
+        }
+
+        self.ipc_key_grab_clients.remove(&key);
+        Ok(())
+    }
+
+    /// Releases all key grabs of a disconnected IPC client.
+    pub fn release_ipc_key_grabs(&mut self, client_id: u64) {
+        self.ipc_key_grab_clients.retain(|_, id| *id != client_id);
+
+        let owners = &self.ipc_key_grab_clients;
+        self.ipc_key_grabs
+            .retain(|bind| owners.contains_key(&bind.key));
+    }
+
+    /// Releases caches to reduce memory usage under memory pressure.
+    ///
+    /// Everything released here is re-created lazily when needed.
+    pub fn release_caches(&mut self) {
+        let _span = tracy_client::span!("Niri::release_caches");
+
+        let mut textures = self.layout.release_caches();
+        textures += self
+            .mapped_layer_surfaces
+            .values()
+            .filter(|mapped| mapped.release_caches())
+            .count();
+
+        textures += self.overlay_widgets.release_caches();
+        textures += self.hotkey_overlay.release_caches();
+        textures += self.config_error_notification.release_caches();
+        textures += self.shortcuts_inhibit_indicator.release_caches();
+        textures += self.exit_confirm_dialog.release_caches();
+        textures += usize::from(self.clipboard_picker.release_caches());
+        textures += usize::from(self.key_sequence_hint.release_caches());
+
+        let thumbnails = self.window_thumbnails.len();
+        self.window_thumbnails.clear();
+        self.cursor_texture_cache.clear();
+
+        // Drop the damage history, which holds on to the geometry of past frames.
+        for (output, state) in &mut self.output_state {
+            state.debug_damage_tracker = OutputDamageTracker::from_output(output);
+        }
+
+        info!(
+            "memory pressure: released {textures} title, blur, workspace snapshot and UI textures, \
+             {thumbnails} window thumbnails and the cursor texture cache"
+        );
+
+        self.queue_redraw_all();
+    }
+
+    pub fn ipc_frame_stats(&self) -> HashMap<String, niri_ipc::FrameStats> {
+        self.output_state
+            .iter()
+            .map(|(output, state)| {
+                let clock = &state.frame_clock;
+                let stats = state
+                    .frame_stats
+                    .to_ipc(clock.refresh_interval(), clock.vrr());
+                (output.name(), stats)
+            })
+            .collect()
+    }
+
+    pub fn ipc_protocol_stats(&self) -> Vec<niri_ipc::ClientProtocolStats> {
+        let handle = self.display_handle.backend_handle();
+
+        let mut stats = Vec::new();
+        for client_id in handle.all_clients() {
+            let Ok(data) = handle.get_client_data(client_id.clone()) else {
+                continue;
+            };
+            let Some(data) = data.downcast_ref::<ClientState>() else {
+                continue;
+            };
+
+            let pid = if data.credentials_unknown {
+                None
+            } else {
+                handle
+                    .get_client_credentials(client_id.clone())
+                    .ok()
+                    .map(|creds| creds.pid)
+            };
+
+            let mut objects = HashMap::new();
+            if let Ok(ids) = handle.all_objects_for(client_id) {
+                for id in ids {
+                    if let Ok(info) = handle.object_info(id) {
+                        *objects.entry(info.interface.name.to_owned()).or_insert(0) += 1;
+                    }
+                }
+            }
+
+            let mut client_stats = data.protocol_stats.lock().unwrap();
+            let is_alive = |id: &ObjectId| handle.object_info(id.clone()).is_ok();
+            stats.push(client_stats.to_ipc(pid, objects, is_alive));
+        }
+
+        stats
+    }
+
+    pub fn ipc_pointer(&self) -> niri_ipc::Pointer {
+        let pos = self.seat.get_pointer().unwrap().current_location();
+        let under = self.output_under(pos);
+
+        niri_ipc::Pointer {
+            x: pos.x,
+            y: pos.y,
+            output: under.map(|(output, _)| output.name()),
+            pos_in_output: under.map(|(_, pos)| (pos.x, pos.y)),
+            window_id: self.window_under(pos).map(|mapped| mapped.id().get()),
+            focused_window_id: self
+                .layout
+                .windows()
+                .find(|(_, mapped)| mapped.is_focused())
+                .map(|(_, mapped)| mapped.id().get()),
+        }
+    }
+
+    pub fn ipc_scrolling_view(
+        &self,
+        workspace_id: Option<u64>,
+    ) -> Result<niri_ipc::ScrollingView, String> {
+        let ws = if let Some(id) = workspace_id {
+            self.layout
+                .workspaces()
+                .map(|(_, _, ws)| ws)
+                .find(|ws| ws.id().get() == id)
+                .ok_or_else(|| format!("workspace {id} not found"))?
+        } else {
+            self.layout
+                .active_workspace()
+                .ok_or_else(|| String::from("no focused workspace"))?
+        };
+
+        let scrolling = ws.scrolling();
+        let columns: Vec<_> = scrolling
+            .columns_with_geometry()
+            .map(|(col, x, width)| niri_ipc::ColumnGeometry {
+                x,
+                width,
+                window_ids: col
+                    .tiles()
+                    .flat_map(|(tile, _)| tile.windows())
+                    .map(|win| win.id().get())
+                    .collect(),
+            })
+            .collect();
+
+        Ok(niri_ipc::ScrollingView {
+            workspace_id: ws.id().get(),
+            view_pos: scrolling.view_pos(),
+            target_view_pos: scrolling.target_view_pos(),
+            view_width: scrolling.view_size().w,
+            active_column_idx: (!columns.is_empty()).then(|| scrolling.active_column_idx()),
+            columns,
+        })
+    }
+
+    pub fn set_overlay_widget(&mut self, widget: niri_ipc::Widget) -> Result<(), String> {
+        self.overlay_widgets
+            .set(widget)
+            .map_err(|err| format!("{err:?}"))?;
+        // FIXME: granular.
+        self.queue_redraw_all();
+        Ok(())
+    }
+
+    pub fn remove_overlay_widget(&mut self, id: &str) -> Result<(), String> {
+        if !self.overlay_widgets.remove(id) {
+            return Err(format!("no widget with id {id:?}"));
+        }
+        // FIXME: granular.
+        self.queue_redraw_all();
+        Ok(())
+    }
+
+    /// Stops tracking a parked window that got unmapped or destroyed.
+    pub fn forget_parked_window(&mut self, window: &Window) {
+        let Some(idx) = self.parked_windows.iter().position(|(w, _)| w == window) else {
+            return;
+        };
+        let (_, ws_id) = self.parked_windows.remove(idx);
+
+        // Let the workspace go once none of its windows are waiting to come back.
+        if !self.parked_windows.iter().any(|(_, id)| *id == ws_id) {
+            self.layout.set_keep_workspace_when_empty(ws_id, false);
+        }
+    }
+
+    /// Moves the parked windows back to their workspaces.
+    ///
+    /// Windows that were moved away from the parking workspace in the meantime are left alone.
+    pub fn unpark_windows(&mut self) {
+        let _span = tracy_client::span!("Niri::unpark_windows");
+
+        let parking_id = {
+            let config = self.config.borrow();
+            let name = config.window_parking.workspace.as_deref();
+            name.and_then(|name| self.layout.find_workspace_by_name(name))
+                .map(|(_, ws)| ws.id())
+        };
+
+        let parked = mem::take(&mut self.parked_windows);
+
+        let mut changed = false;
+        for (window, ws_id) in &parked {
+            let Some(wl_surface) = window.wl_surface() else {
+                continue;
+            };
+            let is_parked = self
+                .layout
+                .workspaces()
+                .any(|(_, _, ws)| Some(ws.id()) == parking_id && ws.has_window(window));
+            if !is_parked {
+                continue;
+            }
+
+            let Some((idx, ws)) = self.layout.find_workspace_by_id(*ws_id) else {
+                continue;
+            };
+            let Some(output) = ws.current_output().cloned() else {
+                continue;
+            };
+
+            self.layout
+                .move_to_output(Some(window), &output, Some(idx), ActivateWindow::No);
+            if let Some((mapped, _)) = self.layout.find_window_and_output_mut(&wl_surface) {
+                // Don't park the window again right away.
+                mapped.touch();
+            }
+            changed = true;
+        }
+
+        // The workspaces no longer need to wait for their windows.
+        for (_, ws_id) in parked {
+            self.layout.set_keep_workspace_when_empty(ws_id, false);
+        }
+
         if changed {
             // FIXME: granular.
             self.queue_redraw_all();
@@ -6303,6 +8793,152 @@ impl Niri {
         }
     }
 
+    /// Opens the column grid for the windows in the focused tab group or column.
+    pub fn open_column_grid(&mut self) {
+        let Some((focus, output)) = self.layout.focus_with_output() else {
+            return;
+        };
+
+        let mut windows: Vec<&Mapped> = Vec::new();
+        if let Some(tile) = self.layout.tile_for_window(&focus.window) {
+            windows.extend(tile.windows());
+        }
+
+        if windows.len() < 2 {
+            windows.clear();
+
+            if let Some(ws) = self.layout.active_workspace() {
+                let ids = ws.column_window_ids(&focus.window).unwrap_or_default();
+                windows.extend(
+                    ids.iter()
+                        .filter_map(|id| ws.windows().find(|m| m.window == *id)),
+                );
+            }
+        }
+
+        // There's nothing to pick from with a single window.
+        if windows.len() < 2 {
+            return;
+        }
+
+        let output = output.clone();
+        let active = focus.id();
+        self.column_grid.open(output.clone(), windows, active);
+        self.queue_redraw(&output);
+    }
+
+    pub fn close_column_grid(&mut self) -> Option<Window> {
+        let output = self.column_grid.output()?.clone();
+        self.queue_redraw(&output);
+
+        let id = self.column_grid.close()?;
+        self.find_window_by_id(id)
+    }
+
+    pub fn queue_redraw_column_grid_output(&mut self) {
+        if let Some(output) = self.column_grid.output().cloned() {
+            self.queue_redraw(&output);
+        }
+    }
+
+    /// Opens the clipboard history picker on the active output.
+    pub fn open_clipboard_picker(&mut self) {
+        if !self.config.borrow().clipboard.history.on {
+            return;
+        }
+
+        let Some(output) = self.layout.active_output().cloned() else {
+            return;
+        };
+
+        self.clipboard_picker
+            .open(output.clone(), &self.clipboard_history);
+        self.queue_redraw(&output);
+    }
+
+    /// Closes the clipboard history picker, returning the selected entry id.
+    pub fn close_clipboard_picker(&mut self) -> Option<u64> {
+        let output = self.clipboard_picker.output()?.clone();
+        self.queue_redraw(&output);
+        self.clipboard_picker.close()
+    }
+
+    pub fn queue_redraw_clipboard_picker_output(&mut self) {
+        if let Some(output) = self.clipboard_picker.output().cloned() {
+            self.queue_redraw(&output);
+        }
+    }
+
+    /// Continues the pending key sequence with `key`, waiting for one of `binds` next.
+    pub fn advance_key_sequence(&mut self, key: Key, binds: Vec<Bind>) {
+        let Some(output) = self
+            .key_sequence_hint
+            .output()
+            .or_else(|| self.layout.active_output())
+            .cloned()
+        else {
+            return;
+        };
+
+        self.key_sequence_hint.advance(output.clone(), key, binds);
+        self.queue_redraw(&output);
+
+        if let Some(token) = self.key_sequence_timer.take() {
+            self.event_loop.remove(token);
+        }
+
+        let timeout = self.config.borrow().input.keyboard.key_sequence_timeout_ms;
+        let timer = Timer::from_duration(Duration::from_millis(u64::from(timeout)));
+        let token = self
+            .event_loop
+            .insert_source(timer, move |_, _, state| {
+                state.niri.key_sequence_timer = None;
+                state.niri.cancel_key_sequence();
+                TimeoutAction::Drop
+            })
+            .unwrap();
+        self.key_sequence_timer = Some(token);
+    }
+
+    /// Cancels the pending key sequence, if any.
+    pub fn cancel_key_sequence(&mut self) {
+        if let Some(token) = self.key_sequence_timer.take() {
+            self.event_loop.remove(token);
+        }
+
+        if let Some(output) = self.key_sequence_hint.output().cloned() {
+            self.key_sequence_hint.cancel();
+            self.queue_redraw(&output);
+        }
+    }
+
+    /// Adds a selection read from a client to the clipboard history.
+    pub fn add_clipboard_history_entry(&mut self, mime_type: String, data: Vec<u8>) {
+        let history = self.config.borrow().clipboard.history;
+        if !history.on {
+            return;
+        }
+
+        let max_entries = history.max_entries as usize;
+        self.clipboard_history
+            .push(mime_type, Arc::from(data), max_entries);
+    }
+
+    /// Puts an entry from the clipboard history back into the clipboard.
+    pub fn select_clipboard_history_entry(&mut self, id: u64) {
+        let Some(entry) = self.clipboard_history.get(id) else {
+            return;
+        };
+
+        set_data_device_selection(
+            &self.display_handle,
+            &self.seat,
+            entry.mime_types(),
+            entry.data.clone(),
+        );
+        self.clipboard_history.promote(id);
+    }
+
     pub fn queue_redraw_mru_output(&mut self) {
         if let Some(output) = self.window_mru_ui.output().cloned() {
             self.queue_redraw(&output);
@@ -6314,6 +8950,8 @@ pub struct NewClient {
     pub client: UnixStream,
     pub restricted: bool,
     pub credentials_unknown: bool,
+    /// Metadata of the security context that this client connected through.
+    pub security_context: Option<SecurityContext>,
 }
 
 pub struct ClientState {
@@ -6324,6 +8962,14 @@ pub struct ClientState {
     pub restricted: bool,
     /// We cannot retrieve this client's socket credentials.
     pub credentials_unknown: bool,
+    /// Whether the security-context policies allow this restricted client to use screencopy.
+    pub allow_screencopy: bool,
+    /// Whether the security-context policies allow this restricted client to use data-control.
+    pub allow_data_control: bool,
+    /// Whether the security-context policies allow this restricted client to list toplevels.
+    pub allow_foreign_toplevel: bool,
+    /// Protocol usage statistics for debugging.
+    pub protocol_stats: Mutex<ClientStats>,
 }
 
 impl ClientData for ClientState {
@@ -6331,6 +8977,60 @@ impl ClientData for ClientState {
     fn disconnected(&self, _client_id: ClientId, _reason: DisconnectReason) {}
 }
 
+/// Signals the wp-fifo barrier of the surface, unblocking its pending FIFO commits.
+fn signal_fifo_barrier(surface: &WlSurface, states: &SurfaceData, tx: &Sender<Client>) {
+    let barrier = states
+        .cached_state
+        .get::<FifoBarrierCachedState>()
+        .current()
+        .barrier
+        .take();
+
+    if let Some(barrier) = barrier {
+        barrier.signal();
+
+        if let Some(client) = surface.client() {
+            let _ = tx.send(client);
+        }
+    }
+}
+
+fn compute_active_time_conditions(config: &Config) -> Vec<bool> {
+    let Some((now, _)) = local_time_of_day() else {
+        return Vec::new();
+    };
+
+    let window_matches = config
+        .window_rules
+        .iter()
+        .flat_map(|rule| rule.matches.iter().chain(&rule.excludes))
+        .filter_map(|m| m.at);
+    let layer_matches = config
+        .layer_rules
+        .iter()
+        .flat_map(|rule| rule.matches.iter().chain(&rule.excludes))
+        .filter_map(|m| m.at);
+
+    window_matches
+        .chain(layer_matches)
+        .map(|at| at.contains(now))
+        .collect()
+}
+
+/// Parses the contents of a `window-rule {}` block.
+fn parse_window_rule(text: &str) -> Result<niri_config::WindowRule, String> {
+    let config = Config::parse_mem(&format!("window-rule {{\n{text}\n}}"))
+        .map_err(|err| format!("error parsing window rule: {err:?}"))?;
+
+    let mut rules = config.window_rules;
+    if rules.len() != 1 {
+        return Err(String::from(
+            "window rule must be the contents of a single window-rule block",
+        ));
+    }
+    Ok(rules.remove(0))
+}
+
 fn scale_relocate_crop<E: Element>(
     elem: E,
     output_scale: Scale<f64>,
@@ -6353,6 +9053,7 @@ niri_render_elements! {
 niri_render_elements! {
     WindowScreenshotRenderElement<R> => {
         Layout = LayoutElementRenderElement<R>,
+        Tile = TileRenderElement<R>,
         Pointer = RelocateRenderElement<PointerRenderElements<R>>,
     }
 }
@@ -6373,6 +9074,7 @@ niri_render_elements! {
         SolidColor = SolidColorRenderElement,
         ScreenshotUi = ScreenshotUiRenderElement,
         WindowMruUi = WindowMruUiRenderElement<R>,
+        ColumnGrid = ColumnGridRenderElement<R>,
         ExitConfirmDialog = ExitConfirmDialogRenderElement,
         Texture = PrimaryGpuTextureRenderElement,
         // Used for the CPU-rendered panels.
@@ -0,0 +1,911 @@
+//! wp-color-management protocol implementation.
+//!
+//! niri composites everything in sRGB, so this doesn't do any color conversion. The image
+//! descriptions let clients find out about HDR outputs and tag their HDR content, which can then
+//! be passed through to the display when it's scanned out directly.
+
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use smithay::output::Output;
+use smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_color_management_output_v1::{
+    self, WpColorManagementOutputV1,
+};
+use smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_color_management_surface_feedback_v1::{
+    self, WpColorManagementSurfaceFeedbackV1,
+};
+use smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_color_management_surface_v1::{
+    self, WpColorManagementSurfaceV1,
+};
+use smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_color_manager_v1::{
+    self, Feature, RenderIntent, WpColorManagerV1,
+};
+pub use smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_color_manager_v1::{
+    Primaries, TransferFunction,
+};
+use smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_image_description_creator_icc_v1::{
+    self, WpImageDescriptionCreatorIccV1,
+};
+use smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_image_description_creator_params_v1::{
+    self, WpImageDescriptionCreatorParamsV1,
+};
+use smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_image_description_info_v1::WpImageDescriptionInfoV1;
+use smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_image_description_v1::{
+    self, WpImageDescriptionV1,
+};
+use smithay::reexports::wayland_server::backend::ClientId;
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::reexports::wayland_server::{
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource, WEnum,
+};
+use smithay::wayland::compositor::{with_states, Cacheable, SurfaceData};
+
+const VERSION: u32 = 1;
+
+/// Reference luminance of SDR content in cd/m², as recommended by ITU-R BT.2408.
+pub const SDR_REFERENCE_LUMINANCE: u32 = 203;
+
+pub struct ColorManagementState {
+    outputs: Vec<OutputInstance>,
+    feedbacks: Vec<FeedbackInstance>,
+    /// Identities of the compositor-provided image descriptions.
+    identities: Vec<(ImageDescription, u32)>,
+    next_identity: u32,
+}
+
+pub struct ColorManagementGlobalData {
+    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+pub trait ColorManagementHandler {
+    fn color_management_state(&mut self) -> &mut ColorManagementState;
+    /// Returns the image description of the content that the output displays.
+    fn output_image_description(&mut self, output: &Output) -> ImageDescription;
+    /// Returns the output that the surface is mainly shown on.
+    fn surface_primary_output(&mut self, surface: &WlSurface) -> Option<Output>;
+}
+
+struct OutputInstance {
+    resource: WpColorManagementOutputV1,
+    output: Output,
+    last_identity: Option<u32>,
+}
+
+struct FeedbackInstance {
+    resource: WpColorManagementSurfaceFeedbackV1,
+    surface: WlSurface,
+    last_identity: Option<u32>,
+}
+
+/// Parametric image description.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageDescription {
+    pub tf: TransferFunction,
+    pub primaries: Primaries,
+    /// Minimum (in 0.0001 cd/m²), maximum and reference (in cd/m²) luminance.
+    pub luminances: Option<(u32, u32, u32)>,
+    /// Chromaticities of the mastering display in 1 / 1 000 000 units: red, green, blue and
+    /// white point x and y.
+    pub mastering_primaries: Option<[i32; 8]>,
+    /// Minimum (in 0.0001 cd/m²) and maximum (in cd/m²) luminance of the mastering display.
+    pub mastering_luminance: Option<(u32, u32)>,
+    pub max_cll: Option<u32>,
+    pub max_fall: Option<u32>,
+}
+
+impl ImageDescription {
+    pub const SRGB: Self = Self {
+        tf: TransferFunction::Gamma22,
+        primaries: Primaries::Srgb,
+        luminances: None,
+        mastering_primaries: None,
+        mastering_luminance: None,
+        max_cll: None,
+        max_fall: None,
+    };
+
+    pub fn is_hdr(&self) -> bool {
+        matches!(self.tf, TransferFunction::St2084Pq | TransferFunction::Hlg)
+    }
+}
+
+pub struct ImageDescriptionData {
+    /// `None` if the image description failed.
+    description: Option<ImageDescription>,
+    /// Whether the client may call get_information.
+    allow_information: bool,
+}
+
+#[derive(Default)]
+pub struct ParamsBuilder {
+    tf: Option<TransferFunction>,
+    primaries: Option<Primaries>,
+    luminances: Option<(u32, u32, u32)>,
+    mastering_primaries: Option<[i32; 8]>,
+    mastering_luminance: Option<(u32, u32)>,
+    max_cll: Option<u32>,
+    max_fall: Option<u32>,
+}
+
+#[derive(Default)]
+struct ColorManagementSurfaceData {
+    /// Whether a color management surface object currently exists for this surface.
+    exists: AtomicBool,
+}
+
+/// Double-buffered image description of a surface.
+#[derive(Debug, Default, Clone, Copy)]
+struct ColorManagementCachedState {
+    description: Option<ImageDescription>,
+}
+
+impl Cacheable for ColorManagementCachedState {
+    fn commit(&mut self, _dh: &DisplayHandle) -> Self {
+        *self
+    }
+
+    fn merge_into(self, into: &mut Self, _dh: &DisplayHandle) {
+        *into = self;
+    }
+}
+
+/// Returns the image description of the committed surface content.
+pub fn surface_image_description(states: &SurfaceData) -> Option<ImageDescription> {
+    let mut cached = states.cached_state.get::<ColorManagementCachedState>();
+    cached.current().description
+}
+
+fn set_pending_image_description(surface: &WlSurface, description: Option<ImageDescription>) {
+    with_states(surface, |states| {
+        let mut cached = states.cached_state.get::<ColorManagementCachedState>();
+        cached.pending().description = description;
+    });
+}
+
+fn with_surface_data<T>(
+    surface: &WlSurface,
+    f: impl FnOnce(&ColorManagementSurfaceData) -> T,
+) -> T {
+    with_states(surface, |states| {
+        states
+            .data_map
+            .insert_if_missing_threadsafe(ColorManagementSurfaceData::default);
+        f(states.data_map.get::<ColorManagementSurfaceData>().unwrap())
+    })
+}
+
+impl ColorManagementState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<WpColorManagerV1, ColorManagementGlobalData>,
+        D: Dispatch<WpColorManagerV1, ()>,
+        D: ColorManagementHandler,
+        D: 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let global_data = ColorManagementGlobalData {
+            filter: Box::new(filter),
+        };
+        display.create_global::<D, WpColorManagerV1, _>(VERSION, global_data);
+
+        Self {
+            outputs: Vec::new(),
+            feedbacks: Vec::new(),
+            identities: Vec::new(),
+            next_identity: 1,
+        }
+    }
+
+    fn next_identity(&mut self) -> u32 {
+        let identity = self.next_identity;
+        self.next_identity = self.next_identity.wrapping_add(1).max(1);
+        identity
+    }
+
+    /// Returns the identity of a compositor-provided image description.
+    ///
+    /// The same parameters always get the same identity, so clients can tell when the preferred
+    /// image description didn't change.
+    fn identity_for(&mut self, description: &ImageDescription) -> u32 {
+        if let Some((_, identity)) = self.identities.iter().find(|(d, _)| d == description) {
+            return *identity;
+        }
+
+        let identity = self.next_identity();
+        self.identities.push((*description, identity));
+        identity
+    }
+
+    /// Forgets the identities that no output or feedback object reports anymore.
+    fn prune_identities(&mut self) {
+        let outputs = &self.outputs;
+        let feedbacks = &self.feedbacks;
+        self.identities.retain(|(_, identity)| {
+            let identity = Some(*identity);
+            outputs.iter().any(|i| i.last_identity == identity)
+                || feedbacks.iter().any(|i| i.last_identity == identity)
+        });
+    }
+}
+
+/// Notifies the clients about changed output and preferred image descriptions.
+pub fn refresh<D: ColorManagementHandler>(state: &mut D) {
+    let _span = tracy_client::span!("color_management::refresh");
+
+    // Take the instances out so that the handler can be called while going through them.
+    let mut outputs = mem::take(&mut state.color_management_state().outputs);
+    for instance in &mut outputs {
+        let description = state.output_image_description(&instance.output);
+        let identity = state.color_management_state().identity_for(&description);
+
+        if instance.last_identity.is_some_and(|last| last != identity) {
+            instance.resource.image_description_changed();
+        }
+        instance.last_identity = Some(identity);
+    }
+
+    let mut feedbacks = mem::take(&mut state.color_management_state().feedbacks);
+    for instance in &mut feedbacks {
+        let description = preferred_image_description(state, &instance.surface);
+        let identity = state.color_management_state().identity_for(&description);
+
+        if instance.last_identity.is_some_and(|last| last != identity) {
+            instance.resource.preferred_changed(identity);
+        }
+        instance.last_identity = Some(identity);
+    }
+
+    let protocol_state = state.color_management_state();
+    protocol_state.outputs = outputs;
+    protocol_state.feedbacks = feedbacks;
+    protocol_state.prune_identities();
+}
+
+fn preferred_image_description<D: ColorManagementHandler>(
+    state: &mut D,
+    surface: &WlSurface,
+) -> ImageDescription {
+    match state.surface_primary_output(surface) {
+        Some(output) => state.output_image_description(&output),
+        None => ImageDescription::SRGB,
+    }
+}
+
+fn init_ready_description<D>(
+    state: &mut D,
+    data_init: &mut DataInit<'_, D>,
+    id: New<WpImageDescriptionV1>,
+    description: ImageDescription,
+) -> u32
+where
+    D: Dispatch<WpImageDescriptionV1, ImageDescriptionData>,
+    D: ColorManagementHandler,
+    D: 'static,
+{
+    let identity = state.color_management_state().identity_for(&description);
+    let data = ImageDescriptionData {
+        description: Some(description),
+        allow_information: true,
+    };
+    let resource = data_init.init(id, data);
+    resource.ready(identity);
+    identity
+}
+
+impl<D> GlobalDispatch<WpColorManagerV1, ColorManagementGlobalData, D> for ColorManagementState
+where
+    D: GlobalDispatch<WpColorManagerV1, ColorManagementGlobalData>,
+    D: Dispatch<WpColorManagerV1, ()>,
+    D: ColorManagementHandler,
+    D: 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<WpColorManagerV1>,
+        _global_data: &ColorManagementGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let manager = data_init.init(resource, ());
+
+        manager.supported_intent(RenderIntent::Perceptual);
+
+        manager.supported_feature(Feature::Parametric);
+        manager.supported_feature(Feature::SetLuminances);
+        manager.supported_feature(Feature::SetMasteringDisplayPrimaries);
+
+        manager.supported_tf_named(TransferFunction::Srgb);
+        manager.supported_tf_named(TransferFunction::Gamma22);
+        manager.supported_tf_named(TransferFunction::St2084Pq);
+
+        manager.supported_primaries_named(Primaries::Srgb);
+        manager.supported_primaries_named(Primaries::Bt2020);
+
+        manager.done();
+    }
+
+    fn can_view(client: Client, global_data: &ColorManagementGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<WpColorManagerV1, (), D> for ColorManagementState
+where
+    D: Dispatch<WpColorManagerV1, ()>,
+    D: Dispatch<WpColorManagementOutputV1, Option<Output>>,
+    D: Dispatch<WpColorManagementSurfaceV1, WlSurface>,
+    D: Dispatch<WpColorManagementSurfaceFeedbackV1, WlSurface>,
+    D: Dispatch<WpImageDescriptionCreatorIccV1, ()>,
+    D: Dispatch<WpImageDescriptionCreatorParamsV1, Mutex<ParamsBuilder>>,
+    D: Dispatch<WpImageDescriptionV1, ImageDescriptionData>,
+    D: ColorManagementHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        manager: &WpColorManagerV1,
+        request: <WpColorManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            wp_color_manager_v1::Request::GetOutput { id, output } => {
+                let output = Output::from_resource(&output);
+                let resource = data_init.init(id, output.clone());
+
+                if let Some(output) = output {
+                    let description = state.output_image_description(&output);
+                    let protocol_state = state.color_management_state();
+                    let identity = protocol_state.identity_for(&description);
+                    protocol_state.outputs.push(OutputInstance {
+                        resource,
+                        output,
+                        last_identity: Some(identity),
+                    });
+                }
+            }
+            wp_color_manager_v1::Request::GetSurface { id, surface } => {
+                let already_exists =
+                    with_surface_data(&surface, |data| data.exists.swap(true, Ordering::Relaxed));
+
+                data_init.init(id, surface);
+
+                if already_exists {
+                    manager.post_error(
+                        wp_color_manager_v1::Error::SurfaceExists,
+                        "the surface already has a color management surface object",
+                    );
+                }
+            }
+            wp_color_manager_v1::Request::GetSurfaceFeedback { id, surface } => {
+                let resource = data_init.init(id, surface.clone());
+
+                let description = preferred_image_description(state, &surface);
+                let protocol_state = state.color_management_state();
+                let identity = protocol_state.identity_for(&description);
+                protocol_state.feedbacks.push(FeedbackInstance {
+                    resource,
+                    surface,
+                    last_identity: Some(identity),
+                });
+            }
+            wp_color_manager_v1::Request::CreateIccCreator { obj } => {
+                data_init.init(obj, ());
+                manager.post_error(
+                    wp_color_manager_v1::Error::UnsupportedFeature,
+                    "ICC image descriptions are not supported",
+                );
+            }
+            wp_color_manager_v1::Request::CreateParametricCreator { obj } => {
+                data_init.init(obj, Mutex::new(ParamsBuilder::default()));
+            }
+            wp_color_manager_v1::Request::CreateWindowsScrgb { image_description } => {
+                let data = ImageDescriptionData {
+                    description: None,
+                    allow_information: false,
+                };
+                data_init.init(image_description, data);
+                manager.post_error(
+                    wp_color_manager_v1::Error::UnsupportedFeature,
+                    "Windows scRGB is not supported",
+                );
+            }
+            wp_color_manager_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<WpColorManagementOutputV1, Option<Output>, D> for ColorManagementState
+where
+    D: Dispatch<WpColorManagementOutputV1, Option<Output>>,
+    D: Dispatch<WpImageDescriptionV1, ImageDescriptionData>,
+    D: ColorManagementHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        _resource: &WpColorManagementOutputV1,
+        request: <WpColorManagementOutputV1 as Resource>::Request,
+        output: &Option<Output>,
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            wp_color_management_output_v1::Request::GetImageDescription { image_description } => {
+                if let Some(output) = output {
+                    let description = state.output_image_description(output);
+                    init_ready_description(state, data_init, image_description, description);
+                } else {
+                    let data = ImageDescriptionData {
+                        description: None,
+                        allow_information: false,
+                    };
+                    let resource = data_init.init(image_description, data);
+                    resource.failed(
+                        wp_image_description_v1::Cause::NoOutput,
+                        String::from("the output is gone"),
+                    );
+                }
+            }
+            wp_color_management_output_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(
+        state: &mut D,
+        _client: ClientId,
+        resource: &WpColorManagementOutputV1,
+        _data: &Option<Output>,
+    ) {
+        let state = state.color_management_state();
+        state
+            .outputs
+            .retain(|instance| instance.resource != *resource);
+    }
+}
+
+impl<D> Dispatch<WpColorManagementSurfaceV1, WlSurface, D> for ColorManagementState
+where
+    D: Dispatch<WpColorManagementSurfaceV1, WlSurface>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        resource: &WpColorManagementSurfaceV1,
+        request: <WpColorManagementSurfaceV1 as Resource>::Request,
+        surface: &WlSurface,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        if !surface.is_alive() {
+            resource.post_error(
+                wp_color_management_surface_v1::Error::Inert,
+                "the surface is gone",
+            );
+            return;
+        }
+
+        match request {
+            wp_color_management_surface_v1::Request::SetImageDescription {
+                image_description,
+                render_intent,
+            } => {
+                if render_intent != WEnum::Value(RenderIntent::Perceptual) {
+                    resource.post_error(
+                        wp_color_management_surface_v1::Error::RenderIntent,
+                        "unsupported render intent",
+                    );
+                    return;
+                }
+
+                let Some(description) = image_description
+                    .data::<ImageDescriptionData>()
+                    .and_then(|data| data.description)
+                else {
+                    resource.post_error(
+                        wp_color_management_surface_v1::Error::ImageDescription,
+                        "the image description is not ready",
+                    );
+                    return;
+                };
+
+                set_pending_image_description(surface, Some(description));
+            }
+            wp_color_management_surface_v1::Request::UnsetImageDescription => {
+                set_pending_image_description(surface, None);
+            }
+            wp_color_management_surface_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(
+        _state: &mut D,
+        _client: ClientId,
+        _resource: &WpColorManagementSurfaceV1,
+        surface: &WlSurface,
+    ) {
+        // Destroying the object resets the surface to the default image description on the next
+        // commit.
+        if surface.is_alive() {
+            with_surface_data(surface, |data| data.exists.store(false, Ordering::Relaxed));
+            set_pending_image_description(surface, None);
+        }
+    }
+}
+
+impl<D> Dispatch<WpColorManagementSurfaceFeedbackV1, WlSurface, D> for ColorManagementState
+where
+    D: Dispatch<WpColorManagementSurfaceFeedbackV1, WlSurface>,
+    D: Dispatch<WpImageDescriptionV1, ImageDescriptionData>,
+    D: ColorManagementHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &WpColorManagementSurfaceFeedbackV1,
+        request: <WpColorManagementSurfaceFeedbackV1 as Resource>::Request,
+        surface: &WlSurface,
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            wp_color_management_surface_feedback_v1::Request::GetPreferred {
+                image_description,
+            }
+            | wp_color_management_surface_feedback_v1::Request::GetPreferredParametric {
+                image_description,
+            } => {
+                if !surface.is_alive() {
+                    data_init.init(
+                        image_description,
+                        ImageDescriptionData {
+                            description: None,
+                            allow_information: false,
+                        },
+                    );
+                    resource.post_error(
+                        wp_color_management_surface_feedback_v1::Error::Inert,
+                        "the surface is gone",
+                    );
+                    return;
+                }
+
+                let description = preferred_image_description(state, surface);
+                init_ready_description(state, data_init, image_description, description);
+            }
+            wp_color_management_surface_feedback_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(
+        state: &mut D,
+        _client: ClientId,
+        resource: &WpColorManagementSurfaceFeedbackV1,
+        _data: &WlSurface,
+    ) {
+        let state = state.color_management_state();
+        state
+            .feedbacks
+            .retain(|instance| instance.resource != *resource);
+    }
+}
+
+impl<D> Dispatch<WpImageDescriptionCreatorIccV1, (), D> for ColorManagementState
+where
+    D: Dispatch<WpImageDescriptionCreatorIccV1, ()>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &WpImageDescriptionCreatorIccV1,
+        request: <WpImageDescriptionCreatorIccV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        // The manager raises a protocol error when creating this object, so the client is gone
+        // before any of these requests could matter.
+        match request {
+            wp_image_description_creator_icc_v1::Request::SetIccFile { .. } => (),
+            wp_image_description_creator_icc_v1::Request::Create { .. } => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<WpImageDescriptionCreatorParamsV1, Mutex<ParamsBuilder>, D>
+    for ColorManagementState
+where
+    D: Dispatch<WpImageDescriptionCreatorParamsV1, Mutex<ParamsBuilder>>,
+    D: Dispatch<WpImageDescriptionV1, ImageDescriptionData>,
+    D: ColorManagementHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &WpImageDescriptionCreatorParamsV1,
+        request: <WpImageDescriptionCreatorParamsV1 as Resource>::Request,
+        builder: &Mutex<ParamsBuilder>,
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        use wp_image_description_creator_params_v1::{Error, Request};
+
+        fn set_once<T>(
+            resource: &WpImageDescriptionCreatorParamsV1,
+            field: &mut Option<T>,
+            value: T,
+        ) {
+            if field.is_some() {
+                resource.post_error(Error::AlreadySet, "the parameter was already set");
+            } else {
+                *field = Some(value);
+            }
+        }
+
+        let mut builder = builder.lock().unwrap();
+
+        match request {
+            Request::Create { image_description } => {
+                let (Some(tf), Some(primaries)) = (builder.tf, builder.primaries) else {
+                    data_init.init(
+                        image_description,
+                        ImageDescriptionData {
+                            description: None,
+                            allow_information: false,
+                        },
+                    );
+                    resource.post_error(
+                        Error::IncompleteSet,
+                        "the transfer function and the primaries must be set",
+                    );
+                    return;
+                };
+
+                let description = ImageDescription {
+                    tf,
+                    primaries,
+                    luminances: builder.luminances,
+                    mastering_primaries: builder.mastering_primaries,
+                    mastering_luminance: builder.mastering_luminance,
+                    max_cll: builder.max_cll,
+                    max_fall: builder.max_fall,
+                };
+
+                // Client-created image descriptions always get a new identity.
+                let identity = state.color_management_state().next_identity();
+                let data = ImageDescriptionData {
+                    description: Some(description),
+                    allow_information: false,
+                };
+                let image_description = data_init.init(image_description, data);
+                image_description.ready(identity);
+            }
+            Request::SetTfNamed { tf } => match tf {
+                WEnum::Value(
+                    tf @ (TransferFunction::Srgb
+                    | TransferFunction::Gamma22
+                    | TransferFunction::St2084Pq),
+                ) => set_once(resource, &mut builder.tf, tf),
+                _ => resource.post_error(Error::InvalidTf, "unsupported transfer function"),
+            },
+            Request::SetPrimariesNamed { primaries } => match primaries {
+                WEnum::Value(primaries @ (Primaries::Srgb | Primaries::Bt2020)) => {
+                    set_once(resource, &mut builder.primaries, primaries)
+                }
+                _ => resource.post_error(Error::InvalidPrimariesNamed, "unsupported primaries"),
+            },
+            Request::SetLuminances {
+                min_lum,
+                max_lum,
+                reference_lum,
+            } => {
+                if u64::from(max_lum) * 10000 <= u64::from(min_lum)
+                    || u64::from(reference_lum) * 10000 <= u64::from(min_lum)
+                {
+                    resource.post_error(Error::InvalidLuminance, "invalid luminances");
+                    return;
+                }
+
+                set_once(
+                    resource,
+                    &mut builder.luminances,
+                    (min_lum, max_lum, reference_lum),
+                );
+            }
+            Request::SetMasteringDisplayPrimaries {
+                r_x,
+                r_y,
+                g_x,
+                g_y,
+                b_x,
+                b_y,
+                w_x,
+                w_y,
+            } => set_once(
+                resource,
+                &mut builder.mastering_primaries,
+                [r_x, r_y, g_x, g_y, b_x, b_y, w_x, w_y],
+            ),
+            Request::SetMasteringLuminance { min_lum, max_lum } => {
+                if u64::from(max_lum) * 10000 <= u64::from(min_lum) {
+                    resource.post_error(Error::InvalidLuminance, "invalid mastering luminance");
+                    return;
+                }
+
+                set_once(
+                    resource,
+                    &mut builder.mastering_luminance,
+                    (min_lum, max_lum),
+                );
+            }
+            Request::SetMaxCll { max_cll } => set_once(resource, &mut builder.max_cll, max_cll),
+            Request::SetMaxFall { max_fall } => set_once(resource, &mut builder.max_fall, max_fall),
+            Request::SetTfPower { .. } | Request::SetPrimaries { .. } => {
+                resource.post_error(Error::UnsupportedFeature, "unsupported feature");
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<WpImageDescriptionV1, ImageDescriptionData, D> for ColorManagementState
+where
+    D: Dispatch<WpImageDescriptionV1, ImageDescriptionData>,
+    D: Dispatch<WpImageDescriptionInfoV1, ()>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        resource: &WpImageDescriptionV1,
+        request: <WpImageDescriptionV1 as Resource>::Request,
+        data: &ImageDescriptionData,
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            wp_image_description_v1::Request::GetInformation { information } => {
+                let info = data_init.init(information, ());
+
+                let Some(description) = data.description else {
+                    resource.post_error(
+                        wp_image_description_v1::Error::NotReady,
+                        "the image description failed",
+                    );
+                    return;
+                };
+
+                if !data.allow_information {
+                    resource.post_error(
+                        wp_image_description_v1::Error::NoInformation,
+                        "information is only available for compositor image descriptions",
+                    );
+                    return;
+                }
+
+                send_information(&info, &description);
+            }
+            wp_image_description_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn send_information(info: &WpImageDescriptionInfoV1, description: &ImageDescription) {
+    info.primaries_named(description.primaries);
+    let [r_x, r_y, g_x, g_y, b_x, b_y, w_x, w_y] = primaries_chromaticities(description.primaries);
+    info.primaries(r_x, r_y, g_x, g_y, b_x, b_y, w_x, w_y);
+    info.tf_named(description.tf);
+
+    // SDR content has 80 cd/m², with 0.2 cd/m² black as in the sRGB spec.
+    let (min_lum, max_lum, reference_lum) = description.luminances.unwrap_or_else(|| {
+        if description.tf == TransferFunction::St2084Pq {
+            (50, 10000, SDR_REFERENCE_LUMINANCE)
+        } else {
+            (2000, 80, 80)
+        }
+    });
+    info.luminances(min_lum, max_lum, reference_lum);
+
+    let [r_x, r_y, g_x, g_y, b_x, b_y, w_x, w_y] = description
+        .mastering_primaries
+        .unwrap_or_else(|| primaries_chromaticities(description.primaries));
+    info.target_primaries(r_x, r_y, g_x, g_y, b_x, b_y, w_x, w_y);
+    let (min_lum, max_lum) = description
+        .mastering_luminance
+        .unwrap_or((min_lum, max_lum));
+    info.target_luminance(min_lum, max_lum);
+    if let Some(max_cll) = description.max_cll {
+        info.target_max_cll(max_cll);
+    }
+    if let Some(max_fall) = description.max_fall {
+        info.target_max_fall(max_fall);
+    }
+
+    info.done();
+}
+
+/// Returns the red, green, blue and white point chromaticities in 1 / 1 000 000 units.
+fn primaries_chromaticities(primaries: Primaries) -> [i32; 8] {
+    match primaries {
+        Primaries::Bt2020 => [
+            708_000, 292_000, 170_000, 797_000, 131_000, 46_000, 312_700, 329_000,
+        ],
+        _ => [
+            640_000, 330_000, 300_000, 600_000, 150_000, 60_000, 312_700, 329_000,
+        ],
+    }
+}
+
+impl<D> Dispatch<WpImageDescriptionInfoV1, (), D> for ColorManagementState
+where
+    D: Dispatch<WpImageDescriptionInfoV1, ()>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &WpImageDescriptionInfoV1,
+        _request: <WpImageDescriptionInfoV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        // The info object has no requests.
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_color_management {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_color_manager_v1::WpColorManagerV1: $crate::protocols::color_management::ColorManagementGlobalData
+        ] => $crate::protocols::color_management::ColorManagementState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_color_manager_v1::WpColorManagerV1: ()
+        ] => $crate::protocols::color_management::ColorManagementState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_color_management_output_v1::WpColorManagementOutputV1: Option<smithay::output::Output>
+        ] => $crate::protocols::color_management::ColorManagementState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_color_management_surface_v1::WpColorManagementSurfaceV1: smithay::reexports::wayland_server::protocol::wl_surface::WlSurface
+        ] => $crate::protocols::color_management::ColorManagementState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_color_management_surface_feedback_v1::WpColorManagementSurfaceFeedbackV1: smithay::reexports::wayland_server::protocol::wl_surface::WlSurface
+        ] => $crate::protocols::color_management::ColorManagementState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_image_description_creator_icc_v1::WpImageDescriptionCreatorIccV1: ()
+        ] => $crate::protocols::color_management::ColorManagementState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_image_description_creator_params_v1::WpImageDescriptionCreatorParamsV1: std::sync::Mutex<$crate::protocols::color_management::ParamsBuilder>
+        ] => $crate::protocols::color_management::ColorManagementState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_image_description_v1::WpImageDescriptionV1: $crate::protocols::color_management::ImageDescriptionData
+        ] => $crate::protocols::color_management::ColorManagementState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::color_management::v1::server::wp_image_description_info_v1::WpImageDescriptionInfoV1: ()
+        ] => $crate::protocols::color_management::ColorManagementState);
+    };
+}
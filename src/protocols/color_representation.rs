@@ -0,0 +1,353 @@
+//! wp-color-representation protocol implementation.
+//!
+//! Smithay imports and scans out buffers without passing any color encoding hints to EGL or KMS,
+//! so both paths use the defaults: identity for RGB, and BT.601 limited range for YUV (the TTY
+//! backend resets the plane COLOR_ENCODING and COLOR_RANGE properties to match). We only advertise
+//! what those defaults get right, and reject everything else, so that clients can tell whether
+//! it's safe to hand us their buffers directly, or whether they need to convert them first.
+//!
+//! Since the advertised values are exactly the defaults, the only thing left to track is the
+//! declared coefficients: on commit, we check that they match the kind of buffer, since the
+//! defaults only work out when they do. The alpha mode and the chroma location are validated but
+//! not stored, as there is nothing to do with them.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use smithay::reexports::wayland_protocols::wp::color_representation::v1::server::wp_color_representation_manager_v1::{
+    self, WpColorRepresentationManagerV1,
+};
+use smithay::reexports::wayland_protocols::wp::color_representation::v1::server::wp_color_representation_surface_v1::{
+    self, WpColorRepresentationSurfaceV1,
+};
+pub use smithay::reexports::wayland_protocols::wp::color_representation::v1::server::wp_color_representation_surface_v1::{
+    AlphaMode, Coefficients, Range,
+};
+use smithay::reexports::wayland_server::backend::ClientId;
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::reexports::wayland_server::{
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource, WEnum,
+};
+use smithay::backend::allocator::{Buffer as _, Fourcc};
+use smithay::wayland::compositor::{
+    add_pre_commit_hook, remove_pre_commit_hook, with_states, BufferAssignment, Cacheable,
+    HookId, SurfaceAttributes,
+};
+use smithay::wayland::dmabuf::get_dmabuf;
+
+const VERSION: u32 = 1;
+
+/// Alpha modes that we render correctly.
+const SUPPORTED_ALPHA_MODES: &[AlphaMode] = &[AlphaMode::PremultipliedElectrical];
+
+/// Matrix coefficients and ranges that we sample and scan out correctly.
+const SUPPORTED_COEFFICIENTS: &[(Coefficients, Range)] = &[
+    (Coefficients::Identity, Range::Full),
+    (Coefficients::Bt601, Range::Limited),
+];
+
+pub struct ColorRepresentationManagerState {}
+
+pub struct ColorRepresentationManagerGlobalData {
+    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+#[derive(Default)]
+struct ColorRepresentationData {
+    /// Whether a color representation object currently exists for this surface.
+    exists: AtomicBool,
+    /// Pre-commit hook checking the representation against the buffer.
+    hook: Mutex<Option<HookId>>,
+}
+
+/// Double-buffered color representation of a surface.
+#[derive(Debug, Default, Clone, Copy)]
+struct ColorRepresentationCachedState {
+    /// Matrix coefficients and range declared for the buffers of the surface.
+    coefficients: Option<(Coefficients, Range)>,
+}
+
+impl Cacheable for ColorRepresentationCachedState {
+    fn commit(&mut self, _dh: &DisplayHandle) -> Self {
+        *self
+    }
+
+    fn merge_into(self, into: &mut Self, _dh: &DisplayHandle) {
+        *into = self;
+    }
+}
+
+fn with_pending<T>(
+    surface: &WlSurface,
+    f: impl FnOnce(&mut ColorRepresentationCachedState) -> T,
+) -> T {
+    with_states(surface, |states| {
+        let mut cached = states.cached_state.get::<ColorRepresentationCachedState>();
+        f(cached.pending())
+    })
+}
+
+fn is_yuv(format: Fourcc) -> bool {
+    matches!(
+        format,
+        Fourcc::Nv12
+            | Fourcc::Nv21
+            | Fourcc::Nv16
+            | Fourcc::Nv61
+            | Fourcc::Nv24
+            | Fourcc::Nv42
+            | Fourcc::P010
+            | Fourcc::P012
+            | Fourcc::P016
+            | Fourcc::Yuyv
+            | Fourcc::Yvyu
+            | Fourcc::Uyvy
+            | Fourcc::Vyuy
+            | Fourcc::Yuv420
+            | Fourcc::Yvu420
+            | Fourcc::Yuv422
+            | Fourcc::Yvu422
+            | Fourcc::Yuv444
+            | Fourcc::Yvu444
+            | Fourcc::Ayuv
+            | Fourcc::Xyuv8888
+    )
+}
+
+/// Checks the pending coefficients against the pending buffer.
+fn check_pending_buffer(resource: &WpColorRepresentationSurfaceV1, surface: &WlSurface) {
+    let res = with_states(surface, |states| {
+        let mut cached = states.cached_state.get::<ColorRepresentationCachedState>();
+        let Some((coefficients, _)) = cached.pending().coefficients else {
+            return Ok(());
+        };
+
+        let mut attrs = states.cached_state.get::<SurfaceAttributes>();
+        let Some(BufferAssignment::NewBuffer(buffer)) = &attrs.pending().buffer else {
+            return Ok(());
+        };
+
+        // Non-dmabuf buffers are always RGB.
+        let yuv = get_dmabuf(buffer).is_ok_and(|dmabuf| is_yuv(dmabuf.format().code));
+        if yuv == (coefficients == Coefficients::Identity) {
+            return Err(());
+        }
+        Ok(())
+    });
+
+    if res.is_err() {
+        resource.post_error(
+            wp_color_representation_surface_v1::Error::PixelFormat,
+            "the coefficients don't match the buffer format",
+        );
+    }
+}
+
+fn with_data<T>(surface: &WlSurface, f: impl FnOnce(&ColorRepresentationData) -> T) -> T {
+    with_states(surface, |states| {
+        states
+            .data_map
+            .insert_if_missing_threadsafe(ColorRepresentationData::default);
+        f(states.data_map.get::<ColorRepresentationData>().unwrap())
+    })
+}
+
+impl ColorRepresentationManagerState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<WpColorRepresentationManagerV1, ColorRepresentationManagerGlobalData>,
+        D: Dispatch<WpColorRepresentationManagerV1, ()>,
+        D: 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let global_data = ColorRepresentationManagerGlobalData {
+            filter: Box::new(filter),
+        };
+        display.create_global::<D, WpColorRepresentationManagerV1, _>(VERSION, global_data);
+
+        Self {}
+    }
+}
+
+impl<D> GlobalDispatch<WpColorRepresentationManagerV1, ColorRepresentationManagerGlobalData, D>
+    for ColorRepresentationManagerState
+where
+    D: GlobalDispatch<WpColorRepresentationManagerV1, ColorRepresentationManagerGlobalData>,
+    D: Dispatch<WpColorRepresentationManagerV1, ()>,
+    D: 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        manager: New<WpColorRepresentationManagerV1>,
+        _manager_state: &ColorRepresentationManagerGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let manager = data_init.init(manager, ());
+
+        for alpha_mode in SUPPORTED_ALPHA_MODES {
+            manager.supported_alpha_mode(*alpha_mode);
+        }
+        for (coefficients, range) in SUPPORTED_COEFFICIENTS {
+            manager.supported_coefficients_and_ranges(*coefficients, *range);
+        }
+        manager.done();
+    }
+
+    fn can_view(client: Client, global_data: &ColorRepresentationManagerGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<WpColorRepresentationManagerV1, (), D> for ColorRepresentationManagerState
+where
+    D: Dispatch<WpColorRepresentationManagerV1, ()>,
+    D: Dispatch<WpColorRepresentationSurfaceV1, WlSurface>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        manager: &WpColorRepresentationManagerV1,
+        request: <WpColorRepresentationManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            wp_color_representation_manager_v1::Request::GetSurface { id, surface } => {
+                let already_exists =
+                    with_data(&surface, |data| data.exists.swap(true, Ordering::Relaxed));
+
+                let resource = data_init.init(id, surface.clone());
+
+                if already_exists {
+                    manager.post_error(
+                        wp_color_representation_manager_v1::Error::SurfaceExists,
+                        "the surface already has a color representation object",
+                    );
+                    return;
+                }
+
+                let hook = add_pre_commit_hook::<D, _>(&surface, move |_, _, surface| {
+                    check_pending_buffer(&resource, surface);
+                });
+                with_data(&surface, |data| *data.hook.lock().unwrap() = Some(hook));
+            }
+            wp_color_representation_manager_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<WpColorRepresentationSurfaceV1, WlSurface, D> for ColorRepresentationManagerState
+where
+    D: Dispatch<WpColorRepresentationSurfaceV1, WlSurface>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        resource: &WpColorRepresentationSurfaceV1,
+        request: <WpColorRepresentationSurfaceV1 as Resource>::Request,
+        surface: &WlSurface,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        if !surface.is_alive() {
+            if !matches!(
+                request,
+                wp_color_representation_surface_v1::Request::Destroy
+            ) {
+                resource.post_error(
+                    wp_color_representation_surface_v1::Error::Inert,
+                    "the surface is gone",
+                );
+            }
+            return;
+        }
+
+        match request {
+            wp_color_representation_surface_v1::Request::SetAlphaMode { alpha_mode } => {
+                // The only supported mode is the one we always render with.
+                if !matches!(alpha_mode, WEnum::Value(mode) if SUPPORTED_ALPHA_MODES.contains(&mode))
+                {
+                    resource.post_error(
+                        wp_color_representation_surface_v1::Error::AlphaMode,
+                        "unsupported alpha mode",
+                    );
+                }
+            }
+            wp_color_representation_surface_v1::Request::SetCoefficientsAndRange {
+                coefficients,
+                range,
+            } => {
+                let value = match (coefficients, range) {
+                    (WEnum::Value(coefficients), WEnum::Value(range))
+                        if SUPPORTED_COEFFICIENTS.contains(&(coefficients, range)) =>
+                    {
+                        (coefficients, range)
+                    }
+                    _ => {
+                        resource.post_error(
+                            wp_color_representation_surface_v1::Error::Coefficients,
+                            "unsupported coefficients and range",
+                        );
+                        return;
+                    }
+                };
+
+                with_pending(surface, |pending| pending.coefficients = Some(value));
+            }
+            wp_color_representation_surface_v1::Request::SetChromaLocation { chroma_location } => {
+                // The chroma location is only a hint, and neither EGL nor KMS get it.
+                if !matches!(chroma_location, WEnum::Value(_)) {
+                    resource.post_error(
+                        wp_color_representation_surface_v1::Error::ChromaLocation,
+                        "invalid chroma location",
+                    );
+                }
+            }
+            wp_color_representation_surface_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(
+        _state: &mut D,
+        _client: ClientId,
+        _resource: &WpColorRepresentationSurfaceV1,
+        surface: &WlSurface,
+    ) {
+        // Destroying the object resets the surface to the default representation on the next
+        // commit.
+        if surface.is_alive() {
+            let hook = with_data(surface, |data| {
+                data.exists.store(false, Ordering::Relaxed);
+                data.hook.lock().unwrap().take()
+            });
+            if let Some(hook) = hook {
+                remove_pre_commit_hook(surface, hook);
+            }
+            with_pending(surface, |pending| pending.coefficients = None);
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_color_representation {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::color_representation::v1::server::wp_color_representation_manager_v1::WpColorRepresentationManagerV1: $crate::protocols::color_representation::ColorRepresentationManagerGlobalData
+        ] => $crate::protocols::color_representation::ColorRepresentationManagerState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::color_representation::v1::server::wp_color_representation_manager_v1::WpColorRepresentationManagerV1: ()
+        ] => $crate::protocols::color_representation::ColorRepresentationManagerState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::color_representation::v1::server::wp_color_representation_surface_v1::WpColorRepresentationSurfaceV1: smithay::reexports::wayland_server::protocol::wl_surface::WlSurface
+        ] => $crate::protocols::color_representation::ColorRepresentationManagerState);
+    };
+}
@@ -0,0 +1,291 @@
+//! ext-foreign-toplevel-list protocol implementation.
+//!
+//! The handles don't do anything by themselves, but other protocols like ext-image-capture-source
+//! use them to refer to windows. The handle user data is the window's WlSurface.
+//!
+//! The toplevel identifier is the window id from the IPC, so that tools can match the handles with
+//! the windows from the IPC. Window ids are never reused, as the protocol requires.
+
+use std::collections::HashMap;
+
+use smithay::reexports::wayland_protocols::ext::foreign_toplevel_list::v1::server::ext_foreign_toplevel_handle_v1::{
+    self, ExtForeignToplevelHandleV1,
+};
+use smithay::reexports::wayland_protocols::ext::foreign_toplevel_list::v1::server::ext_foreign_toplevel_list_v1::{
+    self, ExtForeignToplevelListV1,
+};
+use smithay::reexports::wayland_server::backend::ClientId;
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::reexports::wayland_server::{
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+};
+
+use crate::niri::State;
+use crate::utils::with_toplevel_role;
+
+const VERSION: u32 = 1;
+
+pub struct ExtForeignToplevelListState {
+    display: DisplayHandle,
+    instances: Vec<ExtForeignToplevelListV1>,
+    toplevels: HashMap<WlSurface, ToplevelData>,
+}
+
+pub trait ExtForeignToplevelListHandler {
+    fn ext_foreign_toplevel_list_state(&mut self) -> &mut ExtForeignToplevelListState;
+}
+
+struct ToplevelData {
+    identifier: String,
+    title: Option<String>,
+    app_id: Option<String>,
+    instances: Vec<ExtForeignToplevelHandleV1>,
+}
+
+pub struct ExtForeignToplevelListGlobalData {
+    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+impl ExtForeignToplevelListState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<ExtForeignToplevelListV1, ExtForeignToplevelListGlobalData>,
+        D: Dispatch<ExtForeignToplevelListV1, ()>,
+        D: 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let global_data = ExtForeignToplevelListGlobalData {
+            filter: Box::new(filter),
+        };
+        display.create_global::<D, ExtForeignToplevelListV1, _>(VERSION, global_data);
+        Self {
+            display: display.clone(),
+            instances: Vec::new(),
+            toplevels: HashMap::new(),
+        }
+    }
+}
+
+pub fn refresh(state: &mut State) {
+    let _span = tracy_client::span!("ext_foreign_toplevel_list::refresh");
+
+    let protocol_state = &mut state.niri.ext_foreign_toplevel_list_state;
+
+    // Handle closed windows.
+    protocol_state.toplevels.retain(|surface, data| {
+        if state.niri.layout.find_window_and_output(surface).is_some() {
+            return true;
+        }
+
+        for instance in &data.instances {
+            instance.closed();
+        }
+
+        false
+    });
+
+    // Handle new and existing windows.
+    state.niri.layout.with_windows(|mapped, _, _, _| {
+        let wl_surface = mapped.toplevel().wl_surface();
+        let (title, app_id) = with_toplevel_role(mapped.toplevel(), |role| {
+            (role.title.clone(), role.app_id.clone())
+        });
+
+        if let Some(data) = protocol_state.toplevels.get_mut(wl_surface) {
+            // Existing window, check if anything changed.
+            let new_title = (data.title != title).then(|| title.clone()).flatten();
+            let new_app_id = (data.app_id != app_id).then(|| app_id.clone()).flatten();
+            data.title = title;
+            data.app_id = app_id;
+
+            if new_title.is_none() && new_app_id.is_none() {
+                return;
+            }
+
+            for instance in &data.instances {
+                if let Some(title) = &new_title {
+                    instance.title(title.clone());
+                }
+                if let Some(app_id) = &new_app_id {
+                    instance.app_id(app_id.clone());
+                }
+                instance.done();
+            }
+        } else {
+            // New window, start tracking it.
+            let mut data = ToplevelData {
+                identifier: mapped.id().get().to_string(),
+                title,
+                app_id,
+                instances: Vec::new(),
+            };
+
+            for list in &protocol_state.instances {
+                if let Some(client) = list.client() {
+                    data.add_instance::<State>(&protocol_state.display, &client, list, wl_surface);
+                }
+            }
+
+            protocol_state.toplevels.insert(wl_surface.clone(), data);
+        }
+    });
+}
+
+/// Returns the window surface that this handle refers to.
+pub fn surface_for_handle(handle: &ExtForeignToplevelHandleV1) -> Option<WlSurface> {
+    handle
+        .data::<WlSurface>()
+        .filter(|surface| surface.is_alive())
+        .cloned()
+}
+
+impl ToplevelData {
+    fn add_instance<D>(
+        &mut self,
+        handle: &DisplayHandle,
+        client: &Client,
+        list: &ExtForeignToplevelListV1,
+        wl_surface: &WlSurface,
+    ) where
+        D: Dispatch<ExtForeignToplevelHandleV1, WlSurface>,
+        D: 'static,
+    {
+        let toplevel = client
+            .create_resource::<ExtForeignToplevelHandleV1, _, D>(
+                handle,
+                list.version(),
+                wl_surface.clone(),
+            )
+            .unwrap();
+        list.toplevel(&toplevel);
+
+        toplevel.identifier(self.identifier.clone());
+        if let Some(title) = &self.title {
+            toplevel.title(title.clone());
+        }
+        if let Some(app_id) = &self.app_id {
+            toplevel.app_id(app_id.clone());
+        }
+        toplevel.done();
+
+        self.instances.push(toplevel);
+    }
+}
+
+impl<D> GlobalDispatch<ExtForeignToplevelListV1, ExtForeignToplevelListGlobalData, D>
+    for ExtForeignToplevelListState
+where
+    D: GlobalDispatch<ExtForeignToplevelListV1, ExtForeignToplevelListGlobalData>,
+    D: Dispatch<ExtForeignToplevelListV1, ()>,
+    D: Dispatch<ExtForeignToplevelHandleV1, WlSurface>,
+    D: ExtForeignToplevelListHandler,
+    D: 'static,
+{
+    fn bind(
+        state: &mut D,
+        handle: &DisplayHandle,
+        client: &Client,
+        resource: New<ExtForeignToplevelListV1>,
+        _global_data: &ExtForeignToplevelListGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let list = data_init.init(resource, ());
+
+        let state = state.ext_foreign_toplevel_list_state();
+
+        for (wl_surface, data) in &mut state.toplevels {
+            data.add_instance::<D>(handle, client, &list, wl_surface);
+        }
+
+        state.instances.push(list);
+    }
+
+    fn can_view(client: Client, global_data: &ExtForeignToplevelListGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<ExtForeignToplevelListV1, (), D> for ExtForeignToplevelListState
+where
+    D: Dispatch<ExtForeignToplevelListV1, ()>,
+    D: ExtForeignToplevelListHandler,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &ExtForeignToplevelListV1,
+        request: <ExtForeignToplevelListV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_foreign_toplevel_list_v1::Request::Stop => {
+                resource.finished();
+
+                let state = state.ext_foreign_toplevel_list_state();
+                state.instances.retain(|x| x != resource);
+            }
+            ext_foreign_toplevel_list_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(
+        state: &mut D,
+        _client: ClientId,
+        resource: &ExtForeignToplevelListV1,
+        _data: &(),
+    ) {
+        let state = state.ext_foreign_toplevel_list_state();
+        state.instances.retain(|x| x != resource);
+    }
+}
+
+impl<D> Dispatch<ExtForeignToplevelHandleV1, WlSurface, D> for ExtForeignToplevelListState
+where
+    D: Dispatch<ExtForeignToplevelHandleV1, WlSurface>,
+    D: ExtForeignToplevelListHandler,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &ExtForeignToplevelHandleV1,
+        request: <ExtForeignToplevelHandleV1 as Resource>::Request,
+        _data: &WlSurface,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_foreign_toplevel_handle_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(
+        state: &mut D,
+        _client: ClientId,
+        resource: &ExtForeignToplevelHandleV1,
+        _data: &WlSurface,
+    ) {
+        let state = state.ext_foreign_toplevel_list_state();
+        for data in state.toplevels.values_mut() {
+            data.instances.retain(|instance| instance != resource);
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_ext_foreign_toplevel_list {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::foreign_toplevel_list::v1::server::ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1: $crate::protocols::ext_foreign_toplevel_list::ExtForeignToplevelListGlobalData
+        ] => $crate::protocols::ext_foreign_toplevel_list::ExtForeignToplevelListState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::foreign_toplevel_list::v1::server::ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1: ()
+        ] => $crate::protocols::ext_foreign_toplevel_list::ExtForeignToplevelListState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::foreign_toplevel_list::v1::server::ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1: smithay::reexports::wayland_server::protocol::wl_surface::WlSurface
+        ] => $crate::protocols::ext_foreign_toplevel_list::ExtForeignToplevelListState);
+    };
+}
@@ -329,7 +329,15 @@ fn refresh_workspace(
             }
 
             if recreate {
-                remove_workspace_instances(&protocol_state.workspace_groups, data);
+                if output_changed {
+                    // workspace_leave was already sent to the old output's group above, and the
+                    // new output's group never saw these instances.
+                    for workspace in &data.instances {
+                        workspace.removed();
+                    }
+                } else {
+                    remove_workspace_instances(&protocol_state.workspace_groups, data);
+                }
                 data.instances.clear();
 
                 for manager in protocol_state.instances.keys() {
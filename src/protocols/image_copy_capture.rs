@@ -0,0 +1,662 @@
+//! ext-image-copy-capture and ext-image-capture-source protocol implementation.
+//!
+//! Only window capture sources are supported, created from ext-foreign-toplevel-list handles.
+//! Outputs can be captured with wlr-screencopy. Frames are rendered from the window surfaces
+//! directly, so captures keep working while the window is occluded or on another workspace.
+
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use smithay::backend::renderer::damage::OutputDamageTracker;
+use smithay::reexports::wayland_protocols::ext::image_capture_source::v1::server::ext_foreign_toplevel_image_capture_source_manager_v1::{
+    self, ExtForeignToplevelImageCaptureSourceManagerV1,
+};
+use smithay::reexports::wayland_protocols::ext::image_capture_source::v1::server::ext_image_capture_source_v1::{
+    self, ExtImageCaptureSourceV1,
+};
+use smithay::reexports::wayland_protocols::ext::image_copy_capture::v1::server::ext_image_copy_capture_cursor_session_v1::{
+    self, ExtImageCopyCaptureCursorSessionV1,
+};
+pub use smithay::reexports::wayland_protocols::ext::image_copy_capture::v1::server::ext_image_copy_capture_frame_v1::FailureReason;
+use smithay::reexports::wayland_protocols::ext::image_copy_capture::v1::server::ext_image_copy_capture_frame_v1::{
+    self, ExtImageCopyCaptureFrameV1,
+};
+use smithay::reexports::wayland_protocols::ext::image_copy_capture::v1::server::ext_image_copy_capture_manager_v1::{
+    self, ExtImageCopyCaptureManagerV1,
+};
+use smithay::reexports::wayland_protocols::ext::image_copy_capture::v1::server::ext_image_copy_capture_session_v1::{
+    self, ExtImageCopyCaptureSessionV1,
+};
+use smithay::reexports::wayland_server::backend::ClientId;
+use smithay::reexports::wayland_server::protocol::wl_buffer::WlBuffer;
+use smithay::reexports::wayland_server::protocol::wl_output;
+use smithay::reexports::wayland_server::protocol::wl_shm::Format;
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::reexports::wayland_server::{
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource, WEnum,
+};
+use smithay::utils::{Physical, Scale, Size, Transform};
+use smithay::wayland::shm;
+
+use crate::niri::State;
+use crate::protocols::ext_foreign_toplevel_list::surface_for_handle;
+use crate::utils::get_monotonic_time;
+
+const VERSION: u32 = 1;
+
+pub struct ImageCopyCaptureState {
+    sessions: Vec<Session>,
+    frames: Vec<CaptureFrame>,
+}
+
+pub struct ImageCopyCaptureGlobalData {
+    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+pub trait ImageCopyCaptureHandler {
+    fn image_copy_capture_state(&mut self) -> &mut ImageCopyCaptureState;
+    /// Returns the buffer size to capture this window with, or `None` if it's not mapped.
+    fn window_capture_size(&mut self, surface: &WlSurface) -> Option<Size<i32, Physical>>;
+}
+
+/// User data of a capture source.
+pub struct ImageCaptureSourceData {
+    /// Window to capture, `None` if the toplevel handle was already dead.
+    surface: Option<WlSurface>,
+}
+
+/// User data of a capture session.
+#[derive(Default)]
+pub struct SessionData {
+    has_frame: AtomicBool,
+}
+
+/// User data of a capture frame.
+pub struct FrameData {
+    session: ExtImageCopyCaptureSessionV1,
+    inner: Mutex<FrameInner>,
+}
+
+#[derive(Default)]
+struct FrameInner {
+    buffer: Option<WlBuffer>,
+    captured: bool,
+}
+
+pub struct Session {
+    session: ExtImageCopyCaptureSessionV1,
+    surface: WlSurface,
+    buffer_size: Size<i32, Physical>,
+    scale: Scale<f64>,
+    damage_tracker: OutputDamageTracker,
+}
+
+/// Frame waiting for new window contents.
+pub struct CaptureFrame {
+    frame: ExtImageCopyCaptureFrameV1,
+    session: ExtImageCopyCaptureSessionV1,
+    buffer: WlBuffer,
+    submitted: bool,
+}
+
+impl ImageCopyCaptureState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<ExtImageCopyCaptureManagerV1, ImageCopyCaptureGlobalData>,
+        D: GlobalDispatch<
+            ExtForeignToplevelImageCaptureSourceManagerV1,
+            ImageCopyCaptureGlobalData,
+        >,
+        D: Dispatch<ExtImageCopyCaptureManagerV1, ()>,
+        D: Dispatch<ExtForeignToplevelImageCaptureSourceManagerV1, ()>,
+        D: ImageCopyCaptureHandler,
+        D: 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + Clone + 'static,
+    {
+        let global_data = ImageCopyCaptureGlobalData {
+            filter: Box::new(filter.clone()),
+        };
+        display.create_global::<D, ExtImageCopyCaptureManagerV1, _>(VERSION, global_data);
+
+        let global_data = ImageCopyCaptureGlobalData {
+            filter: Box::new(filter),
+        };
+        display.create_global::<D, ExtForeignToplevelImageCaptureSourceManagerV1, _>(
+            VERSION,
+            global_data,
+        );
+
+        Self {
+            sessions: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Returns whether some client is capturing this window.
+    pub fn is_capturing(&self, surface: &WlSurface) -> bool {
+        self.sessions.iter().any(|s| &s.surface == surface)
+    }
+
+    pub fn has_pending_frames(&self) -> bool {
+        !self.frames.is_empty()
+    }
+
+    pub fn take_frames(&mut self) -> Vec<CaptureFrame> {
+        mem::take(&mut self.frames)
+    }
+
+    pub fn push_frame(&mut self, frame: CaptureFrame) {
+        self.frames.push(frame);
+    }
+
+    pub fn session_mut(&mut self, session: &ExtImageCopyCaptureSessionV1) -> Option<&mut Session> {
+        self.sessions.iter_mut().find(|s| &s.session == session)
+    }
+
+    fn remove_session(&mut self, session: &ExtImageCopyCaptureSessionV1) {
+        self.sessions.retain(|s| &s.session != session);
+
+        // Frames of removed sessions will never be captured.
+        let (stopped, frames) = mem::take(&mut self.frames)
+            .into_iter()
+            .partition::<Vec<_>, _>(|f| &f.session == session);
+        self.frames = frames;
+        for frame in stopped {
+            frame.fail(FailureReason::Stopped);
+        }
+    }
+}
+
+/// Stops the sessions of closed windows.
+pub fn refresh(state: &mut State) {
+    let _span = tracy_client::span!("image_copy_capture::refresh");
+
+    let protocol_state = &mut state.niri.image_copy_capture_state;
+
+    let mut stopped = Vec::new();
+    for session in &protocol_state.sessions {
+        if state
+            .niri
+            .layout
+            .find_window_and_output(&session.surface)
+            .is_none()
+        {
+            session.session.stopped();
+            stopped.push(session.session.clone());
+        }
+    }
+
+    for session in stopped {
+        protocol_state.remove_session(&session);
+    }
+}
+
+impl Session {
+    pub fn surface(&self) -> &WlSurface {
+        &self.surface
+    }
+
+    pub fn damage_tracker(&mut self) -> &mut OutputDamageTracker {
+        &mut self.damage_tracker
+    }
+
+    /// Updates the buffer size and the damage tracker scale.
+    ///
+    /// Returns `false` if the buffer size changed, in which case the new buffer constraints were
+    /// sent, and the pending frame must fail.
+    pub fn update_size(&mut self, size: Size<i32, Physical>, scale: Scale<f64>) -> bool {
+        let size_changed = self.buffer_size != size;
+        if size_changed || self.scale != scale {
+            self.buffer_size = size;
+            self.scale = scale;
+            self.reset_damage();
+        }
+
+        if size_changed {
+            send_buffer_constraints(&self.session, size);
+        }
+
+        !size_changed
+    }
+
+    /// Makes the next frame report full damage.
+    pub fn reset_damage(&mut self) {
+        self.damage_tracker =
+            OutputDamageTracker::new(self.buffer_size, self.scale, Transform::Normal);
+    }
+}
+
+impl CaptureFrame {
+    pub fn session(&self) -> &ExtImageCopyCaptureSessionV1 {
+        &self.session
+    }
+
+    pub fn buffer(&self) -> &WlBuffer {
+        &self.buffer
+    }
+
+    pub fn fail(mut self, reason: FailureReason) {
+        self.frame.failed(reason);
+        self.submitted = true;
+    }
+
+    /// Notifies the client that the whole buffer was copied.
+    pub fn submit(mut self, size: Size<i32, Physical>) {
+        self.frame.transform(wl_output::Transform::Normal);
+        self.frame.damage(0, 0, size.w, size.h);
+
+        let timestamp = get_monotonic_time();
+        let tv_sec_hi = (timestamp.as_secs() >> 32) as u32;
+        let tv_sec_lo = (timestamp.as_secs() & 0xFFFFFFFF) as u32;
+        let tv_nsec = timestamp.subsec_nanos();
+        self.frame.presentation_time(tv_sec_hi, tv_sec_lo, tv_nsec);
+
+        self.frame.ready();
+        self.submitted = true;
+    }
+}
+
+impl Drop for CaptureFrame {
+    fn drop(&mut self) {
+        if !self.submitted {
+            self.frame.failed(FailureReason::Unknown);
+        }
+    }
+}
+
+fn send_buffer_constraints(session: &ExtImageCopyCaptureSessionV1, size: Size<i32, Physical>) {
+    // FIXME: dmabuf support.
+    session.buffer_size(size.w as u32, size.h as u32);
+    session.shm_format(Format::Xrgb8888);
+    session.done();
+}
+
+fn buffer_matches(buffer: &WlBuffer, size: Size<i32, Physical>) -> bool {
+    shm::with_buffer_contents(buffer, |_, shm_len, buffer_data| {
+        buffer_data.format == Format::Xrgb8888
+            && buffer_data.width == size.w
+            && buffer_data.height == size.h
+            && buffer_data.stride == size.w * 4
+            && shm_len == buffer_data.stride as usize * buffer_data.height as usize
+    })
+    .unwrap_or(false)
+}
+
+impl<D> GlobalDispatch<ExtForeignToplevelImageCaptureSourceManagerV1, ImageCopyCaptureGlobalData, D>
+    for ImageCopyCaptureState
+where
+    D: GlobalDispatch<ExtForeignToplevelImageCaptureSourceManagerV1, ImageCopyCaptureGlobalData>,
+    D: Dispatch<ExtForeignToplevelImageCaptureSourceManagerV1, ()>,
+    D: 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        manager: New<ExtForeignToplevelImageCaptureSourceManagerV1>,
+        _global_data: &ImageCopyCaptureGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(manager, ());
+    }
+
+    fn can_view(client: Client, global_data: &ImageCopyCaptureGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<ExtForeignToplevelImageCaptureSourceManagerV1, (), D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtForeignToplevelImageCaptureSourceManagerV1, ()>,
+    D: Dispatch<ExtImageCaptureSourceV1, ImageCaptureSourceData>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &ExtForeignToplevelImageCaptureSourceManagerV1,
+        request: <ExtForeignToplevelImageCaptureSourceManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_foreign_toplevel_image_capture_source_manager_v1::Request::CreateSource {
+                source,
+                toplevel_handle,
+            } => {
+                let surface = surface_for_handle(&toplevel_handle);
+                data_init.init(source, ImageCaptureSourceData { surface });
+            }
+            ext_foreign_toplevel_image_capture_source_manager_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ExtImageCaptureSourceV1, ImageCaptureSourceData, D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtImageCaptureSourceV1, ImageCaptureSourceData>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &ExtImageCaptureSourceV1,
+        request: <ExtImageCaptureSourceV1 as Resource>::Request,
+        _data: &ImageCaptureSourceData,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_image_capture_source_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> GlobalDispatch<ExtImageCopyCaptureManagerV1, ImageCopyCaptureGlobalData, D>
+    for ImageCopyCaptureState
+where
+    D: GlobalDispatch<ExtImageCopyCaptureManagerV1, ImageCopyCaptureGlobalData>,
+    D: Dispatch<ExtImageCopyCaptureManagerV1, ()>,
+    D: 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        manager: New<ExtImageCopyCaptureManagerV1>,
+        _global_data: &ImageCopyCaptureGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(manager, ());
+    }
+
+    fn can_view(client: Client, global_data: &ImageCopyCaptureGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<ExtImageCopyCaptureManagerV1, (), D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtImageCopyCaptureManagerV1, ()>,
+    D: Dispatch<ExtImageCopyCaptureSessionV1, SessionData>,
+    D: Dispatch<ExtImageCopyCaptureCursorSessionV1, ()>,
+    D: ImageCopyCaptureHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &ExtImageCopyCaptureManagerV1,
+        request: <ExtImageCopyCaptureManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_image_copy_capture_manager_v1::Request::CreateSession {
+                session,
+                source,
+                options,
+            } => {
+                let session = data_init.init(session, SessionData::default());
+
+                if let WEnum::Unknown(_) = options {
+                    resource.post_error(
+                        ext_image_copy_capture_manager_v1::Error::InvalidOption,
+                        "invalid options",
+                    );
+                    return;
+                }
+                // The cursor is never painted, since it's not a part of the window.
+
+                let surface = source
+                    .data::<ImageCaptureSourceData>()
+                    .and_then(|data| data.surface.clone());
+                let Some(surface) = surface else {
+                    trace!("image copy capture client requested a dead source");
+                    session.stopped();
+                    return;
+                };
+
+                let Some(size) = state.window_capture_size(&surface) else {
+                    trace!("image copy capture client requested an unmapped window");
+                    session.stopped();
+                    return;
+                };
+
+                send_buffer_constraints(&session, size);
+
+                state.image_copy_capture_state().sessions.push(Session {
+                    session,
+                    surface,
+                    buffer_size: size,
+                    scale: Scale::from(1.),
+                    damage_tracker: OutputDamageTracker::new(size, 1., Transform::Normal),
+                });
+            }
+            ext_image_copy_capture_manager_v1::Request::CreatePointerCursorSession {
+                session,
+                ..
+            } => {
+                // Cursor sessions are not supported, their capture sessions stop right away.
+                data_init.init(session, ());
+            }
+            ext_image_copy_capture_manager_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ExtImageCopyCaptureCursorSessionV1, (), D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtImageCopyCaptureCursorSessionV1, ()>,
+    D: Dispatch<ExtImageCopyCaptureSessionV1, SessionData>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &ExtImageCopyCaptureCursorSessionV1,
+        request: <ExtImageCopyCaptureCursorSessionV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_image_copy_capture_cursor_session_v1::Request::GetCaptureSession { session } => {
+                let session = data_init.init(session, SessionData::default());
+                session.stopped();
+            }
+            ext_image_copy_capture_cursor_session_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ExtImageCopyCaptureSessionV1, SessionData, D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtImageCopyCaptureSessionV1, SessionData>,
+    D: Dispatch<ExtImageCopyCaptureFrameV1, FrameData>,
+    D: ImageCopyCaptureHandler,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        resource: &ExtImageCopyCaptureSessionV1,
+        request: <ExtImageCopyCaptureSessionV1 as Resource>::Request,
+        data: &SessionData,
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_image_copy_capture_session_v1::Request::CreateFrame { frame } => {
+                data_init.init(
+                    frame,
+                    FrameData {
+                        session: resource.clone(),
+                        inner: Mutex::new(FrameInner::default()),
+                    },
+                );
+
+                if data.has_frame.swap(true, Ordering::SeqCst) {
+                    resource.post_error(
+                        ext_image_copy_capture_session_v1::Error::DuplicateFrame,
+                        "the session already has a frame",
+                    );
+                }
+            }
+            ext_image_copy_capture_session_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(
+        state: &mut D,
+        _client: ClientId,
+        resource: &ExtImageCopyCaptureSessionV1,
+        _data: &SessionData,
+    ) {
+        state.image_copy_capture_state().remove_session(resource);
+    }
+}
+
+impl<D> Dispatch<ExtImageCopyCaptureFrameV1, FrameData, D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtImageCopyCaptureFrameV1, FrameData>,
+    D: ImageCopyCaptureHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &ExtImageCopyCaptureFrameV1,
+        request: <ExtImageCopyCaptureFrameV1 as Resource>::Request,
+        data: &FrameData,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        if matches!(request, ext_image_copy_capture_frame_v1::Request::Destroy) {
+            return;
+        }
+
+        let mut inner = data.inner.lock().unwrap();
+        if inner.captured {
+            resource.post_error(
+                ext_image_copy_capture_frame_v1::Error::AlreadyCaptured,
+                "the frame was already captured",
+            );
+            return;
+        }
+
+        match request {
+            ext_image_copy_capture_frame_v1::Request::AttachBuffer { buffer } => {
+                inner.buffer = Some(buffer);
+            }
+            ext_image_copy_capture_frame_v1::Request::DamageBuffer {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                if x < 0 || y < 0 || width <= 0 || height <= 0 {
+                    resource.post_error(
+                        ext_image_copy_capture_frame_v1::Error::InvalidBufferDamage,
+                        "invalid buffer damage",
+                    );
+                }
+                // We always redraw the whole buffer, so the damage isn't needed.
+            }
+            ext_image_copy_capture_frame_v1::Request::Capture => {
+                let Some(buffer) = inner.buffer.clone() else {
+                    resource.post_error(
+                        ext_image_copy_capture_frame_v1::Error::NoBuffer,
+                        "no buffer attached",
+                    );
+                    return;
+                };
+                inner.captured = true;
+                drop(inner);
+
+                let frame = CaptureFrame {
+                    frame: resource.clone(),
+                    session: data.session.clone(),
+                    buffer,
+                    submitted: false,
+                };
+
+                let protocol_state = state.image_copy_capture_state();
+                let Some(session) = protocol_state.session_mut(&data.session) else {
+                    frame.fail(FailureReason::Stopped);
+                    return;
+                };
+
+                if !buffer_matches(frame.buffer(), session.buffer_size) {
+                    frame.fail(FailureReason::BufferConstraints);
+                    return;
+                }
+
+                protocol_state.push_frame(frame);
+            }
+            ext_image_copy_capture_frame_v1::Request::Destroy => unreachable!(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(
+        state: &mut D,
+        _client: ClientId,
+        resource: &ExtImageCopyCaptureFrameV1,
+        data: &FrameData,
+    ) {
+        if let Some(session_data) = data.session.data::<SessionData>() {
+            session_data.has_frame.store(false, Ordering::SeqCst);
+        }
+
+        let protocol_state = state.image_copy_capture_state();
+        if let Some(idx) = protocol_state
+            .frames
+            .iter()
+            .position(|f| &f.frame == resource)
+        {
+            // The client doesn't care about this frame anymore, don't send failed().
+            let mut frame = protocol_state.frames.remove(idx);
+            frame.submitted = true;
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_image_copy_capture {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_copy_capture::v1::server::ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1: $crate::protocols::image_copy_capture::ImageCopyCaptureGlobalData
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_capture_source::v1::server::ext_foreign_toplevel_image_capture_source_manager_v1::ExtForeignToplevelImageCaptureSourceManagerV1: $crate::protocols::image_copy_capture::ImageCopyCaptureGlobalData
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_copy_capture::v1::server::ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1: ()
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_capture_source::v1::server::ext_foreign_toplevel_image_capture_source_manager_v1::ExtForeignToplevelImageCaptureSourceManagerV1: ()
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_capture_source::v1::server::ext_image_capture_source_v1::ExtImageCaptureSourceV1: $crate::protocols::image_copy_capture::ImageCaptureSourceData
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_copy_capture::v1::server::ext_image_copy_capture_session_v1::ExtImageCopyCaptureSessionV1: $crate::protocols::image_copy_capture::SessionData
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_copy_capture::v1::server::ext_image_copy_capture_cursor_session_v1::ExtImageCopyCaptureCursorSessionV1: ()
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_copy_capture::v1::server::ext_image_copy_capture_frame_v1::ExtImageCopyCaptureFrameV1: $crate::protocols::image_copy_capture::FrameData
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+    };
+}
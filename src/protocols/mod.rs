@@ -1,11 +1,18 @@
+pub mod color_management;
+pub mod color_representation;
 pub mod ext_background_effect;
+pub mod ext_foreign_toplevel_list;
 pub mod ext_workspace;
 pub mod foreign_toplevel;
 pub mod gamma_control;
+pub mod image_copy_capture;
 pub mod kde_blur;
 pub mod mutter_x11_interop;
 pub mod output_management;
+pub mod pointer_warp;
 pub mod screencopy;
+pub mod tearing_control;
 pub mod virtual_pointer;
+pub mod xdg_toplevel_tag;
 
 pub mod raw;
@@ -0,0 +1,116 @@
+//! wp-pointer-warp protocol implementation.
+
+use smithay::reexports::wayland_protocols::wp::pointer_warp::v1::server::wp_pointer_warp_v1::{
+    self, WpPointerWarpV1,
+};
+use smithay::reexports::wayland_server::protocol::wl_pointer::WlPointer;
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::reexports::wayland_server::{
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+};
+use smithay::utils::{Logical, Point, Serial};
+
+const VERSION: u32 = 1;
+
+pub struct PointerWarpManagerState {}
+
+pub struct PointerWarpGlobalData {
+    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+pub trait PointerWarpHandler {
+    /// Requests to move the pointer to the location relative to the surface origin.
+    fn warp_pointer(
+        &mut self,
+        surface: &WlSurface,
+        pointer: &WlPointer,
+        location: Point<f64, Logical>,
+        serial: Serial,
+    );
+}
+
+impl PointerWarpManagerState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<WpPointerWarpV1, PointerWarpGlobalData>,
+        D: Dispatch<WpPointerWarpV1, ()>,
+        D: PointerWarpHandler,
+        D: 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let global_data = PointerWarpGlobalData {
+            filter: Box::new(filter),
+        };
+        display.create_global::<D, WpPointerWarpV1, _>(VERSION, global_data);
+
+        Self {}
+    }
+}
+
+impl<D> GlobalDispatch<WpPointerWarpV1, PointerWarpGlobalData, D> for PointerWarpManagerState
+where
+    D: GlobalDispatch<WpPointerWarpV1, PointerWarpGlobalData>,
+    D: Dispatch<WpPointerWarpV1, ()>,
+    D: PointerWarpHandler,
+    D: 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<WpPointerWarpV1>,
+        _global_data: &PointerWarpGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+
+    fn can_view(client: Client, global_data: &PointerWarpGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<WpPointerWarpV1, (), D> for PointerWarpManagerState
+where
+    D: Dispatch<WpPointerWarpV1, ()>,
+    D: PointerWarpHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        _resource: &WpPointerWarpV1,
+        request: <WpPointerWarpV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            wp_pointer_warp_v1::Request::WarpPointer {
+                surface,
+                pointer,
+                x,
+                y,
+                serial,
+            } => {
+                let location = Point::from((x, y));
+                state.warp_pointer(&surface, &pointer, location, Serial::from(serial));
+            }
+            wp_pointer_warp_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_pointer_warp {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::pointer_warp::v1::server::wp_pointer_warp_v1::WpPointerWarpV1: $crate::protocols::pointer_warp::PointerWarpGlobalData
+        ] => $crate::protocols::pointer_warp::PointerWarpManagerState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::pointer_warp::v1::server::wp_pointer_warp_v1::WpPointerWarpV1: ()
+        ] => $crate::protocols::pointer_warp::PointerWarpManagerState);
+    };
+}
@@ -0,0 +1,193 @@
+//! wp-tearing-control protocol implementation.
+//!
+//! The presentation hint is stored in the surface data so that the backend can check it for the
+//! fullscreen window. Tearing is only allowed if the window rules also allow it.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use smithay::reexports::wayland_protocols::wp::tearing_control::v1::server::wp_tearing_control_manager_v1::{
+    self, WpTearingControlManagerV1,
+};
+use smithay::reexports::wayland_protocols::wp::tearing_control::v1::server::wp_tearing_control_v1::{
+    self, WpTearingControlV1,
+};
+use smithay::reexports::wayland_server::backend::ClientId;
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::reexports::wayland_server::{
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource, WEnum,
+};
+use smithay::wayland::compositor::with_states;
+
+const VERSION: u32 = 1;
+
+pub struct TearingControlManagerState {}
+
+pub struct TearingControlManagerGlobalData {
+    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+#[derive(Default)]
+struct TearingControlData {
+    /// Whether a tearing control object currently exists for this surface.
+    exists: AtomicBool,
+    /// Whether the client asked for async presentation.
+    wants_tearing: AtomicBool,
+}
+
+/// Returns whether the client asked for tearing page flips on this surface.
+pub fn wants_tearing(surface: &WlSurface) -> bool {
+    with_states(surface, |states| {
+        states
+            .data_map
+            .get::<TearingControlData>()
+            .is_some_and(|data| data.wants_tearing.load(Ordering::Relaxed))
+    })
+}
+
+fn with_data<T>(surface: &WlSurface, f: impl FnOnce(&TearingControlData) -> T) -> T {
+    with_states(surface, |states| {
+        states
+            .data_map
+            .insert_if_missing_threadsafe(TearingControlData::default);
+        f(states.data_map.get::<TearingControlData>().unwrap())
+    })
+}
+
+impl TearingControlManagerState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<WpTearingControlManagerV1, TearingControlManagerGlobalData>,
+        D: Dispatch<WpTearingControlManagerV1, ()>,
+        D: 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let global_data = TearingControlManagerGlobalData {
+            filter: Box::new(filter),
+        };
+        display.create_global::<D, WpTearingControlManagerV1, _>(VERSION, global_data);
+
+        Self {}
+    }
+}
+
+impl<D> GlobalDispatch<WpTearingControlManagerV1, TearingControlManagerGlobalData, D>
+    for TearingControlManagerState
+where
+    D: GlobalDispatch<WpTearingControlManagerV1, TearingControlManagerGlobalData>,
+    D: Dispatch<WpTearingControlManagerV1, ()>,
+    D: 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        manager: New<WpTearingControlManagerV1>,
+        _manager_state: &TearingControlManagerGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(manager, ());
+    }
+
+    fn can_view(client: Client, global_data: &TearingControlManagerGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<WpTearingControlManagerV1, (), D> for TearingControlManagerState
+where
+    D: Dispatch<WpTearingControlManagerV1, ()>,
+    D: Dispatch<WpTearingControlV1, WlSurface>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        manager: &WpTearingControlManagerV1,
+        request: <WpTearingControlManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            wp_tearing_control_manager_v1::Request::GetTearingControl { id, surface } => {
+                let already_exists =
+                    with_data(&surface, |data| data.exists.swap(true, Ordering::Relaxed));
+
+                data_init.init(id, surface.clone());
+
+                if already_exists {
+                    manager.post_error(
+                        wp_tearing_control_manager_v1::Error::TearingControlExists,
+                        "the surface already has a tearing control object",
+                    );
+                }
+            }
+            wp_tearing_control_manager_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<WpTearingControlV1, WlSurface, D> for TearingControlManagerState
+where
+    D: Dispatch<WpTearingControlV1, WlSurface>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &WpTearingControlV1,
+        request: <WpTearingControlV1 as Resource>::Request,
+        surface: &WlSurface,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            wp_tearing_control_v1::Request::SetPresentationHint { hint } => {
+                // The hint is double-buffered in the protocol, but it only matters for the next
+                // page flip anyway, so we apply it right away.
+                let wants_tearing = matches!(
+                    hint,
+                    WEnum::Value(wp_tearing_control_v1::PresentationHint::Async)
+                );
+                with_data(surface, |data| {
+                    data.wants_tearing.store(wants_tearing, Ordering::Relaxed);
+                });
+            }
+            wp_tearing_control_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(
+        _state: &mut D,
+        _client: ClientId,
+        _resource: &WpTearingControlV1,
+        surface: &WlSurface,
+    ) {
+        // Destroying the object resets the hint to vsync.
+        if surface.is_alive() {
+            with_data(surface, |data| {
+                data.exists.store(false, Ordering::Relaxed);
+                data.wants_tearing.store(false, Ordering::Relaxed);
+            });
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_tearing_control {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::tearing_control::v1::server::wp_tearing_control_manager_v1::WpTearingControlManagerV1: $crate::protocols::tearing_control::TearingControlManagerGlobalData
+        ] => $crate::protocols::tearing_control::TearingControlManagerState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::tearing_control::v1::server::wp_tearing_control_manager_v1::WpTearingControlManagerV1: ()
+        ] => $crate::protocols::tearing_control::TearingControlManagerState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::tearing_control::v1::server::wp_tearing_control_v1::WpTearingControlV1: smithay::reexports::wayland_server::protocol::wl_surface::WlSurface
+        ] => $crate::protocols::tearing_control::TearingControlManagerState);
+    };
+}
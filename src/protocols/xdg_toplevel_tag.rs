@@ -0,0 +1,137 @@
+//! xdg-toplevel-tag protocol implementation.
+//!
+//! Tags are stored in the toplevel surface data so that window rules can match on them.
+
+use std::sync::Mutex;
+
+use smithay::reexports::wayland_protocols::xdg::shell::server::xdg_toplevel::XdgToplevel;
+use smithay::reexports::wayland_protocols::xdg::toplevel_tag::v1::server::xdg_toplevel_tag_manager_v1::{
+    self, XdgToplevelTagManagerV1,
+};
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::reexports::wayland_server::{
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+};
+use smithay::wayland::compositor::with_states;
+
+const VERSION: u32 = 1;
+
+pub struct XdgToplevelTagManagerState {}
+
+pub struct XdgToplevelTagManagerGlobalData {
+    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+pub trait XdgToplevelTagHandler {
+    fn set_toplevel_tag(&mut self, toplevel: &XdgToplevel, tag: String);
+}
+
+#[derive(Default)]
+struct ToplevelTag(Mutex<Option<String>>);
+
+/// Returns the tag that the client set on this toplevel surface.
+pub fn toplevel_tag(surface: &WlSurface) -> Option<String> {
+    with_states(surface, |states| {
+        let tag = states.data_map.get::<ToplevelTag>()?;
+        tag.0.lock().unwrap().clone()
+    })
+}
+
+/// Stores the tag on this toplevel surface and returns whether it changed.
+pub fn set_toplevel_tag(surface: &WlSurface, tag: String) -> bool {
+    with_states(surface, |states| {
+        states
+            .data_map
+            .insert_if_missing_threadsafe(ToplevelTag::default);
+        let data = states.data_map.get::<ToplevelTag>().unwrap();
+        let mut current = data.0.lock().unwrap();
+        if current.as_ref() == Some(&tag) {
+            return false;
+        }
+
+        *current = Some(tag);
+        true
+    })
+}
+
+impl XdgToplevelTagManagerState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<XdgToplevelTagManagerV1, XdgToplevelTagManagerGlobalData>,
+        D: Dispatch<XdgToplevelTagManagerV1, ()>,
+        D: XdgToplevelTagHandler,
+        D: 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let global_data = XdgToplevelTagManagerGlobalData {
+            filter: Box::new(filter),
+        };
+        display.create_global::<D, XdgToplevelTagManagerV1, _>(VERSION, global_data);
+
+        Self {}
+    }
+}
+
+impl<D> GlobalDispatch<XdgToplevelTagManagerV1, XdgToplevelTagManagerGlobalData, D>
+    for XdgToplevelTagManagerState
+where
+    D: GlobalDispatch<XdgToplevelTagManagerV1, XdgToplevelTagManagerGlobalData>,
+    D: Dispatch<XdgToplevelTagManagerV1, ()>,
+    D: XdgToplevelTagHandler,
+    D: 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        manager: New<XdgToplevelTagManagerV1>,
+        _manager_state: &XdgToplevelTagManagerGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(manager, ());
+    }
+
+    fn can_view(client: Client, global_data: &XdgToplevelTagManagerGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<XdgToplevelTagManagerV1, (), D> for XdgToplevelTagManagerState
+where
+    D: Dispatch<XdgToplevelTagManagerV1, ()>,
+    D: XdgToplevelTagHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        _resource: &XdgToplevelTagManagerV1,
+        request: <XdgToplevelTagManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            xdg_toplevel_tag_manager_v1::Request::SetToplevelTag { toplevel, tag } => {
+                state.set_toplevel_tag(&toplevel, tag);
+            }
+            // Descriptions are meant for humans, and we have nowhere to show them yet.
+            xdg_toplevel_tag_manager_v1::Request::SetToplevelDescription { .. } => (),
+            xdg_toplevel_tag_manager_v1::Request::Destroy => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_xdg_toplevel_tag {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::xdg::toplevel_tag::v1::server::xdg_toplevel_tag_manager_v1::XdgToplevelTagManagerV1: $crate::protocols::xdg_toplevel_tag::XdgToplevelTagManagerGlobalData
+        ] => $crate::protocols::xdg_toplevel_tag::XdgToplevelTagManagerState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::xdg::toplevel_tag::v1::server::xdg_toplevel_tag_manager_v1::XdgToplevelTagManagerV1: ()
+        ] => $crate::protocols::xdg_toplevel_tag::XdgToplevelTagManagerState);
+    };
+}
@@ -90,6 +90,11 @@ fn get_rerender_at(fps: Option<f32>) -> Option<Instant> {
     Some(Instant::now() + interval)
 }
 
+/// How far in physical pixels the blur kernel reaches outside of the blurred area.
+fn sample_margin(config: &Blur) -> i32 {
+    (2f32.powi(config.passes as i32 + 1) * config.radius.0 as f32).ceil() as i32
+}
+
 impl EffectsFramebuffers {
     /// Get the associated [`EffectsFramebuffers`] with this output.
     pub fn get<'a>(output: &'a Output) -> Option<RefMut<'a, Self>> {
@@ -216,6 +221,69 @@ impl EffectsFramebuffers {
 
         self.optimized_blur_rerender_at = get_rerender_at(rerender_fps);
 
+        self.render_blurred(renderer, scale, config, elements)?;
+
+        // Now blit from the last render buffer into optimized_blur
+        // We are already bound so its just a blit
+        let tex_fb = renderer.bind(&mut self.effects).unwrap();
+        let mut optimized_blur_fb = renderer.bind(&mut self.optimized_blur).unwrap();
+
+        let _ = renderer.blit(
+            &tex_fb,
+            &mut optimized_blur_fb,
+            Rectangle::from_size(self.output_size),
+            Rectangle::from_size(self.output_size),
+            TextureFilter::Linear,
+        )?;
+
+        self.optimized_blur_generation = self.optimized_blur_generation.wrapping_add(1);
+
+        Ok(())
+    }
+
+    /// Render the elements blurred into a new texture.
+    ///
+    /// Unlike the optimized blur buffer, the texture is never re-rendered afterwards, so it keeps
+    /// showing the elements as they were at the time of this call.
+    pub fn render_blurred_texture(
+        &mut self,
+        renderer: &mut GlesRenderer,
+        scale: Scale<f64>,
+        config: Blur,
+        elements: impl Iterator<Item = impl RenderElement<GlesRenderer>>,
+    ) -> anyhow::Result<GlesTexture> {
+        self.render_blurred(renderer, scale, config, elements)?;
+
+        let mut texture: GlesTexture = renderer.create_buffer(
+            Format::Abgr8888,
+            self.output_size
+                .to_logical(1)
+                .to_buffer(1, Transform::Normal),
+        )?;
+
+        let tex_fb = renderer.bind(&mut self.effects)?;
+        let mut texture_fb = renderer.bind(&mut texture)?;
+
+        renderer.blit(
+            &tex_fb,
+            &mut texture_fb,
+            Rectangle::from_size(self.output_size),
+            Rectangle::from_size(self.output_size),
+            TextureFilter::Linear,
+        )?;
+        drop(texture_fb);
+
+        Ok(texture)
+    }
+
+    /// Render the elements into the effects buffer and blur them in place.
+    fn render_blurred(
+        &mut self,
+        renderer: &mut GlesRenderer,
+        scale: Scale<f64>,
+        config: Blur,
+        elements: impl Iterator<Item = impl RenderElement<GlesRenderer>>,
+    ) -> anyhow::Result<()> {
         let mut fb = renderer.bind(&mut self.effects).unwrap();
 
         let _ = render_elements(
@@ -271,21 +339,6 @@ impl EffectsFramebuffers {
             self.current_buffer.swap();
         }
 
-        // Now blit from the last render buffer into optimized_blur
-        // We are already bound so its just a blit
-        let tex_fb = renderer.bind(&mut self.effects).unwrap();
-        let mut optimized_blur_fb = renderer.bind(&mut self.optimized_blur).unwrap();
-
-        let _ = renderer.blit(
-            &tex_fb,
-            &mut optimized_blur_fb,
-            Rectangle::from_size(self.output_size),
-            Rectangle::from_size(self.output_size),
-            TextureFilter::Linear,
-        )?;
-
-        self.optimized_blur_generation = self.optimized_blur_generation.wrapping_add(1);
-
         Ok(())
     }
 
@@ -317,7 +370,6 @@ pub(super) unsafe fn get_main_buffer_blur(
     shaders: &BlurShaders,
     blur_config: Blur,
     projection_matrix: Mat3,
-    scale: i32,
     vbos: &[u32; 2],
     debug: bool,
     supports_instancing: bool,
@@ -326,16 +378,17 @@ pub(super) unsafe fn get_main_buffer_blur(
     texture_cache: &GlesTexture,
     alpha_tex: Option<&GlesTexture>,
 ) -> Result<GlesTexture, GlesError> {
+    // The effects buffers are allocated in physical pixels, so we work with them directly rather
+    // than going through the output scale, which may be fractional.
     let tex_size = fx_buffers
         .effects
         .size()
         .to_logical(1, Transform::Normal)
-        .to_physical(scale);
+        .to_physical(1);
 
     let dst_expanded = {
         let mut dst = dst;
-        let size =
-            (2f32.powi(blur_config.passes as i32 + 1) * blur_config.radius.0 as f32).ceil() as i32;
+        let size = sample_margin(&blur_config);
         dst.loc -= Point::from((size, size)).upscale(8);
         dst.size += Size::from((size, size)).upscale(16);
         dst
@@ -435,7 +488,6 @@ pub(super) unsafe fn get_main_buffer_blur(
                 projection_matrix,
                 sample_buffer,
                 render_buffer,
-                scale,
                 &shaders.down,
                 half_pixel,
                 blur_config,
@@ -459,7 +511,6 @@ pub(super) unsafe fn get_main_buffer_blur(
                 projection_matrix,
                 sample_buffer,
                 render_buffer,
-                scale,
                 &shaders.up,
                 half_pixel,
                 blur_config,
@@ -548,6 +599,18 @@ pub(super) unsafe fn get_main_buffer_blur(
     Ok(fx_buffers.effects.clone())
 }
 
+/// Returns the source and destination of a blur pass, which covers its whole texture.
+///
+/// The effects buffers are allocated in physical pixels, so they map to the framebuffer one to
+/// one, whatever the output scale is.
+fn blur_pass_rects(
+    tex_size: Size<i32, Buffer>,
+) -> (Rectangle<f64, Buffer>, Rectangle<i32, Physical>) {
+    let src = Rectangle::from_size(tex_size).to_f64();
+    let dst = Rectangle::from_size(tex_size.to_logical(1, Transform::Normal).to_physical(1));
+    (src, dst)
+}
+
 // Renders a blur pass using a GlesFrame with syncing and fencing provided by smithay. Used for
 // updating optimized blur buffer since we are not yet rendering.
 fn render_blur_pass_with_frame(
@@ -577,8 +640,7 @@ fn render_blur_pass_with_frame(
     let projection = Mat3::from_cols_array(frame.projection());
 
     let tex_size = sample_buffer.size();
-    let src = Rectangle::from_size(sample_buffer.size()).to_f64();
-    let dst = Rectangle::from_size(size).to_physical(1);
+    let (src, dst) = blur_pass_rects(tex_size);
 
     frame.with_context(|gl| unsafe {
         // We are doing basically what Frame::render_texture_from_to does, but our own shader struct
@@ -728,7 +790,6 @@ unsafe fn render_blur_pass_with_gl(
     // The buffers used for blurring
     sample_buffer: &GlesTexture,
     render_buffer: &mut GlesTexture,
-    scale: i32,
     // The current blur program + config
     blur_program: &shader::BlurShader,
     half_pixel: [f32; 2],
@@ -740,11 +801,7 @@ unsafe fn render_blur_pass_with_gl(
     trace!("rendering blur pass with GL");
 
     let tex_size = sample_buffer.size();
-    let src = Rectangle::from_size(tex_size.to_f64());
-    let dest = src
-        .to_logical(1.0, Transform::Normal, &src.size)
-        .to_physical(scale as f64)
-        .to_i32_round();
+    let (src, dest) = blur_pass_rects(tex_size);
 
     let damage = dest;
 
@@ -1,6 +1,6 @@
 // Originally ported from https://github.com/nferhat/fht-compositor/blob/main/src/renderer/blur/element.rs
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
 use std::time::Instant;
@@ -47,6 +47,8 @@ enum BlurVariant {
         config: niri_config::Blur,
         /// Timer to limit redraw rate of true blur.
         rerender_at: Rc<RefCell<Option<Instant>>>,
+        /// Commit of the element at which the cached texture was last rendered.
+        rendered_commit: Rc<Cell<Option<CommitCounter>>>,
     },
 }
 
@@ -99,6 +101,10 @@ impl Blur {
         }
     }
 
+    pub fn config(&self) -> niri_config::Blur {
+        self.config
+    }
+
     pub fn update_config(&mut self, config: niri_config::Blur) {
         if self.config != config {
             self.inner.set(None);
@@ -107,6 +113,18 @@ impl Blur {
         self.config = config;
     }
 
+    /// Drops the cached true blur texture, returning whether there was one.
+    pub fn release_cache(&self) -> bool {
+        let mut inner = self.inner.borrow_mut();
+        let is_true = inner
+            .as_ref()
+            .is_some_and(|elem| matches!(elem.variant, BlurVariant::True { .. }));
+        if is_true {
+            *inner = None;
+        }
+        is_true
+    }
+
     // TODO: the alpha tex methods can probably do better / without clearing `self.inner` entirely
 
     pub fn clear_alpha_tex(&self) {
@@ -134,7 +152,7 @@ impl Blur {
         &self,
         renderer: &mut GlesRenderer,
         fx_buffers: EffectsFramebuffersUserData,
-        destination_area: Rectangle<i32, Logical>,
+        destination_area: Rectangle<f64, Logical>,
         corner_radius: CornerRadius,
         scale: f64,
         geometry: Rectangle<f64, Logical>,
@@ -166,25 +184,9 @@ impl Blur {
             true_blur = false;
         }
 
-        let sample_area = if let Some(zoom) = overview.zoom {
-            let mut sample_area = destination_area.to_f64().upscale(zoom);
-            if let Some(offset) = overview.offset {
-                sample_area.loc += offset;
-            } else {
-                let center = overview.center.unwrap_or_else(|| {
-                    (fx_buffers.borrow().output_size.to_f64().to_logical(scale) / 2.).to_point()
-                });
-                sample_area.loc.x = center.x - (center.x - destination_area.loc.x as f64) * zoom;
-                sample_area.loc.y = center.y - (center.y - destination_area.loc.y as f64) * zoom;
-            }
-            sample_area.to_i32_round()
-        } else if let Some(offset) = overview.offset {
-            let mut sample_area = destination_area;
-            sample_area.loc += offset.to_i32_round();
-            sample_area
-        } else {
-            destination_area
-        };
+        let output_size = fx_buffers.borrow().output_size.to_f64().to_logical(scale);
+        let (destination_area, sample_area) =
+            blur_areas(destination_area, scale, &overview, output_size);
 
         let mut tex_buffer = || {
             renderer
@@ -220,6 +222,7 @@ impl Blur {
                         config: render_config,
                         texture: tex_buffer()?,
                         rerender_at: Default::default(),
+                        rendered_commit: Default::default(),
                     }
                 } else {
                     BlurVariant::Optimized {
@@ -242,6 +245,7 @@ impl Blur {
                     config: render_config,
                     texture: tex_buffer()?,
                     rerender_at: Default::default(),
+                    rendered_commit: Default::default(),
                 }
             } else {
                 BlurVariant::Optimized {
@@ -338,12 +342,53 @@ impl Blur {
     }
 }
 
+/// Computes the destination and the sample areas of a blur.
+///
+/// Both are snapped to the physical pixel grid, so that the blur lines up exactly with the surface
+/// at fractional scales instead of drifting by a fraction of a pixel.
+fn blur_areas(
+    destination_area: Rectangle<f64, Logical>,
+    scale: f64,
+    overview: &OverviewZoom,
+    output_size: Size<f64, Logical>,
+) -> (Rectangle<f64, Logical>, Rectangle<f64, Logical>) {
+    let destination_area = destination_area
+        .to_physical_precise_round(scale)
+        .to_logical(scale);
+
+    let sample_area = if let Some(zoom) = overview.zoom {
+        let mut sample_area = destination_area.upscale(zoom);
+        if let Some(offset) = overview.offset {
+            sample_area.loc += offset;
+        } else {
+            let center = overview
+                .center
+                .unwrap_or_else(|| (output_size / 2.).to_point());
+            sample_area.loc.x = center.x - (center.x - destination_area.loc.x) * zoom;
+            sample_area.loc.y = center.y - (center.y - destination_area.loc.y) * zoom;
+        }
+        sample_area
+            .to_physical_precise_round(scale)
+            .to_logical(scale)
+    } else if let Some(offset) = overview.offset {
+        let mut sample_area = destination_area;
+        sample_area.loc += offset;
+        sample_area
+            .to_physical_precise_round(scale)
+            .to_logical(scale)
+    } else {
+        destination_area
+    };
+
+    (destination_area, sample_area)
+}
+
 #[derive(Clone, Debug)]
 pub struct BlurRenderElement {
     id: Id,
     uniforms: Vec<Uniform<'static>>,
-    sample_area: Rectangle<i32, Logical>,
-    destination_area: Rectangle<i32, Logical>,
+    sample_area: Rectangle<f64, Logical>,
+    destination_area: Rectangle<f64, Logical>,
     alpha_tex: Option<GlesTexture>,
     scale: f64,
     commit: CommitCounter,
@@ -366,8 +411,8 @@ impl BlurRenderElement {
     #[allow(clippy::too_many_arguments)]
     fn new(
         fx_buffers: &EffectsFramebuffers,
-        sample_area: Rectangle<i32, Logical>,
-        destination_area: Rectangle<i32, Logical>,
+        sample_area: Rectangle<f64, Logical>,
+        destination_area: Rectangle<f64, Logical>,
         corner_radius: CornerRadius,
         scale: f64,
         config: niri_config::Blur,
@@ -482,11 +527,8 @@ impl Element for BlurRenderElement {
     }
 
     fn src(&self) -> Rectangle<f64, Buffer> {
-        self.sample_area.to_f64().to_buffer(
-            self.scale,
-            Transform::Normal,
-            &self.sample_area.size.to_f64(),
-        )
+        self.sample_area
+            .to_buffer(self.scale, Transform::Normal, &self.sample_area.size)
     }
 
     fn transform(&self) -> Transform {
@@ -520,12 +562,11 @@ impl Element for BlurRenderElement {
     }
 
     fn geometry(&self, scale: Scale<f64>) -> Rectangle<i32, Physical> {
+        // Only the visible area: true blur expands the region that it samples from the
+        // framebuffer on its own, and growing the geometry would break cropping and clipping.
         Rectangle::new(
             self.render_loc.to_physical_precise_round(scale),
-            self.destination_area
-                .to_f64()
-                .to_physical_precise_round(scale)
-                .size,
+            self.destination_area.to_physical_precise_round(scale).size,
         )
     }
 
@@ -551,10 +592,10 @@ impl RenderElement<GlesRenderer> for BlurRenderElement {
 
         let blur_dst = dst;
 
-        let program = Shaders::get_from_frame(gles_frame)
-            .blur_finish
-            .clone()
-            .expect("should be compiled");
+        let shaders = Shaders::get_from_frame(gles_frame);
+        let program = shaders.blur_finish.clone().expect("should be compiled");
+        let mut uniforms = self.uniforms.clone();
+        uniforms.push(shaders.debug_premult_uniform());
 
         if let Some(alpha_tex) = &self.alpha_tex {
             gles_frame.with_context(|gl| unsafe {
@@ -575,13 +616,14 @@ impl RenderElement<GlesRenderer> for BlurRenderElement {
                 Transform::Normal,
                 1.,
                 Some(&program),
-                &self.uniforms,
+                &uniforms,
             ),
             BlurVariant::True {
                 fx_buffers,
                 config,
                 texture,
                 rerender_at,
+                rendered_commit,
             } => {
                 let mut fx_buffers = fx_buffers.borrow_mut();
 
@@ -597,11 +639,12 @@ impl RenderElement<GlesRenderer> for BlurRenderElement {
 
                 // Update the blur buffers.
                 // We use gl ffi directly to circumvent some stuff done by smithay
-                if rerender_at
-                    .borrow()
-                    .map(|r| r < Instant::now())
-                    .unwrap_or(true)
-                {
+                //
+                // A new commit damages the whole element, so at this point the framebuffer
+                // contains exactly what is stacked below us. With any other commit, the damage
+                // may only be partial, and the framebuffer may hold contents of the last frame,
+                // so we keep using the cached texture.
+                if rendered_commit.get() != Some(self.commit) {
                     gles_frame.with_context(|gl| unsafe {
                         super::get_main_buffer_blur(
                             gl,
@@ -609,7 +652,6 @@ impl RenderElement<GlesRenderer> for BlurRenderElement {
                             &shaders,
                             *config,
                             projection_matrix,
-                            self.scale as i32,
                             &vbos,
                             debug,
                             supports_instancing,
@@ -622,6 +664,7 @@ impl RenderElement<GlesRenderer> for BlurRenderElement {
                     let fps = config.true_blur_fps.0 as f32;
                     let fps = if fps > 0.0 { fps.max(15.0) } else { 15.0 };
                     rerender_at.set(get_rerender_at(Some(fps)));
+                    rendered_commit.set(Some(self.commit));
                 };
 
                 gles_frame.render_texture_from_to(
@@ -633,7 +676,7 @@ impl RenderElement<GlesRenderer> for BlurRenderElement {
                     fx_buffers.transform(),
                     1.,
                     Some(&program),
-                    &self.uniforms,
+                    &uniforms,
                 )
             }
         }
@@ -674,3 +717,72 @@ impl<'render> RenderElement<TtyRenderer<'render>> for BlurRenderElement {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output_size() -> Size<f64, Logical> {
+        Size::from((1280., 720.))
+    }
+
+    #[track_caller]
+    fn assert_on_pixel_grid(area: Rectangle<f64, Logical>, scale: f64) {
+        let physical = area.to_physical(scale);
+        for value in [
+            physical.loc.x,
+            physical.loc.y,
+            physical.size.w,
+            physical.size.h,
+        ] {
+            assert!(
+                (value - value.round()).abs() < 1e-9,
+                "{area:?} is not on the pixel grid at scale {scale}"
+            );
+        }
+    }
+
+    #[test]
+    fn blur_areas_snap_to_physical_pixels() {
+        let area = Rectangle::new(Point::from((10.3, 20.7)), Size::from((100.1, 50.4)));
+
+        for scale in [1.25, 1.5, 1.75] {
+            let (dst, sample) = blur_areas(area, scale, &OverviewZoom::default(), output_size());
+            assert_on_pixel_grid(dst, scale);
+            assert_eq!(sample, dst);
+
+            // Snapping never moves the area by more than half a physical pixel.
+            assert!((dst.loc.x - area.loc.x).abs() <= 0.5 / scale + 1e-9);
+            assert!((dst.loc.y - area.loc.y).abs() <= 0.5 / scale + 1e-9);
+        }
+    }
+
+    #[test]
+    fn blur_areas_offset_and_zoom() {
+        let scale = 1.5;
+        let area = Rectangle::new(Point::from((100., 60.)), Size::from((300., 200.)));
+
+        let overview = OverviewZoom {
+            offset: Some(Point::from((33.3, -12.1))),
+            ..Default::default()
+        };
+        let (dst, sample) = blur_areas(area, scale, &overview, output_size());
+        assert_eq!(dst, area);
+        assert_on_pixel_grid(sample, scale);
+        assert_eq!(sample.size, dst.size);
+        assert!((sample.loc.x - 133.3).abs() <= 0.5 / scale);
+        assert!((sample.loc.y - 47.9).abs() <= 0.5 / scale);
+
+        // Zooming out around the output center.
+        let overview = OverviewZoom {
+            zoom: Some(0.5),
+            ..Default::default()
+        };
+        let (dst, sample) = blur_areas(area, scale, &overview, output_size());
+        assert_eq!(dst, area);
+        assert_eq!(
+            sample,
+            Rectangle::new(Point::from((370., 210.)), Size::from((150., 100.)))
+        );
+    }
+}
@@ -128,8 +128,10 @@ where
         }
     }
 
-    fn compute_uniforms(&self) -> Vec<Uniform<'static>> {
-        self.uniforms.clone()
+    fn compute_uniforms(&self, shaders: &Shaders) -> Vec<Uniform<'static>> {
+        let mut uniforms = self.uniforms.clone();
+        uniforms.push(shaders.debug_premult_uniform());
+        uniforms
     }
 
     pub fn will_clip(
@@ -272,7 +274,8 @@ where
         damage: &[Rectangle<i32, Physical>],
         opaque_regions: &[Rectangle<i32, Physical>],
     ) -> Result<(), GlesError> {
-        frame.override_default_tex_program(self.program.clone(), self.compute_uniforms());
+        let uniforms = self.compute_uniforms(Shaders::get_from_frame(frame));
+        frame.override_default_tex_program(self.program.clone(), uniforms);
 
         if let Some(alpha_tex) = &self.alpha_tex {
             frame.with_context(|gl| unsafe {
@@ -307,9 +310,10 @@ where
         damage: &[Rectangle<i32, Physical>],
         opaque_regions: &[Rectangle<i32, Physical>],
     ) -> Result<(), TtyRendererError<'render>> {
+        let uniforms = self.compute_uniforms(Shaders::get_from_frame(frame.as_gles_frame()));
         frame
             .as_gles_frame()
-            .override_default_tex_program(self.program.clone(), self.compute_uniforms());
+            .override_default_tex_program(self.program.clone(), uniforms);
 
         if let Some(alpha_tex) = &self.alpha_tex {
             frame.as_gles_frame().with_context(|gl| unsafe {
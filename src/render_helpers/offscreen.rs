@@ -68,6 +68,13 @@ pub struct OffscreenData {
 }
 
 impl OffscreenBuffer {
+    /// Drops the cached texture, returning whether there was one.
+    ///
+    /// The texture is created anew on the next `render`.
+    pub fn release(&self) -> bool {
+        self.inner.take().is_some()
+    }
+
     pub fn render(
         &self,
         renderer: &mut GlesRenderer,
@@ -36,6 +36,8 @@ uniform float contrast;
 uniform float saturation;
 uniform float ignore_alpha;
 
+//_DEBUG_PREMULT_
+
 float rounding_alpha(vec2 coords, vec2 size) {
     vec2 center;
     float radius;
@@ -143,6 +145,8 @@ void main() {
 
     color *= alpha;
 
+    color = niri_debug_premult_tint(color);
+
     gl_FragColor = color;
 }
 
@@ -22,6 +22,8 @@ uniform float tint;
 
 uniform float niri_scale;
 
+//_DEBUG_PREMULT_
+
 uniform vec2 geo_size;
 uniform vec4 corner_radius;
 uniform mat3 input_to_geo;
@@ -71,6 +73,8 @@ void main() {
     // Apply final alpha and tint.
     color = color * alpha;
 
+    color = niri_debug_premult_tint(color);
+
 #if defined(DEBUG_FLAGS)
     if (tint == 1.0)
         color = vec4(0.0, 0.2, 0.0, 0.2) + color * 0.8;
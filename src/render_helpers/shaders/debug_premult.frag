@@ -0,0 +1,15 @@
+uniform float niri_debug_premult;
+
+// Tints pixels where a color channel exceeds alpha or goes out of range. Such values are invalid
+// for premultiplied alpha and show up as dark or bright fringes after blending.
+vec4 niri_debug_premult_tint(vec4 color) {
+    if (niri_debug_premult != 1.0)
+        return color;
+
+    float max_channel = max(color.r, max(color.g, color.b));
+    float min_channel = min(color.r, min(color.g, color.b));
+    if (color.a + 1.0 / 255.0 < max_channel || min_channel < 0.0 || 1.0 < color.a)
+        return vec4(0.6, 0.0, 0.6, 0.6) + color * 0.4;
+
+    return color;
+}
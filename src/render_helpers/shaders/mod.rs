@@ -1,4 +1,4 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 use glam::Mat3;
 use smithay::backend::renderer::gles::{
@@ -20,8 +20,11 @@ pub struct Shaders {
     pub custom_resize: RefCell<Option<ShaderProgram>>,
     pub custom_close: RefCell<Option<ShaderProgram>>,
     pub custom_open: RefCell<Option<ShaderProgram>>,
+    pub custom_workspace_switch: RefCell<Option<ShaderProgram>>,
     pub blur_finish: Option<GlesTexProgram>,
     pub blur: BlurShaders,
+    /// Whether to tint pixels with out-of-range premultiplied alpha.
+    pub debug_premultiplied_alpha: Cell<bool>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -31,6 +34,7 @@ pub enum ProgramType {
     Resize,
     Close,
     Open,
+    WorkspaceSwitch,
 }
 
 impl Shaders {
@@ -82,12 +86,13 @@ impl Shaders {
 
         let clipped_surface = renderer
             .compile_custom_texture_shader(
-                include_str!("clipped_surface.frag"),
+                &with_debug_premult(include_str!("clipped_surface.frag")),
                 &[
                     UniformName::new("niri_scale", UniformType::_1f),
                     UniformName::new("geo_size", UniformType::_2f),
                     UniformName::new("corner_radius", UniformType::_4f),
                     UniformName::new("input_to_geo", UniformType::Matrix3x3),
+                    UniformName::new("niri_debug_premult", UniformType::_1f),
                 ],
             )
             .map_err(|err| {
@@ -103,7 +108,7 @@ impl Shaders {
 
         let blur_finish = renderer
             .compile_custom_texture_shader(
-                include_str!("blur_finish.frag"),
+                &with_debug_premult(include_str!("blur_finish.frag")),
                 &[
                     UniformName::new("alpha", UniformType::_1f),
                     UniformName::new("noise", UniformType::_1f),
@@ -116,6 +121,7 @@ impl Shaders {
                     UniformName::new("input_to_geo", UniformType::Matrix3x3),
                     UniformName::new("alpha_tex", UniformType::_1i),
                     UniformName::new("ignore_alpha", UniformType::_1f),
+                    UniformName::new("niri_debug_premult", UniformType::_1f),
                 ],
             )
             .map_err(|e| warn!("error compiling blur shader: {e:?}"))
@@ -142,8 +148,10 @@ impl Shaders {
             custom_resize: RefCell::new(None),
             custom_close: RefCell::new(None),
             custom_open: RefCell::new(None),
+            custom_workspace_switch: RefCell::new(None),
             blur_finish,
             blur,
+            debug_premultiplied_alpha: Cell::new(false),
         }
     }
 
@@ -181,6 +189,23 @@ impl Shaders {
         self.custom_open.replace(program)
     }
 
+    pub fn replace_custom_workspace_switch_program(
+        &self,
+        program: Option<ShaderProgram>,
+    ) -> Option<ShaderProgram> {
+        self.custom_workspace_switch.replace(program)
+    }
+
+    /// Uniform enabling the premultiplied alpha debug mode in the texture shaders.
+    pub fn debug_premult_uniform(&self) -> Uniform<'static> {
+        let value = if self.debug_premultiplied_alpha.get() {
+            1.
+        } else {
+            0.
+        };
+        Uniform::new("niri_debug_premult", value)
+    }
+
     pub fn program(&self, program: ProgramType) -> Option<ShaderProgram> {
         match program {
             ProgramType::Border => self.border.clone(),
@@ -192,6 +217,7 @@ impl Shaders {
                 .or_else(|| self.resize.clone()),
             ProgramType::Close => self.custom_close.borrow().clone(),
             ProgramType::Open => self.custom_open.borrow().clone(),
+            ProgramType::WorkspaceSwitch => self.custom_workspace_switch.borrow().clone(),
         }
     }
 }
@@ -204,6 +230,11 @@ pub fn init(renderer: &mut GlesRenderer) {
     }
 }
 
+/// Inserts the premultiplied alpha debug helper in place of the `//_DEBUG_PREMULT_` line.
+fn with_debug_premult(src: &str) -> String {
+    src.replace("//_DEBUG_PREMULT_\n", include_str!("debug_premult.frag"))
+}
+
 fn compile_resize_program(
     renderer: &mut GlesRenderer,
     src: &str,
@@ -337,6 +368,49 @@ pub fn set_custom_open_program(renderer: &mut GlesRenderer, src: Option<&str>) {
     }
 }
 
+fn compile_workspace_switch_program(
+    renderer: &mut GlesRenderer,
+    src: &str,
+) -> Result<ShaderProgram, GlesError> {
+    let mut program = include_str!("workspace_switch_prelude.frag").to_string();
+    program.push_str(src);
+    program.push_str(include_str!("workspace_switch_epilogue.frag"));
+
+    ShaderProgram::compile(
+        renderer,
+        &program,
+        &[
+            UniformName::new("niri_input_to_geo", UniformType::Matrix3x3),
+            UniformName::new("niri_geo_size", UniformType::_2f),
+            UniformName::new("niri_geo_to_tex_from", UniformType::Matrix3x3),
+            UniformName::new("niri_geo_to_tex_to", UniformType::Matrix3x3),
+            UniformName::new("niri_progress", UniformType::_1f),
+            UniformName::new("niri_clamped_progress", UniformType::_1f),
+        ],
+        &["niri_tex_from", "niri_tex_to"],
+    )
+}
+
+pub fn set_custom_workspace_switch_program(renderer: &mut GlesRenderer, src: Option<&str>) {
+    let program = if let Some(src) = src {
+        match compile_workspace_switch_program(renderer, src) {
+            Ok(program) => Some(program),
+            Err(err) => {
+                warn!("error compiling custom workspace switch shader: {err:?}");
+                return;
+            }
+        }
+    } else {
+        None
+    };
+
+    if let Some(prev) = Shaders::get(renderer).replace_custom_workspace_switch_program(program) {
+        if let Err(err) = prev.destroy(renderer) {
+            warn!("error destroying previous custom workspace switch shader: {err:?}");
+        }
+    }
+}
+
 pub fn mat3_uniform(name: &str, mat: Mat3) -> Uniform<'_> {
     Uniform::new(
         name,
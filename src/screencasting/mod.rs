@@ -40,6 +40,11 @@ pub struct Screencasting {
     /// Window ID for the "dynamic cast" special window for the xdp-gnome picker.
     pub dynamic_cast_id_for_portal: MappedId,
 
+    /// Window IDs for the "column" special windows for the xdp-gnome picker.
+    ///
+    /// Maps the special window ID to the column's active window at the time it was listed.
+    pub column_ids_for_portal: HashMap<u64, MappedId>,
+
     // Drop PipeWire last, and specifically after casts, to prevent a double-free (yay).
     pub pipewire: Option<PipeWire>,
 }
@@ -71,9 +76,25 @@ impl Screencasting {
             pw_to_niri,
             mapped_cast_output: HashMap::new(),
             dynamic_cast_id_for_portal: MappedId::next(),
+            column_ids_for_portal: HashMap::new(),
             pipewire: None,
         }
     }
+
+    /// Returns the special window ID casting the column of this window.
+    pub fn column_id_for_portal(&mut self, window: MappedId) -> u64 {
+        let existing = self
+            .column_ids_for_portal
+            .iter()
+            .find(|(_, id)| **id == window);
+        if let Some((special, _)) = existing {
+            return *special;
+        }
+
+        let special = MappedId::next().get();
+        self.column_ids_for_portal.insert(special, window);
+        special
+    }
 }
 
 impl State {
@@ -162,7 +183,7 @@ impl State {
                 }
                 return;
             }
-            CastTarget::Window { id } => *id,
+            CastTarget::Window { id } | CastTarget::Column { id } => *id,
         };
 
         // Lack of partial borrowing strikes again...
@@ -265,7 +286,7 @@ impl State {
                     refresh = Some(output.current_mode().unwrap().refresh as u32);
                 }
             }
-            CastTarget::Window { id } => {
+            CastTarget::Window { id } | CastTarget::Column { id } => {
                 let mut windows = self.niri.layout.windows();
                 if let Some((_, mapped)) = windows.find(|(_, mapped)| mapped.id().get() == *id) {
                     if let Some(output) = self.niri.casting.mapped_cast_output.get(&mapped.window) {
@@ -322,7 +343,7 @@ impl State {
                 };
                 cast_params_for_output(&output)
             }
-            CastTarget::Window { id } => {
+            CastTarget::Window { id } | CastTarget::Column { id } => {
                 let Some((size, refresh)) = self.niri.cast_params_for_window(*id) else {
                     return;
                 };
@@ -422,6 +443,24 @@ impl State {
                         });
                         return;
                     }
+                    StreamTargetId::Window { id }
+                        if self.niri.casting.column_ids_for_portal.contains_key(&id) =>
+                    {
+                        let window = self.niri.casting.column_ids_for_portal[&id];
+                        let Some(column) = self.niri.layout.column_for_window(&window) else {
+                            warn!("error starting screencast: requested column is missing");
+                            self.niri.stop_cast(session_id);
+                            return;
+                        };
+                        let id = column.active_window().id().get();
+
+                        let Some((size, refresh)) = self.niri.cast_params_for_window(id) else {
+                            warn!("error starting screencast: requested window is missing");
+                            self.niri.stop_cast(session_id);
+                            return;
+                        };
+                        (CastTarget::Column { id }, size, refresh, true)
+                    }
                     StreamTargetId::Window { id } => {
                         let Some((size, refresh)) = self.niri.cast_params_for_window(id) else {
                             warn!("error starting screencast: requested window is missing");
@@ -470,6 +509,28 @@ impl State {
 }
 
 impl Niri {
+    /// Moves the column casts to the currently active window of their column.
+    pub fn refresh_column_casts(&mut self) {
+        for cast in &mut self.casting.casts {
+            let CastTarget::Column { id } = cast.target else {
+                continue;
+            };
+
+            let mut windows = self.layout.windows();
+            let Some((_, mapped)) = windows.find(|(_, mapped)| mapped.id().get() == id) else {
+                continue;
+            };
+            let Some(column) = self.layout.column_for_window(&mapped.id()) else {
+                continue;
+            };
+
+            let active = column.active_window().id().get();
+            if active != id {
+                cast.target = CastTarget::Column { id: active };
+            }
+        }
+    }
+
     pub fn refresh_mapped_cast_window_rules(&mut self) {
         // O(N^2) but should be fine since there aren't many casts usually.
         self.layout.with_windows_mut(|mapped, _| {
@@ -479,7 +540,7 @@ impl Niri {
                 .casting
                 .casts
                 .iter()
-                .any(|cast| cast.target == (CastTarget::Window { id }));
+                .any(|cast| cast.target.window_id() == Some(id));
             mapped.set_is_window_cast_target(value);
         });
     }
@@ -515,12 +576,11 @@ impl Niri {
         let mut to_stop = vec![];
         for (id, out) in output_changed {
             let refresh = out.current_mode().unwrap().refresh as u32;
-            let target = CastTarget::Window { id: id.get() };
             for cast in self
                 .casting
                 .casts
                 .iter_mut()
-                .filter(|cast| cast.target == target)
+                .filter(|cast| cast.target.window_id() == Some(id.get()))
             {
                 if let Err(err) = cast.set_refresh(refresh) {
                     warn!("error changing cast FPS: {err:?}");
@@ -632,7 +692,7 @@ impl Niri {
                 continue;
             }
 
-            let CastTarget::Window { id } = cast.target else {
+            let Some(id) = cast.target.window_id() else {
                 continue;
             };
 
@@ -737,11 +797,31 @@ impl Niri {
     pub fn stop_casts_for_target(&mut self, target: CastTarget) {
         let _span = tracy_client::span!("Niri::stop_casts_for_target");
 
+        // Column casts switch to another window of the column, if there's one.
+        let column_target = match target {
+            CastTarget::Window { id } => Some(CastTarget::Column { id }),
+            _ => None,
+        };
+        let other_in_column = match target {
+            CastTarget::Window { id } => self
+                .layout
+                .windows()
+                .find(|(_, mapped)| mapped.id().get() == id)
+                .and_then(|(_, mapped)| self.layout.column_for_window(&mapped.id()))
+                .and_then(|column| {
+                    column
+                        .windows()
+                        .map(|win| win.id().get())
+                        .find(|other| *other != id)
+                }),
+            _ => None,
+        };
+
         // This is O(N^2) but it shouldn't be a problem I think.
         let mut saw_dynamic = false;
         let mut ids = Vec::new();
-        for cast in &self.casting.casts {
-            if cast.target != target {
+        for cast in &mut self.casting.casts {
+            if cast.target != target && Some(&cast.target) != column_target.as_ref() {
                 continue;
             }
 
@@ -750,6 +830,11 @@ impl Niri {
                 continue;
             }
 
+            if let (CastTarget::Column { .. }, Some(id)) = (&cast.target, other_in_column) {
+                cast.target = CastTarget::Column { id };
+                continue;
+            }
+
             ids.push(cast.session_id);
         }
 
@@ -0,0 +1,267 @@
+//! Saving the window layout on exit and restoring it as windows reopen after a restart.
+//!
+//! The saved layout is a flat list of windows, each remembering where it was: the output, the
+//! workspace, the position in the scrolling layout, and the size or the floating geometry. When a
+//! window opens during startup, it is matched against the saved windows by app ID (preferring the
+//! same title), and the matching entry is turned into initial window rules.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use directories::ProjectDirs;
+use niri_config::{FloatOrInt, FloatingPosition, OutputName, PresetSize, RelativeTo};
+use serde::{Deserialize, Serialize};
+
+use crate::layout::workspace::WorkspaceId;
+use crate::layout::{Layout, LayoutElement as _};
+use crate::utils::{output_matches_name, with_toplevel_role};
+use crate::window::{Mapped, ResolvedWindowRules};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedSession {
+    pub windows: Vec<SavedWindow>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedWindow {
+    pub app_id: String,
+    pub title: Option<String>,
+    /// Output make/model/serial, or connector name if those are unknown.
+    pub output: Option<String>,
+    pub workspace_name: Option<String>,
+    /// Index of the workspace on its output.
+    pub workspace_idx: usize,
+    /// Column and tile index in the scrolling layout (1-based), `None` for floating windows.
+    pub pos_in_scrolling_layout: Option<(usize, usize)>,
+    /// Window size in logical pixels.
+    pub size: (i32, i32),
+    /// Floating window position relative to the working area.
+    pub floating_pos: Option<(f64, f64)>,
+}
+
+/// Where to put a window restored from the saved session.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RestorePlacement {
+    pub workspace_id: WorkspaceId,
+    /// Column and tile index in the scrolling layout (0-based), `None` for floating windows.
+    pub pos_in_scrolling_layout: Option<(usize, usize)>,
+}
+
+impl SavedSession {
+    /// Captures the current layout.
+    pub fn capture(layout: &Layout<Mapped>) -> Self {
+        let workspaces: HashMap<WorkspaceId, _> = layout
+            .workspaces()
+            .map(|(_, idx, ws)| (ws.id(), (idx, ws.name().cloned(), ws.working_area().loc)))
+            .collect();
+
+        let mut windows = Vec::new();
+        layout.with_windows(|mapped, output, ws_id, win_layout| {
+            let Some((workspace_idx, workspace_name, working_area_loc)) =
+                ws_id.and_then(|id| workspaces.get(&id))
+            else {
+                return;
+            };
+
+            let (app_id, title) = with_toplevel_role(mapped.toplevel(), |role| {
+                (role.app_id.clone(), role.title.clone())
+            });
+            let Some(app_id) = app_id else {
+                return;
+            };
+
+            let output = output
+                .and_then(|output| output.user_data().get::<OutputName>())
+                .map(|name| name.format_make_model_serial_or_connector());
+
+            let floating_pos = if mapped.is_floating() {
+                win_layout
+                    .tile_pos_in_workspace_view
+                    .map(|(x, y)| (x - working_area_loc.x, y - working_area_loc.y))
+            } else {
+                None
+            };
+
+            windows.push(SavedWindow {
+                app_id,
+                title,
+                output,
+                workspace_name: workspace_name.clone(),
+                workspace_idx: *workspace_idx,
+                pos_in_scrolling_layout: win_layout.pos_in_scrolling_layout,
+                size: win_layout.window_size,
+                floating_pos,
+            });
+        });
+
+        // Sort in layout order so that windows of the same app are matched in a stable order.
+        windows.sort_by(|a, b| {
+            (&a.output, a.workspace_idx, a.pos_in_scrolling_layout).cmp(&(
+                &b.output,
+                b.workspace_idx,
+                b.pos_in_scrolling_layout,
+            ))
+        });
+
+        Self { windows }
+    }
+
+    /// Loads the session saved on the previous exit, if any.
+    pub fn load() -> Option<Self> {
+        let path = session_path()?;
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(err) => {
+                warn!("error reading saved session from {path:?}: {err:?}");
+                return None;
+            }
+        };
+
+        match serde_json::from_str(&contents) {
+            Ok(session) => Some(session),
+            Err(err) => {
+                warn!("error parsing saved session from {path:?}: {err:?}");
+                None
+            }
+        }
+    }
+
+    /// Saves the session to disk.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = session_path().context("error retrieving the state directory")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("error creating the state directory")?;
+        }
+
+        let contents = serde_json::to_string(self).context("error serializing the session")?;
+        fs::write(&path, contents).with_context(|| format!("error writing {path:?}"))?;
+
+        debug!("saved {} windows to {path:?}", self.windows.len());
+        Ok(())
+    }
+
+    /// Finds and removes the saved window matching the opening window.
+    ///
+    /// Windows with the same title are preferred, otherwise the first window with the same app ID
+    /// is picked.
+    pub fn take_match(&mut self, app_id: &str, title: Option<&str>) -> Option<SavedWindow> {
+        let mut candidates = self
+            .windows
+            .iter()
+            .enumerate()
+            .filter(|(_, win)| win.app_id == app_id);
+
+        let first = candidates.clone().next()?.0;
+        let idx = candidates
+            .find(|(_, win)| title.is_some() && win.title.as_deref() == title)
+            .map_or(first, |(idx, _)| idx);
+
+        Some(self.windows.remove(idx))
+    }
+}
+
+impl SavedWindow {
+    /// Overrides the initial placement window rules to put the window back where it was.
+    pub fn apply_to_rules(&self, rules: &mut ResolvedWindowRules) {
+        if let Some(name) = &self.workspace_name {
+            rules.open_on_workspace = vec![name.clone()];
+        } else if let Some(output) = &self.output {
+            rules.open_on_workspace = Vec::new();
+            rules.open_on_output = vec![output.clone()];
+        }
+
+        let (width, height) = self.size;
+        rules.default_width = Some(Some(PresetSize::Fixed(width)));
+
+        if let Some((x, y)) = self.floating_pos {
+            rules.open_floating = Some(true);
+            rules.default_height = Some(Some(PresetSize::Fixed(height)));
+            rules.default_floating_position = Some(FloatingPosition {
+                x: FloatOrInt(x),
+                y: FloatOrInt(y),
+                relative_to: RelativeTo::TopLeft,
+            });
+        } else {
+            rules.open_floating = Some(false);
+        }
+    }
+
+    /// Finds the workspace the window was on and its position within it.
+    ///
+    /// Named workspaces are looked up by name, the others by index on the saved output.
+    pub fn placement(&self, layout: &Layout<Mapped>) -> Option<RestorePlacement> {
+        let workspace_id = if let Some(name) = &self.workspace_name {
+            layout.find_workspace_by_name(name)?.1.id()
+        } else {
+            let output = self.output.as_deref()?;
+            layout
+                .workspaces()
+                .find(|(mon, idx, _)| {
+                    *idx == self.workspace_idx
+                        && mon.is_some_and(|mon| output_matches_name(mon.output(), output))
+                })?
+                .2
+                .id()
+        };
+
+        let pos_in_scrolling_layout = self
+            .pos_in_scrolling_layout
+            .map(|(col, tile)| (col.saturating_sub(1), tile.saturating_sub(1)));
+
+        Some(RestorePlacement {
+            workspace_id,
+            pos_in_scrolling_layout,
+        })
+    }
+}
+
+fn session_path() -> Option<PathBuf> {
+    let dirs = ProjectDirs::from("", "", "niri")?;
+    let mut path = dirs.state_dir()?.to_owned();
+    path.push("session.json");
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved(app_id: &str, title: Option<&str>, workspace_idx: usize) -> SavedWindow {
+        SavedWindow {
+            app_id: app_id.to_owned(),
+            title: title.map(String::from),
+            output: None,
+            workspace_name: None,
+            workspace_idx,
+            pos_in_scrolling_layout: Some((1, 1)),
+            size: (100, 100),
+            floating_pos: None,
+        }
+    }
+
+    #[test]
+    fn take_match_prefers_title() {
+        let mut session = SavedSession {
+            windows: vec![
+                saved("foot", Some("a"), 0),
+                saved("foot", Some("b"), 1),
+                saved("firefox", None, 2),
+            ],
+        };
+
+        let win = session.take_match("foot", Some("b")).unwrap();
+        assert_eq!(win.workspace_idx, 1);
+
+        let win = session.take_match("foot", Some("c")).unwrap();
+        assert_eq!(win.workspace_idx, 0);
+
+        assert_eq!(session.take_match("foot", None), None);
+
+        let win = session.take_match("firefox", Some("x")).unwrap();
+        assert_eq!(win.workspace_idx, 2);
+        assert!(session.windows.is_empty());
+    }
+}
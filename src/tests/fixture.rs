@@ -101,6 +101,7 @@ impl Fixture {
             client: sock1,
             restricted: false,
             credentials_unknown: false,
+            security_context: None,
         });
 
         let client = Client::new(sock2);
@@ -891,3 +891,57 @@ post-map configures:
     let _guard = settings.bind_to_scope();
     assert_snapshot!(snapshot);
 }
+
+#[test]
+fn reapply_rules_on_reload_moves_window() {
+    let workspaces = r##"
+workspace "ws-1" {
+    open-on-output "headless-1"
+}
+
+workspace "ws-2" {
+    open-on-output "headless-2"
+}
+"##;
+    let config = Config::parse_mem(workspaces).unwrap();
+
+    let mut f = Fixture::with_config(config);
+    f.add_output(1, (1280, 720));
+    f.add_output(2, (1920, 1080));
+
+    let id = f.add_client();
+    let window = f.client(id).create_window();
+    let surface = window.surface.clone();
+    window.set_title("chat");
+    window.commit();
+    f.roundtrip(id);
+
+    let window = f.client(id).window(&surface);
+    window.attach_new_buffer();
+    window.ack_last_and_commit();
+    f.double_roundtrip(id);
+
+    let window_ws = |f: &mut Fixture| {
+        f.niri()
+            .layout
+            .workspaces()
+            .find_map(|(_, _, ws)| ws.windows().next().map(|_| ws.name().cloned()))
+            .unwrap()
+    };
+    assert_eq!(window_ws(&mut f).as_deref(), Some("ws-1"));
+
+    let config = format!(
+        r##"{workspaces}
+window-rule {{
+    match title="chat"
+    open-on-workspace "ws-2"
+    reapply-rules-on-reload true
+}}
+"##
+    );
+    let config = Config::parse_mem(&config).unwrap();
+    f.niri_state().reload_config(Ok(config));
+    f.double_roundtrip(id);
+
+    assert_eq!(window_ws(&mut f).as_deref(), Some("ws-2"));
+}
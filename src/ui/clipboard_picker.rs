@@ -0,0 +1,319 @@
+//! Picker for the entries of the built-in clipboard history.
+//!
+//! Shown in the middle of an output, listing the entries most recent first. Confirming puts the
+//! selected entry back into the clipboard.
+
+use std::cell::RefCell;
+
+use pangocairo::cairo::{self, ImageSurface};
+use pangocairo::pango::{AttrInt, AttrList, FontDescription, Weight};
+use smithay::backend::renderer::element::Kind;
+use smithay::backend::renderer::gles::{GlesRenderer, GlesTexture};
+use smithay::output::Output;
+use smithay::reexports::gbm::Format as Fourcc;
+use smithay::utils::{Scale, Transform};
+
+use crate::clipboard_history::ClipboardHistory;
+use crate::render_helpers::primary_gpu_texture::PrimaryGpuTextureRenderElement;
+use crate::render_helpers::renderer::NiriRenderer;
+use crate::render_helpers::texture::{TextureBuffer, TextureRenderElement};
+use crate::utils::{output_size, to_physical_precise_round};
+
+const PADDING: i32 = 8;
+const FONT: &str = "sans 14px";
+const LINE_INTERVAL: i32 = 2;
+const TITLE: &str = "Clipboard History";
+
+/// Number of entries shown at once, the list scrolls to keep the selection visible.
+const VISIBLE_ROWS: usize = 12;
+
+/// Maximum number of characters of a text entry shown in its row.
+const MAX_LABEL_CHARS: usize = 80;
+
+pub struct ClipboardPicker {
+    state: Option<Inner>,
+}
+
+struct Inner {
+    /// Output the picker is shown on.
+    output: Output,
+    /// Entry ids and their labels, most recent first.
+    rows: Vec<(u64, String)>,
+    /// Index of the selected row.
+    selected: usize,
+    /// First visible row.
+    scroll: usize,
+    buffer: RefCell<Option<TextureBuffer<GlesTexture>>>,
+}
+
+impl ClipboardPicker {
+    pub fn new() -> Self {
+        Self { state: None }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state.is_some()
+    }
+
+    pub fn output(&self) -> Option<&Output> {
+        self.state.as_ref().map(|inner| &inner.output)
+    }
+
+    /// Opens the picker with the current history entries, selecting the most recent one.
+    pub fn open(&mut self, output: Output, history: &ClipboardHistory) {
+        if history.is_empty() {
+            return;
+        }
+
+        let rows = history
+            .entries()
+            .map(|entry| {
+                let label = match entry.text() {
+                    Some(text) => text_label(text),
+                    None => format!(
+                        "Image ({}, {} KiB)",
+                        entry.mime_type,
+                        entry.data.len() / 1024
+                    ),
+                };
+                (entry.id, label)
+            })
+            .collect();
+
+        self.state = Some(Inner {
+            output,
+            rows,
+            selected: 0,
+            scroll: 0,
+            buffer: RefCell::new(None),
+        });
+    }
+
+    /// Closes the picker, returning the id of the selected entry.
+    pub fn close(&mut self) -> Option<u64> {
+        let inner = self.state.take()?;
+        Some(inner.rows[inner.selected].0)
+    }
+
+    /// Moves the selection by the given number of rows.
+    pub fn move_selection(&mut self, delta: isize) {
+        let Some(inner) = &mut self.state else {
+            return;
+        };
+
+        let selected = inner.selected.saturating_add_signed(delta);
+        let selected = selected.min(inner.rows.len() - 1);
+        if selected == inner.selected {
+            return;
+        }
+        inner.selected = selected;
+
+        if selected < inner.scroll {
+            inner.scroll = selected;
+        } else if selected >= inner.scroll + VISIBLE_ROWS {
+            inner.scroll = selected + 1 - VISIBLE_ROWS;
+        }
+
+        inner.buffer.replace(None);
+    }
+
+    /// Drops the rendered texture, returning whether there was one.
+    ///
+    /// It is rendered again the next time the picker is drawn.
+    pub fn release_caches(&self) -> bool {
+        self.state
+            .as_ref()
+            .is_some_and(|inner| inner.buffer.take().is_some())
+    }
+
+    pub fn render<R: NiriRenderer>(
+        &self,
+        renderer: &mut R,
+        output: &Output,
+    ) -> Option<PrimaryGpuTextureRenderElement> {
+        let inner = self.state.as_ref()?;
+        if inner.output != *output {
+            return None;
+        }
+
+        let scale = output.current_scale().fractional_scale();
+        let output_size = output_size(output);
+
+        let mut buffer = inner.buffer.borrow_mut();
+        if buffer
+            .as_ref()
+            .is_some_and(|buffer| buffer.texture_scale() != Scale::from(scale))
+        {
+            *buffer = None;
+        }
+
+        if buffer.is_none() {
+            let end = usize::min(inner.scroll + VISIBLE_ROWS, inner.rows.len());
+            let rows = &inner.rows[inner.scroll..end];
+            let selected = inner.selected - inner.scroll;
+            *buffer = render(renderer.as_gles_renderer(), scale, rows, selected)
+                .map_err(|err| warn!("error rendering clipboard picker: {err:?}"))
+                .ok();
+        }
+        let buffer = buffer.clone()?;
+
+        let size = buffer.logical_size();
+        let location = (output_size.to_f64().to_point() - size.to_point()).downscale(2.);
+        let mut location = location.to_physical_precise_round(scale).to_logical(scale);
+        location.x = f64::max(0., location.x);
+        location.y = f64::max(0., location.y);
+
+        let elem = TextureRenderElement::from_texture_buffer(
+            buffer,
+            location,
+            0.9,
+            None,
+            None,
+            Kind::Unspecified,
+        );
+        Some(PrimaryGpuTextureRenderElement(elem))
+    }
+}
+
+impl Default for ClipboardPicker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Makes a single-line label out of a text entry.
+fn text_label(text: &str) -> String {
+    let mut words = text.split_whitespace();
+    let mut label = String::from(words.next().unwrap_or_default());
+    for word in words {
+        label.push(' ');
+        label.push_str(word);
+    }
+
+    if label.is_empty() {
+        return String::from("(whitespace)");
+    }
+
+    if label.chars().count() > MAX_LABEL_CHARS {
+        label = label.chars().take(MAX_LABEL_CHARS - 1).collect();
+        label.push('…');
+    }
+
+    label
+}
+
+fn render(
+    renderer: &mut GlesRenderer,
+    scale: f64,
+    rows: &[(u64, String)],
+    selected: usize,
+) -> anyhow::Result<TextureBuffer<GlesTexture>> {
+    let _span = tracy_client::span!("clipboard_picker::render");
+
+    let padding: i32 = to_physical_precise_round(scale, PADDING);
+    let line_interval: i32 = to_physical_precise_round(scale, LINE_INTERVAL);
+
+    let mut font = FontDescription::from_string(FONT);
+    font.set_absolute_size(to_physical_precise_round(scale, font.size()));
+
+    let bold = AttrList::new();
+    bold.insert(AttrInt::new_weight(Weight::Bold));
+
+    let surface = ImageSurface::create(cairo::Format::ARgb32, 0, 0)?;
+    let cr = cairo::Context::new(&surface)?;
+    let layout = pangocairo::functions::create_layout(&cr);
+    layout.context().set_round_glyph_positions(false);
+    layout.set_font_description(Some(&font));
+
+    layout.set_attributes(Some(&bold));
+    layout.set_text(TITLE);
+    let title_size = layout.pixel_size();
+
+    layout.set_attributes(None);
+    let row_sizes = rows
+        .iter()
+        .map(|(_, label)| {
+            layout.set_text(label);
+            layout.pixel_size()
+        })
+        .collect::<Vec<_>>();
+
+    let rows_width = row_sizes.iter().map(|(w, _)| *w).max().unwrap_or(0);
+    let width = i32::max(title_size.0, rows_width + padding * 2) + padding * 2;
+    let height = row_sizes
+        .iter()
+        .map(|(_, h)| h + line_interval)
+        .sum::<i32>()
+        + title_size.1
+        + padding * 3;
+
+    let surface = ImageSurface::create(cairo::Format::ARgb32, width, height)?;
+    let cr = cairo::Context::new(&surface)?;
+    cr.set_source_rgb(0.1, 0.1, 0.1);
+    cr.paint()?;
+
+    let layout = pangocairo::functions::create_layout(&cr);
+    layout.context().set_round_glyph_positions(false);
+    layout.set_font_description(Some(&font));
+
+    cr.set_source_rgb(1., 1., 1.);
+    cr.move_to(((width - title_size.0) / 2).into(), padding.into());
+    layout.set_attributes(Some(&bold));
+    layout.set_text(TITLE);
+    pangocairo::functions::show_layout(&cr, &layout);
+
+    layout.set_attributes(None);
+    let mut y = padding * 2 + title_size.1;
+    for (idx, ((_, label), (_, row_h))) in rows.iter().zip(&row_sizes).enumerate() {
+        let row_h = row_h + line_interval;
+
+        if idx == selected {
+            cr.rectangle(
+                padding.into(),
+                y.into(),
+                (width - padding * 2).into(),
+                row_h.into(),
+            );
+            cr.set_source_rgb(0.3, 0.3, 0.3);
+            cr.fill()?;
+        }
+
+        cr.set_source_rgb(1., 1., 1.);
+        cr.move_to((padding * 2).into(), (y + line_interval / 2).into());
+        layout.set_text(label);
+        pangocairo::functions::show_layout(&cr, &layout);
+
+        y += row_h;
+    }
+    drop(cr);
+
+    let data = surface.take_data().unwrap();
+    let buffer = TextureBuffer::from_memory(
+        renderer,
+        &data,
+        Fourcc::Argb8888,
+        (width, height),
+        false,
+        scale,
+        Transform::Normal,
+        Vec::new(),
+    )?;
+
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_label_is_single_line() {
+        assert_eq!(text_label("  hello\n\tworld  "), "hello world");
+        assert_eq!(text_label(" \n "), "(whitespace)");
+
+        let long = "a".repeat(200);
+        let label = text_label(&long);
+        assert_eq!(label.chars().count(), MAX_LABEL_CHARS);
+        assert!(label.ends_with('…'));
+    }
+}
@@ -0,0 +1,360 @@
+//! Temporary grid of the windows in a column or a tab group.
+//!
+//! The grid is shown over the whole output, letting the user pick one of the windows with the
+//! keyboard or the mouse. Confirming focuses the picked window and collapses the grid back.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use niri_config::{Config, CornerRadius};
+use smithay::backend::renderer::element::utils::{
+    Relocate, RelocateRenderElement, RescaleRenderElement,
+};
+use smithay::backend::renderer::element::Kind;
+use smithay::backend::renderer::Color32F;
+use smithay::output::Output;
+use smithay::utils::{Logical, Point, Rectangle, Scale, Size};
+
+use crate::layout::focus_ring::{FocusRing, FocusRingRenderElement};
+use crate::layout::{LayoutElement as _, LayoutElementRenderElement};
+use crate::niri::Niri;
+use crate::niri_render_elements;
+use crate::render_helpers::renderer::NiriRenderer;
+use crate::render_helpers::solid_color::{SolidColorBuffer, SolidColorRenderElement};
+use crate::render_helpers::RenderTarget;
+use crate::utils::{output_size, round_logical_in_physical};
+use crate::window::mapped::MappedId;
+use crate::window::Mapped;
+
+/// Gap between the previews and around the grid.
+const GAP: f64 = 32.;
+
+/// Border width on the selected window preview.
+const BORDER: f64 = 4.;
+
+/// Padding between the selected window preview and its border.
+const PADDING: f64 = 4.;
+
+/// Backdrop color behind the previews.
+const BACKDROP_COLOR: Color32F = Color32F::new(0., 0., 0., 0.8);
+
+pub struct ColumnGrid {
+    state: Option<Inner>,
+    config: Rc<RefCell<Config>>,
+}
+
+niri_render_elements! {
+    ColumnGridRenderElement<R> => {
+        SolidColor = SolidColorRenderElement,
+        FocusRing = FocusRingRenderElement,
+        Window = RelocateRenderElement<RescaleRenderElement<LayoutElementRenderElement<R>>>,
+    }
+}
+
+struct Inner {
+    /// Output the grid is shown on.
+    output: Output,
+    /// Windows in the grid, in column order.
+    windows: Vec<GridWindow>,
+    /// Index of the selected window.
+    selected: usize,
+    backdrop: RefCell<SolidColorBuffer>,
+    border: RefCell<FocusRing>,
+}
+
+struct GridWindow {
+    id: MappedId,
+    /// Size of the window when the grid was opened.
+    size: Size<f64, Logical>,
+}
+
+impl ColumnGrid {
+    pub fn new(config: Rc<RefCell<Config>>) -> Self {
+        Self {
+            state: None,
+            config,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state.is_some()
+    }
+
+    pub fn output(&self) -> Option<&Output> {
+        self.state.as_ref().map(|inner| &inner.output)
+    }
+
+    /// Opens the grid with the given windows, selecting the active one.
+    pub fn open<'a>(
+        &mut self,
+        output: Output,
+        windows: impl IntoIterator<Item = &'a Mapped>,
+        active: MappedId,
+    ) {
+        let windows: Vec<_> = windows
+            .into_iter()
+            .map(|mapped| GridWindow {
+                id: mapped.id(),
+                size: mapped.size().to_f64(),
+            })
+            .collect();
+        if windows.is_empty() {
+            return;
+        }
+
+        let selected = windows.iter().position(|w| w.id == active).unwrap_or(0);
+
+        let border = FocusRing::new(niri_config::FocusRing {
+            off: false,
+            width: BORDER,
+            active_color: self.config.borrow().layout.focus_ring.active_color,
+            active_gradient: None,
+            ..Default::default()
+        });
+
+        self.state = Some(Inner {
+            output,
+            windows,
+            selected,
+            backdrop: RefCell::new(SolidColorBuffer::new((0., 0.), BACKDROP_COLOR)),
+            border: RefCell::new(border),
+        });
+    }
+
+    /// Closes the grid, returning the selected window.
+    pub fn close(&mut self) -> Option<MappedId> {
+        let inner = self.state.take()?;
+        Some(inner.windows[inner.selected].id)
+    }
+
+    /// Moves the selection by the given number of columns and rows in the grid.
+    pub fn move_selection(&mut self, dx: i32, dy: i32) {
+        let Some(inner) = &mut self.state else {
+            return;
+        };
+
+        let count = inner.windows.len();
+        let (cols, rows) = grid_size(count);
+        let col = inner.selected % cols;
+        let row = inner.selected / cols;
+
+        let col = col.saturating_add_signed(dx as isize).min(cols - 1);
+        let row = row.saturating_add_signed(dy as isize).min(rows - 1);
+        inner.selected = (row * cols + col).min(count - 1);
+    }
+
+    /// Selects the window under the pointer, if any, and returns it.
+    pub fn pointer_motion(&mut self, pos_within_output: Point<f64, Logical>) -> Option<MappedId> {
+        let inner = self.state.as_mut()?;
+
+        let idx = inner
+            .preview_geometries()
+            .iter()
+            .position(|geo| geo.contains(pos_within_output))?;
+        inner.selected = idx;
+        Some(inner.windows[idx].id)
+    }
+
+    /// Removes a window from the grid, closing the grid if it was the last one.
+    pub fn remove_window(&mut self, id: MappedId) {
+        let Some(inner) = &mut self.state else {
+            return;
+        };
+
+        let Some(idx) = inner.windows.iter().position(|w| w.id == id) else {
+            return;
+        };
+        inner.windows.remove(idx);
+
+        if inner.windows.is_empty() {
+            self.state = None;
+            return;
+        }
+
+        if idx < inner.selected || inner.selected == inner.windows.len() {
+            inner.selected -= 1;
+        }
+    }
+
+    pub fn render_output<R: NiriRenderer>(
+        &self,
+        niri: &Niri,
+        output: &Output,
+        renderer: &mut R,
+        target: RenderTarget,
+        push: &mut dyn FnMut(ColumnGridRenderElement<R>),
+    ) {
+        let Some(inner) = &self.state else {
+            return;
+        };
+        if inner.output != *output {
+            return;
+        }
+
+        let _span = tracy_client::span!("ColumnGrid::render_output");
+
+        let scale = output.current_scale().fractional_scale();
+        let round = move |logical: f64| round_logical_in_physical(scale, logical);
+
+        let geometries = inner.preview_geometries();
+        for (idx, (win, preview_geo)) in inner.windows.iter().zip(&geometries).enumerate() {
+            let Some((_, mapped)) = niri.layout.windows().find(|(_, m)| m.id() == win.id) else {
+                continue;
+            };
+
+            if idx == inner.selected {
+                let padding = round(PADDING);
+                let padding = Point::new(padding, padding);
+                let size = preview_geo.size + padding.to_size().upscale(2.);
+
+                let mut border = inner.border.borrow_mut();
+                let mut config = *border.config();
+                config.width = round(BORDER);
+                border.update_config(config);
+                border.update_render_elements(
+                    size,
+                    true,
+                    true,
+                    false,
+                    Rectangle::default(),
+                    CornerRadius::default(),
+                    scale,
+                    1.,
+                );
+                border.render(renderer, preview_geo.loc - padding, &mut |elem| {
+                    push(ColumnGridRenderElement::FocusRing(elem))
+                });
+            }
+
+            let thumb_scale = Scale {
+                x: preview_geo.size.w / win.size.w,
+                y: preview_geo.size.h / win.size.h,
+            };
+            let loc = preview_geo.loc.to_physical_precise_round(scale);
+            mapped.render_normal(
+                renderer,
+                Point::new(0., 0.),
+                Scale::from(scale),
+                1.,
+                target,
+                &mut |elem| {
+                    let elem =
+                        RescaleRenderElement::from_element(elem, Point::new(0, 0), thumb_scale);
+                    let elem = RelocateRenderElement::from_element(elem, loc, Relocate::Relative);
+                    push(ColumnGridRenderElement::Window(elem));
+                },
+            );
+        }
+
+        let mut backdrop = inner.backdrop.borrow_mut();
+        backdrop.resize(output_size(output));
+        let elem = SolidColorRenderElement::from_buffer(
+            &backdrop,
+            Point::new(0., 0.),
+            1.,
+            Kind::Unspecified,
+        );
+        push(ColumnGridRenderElement::SolidColor(elem));
+    }
+}
+
+impl Inner {
+    fn preview_geometries(&self) -> Vec<Rectangle<f64, Logical>> {
+        let scale = self.output.current_scale().fractional_scale();
+        let sizes: Vec<_> = self.windows.iter().map(|w| w.size).collect();
+        preview_geometries(&sizes, output_size(&self.output), scale)
+    }
+}
+
+/// Returns the number of columns and rows in a grid of `count` windows.
+fn grid_size(count: usize) -> (usize, usize) {
+    let cols = (count as f64).sqrt().ceil().max(1.) as usize;
+    let rows = count.div_ceil(cols).max(1);
+    (cols, rows)
+}
+
+/// Computes the geometry of each window preview within the output.
+///
+/// Windows are laid out left to right, top to bottom, with the last row centered. Each preview
+/// is scaled down to fit its grid cell, but never scaled up.
+fn preview_geometries(
+    sizes: &[Size<f64, Logical>],
+    output_size: Size<f64, Logical>,
+    scale: f64,
+) -> Vec<Rectangle<f64, Logical>> {
+    let (cols, rows) = grid_size(sizes.len());
+
+    let cell_w = f64::max(1., (output_size.w - GAP * (cols + 1) as f64) / cols as f64);
+    let cell_h = f64::max(1., (output_size.h - GAP * (rows + 1) as f64) / rows as f64);
+
+    sizes
+        .iter()
+        .enumerate()
+        .map(|(idx, size)| {
+            let col = idx % cols;
+            let row = idx / cols;
+
+            // Center the last row if it's not full.
+            let in_row = usize::min(cols, sizes.len() - row * cols);
+            let row_offset = (cols - in_row) as f64 * (cell_w + GAP) / 2.;
+
+            let cell_loc = Point::new(
+                GAP + row_offset + col as f64 * (cell_w + GAP),
+                GAP + row as f64 * (cell_h + GAP),
+            );
+
+            let thumb_scale = f64::min(cell_w / size.w, cell_h / size.h).min(1.);
+            let preview_size = size.upscale(thumb_scale);
+            let preview_size = preview_size
+                .to_physical_precise_round(scale)
+                .to_logical(scale);
+
+            let loc = cell_loc
+                + Point::new(cell_w - preview_size.w, cell_h - preview_size.h).downscale(2.);
+            let loc = loc.to_physical_precise_round(scale).to_logical(scale);
+
+            Rectangle::new(loc, preview_size)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_size_is_square_ish() {
+        assert_eq!(grid_size(1), (1, 1));
+        assert_eq!(grid_size(2), (2, 1));
+        assert_eq!(grid_size(3), (2, 2));
+        assert_eq!(grid_size(4), (2, 2));
+        assert_eq!(grid_size(5), (3, 2));
+        assert_eq!(grid_size(10), (4, 3));
+    }
+
+    #[test]
+    fn previews_fit_and_dont_overlap() {
+        let output = Size::from((1920., 1080.));
+        let sizes = vec![Size::from((1000., 2000.)); 3];
+        let geos = preview_geometries(&sizes, output, 1.);
+
+        for (i, a) in geos.iter().enumerate() {
+            assert!(Rectangle::from_size(output).contains_rect(*a));
+            for b in &geos[i + 1..] {
+                assert!(!a.overlaps(*b));
+            }
+        }
+
+        // The last row is centered.
+        let center = geos[2].loc.x + geos[2].size.w / 2.;
+        assert!((center - output.w / 2.).abs() < 1.);
+    }
+
+    #[test]
+    fn small_windows_are_not_upscaled() {
+        let output = Size::from((1920., 1080.));
+        let sizes = vec![Size::from((100., 50.)); 2];
+        let geos = preview_geometries(&sizes, output, 1.);
+        assert_eq!(geos[0].size, sizes[0]);
+    }
+}
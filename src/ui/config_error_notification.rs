@@ -130,6 +130,16 @@ impl ConfigErrorNotification {
         !matches!(self.state, State::Hidden)
     }
 
+    /// Drops the rendered textures, returning how many there were.
+    ///
+    /// They are rendered again the next time the notification is drawn.
+    pub fn release_caches(&self) -> usize {
+        let mut buffers = self.buffers.borrow_mut();
+        let count = buffers.values().filter(|buffer| buffer.is_some()).count();
+        buffers.clear();
+        count
+    }
+
     pub fn render<R: NiriRenderer>(
         &self,
         renderer: &mut R,
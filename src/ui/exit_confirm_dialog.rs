@@ -146,6 +146,20 @@ impl ExitConfirmDialog {
         matches!(self.state, State::Showing(_) | State::Hiding(_))
     }
 
+    /// Drops the buffers rendered for scales other than 1, returning how many there were.
+    ///
+    /// The scale 1 buffer is kept as the fallback in case rendering fails later.
+    pub fn release_caches(&self) -> usize {
+        let mut buffers = self.buffers.borrow_mut();
+        let fallback = NotNan::new(1.).unwrap();
+        let count = buffers
+            .iter()
+            .filter(|(scale, buffer)| **scale != fallback && buffer.is_some())
+            .count();
+        buffers.retain(|scale, _| *scale == fallback);
+        count
+    }
+
     pub fn render<R: NiriRenderer>(
         &self,
         renderer: &mut R,
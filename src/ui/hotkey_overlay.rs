@@ -5,7 +5,10 @@ use std::fmt::Write as _;
 use std::iter::zip;
 use std::rc::Rc;
 
-use niri_config::{Action, Bind, Config, Key, ModKey, Modifiers, Trigger};
+use niri_config::{
+    Action, Bind, Config, Key, ModKey, Modifiers, PinchDirection, SwipeDirection, Trigger,
+    WorkspaceReference,
+};
 use pangocairo::cairo::{self, ImageSurface};
 use pangocairo::pango::{AttrColor, AttrInt, AttrList, AttrString, FontDescription, Weight};
 use smithay::backend::renderer::element::Kind;
@@ -31,6 +34,8 @@ pub struct HotkeyOverlay {
     is_open: bool,
     config: Rc<RefCell<Config>>,
     mod_key: ModKey,
+    /// Bind mode whose binds are shown instead of the regular ones.
+    bind_mode: Option<String>,
     buffers: RefCell<HashMap<WeakOutput, RenderedOverlay>>,
 }
 
@@ -44,6 +49,7 @@ impl HotkeyOverlay {
             is_open: false,
             config,
             mod_key,
+            bind_mode: None,
             buffers: RefCell::new(HashMap::new()),
         }
     }
@@ -75,6 +81,24 @@ impl HotkeyOverlay {
         self.buffers.borrow_mut().clear();
     }
 
+    pub fn set_bind_mode(&mut self, bind_mode: Option<String>) {
+        self.bind_mode = bind_mode;
+        self.buffers.borrow_mut().clear();
+    }
+
+    /// Drops the rendered overlays, returning how many textures there were.
+    ///
+    /// They are rendered again the next time the overlay is drawn.
+    pub fn release_caches(&self) -> usize {
+        let mut buffers = self.buffers.borrow_mut();
+        let count = buffers
+            .values()
+            .filter(|rendered| rendered.buffer.is_some())
+            .count();
+        buffers.clear();
+        count
+    }
+
     pub fn render<R: NiriRenderer>(
         &self,
         renderer: &mut R,
@@ -102,7 +126,9 @@ impl HotkeyOverlay {
 
         let rendered = buffers.entry(weak).or_insert_with(|| {
             let renderer = renderer.as_gles_renderer();
-            render(renderer, &self.config.borrow(), self.mod_key, scale)
+            let config = self.config.borrow();
+            let (title, binds) = collect_binds(&config, self.bind_mode.as_deref());
+            render(renderer, &title, &binds, self.mod_key, scale)
                 .unwrap_or_else(|_| RenderedOverlay { buffer: None })
         });
         let buffer = rendered.buffer.as_ref()?;
@@ -127,16 +153,12 @@ impl HotkeyOverlay {
 
     pub fn a11y_text(&self) -> String {
         let config = self.config.borrow();
-        let actions = collect_actions(&config);
+        let (title, binds) = collect_binds(&config, self.bind_mode.as_deref());
 
         let mut buf = String::new();
-        writeln!(&mut buf, "{TITLE}").unwrap();
-
-        for action in actions {
-            let Some((key, action)) = format_bind(&config.binds.0, action) else {
-                continue;
-            };
+        writeln!(&mut buf, "{title}").unwrap();
 
+        for (key, action) in binds {
             let key = key.map(|key| key_name(true, self.mod_key, &key));
             let key = key.as_deref().unwrap_or("not bound");
 
@@ -194,6 +216,29 @@ fn format_bind(binds: &[Bind], action: &Action) -> Option<(Option<Key>, String)>
     Some((key, title))
 }
 
+/// Returns the overlay title and the keys with their titles to show in the overlay.
+fn collect_binds(config: &Config, bind_mode: Option<&str>) -> (String, Vec<(Option<Key>, String)>) {
+    // In a bind mode, show all of its binds.
+    if let Some(mode) = bind_mode.and_then(|name| config.bind_modes.iter().find(|m| m.name == name))
+    {
+        let title = format!("Bind Mode: {}", mode.name);
+        let binds = mode
+            .binds
+            .0
+            .iter()
+            .filter(|bind| bind.hotkey_overlay_title != Some(None))
+            .map(|bind| (Some(bind.key), bind_title(bind)))
+            .collect();
+        return (title, binds);
+    }
+
+    let binds = collect_actions(config)
+        .into_iter()
+        .filter_map(|action| format_bind(&config.binds.0, action))
+        .collect();
+    (String::from(TITLE), binds)
+}
+
 fn collect_actions(config: &Config) -> Vec<&Action> {
     let binds = &config.binds.0;
 
@@ -305,33 +350,55 @@ fn collect_actions(config: &Config) -> Vec<&Action> {
 
 fn render(
     renderer: &mut GlesRenderer,
-    config: &Config,
+    title: &str,
+    binds: &[(Option<Key>, String)],
     mod_key: ModKey,
     scale: f64,
 ) -> anyhow::Result<RenderedOverlay> {
     let _span = tracy_client::span!("hotkey_overlay::render");
 
-    // let margin = MARGIN * scale;
-    let padding: i32 = to_physical_precise_round(scale, PADDING);
-    let line_interval: i32 = to_physical_precise_round(scale, LINE_INTERVAL);
-
     // FIXME: if it doesn't fit, try splitting in two columns or something.
+    // let margin = MARGIN * scale;
     // let mut target_size = output_size;
     // target_size.w -= margin * 2;
     // target_size.h -= margin * 2;
     // anyhow::ensure!(target_size.w > 0 && target_size.h > 0);
 
-    let strings = collect_actions(config)
-        .into_iter()
-        .filter_map(|action| format_bind(&config.binds.0, action))
+    let rows = binds
+        .iter()
         .map(|(key, action)| {
             let key = key.map(|key| key_name(false, mod_key, &key));
-            let key = key.as_deref().unwrap_or("(not bound)");
-            let key = format!(" {key} ");
-            (key, action)
+            let key = key.unwrap_or_else(|| String::from("(not bound)"));
+            (key, action.clone())
         })
         .collect::<Vec<_>>();
 
+    let buffer = render_binds(renderer, title, &rows, scale)?;
+    Ok(RenderedOverlay {
+        buffer: Some(buffer),
+    })
+}
+
+/// Renders a titled list of key names next to the pango markup of what they do.
+///
+/// This is shared between the hotkey overlay and the key sequence hint.
+pub fn render_binds(
+    renderer: &mut GlesRenderer,
+    title: &str,
+    rows: &[(String, String)],
+    scale: f64,
+) -> anyhow::Result<TextureBuffer<GlesTexture>> {
+    // A bind mode can have all of its binds hidden from the overlay.
+    anyhow::ensure!(!rows.is_empty(), "no binds to show");
+
+    let padding: i32 = to_physical_precise_round(scale, PADDING);
+    let line_interval: i32 = to_physical_precise_round(scale, LINE_INTERVAL);
+
+    let strings = rows
+        .iter()
+        .map(|(key, action)| (format!(" {key} "), action.clone()))
+        .collect::<Vec<_>>();
+
     let mut font = FontDescription::from_string(FONT);
     font.set_absolute_size(to_physical_precise_round(scale, font.size()));
 
@@ -344,7 +411,7 @@ fn render(
     let bold = AttrList::new();
     bold.insert(AttrInt::new_weight(Weight::Bold));
     layout.set_attributes(Some(&bold));
-    layout.set_text(TITLE);
+    layout.set_text(title);
     let title_size = layout.pixel_size();
 
     let attrs = AttrList::new();
@@ -371,7 +438,7 @@ fn render(
 
     let key_width = key_sizes.iter().map(|(w, _)| w).max().unwrap();
     let action_width = action_sizes.iter().map(|(w, _)| w).max().unwrap();
-    let mut width = key_width + padding + action_width;
+    let mut width = max(title_size.0, key_width + padding + action_width);
 
     let mut height = zip(&key_sizes, &action_sizes)
         .map(|((_, key_h), (_, act_h))| max(key_h, act_h))
@@ -397,7 +464,7 @@ fn render(
 
     cr.move_to(((width - title_size.0) / 2).into(), padding.into());
     layout.set_attributes(Some(&bold));
-    layout.set_text(TITLE);
+    layout.set_text(title);
     pangocairo::functions::show_layout(&cr, &layout);
 
     cr.move_to(padding.into(), (padding + title_size.1 + padding).into());
@@ -450,9 +517,24 @@ fn render(
         Vec::new(),
     )?;
 
-    Ok(RenderedOverlay {
-        buffer: Some(buffer),
-    })
+    Ok(buffer)
+}
+
+/// Returns the pango markup describing what the bind does.
+pub fn bind_title(bind: &Bind) -> String {
+    if let Some(Some(title)) = &bind.hotkey_overlay_title {
+        return title.clone();
+    }
+
+    match &bind.action {
+        Action::KeySequence(_) => String::from("…"),
+        Action::Multiple(actions) => actions
+            .iter()
+            .map(action_name)
+            .collect::<Vec<_>>()
+            .join(", "),
+        action => action_name(action),
+    }
 }
 
 fn action_name(action: &Action) -> String {
@@ -462,24 +544,63 @@ fn action_name(action: &Action) -> String {
         Action::CloseWindow => String::from("Close Focused Window"),
         Action::FocusColumnLeft => String::from("Focus Column to the Left"),
         Action::FocusColumnRight => String::from("Focus Column to the Right"),
+        Action::FocusColumnFirst => String::from("Focus the First Column"),
+        Action::FocusColumnLast => String::from("Focus the Last Column"),
+        Action::FocusWindowDown => String::from("Focus Window Down"),
+        Action::FocusWindowUp => String::from("Focus Window Up"),
         Action::MoveColumnLeft => String::from("Move Column Left"),
         Action::MoveColumnRight => String::from("Move Column Right"),
+        Action::MoveWindowDown => String::from("Move Window Down"),
+        Action::MoveWindowUp => String::from("Move Window Up"),
+        Action::FocusMonitorLeft => String::from("Focus Monitor to the Left"),
+        Action::FocusMonitorRight => String::from("Focus Monitor to the Right"),
+        Action::FocusMonitorDown => String::from("Focus Monitor Down"),
+        Action::FocusMonitorUp => String::from("Focus Monitor Up"),
         Action::FocusWorkspaceDown => String::from("Switch Workspace Down"),
         Action::FocusWorkspaceUp => String::from("Switch Workspace Up"),
+        Action::FocusWorkspacePrevious => String::from("Switch to the Previous Workspace"),
+        Action::FocusWorkspace(reference) => match reference {
+            WorkspaceReference::Index(idx) => format!("Switch to Workspace {idx}"),
+            WorkspaceReference::Name(name) => format!("Switch to Workspace {name}"),
+            WorkspaceReference::Id(_) => String::from("Switch to Workspace"),
+        },
         Action::MoveColumnToWorkspaceDown(_) => String::from("Move Column to Workspace Down"),
         Action::MoveColumnToWorkspaceUp(_) => String::from("Move Column to Workspace Up"),
+        Action::MoveColumnToWorkspace(reference, _) => match reference {
+            WorkspaceReference::Index(idx) => format!("Move Column to Workspace {idx}"),
+            WorkspaceReference::Name(name) => format!("Move Column to Workspace {name}"),
+            WorkspaceReference::Id(_) => String::from("Move Column to Workspace"),
+        },
         Action::MoveWindowToWorkspaceDown(_) => String::from("Move Window to Workspace Down"),
         Action::MoveWindowToWorkspaceUp(_) => String::from("Move Window to Workspace Up"),
         Action::SwitchPresetColumnWidth => String::from("Switch Preset Column Widths"),
+        Action::SwitchPresetWindowHeight => String::from("Switch Preset Window Heights"),
+        Action::SetColumnWidth(_) => String::from("Change Column Width"),
+        Action::SetWindowHeight(_) => String::from("Change Window Height"),
+        Action::ResetWindowHeight => String::from("Reset Window Height"),
         Action::MaximizeColumn => String::from("Maximize Column"),
+        Action::MaximizeWindowToEdges => String::from("Maximize Window to Edges"),
+        Action::ExpandColumnToAvailableWidth => String::from("Expand Column to Available Width"),
+        Action::CenterColumn => String::from("Center Column"),
+        Action::FullscreenWindow => String::from("Toggle Fullscreen"),
+        Action::ToggleWindowedFullscreen => String::from("Toggle Windowed Fullscreen"),
         Action::ConsumeOrExpelWindowLeft => String::from("Consume or Expel Window Left"),
         Action::ConsumeOrExpelWindowRight => String::from("Consume or Expel Window Right"),
+        Action::ConsumeWindowIntoColumn => String::from("Consume Window Into Column"),
+        Action::ExpelWindowFromColumn => String::from("Expel Window From Column"),
+        Action::ToggleColumnTabbedDisplay => String::from("Toggle Column Tabbed Display"),
         Action::ToggleWindowFloating => String::from("Move Window Between Floating and Tiling"),
         Action::SwitchFocusBetweenFloatingAndTiling => {
             String::from("Switch Focus Between Floating and Tiling")
         }
         Action::ToggleOverview => String::from("Open the Overview"),
+        Action::ToggleKeyboardShortcutsInhibit => String::from("Toggle Keyboard Shortcuts Inhibit"),
+        Action::SwitchLayout(_) => String::from("Switch Keyboard Layout"),
+        Action::PowerOffMonitors => String::from("Power Off Monitors"),
+        Action::Suspend => String::from("Suspend"),
         Action::Screenshot(_, _) => String::from("Take a Screenshot"),
+        Action::ScreenshotScreen(_, _, _) => String::from("Take a Screenshot of the Screen"),
+        Action::ScreenshotWindow(_, _, _, _, _) => String::from("Take a Screenshot of a Window"),
         Action::Spawn(args) => format!(
             "Spawn <span face='monospace' bgcolor='#000000'>{}</span>",
             args.first().unwrap_or(&String::new())
@@ -489,11 +610,29 @@ fn action_name(action: &Action) -> String {
             // Fairly crude but should get the job done in most cases.
             command.split_ascii_whitespace().next().unwrap_or("")
         ),
-        _ => String::from("FIXME: Unknown"),
+        action => fallback_action_name(action),
     }
 }
 
-fn key_name(screen_reader: bool, mod_key: ModKey, key: &Key) -> String {
+/// Turns the action variant name into words, e.g. `ToggleDebugTint` into "Toggle Debug Tint".
+fn fallback_action_name(action: &Action) -> String {
+    let debug = format!("{action:?}");
+    let variant = debug
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .next()
+        .unwrap_or_default();
+
+    let mut name = String::new();
+    for c in variant.chars() {
+        if c.is_ascii_uppercase() && !name.is_empty() {
+            name.push(' ');
+        }
+        name.push(c);
+    }
+    name
+}
+
+pub fn key_name(screen_reader: bool, mod_key: ModKey, key: &Key) -> String {
     let mut name = String::new();
 
     let has_comp_mod = key.modifiers.contains(Modifiers::COMPOSITOR);
@@ -544,6 +683,10 @@ fn key_name(screen_reader: bool, mod_key: ModKey, key: &Key) -> String {
         Trigger::MouseMiddle => String::from("Mouse Middle"),
         Trigger::MouseBack => String::from("Mouse Back"),
         Trigger::MouseForward => String::from("Mouse Forward"),
+        Trigger::MouseButton(number) => format!("Mouse Button {number}"),
+        Trigger::MouseChord(a, b) => {
+            format!("{} + {}", mouse_button_name(a), mouse_button_name(b))
+        }
         Trigger::WheelScrollDown => String::from("Wheel Scroll Down"),
         Trigger::WheelScrollUp => String::from("Wheel Scroll Up"),
         Trigger::WheelScrollLeft => String::from("Wheel Scroll Left"),
@@ -552,12 +695,39 @@ fn key_name(screen_reader: bool, mod_key: ModKey, key: &Key) -> String {
         Trigger::TouchpadScrollUp => String::from("Touchpad Scroll Up"),
         Trigger::TouchpadScrollLeft => String::from("Touchpad Scroll Left"),
         Trigger::TouchpadScrollRight => String::from("Touchpad Scroll Right"),
+        Trigger::TouchpadSwipe(fingers, direction) => {
+            let direction = match direction {
+                SwipeDirection::Up => "Up",
+                SwipeDirection::Down => "Down",
+                SwipeDirection::Left => "Left",
+                SwipeDirection::Right => "Right",
+            };
+            format!("Touchpad {fingers}-Finger Swipe {direction}")
+        }
+        Trigger::TouchpadPinch(fingers, direction) => {
+            let direction = match direction {
+                PinchDirection::In => "In",
+                PinchDirection::Out => "Out",
+            };
+            format!("Touchpad {fingers}-Finger Pinch {direction}")
+        }
     };
     name.push_str(&pretty);
 
     name
 }
 
+fn mouse_button_name(number: u32) -> String {
+    match Trigger::mouse_button(number) {
+        Some(Trigger::MouseLeft) => String::from("Mouse Left"),
+        Some(Trigger::MouseRight) => String::from("Mouse Right"),
+        Some(Trigger::MouseMiddle) => String::from("Mouse Middle"),
+        Some(Trigger::MouseBack) => String::from("Mouse Back"),
+        Some(Trigger::MouseForward) => String::from("Mouse Forward"),
+        _ => format!("Mouse Button {number}"),
+    }
+}
+
 fn prettify_keysym_name(screen_reader: bool, name: &str) -> String {
     let name = if screen_reader {
         name
@@ -609,6 +779,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_gesture_key_names() {
+        let name = |key: &str| key_name(false, ModKey::Super, &key.parse().unwrap());
+        assert_snapshot!(name("Mod+TouchpadSwipe3Up"), @"Super + Touchpad 3-Finger Swipe Up");
+        assert_snapshot!(name("TouchpadPinch2Out"), @"Touchpad 2-Finger Pinch Out");
+    }
+
+    #[test]
+    fn unnamed_actions_fall_back_to_variant_name() {
+        assert_snapshot!(action_name(&Action::ToggleDebugTint), @"Toggle Debug Tint");
+        assert_snapshot!(action_name(&Action::MoveColumnToIndex(2)), @"Move Column To Index");
+    }
+
     #[test]
     fn test_format_bind() {
         // Not bound.
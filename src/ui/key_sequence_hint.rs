@@ -0,0 +1,155 @@
+//! Hint for a pending key sequence.
+//!
+//! While niri waits for the next key of a sequence, this lists the keys that continue it at the
+//! bottom of the output where the sequence started.
+
+use std::cell::RefCell;
+
+use niri_config::{Bind, Key, ModKey};
+use smithay::backend::renderer::element::Kind;
+use smithay::backend::renderer::gles::GlesTexture;
+use smithay::output::Output;
+use smithay::utils::{Point, Scale};
+
+use crate::render_helpers::primary_gpu_texture::PrimaryGpuTextureRenderElement;
+use crate::render_helpers::renderer::NiriRenderer;
+use crate::render_helpers::texture::{TextureBuffer, TextureRenderElement};
+use crate::ui::hotkey_overlay::{bind_title, key_name, render_binds};
+use crate::utils::output_size;
+
+const PADDING: i32 = 8;
+
+pub struct KeySequenceHint {
+    state: Option<Inner>,
+    mod_key: ModKey,
+}
+
+struct Inner {
+    /// Output the hint is shown on.
+    output: Output,
+    /// Keys pressed so far.
+    keys: Vec<Key>,
+    /// Binds for the next key.
+    binds: Vec<Bind>,
+    buffer: RefCell<Option<TextureBuffer<GlesTexture>>>,
+}
+
+impl KeySequenceHint {
+    pub fn new(mod_key: ModKey) -> Self {
+        Self {
+            state: None,
+            mod_key,
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.state.is_some()
+    }
+
+    pub fn output(&self) -> Option<&Output> {
+        self.state.as_ref().map(|inner| &inner.output)
+    }
+
+    /// Returns the binds that continue the pending sequence.
+    pub fn binds(&self) -> &[Bind] {
+        self.state.as_ref().map_or(&[], |inner| &inner.binds)
+    }
+
+    /// Continues the sequence with `key`, waiting for one of `binds` next.
+    pub fn advance(&mut self, output: Output, key: Key, binds: Vec<Bind>) {
+        let mut keys = self
+            .state
+            .take()
+            .map(|inner| inner.keys)
+            .unwrap_or_default();
+        keys.push(key);
+
+        self.state = Some(Inner {
+            output,
+            keys,
+            binds,
+            buffer: RefCell::new(None),
+        });
+    }
+
+    /// Cancels the pending sequence, returns whether there was one.
+    pub fn cancel(&mut self) -> bool {
+        self.state.take().is_some()
+    }
+
+    pub fn on_hotkey_config_updated(&mut self, mod_key: ModKey) {
+        self.mod_key = mod_key;
+    }
+
+    /// Drops the rendered texture, returning whether there was one.
+    ///
+    /// It is rendered again the next time the hint is drawn.
+    pub fn release_caches(&self) -> bool {
+        self.state
+            .as_ref()
+            .is_some_and(|inner| inner.buffer.take().is_some())
+    }
+
+    pub fn render<R: NiriRenderer>(
+        &self,
+        renderer: &mut R,
+        output: &Output,
+    ) -> Option<PrimaryGpuTextureRenderElement> {
+        let inner = self.state.as_ref()?;
+        if inner.output != *output {
+            return None;
+        }
+
+        let scale = output.current_scale().fractional_scale();
+        let output_size = output_size(output);
+
+        let mut buffer = inner.buffer.borrow_mut();
+        if buffer
+            .as_ref()
+            .is_some_and(|buffer| buffer.texture_scale() != Scale::from(scale))
+        {
+            *buffer = None;
+        }
+
+        if buffer.is_none() {
+            let title = inner
+                .keys
+                .iter()
+                .map(|key| key_name(false, self.mod_key, key))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let title = format!("{title} …");
+
+            let rows = inner
+                .binds
+                .iter()
+                .map(|bind| {
+                    let key = key_name(false, self.mod_key, &bind.key);
+                    (key, bind_title(bind))
+                })
+                .collect::<Vec<_>>();
+
+            *buffer = render_binds(renderer.as_gles_renderer(), &title, &rows, scale)
+                .map_err(|err| warn!("error rendering key sequence hint: {err:?}"))
+                .ok();
+        }
+        let buffer = buffer.clone()?;
+
+        let size = buffer.logical_size();
+        let x = (output_size.w - size.w).max(0.) / 2.;
+        let y = output_size.h - size.h - f64::from(PADDING) * 2.;
+
+        let location = Point::from((x, y));
+        let location = location.to_physical_precise_round(scale).to_logical(scale);
+
+        let elem = TextureRenderElement::from_texture_buffer(
+            buffer,
+            location,
+            1.,
+            None,
+            None,
+            Kind::Unspecified,
+        );
+        Some(PrimaryGpuTextureRenderElement(elem))
+    }
+}
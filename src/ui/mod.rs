@@ -1,6 +1,11 @@
+pub mod clipboard_picker;
+pub mod column_grid;
 pub mod config_error_notification;
 pub mod exit_confirm_dialog;
 pub mod hotkey_overlay;
+pub mod key_sequence_hint;
 pub mod mru;
+pub mod overlay_widgets;
 pub mod screen_transition;
 pub mod screenshot_ui;
+pub mod shortcuts_inhibit_indicator;
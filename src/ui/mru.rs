@@ -1853,7 +1853,10 @@ fn make_preset_opened_binds() -> Vec<Bind> {
             },
             action,
             repeat: true,
+            repeat_delay: None,
+            repeat_rate: None,
             release: false,
+            taps: 1,
             cooldown: None,
             allow_when_locked: false,
             allow_inhibiting: false,
@@ -0,0 +1,373 @@
+//! Overlay widgets created over IPC.
+//!
+//! Scripts can show simple text, progress bars and icons on top of the windows without running a
+//! Wayland client. Every widget is drawn with cairo into a texture, cached per output scale.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use anyhow::{ensure, Context as _};
+use niri_ipc::{Widget, WidgetAnchor};
+use ordered_float::NotNan;
+use pangocairo::cairo::{self, ImageSurface};
+use pangocairo::pango::FontDescription;
+use smithay::backend::renderer::element::Kind;
+use smithay::backend::renderer::gles::{GlesRenderer, GlesTexture};
+use smithay::output::Output;
+use smithay::reexports::gbm::Format as Fourcc;
+use smithay::utils::{Point, Transform};
+
+use crate::render_helpers::primary_gpu_texture::PrimaryGpuTextureRenderElement;
+use crate::render_helpers::renderer::NiriRenderer;
+use crate::render_helpers::texture::{TextureBuffer, TextureRenderElement};
+use crate::utils::{output_matches_name, output_size, to_physical_precise_round};
+
+const PADDING: i32 = 8;
+const MARGIN: f64 = 16.;
+const FONT: &str = "sans 14px";
+const ICON_SIZE: i32 = 24;
+const PROGRESS_WIDTH: i32 = 200;
+const PROGRESS_HEIGHT: i32 = 6;
+
+/// Icons larger than this in either dimension are refused.
+const MAX_ICON_SIZE: u32 = 1024;
+
+#[derive(Default)]
+pub struct OverlayWidgets {
+    /// Widgets in the order they were created; later ones are drawn on top.
+    widgets: Vec<OverlayWidget>,
+}
+
+struct OverlayWidget {
+    widget: Widget,
+    icon: Option<Icon>,
+    buffers: RefCell<HashMap<NotNan<f64>, Option<TextureBuffer<GlesTexture>>>>,
+}
+
+struct Icon {
+    width: i32,
+    height: i32,
+    /// Premultiplied pixels in the cairo ARGB32 format.
+    data: Vec<u8>,
+}
+
+impl OverlayWidgets {
+    /// Creates a widget, or replaces the widget with the same id.
+    pub fn set(&mut self, widget: Widget) -> anyhow::Result<()> {
+        ensure!(
+            widget.text.is_some() || widget.progress.is_some() || widget.icon.is_some(),
+            "widget must have text, progress or an icon"
+        );
+        if let Some(progress) = widget.progress {
+            ensure!(
+                (0. ..=1.).contains(&progress),
+                "progress must be between 0 and 1"
+            );
+        }
+
+        let icon = match &widget.icon {
+            Some(path) => {
+                let path = Path::new(path);
+                ensure!(path.is_absolute(), "icon path must be absolute");
+                Some(load_icon(path).with_context(|| format!("error loading icon {path:?}"))?)
+            }
+            None => None,
+        };
+
+        let new = OverlayWidget {
+            widget,
+            icon,
+            buffers: RefCell::new(HashMap::new()),
+        };
+
+        if let Some(existing) = self
+            .widgets
+            .iter_mut()
+            .find(|w| w.widget.id == new.widget.id)
+        {
+            *existing = new;
+        } else {
+            self.widgets.push(new);
+        }
+
+        Ok(())
+    }
+
+    /// Removes a widget and returns whether it existed.
+    pub fn remove(&mut self, id: &str) -> bool {
+        let len = self.widgets.len();
+        self.widgets.retain(|w| w.widget.id != id);
+        self.widgets.len() != len
+    }
+
+    pub fn widgets(&self) -> impl Iterator<Item = &Widget> {
+        self.widgets.iter().map(|w| &w.widget)
+    }
+
+    /// Drops the cached textures; they will be re-rendered on the next use.
+    pub fn release_caches(&mut self) -> usize {
+        self.widgets
+            .iter()
+            .map(|w| {
+                let mut buffers = w.buffers.borrow_mut();
+                let count = buffers.len();
+                buffers.clear();
+                count
+            })
+            .sum()
+    }
+
+    pub fn render<R: NiriRenderer>(
+        &self,
+        renderer: &mut R,
+        output: &Output,
+        push: &mut dyn FnMut(PrimaryGpuTextureRenderElement),
+    ) {
+        if self.widgets.is_empty() {
+            return;
+        }
+
+        let _span = tracy_client::span!("OverlayWidgets::render");
+
+        let scale = output.current_scale().fractional_scale();
+        let output_size = output_size(output);
+
+        // Push the topmost widget first.
+        for w in self.widgets.iter().rev() {
+            if let Some(name) = &w.widget.output {
+                if !output_matches_name(output, name) {
+                    continue;
+                }
+            }
+
+            let mut buffers = w.buffers.borrow_mut();
+            let buffer = buffers
+                .entry(NotNan::new(scale).unwrap())
+                .or_insert_with(|| {
+                    render(
+                        renderer.as_gles_renderer(),
+                        scale,
+                        &w.widget,
+                        w.icon.as_ref(),
+                    )
+                    .map_err(|err| warn!("error rendering widget {:?}: {err:?}", w.widget.id))
+                    .ok()
+                });
+            let Some(buffer) = buffer.clone() else {
+                continue;
+            };
+
+            let size = buffer.logical_size();
+            let (dx, dy) = w.widget.offset;
+
+            let x = match w.widget.anchor {
+                WidgetAnchor::TopLeft | WidgetAnchor::Left | WidgetAnchor::BottomLeft => {
+                    MARGIN + dx
+                }
+                WidgetAnchor::Top | WidgetAnchor::Center | WidgetAnchor::Bottom => {
+                    (output_size.w - size.w) / 2. + dx
+                }
+                WidgetAnchor::TopRight | WidgetAnchor::Right | WidgetAnchor::BottomRight => {
+                    output_size.w - size.w - MARGIN - dx
+                }
+            };
+            let y = match w.widget.anchor {
+                WidgetAnchor::TopLeft | WidgetAnchor::Top | WidgetAnchor::TopRight => MARGIN + dy,
+                WidgetAnchor::Left | WidgetAnchor::Center | WidgetAnchor::Right => {
+                    (output_size.h - size.h) / 2. + dy
+                }
+                WidgetAnchor::BottomLeft | WidgetAnchor::Bottom | WidgetAnchor::BottomRight => {
+                    output_size.h - size.h - MARGIN - dy
+                }
+            };
+
+            let location = Point::from((x, y));
+            let location = location.to_physical_precise_round(scale).to_logical(scale);
+
+            let elem = TextureRenderElement::from_texture_buffer(
+                buffer,
+                location,
+                1.,
+                None,
+                None,
+                Kind::Unspecified,
+            );
+            push(PrimaryGpuTextureRenderElement(elem));
+        }
+    }
+}
+
+fn render(
+    renderer: &mut GlesRenderer,
+    scale: f64,
+    widget: &Widget,
+    icon: Option<&Icon>,
+) -> anyhow::Result<TextureBuffer<GlesTexture>> {
+    let _span = tracy_client::span!("overlay_widgets::render");
+
+    let padding: i32 = to_physical_precise_round(scale, PADDING);
+
+    let mut font = FontDescription::from_string(FONT);
+    font.set_absolute_size(to_physical_precise_round(scale, font.size()));
+
+    let text_size = match &widget.text {
+        Some(text) => {
+            let surface = ImageSurface::create(cairo::Format::ARgb32, 0, 0)?;
+            let cr = cairo::Context::new(&surface)?;
+            let layout = pangocairo::functions::create_layout(&cr);
+            layout.context().set_round_glyph_positions(false);
+            layout.set_font_description(Some(&font));
+            layout.set_text(text);
+            Some(layout.pixel_size())
+        }
+        None => None,
+    };
+
+    // Icons are scaled to a fixed height, keeping the aspect ratio.
+    let icon_height: i32 = to_physical_precise_round(scale, ICON_SIZE);
+    let icon_size = icon.map(|icon| {
+        let width = f64::from(icon_height) * f64::from(icon.width) / f64::from(icon.height);
+        (width.round() as i32, icon_height)
+    });
+
+    let mut row_width = 0;
+    let mut row_height = 0;
+    if let Some((w, h)) = icon_size {
+        row_width += w;
+        row_height = row_height.max(h);
+    }
+    if let Some((w, h)) = text_size {
+        if icon_size.is_some() {
+            row_width += padding;
+        }
+        row_width += w;
+        row_height = row_height.max(h);
+    }
+
+    let progress_height: i32 = to_physical_precise_round(scale, PROGRESS_HEIGHT);
+    let mut content_width = row_width;
+    let mut content_height = row_height;
+    if widget.progress.is_some() {
+        content_width = content_width.max(to_physical_precise_round(scale, PROGRESS_WIDTH));
+        if row_height > 0 {
+            content_height += padding;
+        }
+        content_height += progress_height;
+    }
+
+    let width = content_width + padding * 2;
+    let height = content_height + padding * 2;
+
+    let surface = ImageSurface::create(cairo::Format::ARgb32, width, height)?;
+    let cr = cairo::Context::new(&surface)?;
+    cr.set_source_rgba(0.1, 0.1, 0.1, 0.9);
+    cr.paint()?;
+
+    let mut x = f64::from(padding);
+    if let (Some(icon), Some((w, h))) = (icon, icon_size) {
+        let stride = cairo::Format::ARgb32.stride_for_width(icon.width as u32)?;
+        let icon_surface = ImageSurface::create_for_data(
+            icon.data.clone(),
+            cairo::Format::ARgb32,
+            icon.width,
+            icon.height,
+            stride,
+        )?;
+
+        let y = f64::from(padding + (row_height - h) / 2);
+        cr.save()?;
+        cr.translate(x, y);
+        cr.scale(
+            f64::from(w) / f64::from(icon.width),
+            f64::from(h) / f64::from(icon.height),
+        );
+        cr.set_source_surface(&icon_surface, 0., 0.)?;
+        cr.paint()?;
+        cr.restore()?;
+
+        x += f64::from(w + padding);
+    }
+
+    if let (Some(text), Some((_, h))) = (&widget.text, text_size) {
+        cr.move_to(x, f64::from(padding + (row_height - h) / 2));
+        let layout = pangocairo::functions::create_layout(&cr);
+        layout.context().set_round_glyph_positions(false);
+        layout.set_font_description(Some(&font));
+        layout.set_text(text);
+
+        cr.set_source_rgb(1., 1., 1.);
+        pangocairo::functions::show_layout(&cr, &layout);
+    }
+
+    if let Some(progress) = widget.progress {
+        let x = f64::from(padding);
+        let y = f64::from(height - padding - progress_height);
+        let w = f64::from(content_width);
+        let h = f64::from(progress_height);
+
+        cr.rectangle(x, y, w, h);
+        cr.set_source_rgba(1., 1., 1., 0.2);
+        cr.fill()?;
+
+        cr.rectangle(x, y, (w * progress).round(), h);
+        cr.set_source_rgb(0.5, 0.8, 1.);
+        cr.fill()?;
+    }
+    drop(cr);
+
+    let data = surface.take_data().unwrap();
+    let buffer = TextureBuffer::from_memory(
+        renderer,
+        &data,
+        Fourcc::Argb8888,
+        (width, height),
+        false,
+        scale,
+        Transform::Normal,
+        Vec::new(),
+    )?;
+
+    Ok(buffer)
+}
+
+fn load_icon(path: &Path) -> anyhow::Result<Icon> {
+    let file = File::open(path)?;
+    let mut decoder = png::Decoder::new(BufReader::new(file));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+
+    let (width, height) = (reader.info().width, reader.info().height);
+    ensure!(width > 0 && height > 0, "icon is empty");
+    ensure!(
+        width <= MAX_ICON_SIZE && height <= MAX_ICON_SIZE,
+        "icon is larger than {MAX_ICON_SIZE}x{MAX_ICON_SIZE}"
+    );
+
+    let (color_type, _) = reader.output_color_type();
+    let samples = color_type.samples();
+    let mut buf = vec![0; width as usize * height as usize * samples];
+    reader.next_frame(&mut buf)?;
+
+    let mut data = Vec::with_capacity(width as usize * height as usize * 4);
+    for px in buf.chunks_exact(samples) {
+        let [r, g, b, a] = match *px {
+            [v] => [v, v, v, 255],
+            [v, a] => [v, v, v, a],
+            [r, g, b] => [r, g, b, 255],
+            [r, g, b, a] => [r, g, b, a],
+            _ => unreachable!(),
+        };
+        let premultiply = |c: u8| (u16::from(c) * u16::from(a) / 255) as u8;
+        let argb = u32::from_be_bytes([a, premultiply(r), premultiply(g), premultiply(b)]);
+        data.extend_from_slice(&argb.to_ne_bytes());
+    }
+
+    Ok(Icon {
+        width: width as i32,
+        height: height as i32,
+        data,
+    })
+}
@@ -0,0 +1,211 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use niri_config::{Action, Config, ModKey};
+use ordered_float::NotNan;
+use pangocairo::cairo::{self, ImageSurface};
+use pangocairo::pango::FontDescription;
+use smithay::backend::renderer::element::Kind;
+use smithay::backend::renderer::gles::{GlesRenderer, GlesTexture};
+use smithay::output::Output;
+use smithay::reexports::gbm::Format as Fourcc;
+use smithay::utils::{Point, Transform};
+
+use crate::render_helpers::primary_gpu_texture::PrimaryGpuTextureRenderElement;
+use crate::render_helpers::renderer::NiriRenderer;
+use crate::render_helpers::texture::{TextureBuffer, TextureRenderElement};
+use crate::ui::hotkey_overlay::key_name;
+use crate::utils::{output_size, to_physical_precise_round};
+
+const PADDING: i32 = 8;
+const FONT: &str = "sans 14px";
+const BORDER: i32 = 4;
+
+pub struct ShortcutsInhibitIndicator {
+    state: IndicatorState,
+    config: Rc<RefCell<Config>>,
+    mod_key: ModKey,
+    buffers: RefCell<HashMap<NotNan<f64>, Option<TextureBuffer<GlesTexture>>>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndicatorState {
+    Hidden,
+    /// The focused window is inhibiting keyboard shortcuts.
+    Inhibiting,
+    /// The focused window asked to inhibit keyboard shortcuts and is waiting for the user.
+    Asking,
+}
+
+impl ShortcutsInhibitIndicator {
+    pub fn new(config: Rc<RefCell<Config>>, mod_key: ModKey) -> Self {
+        Self {
+            state: IndicatorState::Hidden,
+            config,
+            mod_key,
+            buffers: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the indicator state and returns whether it changed.
+    pub fn set_state(&mut self, state: IndicatorState) -> bool {
+        if self.state == state {
+            return false;
+        }
+
+        self.state = state;
+        self.buffers.borrow_mut().clear();
+        true
+    }
+
+    pub fn on_hotkey_config_updated(&mut self, mod_key: ModKey) {
+        self.mod_key = mod_key;
+        self.buffers.borrow_mut().clear();
+    }
+
+    /// Drops the rendered textures, returning how many there were.
+    ///
+    /// They are rendered again the next time the indicator is drawn.
+    pub fn release_caches(&self) -> usize {
+        let mut buffers = self.buffers.borrow_mut();
+        let count = buffers.values().filter(|buffer| buffer.is_some()).count();
+        buffers.clear();
+        count
+    }
+
+    pub fn render<R: NiriRenderer>(
+        &self,
+        renderer: &mut R,
+        output: &Output,
+    ) -> Option<PrimaryGpuTextureRenderElement> {
+        if self.state == IndicatorState::Hidden {
+            return None;
+        }
+
+        let scale = output.current_scale().fractional_scale();
+        let output_size = output_size(output);
+
+        let mut buffers = self.buffers.borrow_mut();
+        let buffer = buffers
+            .entry(NotNan::new(scale).unwrap())
+            .or_insert_with(|| {
+                let text = self.text();
+                render(renderer.as_gles_renderer(), scale, self.state, &text)
+                    .map_err(|err| warn!("error rendering shortcuts inhibit indicator: {err:?}"))
+                    .ok()
+            });
+        let buffer = buffer.clone()?;
+
+        let size = buffer.logical_size();
+        let x = (output_size.w - size.w).max(0.) / 2.;
+        let y = output_size.h - size.h - f64::from(PADDING) * 2.;
+
+        let location = Point::from((x, y));
+        let location = location.to_physical_precise_round(scale).to_logical(scale);
+
+        let elem = TextureRenderElement::from_texture_buffer(
+            buffer,
+            location,
+            1.,
+            None,
+            None,
+            Kind::Unspecified,
+        );
+        Some(PrimaryGpuTextureRenderElement(elem))
+    }
+
+    fn text(&self) -> String {
+        let config = self.config.borrow();
+        let key = config
+            .binds
+            .0
+            .iter()
+            .find(|bind| bind.action == Action::ToggleKeyboardShortcutsInhibit)
+            .map(|bind| key_name(false, self.mod_key, &bind.key));
+
+        match (self.state, key) {
+            (IndicatorState::Hidden, _) => unreachable!(),
+            (IndicatorState::Inhibiting, Some(key)) => {
+                format!("Keyboard shortcuts are inhibited. Press {key} to restore them.")
+            }
+            (IndicatorState::Inhibiting, None) => String::from("Keyboard shortcuts are inhibited."),
+            (IndicatorState::Asking, Some(key)) => {
+                format!("This window wants to inhibit keyboard shortcuts. Press {key} to allow.")
+            }
+            (IndicatorState::Asking, None) => String::from(
+                "This window wants to inhibit keyboard shortcuts. \
+                 Bind toggle-keyboard-shortcuts-inhibit to allow it.",
+            ),
+        }
+    }
+}
+
+fn render(
+    renderer: &mut GlesRenderer,
+    scale: f64,
+    state: IndicatorState,
+    text: &str,
+) -> anyhow::Result<TextureBuffer<GlesTexture>> {
+    let _span = tracy_client::span!("shortcuts_inhibit_indicator::render");
+
+    let padding: i32 = to_physical_precise_round(scale, PADDING);
+
+    let border_color = match state {
+        IndicatorState::Asking => (0.5, 0.8, 1.),
+        _ => (1., 0.6, 0.2),
+    };
+
+    let mut font = FontDescription::from_string(FONT);
+    font.set_absolute_size(to_physical_precise_round(scale, font.size()));
+
+    let surface = ImageSurface::create(cairo::Format::ARgb32, 0, 0)?;
+    let cr = cairo::Context::new(&surface)?;
+    let layout = pangocairo::functions::create_layout(&cr);
+    layout.context().set_round_glyph_positions(false);
+    layout.set_font_description(Some(&font));
+    layout.set_text(text);
+
+    let (mut width, mut height) = layout.pixel_size();
+    width += padding * 2;
+    height += padding * 2;
+
+    let surface = ImageSurface::create(cairo::Format::ARgb32, width, height)?;
+    let cr = cairo::Context::new(&surface)?;
+    cr.set_source_rgb(0.1, 0.1, 0.1);
+    cr.paint()?;
+
+    cr.move_to(padding.into(), padding.into());
+    let layout = pangocairo::functions::create_layout(&cr);
+    layout.context().set_round_glyph_positions(false);
+    layout.set_font_description(Some(&font));
+    layout.set_text(text);
+
+    cr.set_source_rgb(1., 1., 1.);
+    pangocairo::functions::show_layout(&cr, &layout);
+
+    cr.move_to(0., 0.);
+    cr.line_to(width.into(), 0.);
+    cr.line_to(width.into(), height.into());
+    cr.line_to(0., height.into());
+    cr.line_to(0., 0.);
+    cr.set_source_rgb(border_color.0, border_color.1, border_color.2);
+    // Keep the border width even to avoid blurry edges.
+    cr.set_line_width((f64::from(BORDER) / 2. * scale).round() * 2.);
+    cr.stroke()?;
+    drop(cr);
+
+    let data = surface.take_data().unwrap();
+    let buffer = TextureBuffer::from_memory(
+        renderer,
+        &data,
+        Fourcc::Argb8888,
+        (width, height),
+        false,
+        scale,
+        Transform::Normal,
+        Vec::new(),
+    )?;
+
+    Ok(buffer)
+}
@@ -0,0 +1,121 @@
+//! Memory pressure monitoring.
+//!
+//! We register a kernel PSI (pressure stall information) trigger, preferring the memory pressure
+//! file of our own cgroup, and falling back to the system-wide one. When the trigger fires, niri
+//! releases its caches. Everything released this way is re-created lazily on the next use.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write as _};
+use std::os::fd::AsRawFd as _;
+use std::path::PathBuf;
+use std::sync::mpsc::TrySendError;
+use std::thread;
+
+use calloop::channel::{self, Event};
+
+use crate::niri::State;
+
+/// PSI trigger: at least 150 ms of memory stall within a 2 s window.
+///
+/// Unprivileged processes can only use windows that are multiples of 2 s.
+const TRIGGER: &str = "some 150000 2000000";
+
+pub fn setup(state: &mut State) {
+    let file = match open_trigger() {
+        Ok(file) => file,
+        Err(err) => {
+            debug!("error setting up memory pressure monitoring: {err:?}");
+            return;
+        }
+    };
+
+    let (tx, rx) = channel::sync_channel(1);
+    state
+        .niri
+        .event_loop
+        .insert_source(rx, |event, _, state| match event {
+            Event::Msg(()) => state.niri.release_caches(),
+            Event::Closed => (),
+        })
+        .unwrap();
+
+    thread::Builder::new()
+        .name(String::from("Memory Pressure Monitor"))
+        .spawn(move || loop {
+            let mut fds = [libc::pollfd {
+                fd: file.as_raw_fd(),
+                events: libc::POLLPRI,
+                revents: 0,
+            }];
+
+            // SAFETY: fds is a valid array of one pollfd that outlives the call, and file stays
+            // open for the whole lifetime of the thread.
+            let res = unsafe { libc::poll(fds.as_mut_ptr(), 1, -1) };
+            if res < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+
+                warn!("error polling memory pressure: {err:?}");
+                break;
+            }
+
+            let revents = fds[0].revents;
+            if revents & libc::POLLERR != 0 {
+                warn!("memory pressure trigger is no longer valid");
+                break;
+            }
+
+            if revents & libc::POLLPRI != 0 {
+                // If the channel is full, a release is already pending.
+                if let Err(TrySendError::Disconnected(())) = tx.try_send(()) {
+                    break;
+                }
+            }
+        })
+        .unwrap();
+}
+
+fn open_trigger() -> io::Result<File> {
+    let mut last_err = io::Error::new(io::ErrorKind::NotFound, "no memory pressure file");
+
+    for path in cgroup_pressure_path()
+        .into_iter()
+        .chain([PathBuf::from("/proc/pressure/memory")])
+    {
+        let res = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .and_then(|mut file| {
+                file.write_all(TRIGGER.as_bytes())?;
+                Ok(file)
+            });
+
+        match res {
+            Ok(file) => {
+                debug!("monitoring memory pressure from {path:?}");
+                return Ok(file);
+            }
+            Err(err) => {
+                debug!("error registering memory pressure trigger in {path:?}: {err:?}");
+                last_err = err;
+            }
+        }
+    }
+
+    Err(last_err)
+}
+
+/// Returns the `memory.pressure` file of our cgroup on the unified hierarchy.
+fn cgroup_pressure_path() -> Option<PathBuf> {
+    let cgroups = fs::read_to_string("/proc/self/cgroup").ok()?;
+    let path = cgroups.lines().find_map(|line| line.strip_prefix("0::"))?;
+    let path = path.trim_start_matches('/');
+    Some(
+        PathBuf::from("/sys/fs/cgroup")
+            .join(path)
+            .join("memory.pressure"),
+    )
+}
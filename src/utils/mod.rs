@@ -5,7 +5,7 @@ use std::io::Write;
 use std::os::unix::prelude::OsStrExt;
 use std::path::{Path, PathBuf};
 use std::ptr::null_mut;
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 use anyhow::{ensure, Context};
@@ -34,6 +34,8 @@ use crate::handlers::KdeDecorationsModeState;
 use crate::niri::ClientState;
 
 pub mod id;
+pub mod memory_pressure;
+pub mod protocol_stats;
 pub mod scale;
 pub mod signals;
 pub mod spawning;
@@ -251,6 +253,28 @@ pub fn make_screenshot_path(config: &Config) -> anyhow::Result<Option<PathBuf>>
     Ok(Some(path))
 }
 
+/// Returns the local time of day in minutes since midnight, along with the time until the next
+/// minute starts.
+pub fn local_time_of_day() -> Option<(u16, Duration)> {
+    // SAFETY: time() accepts a null pointer, in which case it only returns the time.
+    let time = unsafe { libc::time(null_mut()) };
+    if time == -1 {
+        return None;
+    }
+
+    // SAFETY: tm is a plain C struct for which all zeroes is a valid value.
+    let mut tm = unsafe { std::mem::zeroed::<libc::tm>() };
+    // SAFETY: both pointers are valid for the duration of the call, and localtime_r(), unlike
+    // localtime(), writes into our tm rather than into shared static storage.
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return None;
+    }
+
+    let minute = u16::try_from(tm.tm_hour * 60 + tm.tm_min).ok()?;
+    let until_next_minute = Duration::from_secs(60 - tm.tm_sec.clamp(0, 59) as u64);
+    Some((minute, until_next_minute))
+}
+
 pub fn write_png_rgba8(
     w: impl Write,
     width: u32,
@@ -290,6 +314,29 @@ pub fn with_toplevel_role<T>(
     })
 }
 
+#[derive(Default)]
+struct ModalDialog(AtomicBool);
+
+/// Returns whether the client marked this toplevel as modal through xdg-dialog.
+pub fn is_modal_dialog(toplevel: &ToplevelSurface) -> bool {
+    with_states(toplevel.wl_surface(), |states| {
+        states
+            .data_map
+            .get::<ModalDialog>()
+            .is_some_and(|modal| modal.0.load(Ordering::Relaxed))
+    })
+}
+
+pub fn set_modal_dialog(toplevel: &ToplevelSurface, is_modal: bool) {
+    with_states(toplevel.wl_surface(), |states| {
+        states
+            .data_map
+            .insert_if_missing_threadsafe(ModalDialog::default);
+        let modal = states.data_map.get::<ModalDialog>().unwrap();
+        modal.0.store(is_modal, Ordering::Relaxed);
+    })
+}
+
 pub fn with_toplevel_role_and_current<T>(
     toplevel: &ToplevelSurface,
     f: impl FnOnce(&mut XdgToplevelSurfaceRoleAttributes, Option<&ToplevelState>) -> T,
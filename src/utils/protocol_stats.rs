@@ -0,0 +1,124 @@
+//! Per-client Wayland protocol usage statistics.
+//!
+//! These help to find the client responsible when something tanks compositor performance.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use smithay::reexports::wayland_server::backend::ObjectId;
+use smithay::utils::{Logical, Size};
+
+/// Commits per second above which a client is considered misbehaving.
+const EXCESSIVE_COMMIT_RATE: u32 = 1000;
+
+/// Surfaces larger than this in either dimension are considered oversized.
+const OVERSIZED_SURFACE_SIZE: i32 = 8192;
+
+#[derive(Debug)]
+pub struct ClientStats {
+    /// Total number of commits.
+    commits: u64,
+    /// Start of the current one second window.
+    window_start: Instant,
+    /// Number of commits in the current window.
+    window_commits: u32,
+    /// Number of commits in the previous window.
+    last_window_commits: u32,
+    /// Whether we already warned about the commit rate of this client.
+    warned: bool,
+    /// Sizes of the surfaces that were oversized at their last commit.
+    oversized_surfaces: HashMap<ObjectId, Size<i32, Logical>>,
+}
+
+impl ClientStats {
+    pub fn new() -> Self {
+        Self {
+            commits: 0,
+            window_start: Instant::now(),
+            window_commits: 0,
+            last_window_commits: 0,
+            warned: false,
+            oversized_surfaces: HashMap::new(),
+        }
+    }
+
+    /// Records a surface commit.
+    ///
+    /// Returns the commit rate the first time it becomes excessive, so that the caller can warn.
+    pub fn commit(&mut self, surface: ObjectId, size: Option<Size<i32, Logical>>) -> Option<u32> {
+        self.commits += 1;
+
+        match size {
+            Some(size) if size.w > OVERSIZED_SURFACE_SIZE || size.h > OVERSIZED_SURFACE_SIZE => {
+                self.oversized_surfaces.insert(surface, size);
+            }
+            _ => {
+                self.oversized_surfaces.remove(&surface);
+            }
+        }
+
+        let now = Instant::now();
+        self.advance_window(now);
+        self.window_commits += 1;
+
+        let rate = self.window_commits.max(self.last_window_commits);
+        if rate > EXCESSIVE_COMMIT_RATE && !self.warned {
+            self.warned = true;
+            return Some(rate);
+        }
+
+        None
+    }
+
+    fn advance_window(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.window_start);
+        if elapsed < Duration::from_secs(1) {
+            return;
+        }
+
+        // If more than a whole window passed since the last commit, then the previous window had
+        // no commits.
+        self.last_window_commits = if elapsed < Duration::from_secs(2) {
+            self.window_commits
+        } else {
+            0
+        };
+        self.window_commits = 0;
+        self.window_start = now;
+    }
+
+    /// Returns the number of commits during the last full second.
+    pub fn commit_rate(&mut self) -> u32 {
+        self.advance_window(Instant::now());
+        self.last_window_commits
+    }
+
+    pub fn to_ipc(
+        &mut self,
+        pid: Option<i32>,
+        objects: HashMap<String, u32>,
+        is_alive: impl Fn(&ObjectId) -> bool,
+    ) -> niri_ipc::ClientProtocolStats {
+        self.oversized_surfaces.retain(|id, _| is_alive(id));
+
+        let commit_rate = self.commit_rate();
+        niri_ipc::ClientProtocolStats {
+            pid,
+            name: pid.and_then(process_name),
+            objects,
+            commits: self.commits,
+            commit_rate,
+            excessive_commit_rate: commit_rate > EXCESSIVE_COMMIT_RATE,
+            oversized_surfaces: self
+                .oversized_surfaces
+                .values()
+                .map(|size| (size.w, size.h))
+                .collect(),
+        }
+    }
+}
+
+fn process_name(pid: i32) -> Option<String> {
+    let name = std::fs::read_to_string(format!("/proc/{pid}/comm")).ok()?;
+    Some(name.trim_end().to_owned())
+}
@@ -15,6 +15,7 @@ const POLLING_INTERVAL: Duration = Duration::from_millis(500);
 
 pub struct Watcher {
     load_config: mpsc::Sender<()>,
+    path: ConfigPath,
 }
 
 struct WatcherInner {
@@ -60,10 +61,11 @@ impl Watcher {
     ) -> Self {
         let (load_config, load_config_rx) = mpsc::channel();
 
+        let inner_path = path.clone();
         thread::Builder::new()
             .name(format!("Filesystem Watcher for {path:?}"))
             .spawn(move || {
-                let mut inner = WatcherInner::new(path, includes);
+                let mut inner = WatcherInner::new(inner_path, includes);
 
                 loop {
                     let mut should_load = match load_config_rx.recv_timeout(POLLING_INTERVAL) {
@@ -102,12 +104,17 @@ impl Watcher {
             })
             .unwrap();
 
-        Self { load_config }
+        Self { load_config, path }
     }
 
     pub fn load_config(&self) {
         let _ = self.load_config.send(());
     }
+
+    /// Returns the watched config path.
+    pub fn path(&self) -> &ConfigPath {
+        &self.path
+    }
 }
 
 impl Props {
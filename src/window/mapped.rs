@@ -1,7 +1,10 @@
 use std::cell::{Cell, Ref, RefCell};
 use std::time::Duration;
 
-use niri_config::{Color, CornerRadius, GradientInterpolation, WindowRule};
+use niri_config::utils::MergeWith as _;
+use niri_config::{
+    BlurRule, Color, CornerRadius, GradientInterpolation, TitleFallback, WindowRule,
+};
 use smithay::backend::renderer::element::surface::WaylandSurfaceRenderElement;
 use smithay::backend::renderer::element::Kind;
 use smithay::backend::renderer::gles::GlesRenderer;
@@ -40,7 +43,7 @@ use crate::render_helpers::{BakedBuffer, RenderTarget};
 use crate::utils::id::IdCounter;
 use crate::utils::transaction::Transaction;
 use crate::utils::{
-    get_credentials_for_surface, send_scale_transform, update_tiled_state,
+    get_credentials_for_surface, get_monotonic_time, send_scale_transform, update_tiled_state,
     with_toplevel_last_uncommitted_configure, with_toplevel_role, with_toplevel_role_and_current,
     ResizeEdge,
 };
@@ -78,6 +81,18 @@ pub struct Mapped {
     /// resizes immediately, without waiting for a 1 second throttled callback.
     needs_frame_callback: bool,
 
+    /// Time when the last configure was sent to this window.
+    last_configure_time: Option<Duration>,
+
+    /// Minimum interval between size-only configures during an interactive resize.
+    ///
+    /// Set to the refresh interval of the output the window is on, so that fast resizes are
+    /// batched to at most one configure per frame.
+    configure_batch_interval: Cell<Option<Duration>>,
+
+    /// Time when the currently batched size configure can be sent.
+    batched_configure_deadline: Cell<Option<Duration>>,
+
     /// Data of the offscreen element rendered in place of this window.
     ///
     /// If `None`, then the window is not offscreened.
@@ -86,6 +101,14 @@ pub struct Mapped {
     /// Whether this has an urgent indicator.
     is_urgent: bool,
 
+    /// Marks of this window, in the order they were added.
+    ///
+    /// A mark is on at most one window at a time.
+    marks: Vec<String>,
+
+    /// Number that tells this window apart from other untitled windows of the same app.
+    untitled_number: u64,
+
     /// Whether this window has the keyboard focus.
     is_focused: bool,
 
@@ -104,6 +127,12 @@ pub struct Mapped {
     /// Buffer to draw instead of the window when it should be blocked out.
     block_out_buffer: RefCell<SolidColorBuffer>,
 
+    /// Whether this window is dimmed because it has a modal dialog open.
+    is_dimmed_by_modal_dialog: bool,
+
+    /// Buffer to draw on top of the window when it is dimmed.
+    modal_dim_buffer: RefCell<SolidColorBuffer>,
+
     /// Whether the next configure should be animated, if the configured state changed.
     animate_next_configure: bool,
 
@@ -189,8 +218,25 @@ pub struct Mapped {
     /// Most recent monotonic time when the window had the focus.
     focus_timestamp: Option<Duration>,
 
+    /// Most recent monotonic time when the window was opened, gained or lost the focus.
+    ///
+    /// Used for parking inactive windows.
+    last_interaction: Duration,
+
     /// Whether this window wants blur as specified by any of the wayland protocols.
     proto_wants_blur: bool,
+
+    /// Blur settings changed at runtime through IPC, applied on top of the window rules.
+    blur_override: BlurRule,
+
+    /// Opacity set at runtime through IPC, overriding the window rules.
+    opacity_override: Option<f32>,
+
+    /// Whether to draw the border, set at runtime through IPC, overriding the window rules.
+    border_override: Option<bool>,
+
+    /// Whether to draw the shadow, set at runtime through IPC, overriding the window rules.
+    shadow_override: Option<bool>,
 }
 
 niri_render_elements! {
@@ -265,14 +311,21 @@ impl Mapped {
             need_to_recompute_rules: false,
             needs_configure: false,
             needs_frame_callback: false,
+            last_configure_time: None,
+            configure_batch_interval: Cell::new(None),
+            batched_configure_deadline: Cell::new(None),
             offscreen_data: RefCell::new(None),
             is_urgent: false,
+            marks: Vec::new(),
+            untitled_number: 1,
             is_focused: false,
             is_active_in_column: true,
             is_floating: false,
             is_window_cast_target: false,
             ignore_opacity_window_rule: false,
             block_out_buffer: RefCell::new(SolidColorBuffer::new((0., 0.), [0., 0., 0., 1.])),
+            is_dimmed_by_modal_dialog: false,
+            modal_dim_buffer: RefCell::new(SolidColorBuffer::new((0., 0.), [0., 0., 0., 0.5])),
             animate_next_configure: false,
             animate_serials: Vec::new(),
             animation_snapshot: None,
@@ -288,7 +341,12 @@ impl Mapped {
             is_pending_maximized: false,
             uncommitted_maximized: Vec::new(),
             focus_timestamp: None,
+            last_interaction: get_monotonic_time(),
             proto_wants_blur: false,
+            blur_override: BlurRule::default(),
+            opacity_override: None,
+            border_override: None,
+            shadow_override: None,
         };
 
         rv.is_maximized = rv.sizing_mode().is_maximized();
@@ -305,7 +363,20 @@ impl Mapped {
     pub fn recompute_window_rules(&mut self, rules: &[WindowRule], is_at_startup: bool) -> bool {
         self.need_to_recompute_rules = false;
 
-        let new_rules = ResolvedWindowRules::compute(rules, WindowRef::Mapped(self), is_at_startup);
+        let mut new_rules =
+            ResolvedWindowRules::compute(rules, WindowRef::Mapped(self), is_at_startup);
+        new_rules.blur.merge_with(&self.blur_override);
+        if let Some(opacity) = self.opacity_override {
+            new_rules.opacity = Some(opacity);
+        }
+        if let Some(on) = self.border_override {
+            new_rules.border.on = on;
+            new_rules.border.off = !on;
+        }
+        if let Some(on) = self.shadow_override {
+            new_rules.shadow.on = on;
+            new_rules.shadow.off = !on;
+        }
         if new_rules == self.rules {
             return false;
         }
@@ -320,6 +391,61 @@ impl Mapped {
         true
     }
 
+    pub fn blur_override(&self) -> BlurRule {
+        self.blur_override
+    }
+
+    /// Sets the runtime blur override and returns whether the rules changed.
+    pub fn set_blur_override(
+        &mut self,
+        blur: BlurRule,
+        rules: &[WindowRule],
+        is_at_startup: bool,
+    ) -> bool {
+        self.blur_override = blur;
+        self.recompute_window_rules(rules, is_at_startup)
+    }
+
+    /// Sets the runtime opacity override and returns whether the rules changed.
+    pub fn set_opacity_override(
+        &mut self,
+        opacity: Option<f32>,
+        rules: &[WindowRule],
+        is_at_startup: bool,
+    ) -> bool {
+        self.opacity_override = opacity;
+        self.recompute_window_rules(rules, is_at_startup)
+    }
+
+    /// Applies runtime decoration changes and returns whether the rules changed.
+    pub fn set_decorations_override(
+        &mut self,
+        change: niri_ipc::WindowDecorationsChange,
+        rules: &[WindowRule],
+        is_at_startup: bool,
+    ) -> bool {
+        if let Some(on) = change.border {
+            self.border_override = Some(on);
+        }
+        if let Some(on) = change.shadow {
+            self.shadow_override = Some(on);
+        }
+        if let Some(on) = change.blur {
+            self.blur_override.on = on;
+            self.blur_override.off = !on;
+        }
+        self.recompute_window_rules(rules, is_at_startup)
+    }
+
+    /// Returns the opacity that the window is drawn with, outside of fullscreen.
+    pub fn opacity(&self) -> f32 {
+        if self.ignore_opacity_window_rule {
+            1.
+        } else {
+            self.rules.opacity.unwrap_or(1.).clamp(0., 1.)
+        }
+    }
+
     pub fn recompute_window_rules_if_needed(
         &mut self,
         rules: &[WindowRule],
@@ -376,6 +502,7 @@ impl Mapped {
         self.is_focused = is_focused;
         self.is_urgent = false;
         self.need_to_recompute_rules = true;
+        self.last_interaction = get_monotonic_time();
     }
 
     pub fn set_is_window_cast_target(&mut self, value: bool) {
@@ -387,6 +514,16 @@ impl Mapped {
         self.need_to_recompute_rules = true;
     }
 
+    /// Sets whether this window is dimmed by a modal dialog, returning whether it changed.
+    pub fn set_is_dimmed_by_modal_dialog(&mut self, value: bool) -> bool {
+        if self.is_dimmed_by_modal_dialog == value {
+            return false;
+        }
+
+        self.is_dimmed_by_modal_dialog = value;
+        true
+    }
+
     /// Renders a snapshot of the window without popups.
     fn render_snapshot(&self, renderer: &mut GlesRenderer) -> LayoutElementRenderSnapshot {
         let _span = tracy_client::span!("Mapped::render_snapshot");
@@ -533,6 +670,11 @@ impl Mapped {
         );
     }
 
+    /// Returns the time when a batched size configure for this window is due, if any.
+    pub fn batched_configure_deadline(&self) -> Option<Duration> {
+        self.batched_configure_deadline.get()
+    }
+
     pub fn get_focus_timestamp(&self) -> Option<Duration> {
         self.focus_timestamp
     }
@@ -541,6 +683,14 @@ impl Mapped {
         self.focus_timestamp.replace(timestamp);
     }
 
+    pub fn last_interaction(&self) -> Duration {
+        self.last_interaction
+    }
+
+    pub fn touch(&mut self) {
+        self.last_interaction = get_monotonic_time();
+    }
+
     pub fn send_frame<T, F>(
         &mut self,
         output: &Output,
@@ -587,6 +737,46 @@ impl Mapped {
     pub fn is_urgent(&self) -> bool {
         self.is_urgent
     }
+
+    pub fn app_id(&self) -> Option<String> {
+        with_toplevel_role(self.toplevel(), |role| role.app_id.clone())
+    }
+
+    pub fn untitled_number(&self) -> u64 {
+        self.untitled_number
+    }
+
+    pub fn set_untitled_number(&mut self, number: u64) {
+        self.untitled_number = number;
+    }
+
+    pub fn marks(&self) -> &[String] {
+        &self.marks
+    }
+
+    pub fn has_mark(&self, mark: &str) -> bool {
+        self.marks.iter().any(|m| m == mark)
+    }
+
+    pub fn add_mark(&mut self, mark: String) {
+        if !self.has_mark(&mark) {
+            self.marks.push(mark);
+        }
+    }
+
+    /// Removes a mark and returns whether the window had it.
+    pub fn remove_mark(&mut self, mark: &str) -> bool {
+        let len = self.marks.len();
+        self.marks.retain(|m| m != mark);
+        self.marks.len() != len
+    }
+
+    /// Removes all marks and returns whether the window had any.
+    pub fn clear_marks(&mut self) -> bool {
+        let had_marks = !self.marks.is_empty();
+        self.marks.clear();
+        had_marks
+    }
 }
 
 impl Drop for Mapped {
@@ -631,6 +821,18 @@ impl LayoutElement for Mapped {
                 SolidColorRenderElement::from_buffer(&buffer, location, alpha, Kind::Unspecified);
             push(elem.into());
         } else {
+            if self.is_dimmed_by_modal_dialog {
+                let mut buffer = self.modal_dim_buffer.borrow_mut();
+                buffer.resize(self.window.geometry().size.to_f64());
+                let elem = SolidColorRenderElement::from_buffer(
+                    &buffer,
+                    location,
+                    alpha,
+                    Kind::Unspecified,
+                );
+                push(elem.into());
+            }
+
             let buf_pos = location - self.window.geometry().loc.to_f64();
             let surface = self.toplevel().wl_surface();
             let mut push = |elem: WaylandSurfaceRenderElement<R>| push(elem.into());
@@ -701,6 +903,12 @@ impl LayoutElement for Mapped {
             self.needs_configure = true;
         }
 
+        let size = if mode.is_fullscreen() || self.is_pending_windowed_fullscreen {
+            size
+        } else {
+            self.rules.apply_aspect_ratio(size)
+        };
+
         let changed = self.toplevel().with_pending_state(|state| {
             let changed = state.size != Some(size);
             state.size = Some(size);
@@ -736,6 +944,8 @@ impl LayoutElement for Mapped {
     }
 
     fn request_size_once(&mut self, size: Size<i32, Logical>, animate: bool) {
+        let size = self.rules.apply_aspect_ratio(size);
+
         // Assume that when calling this function, the window is going floating, so it can no
         // longer participate in any transactions with other windows.
         self.transaction_for_next_configure = None;
@@ -856,6 +1066,12 @@ impl LayoutElement for Mapped {
     }
 
     fn output_enter(&self, output: &Output) {
+        let interval = output
+            .current_mode()
+            .filter(|mode| mode.refresh > 0)
+            .map(|mode| Duration::from_secs_f64(1000. / f64::from(mode.refresh)));
+        self.configure_batch_interval.set(interval);
+
         let overlap = Rectangle::from_size(Size::from((i32::MAX, i32::MAX)));
         self.window.output_enter(output, overlap)
     }
@@ -925,6 +1141,8 @@ impl LayoutElement for Mapped {
         let _span =
             trace_span!("configure_intent", surface = ?self.toplevel().wl_surface().id()).entered();
 
+        self.batched_configure_deadline.set(None);
+
         if self.needs_configure {
             trace!("the window needs_configure");
             return ConfigureIntent::ShouldSend;
@@ -948,7 +1166,22 @@ impl LayoutElement for Mapped {
 
                         if current_committed.size == current_server.size {
                             // The window had committed for our previous size change, so we can
-                            // change the size again.
+                            // change the size again. However, do not send size changes more
+                            // often than once per output refresh: during continuous resizes
+                            // (interactive, animated or gesture-driven) the client would not be
+                            // able to present the intermediate sizes anyway. The final size is
+                            // sent once the interval passes.
+                            let deadline = self
+                                .last_configure_time
+                                .zip(self.configure_batch_interval.get())
+                                .map(|(last, interval)| last + interval)
+                                .filter(|deadline| get_monotonic_time() < *deadline);
+                            if let Some(deadline) = deadline {
+                                trace!("batching resize until {deadline:?}");
+                                self.batched_configure_deadline.set(Some(deadline));
+                                return ConfigureIntent::Throttled;
+                            }
+
                             trace!(
                                 "current size matches server size: {:?}",
                                 current_committed.size
@@ -1019,6 +1252,8 @@ impl LayoutElement for Mapped {
             trace!(?serial, "sending configure");
 
             self.needs_configure = false;
+            self.last_configure_time = Some(get_monotonic_time());
+            self.batched_configure_deadline.set(None);
 
             // Send the window a frame callback unconditionally to let it respond to size changes
             // and such immediately, even when it's hidden. This especially matters for cases like
@@ -1342,6 +1577,16 @@ impl LayoutElement for Mapped {
     fn title(&self) -> Option<String> {
         with_toplevel_role(self.toplevel(), |role| role.title.clone())
     }
+
+    fn display_title(&self, fallback: TitleFallback) -> String {
+        with_toplevel_role(self.toplevel(), |role| {
+            fallback.apply(
+                role.title.as_deref(),
+                role.app_id.as_deref(),
+                self.untitled_number,
+            )
+        })
+    }
     ///
     /// Set the preferred blurred state of this window.
     fn set_proto_wants_blur(&mut self, new_blurred: bool) {
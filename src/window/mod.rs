@@ -1,10 +1,11 @@
+use std::cell::OnceCell;
 use std::cmp::{max, min};
 
 use niri_config::utils::MergeWith as _;
-use niri_config::window_rule::{Match, WindowRule};
+use niri_config::window_rule::{AspectRatio, Match, WindowRule};
 use niri_config::{
-    BlockOutFrom, BlurRule, BorderRule, CornerRadius, FloatingPosition, PresetSize, ShadowRule,
-    TabIndicatorRule,
+    BlockOutFrom, BlurRule, BorderRule, CornerRadius, FloatingPosition, ForcePointerConstraint,
+    KeyboardShortcutsInhibit, PresetSize, ShadowRule, TabIndicatorRule,
 };
 use smithay::reexports::wayland_protocols::xdg::shell::server::xdg_toplevel;
 use smithay::utils::{Logical, Size};
@@ -13,7 +14,8 @@ use smithay::wayland::shell::xdg::{
     SurfaceCachedState, ToplevelSurface, XdgToplevelSurfaceRoleAttributes,
 };
 
-use crate::utils::with_toplevel_role;
+use crate::protocols::xdg_toplevel_tag::toplevel_tag;
+use crate::utils::{local_time_of_day, with_toplevel_role};
 
 pub mod mapped;
 pub use mapped::Mapped;
@@ -48,11 +50,11 @@ pub struct ResolvedWindowRules {
     /// Default floating position for this window.
     pub default_floating_position: Option<FloatingPosition>,
 
-    /// Output to open this window on.
-    pub open_on_output: Option<String>,
+    /// Outputs to open this window on, in order of preference.
+    pub open_on_output: Vec<String>,
 
-    /// Workspace to open this window on.
-    pub open_on_workspace: Option<String>,
+    /// Workspaces to open this window on, in order of preference.
+    pub open_on_workspace: Vec<String>,
 
     /// Whether the window should open full-width.
     pub open_maximized: Option<bool>,
@@ -69,6 +71,9 @@ pub struct ResolvedWindowRules {
     /// Whether the window should open focused.
     pub open_focused: Option<bool>,
 
+    /// Whether to move the window to its open-on-workspace or open-on-output on config reload.
+    pub reapply_rules_on_reload: Option<bool>,
+
     /// Extra bound on the minimum window width.
     pub min_width: Option<u16>,
     /// Extra bound on the minimum window height.
@@ -77,6 +82,8 @@ pub struct ResolvedWindowRules {
     pub max_width: Option<u16>,
     /// Extra bound on the maximum window height.
     pub max_height: Option<u16>,
+    /// Aspect ratio to constrain the window size to.
+    pub force_aspect_ratio: Option<AspectRatio>,
 
     /// Focus ring overrides.
     pub focus_ring: BorderRule,
@@ -117,6 +124,28 @@ pub struct ResolvedWindowRules {
 
     /// Override whether to set the Tiled xdg-toplevel state on the window.
     pub tiled_state: Option<bool>,
+
+    /// Whether to keep sending frame callbacks to this window while it is hidden.
+    pub keep_rendering: bool,
+
+    /// Override whether this window inhibits idle while it is visible.
+    ///
+    /// `Some(true)` inhibits idle regardless of the idle-inhibit protocol, `Some(false)` ignores
+    /// the window's idle inhibitors.
+    pub inhibit_idle: Option<bool>,
+
+    /// Whether this window may present with tearing page flips when it is fullscreen.
+    pub allow_tearing: bool,
+
+    /// Whether to dim the parent window while this window is an open modal dialog.
+    pub dim_parent: bool,
+
+    /// Whether this window may inhibit the compositor keyboard shortcuts.
+    pub keyboard_shortcuts_inhibit: KeyboardShortcutsInhibit,
+
+    /// Pointer constraint to apply to this window while it is focused, regardless of the
+    /// pointer-constraints protocol.
+    pub force_pointer_constraint: Option<ForcePointerConstraint>,
 }
 
 impl<'a> WindowRef<'a> {
@@ -177,14 +206,19 @@ impl ResolvedWindowRules {
 
         let mut resolved = ResolvedWindowRules::default();
 
+        // Read the tag before locking the role, since both live in the surface data.
+        let tag = toplevel_tag(window.toplevel().wl_surface());
+
         with_toplevel_role(window.toplevel(), |role| {
             // Ensure server_pending like in Smithay's with_pending_state().
             if role.server_pending.is_none() {
                 role.server_pending = Some(role.current_server_state().clone());
             }
 
-            let mut open_on_output = None;
-            let mut open_on_workspace = None;
+            let mut open_on_output: &[String] = &[];
+            let mut open_on_workspace: &[String] = &[];
+
+            let now = OnceCell::new();
 
             for rule in rules {
                 let matches = |m: &Match| {
@@ -194,7 +228,14 @@ impl ResolvedWindowRules {
                         }
                     }
 
-                    window_matches(window, role, m)
+                    if let Some(at) = m.at {
+                        let now = now.get_or_init(|| local_time_of_day().map(|(minute, _)| minute));
+                        if !now.is_some_and(|minute| at.contains(minute)) {
+                            return false;
+                        }
+                    }
+
+                    window_matches(window, role, tag.as_deref(), m)
                 };
 
                 if !(rule.matches.is_empty() || rule.matches.iter().any(matches)) {
@@ -218,11 +259,11 @@ impl ResolvedWindowRules {
                 }
 
                 if let Some(x) = rule.open_on_output.as_deref() {
-                    open_on_output = Some(x);
+                    open_on_output = x;
                 }
 
                 if let Some(x) = rule.open_on_workspace.as_deref() {
-                    open_on_workspace = Some(x);
+                    open_on_workspace = x;
                 }
 
                 if let Some(x) = rule.open_maximized {
@@ -245,6 +286,10 @@ impl ResolvedWindowRules {
                     resolved.open_focused = Some(x);
                 }
 
+                if let Some(x) = rule.reapply_rules_on_reload {
+                    resolved.reapply_rules_on_reload = Some(x);
+                }
+
                 if let Some(x) = rule.min_width {
                     resolved.min_width = Some(x);
                 }
@@ -257,6 +302,9 @@ impl ResolvedWindowRules {
                 if let Some(x) = rule.max_height {
                     resolved.max_height = Some(x);
                 }
+                if let Some(x) = rule.force_aspect_ratio {
+                    resolved.force_aspect_ratio = Some(x);
+                }
 
                 resolved.focus_ring.merge_with(&rule.focus_ring);
                 resolved.border.merge_with(&rule.border);
@@ -291,10 +339,28 @@ impl ResolvedWindowRules {
                 if let Some(x) = rule.tiled_state {
                     resolved.tiled_state = Some(x);
                 }
+                if let Some(x) = rule.keep_rendering {
+                    resolved.keep_rendering = x;
+                }
+                if let Some(x) = rule.inhibit_idle {
+                    resolved.inhibit_idle = Some(x);
+                }
+                if let Some(x) = rule.allow_tearing {
+                    resolved.allow_tearing = x;
+                }
+                if let Some(x) = rule.dim_parent {
+                    resolved.dim_parent = x;
+                }
+                if let Some(x) = rule.keyboard_shortcuts_inhibit {
+                    resolved.keyboard_shortcuts_inhibit = x;
+                }
+                if let Some(x) = rule.force_pointer_constraint {
+                    resolved.force_pointer_constraint = Some(x);
+                }
             }
 
-            resolved.open_on_output = open_on_output.map(|x| x.to_owned());
-            resolved.open_on_workspace = open_on_workspace.map(|x| x.to_owned());
+            resolved.open_on_output = open_on_output.to_vec();
+            resolved.open_on_workspace = open_on_workspace.to_vec();
         });
 
         resolved
@@ -344,6 +410,29 @@ impl ResolvedWindowRules {
         (min_size, max_size)
     }
 
+    /// Shrinks the size to fit the forced aspect ratio, if any.
+    ///
+    /// A zero dimension, which lets the window pick its size, is computed from the other one.
+    pub fn apply_aspect_ratio(&self, size: Size<i32, Logical>) -> Size<i32, Logical> {
+        let Some(ratio) = self.force_aspect_ratio else {
+            return size;
+        };
+        let ratio = ratio.ratio();
+
+        let mut size = size;
+        let w = f64::from(size.w);
+        let h = f64::from(size.h);
+        match (size.w, size.h) {
+            (0, 0) => (),
+            (0, _) => size.w = (h * ratio).round() as i32,
+            (_, 0) => size.h = (w / ratio).round() as i32,
+            _ if w / h > ratio => size.w = (h * ratio).round().max(1.) as i32,
+            _ => size.h = (w / ratio).round().max(1.) as i32,
+        }
+
+        size
+    }
+
     pub fn compute_open_floating(&self, toplevel: &ToplevelSurface) -> bool {
         if let Some(res) = self.open_floating {
             return res;
@@ -366,7 +455,12 @@ impl ResolvedWindowRules {
     }
 }
 
-fn window_matches(window: WindowRef, role: &XdgToplevelSurfaceRoleAttributes, m: &Match) -> bool {
+fn window_matches(
+    window: WindowRef,
+    role: &XdgToplevelSurfaceRoleAttributes,
+    tag: Option<&str>,
+    m: &Match,
+) -> bool {
     // Must be ensured by the caller.
     let server_pending = role.server_pending.as_ref().unwrap();
 
@@ -410,6 +504,15 @@ fn window_matches(window: WindowRef, role: &XdgToplevelSurfaceRoleAttributes, m:
         }
     }
 
+    if let Some(tag_re) = &m.tag {
+        let Some(tag) = tag else {
+            return false;
+        };
+        if !tag_re.0.is_match(tag) {
+            return false;
+        }
+    }
+
     if let Some(is_active_in_column) = m.is_active_in_column {
         if window.is_active_in_column() != is_active_in_column {
             return false;
@@ -430,3 +533,69 @@ fn window_matches(window: WindowRef, role: &XdgToplevelSurfaceRoleAttributes, m:
 
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules_with_ratio(width: u16, height: u16) -> ResolvedWindowRules {
+        ResolvedWindowRules {
+            force_aspect_ratio: Some(AspectRatio { width, height }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn apply_aspect_ratio_without_rule() {
+        let rules = ResolvedWindowRules::default();
+        assert_eq!(
+            rules.apply_aspect_ratio(Size::from((800, 300))),
+            Size::from((800, 300))
+        );
+    }
+
+    #[test]
+    fn apply_aspect_ratio_shrinks_to_fit() {
+        let rules = rules_with_ratio(16, 9);
+
+        // Too wide: the width shrinks.
+        assert_eq!(
+            rules.apply_aspect_ratio(Size::from((1000, 360))),
+            Size::from((640, 360))
+        );
+        // Too tall: the height shrinks.
+        assert_eq!(
+            rules.apply_aspect_ratio(Size::from((640, 1000))),
+            Size::from((640, 360))
+        );
+        // Already matching.
+        assert_eq!(
+            rules.apply_aspect_ratio(Size::from((1920, 1080))),
+            Size::from((1920, 1080))
+        );
+        // Never shrinks to zero.
+        assert_eq!(
+            rules.apply_aspect_ratio(Size::from((1, 1000))),
+            Size::from((1, 1))
+        );
+    }
+
+    #[test]
+    fn apply_aspect_ratio_fills_in_unset_dimension() {
+        let rules = rules_with_ratio(4, 3);
+
+        assert_eq!(
+            rules.apply_aspect_ratio(Size::from((0, 600))),
+            Size::from((800, 600))
+        );
+        assert_eq!(
+            rules.apply_aspect_ratio(Size::from((800, 0))),
+            Size::from((800, 600))
+        );
+        // The window picks both dimensions itself.
+        assert_eq!(
+            rules.apply_aspect_ratio(Size::from((0, 0))),
+            Size::from((0, 0))
+        );
+    }
+}
@@ -5,6 +5,7 @@ use smithay::wayland::shell::xdg::ToplevelSurface;
 use smithay::wayland::xdg_activation::XdgActivationTokenData;
 
 use super::ResolvedWindowRules;
+use crate::session::RestorePlacement;
 
 #[derive(Debug)]
 pub struct Unmapped {
@@ -68,6 +69,9 @@ pub enum InitialConfigureState {
         /// Workspace to open this window on.
         workspace_name: Option<String>,
 
+        /// Where to put this window back from the saved session.
+        restore: Option<RestorePlacement>,
+
         /// Whether the window should be maximized.
         ///
         /// This corresponds to the window having the Maximized toplevel state. However, if the